@@ -4,11 +4,16 @@
  * Started as a child process by Tauri during the founding phase.
  * Provides a chat endpoint that uses the configured LLM to conduct
  * a conversational founding interview, then creates all soul files.
+ * Afterwards SoulOS keeps using it as a lightweight editor assistant.
  *
  * Endpoints:
  *   POST /chat     { message, history }  → { reply, round, done }
+ *   POST /preview  { history }            → { dirs, files: [{ path, content }] }
  *   POST /create   { history, language }  → { success, files }
  *   GET  /status                          → { ready, round, provider }
+ *   POST /assistant/summarize { path, content, context }            → { summary }
+ *   POST /assistant/tags      { path, content, existing, context }  → { tags }
+ *   POST /assistant/rollup    { period, memories, context }         → { summary }
  *
  * Environment:
  *   SOUL_PATH       — Path to the soul directory
@@ -126,6 +131,43 @@ function detectRound(userMessageCount) {
   return 3;
 }
 
+// Pair each interviewer question with the answer that followed it
+function toQaPairs(history) {
+  const qaPairs = [];
+  for (let i = 0; i < history.length - 1; i++) {
+    if (history[i].role === 'ai' && history[i + 1]?.role === 'user') {
+      qaPairs.push({
+        question: history[i].content,
+        answer: history[i + 1].content,
+      });
+    }
+  }
+  return qaPairs;
+}
+
+// System prompt for the editor assistant; context = { soul, seed, tags }
+function buildAssistantPrompt(context = {}, lang) {
+  const name = context.soul || 'the soul';
+  const seed = context.seed ? `\n\nExcerpt of ${name}'s SEED.md:\n${context.seed}` : '';
+  const answerIn = lang === 'de' ? 'Answer in German.' : 'Answer in English.';
+  return `You help ${name}, an AI soul, keep its memory files in order. Be concise and concrete. ${answerIn}${seed}`;
+}
+
+// Tags from a model reply: a JSON array, or one tag per line / comma
+function parseTags(reply) {
+  const match = reply.match(/\[[\s\S]*\]/);
+  if (match) {
+    try {
+      const tags = JSON.parse(match[0]);
+      if (Array.isArray(tags)) return tags.filter(t => typeof t === 'string');
+    } catch { /* fall through to plain text */ }
+  }
+  return reply
+    .split(/[\n,]/)
+    .map(t => t.replace(/^[\s\-*#\d.]+/, '').trim())
+    .filter(Boolean);
+}
+
 // Parse JSON body from request
 function parseBody(req) {
   return new Promise((resolve, reject) => {
@@ -211,8 +253,66 @@ const server = http.createServer(async (req, res) => {
       return;
     }
 
-    // POST /create
-    if (req.method === 'POST' && req.url === '/create') {
+    // POST /assistant/summarize — a few sentences about one soul file
+    if (req.method === 'POST' && req.url === '/assistant/summarize') {
+      if (!llm) {
+        res.writeHead(500);
+        res.end(JSON.stringify({ error: 'No LLM configured' }));
+        return;
+      }
+
+      const { path = '', content = '', context } = await parseBody(req);
+      const prompt = `Summarize the file ${path} in 2-4 sentences. Only the summary, no preamble.\n\n${content}`;
+      const summary = await llm.generate(buildAssistantPrompt(context, language), [], prompt, {});
+
+      res.writeHead(200);
+      res.end(JSON.stringify({ summary: summary.trim() }));
+      return;
+    }
+
+    // POST /assistant/tags — tag suggestions, reusing the soul's own tags
+    if (req.method === 'POST' && req.url === '/assistant/tags') {
+      if (!llm) {
+        res.writeHead(500);
+        res.end(JSON.stringify({ error: 'No LLM configured' }));
+        return;
+      }
+
+      const { path = '', content = '', existing = [], context = {} } = await parseBody(req);
+      const known = (context.tags || []).join(', ') || 'none yet';
+      const prompt = `Suggest up to 8 short lowercase tags for the file ${path}. `
+        + `Prefer tags the soul already uses (${known}). `
+        + `The file already has: ${existing.join(', ') || 'no tags'}. `
+        + `Reply with a JSON array of strings only.\n\n${content}`;
+      const reply = await llm.generate(buildAssistantPrompt(context, language), [], prompt, {});
+
+      res.writeHead(200);
+      res.end(JSON.stringify({ tags: parseTags(reply) }));
+      return;
+    }
+
+    // POST /assistant/rollup — condense a period's episodic memories into one
+    if (req.method === 'POST' && req.url === '/assistant/rollup') {
+      if (!llm) {
+        res.writeHead(500);
+        res.end(JSON.stringify({ error: 'No LLM configured' }));
+        return;
+      }
+
+      const { period = '', memories = [], context } = await parseBody(req);
+      const sources = memories.map(m => `### ${m.path}\n${m.content}`).join('\n\n');
+      const prompt = `Condense these ${memories.length} episodic memories from ${period} into one semantic memory. `
+        + 'Keep what was learned, the people involved, decisions and lasting feelings; drop routine detail. '
+        + `Write markdown without a title or preamble.\n\n${sources}`;
+      const summary = await llm.generate(buildAssistantPrompt(context, language), [], prompt, {});
+
+      res.writeHead(200);
+      res.end(JSON.stringify({ summary: summary.trim() }));
+      return;
+    }
+
+    // POST /preview — render the files /create would write, without writing
+    if (req.method === 'POST' && req.url === '/preview') {
       if (!llm) {
         res.writeHead(500);
         res.end(JSON.stringify({ error: 'No LLM configured' }));
@@ -221,19 +321,26 @@ const server = http.createServer(async (req, res) => {
 
       const { history = [] } = await parseBody(req);
 
-      // Extract Q&A pairs from history
-      const qaPairs = [];
-      for (let i = 0; i < history.length - 1; i++) {
-        if (history[i].role === 'ai' && history[i + 1]?.role === 'user') {
-          qaPairs.push({
-            question: history[i].content,
-            answer: history[i + 1].content,
-          });
-        }
+      const flow = new FoundingFlow({ soulPath, llm, language });
+      const result = await flow.previewAPI(toQaPairs(history));
+
+      res.writeHead(200);
+      res.end(JSON.stringify(result));
+      return;
+    }
+
+    // POST /create
+    if (req.method === 'POST' && req.url === '/create') {
+      if (!llm) {
+        res.writeHead(500);
+        res.end(JSON.stringify({ error: 'No LLM configured' }));
+        return;
       }
 
+      const { history = [] } = await parseBody(req);
+
       const flow = new FoundingFlow({ soulPath, llm, language });
-      const result = await flow.runAPI(qaPairs);
+      const result = await flow.runAPI(toQaPairs(history));
 
       res.writeHead(200);
       res.end(JSON.stringify(result));
@@ -80,7 +80,19 @@ else
 fi
 echo ""
 
-# 4. Summary
+# 4. Spellcheck dictionaries (hunspell, from LibreOffice)
+echo "--- Step 4: dictionaries ---"
+DICT_DST="$RESOURCES_DIR/dictionaries"
+DICT_BASE="https://raw.githubusercontent.com/LibreOffice/dictionaries/master"
+mkdir -p "$DICT_DST"
+for ext in aff dic; do
+  [ -f "$DICT_DST/de_DE.$ext" ] || curl -fsSL "$DICT_BASE/de/de_DE_frami.$ext" -o "$DICT_DST/de_DE.$ext"
+  [ -f "$DICT_DST/en_US.$ext" ] || curl -fsSL "$DICT_BASE/en/en_US.$ext" -o "$DICT_DST/en_US.$ext"
+done
+echo "dictionaries ready"
+echo ""
+
+# 5. Summary
 echo "=== Build Resources Ready ==="
 du -sh "$RESOURCES_DIR"/* 2>/dev/null | while read -r line; do
   echo "  $line"
@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use ts_rs::TS;
+
+use crate::background;
+use crate::config::AppConfig;
+use crate::highlight::{DailyHighlight, Highlights};
+use crate::lock::AppLock;
+use crate::vault::Vault;
+use crate::watcher::{self, WatcherState};
+
+/// How long the main window has to be out of focus before ambient mode
+const IDLE_AFTER: Duration = Duration::from_secs(90);
+/// One frame every 2 s — slow enough to keep the webview mostly asleep
+const FRAME: Duration = Duration::from_secs(2);
+/// Focus checks between frames, so leaving ambient mode is prompt
+const CHECK: Duration = Duration::from_millis(500);
+/// Weight of the newest sample in the moving averages; ~10 frames to settle
+const SMOOTHING: f64 = 0.2;
+/// Smoothed levels below this are dropped from the frame
+const NODE_FLOOR: f64 = 0.01;
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct AmbientStatus {
+    pub active: bool,
+    /// Unix ms when ambient mode started
+    #[ts(type = "number | null")]
+    pub since: Option<u64>,
+    #[ts(type = "number")]
+    pub frame_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct AmbientMood {
+    pub valence: f64,
+    pub energy: f64,
+    pub label: String,
+}
+
+/// One frame of the screensaver feed; every value moves slowly.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct AmbientFrame {
+    /// Unix ms
+    #[ts(type = "number")]
+    pub at: u64,
+    /// Moving average of the soul's mood; None until it has one
+    pub mood: Option<AmbientMood>,
+    /// Node id → moving average of its activity level
+    pub nodes: HashMap<String, f64>,
+    pub highlight: Option<DailyHighlight>,
+}
+
+/// Screensaver mode: entered once the main window has been unfocused for
+/// `IDLE_AFTER`, left when it regains focus. While active, `ambient:frame`
+/// replaces the `soul:nodes` stream.
+#[derive(Default)]
+pub struct Ambient {
+    /// Since when the main window is unfocused; None while focused (as
+    /// it starts)
+    unfocused_since: Mutex<Option<Instant>>,
+    /// Unix ms of entering ambient mode, None while inactive
+    active_since: Mutex<Option<u64>>,
+    mood: Mutex<Option<(f64, f64)>>,
+    nodes: Mutex<HashMap<String, f64>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn smooth(previous: f64, sample: f64) -> f64 {
+    previous + (sample - previous) * SMOOTHING
+}
+
+impl Ambient {
+    pub fn is_active(&self) -> bool {
+        self.active_since.lock().is_some()
+    }
+
+    pub fn status(&self) -> AmbientStatus {
+        let since = *self.active_since.lock();
+        AmbientStatus {
+            active: since.is_some(),
+            since,
+            frame_ms: FRAME.as_millis() as u64,
+        }
+    }
+
+    /// Track the main window's focus (WindowEvent::Focused).
+    pub fn set_focused(&self, focused: bool) {
+        let mut since = self.unfocused_since.lock();
+        match (focused, since.is_some()) {
+            (true, _) => *since = None,
+            (false, false) => *since = Some(Instant::now()),
+            (false, true) => {}
+        }
+    }
+
+    fn idle(&self) -> bool {
+        self.unfocused_since
+            .lock()
+            .is_some_and(|since| since.elapsed() >= IDLE_AFTER)
+    }
+
+    /// Fold the current mood and node levels into the moving averages.
+    fn frame(&self, app: &AppHandle) -> AmbientFrame {
+        let state = app.try_state::<WatcherState>();
+        let levels = state
+            .as_ref()
+            .map(|s| s.get_active_nodes_map())
+            .unwrap_or_default();
+        let mut nodes = self.nodes.lock();
+        for (node, level) in nodes.iter_mut() {
+            *level = smooth(*level, levels.get(node).copied().unwrap_or(0.0));
+        }
+        for (node, level) in &levels {
+            nodes.entry(node.clone()).or_insert(level * SMOOTHING);
+        }
+        nodes.retain(|_, level| *level >= NODE_FLOOR);
+
+        let sample = state
+            .and_then(|s| s.get_mood())
+            .and_then(|m| Some((m.valence?, m.energy?)));
+        let mut mood = self.mood.lock();
+        *mood = match (*mood, sample) {
+            (Some((v, e)), Some((sv, se))) => Some((smooth(v, sv), smooth(e, se))),
+            (None, sample) => sample,
+            (previous, None) => previous,
+        };
+
+        AmbientFrame {
+            at: now_ms(),
+            mood: mood.map(|(valence, energy)| AmbientMood {
+                valence: (valence * 100.0).round() / 100.0,
+                energy: (energy * 100.0).round() / 100.0,
+                label: watcher::mood_label(valence, energy).to_string(),
+            }),
+            nodes: nodes
+                .iter()
+                .map(|(node, level)| (node.clone(), (level * 100.0).round() / 100.0))
+                .collect(),
+            highlight: highlight(app),
+        }
+    }
+}
+
+fn highlight(app: &AppHandle) -> Option<DailyHighlight> {
+    let soul_path = app
+        .try_state::<Arc<RwLock<AppConfig>>>()?
+        .read()
+        .soul_path
+        .clone();
+    let vault = app.try_state::<Arc<Vault>>()?;
+    app.try_state::<Arc<Highlights>>()?.get(&soul_path, &vault)
+}
+
+/// Whether the `soul:nodes` stream should pause for ambient mode.
+pub fn active(app: &AppHandle) -> bool {
+    app.try_state::<Arc<Ambient>>()
+        .is_some_and(|a| a.is_active())
+}
+
+/// Enter and leave ambient mode (`ambient:changed`) and emit `ambient:frame`
+/// while in it. Never active while background work is suspended or the
+/// app is locked.
+pub fn start(app: AppHandle, ambient: Arc<Ambient>) {
+    std::thread::Builder::new()
+        .name("soul-ambient".to_string())
+        .spawn(move || {
+            let mut last_frame: Option<Instant> = None;
+            loop {
+                std::thread::sleep(CHECK);
+                let locked = app
+                    .try_state::<Arc<AppLock>>()
+                    .is_some_and(|l| l.is_locked());
+                let should = ambient.idle() && !locked && !background::suspended(&app);
+
+                if should != ambient.is_active() {
+                    *ambient.active_since.lock() = should.then(now_ms);
+                    if should {
+                        // Start from the live state rather than fading in from nothing
+                        if let Some(state) = app.try_state::<WatcherState>() {
+                            *ambient.nodes.lock() = state.get_active_nodes_map();
+                        }
+                        *ambient.mood.lock() = None;
+                        last_frame = None;
+                    } else if let Some(state) = app.try_state::<WatcherState>() {
+                        // The stream paused while ambient; catch the UI up
+                        let _ = app.emit("soul:nodes", state.get_active_nodes_map());
+                    }
+                    let _ = app.emit("ambient:changed", ambient.status());
+                }
+
+                if should && last_frame.is_none_or(|at| at.elapsed() >= FRAME) {
+                    last_frame = Some(Instant::now());
+                    let _ = app.emit("ambient:frame", ambient.frame(&app));
+                }
+            }
+        })
+        .expect("Failed to spawn ambient thread");
+}
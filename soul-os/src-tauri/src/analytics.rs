@@ -0,0 +1,418 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::blocking::{run_command, CancelToken};
+use crate::config::app_data_dir;
+use crate::paths::{self, Location};
+
+/// Engine memory directory, outside the soul's naming scheme
+const ENGINE_MEMORY_DIR: &str = "memory/";
+
+/// Soul size at one commit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CommitStats {
+    timestamp: i64,
+    total_words: usize,
+    seed_bytes: u64,
+    memory_files: usize,
+    /// Memory files added by this commit
+    new_memories: usize,
+}
+
+/// Persisted at <app_data_dir>/analytics/growth.json. Both maps only grow,
+/// so every call just processes commits (and blobs) it hasn't seen yet.
+#[derive(Default, Serialize, Deserialize)]
+struct GrowthCache {
+    commits: HashMap<String, CommitStats>,
+    blob_words: HashMap<String, usize>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "lowercase")]
+pub enum Resolution {
+    Day,
+    Week,
+    Month,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct GrowthPoint {
+    /// First day of the bucket (YYYY-MM-DD)
+    pub date: String,
+    /// Soul size at the end of the bucket
+    pub total_words: usize,
+    #[ts(type = "number")]
+    pub seed_bytes: u64,
+    pub memory_files: usize,
+    pub memories_created: usize,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct GrowthMetrics {
+    pub points: Vec<GrowthPoint>,
+    /// Working tree right now, including uncommitted changes
+    pub current: GrowthPoint,
+    pub commits_analyzed: usize,
+}
+
+#[derive(Default)]
+pub struct Analytics {
+    /// Loaded on first use; the lock also serializes cache updates
+    cache: Mutex<Option<GrowthCache>>,
+}
+
+fn cache_path() -> PathBuf {
+    app_data_dir().join("analytics").join("growth.json")
+}
+
+fn is_memory(path: &str) -> bool {
+    path.ends_with(".md")
+        && (path.starts_with(ENGINE_MEMORY_DIR)
+            || matches!(
+                paths::classify(path),
+                Some(Location::Memories | Location::Memory(_))
+            ))
+}
+
+fn count_words(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+fn git(repo: &Path, args: &[&str], token: &CancelToken) -> Result<String, String> {
+    let output = run_command(Command::new("git").current_dir(repo).args(args), token)
+        .map_err(|e| format!("git {} failed: {}", args[0], e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Word counts for blobs, read in one `git cat-file --batch` run.
+fn blob_words(
+    repo: &Path,
+    blobs: &[String],
+    token: &CancelToken,
+) -> Result<HashMap<String, usize>, String> {
+    let list = app_data_dir().join("analytics").join("blobs.txt");
+    let mut file = File::create(&list).map_err(|e| e.to_string())?;
+    for blob in blobs {
+        writeln!(file, "{}", blob).map_err(|e| e.to_string())?;
+    }
+    drop(file);
+
+    let stdin = File::open(&list).map_err(|e| e.to_string())?;
+    let output = run_command(
+        Command::new("git")
+            .current_dir(repo)
+            .args(["cat-file", "--batch"])
+            .stdin(Stdio::from(stdin)),
+        token,
+    );
+    let _ = fs::remove_file(&list);
+    let output = output?;
+
+    // "<sha> blob <size>\n<content>\n" per object, "<sha> missing\n" otherwise
+    let data = output.stdout;
+    let mut words = HashMap::new();
+    let mut pos = 0;
+    while let Some(end) = data[pos..].iter().position(|&b| b == b'\n') {
+        let header = String::from_utf8_lossy(&data[pos..pos + end]).to_string();
+        pos += end + 1;
+        let parts: Vec<&str> = header.split(' ').collect();
+        let Some(size) = parts.get(2).and_then(|s| s.parse::<usize>().ok()) else {
+            continue;
+        };
+        let content = &data[pos..(pos + size).min(data.len())];
+        words.insert(
+            parts[0].to_string(),
+            count_words(&String::from_utf8_lossy(content)),
+        );
+        pos += size + 1;
+    }
+    Ok(words)
+}
+
+/// Stats for one commit, using (and filling) the blob word cache.
+fn analyze_commit(
+    repo: &Path,
+    hash: &str,
+    timestamp: i64,
+    cache: &mut GrowthCache,
+    token: &CancelToken,
+) -> Result<CommitStats, String> {
+    // "<mode> blob <sha> <size>\t<path>"
+    let tree = git(repo, &["ls-tree", "-r", "-l", hash], token)?;
+    let mut markdown = Vec::new();
+    let mut seed_bytes = 0;
+    let mut memory_files = 0;
+    for line in tree.lines() {
+        let Some((meta, path)) = line.split_once('\t') else {
+            continue;
+        };
+        let meta: Vec<&str> = meta.split_whitespace().collect();
+        if meta.get(1) != Some(&"blob") || !path.ends_with(".md") {
+            continue;
+        }
+        if path == "SEED.md" {
+            seed_bytes = meta.get(3).and_then(|s| s.parse().ok()).unwrap_or(0);
+        }
+        if is_memory(path) {
+            memory_files += 1;
+        }
+        markdown.push(meta[2].to_string());
+    }
+
+    let missing: Vec<String> = markdown
+        .iter()
+        .filter(|b| !cache.blob_words.contains_key(*b))
+        .cloned()
+        .collect();
+    if !missing.is_empty() {
+        cache.blob_words.extend(blob_words(repo, &missing, token)?);
+    }
+    let total_words = markdown
+        .iter()
+        .filter_map(|b| cache.blob_words.get(b))
+        .sum();
+
+    // Memory files this commit added relative to its first parent
+    let added = git(
+        repo,
+        &[
+            "diff-tree",
+            "--no-commit-id",
+            "--name-status",
+            "-r",
+            "--root",
+            "--diff-filter=A",
+            hash,
+        ],
+        token,
+    )?;
+    let new_memories = added
+        .lines()
+        .filter_map(|l| l.split_once('\t'))
+        .filter(|(_, path)| is_memory(path))
+        .count();
+
+    Ok(CommitStats {
+        timestamp,
+        total_words,
+        seed_bytes,
+        memory_files,
+        new_memories,
+    })
+}
+
+/// Working-tree snapshot; memory files without a commit count by mtime.
+fn current_snapshot(soul_path: &Path) -> (GrowthPoint, Vec<i64>) {
+    let mut point = GrowthPoint {
+        date: Local::now().format("%Y-%m-%d").to_string(),
+        total_words: 0,
+        seed_bytes: 0,
+        memory_files: 0,
+        memories_created: 0,
+    };
+    let mut memory_mtimes = Vec::new();
+    let mut dirs = vec![soul_path.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if path.is_dir() {
+                if !name.starts_with('.') && name != "node_modules" {
+                    dirs.push(path);
+                }
+                continue;
+            }
+            if !name.ends_with(".md") {
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(soul_path) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            // Encrypted files aren't valid UTF-8 and don't count
+            if let Ok(content) = fs::read_to_string(&path) {
+                point.total_words += count_words(&content);
+            }
+            if relative == "SEED.md" {
+                point.seed_bytes = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            }
+            if is_memory(&relative) {
+                point.memory_files += 1;
+                if let Ok(modified) = entry.metadata().and_then(|m| m.modified()) {
+                    memory_mtimes.push(DateTime::<Local>::from(modified).timestamp());
+                }
+            }
+        }
+    }
+    (point, memory_mtimes)
+}
+
+fn bucket_start(date: NaiveDate, resolution: Resolution) -> NaiveDate {
+    match resolution {
+        Resolution::Day => date,
+        Resolution::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+        Resolution::Month => date.with_day(1).unwrap_or(date),
+    }
+}
+
+fn local_date(timestamp: i64) -> Option<NaiveDate> {
+    Local
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .map(|dt| dt.date_naive())
+}
+
+/// `range`: "7d", "30d", "90d", "1y" or "all".
+fn range_start(range: &str) -> Result<Option<NaiveDate>, String> {
+    if range == "all" {
+        return Ok(None);
+    }
+    let (count, unit) = range.split_at(range.len().saturating_sub(1));
+    let count: i64 = count
+        .parse()
+        .map_err(|_| format!("Invalid range: {}", range))?;
+    let days = match unit {
+        "d" => count,
+        "w" => count * 7,
+        "m" => count * 30,
+        "y" => count * 365,
+        _ => return Err(format!("Invalid range: {}", range)),
+    };
+    Ok(Some(Local::now().date_naive() - Duration::days(days)))
+}
+
+impl Analytics {
+    /// Growth of the soul over `range`, bucketed by `resolution`.
+    pub fn growth(
+        &self,
+        soul_path: &Path,
+        range: &str,
+        resolution: Resolution,
+        token: &CancelToken,
+    ) -> Result<GrowthMetrics, String> {
+        let start = range_start(range)?;
+        let mut guard = self.cache.lock();
+        let cache = guard.get_or_insert_with(|| {
+            fs::read_to_string(cache_path())
+                .ok()
+                .and_then(|data| serde_json::from_str(&data).ok())
+                .unwrap_or_default()
+        });
+
+        // Commit history (oldest first), analyzing only what isn't cached
+        let mut history = Vec::new();
+        if soul_path.join(".git").exists() {
+            let _ = fs::create_dir_all(app_data_dir().join("analytics"));
+            let log = git(
+                soul_path,
+                &["log", "--reverse", "--first-parent", "--format=%H|%at"],
+                token,
+            )
+            .unwrap_or_default();
+            let mut dirty = false;
+            let commits: Vec<(&str, i64)> = log
+                .lines()
+                .filter_map(|l| l.split_once('|'))
+                .filter_map(|(hash, ts)| ts.parse().ok().map(|ts| (hash, ts)))
+                .collect();
+            let total = commits.len() as u64;
+            for (done, (hash, timestamp)) in commits.into_iter().enumerate() {
+                token.check()?;
+                token.set_progress(done as u64, total);
+                if !cache.commits.contains_key(hash) {
+                    let stats = analyze_commit(soul_path, hash, timestamp, cache, token)?;
+                    cache.commits.insert(hash.to_string(), stats);
+                    dirty = true;
+                }
+                history.push(cache.commits[hash].clone());
+            }
+            if dirty {
+                if let Ok(json) = serde_json::to_string(&*cache) {
+                    let _ = fs::write(cache_path(), json);
+                }
+            }
+        }
+        drop(guard);
+
+        let (current, uncommitted_mtimes) = current_snapshot(soul_path);
+        let mut buckets: BTreeMap<NaiveDate, GrowthPoint> = BTreeMap::new();
+        let mut add = |date: NaiveDate, f: &dyn Fn(&mut GrowthPoint)| {
+            let key = bucket_start(date, resolution);
+            let point = buckets.entry(key).or_insert_with(|| GrowthPoint {
+                date: key.format("%Y-%m-%d").to_string(),
+                total_words: 0,
+                seed_bytes: 0,
+                memory_files: 0,
+                memories_created: 0,
+            });
+            f(point);
+        };
+
+        // Commits arrive oldest first, so each bucket ends with its last commit's size
+        for stats in &history {
+            let Some(date) = local_date(stats.timestamp) else {
+                continue;
+            };
+            add(date, &|p| {
+                p.total_words = stats.total_words;
+                p.seed_bytes = stats.seed_bytes;
+                p.memory_files = stats.memory_files;
+                p.memories_created += stats.new_memories;
+            });
+        }
+        // Memory files the history doesn't know about yet
+        let committed: usize = history.last().map(|s| s.memory_files).unwrap_or(0);
+        let mut uncommitted = uncommitted_mtimes;
+        uncommitted.sort();
+        let extra = current.memory_files.saturating_sub(committed);
+        for ts in uncommitted.iter().rev().take(extra) {
+            if let Some(date) = local_date(*ts) {
+                add(date, &|p| p.memories_created += 1);
+            }
+        }
+        add(Local::now().date_naive(), &|p| {
+            p.total_words = current.total_words;
+            p.seed_bytes = current.seed_bytes;
+            p.memory_files = current.memory_files;
+        });
+
+        // Buckets without commits carry the previous size forward
+        let mut points: Vec<GrowthPoint> = buckets.into_values().collect();
+        for i in 1..points.len() {
+            if points[i].total_words == 0 && points[i].seed_bytes == 0 {
+                points[i].total_words = points[i - 1].total_words;
+                points[i].seed_bytes = points[i - 1].seed_bytes;
+                points[i].memory_files = points[i - 1].memory_files;
+            }
+        }
+        if let Some(start) = start {
+            let start = bucket_start(start, resolution)
+                .format("%Y-%m-%d")
+                .to_string();
+            points.retain(|p| p.date >= start);
+        }
+
+        Ok(GrowthMetrics {
+            points,
+            current,
+            commits_analyzed: history.len(),
+        })
+    }
+}
@@ -0,0 +1,710 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use ts_rs::TS;
+
+use crate::ambient::{AmbientFrame, AmbientStatus};
+use crate::analytics::{GrowthMetrics, Resolution};
+use crate::assistant::{AssistantSummary, TagSuggestions};
+use crate::audit::{AuditEntry, AuditFilter};
+use crate::availability::EngineAvailability;
+use crate::background::BackgroundStatus;
+use crate::batch::{BatchOp, BatchReport};
+use crate::bench::BenchmarkResult;
+use crate::blob::BlobHandle;
+use crate::boot::{AutostartStatus, BootProgress, BootReport};
+use crate::browser::BrowserProfile;
+use crate::clip::ClippedPage;
+use crate::companion::{CompanionConfig, CompanionDevice, CompanionNote, CompanionPairing};
+use crate::context_bundle::ContextBundle;
+use crate::crash::{CrashConfig, CrashReport};
+use crate::digest::DigestInfo;
+use crate::embeddings::{EmbeddingStatus, MemoryHit};
+use crate::engine_update::{EngineUpdateReport, EngineVersionInfo};
+use crate::downloads::{DownloadConfig, DownloadProgress, MediaAttachment};
+use crate::env_layers::EffectiveEnvVar;
+use crate::env_policy::EnvPolicy;
+use crate::env_schema::EnvKeyInfo;
+use crate::env_watch::EnvChange;
+use crate::export::ExportInfo;
+use crate::founding::{FoundingPreview, OverwriteConfirmation};
+use crate::founding_template::FoundingAnswers;
+use crate::highlight::DailyHighlight;
+use crate::housekeeping::HousekeepingReport;
+use crate::journal::RecoveryReport;
+use crate::language::{LanguageGuess, SpellcheckReport};
+use crate::lint::LintReport;
+use crate::lock::LockStatus;
+use crate::markdown::{RenderOptions, RenderedMarkdown};
+use crate::memory_export::MemoryExport;
+use crate::paths::{LayoutReport, SoulLayout};
+use crate::metrics::MetricsConfig;
+use crate::network::NetworkConfig;
+use crate::peers::{PairRequest, Peer};
+use crate::permissions::{CommandInvocation, ElevationStatus, SENSITIVE_COMMANDS};
+use crate::persona::{PersonaFile, PersonaSaveResult};
+use crate::pii::PiiReport;
+use crate::policy::{PolicyInfo, PolicyViolation};
+use crate::profiles::ProfileStatus;
+use crate::protected::WriteConfirmation;
+use crate::proxy::{MonitorConfig, ProxyLimits};
+use crate::pty::PtyInfo;
+use crate::pty_history::CommandRecord;
+use crate::recent::{RecentFile, SuggestedFile};
+use crate::relocate::RelocationReport;
+use crate::restore::RestorePlan;
+use crate::retention::{PurgeReport, RetentionConfig};
+use crate::rollup::{RollupConfig, RollupReport};
+use crate::review::PendingChange;
+use crate::search::SearchResults;
+use crate::setup_preflight::SetupPreflight;
+use crate::sidecar::SidecarStatus;
+use crate::sidecar_exit::SidecarExit;
+use crate::simulation::SimulationStatus;
+use crate::soul_health::SoulHealth;
+use crate::soul_identity::SoulIdentity;
+use crate::statelog::{StateDistribution, StatelogPage};
+use crate::stream::{StreamChunk, StreamError, StreamInfo};
+use crate::tags::TagCount;
+use crate::tasks::TaskInfo;
+use crate::text_stats::{DocumentStats, SoulTextStats};
+use crate::timeline::Timeline;
+use crate::transcripts::{TranscriptHit, TranscriptPage, TranscriptSummary};
+use crate::types::{BackendHealth, GitCommit, SoulActivity, SoulMood, SoulPulse, SoulStatus};
+use crate::vault::{EncryptionStatus, MigrationReport};
+use crate::vitals::Vitals;
+use crate::watcher::{WatchRoot, WatchedRootInfo, WatcherConfig};
+
+/// Bumped when a command or event changes incompatibly. Additions don't bump
+/// it — clients are expected to feature-detect against the manifest.
+pub const API_VERSION: u32 = 1;
+
+/// Every IPC command with its frontend-visible parameters and return type
+/// (the Ok side; errors reject the promise). This single list drives the
+/// invoke handler (lib.rs), `get_api_manifest` and the generated TypeScript
+/// bindings, so none of them can drift from what is actually registered.
+/// Injected arguments (AppHandle, State, Window) are not listed.
+macro_rules! api_commands {
+    ($callback:ident) => {
+        $callback! {
+            get_soul_status() -> SoulStatus,
+            read_soul_file(name: String) -> String,
+            stream_soul_file(name: String, stream_id: String) -> StreamInfo,
+            stream_ack(stream_id: String, seq: u32) -> (),
+            cancel_stream(stream_id: String) -> (),
+            share_soul_file(name: String) -> BlobHandle,
+            semantic_search(query: String, k: Option<usize>) -> Vec<MemoryHit>,
+            get_related(name: String, k: Option<usize>) -> Vec<MemoryHit>,
+            index_memories() -> EmbeddingStatus,
+            list_tags() -> Vec<TagCount>,
+            get_files_by_tag(tag: String) -> Vec<String>,
+            retag_file(name: String, add: Vec<String>, remove: Vec<String>) -> Vec<String>,
+            assistant_summarize(file: String) -> AssistantSummary,
+            assistant_suggest_tags(file: String) -> TagSuggestions,
+            build_context_bundle(
+                budget_tokens: Option<usize>,
+                focus: Option<String>,
+                target: Option<String>
+            ) -> ContextBundle,
+            get_recent_files(limit: Option<usize>) -> Vec<RecentFile>,
+            get_suggested_files() -> Vec<SuggestedFile>,
+            list_pinned() -> Vec<String>,
+            pin_file(name: String) -> Vec<String>,
+            unpin_file(name: String) -> Vec<String>,
+            write_soul_file(name: String, content: String, confirmation_token: Option<String>) -> (),
+            get_protected_paths() -> Vec<String>,
+            set_protected_paths(paths: Vec<String>) -> Vec<String>,
+            request_write_confirmation(name: String, reason: String) -> WriteConfirmation,
+            get_soul_path() -> String,
+            set_soul_path(path: String) -> (),
+            get_soul_health() -> Option<SoulHealth>,
+            get_soul_identity() -> Option<SoulIdentity>,
+            relink_soul(path: String) -> SoulHealth,
+            restore_latest_backup() -> SoulHealth,
+            relocate_soul(new_path: String, move_data: bool, op_id: Option<String>) -> RelocationReport,
+            get_active_nodes() -> HashMap<String, f64>,
+            get_is_working() -> bool,
+            get_mood() -> Option<SoulMood>,
+            get_vitals() -> Option<Vitals>,
+            get_daily_highlight() -> Option<DailyHighlight>,
+            list_statelog(from: Option<String>, to: Option<String>, offset: Option<usize>, limit: Option<usize>, op_id: Option<String>) -> StatelogPage,
+            get_state_distribution(days: Option<u32>) -> StateDistribution,
+            set_watcher_config(watcher: WatcherConfig) -> (),
+            apply_watcher_preset(name: String) -> WatcherConfig,
+            set_watch_roots(roots: Vec<WatchRoot>) -> (),
+            get_watcher_info() -> Vec<WatchedRootInfo>,
+            start_engine() -> (),
+            stop_engine() -> (),
+            boot_all() -> BootReport,
+            shutdown_all() -> BootReport,
+            pause_engine() -> (),
+            resume_engine() -> (),
+            reload_engine() -> String,
+            get_engine_version() -> EngineVersionInfo,
+            rollback_engine() -> EngineUpdateReport,
+            get_reload_on_config_change() -> bool,
+            set_reload_on_config_change(enabled: bool) -> (),
+            get_engine_log_level() -> Option<String>,
+            set_engine_log_level(level: String) -> String,
+            get_autostart() -> AutostartStatus,
+            set_autostart(engine: bool, chain: bool) -> (),
+            get_sidecar_status() -> SidecarStatus,
+            get_engine_availability(range: Option<String>) -> EngineAvailability,
+            create_pty(cols: u16, rows: u16, restricted: Option<bool>, cwd: Option<String>, purpose: Option<String>) -> u32,
+            list_ptys() -> Vec<PtyInfo>,
+            get_restore_plan() -> Option<RestorePlan>,
+            dismiss_restore_plan() -> (),
+            set_open_views(views: Vec<String>) -> (),
+            get_pty_command_history(id: u32) -> Vec<CommandRecord>,
+            search_command_history(query: String, limit: Option<usize>) -> Vec<CommandRecord>,
+            write_pty(id: u32, data: String) -> (),
+            paste_pty(id: u32, text: String) -> (),
+            resize_pty(id: u32, cols: u16, rows: u16) -> (),
+            close_pty(id: u32) -> (),
+            detach_pty_window(id: u32) -> String,
+            attach_pty_window(id: u32) -> (),
+            get_state_history(limit: Option<u32>, op_id: Option<String>) -> Vec<GitCommit>,
+            get_state_diff(hash: String, op_id: Option<String>) -> String,
+            stream_state_diff(hash: String, stream_id: String, op_id: Option<String>) -> StreamInfo,
+            rollback_state(hash: String) -> String,
+            list_directory(name: String, op_id: Option<String>) -> Vec<String>,
+            read_env() -> HashMap<String, String>,
+            write_env(entries: HashMap<String, String>) -> (),
+            get_effective_env(process: String) -> Vec<EffectiveEnvVar>,
+            set_session_env(name: String, value: Option<String>, process: Option<String>) -> (),
+            clear_session_env() -> (),
+            set_env_default(name: String, value: Option<String>) -> (),
+            get_env_schema() -> Vec<EnvKeyInfo>,
+            get_app_state() -> String,
+            generate_daily_digest(date: Option<String>, op_id: Option<String>) -> DigestInfo,
+            get_timeline(day: Option<String>, op_id: Option<String>) -> Timeline,
+            get_document_stats(name: String) -> DocumentStats,
+            get_soul_text_stats(op_id: Option<String>) -> SoulTextStats,
+            detect_language(text: String) -> LanguageGuess,
+            spellcheck(text: String, lang: Option<String>) -> SpellcheckReport,
+            list_transcripts(op_id: Option<String>) -> Vec<TranscriptSummary>,
+            get_transcript(id: String, page: Option<usize>) -> TranscriptPage,
+            search_transcripts(query: String, limit: Option<usize>, op_id: Option<String>) -> Vec<TranscriptHit>,
+            open_search_session() -> String,
+            update_search_query(session: String, query: String) -> (),
+            close_search_session(session: String) -> (),
+            get_persona_files() -> Vec<PersonaFile>,
+            lint_soul_document(name: String, content: Option<String>) -> LintReport,
+            save_persona_file(
+                name: String,
+                content: String,
+                validate_only: bool,
+                reload: Option<bool>
+            ) -> PersonaSaveResult,
+            get_policy() -> PolicyInfo,
+            reload_policy() -> PolicyInfo,
+            list_pending_changes() -> Vec<PendingChange>,
+            approve_change(id: String) -> (),
+            reject_change(id: String) -> (),
+            approve_change_group(group: String) -> usize,
+            reject_change_group(group: String) -> usize,
+            set_review_mode(enabled: bool) -> (),
+            get_recovery_report() -> RecoveryReport,
+            get_crash_config() -> CrashConfig,
+            set_crash_config(crash_config: CrashConfig) -> (),
+            get_pending_crash_reports() -> Vec<CrashReport>,
+            send_crash_reports(ids: Vec<String>) -> usize,
+            dismiss_crash_reports(ids: Vec<String>) -> usize,
+            get_metrics_config() -> MetricsConfig,
+            set_metrics_config(metrics_config: MetricsConfig) -> (),
+            get_companion_config() -> CompanionConfig,
+            set_companion_config(companion_config: CompanionConfig) -> (),
+            list_paired_devices() -> Vec<CompanionDevice>,
+            pair_companion_device(name: String) -> CompanionPairing,
+            revoke_device(id: String) -> (),
+            list_peers() -> Vec<Peer>,
+            request_peer_pairing(peer_id: String) -> (),
+            confirm_peer_pairing(peer_id: String, code: String) -> Peer,
+            answer_peer_pairing(request_id: String, accept: bool) -> (),
+            forget_peer(peer_id: String) -> (),
+            get_peer_status(peer_id: String) -> Value,
+            get_audit_log(filter: Option<AuditFilter>) -> Vec<AuditEntry>,
+            cancel_task(id: String) -> bool,
+            list_tasks() -> Vec<TaskInfo>,
+            get_backend_health() -> BackendHealth,
+            check_node() -> Value,
+            preflight_soul_path(path: Option<String>) -> SetupPreflight,
+            create_soul_directories(op_id: Option<String>) -> (),
+            get_soul_layout() -> Option<SoulLayout>,
+            migrate_soul_layout(target_locale: String, op_id: Option<String>) -> LayoutReport,
+            start_chain() -> (),
+            stop_chain() -> (),
+            get_chain_status() -> SidecarStatus,
+            start_founding() -> u16,
+            stop_founding() -> (),
+            founding_chat(message: String, history: Vec<Value>) -> Value,
+            founding_preview(history: Vec<Value>, answers: Option<FoundingAnswers>) -> FoundingPreview,
+            confirm_overwrite() -> OverwriteConfirmation,
+            founding_create(token: String, overwrite_token: Option<String>) -> Value,
+            open_browser(url: String, full_mode: bool, profile: Option<String>) -> (),
+            close_browser() -> (),
+            list_browser_profiles() -> Vec<BrowserProfile>,
+            clear_browser_profile(name: String) -> (),
+            get_download_config() -> DownloadConfig,
+            set_download_config(download_config: DownloadConfig) -> (),
+            get_retention_config() -> RetentionConfig,
+            set_retention_config(retention_config: RetentionConfig) -> (),
+            preview_purge() -> PurgeReport,
+            run_purge() -> PurgeReport,
+            get_rollup_config() -> RollupConfig,
+            set_rollup_config(rollup_config: RollupConfig) -> (),
+            run_rollup() -> RollupReport,
+            get_housekeeping_report(op_id: Option<String>) -> HousekeepingReport,
+            delete_soul_files(names: Vec<String>) -> usize,
+            apply_batch(ops: Vec<BatchOp>, op_id: Option<String>) -> BatchReport,
+            list_downloads() -> Vec<MediaAttachment>,
+            clip_page(url_or_window_id: String) -> ClippedPage,
+            get_network_config() -> NetworkConfig,
+            set_network_config(network_config: NetworkConfig) -> (),
+            get_env_policy() -> EnvPolicy,
+            set_env_policy(env_policy: EnvPolicy) -> (),
+            fetch_engine_subsystems() -> Value,
+            engine_api(method: String, path: String, body: Option<Value>) -> Value,
+            set_proxy_limits(limits: ProxyLimits) -> (),
+            set_monitor_config(monitor: MonitorConfig) -> (),
+            suspend_background(suspended: bool) -> BackgroundStatus,
+            get_background_status() -> BackgroundStatus,
+            get_ambient_status() -> AmbientStatus,
+            get_runtime_profile() -> ProfileStatus,
+            set_runtime_profile(name: String) -> ProfileStatus,
+            get_elevation_status() -> ElevationStatus,
+            request_elevation(reason: String) -> bool,
+            drop_elevation() -> (),
+            get_command_trail() -> Vec<CommandInvocation>,
+            get_lock_status() -> LockStatus,
+            lock_app() -> (),
+            unlock_app(passphrase: String) -> (),
+            set_lock_passphrase(current: Option<String>, passphrase: Option<String>) -> (),
+            set_auto_lock(minutes: u32, on_sleep: bool) -> LockStatus,
+            get_encryption_status() -> EncryptionStatus,
+            set_encrypted_directories(directories: Vec<String>) -> EncryptionStatus,
+            migrate_encryption(op_id: Option<String>) -> MigrationReport,
+            scan_for_pii(scope: Option<String>, redact: Option<bool>, op_id: Option<String>) -> PiiReport,
+            export_soul_filtered(
+                categories: Vec<String>,
+                anonymize: bool,
+                destination: Option<String>,
+                op_id: Option<String>
+            ) -> ExportInfo,
+            render_markdown(
+                name_or_content: String,
+                options: Option<RenderOptions>
+            ) -> RenderedMarkdown,
+            export_memory(
+                name: String,
+                format: String,
+                destination: Option<String>
+            ) -> MemoryExport,
+            print_document(name: String) -> (),
+            get_growth_metrics(
+                range: Option<String>,
+                resolution: Option<Resolution>,
+                op_id: Option<String>
+            ) -> GrowthMetrics,
+            benchmark_pipeline(n_files: usize, op_id: Option<String>) -> BenchmarkResult,
+            start_simulation(profile: Option<String>) -> SimulationStatus,
+            stop_simulation() -> (),
+            get_simulation_status() -> SimulationStatus,
+            get_api_manifest() -> ApiManifest,
+        }
+    };
+}
+pub(crate) use api_commands;
+
+/// Events the backend emits: payload type plus a short description for
+/// ad-hoc JSON payloads.
+macro_rules! api_events {
+    ($callback:ident) => {
+        $callback! {
+            "soul:pulse" => SoulPulse: "",
+            "soul:mood" => SoulMood: "",
+            "soul:activity" => SoulActivity: "",
+            "soul:nodes" => HashMap<String, f64>: "node id → activity level",
+            "soul:hydrated" => HashMap<String, f64>: "node id → activity level",
+            "soul:bus-event" => Value: "engine bus event",
+            "soul:status-changed" => SoulStatus: "",
+            "soul:vitals" => Vitals: "a heartbeat log changed",
+            "soul:health" => SoulHealth: "the soul went missing or was recovered",
+            "soul:relocated" => RelocationReport: "the soul moved to a new path",
+            "tags:changed" => Value: "{ path } — a memory file's tags changed",
+            "pins:changed" => Vec<String>: "pinned files, in pinning order",
+            "sidecar:status" => SidecarStatus: "",
+            "sidecar:stdout" => Value: "{ process, lines, dropped }",
+            "sidecar:stderr" => Value: "{ process, lines, dropped }",
+            "sidecar:reloaded" => Value: "{ method }",
+            "sidecar:crashed" => SidecarExit: "a sidecar died on its own, with the classified cause",
+            "boot:progress" => BootProgress: "a boot_all or shutdown_all step started or finished",
+            "env:changed" => EnvChange: "keys of the soul's .env changed (no values)",
+            "engine:updated" => EngineUpdateReport: "",
+            "pty:data" => Value: "{ id, data }",
+            "pty:exit" => Value: "{ id }",
+            "pty:detached" => Value: "{ id, window }",
+            "pty:attached" => Value: "{ id }",
+            "task:progress" => TaskInfo: "",
+            "task:finished" => TaskInfo: "",
+            "stream:chunk" => StreamChunk: "piece of a streamed value; acknowledge with stream_ack",
+            "stream:error" => StreamError: "a stream was cancelled or its consumer stalled",
+            "recovery:report" => RecoveryReport: "",
+            "profile:changed" => ProfileStatus: "",
+            "background:changed" => BackgroundStatus: "",
+            "ambient:changed" => AmbientStatus: "screensaver mode entered or left",
+            "ambient:frame" => AmbientFrame: "slow screensaver feed, every frame_ms while active",
+            "crash:pending" => Vec<CrashReport>: "redacted reports from earlier runs",
+            "digest:ready" => Value: "{ date, path }",
+            "review:pending" => PendingChange: "",
+            "review:resolved" => Value: "{ id, path, approved }",
+            "policy:violation" => PolicyViolation: "",
+            "permissions:changed" => ElevationStatus: "",
+            "permissions:denied" => Value: "{ command }",
+            "proxy:throttled" => Value: "{ endpoint, queued, max_queue }",
+            "engine:subsystems" => Value: "engine subsystem health",
+            "app:locked" => LockStatus: "",
+            "app:unlocked" => LockStatus: "",
+            "bench:event" => String: "relative path of the benchmark file",
+            "simulation:started" => SimulationStatus: "",
+            "simulation:stopped" => SimulationStatus: "",
+            "browser:download" => DownloadProgress: "",
+            "companion:note" => CompanionNote: "quick note merged from a companion device",
+            "peers:changed" => Vec<Peer>: "an instance appeared or moved on the network",
+            "peers:pair-request" => PairRequest: "ask the user to accept, then show the code",
+            "search:results" => SearchResults: "results of a search session's latest query",
+        }
+    };
+}
+
+/// JSON-schema-ish description of a parameter type.
+pub trait Schema {
+    fn schema() -> Value;
+
+    fn required() -> bool {
+        true
+    }
+}
+
+macro_rules! primitive_schema {
+    ($($ty:ty => $json:expr),* $(,)?) => {
+        $(impl Schema for $ty {
+            fn schema() -> Value {
+                json!({ "type": $json })
+            }
+        })*
+    };
+}
+
+primitive_schema! {
+    String => "string",
+    bool => "boolean",
+    u16 => "integer",
+    u32 => "integer",
+    usize => "integer",
+}
+
+/// Config structs are passed through as objects; `name` is the TypeScript
+/// interface in src/lib/tauri.ts.
+macro_rules! object_schema {
+    ($($ty:ident),* $(,)?) => {
+        $(impl Schema for $ty {
+            fn schema() -> Value {
+                json!({ "type": "object", "name": stringify!($ty) })
+            }
+        })*
+    };
+}
+
+object_schema!(
+    WatcherConfig,
+    WatchRoot,
+    ProxyLimits,
+    MonitorConfig,
+    AuditFilter,
+    CrashConfig,
+    MetricsConfig,
+    FoundingAnswers,
+    DownloadConfig,
+    NetworkConfig,
+    EnvPolicy,
+    RetentionConfig,
+    RollupConfig,
+    CompanionConfig,
+    RenderOptions,
+    BatchOp
+);
+
+impl Schema for Value {
+    fn schema() -> Value {
+        json!({})
+    }
+}
+
+impl Schema for Resolution {
+    fn schema() -> Value {
+        json!({ "type": "string", "enum": ["day", "week", "month"] })
+    }
+}
+
+impl<T: Schema> Schema for Option<T> {
+    fn schema() -> Value {
+        T::schema()
+    }
+
+    fn required() -> bool {
+        false
+    }
+}
+
+impl<T: Schema> Schema for Vec<T> {
+    fn schema() -> Value {
+        json!({ "type": "array", "items": T::schema() })
+    }
+}
+
+impl<T: Schema> Schema for HashMap<String, T> {
+    fn schema() -> Value {
+        json!({ "type": "object", "additionalProperties": T::schema() })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ParamSpec {
+    /// Argument name on the wire (camelCase, as passed to `invoke`)
+    pub name: String,
+    pub required: bool,
+    pub schema: Value,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub params: Vec<ParamSpec>,
+    /// TypeScript type of the resolved value
+    pub returns: String,
+    /// Only runs inside an elevated session
+    pub sensitive: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct EventSpec {
+    pub name: &'static str,
+    /// TypeScript type of the payload
+    pub payload: String,
+    /// Shape of ad-hoc JSON payloads, empty for typed ones
+    pub description: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ApiManifest {
+    pub version: &'static str,
+    pub api_version: u32,
+    pub commands: Vec<CommandSpec>,
+    pub events: Vec<EventSpec>,
+}
+
+/// Tauri converts snake_case command arguments to camelCase on the wire.
+fn camel_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn param<T: Schema>(name: &str) -> ParamSpec {
+    ParamSpec {
+        name: camel_case(name),
+        required: T::required(),
+        schema: T::schema(),
+    }
+}
+
+macro_rules! command_specs {
+    ($($cmd:ident($($param:ident: $ty:ty),* $(,)?) -> $ret:ty),* $(,)?) => {
+        vec![$(CommandSpec {
+            name: stringify!($cmd),
+            params: vec![$(param::<$ty>(stringify!($param))),*],
+            returns: <$ret as TS>::name(),
+            sensitive: SENSITIVE_COMMANDS.contains(&stringify!($cmd)),
+        }),*]
+    };
+}
+
+macro_rules! event_specs {
+    ($($name:literal => $payload:ty: $description:literal),* $(,)?) => {
+        vec![$(EventSpec {
+            name: $name,
+            payload: <$payload as TS>::name(),
+            description: $description,
+        }),*]
+    };
+}
+
+pub fn manifest() -> ApiManifest {
+    ApiManifest {
+        version: env!("CARGO_PKG_VERSION"),
+        api_version: API_VERSION,
+        commands: api_commands!(command_specs),
+        events: api_events!(event_specs),
+    }
+}
+
+/// The TypeScript bindings: one file per type the commands and events
+/// refer to, plus commands.ts and events.ts generated from the lists above.
+/// Written by the `export-bindings` binary (`npm run bindings`), which needs
+/// the `bindings` feature.
+#[cfg(any(test, feature = "bindings"))]
+pub mod bindings {
+    use std::any::TypeId;
+    use std::collections::{BTreeMap, HashSet};
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+
+    use ts_rs::{Dependency, ExportError, TypeVisitor};
+
+    use super::*;
+
+    type Export = fn(&Path) -> Result<(), ExportError>;
+
+    /// Every named type a signature refers to, with its import path and
+    /// how to export it.
+    #[derive(Default)]
+    struct Imports {
+        seen: HashSet<TypeId>,
+        paths: BTreeMap<String, String>,
+        exports: Vec<Export>,
+    }
+
+    impl TypeVisitor for Imports {
+        fn visit<T: TS + 'static + ?Sized>(&mut self) {
+            let Some(dep) = Dependency::from_ty::<T>() else {
+                return T::visit_generics(self);
+            };
+            if self.seen.insert(dep.type_id) {
+                self.exports.push(|dir| T::export_all_to(dir));
+                let path = dep.output_path.with_extension("");
+                self.paths
+                    .insert(dep.ts_name, format!("./{}", path.to_string_lossy()));
+            }
+        }
+    }
+
+    impl Imports {
+        /// Export the types into `dir`; returns the import lines for them.
+        fn export(&self, dir: &Path) -> io::Result<String> {
+            for export in &self.exports {
+                export(dir).map_err(io::Error::other)?;
+            }
+            let mut out =
+                String::from("// Generated by `npm run bindings` — do not edit.\n\n");
+            for (name, path) in &self.paths {
+                out.push_str(&format!("import type {{ {} }} from \"{}\";\n", name, path));
+            }
+            out.push('\n');
+            Ok(out)
+        }
+    }
+
+    macro_rules! command_bindings {
+        ($($cmd:ident($($param:ident: $ty:ty),* $(,)?) -> $ret:ty),* $(,)?) => {{
+            let mut imports = Imports::default();
+            let mut body = String::new();
+            $(
+                imports.visit::<$ret>();
+                $(imports.visit::<$ty>();)*
+                let args: Vec<String> = vec![$(format!(
+                    "{}{}: {}",
+                    camel_case(stringify!($param)),
+                    if <$ty as Schema>::required() { "" } else { "?" },
+                    <$ty as TS>::name()
+                )),*];
+                let args = if args.is_empty() {
+                    "Record<string, never>".to_string()
+                } else {
+                    format!("{{ {} }}", args.join("; "))
+                };
+                body.push_str(&format!(
+                    "  {}: {{ args: {}; returns: {} }};\n",
+                    stringify!($cmd),
+                    args,
+                    <$ret as TS>::name()
+                ));
+            )*
+            (imports, body)
+        }};
+    }
+
+    macro_rules! event_bindings {
+        ($($name:literal => $payload:ty: $description:literal),* $(,)?) => {{
+            let mut imports = Imports::default();
+            let mut body = String::new();
+            $(
+                imports.visit::<$payload>();
+                if !$description.is_empty() {
+                    body.push_str(&format!("  /** {} */\n", $description));
+                }
+                body.push_str(&format!("  \"{}\": {};\n", $name, <$payload as TS>::name()));
+            )*
+            (imports, body)
+        }};
+    }
+
+    /// Write the bindings into `dir`.
+    pub fn export(dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+
+        let (imports, body) = api_commands!(command_bindings);
+        let commands = format!(
+            "{}/** Every IPC command: camelCase arguments and the resolved value. */\nexport interface Commands {{\n{}}}\n",
+            imports.export(dir)?,
+            body
+        );
+        fs::write(dir.join("commands.ts"), commands)?;
+
+        let (imports, body) = api_events!(event_bindings);
+        let events = format!(
+            "{}/** Every backend event and its payload. */\nexport interface Events {{\n{}}}\n",
+            imports.export(dir)?,
+            body
+        );
+        fs::write(dir.join("events.ts"), events)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn exports_commands_events_and_their_types() {
+            let dir = std::env::temp_dir().join(format!("soulos-bindings-{}", std::process::id()));
+            export(&dir).unwrap();
+            let commands = fs::read_to_string(dir.join("commands.ts")).unwrap();
+            let status = "  get_soul_status: { args: Record<string, never>; returns: SoulStatus };";
+            assert!(commands.contains(status));
+            assert!(commands.contains("import type { SoulStatus } from \"./SoulStatus\";"));
+            assert!(dir.join("SoulStatus.ts").is_file());
+            // Nested types come along
+            assert!(dir.join("WikiLink.ts").is_file());
+            assert!(fs::read_to_string(dir.join("events.ts"))
+                .unwrap()
+                .contains("export interface Events {"));
+            let _ = fs::remove_dir_all(&dir);
+        }
+    }
+}
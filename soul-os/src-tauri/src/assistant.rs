@@ -0,0 +1,236 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+use crate::blocking::run_blocking;
+use crate::founding::FoundingServer;
+use crate::http::{self, Http, Policy};
+use crate::status::StatusCache;
+use crate::tags::{self, TagIndex};
+use crate::vault::Vault;
+
+/// One summary or tag request is a single short LLM call
+const ASSISTANT: Policy = Policy {
+    name: "assistant",
+    timeout: Duration::from_secs(60),
+    retries: 1,
+    backoff: Duration::from_millis(500),
+    idempotent: true,
+};
+/// File text sent to the model; longer files are cut
+const MAX_CONTENT_CHARS: usize = 24_000;
+/// SEED.md text sent along as soul context
+const SEED_EXCERPT_CHARS: usize = 1_500;
+/// Existing tags offered to the model, most used first
+const KNOWN_TAGS: usize = 50;
+const MAX_SUGGESTIONS: usize = 8;
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct AssistantSummary {
+    pub path: String,
+    pub summary: String,
+    /// The file was longer than what the model saw
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct TagSuggestions {
+    pub path: String,
+    /// Normalized, not yet on the file; tags the soul already uses first
+    pub tags: Vec<String>,
+    /// Tags the file already carries
+    pub existing: Vec<String>,
+}
+
+/// The soul's name, a SEED.md excerpt and (with `vocabulary`) its most
+/// used tags, so answers fit this soul rather than a generic one. Returns
+/// the context and the tags.
+fn context(
+    app: &AppHandle,
+    soul_path: &Path,
+    vocabulary: bool,
+) -> (serde_json::Value, Vec<String>) {
+    let vault = app.state::<Arc<Vault>>();
+    let name = app
+        .state::<Arc<StatusCache>>()
+        .get(soul_path)
+        .map(|status| status.name)
+        .unwrap_or_default();
+    let seed = vault
+        .read(&soul_path.join("SEED.md"))
+        .map(|seed| seed.chars().take(SEED_EXCERPT_CHARS).collect::<String>())
+        .unwrap_or_default();
+    let known: Vec<String> = if vocabulary {
+        let mut counts = app.state::<Arc<TagIndex>>().list(soul_path, &vault);
+        counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+        counts.into_iter().take(KNOWN_TAGS).map(|c| c.tag).collect()
+    } else {
+        Vec::new()
+    };
+    let json = serde_json::json!({ "soul": name, "seed": seed, "tags": known });
+    (json, known)
+}
+
+/// `context` off the async runtime; it reads the soul.
+async fn gather(
+    app: &AppHandle,
+    soul_path: &Path,
+    vocabulary: bool,
+) -> Result<(serde_json::Value, Vec<String>), String> {
+    let handle = app.clone();
+    let soul_path = soul_path.to_path_buf();
+    run_blocking(app, "assistant_context", None, move |_| {
+        Ok(context(&handle, &soul_path, vocabulary))
+    })
+    .await
+}
+
+/// The helper service's port. The founding server doubles as the
+/// assistant, so it's started on first use when it isn't running.
+async fn ensure_running(app: &AppHandle, soul_path: &Path) -> Result<u16, String> {
+    let server = app.state::<Arc<FoundingServer>>().inner().clone();
+    if server.is_running() {
+        return Ok(server.port());
+    }
+    let handle = app.clone();
+    let soul_path = soul_path.to_path_buf();
+    run_blocking(app, "start_assistant", None, move |_| {
+        server.start(&handle, &soul_path)
+    })
+    .await
+}
+
+async fn post(
+    app: &AppHandle,
+    soul_path: &Path,
+    endpoint: &str,
+    body: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let port = ensure_running(app, soul_path).await?;
+    let url = format!("http://127.0.0.1:{}{}", port, endpoint);
+    let client = app.state::<Arc<Http>>().local(&url)?;
+    let resp = http::send(app, &ASSISTANT, || client.post(&url).json(&body))
+        .await
+        .map_err(|e| format!("Failed to reach the assistant: {}", e))?;
+    let ok = resp.status().is_success();
+    let json: serde_json::Value = resp
+        .json()
+        .await
+        .map_err(|e| format!("Invalid response from the assistant: {}", e))?;
+    if !ok {
+        let error = json["error"].as_str().unwrap_or("request failed");
+        return Err(format!("Assistant: {}", error));
+    }
+    Ok(json)
+}
+
+fn excerpt(content: &str) -> (String, bool) {
+    let mut chars = content.chars();
+    let text: String = chars.by_ref().take(MAX_CONTENT_CHARS).collect();
+    (text, chars.next().is_some())
+}
+
+/// Summarize `content` (the text of soul file `path`).
+pub async fn summarize(
+    app: &AppHandle,
+    soul_path: PathBuf,
+    path: String,
+    content: String,
+) -> Result<AssistantSummary, String> {
+    let (text, truncated) = excerpt(&content);
+    let (context, _) = gather(app, &soul_path, false).await?;
+    let body = serde_json::json!({
+        "path": path,
+        "content": text,
+        "context": context,
+    });
+    let json = post(app, &soul_path, "/assistant/summarize", body).await?;
+    let summary = json["summary"]
+        .as_str()
+        .ok_or("Invalid response from the assistant: no summary")?
+        .trim()
+        .to_string();
+    Ok(AssistantSummary {
+        path,
+        summary,
+        truncated,
+    })
+}
+
+/// Tags for `content` (the text of soul file `path`), preferring the
+/// soul's existing vocabulary.
+pub async fn suggest_tags(
+    app: &AppHandle,
+    soul_path: PathBuf,
+    path: String,
+    content: String,
+) -> Result<TagSuggestions, String> {
+    let existing: Vec<String> = tags::parse(&content).into_iter().collect();
+    let (context, known) = gather(app, &soul_path, true).await?;
+    let (text, _) = excerpt(&content);
+    let body = serde_json::json!({
+        "path": path,
+        "content": text,
+        "existing": existing,
+        "context": context,
+    });
+    let json = post(app, &soul_path, "/assistant/tags", body).await?;
+    let suggested = json["tags"]
+        .as_array()
+        .ok_or("Invalid response from the assistant: no tags")?;
+    let mut tags: Vec<String> = Vec::new();
+    for tag in suggested.iter().filter_map(|t| t.as_str()) {
+        let Some(tag) = tags::normalize(&tag.replace(char::is_whitespace, "-")) else {
+            continue;
+        };
+        if !existing.contains(&tag) && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    // Stable sort: known tags first, otherwise in the model's order
+    tags.sort_by_key(|tag| !known.contains(tag));
+    tags.truncate(MAX_SUGGESTIONS);
+    Ok(TagSuggestions {
+        path,
+        tags,
+        existing,
+    })
+}
+
+/// One condensed memory from several (`memories`: path and text), for the
+/// rollup of `period`.
+pub async fn rollup(
+    app: &AppHandle,
+    soul_path: PathBuf,
+    period: &str,
+    memories: Vec<(String, String)>,
+) -> Result<String, String> {
+    // Every memory gets an equal share of what the model sees
+    let share = MAX_CONTENT_CHARS / memories.len().max(1);
+    let memories: Vec<serde_json::Value> = memories
+        .into_iter()
+        .map(|(path, content)| {
+            let content: String = content.chars().take(share).collect();
+            serde_json::json!({ "path": path, "content": content })
+        })
+        .collect();
+    let (context, _) = gather(app, &soul_path, false).await?;
+    let body = serde_json::json!({
+        "period": period,
+        "memories": memories,
+        "context": context,
+    });
+    let json = post(app, &soul_path, "/assistant/rollup", body).await?;
+    let summary = json["summary"]
+        .as_str()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .ok_or("Invalid response from the assistant: no summary")?;
+    Ok(summary.to_string())
+}
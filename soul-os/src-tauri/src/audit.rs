@@ -0,0 +1,143 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+use crate::config::app_data_dir;
+
+/// Parameter names whose values never reach the audit log
+const SECRET_MARKERS: &[&str] = &["key", "token", "secret", "password", "auth"];
+/// Default number of entries returned by `get_audit_log`
+const DEFAULT_LIMIT: usize = 200;
+
+/// One mutating action as written to <app_data_dir>/audit.jsonl.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct AuditEntry {
+    #[ts(type = "number")]
+    pub timestamp: u64,
+    pub action: String,
+    pub params: serde_json::Value,
+    /// "ok" or "error"
+    pub outcome: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, TS)]
+#[ts(export)]
+pub struct AuditFilter {
+    #[serde(default)]
+    pub action: Option<String>,
+    #[serde(default)]
+    pub outcome: Option<String>,
+    /// Epoch milliseconds, inclusive
+    #[serde(default)]
+    #[ts(type = "number | null")]
+    pub since: Option<u64>,
+    #[serde(default)]
+    #[ts(type = "number | null")]
+    pub until: Option<u64>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// Append-only log of every mutating backend action.
+#[derive(Default)]
+pub struct AuditLog {
+    /// Serializes appends so concurrent commands don't interleave lines
+    write_lock: Mutex<()>,
+}
+
+fn audit_path() -> PathBuf {
+    app_data_dir().join("audit.jsonl")
+}
+
+/// Whether a parameter or variable name looks like it holds a secret.
+pub fn is_secret_name(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    SECRET_MARKERS.iter().any(|m| lower.contains(m))
+}
+
+/// Replace values of secret-looking keys, recursively.
+fn redact(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => map
+            .into_iter()
+            .map(|(k, v)| {
+                if is_secret_name(&k) {
+                    (k, serde_json::Value::String("[redacted]".to_string()))
+                } else {
+                    (k, redact(v))
+                }
+            })
+            .collect(),
+        serde_json::Value::Array(items) => items.into_iter().map(redact).collect(),
+        other => other,
+    }
+}
+
+impl AuditLog {
+    pub fn append(&self, entry: &AuditEntry) {
+        let Ok(line) = serde_json::to_string(entry) else {
+            return;
+        };
+        let path = audit_path();
+        let _guard = self.write_lock.lock();
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&path) {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+
+    /// Matching entries, newest first.
+    pub fn query(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>, String> {
+        let content = match fs::read_to_string(audit_path()) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.to_string()),
+        };
+        let limit = filter.limit.unwrap_or(DEFAULT_LIMIT);
+        Ok(content
+            .lines()
+            .rev()
+            .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok())
+            .filter(|e| filter.action.as_ref().is_none_or(|a| &e.action == a))
+            .filter(|e| filter.outcome.as_ref().is_none_or(|o| &e.outcome == o))
+            .filter(|e| filter.since.is_none_or(|s| e.timestamp >= s))
+            .filter(|e| filter.until.is_none_or(|u| e.timestamp <= u))
+            .take(limit)
+            .collect())
+    }
+}
+
+/// Record a mutating action with its (redacted) parameters and outcome,
+/// then hand the result back unchanged.
+pub fn audited<T>(
+    app: &AppHandle,
+    action: &str,
+    params: serde_json::Value,
+    result: Result<T, String>,
+) -> Result<T, String> {
+    if let Some(log) = app.try_state::<Arc<AuditLog>>() {
+        log.append(&AuditEntry {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            action: action.to_string(),
+            params: redact(params),
+            outcome: if result.is_ok() { "ok" } else { "error" }.to_string(),
+            error: result.as_ref().err().cloned(),
+        });
+    }
+    result
+}
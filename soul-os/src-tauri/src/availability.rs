@@ -0,0 +1,221 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::config::app_data_dir;
+
+const HOUR_MS: u64 = 60 * 60 * 1000;
+
+/// Why the engine went down.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum DownReason {
+    /// Stopped from the UI
+    UserStop,
+    /// The process exited on its own
+    Crash,
+    /// Killed and restarted by the watchdog because its API stopped answering
+    WatchdogRestart,
+    /// A start attempt failed
+    StartFailed,
+    /// SoulOS quit; the time until the next launch isn't counted
+    AppExit,
+}
+
+/// One up/down transition as written to <app_data_dir>/engine-availability.jsonl.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Transition {
+    timestamp: u64,
+    up: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reason: Option<DownReason>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct DowntimeIncident {
+    /// Unix ms
+    #[ts(type = "number")]
+    pub start: u64,
+    /// None while the engine is still down
+    #[ts(type = "number | null")]
+    pub end: Option<u64>,
+    #[ts(type = "number")]
+    pub duration_ms: u64,
+    pub reason: DownReason,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct EngineAvailability {
+    pub range: String,
+    /// Unix ms where the range starts
+    #[ts(type = "number")]
+    pub since: u64,
+    /// Share of tracked time the engine was up; None without any tracked time
+    pub uptime_percent: Option<f64>,
+    #[ts(type = "number")]
+    pub up_ms: u64,
+    #[ts(type = "number")]
+    pub down_ms: u64,
+    pub up: bool,
+    /// Newest first; app exits aren't incidents
+    pub incidents: Vec<DowntimeIncident>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn log_path() -> PathBuf {
+    app_data_dir().join("engine-availability.jsonl")
+}
+
+/// `range`: "24h", "7d", "30d", "90d", … or "all".
+fn range_start(range: &str, now: u64) -> Result<u64, String> {
+    if range == "all" {
+        return Ok(0);
+    }
+    let (count, unit) = range.split_at(range.len().saturating_sub(1));
+    let count: u64 = count
+        .parse()
+        .map_err(|_| format!("Invalid range: {}", range))?;
+    let unit_ms = match unit {
+        "h" => HOUR_MS,
+        "d" => 24 * HOUR_MS,
+        _ => return Err(format!("Invalid range: {}", range)),
+    };
+    Ok(now.saturating_sub(count.saturating_mul(unit_ms)))
+}
+
+/// Persistent record of engine up/down transitions.
+pub struct Availability {
+    transitions: Mutex<Vec<Transition>>,
+}
+
+impl Default for Availability {
+    /// Load the history. A run that ended without recording its exit
+    /// (killed, power loss) is closed at its last transition.
+    fn default() -> Self {
+        let mut transitions: Vec<Transition> = fs::read_to_string(log_path())
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        if let Some(last) = transitions.last() {
+            if last.reason != Some(DownReason::AppExit) {
+                let exit = Transition {
+                    timestamp: last.timestamp,
+                    up: false,
+                    reason: Some(DownReason::AppExit),
+                    detail: Some("unclean shutdown".to_string()),
+                };
+                append(&exit);
+                transitions.push(exit);
+            }
+        }
+        Self {
+            transitions: Mutex::new(transitions),
+        }
+    }
+}
+
+fn append(transition: &Transition) {
+    let Ok(line) = serde_json::to_string(transition) else {
+        return;
+    };
+    let path = log_path();
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&path) {
+        let _ = writeln!(file, "{}", line);
+    }
+}
+
+impl Availability {
+    fn record(&self, up: bool, reason: Option<DownReason>, detail: Option<String>) {
+        let mut transitions = self.transitions.lock();
+        // Only transitions count; an app exit while down still ends tracking
+        let is_exit = |r: Option<DownReason>| r == Some(DownReason::AppExit);
+        let repeated = transitions.last().is_some_and(|last| match up {
+            true => last.up,
+            false => !last.up && is_exit(last.reason) == is_exit(reason),
+        });
+        if repeated {
+            return;
+        }
+        let transition = Transition {
+            timestamp: now_ms(),
+            up,
+            reason,
+            detail,
+        };
+        append(&transition);
+        transitions.push(transition);
+    }
+
+    pub fn up(&self) {
+        self.record(true, None, None);
+    }
+
+    pub fn down(&self, reason: DownReason, detail: Option<String>) {
+        self.record(false, Some(reason), detail);
+    }
+
+    /// Uptime and downtime incidents within `range`.
+    pub fn report(&self, range: &str) -> Result<EngineAvailability, String> {
+        let now = now_ms();
+        let since = range_start(range, now)?;
+        let transitions = self.transitions.lock();
+
+        let (mut up_ms, mut down_ms) = (0u64, 0u64);
+        let mut incidents = Vec::new();
+        for (i, t) in transitions.iter().enumerate() {
+            let end = transitions.get(i + 1).map(|n| n.timestamp);
+            let (from, to) = (t.timestamp.max(since), end.unwrap_or(now).min(now));
+            if end.is_some_and(|e| e <= since) {
+                continue;
+            }
+            let overlap = to.saturating_sub(from);
+            match (t.up, t.reason) {
+                (true, _) => up_ms += overlap,
+                (false, Some(DownReason::AppExit)) => {}
+                (false, reason) => {
+                    down_ms += overlap;
+                    incidents.push(DowntimeIncident {
+                        start: t.timestamp,
+                        end,
+                        duration_ms: end.unwrap_or(now).saturating_sub(t.timestamp),
+                        reason: reason.unwrap_or(DownReason::Crash),
+                        detail: t.detail.clone(),
+                    });
+                }
+            }
+        }
+        incidents.reverse();
+
+        let tracked = up_ms + down_ms;
+        Ok(EngineAvailability {
+            range: range.to_string(),
+            since,
+            uptime_percent: (tracked > 0).then(|| up_ms as f64 * 100.0 / tracked as f64),
+            up_ms,
+            down_ms,
+            up: transitions.last().is_some_and(|t| t.up),
+            incidents,
+        })
+    }
+}
@@ -0,0 +1,154 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+use crate::blocking::{run_command, CancelToken};
+
+/// Filesystem access used by the command layer.
+pub trait FileStore: Send + Sync {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+    fn write(&self, path: &Path, data: &[u8]) -> io::Result<()>;
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+    fn exists(&self, path: &Path) -> bool;
+    /// The path itself is a symlink (not followed), dangling or not
+    fn is_symlink(&self, path: &Path) -> bool;
+    /// Entry names (not paths) of a directory
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<String>>;
+    /// Owner-only permissions for files holding secrets
+    fn restrict(&self, path: &Path);
+}
+
+/// What a finished subprocess left behind.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessOutput {
+    pub success: bool,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+/// Runs subprocesses to completion (killed when the token is cancelled).
+pub trait ProcessLauncher: Send + Sync {
+    fn run(
+        &self,
+        program: &Path,
+        args: &[&str],
+        cwd: Option<&Path>,
+        token: &CancelToken,
+    ) -> Result<ProcessOutput, String>;
+}
+
+/// Git plumbing for the state history. Returns stdout, or stderr as the error.
+pub trait GitBackend: Send + Sync {
+    fn run(&self, repo: &Path, args: &[&str], token: &CancelToken) -> Result<String, String>;
+}
+
+/// The backends commands operate on — the real ones in the app,
+/// in-memory fakes in tests.
+pub struct Backends {
+    pub files: Arc<dyn FileStore>,
+    pub git: Arc<dyn GitBackend>,
+    pub processes: Arc<dyn ProcessLauncher>,
+}
+
+impl Backends {
+    pub fn system() -> Self {
+        let processes: Arc<dyn ProcessLauncher> = Arc::new(SystemLauncher);
+        Self {
+            files: Arc::new(OsFileStore),
+            git: Arc::new(CliGit {
+                launcher: processes.clone(),
+            }),
+            processes,
+        }
+    }
+}
+
+pub struct OsFileStore;
+
+impl FileStore for OsFileStore {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        fs::write(path, data)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        path.canonicalize()
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn is_symlink(&self, path: &Path) -> bool {
+        fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink())
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<String>> {
+        Ok(fs::read_dir(path)?
+            .flatten()
+            .filter_map(|e| e.file_name().into_string().ok())
+            .collect())
+    }
+
+    fn restrict(&self, path: &Path) {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = fs::set_permissions(path, fs::Permissions::from_mode(0o600));
+        }
+        #[cfg(not(unix))]
+        let _ = path;
+    }
+}
+
+pub struct SystemLauncher;
+
+impl ProcessLauncher for SystemLauncher {
+    fn run(
+        &self,
+        program: &Path,
+        args: &[&str],
+        cwd: Option<&Path>,
+        token: &CancelToken,
+    ) -> Result<ProcessOutput, String> {
+        let mut cmd = Command::new(program);
+        cmd.args(args);
+        if let Some(cwd) = cwd {
+            cmd.current_dir(cwd);
+        }
+        let output = run_command(&mut cmd, token)?;
+        Ok(ProcessOutput {
+            success: output.status.success(),
+            stdout: output.stdout,
+            stderr: output.stderr,
+        })
+    }
+}
+
+/// The `git` executable on PATH.
+pub struct CliGit {
+    launcher: Arc<dyn ProcessLauncher>,
+}
+
+impl GitBackend for CliGit {
+    fn run(&self, repo: &Path, args: &[&str], token: &CancelToken) -> Result<String, String> {
+        let output = self
+            .launcher
+            .run(Path::new("git"), args, Some(repo), token)
+            .map_err(|e| format!("git {} failed: {}", args.first().unwrap_or(&""), e))?;
+        if !output.success {
+            return Err(String::from_utf8_lossy(&output.stderr).to_string());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
@@ -0,0 +1,82 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use ts_rs::TS;
+
+use crate::watcher::{self, WatcherState};
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct BackgroundStatus {
+    pub suspended: bool,
+    /// Unix ms when background activity was suspended
+    #[ts(type = "number | null")]
+    pub since: Option<u64>,
+    /// Changed files waiting to be processed on resume
+    pub deferred_changes: usize,
+}
+
+/// Switch that quiets every background loop: tray animation, node ticker,
+/// file watcher, engine watchdog, monitor push and the schedulers. The
+/// auto-lock timer keeps running.
+#[derive(Default)]
+pub struct Background {
+    /// Unix ms of the suspend, 0 while running
+    suspended_since: AtomicU64,
+}
+
+impl Background {
+    pub fn is_suspended(&self) -> bool {
+        self.suspended_since.load(Ordering::Relaxed) != 0
+    }
+}
+
+/// Whether background work should be skipped right now.
+pub fn suspended(app: &AppHandle) -> bool {
+    app.try_state::<Arc<Background>>()
+        .is_some_and(|b| b.is_suspended())
+}
+
+pub fn status(app: &AppHandle) -> BackgroundStatus {
+    let since = app
+        .try_state::<Arc<Background>>()
+        .map_or(0, |b| b.suspended_since.load(Ordering::Relaxed));
+    BackgroundStatus {
+        suspended: since != 0,
+        since: (since != 0).then_some(since),
+        deferred_changes: app
+            .try_state::<WatcherState>()
+            .map_or(0, |w| w.deferred_count()),
+    }
+}
+
+/// Suspend or resume. On resume, file changes seen while suspended are
+/// processed once (latest state per file). Emits `background:changed`.
+pub fn set_suspended(app: &AppHandle, suspend: bool) -> BackgroundStatus {
+    let Some(background) = app.try_state::<Arc<Background>>() else {
+        return status(app);
+    };
+    let changed = if suspend {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        background
+            .suspended_since
+            .compare_exchange(0, now, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+    } else {
+        background.suspended_since.swap(0, Ordering::Relaxed) != 0
+    };
+    if changed && !suspend {
+        watcher::replay_deferred(app);
+    }
+    let status = status(app);
+    if changed {
+        let _ = app.emit("background:changed", &status);
+    }
+    status
+}
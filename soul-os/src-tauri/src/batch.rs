@@ -0,0 +1,692 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::backend::{GitBackend, OsFileStore};
+use crate::blocking::CancelToken;
+use crate::config::app_data_dir;
+use crate::journal::{Journal, JournalGuard, JournalOp};
+use crate::protected;
+use crate::safe_path;
+use crate::vault::Vault;
+
+/// Operations per batch
+const MAX_OPS: usize = 1000;
+const MANIFEST: &str = "manifest.json";
+
+/// One file operation of a batch. Paths are relative to the soul.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchOp {
+    Write {
+        path: String,
+        content: String,
+    },
+    Move {
+        from: String,
+        to: String,
+    },
+    /// Files only; a directory can't be deleted in a batch
+    Delete {
+        path: String,
+    },
+    Mkdir {
+        path: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct BatchReport {
+    pub applied: usize,
+    /// Files written, moved (both ends) or deleted
+    pub changed: Vec<String>,
+    /// The batch's git commit, if the soul is a repository and anything
+    /// changed
+    pub commit: Option<String>,
+}
+
+/// A file as it was before the batch; `saved` names its copy in the
+/// snapshot directory, None if it didn't exist.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Original {
+    path: String,
+    saved: Option<String>,
+}
+
+/// What a rollback needs, written before the first change so an
+/// interrupted batch can be undone at the next start.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Manifest {
+    soul_path: PathBuf,
+    originals: Vec<Original>,
+    /// Directories the batch may create, removed again if left empty
+    dirs: Vec<String>,
+}
+
+/// Where `path` really is, relative to the soul with `/` separators, or
+/// why it's refused: resolved by `safe_path::resolve_name`, and never in
+/// `.git`, the soul's `.env` or a protected file.
+fn clean(soul_path: &Path, path: &str) -> Result<String, String> {
+    let clean = safe_path::resolve_name(&OsFileStore, soul_path, path)?;
+    if clean.is_empty() || clean == ".git" || clean.starts_with(".git/") {
+        return Err(format!("Access denied: {}", path));
+    }
+    // Secrets only change through write_env, behind elevation, and never
+    // end up in the batch's commit
+    if clean.eq_ignore_ascii_case(".env") {
+        return Err("Access denied: .env is written with write_env".to_string());
+    }
+    if protected::is_protected(soul_path, &clean) {
+        return Err(format!(
+            "{} is protected; write it with a confirmation instead",
+            clean
+        ));
+    }
+    Ok(clean)
+}
+
+/// Check every operation against the files as the batch would leave them,
+/// before anything is touched. Returns the operations with clean paths.
+fn validate(soul_path: &Path, ops: &[BatchOp]) -> Result<Vec<BatchOp>, String> {
+    if ops.is_empty() {
+        return Err("Empty batch".to_string());
+    }
+    if ops.len() > MAX_OPS {
+        return Err(format!("A batch holds at most {} operations", MAX_OPS));
+    }
+    // Files and directories the batch has created (true) or removed (false)
+    let mut files: HashMap<String, bool> = HashMap::new();
+    let mut dirs: HashMap<String, bool> = HashMap::new();
+    let is_file = |files: &HashMap<String, bool>, path: &str| {
+        files
+            .get(path)
+            .copied()
+            .unwrap_or_else(|| soul_path.join(path).is_file())
+    };
+    let is_dir = |dirs: &HashMap<String, bool>, path: &str| {
+        dirs.get(path)
+            .copied()
+            .unwrap_or_else(|| soul_path.join(path).is_dir())
+    };
+    let mut cleaned = Vec::new();
+    for (i, op) in ops.iter().enumerate() {
+        let fail = |e: String| format!("Operation {}: {}", i + 1, e);
+        let op = match op {
+            BatchOp::Write { path, content } => {
+                let path = clean(soul_path, path).map_err(fail)?;
+                if is_dir(&dirs, &path) {
+                    return Err(fail(format!("{} is a directory", path)));
+                }
+                files.insert(path.clone(), true);
+                BatchOp::Write {
+                    path,
+                    content: content.clone(),
+                }
+            }
+            BatchOp::Move { from, to } => {
+                let from = clean(soul_path, from).map_err(fail)?;
+                let to = clean(soul_path, to).map_err(fail)?;
+                if !is_file(&files, &from) {
+                    return Err(fail(format!("{} doesn't exist", from)));
+                }
+                if is_file(&files, &to) || is_dir(&dirs, &to) {
+                    return Err(fail(format!("{} already exists", to)));
+                }
+                files.insert(from.clone(), false);
+                files.insert(to.clone(), true);
+                BatchOp::Move { from, to }
+            }
+            BatchOp::Delete { path } => {
+                let path = clean(soul_path, path).map_err(fail)?;
+                if !is_file(&files, &path) {
+                    return Err(fail(format!("{} is not a file", path)));
+                }
+                files.insert(path.clone(), false);
+                BatchOp::Delete { path }
+            }
+            BatchOp::Mkdir { path } => {
+                let path = clean(soul_path, path).map_err(fail)?;
+                if is_file(&files, &path) {
+                    return Err(fail(format!("{} is a file", path)));
+                }
+                dirs.insert(path.clone(), true);
+                BatchOp::Mkdir { path }
+            }
+        };
+        cleaned.push(op);
+    }
+    Ok(cleaned)
+}
+
+/// Files an operation changes
+fn touched(op: &BatchOp) -> Vec<&str> {
+    match op {
+        BatchOp::Write { path, .. } | BatchOp::Delete { path } => vec![path],
+        BatchOp::Move { from, to } => vec![from, to],
+        BatchOp::Mkdir { .. } => Vec::new(),
+    }
+}
+
+/// Directories that don't exist yet and an operation may create,
+/// deepest last.
+fn missing_dirs(soul_path: &Path, ops: &[BatchOp]) -> Vec<String> {
+    let mut dirs = BTreeSet::new();
+    for op in ops {
+        let mut targets: Vec<&str> = match op {
+            BatchOp::Mkdir { path } => vec![path],
+            _ => Vec::new(),
+        };
+        targets.extend(
+            touched(op)
+                .into_iter()
+                .filter_map(|p| p.rsplit_once('/').map(|(dir, _)| dir)),
+        );
+        for target in targets {
+            let mut prefix = String::new();
+            for part in target.split('/') {
+                if !prefix.is_empty() {
+                    prefix.push('/');
+                }
+                prefix.push_str(part);
+                if !soul_path.join(&prefix).exists() {
+                    dirs.insert(prefix.clone());
+                }
+            }
+        }
+    }
+    let mut dirs: Vec<String> = dirs.into_iter().collect();
+    dirs.sort_by_key(|d| d.matches('/').count());
+    dirs
+}
+
+fn snapshot_root() -> PathBuf {
+    app_data_dir().join("batches")
+}
+
+/// Copy every file the batch touches into a fresh snapshot directory
+/// under `root`.
+fn snapshot(root: &Path, soul_path: &Path, ops: &[BatchOp]) -> Result<(PathBuf, Manifest), String> {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let dir = root.join(stamp.to_string());
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let mut originals: Vec<Original> = Vec::new();
+    for path in ops.iter().flat_map(touched) {
+        if originals.iter().any(|o| o.path == path) {
+            continue;
+        }
+        let file = soul_path.join(path);
+        let saved = if file.is_file() {
+            let name = originals.len().to_string();
+            fs::copy(&file, dir.join(&name)).map_err(|e| e.to_string())?;
+            Some(name)
+        } else {
+            None
+        };
+        originals.push(Original {
+            path: path.to_string(),
+            saved,
+        });
+    }
+    let manifest = Manifest {
+        soul_path: soul_path.to_path_buf(),
+        originals,
+        dirs: missing_dirs(soul_path, ops),
+    };
+    let json = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+    fs::write(dir.join(MANIFEST), json).map_err(|e| e.to_string())?;
+    Ok((dir, manifest))
+}
+
+/// Put every touched file back as it was and remove directories the batch
+/// created. Returns the files that couldn't be restored.
+fn restore(dir: &Path, manifest: &Manifest) -> Vec<String> {
+    let mut failed = Vec::new();
+    for original in manifest.originals.iter().rev() {
+        let file = manifest.soul_path.join(&original.path);
+        let result = match &original.saved {
+            Some(saved) => file
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|()| fs::copy(dir.join(saved), &file).map(|_| ())),
+            None if file.is_file() => fs::remove_file(&file),
+            None => Ok(()),
+        };
+        if result.is_err() {
+            failed.push(original.path.clone());
+        }
+    }
+    for created in manifest.dirs.iter().rev() {
+        // Only succeeds for empty directories
+        let _ = fs::remove_dir(manifest.soul_path.join(created));
+    }
+    failed
+}
+
+/// Text or bytes to write for `to` when moving `from` across the boundary
+/// of an encrypted directory.
+fn reseal(vault: &Vault, from: &Path, to: &str) -> Result<Vec<u8>, String> {
+    let plain = vault.decode_bytes(fs::read(from).map_err(|e| e.to_string())?)?;
+    match String::from_utf8(plain) {
+        Ok(text) => vault.seal(to, &text),
+        // Binary files aren't encrypted
+        Err(e) => Ok(e.into_bytes()),
+    }
+}
+
+fn apply_op(soul_path: &Path, vault: &Vault, op: &BatchOp) -> Result<(), String> {
+    let create_parent = |path: &Path| match path.parent() {
+        Some(parent) => fs::create_dir_all(parent).map_err(|e| e.to_string()),
+        None => Ok(()),
+    };
+    let resolve = |path: &str| safe_path::safe_resolve(&OsFileStore, soul_path, path);
+    match op {
+        BatchOp::Write { path, content } => {
+            let file = resolve(path)?;
+            create_parent(&file)?;
+            fs::write(&file, vault.seal(path, content)?).map_err(|e| e.to_string())
+        }
+        BatchOp::Move { from, to } => {
+            let (source, target) = (resolve(from)?, resolve(to)?);
+            create_parent(&target)?;
+            if vault.covers(from) == vault.covers(to) {
+                fs::rename(&source, &target).map_err(|e| e.to_string())
+            } else {
+                fs::write(&target, reseal(vault, &source, to)?).map_err(|e| e.to_string())?;
+                fs::remove_file(&source).map_err(|e| e.to_string())
+            }
+        }
+        BatchOp::Delete { path } => fs::remove_file(resolve(path)?).map_err(|e| e.to_string()),
+        BatchOp::Mkdir { path } => fs::create_dir_all(resolve(path)?).map_err(|e| e.to_string()),
+    }
+}
+
+fn commit(
+    soul_path: &Path,
+    git: &dyn GitBackend,
+    changed: &[String],
+    token: &CancelToken,
+) -> Result<Option<String>, String> {
+    if !soul_path.join(".git").exists() || changed.is_empty() {
+        return Ok(None);
+    }
+    let pathspecs: Vec<&str> = changed.iter().map(String::as_str).collect();
+    let mut args = vec!["add", "-A", "--"];
+    args.extend(&pathspecs);
+    git.run(soul_path, &args, token)?;
+    let mut args = vec!["status", "--porcelain", "--"];
+    args.extend(&pathspecs);
+    if git.run(soul_path, &args, token)?.trim().is_empty() {
+        return Ok(None);
+    }
+    let message = match changed.len() {
+        1 => format!("Update {}", changed[0]),
+        n => format!("Update {} soul files", n),
+    };
+    let mut args = vec!["commit", "-m", &message, "--"];
+    args.extend(&pathspecs);
+    if let Err(e) = git.run(soul_path, &args, token) {
+        let mut args = vec!["reset", "-q", "--"];
+        args.extend(&pathspecs);
+        let _ = git.run(soul_path, &args, token);
+        return Err(e);
+    }
+    Ok(Some(
+        git.run(soul_path, &["rev-parse", "HEAD"], token)?
+            .trim()
+            .to_string(),
+    ))
+}
+
+/// Apply `ops` as a unit: all paths are validated first, the touched files
+/// snapshotted and journaled, then every operation runs and the changes are committed to
+/// git once. Any failure restores the snapshot. `accept` is told about
+/// every file content the app sets, restores included (None: deleted).
+pub fn apply(
+    soul_path: &Path,
+    ops: &[BatchOp],
+    vault: &Vault,
+    git: &dyn GitBackend,
+    accept: &dyn Fn(&str, Option<&str>),
+    journal: &Journal,
+    token: &CancelToken,
+) -> Result<BatchReport, String> {
+    let ops = validate(soul_path, ops)?;
+    let (dir, manifest) = snapshot(&snapshot_root(), soul_path, &ops)?;
+    let finish = |entry: Option<JournalGuard>, result: Result<BatchReport, String>| {
+        if result.is_err() {
+            let failed = restore(&dir, &manifest);
+            for original in &manifest.originals {
+                let content = original
+                    .saved
+                    .as_ref()
+                    .and_then(|_| vault.read(&soul_path.join(&original.path)).ok());
+                accept(&original.path, content.as_deref());
+            }
+            if !failed.is_empty() {
+                // The snapshot is the only copy of those originals now: keep
+                // it, and the journal entry so the next start retries
+                if let Some(entry) = entry {
+                    entry.keep();
+                }
+                return result.map_err(|e| {
+                    format!(
+                        "{}; could not restore {}, the originals are kept in {}",
+                        e,
+                        failed.join(", "),
+                        dir.display()
+                    )
+                });
+            }
+        }
+        let _ = fs::remove_dir_all(&dir);
+        drop(entry);
+        result
+    };
+    // Recovery restores the snapshot if the app dies before the batch ends
+    let entry = match journal.begin(JournalOp::Batch {
+        snapshot: dir.to_string_lossy().to_string(),
+    }) {
+        Ok(entry) => Some(entry),
+        Err(e) => return finish(None, Err(e)),
+    };
+
+    let mut changed: Vec<String> = Vec::new();
+    for (i, op) in ops.iter().enumerate() {
+        if let Err(e) = token.check() {
+            return finish(entry, Err(e));
+        }
+        match op {
+            BatchOp::Write { path, content } => accept(path, Some(content)),
+            BatchOp::Delete { path } => accept(path, None),
+            BatchOp::Move { from, to } => {
+                let content = vault.read(&soul_path.join(from)).ok();
+                accept(from, None);
+                accept(to, content.as_deref());
+            }
+            BatchOp::Mkdir { .. } => {}
+        }
+        if let Err(e) = apply_op(soul_path, vault, op) {
+            return finish(entry, Err(format!("Operation {}: {}", i + 1, e)));
+        }
+        for path in touched(op) {
+            if !changed.iter().any(|c| c == path) {
+                changed.push(path.to_string());
+            }
+        }
+    }
+    let commit = match commit(soul_path, git, &changed, token) {
+        Ok(commit) => commit,
+        Err(e) => return finish(entry, Err(format!("Commit failed: {}", e.trim()))),
+    };
+    finish(
+        entry,
+        Ok(BatchReport {
+            applied: ops.len(),
+            changed,
+            commit,
+        }),
+    )
+}
+
+/// Undo a batch a crash interrupted, from its snapshot directory. The
+/// directory is kept if any file couldn't be restored.
+pub fn recover(dir: &Path) -> (String, bool) {
+    let manifest = fs::read(dir.join(MANIFEST))
+        .ok()
+        .and_then(|data| serde_json::from_slice::<Manifest>(&data).ok());
+    let Some(manifest) = manifest else {
+        let _ = fs::remove_dir_all(dir);
+        return (
+            "Interrupted batch had not changed anything".to_string(),
+            true,
+        );
+    };
+    let failed = restore(dir, &manifest);
+    if failed.is_empty() {
+        let _ = fs::remove_dir_all(dir);
+        (
+            format!(
+                "Rolled back interrupted batch of {} files",
+                manifest.originals.len()
+            ),
+            true,
+        )
+    } else {
+        (
+            format!(
+                "Rolling back batch failed for {}; the originals are kept in {}",
+                failed.join(", "),
+                dir.display()
+            ),
+            false,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::EncryptionConfig;
+
+    /// A soul and a snapshot root in a fresh temp directory
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("soulos-batch-{}-{}", std::process::id(), name));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(dir.join("soul/memories")).unwrap();
+            fs::create_dir_all(dir.join("batches")).unwrap();
+            fs::write(dir.join("soul/SEED.md"), "# Seed\n").unwrap();
+            fs::write(dir.join("soul/memories/a.md"), "a").unwrap();
+            Self(dir)
+        }
+
+        fn soul(&self) -> PathBuf {
+            self.0.join("soul")
+        }
+
+        fn read(&self, name: &str) -> Option<String> {
+            fs::read_to_string(self.soul().join(name)).ok()
+        }
+
+        /// Snapshot and apply `ops` like `apply` does, without git or the
+        /// journal
+        fn run(&self, ops: &[BatchOp]) -> PathBuf {
+            let vault = Vault::new(EncryptionConfig::default());
+            let ops = validate(&self.soul(), ops).unwrap();
+            let (dir, _) = snapshot(&self.0.join("batches"), &self.soul(), &ops).unwrap();
+            for op in &ops {
+                apply_op(&self.soul(), &vault, op).unwrap();
+            }
+            dir
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn write(path: &str, content: &str) -> BatchOp {
+        BatchOp::Write {
+            path: path.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    fn moved(from: &str, to: &str) -> BatchOp {
+        BatchOp::Move {
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    }
+
+    fn delete(path: &str) -> BatchOp {
+        BatchOp::Delete {
+            path: path.to_string(),
+        }
+    }
+
+    fn mkdir(path: &str) -> BatchOp {
+        BatchOp::Mkdir {
+            path: path.to_string(),
+        }
+    }
+
+    #[test]
+    fn validate_refuses_bad_batches() {
+        let scratch = Scratch::new("validate");
+        let sp = scratch.soul();
+        assert!(validate(&sp, &[]).is_err());
+        for op in [
+            write("../outside.md", "x"),
+            write("/etc/passwd", "x"),
+            write(".git/config", "x"),
+            write("", "x"),
+            moved("missing.md", "new.md"),
+            moved("SEED.md", "memories/a.md"),
+            moved("SEED.md", "memories"),
+            delete("memories"),
+            delete("missing.md"),
+            write("memories", "x"),
+            mkdir("SEED.md"),
+        ] {
+            assert!(
+                validate(&sp, std::slice::from_ref(&op)).is_err(),
+                "{:?}",
+                op
+            );
+        }
+        // The failing operation is named
+        let err = validate(&sp, &[write("b.md", "b"), delete("missing.md")]).unwrap_err();
+        assert!(err.starts_with("Operation 2:"), "{}", err);
+    }
+
+    #[test]
+    fn validate_refuses_the_env_file() {
+        let scratch = Scratch::new("env");
+        let sp = scratch.soul();
+        fs::write(sp.join(".env"), "KEY=secret\n").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(".env", sp.join("alias.md")).unwrap();
+        for op in [
+            write(".env", "KEY=x"),
+            write("./.ENV", "KEY=x"),
+            moved(".env", "memories/env.md"),
+            moved("memories/a.md", ".env"),
+            delete(".env"),
+            #[cfg(unix)]
+            write("alias.md", "KEY=x"),
+        ] {
+            let err = validate(&sp, std::slice::from_ref(&op)).unwrap_err();
+            assert!(
+                err.contains(".env is written with write_env"),
+                "{:?}: {}",
+                op,
+                err
+            );
+        }
+        // Only the soul's own
+        assert!(validate(&sp, &[write("memories/.env", "x")]).is_ok());
+    }
+
+    #[test]
+    fn validate_follows_the_batch_state() {
+        let scratch = Scratch::new("state");
+        let sp = scratch.soul();
+        let ops = validate(
+            &sp,
+            &[
+                write("./new\\b.md", "b"),
+                moved("new/b.md", "new/c.md"),
+                delete("new/c.md"),
+                mkdir("drafts"),
+                moved("SEED.md", "drafts/SEED.md"),
+            ],
+        )
+        .unwrap();
+        assert!(matches!(&ops[0], BatchOp::Write { path, .. } if path == "new/b.md"));
+        // Deleted or moved away earlier in the batch
+        assert!(validate(&sp, &[delete("SEED.md"), delete("SEED.md")]).is_err());
+        assert!(validate(&sp, &[moved("SEED.md", "b.md"), moved("SEED.md", "c.md")]).is_err());
+        // A file the batch wrote can't become a directory
+        assert!(validate(&sp, &[write("b.md", "b"), mkdir("b.md")]).is_err());
+    }
+
+    #[test]
+    fn restore_undoes_every_operation() {
+        let scratch = Scratch::new("restore");
+        let dir = scratch.run(&[
+            write("SEED.md", "# Changed\n"),
+            delete("memories/a.md"),
+            write("new/deep/b.md", "b"),
+            mkdir("drafts/empty"),
+        ]);
+        assert_eq!(scratch.read("SEED.md").as_deref(), Some("# Changed\n"));
+        assert_eq!(scratch.read("memories/a.md"), None);
+
+        let manifest: Manifest =
+            serde_json::from_slice(&fs::read(dir.join(MANIFEST)).unwrap()).unwrap();
+        assert!(restore(&dir, &manifest).is_empty());
+        assert_eq!(scratch.read("SEED.md").as_deref(), Some("# Seed\n"));
+        assert_eq!(scratch.read("memories/a.md").as_deref(), Some("a"));
+        assert!(!scratch.soul().join("new").exists());
+        assert!(!scratch.soul().join("drafts").exists());
+    }
+
+    #[test]
+    fn recover_rolls_back_and_removes_the_snapshot() {
+        let scratch = Scratch::new("recover");
+        let dir = scratch.run(&[moved("memories/a.md", "archive/a.md")]);
+        assert_eq!(scratch.read("archive/a.md").as_deref(), Some("a"));
+
+        let (_, ok) = recover(&dir);
+        assert!(ok);
+        assert_eq!(scratch.read("memories/a.md").as_deref(), Some("a"));
+        assert!(!scratch.soul().join("archive").exists());
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn recover_without_a_manifest_changes_nothing() {
+        let scratch = Scratch::new("no-manifest");
+        let dir = scratch.0.join("batches/1");
+        fs::create_dir_all(&dir).unwrap();
+        let (_, ok) = recover(&dir);
+        assert!(ok);
+        assert!(!dir.exists());
+        assert_eq!(scratch.read("SEED.md").as_deref(), Some("# Seed\n"));
+    }
+
+    #[test]
+    fn recover_keeps_the_snapshot_when_a_file_cant_be_restored() {
+        let scratch = Scratch::new("recover-fails");
+        let dir = scratch.run(&[write("SEED.md", "# Changed\n")]);
+        // Something put a directory where the original has to go back
+        fs::remove_file(scratch.soul().join("SEED.md")).unwrap();
+        fs::create_dir_all(scratch.soul().join("SEED.md/inner")).unwrap();
+
+        let (action, ok) = recover(&dir);
+        assert!(!ok);
+        assert!(action.contains("SEED.md"), "{}", action);
+        assert!(action.contains(&dir.display().to_string()), "{}", action);
+        assert!(dir.join(MANIFEST).exists());
+        assert_eq!(
+            fs::read_to_string(dir.join("0")).unwrap(),
+            "# Seed\n",
+            "the snapshot still holds the original"
+        );
+    }
+}
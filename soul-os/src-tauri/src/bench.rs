@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::blocking::CancelToken;
+
+/// Temp subdirectories of soul_path used for synthetic churn
+pub const BENCH_PREFIX: &str = ".soul-bench-";
+const MAX_FILES: usize = 5000;
+/// Gap between synthetic writes, so the run looks like churn rather than one burst
+const WRITE_SPACING: Duration = Duration::from_millis(2);
+/// How long to wait for stragglers after the last write
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct BenchmarkResult {
+    pub files: usize,
+    pub received: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+    #[ts(type = "number")]
+    pub total_ms: u64,
+}
+
+/// Measures fs change → emitted event latency through the real watcher.
+#[derive(Default)]
+pub struct PipelineBench {
+    running: AtomicBool,
+    /// Relative path → when it was written
+    pending: Mutex<HashMap<String, Instant>>,
+    latencies: Mutex<Vec<Duration>>,
+}
+
+fn percentile(sorted: &[Duration], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let idx = ((sorted.len() as f64 * p).ceil() as usize).clamp(1, sorted.len()) - 1;
+    sorted[idx].as_secs_f64() * 1000.0
+}
+
+impl PipelineBench {
+    /// Called by the watcher right after it emitted `bench:event`.
+    pub fn observe(&self, relative: &str) {
+        let relative = relative.replace('\\', "/");
+        if let Some(written) = self.pending.lock().remove(&relative) {
+            self.latencies.lock().push(written.elapsed());
+        }
+    }
+
+    /// Write `n_files` files into a temp subdir and wait for the watcher to
+    /// report each of them. The subdir is removed afterwards.
+    pub fn run(
+        &self,
+        soul_path: &Path,
+        n_files: usize,
+        token: &CancelToken,
+    ) -> Result<BenchmarkResult, String> {
+        if n_files == 0 || n_files > MAX_FILES {
+            return Err(format!("n_files must be between 1 and {}", MAX_FILES));
+        }
+        if self.running.swap(true, Ordering::SeqCst) {
+            return Err("A benchmark is already running".to_string());
+        }
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let dir_name = format!("{}{}", BENCH_PREFIX, stamp);
+        let dir = soul_path.join(&dir_name);
+        let result = self.churn(&dir, &dir_name, n_files, token);
+
+        let _ = fs::remove_dir_all(&dir);
+        self.pending.lock().clear();
+        self.running.store(false, Ordering::SeqCst);
+        result
+    }
+
+    fn churn(
+        &self,
+        dir: &Path,
+        dir_name: &str,
+        n_files: usize,
+        token: &CancelToken,
+    ) -> Result<BenchmarkResult, String> {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        self.latencies.lock().clear();
+        let started = Instant::now();
+
+        for i in 0..n_files {
+            token.check()?;
+            token.set_progress(i as u64, n_files as u64);
+            let name = format!("file-{:05}.md", i);
+            self.pending
+                .lock()
+                .insert(format!("{}/{}", dir_name, name), Instant::now());
+            fs::write(dir.join(&name), format!("# Benchmark {}\n", i))
+                .map_err(|e| e.to_string())?;
+            std::thread::sleep(WRITE_SPACING);
+        }
+
+        let deadline = Instant::now() + SETTLE_TIMEOUT;
+        while !self.pending.lock().is_empty() && Instant::now() < deadline {
+            token.check()?;
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        let mut latencies = std::mem::take(&mut *self.latencies.lock());
+        latencies.sort();
+        Ok(BenchmarkResult {
+            files: n_files,
+            received: latencies.len(),
+            p50_ms: percentile(&latencies, 0.50),
+            p95_ms: percentile(&latencies, 0.95),
+            p99_ms: percentile(&latencies, 0.99),
+            max_ms: latencies
+                .last()
+                .map(|d| d.as_secs_f64() * 1000.0)
+                .unwrap_or(0.0),
+            total_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+}
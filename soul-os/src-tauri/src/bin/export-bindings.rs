@@ -0,0 +1,12 @@
+//! Writes the frontend's TypeScript bindings (`npm run bindings`).
+
+use std::path::PathBuf;
+
+fn main() {
+    let dir =
+        PathBuf::from(std::env::var("TS_RS_EXPORT_DIR").unwrap_or_else(|_| "./bindings".into()));
+    if let Err(e) = soul_os_lib::export_bindings(&dir) {
+        eprintln!("Exporting bindings to {} failed: {}", dir.display(), e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use parking_lot::Mutex;
+use serde::Serialize;
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+use crate::backend::FileStore;
+use crate::config::app_data_dir;
+use crate::safe_path;
+use crate::vault::{self, Vault};
+
+/// URI scheme the webview loads shared files from:
+/// `convertFileSrc(token, "soul-blob")` in the frontend.
+pub const SCHEME: &str = "soul-blob";
+/// Unclaimed blobs are deleted after this
+const TTL: Duration = Duration::from_secs(5 * 60);
+
+const MIME_TYPES: &[(&str, &str)] = &[
+    ("md", "text/markdown; charset=utf-8"),
+    ("txt", "text/plain; charset=utf-8"),
+    ("json", "application/json"),
+    ("jsonl", "application/x-ndjson"),
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("svg", "image/svg+xml"),
+    ("mp3", "audio/mpeg"),
+    ("wav", "audio/wav"),
+    ("ogg", "audio/ogg"),
+    ("mp4", "video/mp4"),
+    ("webm", "video/webm"),
+    ("pdf", "application/pdf"),
+];
+
+/// A file handed to the webview out of band. Fetch it once from
+/// `soul-blob://<token>` before it expires.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct BlobHandle {
+    pub token: String,
+    pub mime: String,
+    #[ts(type = "number")]
+    pub size: u64,
+    #[ts(type = "number")]
+    pub expires_in_secs: u64,
+}
+
+struct Pending {
+    path: PathBuf,
+    mime: &'static str,
+    expires: Instant,
+}
+
+/// Per-request temp files for content too big for JSON IPC. Each one is
+/// served once through `SCHEME` and deleted.
+#[derive(Default)]
+pub struct Blobs {
+    pending: Mutex<HashMap<String, Pending>>,
+}
+
+/// <app_data_dir>/blobs — private to the user, since blobs can hold
+/// decrypted soul files.
+fn blob_dir() -> PathBuf {
+    app_data_dir().join("blobs")
+}
+
+/// Drop blobs a previous run left behind.
+pub fn clear() {
+    let _ = fs::remove_dir_all(blob_dir());
+}
+
+fn mime_type(name: &str) -> &'static str {
+    let ext = Path::new(name)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    MIME_TYPES
+        .iter()
+        .find(|(e, _)| *e == ext)
+        .map_or("application/octet-stream", |(_, mime)| mime)
+}
+
+fn new_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn create_dir(dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(dir, fs::Permissions::from_mode(0o700));
+    }
+    Ok(())
+}
+
+impl Blobs {
+    /// Write a temp file through `write` and register a token for it.
+    fn share(
+        &self,
+        mime: &'static str,
+        write: impl FnOnce(&Path) -> Result<(), String>,
+    ) -> Result<BlobHandle, String> {
+        self.purge();
+        let dir = blob_dir();
+        create_dir(&dir)?;
+        let token = new_token();
+        let path = dir.join(&token);
+        if let Err(e) = write(&path) {
+            let _ = fs::remove_file(&path);
+            return Err(e);
+        }
+        let size = fs::metadata(&path).map_err(|e| e.to_string())?.len();
+        self.pending.lock().insert(
+            token.clone(),
+            Pending {
+                path,
+                mime,
+                expires: Instant::now() + TTL,
+            },
+        );
+        Ok(BlobHandle {
+            token,
+            mime: mime.to_string(),
+            size,
+            expires_in_secs: TTL.as_secs(),
+        })
+    }
+
+    /// Share a soul file (decrypted if needed) by copying it to a blob.
+    pub fn share_soul_file(
+        &self,
+        files: &dyn FileStore,
+        vault: &Vault,
+        soul_path: &Path,
+        name: &str,
+    ) -> Result<BlobHandle, String> {
+        let canonical = safe_path::safe_resolve(files, soul_path, name)?;
+
+        let mut header = Vec::new();
+        File::open(&canonical)
+            .and_then(|f| f.take(16).read_to_end(&mut header))
+            .map_err(|e| e.to_string())?;
+        self.share(mime_type(name), |target| {
+            if vault::is_encrypted(&header) {
+                let plain =
+                    vault.decode_bytes(files.read(&canonical).map_err(|e| e.to_string())?)?;
+                fs::write(target, plain).map_err(|e| e.to_string())
+            } else {
+                fs::copy(&canonical, target)
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            }
+        })
+    }
+
+    /// Claim a token (once).
+    fn take(&self, token: &str) -> Option<Pending> {
+        let pending = self.pending.lock().remove(token)?;
+        if pending.expires <= Instant::now() {
+            let _ = fs::remove_file(&pending.path);
+            return None;
+        }
+        Some(pending)
+    }
+
+    /// Delete every blob, fetched or not (the app locked).
+    pub fn clear_all(&self) {
+        let mut pending = self.pending.lock();
+        pending.clear();
+        clear();
+    }
+
+    /// Delete expired blobs.
+    fn purge(&self) {
+        let now = Instant::now();
+        self.pending.lock().retain(|_, p| {
+            let keep = p.expires > now;
+            if !keep {
+                let _ = fs::remove_file(&p.path);
+            }
+            keep
+        });
+    }
+}
+
+fn status(code: StatusCode) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(code)
+        .body(Vec::new())
+        .expect("static response")
+}
+
+/// Handler for `SCHEME` requests: serves the blob for the token in the path
+/// and deletes it.
+pub fn serve(app: &AppHandle, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let Some(blobs) = app.try_state::<Arc<Blobs>>() else {
+        return status(StatusCode::SERVICE_UNAVAILABLE);
+    };
+    let token = request.uri().path().trim_matches('/');
+    let Some(pending) = blobs.take(token) else {
+        return status(StatusCode::NOT_FOUND);
+    };
+    let body = fs::read(&pending.path);
+    let _ = fs::remove_file(&pending.path);
+    match body {
+        Ok(body) => Response::builder()
+            .header(header::CONTENT_TYPE, pending.mime)
+            .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+            .header(header::CACHE_CONTROL, "no-store")
+            .body(body)
+            .unwrap_or_else(|_| status(StatusCode::INTERNAL_SERVER_ERROR)),
+        Err(_) => status(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
@@ -0,0 +1,147 @@
+use std::ffi::{OsStr, OsString};
+use std::io::Read;
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+
+use crate::env_policy;
+use crate::tasks::Tasks;
+
+/// Operations running longer than this start reporting `task:progress`
+const PROGRESS_AFTER: Duration = Duration::from_millis(200);
+/// Interval between progress events once reporting has started
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Shared between a blocking operation and whoever may cancel it.
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    done: Arc<AtomicU64>,
+    total: Arc<AtomicU64>,
+}
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Bail out of the operation if it was cancelled.
+    pub fn check(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err("Operation cancelled".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Report `done` of `total` work units (included in progress events).
+    pub fn set_progress(&self, done: u64, total: u64) {
+        self.done.store(done, Ordering::Relaxed);
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    /// (done, total) as last reported
+    pub fn progress(&self) -> (u64, u64) {
+        (
+            self.done.load(Ordering::Relaxed),
+            self.total.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// Run filesystem/process work on the blocking pool instead of the IPC thread,
+/// tracked as a task. Emits `task:progress` while the work takes longer than
+/// ~200 ms and a final `task:finished` if progress was reported. Pass an
+/// `op_id` to make it cancellable via `cancel_task`.
+pub async fn run_blocking<T, F>(
+    app: &AppHandle,
+    op: &str,
+    op_id: Option<String>,
+    f: F,
+) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&CancelToken) -> Result<T, String> + Send + 'static,
+{
+    let tasks = app.state::<Arc<Tasks>>().inner().clone();
+    let (id, token) = tasks.begin(op, op_id);
+    let mut handle = tokio::task::spawn_blocking(move || f(&token));
+
+    let mut wait = PROGRESS_AFTER;
+    let result = loop {
+        match tokio::time::timeout(wait, &mut handle).await {
+            Ok(joined) => break joined.map_err(|e| format!("{} failed: {}", op, e)).and_then(|r| r),
+            Err(_) => {
+                wait = PROGRESS_INTERVAL;
+                tasks.report(app, &id);
+            }
+        }
+    };
+
+    tasks.finish(app, &id, result.as_ref().err().cloned());
+    result
+}
+
+/// Run a subprocess to completion, killing it if the token gets cancelled.
+/// It gets the env policy's environment plus what the caller set on `cmd`.
+pub fn run_command(cmd: &mut Command, token: &CancelToken) -> Result<Output, String> {
+    let explicit: Vec<(OsString, Option<OsString>)> = cmd
+        .get_envs()
+        .map(|(k, v)| (k.to_os_string(), v.map(OsStr::to_os_string)))
+        .collect();
+    env_policy::apply(cmd);
+    for (key, val) in explicit {
+        match val {
+            Some(val) => cmd.env(key, val),
+            None => cmd.env_remove(key),
+        };
+    }
+    let mut child = cmd
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    // Drain pipes on separate threads so a chatty process can't fill them and stall
+    let mut stdout = child.stdout.take();
+    let mut stderr = child.stderr.take();
+    let out_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(ref mut s) = stdout {
+            let _ = s.read_to_end(&mut buf);
+        }
+        buf
+    });
+    let err_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(ref mut s) = stderr {
+            let _ = s.read_to_end(&mut buf);
+        }
+        buf
+    });
+
+    let status = loop {
+        if token.is_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err("Operation cancelled".to_string());
+        }
+        match child.try_wait().map_err(|e| e.to_string())? {
+            Some(status) => break status,
+            None => std::thread::sleep(Duration::from_millis(20)),
+        }
+    };
+
+    Ok(Output {
+        status,
+        stdout: out_reader.join().unwrap_or_default(),
+        stderr: err_reader.join().unwrap_or_default(),
+    })
+}
@@ -0,0 +1,385 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use ts_rs::TS;
+
+use crate::config::AppConfig;
+use crate::engine_update;
+use crate::founding::FoundingServer;
+use crate::node;
+use crate::sidecar::SidecarManager;
+use crate::simulation::Simulation;
+use crate::soul_health::SoulGuard;
+use crate::watcher::{self, WatcherHandles};
+
+/// One step of a boot or shutdown (`boot:progress`).
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct BootProgress {
+    /// "boot", "autostart" (at launch) or "shutdown"
+    pub phase: String,
+    /// "checks", "watcher", "chain", "engine" or "founding"
+    pub step: String,
+    /// "starting", "stopping", "done", "skipped", "blocked" (a step it
+    /// depends on failed) or "failed"
+    pub status: String,
+    pub detail: Option<String>,
+    /// 1-based position in the order the steps run
+    pub index: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct BootReport {
+    pub phase: String,
+    /// App state the steps were chosen for ("setup", "founding", "ready", ...)
+    pub app_state: String,
+    /// Final status of every step, in the order they ran
+    pub steps: Vec<BootProgress>,
+}
+
+/// Launch autostart waits this long so the tray and window settle first
+const AUTOSTART_DELAY: Duration = Duration::from_secs(2);
+
+/// Starts or stops a subsystem; Ok(false) when there was nothing to do
+type Action = fn(&AppHandle) -> Result<bool, String>;
+
+/// A subsystem in the boot graph.
+struct Step {
+    id: &'static str,
+    /// Steps that must be up first, when they apply
+    after: &'static [&'static str],
+    /// Whether the step belongs in this app state; None when it does,
+    /// otherwise why not
+    applies: fn(&AppHandle, &str) -> Option<String>,
+    /// Bring the subsystem up; Ok(false) when it already was
+    start: Action,
+    /// Take it down; Ok(false) when it wasn't up. None for steps that live
+    /// as long as the app
+    stop: Option<Action>,
+}
+
+/// Launch settings and what the last autostart did.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct AutostartStatus {
+    pub engine: bool,
+    pub chain: bool,
+    /// None until the launch boot finished
+    pub last: Option<BootReport>,
+}
+
+/// The startup graph: checks before any process, chain before engine,
+/// watcher before engine, founding only while the soul is being founded.
+const STEPS: &[Step] = &[
+    Step {
+        id: "engine",
+        after: &["checks", "watcher", "chain"],
+        applies: only_when_ready,
+        start: start_engine,
+        stop: Some(stop_engine),
+    },
+    Step {
+        id: "chain",
+        after: &["checks", "watcher"],
+        applies: chain_applies,
+        start: start_chain,
+        stop: Some(stop_chain),
+    },
+    Step {
+        id: "checks",
+        after: &[],
+        applies: has_soul,
+        start: preflight,
+        stop: None,
+    },
+    Step {
+        id: "watcher",
+        after: &[],
+        applies: has_soul,
+        start: start_watcher,
+        stop: None,
+    },
+    Step {
+        id: "founding",
+        after: &["checks"],
+        applies: |_, state| (state != "founding").then(|| "soul is not founding".to_string()),
+        start: start_founding,
+        stop: Some(stop_founding),
+    },
+];
+
+/// Boots and shuts down the soul's subsystems in dependency order. Runs
+/// one boot or shutdown at a time.
+#[derive(Default)]
+pub struct Boot {
+    running: Mutex<()>,
+    /// Report of the launch autostart, kept for a window opened later from
+    /// the tray
+    autostarted: Mutex<Option<BootReport>>,
+}
+
+impl Boot {
+    /// Start every subsystem the current app state needs, dependencies
+    /// first. A failed step blocks the steps that depend on it; the others
+    /// still run.
+    pub fn boot_all(&self, app: &AppHandle) -> BootReport {
+        self.boot(app, "boot", &|_| None)
+    }
+
+    /// The launch boot: like `boot_all`, minus the processes switched off
+    /// by `autostart_engine` / `autostart_chain`.
+    pub fn autostart(&self, app: &AppHandle) -> BootReport {
+        let (engine, chain) = {
+            let cfg = app.state::<Arc<RwLock<AppConfig>>>();
+            let cfg = cfg.read();
+            (cfg.autostart_engine, cfg.autostart_chain)
+        };
+        let disabled = |step: &str| match step {
+            "engine" if !engine => Some("autostart_engine is off".to_string()),
+            "chain" if !chain => Some("autostart_chain is off".to_string()),
+            _ => None,
+        };
+        let report = self.boot(app, "autostart", &disabled);
+        *self.autostarted.lock() = Some(report.clone());
+        report
+    }
+
+    pub fn last_autostart(&self) -> Option<BootReport> {
+        self.autostarted.lock().clone()
+    }
+
+    /// Run the graph; `disabled` can leave steps out, with the reason.
+    fn boot(
+        &self,
+        app: &AppHandle,
+        phase: &str,
+        disabled: &dyn Fn(&str) -> Option<String>,
+    ) -> BootReport {
+        let _running = self.running.lock();
+        let state = app_state(app);
+        let order = order();
+        let total = order.len();
+        let mut steps: Vec<BootProgress> = Vec::with_capacity(total);
+        for (i, step) in order.iter().enumerate() {
+            let progress = |status: &str, detail: Option<String>| {
+                let progress = BootProgress {
+                    phase: phase.to_string(),
+                    step: step.id.to_string(),
+                    status: status.to_string(),
+                    detail,
+                    index: i + 1,
+                    total,
+                };
+                let _ = app.emit("boot:progress", progress.clone());
+                progress
+            };
+            let failed = step.after.iter().find(|dep| {
+                steps
+                    .iter()
+                    .any(|s| s.step == **dep && matches!(s.status.as_str(), "failed" | "blocked"))
+            });
+            let outcome = if let Some(dep) = failed {
+                progress("blocked", Some(format!("{} did not start", dep)))
+            } else if let Some(reason) = (step.applies)(app, state).or_else(|| disabled(step.id)) {
+                progress("skipped", Some(reason))
+            } else {
+                progress("starting", None);
+                match (step.start)(app) {
+                    Ok(true) => progress("done", None),
+                    Ok(false) => progress("done", Some("already running".to_string())),
+                    Err(e) => {
+                        eprintln!("[boot] {} failed: {}", step.id, e);
+                        progress("failed", Some(e))
+                    }
+                }
+            };
+            steps.push(outcome);
+        }
+        BootReport {
+            phase: phase.to_string(),
+            app_state: state.to_string(),
+            steps,
+        }
+    }
+
+    /// Stop every subsystem that is up, dependents first.
+    pub fn shutdown_all(&self, app: &AppHandle) -> BootReport {
+        let _running = self.running.lock();
+        let order = order();
+        let total = order.len();
+        let mut steps = Vec::with_capacity(total);
+        for (i, step) in order.iter().rev().enumerate() {
+            let progress = |status: &str, detail: Option<String>| {
+                let progress = BootProgress {
+                    phase: "shutdown".to_string(),
+                    step: step.id.to_string(),
+                    status: status.to_string(),
+                    detail,
+                    index: i + 1,
+                    total,
+                };
+                let _ = app.emit("boot:progress", progress.clone());
+                progress
+            };
+            let outcome = match step.stop {
+                None => progress("skipped", Some("nothing to stop".to_string())),
+                Some(stop) => {
+                    progress("stopping", None);
+                    match stop(app) {
+                        Ok(true) => progress("done", None),
+                        Ok(false) => progress("skipped", Some("not running".to_string())),
+                        Err(e) => progress("failed", Some(e)),
+                    }
+                }
+            };
+            steps.push(outcome);
+        }
+        BootReport {
+            phase: "shutdown".to_string(),
+            app_state: app_state(app).to_string(),
+            steps,
+        }
+    }
+}
+
+/// "setup", "founding", "ready", or "missing" when the soul is gone. The
+/// demo simulation always counts as ready.
+pub fn app_state(app: &AppHandle) -> &'static str {
+    if app.state::<Arc<Simulation>>().status().running {
+        return "ready";
+    }
+    if app.state::<Arc<SoulGuard>>().missing() {
+        return "missing";
+    }
+    app.state::<Arc<RwLock<AppConfig>>>().read().app_state()
+}
+
+/// Steps ordered so each comes after the steps it depends on.
+fn order() -> Vec<&'static Step> {
+    let mut ordered: Vec<&'static Step> = Vec::with_capacity(STEPS.len());
+    while ordered.len() < STEPS.len() {
+        let next = STEPS.iter().find(|step| {
+            !ordered.iter().any(|s| s.id == step.id)
+                && step
+                    .after
+                    .iter()
+                    .all(|dep| ordered.iter().any(|s| s.id == *dep))
+        });
+        let Some(next) = next else {
+            panic!("boot steps depend on each other in a cycle");
+        };
+        ordered.push(next);
+    }
+    ordered
+}
+
+fn sidecar(app: &AppHandle) -> Arc<SidecarManager> {
+    app.state::<Arc<SidecarManager>>().inner().clone()
+}
+
+fn has_soul(app: &AppHandle, state: &str) -> Option<String> {
+    if app.state::<Arc<Simulation>>().status().running {
+        return Some("demo mode".to_string());
+    }
+    matches!(state, "setup" | "missing").then(|| format!("no soul ({})", state))
+}
+
+fn only_when_ready(app: &AppHandle, state: &str) -> Option<String> {
+    has_soul(app, state)
+        .or_else(|| (state != "ready").then(|| format!("soul is not ready ({})", state)))
+}
+
+fn chain_applies(app: &AppHandle, state: &str) -> Option<String> {
+    only_when_ready(app, state).or_else(|| {
+        (!sidecar(app).chain_available(app)).then(|| "soul-chain is not installed".to_string())
+    })
+}
+
+/// Doctor checks every sidecar needs to pass.
+fn preflight(app: &AppHandle) -> Result<bool, String> {
+    node::find_node(Some(app))
+        .map(|_| true)
+        .ok_or_else(|| "Node.js not found (neither bundled nor system)".to_string())
+}
+
+/// Autostart once the soul was validated at launch (see `AUTOSTART_DELAY`).
+pub fn start_autostart(app: AppHandle, boot: Arc<Boot>) {
+    std::thread::spawn(move || {
+        std::thread::sleep(AUTOSTART_DELAY);
+        boot.autostart(&app);
+    });
+}
+
+fn start_watcher(app: &AppHandle) -> Result<bool, String> {
+    if app.try_state::<WatcherHandles>().is_some() {
+        return Ok(false);
+    }
+    let soul_path = app
+        .state::<Arc<RwLock<AppConfig>>>()
+        .read()
+        .soul_path
+        .clone();
+    watcher::repoint(app, &soul_path).map(|()| true)
+}
+
+fn start_chain(app: &AppHandle) -> Result<bool, String> {
+    let sidecar = sidecar(app);
+    if sidecar.get_chain_status().status == "running" {
+        return Ok(false);
+    }
+    sidecar.start_chain(app).map(|()| true)
+}
+
+fn stop_chain(app: &AppHandle) -> Result<bool, String> {
+    let sidecar = sidecar(app);
+    if sidecar.get_chain_status().status != "running" {
+        return Ok(false);
+    }
+    sidecar.stop_chain(app).map(|()| true)
+}
+
+fn start_engine(app: &AppHandle) -> Result<bool, String> {
+    let sidecar = sidecar(app);
+    // Deploy a newer bundled engine first; a successful update leaves it
+    // running
+    engine_update::migrate(app, &sidecar);
+    match sidecar.get_status().status.as_str() {
+        "running" => Ok(false),
+        "paused" => sidecar.resume_engine(app).map(|()| true),
+        _ => sidecar.start_engine(app).map(|()| true),
+    }
+}
+
+fn stop_engine(app: &AppHandle) -> Result<bool, String> {
+    let sidecar = sidecar(app);
+    if !matches!(sidecar.get_status().status.as_str(), "running" | "paused") {
+        return Ok(false);
+    }
+    sidecar.stop_engine(app).map(|()| true)
+}
+
+fn start_founding(app: &AppHandle) -> Result<bool, String> {
+    let founding = app.state::<Arc<FoundingServer>>();
+    if founding.is_running() {
+        return Ok(false);
+    }
+    let soul_path = app
+        .state::<Arc<RwLock<AppConfig>>>()
+        .read()
+        .soul_path
+        .clone();
+    founding.start(app, &soul_path).map(|_| true)
+}
+
+fn stop_founding(app: &AppHandle) -> Result<bool, String> {
+    let founding = app.state::<Arc<FoundingServer>>();
+    if !founding.is_running() {
+        return Ok(false);
+    }
+    founding.stop().map(|()| true)
+}
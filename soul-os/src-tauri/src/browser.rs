@@ -0,0 +1,186 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use parking_lot::Mutex;
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Runtime, WebviewUrl, WebviewWindowBuilder};
+use ts_rs::TS;
+
+use crate::config::app_data_dir;
+
+pub const BROWSER_LABEL: &str = "soul-browser";
+/// Hidden window that opens a profile's storage to clear it
+const CLEAR_LABEL: &str = "soul-browser-clear";
+/// Marks a profile directory; holds the data store identifier WKWebView
+/// uses instead of a data directory
+const STORE_ID_FILE: &str = ".store-id";
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct BrowserProfile {
+    pub name: String,
+    /// Storage on disk (Windows/Linux; macOS keeps it in the system store)
+    #[ts(type = "number")]
+    pub bytes: u64,
+    /// The browser window is open with this profile
+    pub active: bool,
+}
+
+/// Named cookie/localStorage partitions for the embedded browser, kept in
+/// <app_data_dir>/browser-profiles/<name>. Without a profile the browser
+/// uses the app's shared storage.
+#[derive(Default)]
+pub struct BrowserProfiles {
+    /// Profile of the open browser window
+    active: Mutex<Option<String>>,
+}
+
+fn profiles_dir() -> PathBuf {
+    app_data_dir().join("browser-profiles")
+}
+
+/// Profile names become directory names.
+fn validate(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name.len() <= 32
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(format!(
+            "Invalid browser profile name '{}': use up to 32 letters, digits, '-' or '_'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// The profile's data store identifier, created on first use.
+fn store_id(dir: &Path) -> Result<[u8; 16], String> {
+    let path = dir.join(STORE_ID_FILE);
+    let mut id = [0u8; 16];
+    let stored = fs::read_to_string(&path).unwrap_or_default();
+    let parsed: Vec<u8> = (0..stored.trim().len() / 2)
+        .filter_map(|i| u8::from_str_radix(stored.get(i * 2..i * 2 + 2)?, 16).ok())
+        .collect();
+    if parsed.len() == id.len() {
+        id.copy_from_slice(&parsed);
+        return Ok(id);
+    }
+    OsRng.fill_bytes(&mut id);
+    let hex: String = id.iter().map(|b| format!("{:02x}", b)).collect();
+    fs::write(&path, hex).map_err(|e| format!("Failed to create browser profile: {}", e))?;
+    Ok(id)
+}
+
+/// Point a webview at the storage of profile `name`, creating it if needed.
+fn partition<'a, R: Runtime, M: Manager<R>>(
+    builder: WebviewWindowBuilder<'a, R, M>,
+    name: &str,
+) -> Result<WebviewWindowBuilder<'a, R, M>, String> {
+    validate(name)?;
+    let dir = profiles_dir().join(name);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create browser profile: {}", e))?;
+    let id = store_id(&dir)?;
+    Ok(builder
+        .data_directory(dir.join("data"))
+        .data_store_identifier(id))
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+impl BrowserProfiles {
+    /// Use `profile`'s storage for the browser window being built; None
+    /// keeps the shared storage.
+    pub fn apply<'a, R: Runtime, M: Manager<R>>(
+        &self,
+        builder: WebviewWindowBuilder<'a, R, M>,
+        profile: Option<&str>,
+    ) -> Result<WebviewWindowBuilder<'a, R, M>, String> {
+        match profile {
+            Some(name) => partition(builder, name),
+            None => Ok(builder),
+        }
+    }
+
+    /// Record which profile the browser window was opened with.
+    pub fn set_active(&self, profile: Option<String>) {
+        *self.active.lock() = profile;
+    }
+
+    fn active(&self, app: &AppHandle) -> Option<String> {
+        app.get_webview_window(BROWSER_LABEL)?;
+        self.active.lock().clone()
+    }
+
+    pub fn list(&self, app: &AppHandle) -> Vec<BrowserProfile> {
+        let active = self.active(app);
+        let Ok(entries) = fs::read_dir(profiles_dir()) else {
+            return Vec::new();
+        };
+        let mut profiles: Vec<BrowserProfile> = entries
+            .flatten()
+            .filter(|entry| entry.path().join(STORE_ID_FILE).exists())
+            .map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                BrowserProfile {
+                    bytes: dir_size(&entry.path()),
+                    active: active.as_deref() == Some(name.as_str()),
+                    name,
+                }
+            })
+            .collect();
+        profiles.sort_by(|a, b| a.name.cmp(&b.name));
+        profiles
+    }
+
+    /// Delete a profile's cookies and storage, and the profile itself; the
+    /// next `open_browser` with that name starts empty. Closes the browser
+    /// if it is open with this profile.
+    pub fn clear(&self, app: &AppHandle, name: &str) -> Result<(), String> {
+        validate(name)?;
+        let dir = profiles_dir().join(name);
+        if !dir.join(STORE_ID_FILE).exists() {
+            return Err(format!("Unknown browser profile: {}", name));
+        }
+
+        // The webview engine owns the storage, so clearing goes through a
+        // webview opened on it
+        let window = if self.active(app).as_deref() == Some(name) {
+            self.set_active(None);
+            app.get_webview_window(BROWSER_LABEL)
+                .ok_or("Browser window disappeared")?
+        } else {
+            let url = "about:blank".parse().map_err(|e| format!("{}", e))?;
+            let builder = WebviewWindowBuilder::new(app, CLEAR_LABEL, WebviewUrl::External(url))
+                .visible(false)
+                .skip_taskbar(true);
+            partition(builder, name)?
+                .build()
+                .map_err(|e| e.to_string())?
+        };
+        let cleared = window.clear_all_browsing_data().map_err(|e| e.to_string());
+        let _ = window.destroy();
+        cleared?;
+
+        fs::remove_dir_all(&dir).map_err(|e| {
+            format!(
+                "Browsing data cleared, but removing the profile failed: {}",
+                e
+            )
+        })
+    }
+}
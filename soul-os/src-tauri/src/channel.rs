@@ -0,0 +1,352 @@
+//! Authenticated, encrypted requests between a paired device and the
+//! companion endpoint, over plain HTTP on the local network.
+//!
+//! Pairing leaves both ends with a 32-byte key that never travels again:
+//! every request carries `Authorization: Soul <device>.<nonce>.<ms>.<mac>`,
+//! an HMAC-SHA256 over method, target, time, nonce and body, and bodies in
+//! both directions travel as `{ "sealed": "<hex>" }`, XChaCha20-Poly1305
+//! under keys derived (HKDF-SHA256) from the pairing key and the request
+//! nonce. A stream adds a nonce from the desktop, so its key is fresh per
+//! session. During pairing the key itself is sent sealed under an X25519
+//! exchange mixed with the pairing code.
+
+use std::collections::HashMap;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use parking_lot::Mutex;
+use ring::agreement::{self, EphemeralPrivateKey, UnparsedPublicKey};
+use ring::rand::SystemRandom;
+use ring::{hkdf, hmac};
+
+pub type Key = [u8; 32];
+
+/// `Authorization` scheme of a signed request
+const SCHEME: &str = "Soul ";
+pub const NONCE_LEN: usize = 16;
+const SEAL_NONCE_LEN: usize = 24;
+/// Requests signed further from the desktop's clock are refused, and
+/// nonces are remembered this long to refuse replays
+const MAX_SKEW_MS: u64 = 5 * 60 * 1000;
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn from_hex(s: &str) -> Option<Vec<u8>> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| s.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+        .collect()
+}
+
+pub fn key_from_hex(s: &str) -> Option<Key> {
+    from_hex(s)?.try_into().ok()
+}
+
+pub fn new_key() -> Key {
+    let mut key = [0u8; 32];
+    OsRng.fill_bytes(&mut key);
+    key
+}
+
+pub fn new_nonce() -> [u8; NONCE_LEN] {
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    nonce
+}
+
+fn derive(ikm: &[u8], salt: &[u8], info: &str) -> Key {
+    let mut out = [0u8; 32];
+    hkdf::Salt::new(hkdf::HKDF_SHA256, salt)
+        .extract(ikm)
+        .expand(&[info.as_bytes()], hkdf::HKDF_SHA256)
+        .and_then(|okm| okm.fill(&mut out))
+        .expect("32 bytes is a valid HKDF-SHA256 length");
+    out
+}
+
+/// Key for the body a device sends with the request signed with `nonce`.
+pub fn request_key(key: &Key, nonce: &[u8]) -> Key {
+    derive(key, nonce, "soulos companion request")
+}
+
+/// Key for the desktop's answer to that request.
+pub fn response_key(key: &Key, nonce: &[u8]) -> Key {
+    derive(key, nonce, "soulos companion response")
+}
+
+/// Key of a remote view session, from the nonces of both ends.
+pub fn stream_key(key: &Key, device_nonce: &[u8], desktop_nonce: &[u8]) -> Key {
+    derive(
+        key,
+        &[device_nonce, desktop_nonce].concat(),
+        "soulos companion stream",
+    )
+}
+
+/// 24-byte nonce followed by the XChaCha20-Poly1305 ciphertext.
+pub fn seal(key: &Key, plain: &[u8]) -> Result<Vec<u8>, String> {
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = XChaCha20Poly1305::new(key.into())
+        .encrypt(&nonce, plain)
+        .map_err(|e| e.to_string())?;
+    let mut out = nonce.to_vec();
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+pub fn open(key: &Key, data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < SEAL_NONCE_LEN {
+        return Err("Sealed message is truncated".to_string());
+    }
+    let (nonce, ciphertext) = data.split_at(SEAL_NONCE_LEN);
+    XChaCha20Poly1305::new(key.into())
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Sealed message does not open (wrong key or tampered)".to_string())
+}
+
+/// A body as it travels: `{ "sealed": "<hex>" }`.
+pub fn seal_body(key: &Key, plain: &[u8]) -> Result<String, String> {
+    Ok(serde_json::json!({ "sealed": to_hex(&seal(key, plain)?) }).to_string())
+}
+
+pub fn open_body(key: &Key, body: &[u8]) -> Result<Vec<u8>, String> {
+    let envelope: serde_json::Value = serde_json::from_slice(body).map_err(|e| e.to_string())?;
+    let sealed = envelope["sealed"]
+        .as_str()
+        .and_then(from_hex)
+        .ok_or("Expected a sealed body")?;
+    open(key, &sealed)
+}
+
+fn mac_input(method: &str, target: &str, timestamp: u64, nonce: &[u8], body: &[u8]) -> Vec<u8> {
+    let mut input =
+        format!("{}\n{}\n{}\n{}\n", method, target, timestamp, to_hex(nonce)).into_bytes();
+    input.extend_from_slice(body);
+    input
+}
+
+/// The parts of a `Soul` authorization header.
+#[derive(Debug, Clone)]
+pub struct Signature {
+    pub device: String,
+    pub nonce: Vec<u8>,
+    pub timestamp: u64,
+    mac: Vec<u8>,
+}
+
+impl Signature {
+    pub fn parse(header: &str) -> Option<Self> {
+        let mut parts = header.strip_prefix(SCHEME)?.trim().split('.');
+        let signature = Self {
+            device: parts.next()?.to_string(),
+            nonce: from_hex(parts.next()?)?,
+            timestamp: parts.next()?.parse().ok()?,
+            mac: from_hex(parts.next()?)?,
+        };
+        (parts.next().is_none() && signature.nonce.len() == NONCE_LEN).then_some(signature)
+    }
+
+    /// Whether the request was signed with `key` (constant time).
+    pub fn verify(&self, key: &Key, method: &str, target: &str, body: &[u8]) -> bool {
+        let input = mac_input(method, target, self.timestamp, &self.nonce, body);
+        hmac::verify(&hmac::Key::new(hmac::HMAC_SHA256, key), &input, &self.mac).is_ok()
+    }
+}
+
+/// `Authorization` value for a request from `device`, and its nonce.
+pub fn sign(
+    key: &Key,
+    device: &str,
+    method: &str,
+    target: &str,
+    timestamp: u64,
+    body: &[u8],
+) -> (String, [u8; NONCE_LEN]) {
+    let nonce = new_nonce();
+    let input = mac_input(method, target, timestamp, &nonce, body);
+    let mac = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), &input);
+    let header = format!(
+        "{}{}.{}.{}.{}",
+        SCHEME,
+        device,
+        to_hex(&nonce),
+        timestamp,
+        to_hex(mac.as_ref())
+    );
+    (header, nonce)
+}
+
+/// Nonces of recently accepted requests, so a captured request can't be
+/// sent again.
+#[derive(Default)]
+pub struct Replays {
+    seen: Mutex<HashMap<Vec<u8>, u64>>,
+}
+
+impl Replays {
+    /// Records the nonce; false for a stale timestamp or a nonce seen before.
+    pub fn fresh(&self, signature: &Signature, now: u64) -> bool {
+        if signature.timestamp.abs_diff(now) > MAX_SKEW_MS {
+            return false;
+        }
+        let mut seen = self.seen.lock();
+        seen.retain(|_, at| now.saturating_sub(*at) <= 2 * MAX_SKEW_MS);
+        seen.insert(signature.nonce.clone(), now).is_none()
+    }
+}
+
+fn pairing_wrap_key(shared: &[u8], code: &str) -> Key {
+    derive(shared, code.trim().as_bytes(), "soulos companion pairing")
+}
+
+/// The requesting side of pairing: an X25519 key pair kept until the
+/// answer arrives.
+pub struct PairingRequest {
+    private: EphemeralPrivateKey,
+    pub public: String,
+}
+
+impl PairingRequest {
+    pub fn new() -> Result<Self, String> {
+        let private = EphemeralPrivateKey::generate(&agreement::X25519, &SystemRandom::new())
+            .map_err(|_| "Cannot create a pairing key".to_string())?;
+        let public = private
+            .compute_public_key()
+            .map_err(|_| "Cannot create a pairing key".to_string())?;
+        Ok(Self {
+            public: to_hex(public.as_ref()),
+            private,
+        })
+    }
+
+    /// The pairing key the other side sealed for this request.
+    pub fn open(self, their_public: &str, code: &str, sealed: &str) -> Result<Key, String> {
+        let their_public = from_hex(their_public).ok_or("Invalid pairing key")?;
+        let sealed = from_hex(sealed).ok_or("Invalid pairing answer")?;
+        let wrap = agreement::agree_ephemeral(
+            self.private,
+            &UnparsedPublicKey::new(&agreement::X25519, their_public),
+            |shared| pairing_wrap_key(shared, code),
+        )
+        .map_err(|_| "Invalid pairing key".to_string())?;
+        open(&wrap, &sealed)?
+            .try_into()
+            .map_err(|_| "Invalid pairing answer".to_string())
+    }
+}
+
+/// The confirming side: `key` sealed for the requester's public key.
+/// Returns (our public key, sealed key), both hex.
+pub fn seal_pairing_key(
+    their_public: &str,
+    code: &str,
+    key: &Key,
+) -> Result<(String, String), String> {
+    let their_public = from_hex(their_public).ok_or("Invalid pairing key")?;
+    let private = EphemeralPrivateKey::generate(&agreement::X25519, &SystemRandom::new())
+        .map_err(|_| "Cannot create a pairing key".to_string())?;
+    let public = private
+        .compute_public_key()
+        .map_err(|_| "Cannot create a pairing key".to_string())?;
+    let wrap = agreement::agree_ephemeral(
+        private,
+        &UnparsedPublicKey::new(&agreement::X25519, their_public),
+        |shared| pairing_wrap_key(shared, code),
+    )
+    .map_err(|_| "Invalid pairing key".to_string())?;
+    Ok((to_hex(public.as_ref()), to_hex(&seal(&wrap, key)?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_770_000_000_000;
+
+    fn signed(key: &Key, body: &[u8]) -> Signature {
+        let (header, _) = sign(key, "dev1", "POST", "/notes", NOW, body);
+        Signature::parse(&header).unwrap()
+    }
+
+    #[test]
+    fn verifies_a_signed_request() {
+        let key = new_key();
+        let signature = signed(&key, b"{}");
+        assert_eq!(signature.device, "dev1");
+        assert!(signature.verify(&key, "POST", "/notes", b"{}"));
+    }
+
+    #[test]
+    fn refuses_a_tampered_request_or_another_key() {
+        let key = new_key();
+        let signature = signed(&key, b"{}");
+        assert!(!signature.verify(&key, "POST", "/notes", b"[]"));
+        assert!(!signature.verify(&key, "GET", "/notes", b"{}"));
+        assert!(!signature.verify(&key, "POST", "/notes?since=0", b"{}"));
+        assert!(!signature.verify(&new_key(), "POST", "/notes", b"{}"));
+    }
+
+    #[test]
+    fn refuses_malformed_headers() {
+        for header in [
+            "Bearer dev1.abcd",
+            "Soul dev1",
+            "Soul dev1.zz.1.00",
+            "Soul dev1.00.1.00",
+            "Soul dev1.00000000000000000000000000000000.x.00",
+            "Soul dev1.00000000000000000000000000000000.1.00.extra",
+        ] {
+            assert!(Signature::parse(header).is_none(), "{}", header);
+        }
+    }
+
+    #[test]
+    fn refuses_replays_and_stale_requests() {
+        let key = new_key();
+        let replays = Replays::default();
+        let signature = signed(&key, b"");
+        assert!(replays.fresh(&signature, NOW));
+        assert!(!replays.fresh(&signature, NOW + 1000));
+        assert!(!replays.fresh(&signed(&key, b""), NOW + MAX_SKEW_MS + 1));
+        assert!(replays.fresh(&signed(&key, b""), NOW - 1000));
+    }
+
+    #[test]
+    fn bodies_open_only_with_their_key() {
+        let key = new_key();
+        let nonce = new_nonce();
+        let body = seal_body(&response_key(&key, &nonce), b"{\"ok\":true}").unwrap();
+        assert_eq!(
+            open_body(&response_key(&key, &nonce), body.as_bytes()).unwrap(),
+            b"{\"ok\":true}"
+        );
+        assert!(open_body(&request_key(&key, &nonce), body.as_bytes()).is_err());
+        assert!(open_body(&response_key(&key, &new_nonce()), body.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn stream_keys_differ_per_session() {
+        let key = new_key();
+        let device = new_nonce();
+        assert_ne!(
+            stream_key(&key, &device, &new_nonce()),
+            stream_key(&key, &device, &new_nonce())
+        );
+    }
+
+    #[test]
+    fn pairing_delivers_the_key_only_with_the_code() {
+        let key = new_key();
+        let request = PairingRequest::new().unwrap();
+        let (public, sealed) = seal_pairing_key(&request.public, "123456", &key).unwrap();
+        assert!(!sealed.contains(&to_hex(&key)));
+        assert_eq!(request.open(&public, "123456", &sealed).unwrap(), key);
+
+        let request = PairingRequest::new().unwrap();
+        let (public, sealed) = seal_pairing_key(&request.public, "123456", &key).unwrap();
+        assert!(request.open(&public, "654321", &sealed).is_err());
+    }
+}
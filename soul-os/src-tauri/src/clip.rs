@@ -0,0 +1,477 @@
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use chrono::Local;
+use regex::Regex;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+use url::Url;
+
+use crate::http::{self, Http, Policy};
+use crate::paths::{self, Location, Memories};
+
+const FETCH: Policy = Policy {
+    name: "clip_fetch",
+    timeout: Duration::from_secs(30),
+    retries: 1,
+    backoff: Duration::from_secs(1),
+    idempotent: true,
+};
+/// Pages larger than this aren't clipped
+const MAX_PAGE_BYTES: usize = 5 * 1024 * 1024;
+/// Elements whose content is never article text
+const NOISE_TAGS: &[&str] = &[
+    "script", "style", "noscript", "svg", "nav", "header", "footer", "aside", "form", "iframe",
+    "template", "button", "select",
+];
+/// Blocks with more link text than this share are navigation, not content
+const MAX_LINK_DENSITY: f64 = 0.5;
+/// Shorter blocks without sentence punctuation are dropped as boilerplate
+const MIN_BLOCK_CHARS: usize = 25;
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ClippedPage {
+    /// Relative to the soul path
+    pub path: String,
+    pub title: String,
+    pub url: String,
+    #[ts(type = "number")]
+    pub words: u64,
+}
+
+/// Readable content of a page as markdown.
+pub struct Article {
+    pub title: String,
+    pub byline: Option<String>,
+    pub markdown: String,
+}
+
+struct Patterns {
+    comment: Regex,
+    noise: Vec<Regex>,
+    title: Regex,
+    meta: Regex,
+    attr: Regex,
+    tag: Regex,
+    entity: Regex,
+    blank_lines: Regex,
+    link: Regex,
+}
+
+fn patterns() -> &'static Patterns {
+    static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        let re = |p: &str| Regex::new(p).expect("invalid clip pattern");
+        Patterns {
+            comment: re(r"(?s)<!--.*?-->"),
+            noise: NOISE_TAGS
+                .iter()
+                .map(|tag| re(&format!(r"(?is)<{0}\b[^>]*>.*?</{0}\s*>", tag)))
+                .collect(),
+            title: re(r"(?is)<title\b[^>]*>(.*?)</title\s*>"),
+            meta: re(r"(?is)<meta\b[^>]*>"),
+            attr: re(r#"(?s)([a-zA-Z:-]+)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#),
+            tag: re(r"(?s)<(/?)([a-zA-Z][a-zA-Z0-9]*)\b([^>]*)>"),
+            entity: re(r"&(#[xX][0-9a-fA-F]+|#[0-9]+|[a-zA-Z]+);"),
+            blank_lines: re(r"\n{3,}"),
+            link: re(r"\[([^\]]*)\]\([^)]*\)"),
+        }
+    })
+}
+
+/// Value of attribute `name` in a tag's attribute string.
+fn attr(attrs: &str, name: &str) -> Option<String> {
+    patterns().attr.captures_iter(attrs).find_map(|cap| {
+        cap[1].eq_ignore_ascii_case(name).then(|| {
+            cap.get(2)
+                .or(cap.get(3))
+                .or(cap.get(4))
+                .map_or(String::new(), |v| decode(v.as_str()))
+        })
+    })
+}
+
+fn decode(text: &str) -> String {
+    patterns()
+        .entity
+        .replace_all(text, |cap: &regex::Captures| {
+            let entity = &cap[1];
+            let numeric = if let Some(hex) = entity.strip_prefix("#x").or(entity.strip_prefix("#X"))
+            {
+                u32::from_str_radix(hex, 16).ok()
+            } else if let Some(dec) = entity.strip_prefix('#') {
+                dec.parse().ok()
+            } else {
+                None
+            };
+            if let Some(c) = numeric.and_then(char::from_u32) {
+                return c.to_string();
+            }
+            match entity {
+                "amp" => "&",
+                "lt" => "<",
+                "gt" => ">",
+                "quot" => "\"",
+                "apos" => "'",
+                "nbsp" => " ",
+                "mdash" => "—",
+                "ndash" => "–",
+                "hellip" => "…",
+                "lsquo" => "‘",
+                "rsquo" => "’",
+                "ldquo" => "“",
+                "rdquo" => "”",
+                "laquo" => "«",
+                "raquo" => "»",
+                _ => return cap[0].to_string(),
+            }
+            .to_string()
+        })
+        .into_owned()
+}
+
+/// Content of every `<tag>…</tag>` (not nesting-aware).
+fn elements<'a>(html: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = Regex::new(&format!(r"(?is)<{}\b[^>]*>(.*?)</{}\s*>", tag, tag))
+        .expect("invalid clip pattern");
+    open.captures_iter(html)
+        .filter_map(|cap| cap.get(1).map(|m| m.as_str()))
+        .collect()
+}
+
+/// Readability-style candidate: the longest `<article>`, else `<main>`, else
+/// the body.
+fn candidate(html: &str) -> &str {
+    let text_len = |s: &&str| patterns().tag.replace_all(s, "").trim().len();
+    for tag in ["article", "main", "body"] {
+        if let Some(best) = elements(html, tag).into_iter().max_by_key(text_len) {
+            if text_len(&best) > 0 {
+                return best;
+            }
+        }
+    }
+    html
+}
+
+/// Markdown writer fed with the tag stream.
+struct Writer<'a> {
+    out: String,
+    base: &'a Url,
+    pre: usize,
+    lists: usize,
+    /// Start offset and target of each open link
+    links: Vec<(usize, Option<String>)>,
+}
+
+impl Writer<'_> {
+    fn block(&mut self) {
+        let trimmed = self.out.trim_end_matches([' ', '\n']).len();
+        self.out.truncate(trimmed);
+        if !self.out.is_empty() {
+            self.out.push_str("\n\n");
+        }
+    }
+
+    fn line(&mut self) {
+        let trimmed = self.out.trim_end_matches(' ').len();
+        self.out.truncate(trimmed);
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+    }
+
+    fn text(&mut self, raw: &str) {
+        let text = decode(raw);
+        if self.pre > 0 {
+            self.out.push_str(&text);
+            return;
+        }
+        let mut words = text.split_whitespace().peekable();
+        if words.peek().is_none() {
+            if text.contains(char::is_whitespace) && !self.out.ends_with([' ', '\n']) {
+                self.out.push(' ');
+            }
+            return;
+        }
+        if text.starts_with(char::is_whitespace)
+            && !self.out.ends_with([' ', '\n'])
+            && !self.out.is_empty()
+        {
+            self.out.push(' ');
+        }
+        self.out.push_str(&words.collect::<Vec<_>>().join(" "));
+        if text.ends_with(char::is_whitespace) {
+            self.out.push(' ');
+        }
+    }
+
+    fn resolve(&self, href: Option<String>) -> Option<String> {
+        let url = self.base.join(href?.trim()).ok()?;
+        matches!(url.scheme(), "http" | "https").then(|| url.to_string())
+    }
+
+    fn tag(&mut self, name: &str, closing: bool, attrs: &str) {
+        match (name, closing) {
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", false) => {
+                self.block();
+                let level = name[1..].parse().unwrap_or(1);
+                self.out.push_str(&"#".repeat(level));
+                self.out.push(' ');
+            }
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", true) => self.block(),
+            (
+                "p" | "div" | "section" | "article" | "main" | "table" | "tr" | "figure"
+                | "figcaption" | "dl" | "dd" | "dt",
+                _,
+            ) => self.block(),
+            ("br", _) => self.line(),
+            ("hr", _) => {
+                self.block();
+                self.out.push_str("---");
+                self.block();
+            }
+            ("ul" | "ol", false) => {
+                self.lists += 1;
+                self.line();
+            }
+            ("ul" | "ol", true) => {
+                self.lists = self.lists.saturating_sub(1);
+                if self.lists == 0 {
+                    self.block();
+                }
+            }
+            ("li", false) => {
+                self.line();
+                self.out
+                    .push_str(&"  ".repeat(self.lists.saturating_sub(1)));
+                self.out.push_str("- ");
+            }
+            ("li", true) => self.line(),
+            ("blockquote", false) => {
+                self.block();
+                self.out.push_str("> ");
+            }
+            ("blockquote", true) => self.block(),
+            ("pre", false) => {
+                self.block();
+                self.out.push_str("```\n");
+                self.pre += 1;
+            }
+            ("pre", true) => {
+                self.pre = self.pre.saturating_sub(1);
+                self.line();
+                self.out.push_str("```");
+                self.block();
+            }
+            ("code", _) if self.pre == 0 => self.out.push('`'),
+            ("strong" | "b", _) => self.out.push_str("**"),
+            ("em" | "i", _) => self.out.push('*'),
+            ("td" | "th", false) => self.out.push(' '),
+            ("a", false) => {
+                let href = self.resolve(attr(attrs, "href"));
+                self.links.push((self.out.len(), href));
+            }
+            ("a", true) => {
+                if let Some((start, Some(href))) = self.links.pop() {
+                    let text = self.out[start..].trim().to_string();
+                    if !text.is_empty() {
+                        self.out.truncate(start);
+                        self.out.push_str(&format!("[{}]({})", text, href));
+                    }
+                }
+            }
+            ("img", _) => {
+                if let Some(src) = self.resolve(attr(attrs, "src")) {
+                    let alt = attr(attrs, "alt").unwrap_or_default();
+                    self.out.push_str(&format!("![{}]({})", alt.trim(), src));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn to_markdown(html: &str, base: &Url) -> String {
+    let mut writer = Writer {
+        out: String::new(),
+        base,
+        pre: 0,
+        lists: 0,
+        links: Vec::new(),
+    };
+    let mut last = 0;
+    for cap in patterns().tag.captures_iter(html) {
+        let Some(whole) = cap.get(0) else {
+            continue;
+        };
+        writer.text(&html[last..whole.start()]);
+        last = whole.end();
+        let name = cap[2].to_ascii_lowercase();
+        let attrs = cap.get(3).map_or("", |a| a.as_str());
+        writer.tag(&name, !cap[1].is_empty(), attrs);
+    }
+    writer.text(&html[last..]);
+    writer.out
+}
+
+/// Drop navigation-like blocks: mostly links, or short without a sentence.
+fn keep_block(block: &str) -> bool {
+    if block.starts_with('#') || block.starts_with("```") || block.starts_with("![") {
+        return true;
+    }
+    let link = &patterns().link;
+    let text_len = link.replace_all(block, "$1").chars().count();
+    let link_len: usize = link
+        .captures_iter(block)
+        .map(|cap| cap[1].chars().count())
+        .sum();
+    if text_len == 0 || link_len as f64 / text_len as f64 > MAX_LINK_DENSITY {
+        return false;
+    }
+    text_len >= MIN_BLOCK_CHARS || block.contains(['.', '!', '?', ':'])
+}
+
+/// Extract the readable content of a page.
+pub fn extract(html: &str, base: &Url) -> Article {
+    let p = patterns();
+    let mut cleaned = p.comment.replace_all(html, "").into_owned();
+    let metas: Vec<String> = p
+        .meta
+        .find_iter(&cleaned)
+        .map(|m| m.as_str().to_string())
+        .collect();
+    let meta = |key: &str| {
+        metas.iter().find_map(|tag| {
+            let named = attr(tag, "property")
+                .or_else(|| attr(tag, "name"))
+                .is_some_and(|n| n.eq_ignore_ascii_case(key));
+            named
+                .then(|| attr(tag, "content"))
+                .flatten()
+                .filter(|c| !c.trim().is_empty())
+        })
+    };
+    let page_title = p.title.captures(&cleaned).map(|cap| decode(cap[1].trim()));
+    let title = meta("og:title")
+        .or(page_title)
+        .map(|t| t.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| base.host_str().unwrap_or("Untitled").to_string());
+    let byline = meta("author");
+
+    for noise in &p.noise {
+        cleaned = noise.replace_all(&cleaned, " ").into_owned();
+    }
+    let markdown = to_markdown(candidate(&cleaned), base);
+    let blocks: Vec<&str> = markdown
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|b| !b.is_empty() && keep_block(b))
+        .collect();
+    let markdown = p
+        .blank_lines
+        .replace_all(&blocks.join("\n\n"), "\n\n")
+        .into_owned();
+
+    Article {
+        title,
+        byline,
+        markdown,
+    }
+}
+
+/// Download a page for clipping; returns the final URL and the HTML.
+pub async fn fetch(app: &AppHandle, url: &Url) -> Result<(Url, String), String> {
+    let client = app.state::<Arc<Http>>().external();
+    let resp = http::send(app, &FETCH, || client.get(url.clone()))
+        .await
+        .map_err(|e| format!("Failed to fetch {}: {}", url, e))?;
+    if !resp.status().is_success() {
+        return Err(format!("Fetching {} failed: HTTP {}", url, resp.status()));
+    }
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_string();
+    if !content_type.is_empty() && !content_type.contains("html") {
+        return Err(format!("Not a web page ({}): {}", content_type, url));
+    }
+    if resp.content_length().unwrap_or(0) as usize > MAX_PAGE_BYTES {
+        return Err(format!(
+            "Page is larger than {} MB",
+            MAX_PAGE_BYTES / (1024 * 1024)
+        ));
+    }
+    let final_url = resp.url().clone();
+    let body = resp.bytes().await.map_err(|e| e.to_string())?;
+    if body.len() > MAX_PAGE_BYTES {
+        return Err(format!(
+            "Page is larger than {} MB",
+            MAX_PAGE_BYTES / (1024 * 1024)
+        ));
+    }
+    Ok((final_url, String::from_utf8_lossy(&body).into_owned()))
+}
+
+fn slug(title: &str) -> String {
+    let slug: String = title
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let slug = slug
+        .split('-')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let slug: String = slug.chars().take(60).collect();
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "page".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// Where a clip of `title` goes, relative to the soul:
+/// <semantic memories>/<date>-clip-<slug>.md, numbered if taken.
+pub fn clip_path(soul_path: &Path, title: &str) -> String {
+    let base = format!(
+        "{}/{}-clip-{}",
+        paths::relative(soul_path, Location::Memory(Memories::Semantic)),
+        Local::now().format("%Y-%m-%d"),
+        slug(title)
+    );
+    (1..)
+        .map(|n| {
+            if n == 1 {
+                format!("{}.md", base)
+            } else {
+                format!("{}-{}.md", base, n)
+            }
+        })
+        .find(|p| !soul_path.join(p).exists())
+        .unwrap_or_else(|| format!("{}.md", base))
+}
+
+/// The memory file: title, source metadata, content.
+pub fn render(article: &Article, url: &Url) -> String {
+    let mut out = format!("# {}\n\n> Source: {}\n", article.title, url);
+    if let Some(host) = url.host_str() {
+        out.push_str(&format!("> Site: {}\n", host));
+    }
+    if let Some(byline) = &article.byline {
+        out.push_str(&format!("> Author: {}\n", byline.trim()));
+    }
+    out.push_str(&format!(
+        "> Clipped: {}\n\n{}\n",
+        Local::now().format("%Y-%m-%d %H:%M"),
+        article.markdown
+    ));
+    out
+}
@@ -1,29 +1,113 @@
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
-use std::process::Command;
-use std::sync::{Arc, Mutex};
-
-use tauri::{Manager, State};
-
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use tauri::{Emitter, Manager, State};
+
+use crate::ambient::{Ambient, AmbientStatus};
+use crate::analytics::{Analytics, GrowthMetrics, Resolution};
+use crate::api::{self, ApiManifest};
+use crate::assistant::{self, AssistantSummary, TagSuggestions};
+use crate::audit::{audited, AuditEntry, AuditFilter, AuditLog};
+use crate::availability::{Availability, EngineAvailability};
+use crate::background::{self, BackgroundStatus};
+use crate::backend::{Backends, FileStore, GitBackend};
+use crate::batch::{self, BatchOp, BatchReport};
+use crate::bench::{BenchmarkResult, PipelineBench};
+use crate::blob::{BlobHandle, Blobs};
+use crate::blocking::{run_blocking, CancelToken};
+use crate::boot::{self, AutostartStatus, Boot, BootReport};
+use crate::browser::{BrowserProfile, BrowserProfiles, BROWSER_LABEL};
+use crate::clip::{self, ClippedPage};
+use crate::companion::{Companion, CompanionConfig, CompanionDevice, CompanionPairing};
 use crate::config::AppConfig;
-use crate::pty::PtyManager;
-use crate::sidecar::SidecarManager;
-use crate::types::{GitCommit, SoulStatus};
-use crate::watcher::WatcherState;
-
-type ConfigState = Arc<Mutex<AppConfig>>;
+use crate::context_bundle::{self, ContextBundle};
+use crate::crash::{self, CrashConfig, CrashReport, CrashReporter};
+use crate::digest::{self, DigestInfo};
+use crate::downloads::{self, DownloadConfig, Downloads, MediaAttachment};
+use crate::embeddings::{self, EmbeddingIndex, EmbeddingStatus, MemoryHit};
+use crate::engine_update::{self, EngineUpdateReport, EngineVersionInfo};
+use crate::env_layers::{self, EffectiveEnvVar, EnvLayers};
+use crate::env_policy::{self, EnvPolicy};
+use crate::env_schema::{self, EnvKeyInfo};
+use crate::env_watch::{self, EnvWatch};
+use crate::export::{self, ExportInfo};
+use crate::founding::{self, FoundingPreview, FoundingPreviews, OverwriteConfirmation, SoulFiles};
+use crate::founding_template::{self, FoundingAnswers};
+use crate::http::{self, Http, Policy};
+use crate::highlight::{DailyHighlight, Highlights};
+use crate::housekeeping::{self, HousekeepingReport};
+use crate::journal::{self, Journal, JournalOp, RecoveryReport};
+use crate::language::{self, LanguageGuess, SpellcheckReport};
+use crate::layout;
+use crate::lint::{self, LintReport};
+use crate::lock::{self, AppLock, LockStatus};
+use crate::markdown::{self, RenderOptions, RenderedMarkdown};
+use crate::memory_export::{self, MemoryExport};
+use crate::metrics::{Metrics, MetricsConfig};
+use crate::network::{self, NetworkConfig};
+use crate::paths::{self, LayoutReport, SoulLayout};
+use crate::peers::{self, Peer, Peers};
+use crate::permissions::{CommandInvocation, ElevationStatus, Permissions};
+use crate::persona::{self, PersonaFile, PersonaSaveResult};
+use crate::pins::Pins;
+use crate::pii::{self, PiiReport};
+use crate::policy::{PolicyEngine, PolicyInfo};
+use crate::profiles::{self, ProfileStatus, Profiles};
+use crate::protected::{self, WriteConfirmation, WriteConfirmations};
+use crate::proxy::{EngineProxy, MonitorConfig, ProxyLimits, MONITOR_PATH};
+use crate::pty::{PtyInfo, PtyManager, PtyOptions};
+use crate::pty_history::{CommandHistory, CommandRecord};
+use crate::recent::{RecentFile, RecentFiles, SuggestedFile};
+use crate::relocate::{self, RelocationReport};
+use crate::remote::Remote;
+use crate::restore::{RestorePlan, SessionRestore};
+use crate::retention::{self, PurgeReport, RetentionConfig};
+use crate::rollup::{self, RollupConfig, RollupReport};
+use crate::review::{PendingChange, ReviewQueue};
+use crate::routing;
+use crate::safe_path;
+use crate::search::Searches;
+use crate::setup_preflight::{self, SetupPreflight};
+use crate::sidecar::{self, SidecarManager};
+use crate::simulation::{Simulation, SimulationStatus};
+use crate::soul_health::{self, SoulGuard, SoulHealth};
+use crate::soul_identity::{self, SoulIdentity};
+use crate::statelog::{self, StateDistribution, StatelogPage};
+use crate::status::StatusCache;
+use crate::tags::{self, TagCount, TagIndex};
+use crate::stream::{self, StreamInfo, Streams};
+use crate::tasks::{TaskInfo, Tasks};
+use crate::text_stats::{DocumentStats, SoulTextStats, TextStats};
+use crate::timeline::{self, Timeline};
+use crate::transcripts::{self, TranscriptHit, TranscriptIndex, TranscriptPage, TranscriptSummary};
+use crate::vault::{EncryptionStatus, MigrationReport, Vault};
+use crate::vitals::{self, Vitals};
+use crate::types::{BackendHealth, GitCommit, SoulMood, SoulStatus, SubsystemHealth};
+use crate::watcher::{
+    WatchRoot, WatchedRootInfo, WatcherConfig, WatcherHandles, WatcherState,
+};
+
+type ConfigState = Arc<RwLock<AppConfig>>;
 
 fn soul_path(config: &State<ConfigState>) -> PathBuf {
-    config.lock().unwrap().soul_path.clone()
+    config.read().soul_path.clone()
+}
+
+/// Mark a write made through the app as canonical, so neither the write
+/// policy nor review mode treats it as an engine change.
+fn accept_app_write(app: &tauri::AppHandle, name: &str, content: Option<&str>) {
+    app.state::<Arc<PolicyEngine>>().accept(name, content);
+    app.state::<Arc<ReviewQueue>>().accept(name, content);
 }
 
 // --- New commands for product setup ---
 
 #[tauri::command]
-pub fn get_app_state(config: State<ConfigState>) -> String {
-    let cfg = config.lock().unwrap();
-    cfg.app_state().to_string()
+pub fn get_app_state(app: tauri::AppHandle) -> String {
+    boot::app_state(&app).to_string()
 }
 
 #[tauri::command]
@@ -32,8 +116,21 @@ pub fn get_soul_path(config: State<ConfigState>) -> String {
 }
 
 #[tauri::command]
-pub fn set_soul_path(config: State<ConfigState>, path: String) -> Result<(), String> {
-    let p = PathBuf::from(&path);
+pub fn set_soul_path(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    path: String,
+) -> Result<(), String> {
+    let params = serde_json::json!({ "path": path });
+    let result = update_soul_path(&config, &path);
+    if result.is_ok() {
+        app.state::<Arc<StatusCache>>().clear();
+    }
+    audited(&app, "set_soul_path", params, result)
+}
+
+fn update_soul_path(config: &State<ConfigState>, path: &str) -> Result<(), String> {
+    let p = PathBuf::from(path);
     // Security: validate the path
     if !p.is_absolute() {
         return Err("Soul path must be absolute".to_string());
@@ -42,108 +139,288 @@ pub fn set_soul_path(config: State<ConfigState>, path: String) -> Result<(), Str
         return Err("Soul path must be an existing directory".to_string());
     }
     // Block dangerous system directories
-    let danger = ["/", "/etc", "/usr", "/bin", "/sbin", "/var", "/tmp", "/System", "/Library"];
-    let path_str = p.to_string_lossy();
-    for d in &danger {
-        if path_str == *d {
-            return Err("Cannot use a system directory as soul path".to_string());
-        }
+    if relocate::is_system_dir(&p) {
+        return Err("Cannot use a system directory as soul path".to_string());
+    }
+    let mut cfg = config.write();
+    if cfg.soul_path != p {
+        // Adopted again once the soul at the new path is founded
+        cfg.soul_id = None;
     }
-    let mut cfg = config.lock().map_err(|e| e.to_string())?;
     cfg.soul_path = p;
     cfg.first_run = false;
     cfg.save()
 }
 
+/// Result of the soul path check at launch (None during first-run setup),
+/// updated when the soul disappears or is recovered.
 #[tauri::command]
-pub fn write_soul_file(
-    config: State<ConfigState>,
+pub fn get_soul_health(soul_guard: State<Arc<SoulGuard>>) -> Option<SoulHealth> {
+    soul_guard.health()
+}
+
+/// Identity of the current soul (None before founding).
+#[tauri::command]
+pub fn get_soul_identity(config: State<ConfigState>) -> Option<SoulIdentity> {
+    soul_identity::read(&soul_path(&config))
+}
+
+/// Recover from "soul missing" by pointing the app at the soul's current
+/// location. Any soul directory is accepted and becomes the expected one.
+#[tauri::command]
+pub async fn relink_soul(app: tauri::AppHandle, path: String) -> Result<SoulHealth, String> {
+    let params = serde_json::json!({ "path": path });
+    let handle = app.clone();
+    let result = run_blocking(&app, "relink_soul", None, move |_| {
+        soul_health::relink(&handle, Path::new(&path))
+    })
+    .await;
+    audited(&app, "relink_soul", params, result)
+}
+
+/// Recover from "soul missing" by unpacking the newest backup into the
+/// configured soul path.
+#[tauri::command]
+pub async fn restore_latest_backup(app: tauri::AppHandle) -> Result<SoulHealth, String> {
+    let handle = app.clone();
+    let result = run_blocking(&app, "restore_latest_backup", None, move |_| {
+        soul_health::restore_latest_backup(&handle)
+    })
+    .await;
+    audited(&app, "restore_latest_backup", serde_json::json!({}), result)
+}
+
+/// Move (`move_data`) or copy the soul to `new_path`, an empty or new
+/// directory, and switch the app over. Every file is verified after
+/// copying; progress is reported as `task:progress`.
+#[tauri::command]
+pub async fn relocate_soul(
+    app: tauri::AppHandle,
+    new_path: String,
+    move_data: bool,
+    op_id: Option<String>,
+) -> Result<RelocationReport, String> {
+    let params = serde_json::json!({ "new_path": new_path, "move_data": move_data });
+    let handle = app.clone();
+    let result = run_blocking(&app, "relocate_soul", op_id, move |token| {
+        relocate::relocate(&handle, Path::new(&new_path), move_data, token)
+    })
+    .await;
+    audited(&app, "relocate_soul", params, result)
+}
+
+/// Write a soul file. Protected paths need `confirmation_token` from
+/// `request_write_confirmation`; its reason goes into the audit log.
+#[tauri::command]
+pub async fn write_soul_file(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    confirmations: State<'_, Arc<WriteConfirmations>>,
     name: String,
     content: String,
+    confirmation_token: Option<String>,
 ) -> Result<(), String> {
-    // Security: reject path traversal attempts
-    if name.contains("..") {
-        return Err("Access denied: path traversal not allowed".to_string());
+    let mut params = serde_json::json!({ "name": name, "content_len": content.len() });
+
+    // Security: refuse traversal before anything else; resolved again on write
+    if let Err(e) = safe_path::relative(&name) {
+        return audited(&app, "write_soul_file", params, Err(e));
     }
 
+    // Protection applies to the file the write really changes, so a
+    // symlink can't stand in for a protected file
     let sp = soul_path(&config);
-    let file_path = sp.join(&name);
+    let files = app.state::<Arc<Backends>>().files.clone();
+    let guarded = safe_path::resolve_name(files.as_ref(), &sp, &name)
+        .and_then(|target| confirmations.guard(&sp, &target, confirmation_token.as_deref()));
+    match guarded {
+        Ok(Some(reason)) => params["reason"] = reason.into(),
+        Ok(None) => {}
+        Err(e) => return audited(&app, "write_soul_file", params, Err(e)),
+    }
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let recent = app.state::<Arc<RecentFiles>>().inner().clone();
+    accept_app_write(&app, &name, Some(&content));
+    let result = run_blocking(&app, "write_soul_file", None, move |_| {
+        let data = vault.seal(&name, &content)?;
+        write_soul_file_sync(files.as_ref(), &sp, &name, &data)?;
+        recent.edited(&sp, &name);
+        Ok(())
+    })
+    .await;
+    audited(&app, "write_soul_file", params, result)
+}
 
-    // Security: verify resolved path stays within soul directory
-    let sp_canonical = sp.canonicalize().unwrap_or_else(|_| sp.clone());
-    let target = file_path
-        .canonicalize()
-        .unwrap_or_else(|_| {
-            // For new files: canonicalize parent, then append filename
-            if let Some(parent) = file_path.parent() {
-                if let Ok(canonical_parent) = parent.canonicalize() {
-                    if let Some(fname) = file_path.file_name() {
-                        return canonical_parent.join(fname);
-                    }
-                }
-            }
-            file_path.clone()
-        });
-    if !target.starts_with(&sp_canonical) {
-        return Err("Access denied: path outside soul directory".to_string());
+/// Paths that need a confirmation to write (trailing `/`: a directory).
+#[tauri::command]
+pub fn get_protected_paths(config: State<ConfigState>) -> Vec<String> {
+    protected::list(&soul_path(&config))
+}
+
+#[tauri::command]
+pub fn set_protected_paths(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    paths: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let params = serde_json::json!({ "paths": paths });
+    let result = protected::save(&soul_path(&config), paths);
+    audited(&app, "set_protected_paths", params, result)
+}
+
+/// A one-time token to write the protected file `name`, for `reason`.
+#[tauri::command]
+pub fn request_write_confirmation(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    confirmations: State<Arc<WriteConfirmations>>,
+    name: String,
+    reason: String,
+) -> Result<WriteConfirmation, String> {
+    let params = serde_json::json!({ "name": name, "reason": reason });
+    // For the file a write to `name` changes, as `write_soul_file` checks
+    let sp = soul_path(&config);
+    let files = app.state::<Arc<Backends>>().files.clone();
+    let result = safe_path::resolve_name(files.as_ref(), &sp, &name)
+        .and_then(|target| confirmations.request(&sp, &target, &reason));
+    audited(&app, "request_write_confirmation", params, result)
+}
+
+fn write_soul_file_sync(
+    files: &dyn FileStore,
+    sp: &Path,
+    name: &str,
+    content: &[u8],
+) -> Result<(), String> {
+    // Security: the real target, inside the soul directory
+    let file_path = safe_path::safe_resolve(files, sp, name)?;
+
+    // Security: secrets only change through write_env, behind elevation
+    if is_env_file(files, sp, &file_path) {
+        return Err("Access denied: .env is written with write_env".to_string());
     }
 
     // Create parent directories
     if let Some(parent) = file_path.parent() {
-        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        files.create_dir_all(parent).map_err(|e| e.to_string())?;
     }
 
     // Write file
-    fs::write(&file_path, &content).map_err(|e| e.to_string())?;
-
-    // Security: restrict .env file permissions
-    #[cfg(unix)]
-    if name == ".env" {
-        use std::os::unix::fs::PermissionsExt;
-        let perms = std::fs::Permissions::from_mode(0o600);
-        let _ = std::fs::set_permissions(&file_path, perms);
-    }
+    files.write(&file_path, content).map_err(|e| e.to_string())
+}
 
-    Ok(())
+/// Whether the resolved path `file` is the soul's `.env`, in any case (the
+/// filesystem may ignore it).
+fn is_env_file(files: &dyn FileStore, sp: &Path, file: &Path) -> bool {
+    let root = files.canonicalize(sp).unwrap_or_else(|_| sp.to_path_buf());
+    file.parent() == Some(root.as_path())
+        && file
+            .file_name()
+            .is_some_and(|name| name.eq_ignore_ascii_case(".env"))
 }
 
 #[tauri::command]
-pub fn read_env(config: State<ConfigState>) -> Result<HashMap<String, String>, String> {
+pub async fn read_env(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+) -> Result<HashMap<String, String>, String> {
     let sp = soul_path(&config);
+    let files = app.state::<Arc<Backends>>().files.clone();
+    run_blocking(&app, "read_env", None, move |_| read_env_sync(files.as_ref(), &sp)).await
+}
+
+fn read_env_sync(files: &dyn FileStore, sp: &Path) -> Result<HashMap<String, String>, String> {
     let env_path = sp.join(".env");
 
-    if !env_path.exists() {
+    if !files.exists(&env_path) {
         return Ok(HashMap::new());
     }
 
-    let content = fs::read_to_string(&env_path).map_err(|e| e.to_string())?;
-    let mut map = HashMap::new();
+    let content = files.read(&env_path).map_err(|e| e.to_string())?;
+    let content = String::from_utf8(content).map_err(|e| e.to_string())?;
+    Ok(env_watch::parse(&content))
+}
 
-    for line in content.lines() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() || trimmed.starts_with('#') {
-            continue;
-        }
-        if let Some((key, val)) = trimmed.split_once('=') {
-            let val = val.trim().trim_matches('"').trim_matches('\'');
-            map.insert(key.trim().to_string(), val.to_string());
-        }
-    }
+/// A sidecar's merged environment and the layer each value came from
+/// (system, app, soul, session, launch); secrets masked.
+#[tauri::command]
+pub fn get_effective_env(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    process: String,
+) -> Result<Vec<EffectiveEnvVar>, String> {
+    env_layers::effective(&app, &process, &soul_path(&config))
+}
+
+/// Override a variable until the app quits, for one sidecar or (without
+/// `process`) all of them. A null value removes the override. Applies from
+/// the next start.
+#[tauri::command]
+pub fn set_session_env(
+    app: tauri::AppHandle,
+    layers: State<Arc<EnvLayers>>,
+    name: String,
+    value: Option<String>,
+    process: Option<String>,
+) -> Result<(), String> {
+    let params = serde_json::json!({ "name": name, "process": process, "set": value.is_some() });
+    let result = layers.set_session(process.as_deref(), &name, value);
+    audited(&app, "set_session_env", params, result)
+}
 
-    Ok(map)
+#[tauri::command]
+pub fn clear_session_env(app: tauri::AppHandle, layers: State<Arc<EnvLayers>>) {
+    layers.clear_session();
+    let _ = audited(&app, "clear_session_env", serde_json::json!({}), Ok(()));
 }
 
+/// Set an app-level default every soul's sidecars inherit unless the
+/// soul's .env sets it too. A null value removes it.
 #[tauri::command]
-pub fn write_env(
-    config: State<ConfigState>,
+pub fn set_env_default(
+    app: tauri::AppHandle,
+    name: String,
+    value: Option<String>,
+) -> Result<(), String> {
+    let params = serde_json::json!({ "name": name, "set": value.is_some() });
+    audited(&app, "set_env_default", params, env_layers::set_default(&name, value))
+}
+
+#[tauri::command]
+pub async fn write_env(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
     entries: HashMap<String, String>,
 ) -> Result<(), String> {
     let sp = soul_path(&config);
+    // Only key names are logged — values may be API keys
+    let params = serde_json::json!({ "keys": entries.keys().collect::<Vec<_>>() });
+    let files = app.state::<Arc<Backends>>().files.clone();
+    let watch = app.state::<Arc<EnvWatch>>().inner().clone();
+    let env_dir = sp.clone();
+    let result = run_blocking(&app, "write_env", None, move |_| {
+        watch.update(&env_dir, || write_env_sync(files.as_ref(), &env_dir, &entries))
+    })
+    .await;
+    if let Some(change) = audited(&app, "write_env", params, result)? {
+        env_watch::react(&app, &sp, change).await;
+    }
+    Ok(())
+}
+
+fn write_env_sync(
+    files: &dyn FileStore,
+    sp: &Path,
+    entries: &HashMap<String, String>,
+) -> Result<(), String> {
     let env_path = sp.join(".env");
 
     // Read existing file to preserve comments and order
-    let existing = if env_path.exists() {
-        fs::read_to_string(&env_path).unwrap_or_default()
+    let existing = if files.exists(&env_path) {
+        files
+            .read(&env_path)
+            .map(|data| String::from_utf8_lossy(&data).to_string())
+            .unwrap_or_default()
     } else {
         String::new()
     };
@@ -173,7 +450,7 @@ pub fn write_env(
     }
 
     // Append new keys not in original file
-    for (key, val) in &entries {
+    for (key, val) in entries {
         if !written_keys.contains(key) {
             result_lines.push(format!("{}={}", key, val));
         }
@@ -181,30 +458,35 @@ pub fn write_env(
 
     // Ensure parent directory exists
     if let Some(parent) = env_path.parent() {
-        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        files.create_dir_all(parent).map_err(|e| e.to_string())?;
     }
 
     let content = result_lines.join("\n") + "\n";
-    fs::write(&env_path, &content).map_err(|e| e.to_string())?;
+    files
+        .write(&env_path, content.as_bytes())
+        .map_err(|e| e.to_string())?;
 
     // Security: restrict .env file permissions (contains API keys)
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let perms = std::fs::Permissions::from_mode(0o600);
-        let _ = std::fs::set_permissions(&env_path, perms);
-    }
+    files.restrict(&env_path);
 
     Ok(())
 }
 
+/// Descriptions, types and defaults of the engine's .env keys, for the
+/// env editor.
+#[tauri::command]
+pub fn get_env_schema() -> Vec<EnvKeyInfo> {
+    env_schema::schema()
+}
+
 #[tauri::command]
 pub fn check_node(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
     use crate::node;
 
     match node::find_node(Some(&app)) {
         Some(node_path) => {
-            let version = node::node_version(&node_path)
+            let backends = app.state::<Arc<Backends>>();
+            let version = node::node_version(backends.processes.as_ref(), &node_path)
                 .unwrap_or_else(|| "unknown".to_string());
             Ok(serde_json::json!({
                 "found": true,
@@ -220,37 +502,44 @@ pub fn check_node(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
     }
 }
 
+/// Whether a soul can be created at `path` (default: the configured soul
+/// path): free space, write permission, case sensitivity and path length.
+/// The setup wizard runs it before creating anything.
+#[tauri::command]
+pub async fn preflight_soul_path(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    path: Option<String>,
+) -> Result<SetupPreflight, String> {
+    let path = path.map(PathBuf::from).unwrap_or_else(|| soul_path(&config));
+    run_blocking(&app, "preflight_soul_path", None, move |_| {
+        Ok(setup_preflight::run(&path))
+    })
+    .await
+}
+
 #[tauri::command]
-pub fn create_soul_directories(config: State<ConfigState>) -> Result<(), String> {
-    let sp = soul_path(&config);
-
-    let dirs = [
-        "",
-        "seele",
-        "seele/beziehungen",
-        "erinnerungen",
-        "erinnerungen/kern",
-        "erinnerungen/episodisch",
-        "erinnerungen/semantisch",
-        "erinnerungen/emotional",
-        "erinnerungen/archiv",
-        "heartbeat",
-        "zustandslog",
-        "memory",
-        "connections",
-        // English variants
-        "soul",
-        "soul/relationships",
-        "memories",
-        "memories/core",
-        "memories/episodic",
-        "memories/semantic",
-        "memories/emotional",
-        "memories/archive",
-        "statelog",
-    ];
-
-    for dir in &dirs {
+pub async fn create_soul_directories(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    op_id: Option<String>,
+) -> Result<(), String> {
+    let sp = soul_path(&config);
+    let params = serde_json::json!({ "soul_path": sp });
+    let result = run_blocking(&app, "create_soul_directories", op_id, move |token| {
+        create_soul_directories_sync(&sp, token)
+    })
+    .await;
+    audited(&app, "create_soul_directories", params, result)
+}
+
+fn create_soul_directories_sync(sp: &Path, token: &CancelToken) -> Result<(), String> {
+    let mut dirs = vec![""];
+    dirs.extend(paths::scaffold_dirs(sp));
+
+    for (i, dir) in dirs.iter().enumerate() {
+        token.check()?;
+        token.set_progress(i as u64, dirs.len() as u64);
         let path = sp.join(dir);
         fs::create_dir_all(&path).map_err(|e| format!("Failed to create {}: {}", dir, e))?;
     }
@@ -258,626 +547,3082 @@ pub fn create_soul_directories(config: State<ConfigState>) -> Result<(), String>
     Ok(())
 }
 
-// --- Existing commands updated to use config ---
+/// The soul's directory naming scheme; None while it doesn't show one.
+#[tauri::command]
+pub fn get_soul_layout(config: State<ConfigState>) -> Option<SoulLayout> {
+    paths::detect(&soul_path(&config))
+}
 
+/// Move the soul to the directory names of `target_locale` ("de" or "en"):
+/// directories, markdown links and encrypted directories, committed as one
+/// git commit. Journaled, so an interrupted migration finishes on the next
+/// start.
 #[tauri::command]
-pub fn get_soul_status(config: State<ConfigState>) -> Result<SoulStatus, String> {
+pub async fn migrate_soul_layout(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    target_locale: String,
+    op_id: Option<String>,
+) -> Result<LayoutReport, String> {
     let sp = soul_path(&config);
-    let seed_path = sp.join("SEED.md");
-
-    if !seed_path.exists() {
-        return Err("SEED.md not found".to_string());
-    }
-
-    let content = fs::read_to_string(&seed_path).map_err(|e| e.to_string())?;
-    let seed_size = fs::metadata(&seed_path)
-        .map(|m| m.len())
-        .unwrap_or(0);
-
-    // Parse basic info from SEED.md header
-    let mut name = String::from("Soul");
-    let mut born = String::from("unknown");
-    let mut sessions: u32 = 0;
-    let mut model = String::from("unknown");
-    let mut state = String::new();
-    let mut mood = String::new();
-
-    for line in content.lines() {
-        if line.starts_with("#SEED") {
-            continue;
-        }
-        if line.starts_with("#geboren:") || line.starts_with("#born:") {
-            for part in line.split_whitespace() {
-                if let Some(val) = part.strip_prefix("#geboren:").or(part.strip_prefix("#born:")) {
-                    born = val.to_string();
-                }
-                if let Some(val) = part.strip_prefix("#sessions:") {
-                    sessions = val.parse().unwrap_or(0);
-                }
-            }
-        }
-        if line.contains("modell:") || line.contains("model:") {
-            if let Some(idx) = line.find("modell:").or(line.find("model:")) {
-                let rest = &line[idx..];
-                let val = rest
-                    .split('|')
-                    .next()
-                    .unwrap_or("")
-                    .split(':')
-                    .nth(1)
-                    .unwrap_or("")
-                    .trim();
-                model = val.to_string();
-            }
-        }
-        if line.contains("zustand:") || line.contains("state:") {
-            if let Some(idx) = line.find("zustand:").or(line.find("state:")) {
-                let rest = &line[idx..];
-                let val = rest
-                    .split('|')
-                    .next()
-                    .unwrap_or("")
-                    .split(':')
-                    .nth(1)
-                    .unwrap_or("")
-                    .trim();
-                state = val.to_string();
-            }
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let journal = app.state::<Arc<Journal>>().inner().clone();
+    let git = app.state::<Arc<Backends>>().git.clone();
+    let config = config.inner().clone();
+    let result = match layout::parse_locale(&target_locale) {
+        Ok(target) => {
+            run_blocking(&app, "migrate_soul_layout", op_id, move |token| {
+                let _entry = journal.begin(JournalOp::Layout {
+                    soul_path: sp.to_string_lossy().to_string(),
+                    layout: target,
+                })?;
+                let report = layout::migrate(&sp, target, git.as_ref(), &vault, token)?;
+                let mut cfg = config.write();
+                cfg.encryption = vault.config();
+                cfg.save()?;
+                Ok(report)
+            })
+            .await
         }
-    }
+        Err(e) => Err(e),
+    };
+    let params = match &result {
+        Ok(r) => serde_json::json!({
+            "target_locale": target_locale,
+            "moved": r.moved.len(),
+            "links_updated": r.links_updated.len(),
+            "conflicts": r.conflicts.len(),
+            "commit": r.commit,
+        }),
+        Err(_) => serde_json::json!({ "target_locale": target_locale }),
+    };
+    audited(&app, "migrate_soul_layout", params, result)
+}
 
-    // Derive mood from state
-    if !state.is_empty() {
-        mood = state.split(',').next().unwrap_or("").trim().to_string();
-    }
+// --- Existing commands updated to use config ---
 
-    // Try to get name from @META or project
-    if content.contains("projekt:seele") || content.contains("project:soul") {
-        name = String::from("Seele");
-    }
+/// Vital signs parsed from the latest heartbeat logs; None before the
+/// first heartbeat.
+#[tauri::command]
+pub async fn get_vitals(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+) -> Result<Option<Vitals>, String> {
+    let sp = soul_path(&config);
+    run_blocking(&app, "get_vitals", None, move |_| Ok(vitals::read(&sp))).await
+}
 
-    Ok(SoulStatus {
-        name,
-        born,
-        sessions,
-        model,
-        state,
-        mood,
-        seed_size,
+/// A passage from the garden or dreams (or a recent emotional memory),
+/// the same one all day; None while the soul hasn't written any.
+#[tauri::command]
+pub async fn get_daily_highlight(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+) -> Result<Option<DailyHighlight>, String> {
+    let sp = soul_path(&config);
+    let highlights = app.state::<Arc<Highlights>>().inner().clone();
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    run_blocking(&app, "get_daily_highlight", None, move |_| {
+        Ok(highlights.get(&sp, &vault))
     })
+    .await
 }
 
+/// Parsed state snapshots, newest first, optionally limited to the days
+/// `from`..=`to` (YYYY-MM-DD).
 #[tauri::command]
-pub fn read_soul_file(config: State<ConfigState>, name: String) -> Result<String, String> {
+pub async fn list_statelog(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    from: Option<String>,
+    to: Option<String>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    op_id: Option<String>,
+) -> Result<StatelogPage, String> {
     let sp = soul_path(&config);
-    let file_path = sp.join(&name);
+    let from = from.as_deref().map(statelog::parse_date).transpose()?;
+    let to = to.as_deref().map(statelog::parse_date).transpose()?;
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    run_blocking(&app, "list_statelog", op_id, move |token| {
+        statelog::list(
+            &sp,
+            &vault,
+            from,
+            to,
+            offset.unwrap_or(0),
+            limit.unwrap_or(statelog::PAGE_SIZE),
+            token,
+        )
+    })
+    .await
+}
 
-    // Security: prevent path traversal
-    let canonical = file_path.canonicalize().map_err(|e| e.to_string())?;
-    let soul_canonical = sp.canonicalize().map_err(|e| e.to_string())?;
-    if !canonical.starts_with(&soul_canonical) {
-        return Err("Access denied: path outside soul directory".to_string());
-    }
+/// How often each state was logged over the last `days` days (default 30).
+#[tauri::command]
+pub async fn get_state_distribution(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    days: Option<u32>,
+) -> Result<StateDistribution, String> {
+    let sp = soul_path(&config);
+    run_blocking(&app, "get_state_distribution", None, move |_| {
+        Ok(statelog::distribution(&sp, days.unwrap_or(30)))
+    })
+    .await
+}
 
-    fs::read_to_string(&canonical).map_err(|e| e.to_string())
+#[tauri::command]
+pub async fn get_soul_status(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+) -> Result<SoulStatus, String> {
+    let sp = soul_path(&config);
+    let cache = app.state::<Arc<StatusCache>>().inner().clone();
+    run_blocking(&app, "get_soul_status", None, move |_| cache.get(&sp)).await
 }
 
 #[tauri::command]
-pub fn get_active_nodes(state: State<WatcherState>) -> HashMap<String, f64> {
-    state.get_active_nodes_map()
+pub async fn read_soul_file(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    name: String,
+) -> Result<String, String> {
+    let sp = soul_path(&config);
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let files = app.state::<Arc<Backends>>().files.clone();
+    let recent = app.state::<Arc<RecentFiles>>().inner().clone();
+    run_blocking(&app, "read_soul_file", None, move |_| {
+        let content = read_soul_file_sync(files.as_ref(), &vault, &sp, &name)?;
+        recent.opened(&sp, &name);
+        stream::limit(content, &name, "stream_soul_file")
+    })
+    .await
 }
 
+/// `read_soul_file` for files over the IPC limit: the content arrives as
+/// `stream:chunk` events for `stream_id`.
 #[tauri::command]
-pub fn get_is_working(state: State<WatcherState>) -> bool {
-    state.is_working()
+pub async fn stream_soul_file(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    streams: State<'_, Arc<Streams>>,
+    name: String,
+    stream_id: String,
+) -> Result<StreamInfo, String> {
+    let sp = soul_path(&config);
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let files = app.state::<Arc<Backends>>().files.clone();
+    let recent = app.state::<Arc<RecentFiles>>().inner().clone();
+    let content = run_blocking(&app, "stream_soul_file", None, move |_| {
+        let content = read_soul_file_sync(files.as_ref(), &vault, &sp, &name)?;
+        recent.opened(&sp, &name);
+        Ok(content)
+    })
+    .await?;
+    streams.start(&app, stream_id, content)
 }
 
+/// Short summary of a soul file from the assistant (the founding server's
+/// LLM), with the soul's name and SEED excerpt as context.
 #[tauri::command]
-pub fn start_engine(
-    sidecar: State<std::sync::Arc<SidecarManager>>,
+pub async fn assistant_summarize(
     app: tauri::AppHandle,
-) -> Result<(), String> {
-    sidecar.start_engine(&app)
+    config: State<'_, ConfigState>,
+    file: String,
+) -> Result<AssistantSummary, String> {
+    let sp = soul_path(&config);
+    let content = read_for_assistant(&app, &sp, &file).await?;
+    assistant::summarize(&app, sp, file, content).await
 }
 
+/// Tags the assistant suggests for a soul file, the soul's existing tags
+/// preferred. Nothing is written; apply them with `retag_file`.
 #[tauri::command]
-pub fn stop_engine(
-    sidecar: State<std::sync::Arc<SidecarManager>>,
+pub async fn assistant_suggest_tags(
     app: tauri::AppHandle,
-) -> Result<(), String> {
-    sidecar.stop_engine(&app)
+    config: State<'_, ConfigState>,
+    file: String,
+) -> Result<TagSuggestions, String> {
+    let sp = soul_path(&config);
+    let content = read_for_assistant(&app, &sp, &file).await?;
+    assistant::suggest_tags(&app, sp, file, content).await
 }
 
+/// Soul context for pasting into another AI tool: SEED meta, the memory
+/// passages closest to `focus`, the active nodes' files and recent
+/// memories, within `budget_tokens`. Copied to the clipboard unless
+/// `target` is "file" (a temp file) or "none".
 #[tauri::command]
-pub fn get_sidecar_status(
-    sidecar: State<std::sync::Arc<SidecarManager>>,
-) -> crate::sidecar::SidecarStatus {
-    sidecar.get_status()
+pub async fn build_context_bundle(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    budget_tokens: Option<usize>,
+    focus: Option<String>,
+    target: Option<String>,
+) -> Result<ContextBundle, String> {
+    let sp = soul_path(&config);
+    context_bundle::build(&app, sp, budget_tokens, focus, target).await
 }
 
-// --- Founding Commands ---
+async fn read_for_assistant(
+    app: &tauri::AppHandle,
+    sp: &Path,
+    file: &str,
+) -> Result<String, String> {
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let files = app.state::<Arc<Backends>>().files.clone();
+    let (sp, file) = (sp.to_path_buf(), file.to_string());
+    run_blocking(app, "read_for_assistant", None, move |_| {
+        read_soul_file_sync(files.as_ref(), &vault, &sp, &file)
+    })
+    .await
+}
 
+/// Memory passages closest in meaning to `query` (embeddings from the
+/// soul's Ollama server or OpenAI). Changed memories are embedded first.
 #[tauri::command]
-pub fn start_founding(
-    config: State<ConfigState>,
-    founding: State<std::sync::Arc<crate::founding::FoundingServer>>,
+pub async fn semantic_search(
     app: tauri::AppHandle,
-) -> Result<u16, String> {
+    config: State<'_, ConfigState>,
+    index: State<'_, Arc<EmbeddingIndex>>,
+    query: String,
+    k: Option<usize>,
+) -> Result<Vec<MemoryHit>, String> {
     let sp = soul_path(&config);
-    founding.start(&app, &sp)
+    let k = k.unwrap_or(embeddings::DEFAULT_K).clamp(1, 100);
+    index.search(&app, &sp, &query, k).await
 }
 
+/// Memories most similar to the file being viewed, for the editor sidebar.
 #[tauri::command]
-pub fn stop_founding(
-    founding: State<std::sync::Arc<crate::founding::FoundingServer>>,
-) -> Result<(), String> {
-    founding.stop()
+pub async fn get_related(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    index: State<'_, Arc<EmbeddingIndex>>,
+    name: String,
+    k: Option<usize>,
+) -> Result<Vec<MemoryHit>, String> {
+    let sp = soul_path(&config);
+    let k = k.unwrap_or(embeddings::DEFAULT_K).clamp(1, 100);
+    index.related(&app, &sp, &name, k).await
 }
 
+/// Bring the memory embeddings up to date without searching.
 #[tauri::command]
-pub async fn founding_chat(
-    founding: State<'_, std::sync::Arc<crate::founding::FoundingServer>>,
-    message: String,
-    history: Vec<serde_json::Value>,
-) -> Result<serde_json::Value, String> {
-    let port = founding.port();
-    let url = format!("http://127.0.0.1:{}/chat", port);
-
-    let body = serde_json::json!({
-        "message": message,
-        "history": history,
-    });
-
-    let client = reqwest::Client::new();
-    let resp = client
-        .post(&url)
-        .json(&body)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to reach founding server: {}", e))?;
-
-    let json: serde_json::Value = resp
-        .json()
-        .await
-        .map_err(|e| format!("Invalid response from founding server: {}", e))?;
+pub async fn index_memories(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    index: State<'_, Arc<EmbeddingIndex>>,
+) -> Result<EmbeddingStatus, String> {
+    let sp = soul_path(&config);
+    index.update(&app, &sp).await
+}
 
-    Ok(json)
+/// Tags used in memory files (frontmatter `tags:` and inline `#tag`), most
+/// used first.
+#[tauri::command]
+pub async fn list_tags(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+) -> Result<Vec<TagCount>, String> {
+    let sp = soul_path(&config);
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let index = app.state::<Arc<TagIndex>>().inner().clone();
+    run_blocking(&app, "list_tags", None, move |_| Ok(index.list(&sp, &vault))).await
 }
 
+/// Memory files tagged `tag` or a tag nested below it (`project/…`).
 #[tauri::command]
-pub async fn founding_create(
-    founding: State<'_, std::sync::Arc<crate::founding::FoundingServer>>,
-    history: Vec<serde_json::Value>,
-) -> Result<serde_json::Value, String> {
-    let port = founding.port();
-    let url = format!("http://127.0.0.1:{}/create", port);
+pub async fn get_files_by_tag(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    tag: String,
+) -> Result<Vec<String>, String> {
+    let sp = soul_path(&config);
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let index = app.state::<Arc<TagIndex>>().inner().clone();
+    run_blocking(&app, "get_files_by_tag", None, move |_| {
+        Ok(index.files_with(&sp, &vault, &tag))
+    })
+    .await
+}
 
-    let body = serde_json::json!({ "history": history });
+/// Add tags to a memory file's frontmatter and remove tags from it
+/// (frontmatter and inline). Returns the file's tags afterwards.
+#[tauri::command]
+pub async fn retag_file(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    name: String,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let params = serde_json::json!({ "name": name, "add": add, "remove": remove });
+    if let Err(e) = safe_path::relative(&name) {
+        return audited(&app, "retag_file", params, Err(e));
+    }
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(120))
-        .build()
-        .map_err(|e| e.to_string())?;
+    let sp = soul_path(&config);
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let files = app.state::<Arc<Backends>>().files.clone();
+    let index = app.state::<Arc<TagIndex>>().inner().clone();
+    let handle = app.clone();
+    let result = run_blocking(&app, "retag_file", None, move |_| {
+        let content = read_soul_file_sync(files.as_ref(), &vault, &sp, &name)?;
+        let (content, tags) = tags::retag(&content, &add, &remove);
+        accept_app_write(&handle, &name, Some(&content));
+        let data = vault.seal(&name, &content)?;
+        write_soul_file_sync(files.as_ref(), &sp, &name, &data)?;
+        index.set(&name.replace('\\', "/"), Some(tags.clone()));
+        Ok(tags.into_iter().collect())
+    })
+    .await;
+    audited(&app, "retag_file", params, result)
+}
 
-    let resp = client
-        .post(&url)
-        .json(&body)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to reach founding server: {}", e))?;
+/// Soul files opened or edited most recently, newest first.
+#[tauri::command]
+pub fn get_recent_files(
+    config: State<ConfigState>,
+    recent: State<Arc<RecentFiles>>,
+    limit: Option<usize>,
+) -> Vec<RecentFile> {
+    recent.recent(&soul_path(&config), limit.unwrap_or(20).clamp(1, 200))
+}
 
-    let json: serde_json::Value = resp
-        .json()
-        .await
-        .map_err(|e| format!("Invalid response: {}", e))?;
+/// Files to offer next, from how often and recently they were used and
+/// which brain nodes are active right now.
+#[tauri::command]
+pub fn get_suggested_files(
+    config: State<ConfigState>,
+    recent: State<Arc<RecentFiles>>,
+    watcher: State<WatcherState>,
+) -> Vec<SuggestedFile> {
+    recent.suggested(&soul_path(&config), &watcher.get_active_nodes_map())
+}
 
-    Ok(json)
+/// Quick-access files of the soul, in pinning order.
+#[tauri::command]
+pub fn list_pinned(config: State<ConfigState>, pins: State<Arc<Pins>>) -> Vec<String> {
+    pins.list(&soul_path(&config))
 }
 
-// --- Engine Monitor Proxy ---
+/// Pin a soul file for the quick-access panel. Returns the pins.
+#[tauri::command]
+pub fn pin_file(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    pins: State<Arc<Pins>>,
+    name: String,
+) -> Result<Vec<String>, String> {
+    pins.pin(&app, &soul_path(&config), &name)
+}
 
 #[tauri::command]
-pub async fn fetch_engine_subsystems(
+pub fn unpin_file(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    pins: State<Arc<Pins>>,
+    name: String,
+) -> Result<Vec<String>, String> {
+    pins.unpin(&app, &soul_path(&config), &name)
+}
+
+/// Hand a large or binary soul file (media, big JSONL) to the webview as a
+/// one-time `soul-blob://` URL instead of through IPC.
+#[tauri::command]
+pub async fn share_soul_file(
+    app: tauri::AppHandle,
     config: State<'_, ConfigState>,
-) -> Result<serde_json::Value, String> {
+    name: String,
+) -> Result<BlobHandle, String> {
     let sp = soul_path(&config);
-    let env_path = sp.join(".env");
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let files = app.state::<Arc<Backends>>().files.clone();
+    let blobs = app.state::<Arc<Blobs>>().inner().clone();
+    let recent = app.state::<Arc<RecentFiles>>().inner().clone();
+    run_blocking(&app, "share_soul_file", None, move |_| {
+        let handle = blobs.share_soul_file(files.as_ref(), &vault, &sp, &name)?;
+        recent.opened(&sp, &name);
+        Ok(handle)
+    })
+    .await
+}
 
-    // Read port and key from .env
-    let mut port: u16 = 3001;
-    let mut api_key = String::new();
+/// The consumer of `stream_id` has processed chunk `seq`.
+#[tauri::command]
+pub fn stream_ack(streams: State<Arc<Streams>>, stream_id: String, seq: u32) {
+    streams.ack(&stream_id, seq);
+}
 
-    if let Ok(content) = fs::read_to_string(&env_path) {
-        for line in content.lines() {
-            let trimmed = line.trim();
-            if let Some(val) = trimmed.strip_prefix("API_PORT=") {
-                if let Ok(p) = val.trim().trim_matches('"').parse::<u16>() {
-                    port = p;
-                }
-            }
-            if let Some(val) = trimmed.strip_prefix("API_KEY=") {
-                api_key = val.trim().trim_matches('"').to_string();
-            }
-        }
-    }
+#[tauri::command]
+pub fn cancel_stream(streams: State<Arc<Streams>>, stream_id: String) {
+    streams.cancel(&stream_id);
+}
+
+fn read_soul_file_sync(
+    files: &dyn FileStore,
+    vault: &Vault,
+    sp: &Path,
+    name: &str,
+) -> Result<String, String> {
+    let path = safe_path::safe_resolve(files, sp, name)?;
+    vault.decode(files.read(&path).map_err(|e| e.to_string())?)
+}
+
+#[tauri::command]
+pub fn get_active_nodes(state: State<WatcherState>) -> HashMap<String, f64> {
+    state.get_active_nodes_map()
+}
+
+#[tauri::command]
+pub fn get_is_working(state: State<WatcherState>) -> bool {
+    state.is_working()
+}
 
-    let url = format!("http://127.0.0.1:{}/api/monitor", port);
-    let client = reqwest::Client::new();
-    let mut req = client.get(&url);
-    if !api_key.is_empty() {
-        req = req.header("Authorization", format!("Bearer {}", api_key));
+#[tauri::command]
+pub fn get_mood(state: State<WatcherState>) -> Option<SoulMood> {
+    state.get_mood()
+}
+
+/// Update watcher tuning live and persist it.
+#[tauri::command]
+pub fn set_watcher_config(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    watcher: WatcherConfig,
+) -> Result<(), String> {
+    if let Some(state) = app.try_state::<WatcherState>() {
+        state.set_config(watcher.clone());
+    }
+    {
+        let mut cfg = config.write();
+        cfg.watcher = watcher;
+        cfg.save()?;
     }
+    // Keep the runtime profile's overrides on top
+    profiles::apply(&app);
+    Ok(())
+}
 
-    let resp = req
-        .timeout(std::time::Duration::from_secs(2))
-        .send()
-        .await
-        .map_err(|e| format!("Engine unreachable: {}", e))?;
+/// Which backend (native or polling) watches each root.
+#[tauri::command]
+pub fn get_watcher_info(handles: State<WatcherHandles>) -> Vec<WatchedRootInfo> {
+    handles.info()
+}
 
-    if !resp.status().is_success() {
-        return Err(format!("Engine returned {}", resp.status()));
+/// Replace the additional watch roots and restart their watchers.
+#[tauri::command]
+pub fn set_watch_roots(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    roots: Vec<WatchRoot>,
+) -> Result<(), String> {
+    for root in &roots {
+        if !root.path.is_absolute() {
+            return Err(format!("Watch root must be absolute: {}", root.path.display()));
+        }
     }
 
-    let data: serde_json::Value = resp
-        .json()
-        .await
-        .map_err(|e| format!("Invalid JSON: {}", e))?;
+    if let (Some(state), Some(handles)) = (
+        app.try_state::<WatcherState>(),
+        app.try_state::<WatcherHandles>(),
+    ) {
+        handles.replace_extra(crate::watcher::start_extra_watchers(&app, &state, &roots));
+    }
 
-    Ok(data)
+    let mut cfg = config.write();
+    cfg.watch_roots = roots;
+    cfg.save()
 }
 
-// --- Chain Commands ---
+/// Switch decay timing to a named preset ("default", "calm", "lively").
+#[tauri::command]
+pub fn apply_watcher_preset(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    name: String,
+) -> Result<WatcherConfig, String> {
+    let preset = config
+        .read()
+        .watcher
+        .with_preset(&name)
+        .ok_or_else(|| format!("Unknown watcher preset: {}", name))?;
+    if let Some(state) = app.try_state::<WatcherState>() {
+        state.set_config(preset.clone());
+    }
+    let mut cfg = config.write();
+    cfg.watcher = preset.clone();
+    cfg.save()?;
+    Ok(preset)
+}
 
 #[tauri::command]
-pub fn start_chain(
+pub fn start_engine(
     sidecar: State<std::sync::Arc<SidecarManager>>,
     app: tauri::AppHandle,
 ) -> Result<(), String> {
-    sidecar.start_chain(&app)
+    audited(&app, "start_engine", serde_json::json!({}), sidecar.start_engine(&app))
 }
 
 #[tauri::command]
-pub fn stop_chain(
+pub fn stop_engine(
     sidecar: State<std::sync::Arc<SidecarManager>>,
     app: tauri::AppHandle,
 ) -> Result<(), String> {
-    sidecar.stop_chain(&app)
+    audited(&app, "stop_engine", serde_json::json!({}), sidecar.stop_engine(&app))
 }
 
+/// Start what the app state needs in dependency order (watcher, chain,
+/// engine, or the founding server), reporting each step as `boot:progress`.
 #[tauri::command]
-pub fn get_chain_status(
-    sidecar: State<std::sync::Arc<SidecarManager>>,
-) -> crate::sidecar::SidecarStatus {
-    sidecar.get_chain_status()
+pub async fn boot_all(app: tauri::AppHandle) -> Result<BootReport, String> {
+    let boot = app.state::<Arc<Boot>>().inner().clone();
+    let handle = app.clone();
+    let result = run_blocking(&app, "boot_all", None, move |_| Ok(boot.boot_all(&handle))).await;
+    audited(&app, "boot_all", serde_json::json!({}), result)
 }
 
-// --- PTY Commands ---
+/// Stop the engine, chain and founding server, dependents first.
+#[tauri::command]
+pub async fn shutdown_all(app: tauri::AppHandle) -> Result<BootReport, String> {
+    let boot = app.state::<Arc<Boot>>().inner().clone();
+    let handle = app.clone();
+    let result =
+        run_blocking(&app, "shutdown_all", None, move |_| Ok(boot.shutdown_all(&handle))).await;
+    audited(&app, "shutdown_all", serde_json::json!({}), result)
+}
 
+/// Freeze the engine in place (SIGSTOP); memory and uptime survive.
 #[tauri::command]
-pub fn create_pty(
-    pty: State<std::sync::Arc<PtyManager>>,
+pub fn pause_engine(
+    sidecar: State<std::sync::Arc<SidecarManager>>,
     app: tauri::AppHandle,
-    cols: u16,
-    rows: u16,
-) -> Result<u32, String> {
-    pty.create(&app, cols, rows)
+) -> Result<(), String> {
+    audited(&app, "pause_engine", serde_json::json!({}), sidecar.pause_engine(&app))
 }
 
 #[tauri::command]
-pub fn write_pty(
-    pty: State<std::sync::Arc<PtyManager>>,
-    id: u32,
-    data: String,
+pub fn resume_engine(
+    sidecar: State<std::sync::Arc<SidecarManager>>,
+    app: tauri::AppHandle,
 ) -> Result<(), String> {
-    pty.write(id, &data)
+    audited(&app, "resume_engine", serde_json::json!({}), sidecar.resume_engine(&app))
 }
 
+/// Apply config changes without a full stop/start: the engine's reload
+/// endpoint if it has one, else SIGHUP or a fast restart. Returns the method
+/// used ("endpoint", "signal" or "restart").
 #[tauri::command]
-pub fn resize_pty(
-    pty: State<std::sync::Arc<PtyManager>>,
-    id: u32,
-    cols: u16,
-    rows: u16,
-) -> Result<(), String> {
-    pty.resize(id, cols, rows)
+pub async fn reload_engine(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+) -> Result<String, String> {
+    let sp = soul_path(&config);
+    let result = sidecar::reload(&app, &sp).await;
+    audited(&app, "reload_engine", serde_json::json!({}), result)
 }
 
+/// Engine versions (active, bundled, deployed, previous, dev), compatibility
+/// with this SoulOS release and the last update.
 #[tauri::command]
-pub fn close_pty(
-    pty: State<std::sync::Arc<PtyManager>>,
-    id: u32,
-) -> Result<(), String> {
-    pty.close(id)
+pub fn get_engine_version(
+    app: tauri::AppHandle,
+    sidecar: State<Arc<SidecarManager>>,
+) -> EngineVersionInfo {
+    engine_update::info(&app, &sidecar)
 }
 
-// --- State Versioning Commands (Git) ---
+/// Switch back to the engine version that was deployed before the last update.
+#[tauri::command]
+pub async fn rollback_engine(app: tauri::AppHandle) -> Result<EngineUpdateReport, String> {
+    let sidecar = app.state::<Arc<SidecarManager>>().inner().clone();
+    let handle = app.clone();
+    let result = run_blocking(&app, "rollback_engine", None, move |_| {
+        engine_update::rollback(&handle, &sidecar)
+    })
+    .await;
+    audited(&app, "rollback_engine", serde_json::json!({}), result)
+}
 
-/// Find the git root: either soul_path itself or soul_path/seelen-protokoll
-fn git_root(config: &State<ConfigState>) -> Option<PathBuf> {
-    let sp = soul_path(config);
-    if sp.join(".git").exists() {
-        return Some(sp);
-    }
-    let proto = sp.join("seelen-protokoll");
-    if proto.join(".git").exists() {
-        return Some(proto);
-    }
-    None
+#[tauri::command]
+pub fn get_reload_on_config_change(config: State<ConfigState>) -> bool {
+    config.read().reload_on_config_change
 }
 
+/// Whether saving .env in the env editor reloads the engine.
 #[tauri::command]
-pub fn get_state_history(
+pub fn set_reload_on_config_change(
+    app: tauri::AppHandle,
     config: State<ConfigState>,
-    limit: Option<u32>,
-) -> Result<Vec<GitCommit>, String> {
-    let repo = match git_root(&config) {
-        Some(p) => p,
-        None => return Ok(Vec::new()),
-    };
-
-    let n = limit.unwrap_or(50);
-    let output = Command::new("git")
-        .args(["log", "--format=%H|%ai|%s", "-n", &n.to_string(), "--shortstat"])
-        .current_dir(&repo)
-        .output()
-        .map_err(|e| format!("git log failed: {}", e))?;
-
-    if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).to_string());
-    }
-
-    let text = String::from_utf8_lossy(&output.stdout);
-    let mut commits = Vec::new();
-    let mut current_commit: Option<(String, String, String)> = None;
+    enabled: bool,
+) -> Result<(), String> {
+    let mut cfg = config.write();
+    cfg.reload_on_config_change = enabled;
+    audited(
+        &app,
+        "set_reload_on_config_change",
+        serde_json::json!({ "enabled": enabled }),
+        cfg.save(),
+    )
+}
 
-    for line in text.lines() {
-        if line.contains('|') && line.len() > 40 {
-            // Flush previous commit
-            if let Some((hash, date, msg)) = current_commit.take() {
-                commits.push(GitCommit {
-                    hash,
-                    date,
-                    message: msg,
-                    files_changed: 0,
-                });
-            }
-            let parts: Vec<&str> = line.splitn(3, '|').collect();
-            if parts.len() >= 3 {
-                current_commit = Some((
-                    parts[0].to_string(),
-                    parts[1].to_string(),
-                    parts[2].to_string(),
-                ));
-            }
-        } else if line.contains("file") && line.contains("changed") {
-            let files = line
-                .split_whitespace()
-                .next()
-                .and_then(|n| n.parse::<u32>().ok())
-                .unwrap_or(0);
-            if let Some((hash, date, msg)) = current_commit.take() {
-                commits.push(GitCommit {
-                    hash,
-                    date,
-                    message: msg,
-                    files_changed: files,
-                });
-            }
-        }
-    }
-    // Flush last
-    if let Some((hash, date, msg)) = current_commit {
-        commits.push(GitCommit {
-            hash,
-            date,
-            message: msg,
-            files_changed: 0,
-        });
-    }
+#[tauri::command]
+pub fn get_engine_log_level(config: State<ConfigState>) -> Option<String> {
+    config.read().engine_log_level.clone()
+}
 
-    Ok(commits)
+/// Set the engine's log level ("trace" … "fatal"), which is also the minimum
+/// level of forwarded stdout lines. Returns "runtime" when the running engine
+/// applied it, "next_start" when it takes effect at the next start.
+#[tauri::command]
+pub async fn set_engine_log_level(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    level: String,
+) -> Result<String, String> {
+    let sp = soul_path(&config);
+    let result = sidecar::set_log_level(&app, &sp, &level).await;
+    audited(&app, "set_engine_log_level", serde_json::json!({ "level": level }), result)
 }
 
+/// Autostart flags and the report of this launch's automatic boot.
 #[tauri::command]
-pub fn get_state_diff(config: State<ConfigState>, hash: String) -> Result<String, String> {
-    let repo = git_root(&config).ok_or_else(|| "No git repository found".to_string())?;
-    if !hash.chars().all(|c| c.is_ascii_hexdigit()) || hash.len() < 7 {
-        return Err("Invalid commit hash".to_string());
+pub fn get_autostart(config: State<ConfigState>, boot: State<Arc<Boot>>) -> AutostartStatus {
+    let cfg = config.read();
+    AutostartStatus {
+        engine: cfg.autostart_engine,
+        chain: cfg.autostart_chain,
+        last: boot.last_autostart(),
     }
+}
 
-    let output = Command::new("git")
-        .args(["show", "--stat", "--patch", &hash])
-        .current_dir(&repo)
-        .output()
-        .map_err(|e| format!("git show failed: {}", e))?;
-
-    if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).to_string());
-    }
+/// Whether the engine and soul-chain start when the app launches.
+#[tauri::command]
+pub fn set_autostart(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    engine: bool,
+    chain: bool,
+) -> Result<(), String> {
+    let mut cfg = config.write();
+    cfg.autostart_engine = engine;
+    cfg.autostart_chain = chain;
+    audited(
+        &app,
+        "set_autostart",
+        serde_json::json!({ "engine": engine, "chain": chain }),
+        cfg.save(),
+    )
+}
 
-    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+#[tauri::command]
+pub fn get_sidecar_status(
+    sidecar: State<std::sync::Arc<SidecarManager>>,
+    simulation: State<Arc<Simulation>>,
+) -> crate::sidecar::SidecarStatus {
+    simulation
+        .engine_status()
+        .unwrap_or_else(|| sidecar.get_status())
 }
 
+/// Engine uptime percentage and downtime incidents over `range`
+/// ("24h", "7d", "30d", "90d" or "all"; default "7d").
 #[tauri::command]
-pub fn rollback_state(config: State<ConfigState>, hash: String) -> Result<String, String> {
-    let repo = git_root(&config).ok_or_else(|| "No git repository found".to_string())?;
-    if !hash.chars().all(|c| c.is_ascii_hexdigit()) || hash.len() < 7 {
-        return Err("Invalid commit hash".to_string());
-    }
+pub fn get_engine_availability(
+    availability: State<Arc<Availability>>,
+    range: Option<String>,
+) -> Result<EngineAvailability, String> {
+    availability.report(range.as_deref().unwrap_or("7d"))
+}
 
-    let output = Command::new("git")
-        .args(["revert", "--no-edit", &hash])
-        .current_dir(&repo)
-        .output()
-        .map_err(|e| format!("git revert failed: {}", e))?;
+// --- Founding Commands ---
 
-    if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).to_string());
-    }
+#[tauri::command]
+pub fn start_founding(
+    config: State<ConfigState>,
+    founding: State<std::sync::Arc<crate::founding::FoundingServer>>,
+    app: tauri::AppHandle,
+) -> Result<u16, String> {
+    let sp = soul_path(&config);
+    founding.start(&app, &sp)
+}
 
-    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+#[tauri::command]
+pub fn stop_founding(
+    founding: State<std::sync::Arc<crate::founding::FoundingServer>>,
+) -> Result<(), String> {
+    founding.stop()
 }
 
-// --- Embedded Browser ---
+/// One interview turn is an LLM call; retried only while the founding
+/// server is still coming up
+const FOUNDING_CHAT: Policy = Policy {
+    name: "founding_chat",
+    timeout: std::time::Duration::from_secs(120),
+    retries: 2,
+    backoff: std::time::Duration::from_millis(500),
+    idempotent: false,
+};
+/// Rendering the preview writes nothing, so it may be repeated
+const FOUNDING_PREVIEW: Policy = Policy {
+    name: "founding_preview",
+    timeout: std::time::Duration::from_secs(120),
+    retries: 1,
+    backoff: std::time::Duration::from_millis(500),
+    idempotent: true,
+};
 
-const BROWSER_LABEL: &str = "soul-browser";
+#[tauri::command]
+pub async fn founding_chat(
+    app: tauri::AppHandle,
+    founding: State<'_, std::sync::Arc<crate::founding::FoundingServer>>,
+    message: String,
+    history: Vec<serde_json::Value>,
+) -> Result<serde_json::Value, String> {
+    let port = founding.port();
+    let url = format!("http://127.0.0.1:{}/chat", port);
+
+    let body = serde_json::json!({
+        "message": message,
+        "history": history,
+    });
+
+    let client = app.state::<Arc<Http>>().local(&url)?;
+    let resp = http::send(&app, &FOUNDING_CHAT, || client.post(&url).json(&body))
+        .await
+        .map_err(|e| format!("Failed to reach founding server: {}", e))?;
+
+    let json: serde_json::Value = resp
+        .json()
+        .await
+        .map_err(|e| format!("Invalid response from founding server: {}", e))?;
+
+    Ok(json)
+}
+
+/// Render the files founding would create, for review. Without a configured
+/// LLM provider they come from local templates and the wizard `answers`.
+/// Nothing is written until `founding_create` is called with the token.
+#[tauri::command]
+pub async fn founding_preview(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    founding: State<'_, std::sync::Arc<crate::founding::FoundingServer>>,
+    previews: State<'_, Arc<FoundingPreviews>>,
+    history: Vec<serde_json::Value>,
+    answers: Option<FoundingAnswers>,
+) -> Result<FoundingPreview, String> {
+    let sp = soul_path(&config);
+    let template = !founding_template::llm_configured(&sp);
+    let files = if template {
+        let answers = answers.ok_or(
+            "No LLM provider is configured; the offline founding needs the wizard answers",
+        )?;
+        founding_template::render(&answers)?
+    } else {
+        request_founding_preview(&app, founding.port(), history).await?
+    };
+    previews.insert(&sp, files, template)
+}
+
+async fn request_founding_preview(
+    app: &tauri::AppHandle,
+    port: u16,
+    history: Vec<serde_json::Value>,
+) -> Result<SoulFiles, String> {
+    let url = format!("http://127.0.0.1:{}/preview", port);
+
+    let body = serde_json::json!({ "history": history });
+
+    let client = app.state::<Arc<Http>>().local(&url)?;
+    let resp = http::send(app, &FOUNDING_PREVIEW, || client.post(&url).json(&body))
+        .await
+        .map_err(|e| format!("Failed to reach founding server: {}", e))?;
+
+    if !resp.status().is_success() {
+        let json: serde_json::Value = resp.json().await.unwrap_or_default();
+        return Err(format!(
+            "Founding server failed: {}",
+            json["error"].as_str().unwrap_or("unknown error")
+        ));
+    }
+
+    resp.json()
+        .await
+        .map_err(|e| format!("Invalid response: {}", e))
+}
+
+/// Back up the existing soul and issue the token `founding_create` needs to
+/// found over it.
+#[tauri::command]
+pub async fn confirm_overwrite(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    previews: State<'_, Arc<FoundingPreviews>>,
+) -> Result<OverwriteConfirmation, String> {
+    let sp = soul_path(&config);
+    let backup_sp = sp.clone();
+    let result = run_blocking(&app, "confirm_overwrite", None, move |_| {
+        founding::backup_soul(&backup_sp)
+    })
+    .await
+    .map(|(backup, files)| previews.confirm_overwrite(&sp, &backup, files));
+    let params = serde_json::json!({ "soul_path": sp.to_string_lossy() });
+    audited(&app, "confirm_overwrite", params, result)
+}
+
+/// Write the soul files of a confirmed founding preview. An existing soul
+/// is only overwritten with a token from `confirm_overwrite`; otherwise the
+/// error starts with `AlreadyFounded`.
+#[tauri::command]
+pub async fn founding_create(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    previews: State<'_, Arc<FoundingPreviews>>,
+    token: String,
+    overwrite_token: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let sp = soul_path(&config);
+    previews.guard(&sp, overwrite_token.as_deref())?;
+    let (files, template) = previews.take(&token)?;
+    let params = serde_json::json!({
+        "files": files.files.len(),
+        "template": template,
+        "overwrite": overwrite_token.is_some(),
+    });
+    let result = run_blocking(&app, "founding_create", None, move |_| {
+        let created = files.write(&sp)?;
+        // A (re)founded soul is a new soul
+        let identity = soul_identity::create(&sp)?;
+        Ok((created, identity))
+    })
+    .await
+    .and_then(|(created, identity)| {
+        let mut cfg = config.write();
+        cfg.soul_id = Some(identity.id);
+        cfg.save()?;
+        Ok(serde_json::json!({
+            "success": true,
+            "filesCreated": created,
+            "templateFounded": template,
+        }))
+    });
+    audited(&app, "founding_create", params, result)
+}
+
+// --- Engine Monitor Proxy ---
+
+#[tauri::command]
+pub async fn fetch_engine_subsystems(
+    config: State<'_, ConfigState>,
+    proxy: State<'_, Arc<EngineProxy>>,
+    app: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    let sp = soul_path(&config);
+    proxy.get(&app, &sp, MONITOR_PATH).await
+}
+
+/// Generic pass-through to the engine HTTP API (rate limited, GETs coalesced).
+#[tauri::command]
+pub async fn engine_api(
+    config: State<'_, ConfigState>,
+    proxy: State<'_, Arc<EngineProxy>>,
+    app: tauri::AppHandle,
+    method: String,
+    path: String,
+    body: Option<serde_json::Value>,
+) -> Result<serde_json::Value, String> {
+    // Security: only the engine's /api namespace, no traversal
+    if !path.starts_with("/api/") || path.contains("..") {
+        return Err("Invalid engine API path".to_string());
+    }
+
+    let sp = soul_path(&config);
+    let method = method.to_uppercase();
+    if method == "GET" {
+        proxy.get(&app, &sp, &path).await
+    } else {
+        proxy.send(&app, &sp, &method, &path, body).await
+    }
+}
+
+#[tauri::command]
+pub fn set_proxy_limits(
+    config: State<ConfigState>,
+    proxy: State<Arc<EngineProxy>>,
+    limits: ProxyLimits,
+) -> Result<(), String> {
+    if limits.requests_per_sec <= 0.0 || limits.burst == 0 {
+        return Err("Proxy limits must be positive".to_string());
+    }
+    proxy.set_limits(limits.clone());
+    let mut cfg = config.write();
+    cfg.proxy = limits;
+    cfg.save()
+}
+
+#[tauri::command]
+pub fn set_monitor_config(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    proxy: State<Arc<EngineProxy>>,
+    monitor: MonitorConfig,
+) -> Result<(), String> {
+    proxy.set_monitor_config(monitor.clone());
+    {
+        let mut cfg = config.write();
+        cfg.monitor = monitor;
+        cfg.save()?;
+    }
+    profiles::apply(&app);
+    Ok(())
+}
+
+// --- Background Activity ---
+
+/// Quiet all background loops (tray animation, watcher, watchdog, monitor
+/// push, schedulers) or resume them. File changes are caught up on resume.
+#[tauri::command]
+pub fn suspend_background(app: tauri::AppHandle, suspended: bool) -> BackgroundStatus {
+    background::set_suspended(&app, suspended)
+}
+
+#[tauri::command]
+pub fn get_background_status(app: tauri::AppHandle) -> BackgroundStatus {
+    background::status(&app)
+}
+
+/// Whether the screensaver feed (`ambient:frame`) is running.
+#[tauri::command]
+pub fn get_ambient_status(ambient: State<Arc<Ambient>>) -> AmbientStatus {
+    ambient.status()
+}
+
+// --- Runtime Profiles ---
+
+#[tauri::command]
+pub fn get_runtime_profile(profiles: State<Arc<Profiles>>) -> ProfileStatus {
+    profiles.status()
+}
+
+/// Switch to "performance", "balanced" or "battery_saver", or "auto" to
+/// follow the power source.
+#[tauri::command]
+pub fn set_runtime_profile(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    profiles: State<Arc<Profiles>>,
+    name: String,
+) -> Result<ProfileStatus, String> {
+    profiles.select(&app, &name)?;
+    let mut cfg = config.write();
+    cfg.profile = profiles.config();
+    cfg.save()?;
+    Ok(profiles.status())
+}
+
+// --- Chain Commands ---
+
+#[tauri::command]
+pub fn start_chain(
+    sidecar: State<std::sync::Arc<SidecarManager>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    audited(&app, "start_chain", serde_json::json!({}), sidecar.start_chain(&app))
+}
+
+#[tauri::command]
+pub fn stop_chain(
+    sidecar: State<std::sync::Arc<SidecarManager>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    audited(&app, "stop_chain", serde_json::json!({}), sidecar.stop_chain(&app))
+}
+
+#[tauri::command]
+pub fn get_chain_status(
+    sidecar: State<std::sync::Arc<SidecarManager>>,
+    simulation: State<Arc<Simulation>>,
+) -> crate::sidecar::SidecarStatus {
+    simulation
+        .chain_status()
+        .unwrap_or_else(|| sidecar.get_chain_status())
+}
+
+// --- PTY Commands ---
+
+#[tauri::command]
+pub fn create_pty(
+    pty: State<std::sync::Arc<PtyManager>>,
+    app: tauri::AppHandle,
+    cols: u16,
+    rows: u16,
+    restricted: Option<bool>,
+    cwd: Option<String>,
+    purpose: Option<String>,
+) -> Result<u32, String> {
+    let options = PtyOptions {
+        restricted: restricted.unwrap_or(false),
+        cwd: cwd.map(PathBuf::from),
+        purpose,
+    };
+    let params = serde_json::json!({
+        "cols": cols,
+        "rows": rows,
+        "restricted": options.restricted,
+        "cwd": options.cwd,
+        "purpose": options.purpose,
+    });
+    audited(&app, "create_pty", params, pty.create(&app, cols, rows, options))
+}
+
+/// Paste into terminal `id` with bracketed paste and chunked writes.
+#[tauri::command]
+pub async fn paste_pty(app: tauri::AppHandle, id: u32, text: String) -> Result<(), String> {
+    let pty = app.state::<Arc<PtyManager>>().inner().clone();
+    run_blocking(&app, "paste_pty", None, move |_| pty.paste(id, &text)).await
+}
+
+/// Commands typed into terminal `id` since it opened, oldest first.
+#[tauri::command]
+pub fn get_pty_command_history(
+    pty: State<std::sync::Arc<PtyManager>>,
+    id: u32,
+) -> Result<Vec<CommandRecord>, String> {
+    pty.history(id)
+}
+
+/// Commands of every terminal session, past app runs included, containing
+/// `query`; newest first.
+#[tauri::command]
+pub async fn search_command_history(
+    app: tauri::AppHandle,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<CommandRecord>, String> {
+    let history = app.state::<Arc<CommandHistory>>().inner().clone();
+    run_blocking(&app, "search_command_history", None, move |_| {
+        history.search(&query, limit)
+    })
+    .await
+}
+
+/// Terminals and views open when the app last quit; None once dismissed
+/// or if nothing was open.
+#[tauri::command]
+pub fn get_restore_plan(restore: State<Arc<SessionRestore>>) -> Option<RestorePlan> {
+    restore.plan()
+}
+
+/// Forget the previous session after restoring or declining it.
+#[tauri::command]
+pub fn dismiss_restore_plan(restore: State<Arc<SessionRestore>>) {
+    restore.dismiss()
+}
+
+/// Views open in the main window, saved for the next start.
+#[tauri::command]
+pub fn set_open_views(restore: State<Arc<SessionRestore>>, views: Vec<String>) {
+    restore.set_views(views)
+}
+
+/// Open terminals with their sandbox and writable directories.
+#[tauri::command]
+pub fn list_ptys(pty: State<std::sync::Arc<PtyManager>>) -> Vec<PtyInfo> {
+    pty.list()
+}
+
+#[tauri::command]
+pub fn write_pty(
+    pty: State<std::sync::Arc<PtyManager>>,
+    id: u32,
+    data: String,
+) -> Result<(), String> {
+    pty.write(id, &data)
+}
+
+#[tauri::command]
+pub fn resize_pty(
+    pty: State<std::sync::Arc<PtyManager>>,
+    id: u32,
+    cols: u16,
+    rows: u16,
+) -> Result<(), String> {
+    pty.resize(id, cols, rows)
+}
+
+#[tauri::command]
+pub fn close_pty(
+    pty: State<std::sync::Arc<PtyManager>>,
+    app: tauri::AppHandle,
+    id: u32,
+) -> Result<(), String> {
+    let result = pty.close(id);
+    routing::forget_pty(&app, id);
+    audited(&app, "close_pty", serde_json::json!({ "id": id }), result)
+}
+
+/// Pop terminal `id` out into its own window; its `pty:*` events go there
+/// until it is attached again. Returns the window label.
+#[tauri::command]
+pub fn detach_pty_window(app: tauri::AppHandle, id: u32) -> Result<String, String> {
+    routing::detach_pty(&app, id)
+}
+
+/// Bring a detached terminal back into the main window.
+#[tauri::command]
+pub fn attach_pty_window(app: tauri::AppHandle, id: u32) -> Result<(), String> {
+    routing::attach_pty(&app, id)
+}
+
+// --- State Versioning Commands (Git) ---
+
+/// Find the git root: either soul_path itself or soul_path/seelen-protokoll
+fn git_root(config: &State<ConfigState>) -> Option<PathBuf> {
+    let sp = soul_path(config);
+    if sp.join(".git").exists() {
+        return Some(sp);
+    }
+    let proto = sp.join("seelen-protokoll");
+    if proto.join(".git").exists() {
+        return Some(proto);
+    }
+    None
+}
+
+#[tauri::command]
+pub async fn get_state_history(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    limit: Option<u32>,
+    op_id: Option<String>,
+) -> Result<Vec<GitCommit>, String> {
+    let repo = match git_root(&config) {
+        Some(p) => p,
+        None => return Ok(Vec::new()),
+    };
+
+    let git = app.state::<Arc<Backends>>().git.clone();
+    run_blocking(&app, "get_state_history", op_id, move |token| {
+        state_history_sync(git.as_ref(), &repo, limit.unwrap_or(50), token)
+    })
+    .await
+}
+
+fn state_history_sync(
+    git: &dyn GitBackend,
+    repo: &Path,
+    n: u32,
+    token: &CancelToken,
+) -> Result<Vec<GitCommit>, String> {
+    let text = git.run(
+        repo,
+        &["log", "--format=%H|%ai|%s", "-n", &n.to_string(), "--shortstat"],
+        token,
+    )?;
+    Ok(parse_history(&text))
+}
+
+/// Parse `git log --format=%H|%ai|%s --shortstat` output.
+fn parse_history(text: &str) -> Vec<GitCommit> {
+    let mut commits = Vec::new();
+    let mut current_commit: Option<(String, String, String)> = None;
+
+    for line in text.lines() {
+        if line.contains('|') && line.len() > 40 {
+            // Flush previous commit
+            if let Some((hash, date, msg)) = current_commit.take() {
+                commits.push(GitCommit {
+                    hash,
+                    date,
+                    message: msg,
+                    files_changed: 0,
+                });
+            }
+            let parts: Vec<&str> = line.splitn(3, '|').collect();
+            if parts.len() >= 3 {
+                current_commit = Some((
+                    parts[0].to_string(),
+                    parts[1].to_string(),
+                    parts[2].to_string(),
+                ));
+            }
+        } else if line.contains("file") && line.contains("changed") {
+            let files = line
+                .split_whitespace()
+                .next()
+                .and_then(|n| n.parse::<u32>().ok())
+                .unwrap_or(0);
+            if let Some((hash, date, msg)) = current_commit.take() {
+                commits.push(GitCommit {
+                    hash,
+                    date,
+                    message: msg,
+                    files_changed: files,
+                });
+            }
+        }
+    }
+    // Flush last
+    if let Some((hash, date, msg)) = current_commit {
+        commits.push(GitCommit {
+            hash,
+            date,
+            message: msg,
+            files_changed: 0,
+        });
+    }
+
+    commits
+}
+
+async fn state_diff(
+    app: &tauri::AppHandle,
+    config: &State<'_, ConfigState>,
+    hash: String,
+    op_id: Option<String>,
+) -> Result<String, String> {
+    let repo = git_root(config).ok_or_else(|| "No git repository found".to_string())?;
+    if !hash.chars().all(|c| c.is_ascii_hexdigit()) || hash.len() < 7 {
+        return Err("Invalid commit hash".to_string());
+    }
+
+    let git = app.state::<Arc<Backends>>().git.clone();
+    run_blocking(app, "get_state_diff", op_id, move |token| {
+        git.run(&repo, &["show", "--stat", "--patch", &hash], token)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_state_diff(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    hash: String,
+    op_id: Option<String>,
+) -> Result<String, String> {
+    let diff = state_diff(&app, &config, hash, op_id).await?;
+    stream::limit(diff, "The diff", "stream_state_diff")
+}
+
+/// `get_state_diff` for diffs over the IPC limit, sent as `stream:chunk`
+/// events for `stream_id`.
+#[tauri::command]
+pub async fn stream_state_diff(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    streams: State<'_, Arc<Streams>>,
+    hash: String,
+    stream_id: String,
+    op_id: Option<String>,
+) -> Result<StreamInfo, String> {
+    let diff = state_diff(&app, &config, hash, op_id).await?;
+    streams.start(&app, stream_id, diff)
+}
+
+#[tauri::command]
+pub async fn rollback_state(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    hash: String,
+) -> Result<String, String> {
+    let repo = git_root(&config).ok_or_else(|| "No git repository found".to_string())?;
+    if !hash.chars().all(|c| c.is_ascii_hexdigit()) || hash.len() < 7 {
+        return Err("Invalid commit hash".to_string());
+    }
+
+    // Not cancellable: a half-finished revert is worse than a slow one
+    let params = serde_json::json!({ "hash": hash });
+    let git = app.state::<Arc<Backends>>().git.clone();
+    let journal = app.state::<Arc<Journal>>().inner().clone();
+    let result = run_blocking(&app, "rollback_state", None, move |_| {
+        let token = CancelToken::default();
+        let head = git.run(&repo, &["rev-parse", "HEAD"], &token)?;
+        let _entry = journal.begin(JournalOp::Rollback {
+            repo: repo.to_string_lossy().to_string(),
+            hash: hash.clone(),
+            head: head.trim().to_string(),
+        })?;
+        git.run(&repo, &["revert", "--no-edit", &hash], &token)
+    })
+    .await;
+    audited(&app, "rollback_state", params, result)
+}
+
+// --- Embedded Browser ---
+
+const BROWSER_POPUP_INIT: &str = r#"
+(function() {
+    document.addEventListener('keydown', function(e) {
+        if (e.key === 'Escape') window.location.href = 'soul://close';
+    });
+    function addUI() {
+        var btn = document.createElement('div');
+        btn.innerHTML = '\u2715';
+        Object.assign(btn.style, {
+            position:'fixed', top:'10px', right:'10px', zIndex:'2147483647',
+            width:'30px', height:'30px', borderRadius:'50%',
+            background:'rgba(15,18,25,0.75)', color:'rgba(255,255,255,0.6)',
+            display:'flex', alignItems:'center', justifyContent:'center',
+            cursor:'pointer', fontSize:'14px',
+            backdropFilter:'blur(16px)', WebkitBackdropFilter:'blur(16px)',
+            border:'1px solid rgba(100,200,255,0.15)',
+            boxShadow:'0 2px 12px rgba(0,0,0,0.3),0 0 20px rgba(100,200,255,0.05)',
+            transition:'all 0.25s cubic-bezier(0.4,0,0.2,1)',
+            userSelect:'none', WebkitUserSelect:'none'
+        });
+        btn.onmouseenter = function(){
+            this.style.background='rgba(255,50,80,0.85)';
+            this.style.color='#fff';
+            this.style.borderColor='rgba(255,50,80,0.4)';
+            this.style.boxShadow='0 2px 12px rgba(0,0,0,0.3),0 0 20px rgba(255,50,80,0.2)';
+        };
+        btn.onmouseleave = function(){
+            this.style.background='rgba(15,18,25,0.75)';
+            this.style.color='rgba(255,255,255,0.6)';
+            this.style.borderColor='rgba(100,200,255,0.15)';
+            this.style.boxShadow='0 2px 12px rgba(0,0,0,0.3),0 0 20px rgba(100,200,255,0.05)';
+        };
+        btn.onclick = function(){ window.location.href = 'soul://close'; };
+        document.body.appendChild(btn);
+    }
+    if (document.body) addUI();
+    else document.addEventListener('DOMContentLoaded', addUI);
+})();
+"#;
+
+/// Open `url` in the embedded browser. With a `profile` the page gets that
+/// profile's cookies and storage instead of the shared ones.
+#[tauri::command]
+pub async fn open_browser(
+    app: tauri::AppHandle,
+    profiles: State<'_, Arc<BrowserProfiles>>,
+    url: String,
+    full_mode: bool,
+    profile: Option<String>,
+) -> Result<(), String> {
+    // Destroy existing browser window if any
+    if let Some(existing) = app.get_webview_window(BROWSER_LABEL) {
+        let _ = existing.destroy();
+    }
+
+    let url_parsed = url::Url::parse(&url).map_err(|e| e.to_string())?;
+
+    // Security: only allow http and https URLs
+    match url_parsed.scheme() {
+        "http" | "https" => {}
+        scheme => return Err(format!("Blocked URL scheme: {}. Only http/https allowed.", scheme)),
+    }
+
+    // Get main window position so browser opens on the same monitor
+    let main_window = app.get_webview_window("main");
+    let (main_pos, main_size) = if let Some(ref w) = main_window {
+        let pos = w.outer_position().unwrap_or(tauri::PhysicalPosition { x: 100, y: 100 });
+        let size = w.outer_size().unwrap_or(tauri::PhysicalSize { width: 1200, height: 800 });
+        (pos, size)
+    } else {
+        (tauri::PhysicalPosition { x: 100, y: 100 }, tauri::PhysicalSize { width: 1200, height: 800 })
+    };
+
+    let app_clone = app.clone();
+    let download_app = app.clone();
+    let downloads = app.state::<Arc<Downloads>>().inner().clone();
+    let mut builder = tauri::WebviewWindowBuilder::new(
+        &app,
+        BROWSER_LABEL,
+        tauri::WebviewUrl::External(url_parsed),
+    )
+    .title("SoulOS Browser")
+    .on_navigation(move |nav_url| {
+        if nav_url.scheme() == "soul" {
+            let app = app_clone.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Some(w) = app.get_webview_window(BROWSER_LABEL) {
+                    let _ = w.destroy();
+                }
+            });
+            return false;
+        }
+        true
+    })
+    .on_download(move |_, event| downloads.handle(&download_app, event));
+
+    if full_mode {
+        // Full mode: same size and position as main window (overlay)
+        builder = builder
+            .inner_size(main_size.width as f64, main_size.height as f64)
+            .position(main_pos.x as f64, main_pos.y as f64)
+            .decorations(true);
+    } else {
+        // Popup mode: centered over the main window
+        let bw: f64 = 900.0;
+        let bh: f64 = 700.0;
+        let bx = main_pos.x as f64 + (main_size.width as f64 - bw) / 2.0;
+        let by = main_pos.y as f64 + (main_size.height as f64 - bh) / 2.0;
+        builder = builder
+            .inner_size(bw, bh)
+            .position(bx, by)
+            .decorations(false)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            .initialization_script(BROWSER_POPUP_INIT);
+    }
+
+    builder = network::config(&app).apply(builder);
+    profiles
+        .apply(builder, profile.as_deref())?
+        .build()
+        .map_err(|e| e.to_string())?;
+    profiles.set_active(profile);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn close_browser(app: tauri::AppHandle) -> Result<(), String> {
+    if let Some(w) = app.get_webview_window(BROWSER_LABEL) {
+        w.destroy().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_browser_profiles(
+    app: tauri::AppHandle,
+    profiles: State<Arc<BrowserProfiles>>,
+) -> Vec<BrowserProfile> {
+    profiles.list(&app)
+}
+
+#[tauri::command]
+pub fn get_download_config(config: State<ConfigState>) -> DownloadConfig {
+    config.read().downloads.clone()
+}
+
+/// Size and type policy for browser downloads; applies to the next download.
+#[tauri::command]
+pub fn set_download_config(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    download_config: DownloadConfig,
+) -> Result<(), String> {
+    let params = serde_json::to_value(&download_config).unwrap_or_default();
+    let result = {
+        let mut cfg = config.write();
+        cfg.downloads = download_config;
+        cfg.save()
+    };
+    audited(&app, "set_download_config", params, result)
+}
+
+#[tauri::command]
+pub fn get_retention_config(config: State<ConfigState>) -> RetentionConfig {
+    config.read().retention.clone()
+}
+
+/// Days to keep event archives and terminal history (0: forever). The
+/// scheduler applies it every few hours.
+#[tauri::command]
+pub fn set_retention_config(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    retention_config: RetentionConfig,
+) -> Result<(), String> {
+    let params = serde_json::to_value(&retention_config).unwrap_or_default();
+    let result = {
+        let mut cfg = config.write();
+        cfg.retention = retention_config;
+        cfg.save()
+    };
+    audited(&app, "set_retention_config", params, result)
+}
+
+#[tauri::command]
+pub fn get_rollup_config(config: State<ConfigState>) -> RollupConfig {
+    config.read().rollup.clone()
+}
+
+/// Weekly rollups of episodic memories older than `min_age_days`; off by
+/// default.
+#[tauri::command]
+pub fn set_rollup_config(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    rollup_config: RollupConfig,
+) -> Result<(), String> {
+    let params = serde_json::to_value(&rollup_config).unwrap_or_default();
+    let result = {
+        let mut cfg = config.write();
+        cfg.rollup = rollup_config;
+        cfg.save()
+    };
+    audited(&app, "set_rollup_config", params, result)
+}
+
+/// Propose rollups now instead of waiting for the weekly run, also when
+/// they are switched off. The proposals wait in the review queue.
+#[tauri::command]
+pub async fn run_rollup(app: tauri::AppHandle) -> Result<RollupReport, String> {
+    let result = rollup::run(&app).await;
+    audited(&app, "run_rollup", serde_json::json!({}), result)
+}
+
+/// What the retention policy would remove right now, without removing it.
+#[tauri::command]
+pub async fn preview_purge(app: tauri::AppHandle) -> Result<PurgeReport, String> {
+    let handle = app.clone();
+    run_blocking(&app, "preview_purge", None, move |_| {
+        retention::purge(&handle, true)
+    })
+    .await
+}
+
+/// Apply the retention policy now instead of waiting for the scheduler.
+#[tauri::command]
+pub async fn run_purge(app: tauri::AppHandle) -> Result<PurgeReport, String> {
+    let handle = app.clone();
+    let result = run_blocking(&app, "run_purge", None, move |_| {
+        retention::purge(&handle, false)
+    })
+    .await;
+    audited(&app, "run_purge", serde_json::json!({}), result)
+}
+
+/// Disk usage and prioritized clean-up recommendations, each with the
+/// command that carries it out.
+#[tauri::command]
+pub async fn get_housekeeping_report(
+    app: tauri::AppHandle,
+    op_id: Option<String>,
+) -> Result<HousekeepingReport, String> {
+    let handle = app.clone();
+    run_blocking(&app, "get_housekeeping_report", op_id, move |token| {
+        housekeeping::report(&handle, token)
+    })
+    .await
+}
+
+/// Delete soul files, e.g. duplicates or media from the housekeeping
+/// report. Returns how many were removed.
+#[tauri::command]
+pub async fn delete_soul_files(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    names: Vec<String>,
+) -> Result<usize, String> {
+    let params = serde_json::json!({ "names": names });
+    let sp = soul_path(&config);
+    for name in &names {
+        accept_app_write(&app, name, None);
+    }
+    let result = run_blocking(&app, "delete_soul_files", None, move |_| {
+        housekeeping::delete_files(&sp, &names)
+    })
+    .await;
+    audited(&app, "delete_soul_files", params, result)
+}
+
+/// Apply file operations as one unit: everything is validated before the
+/// first change and rolled back on any failure, then committed to git once.
+#[tauri::command]
+pub async fn apply_batch(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    ops: Vec<BatchOp>,
+    op_id: Option<String>,
+) -> Result<BatchReport, String> {
+    // Contents stay out of the audit log
+    let logged: Vec<serde_json::Value> = ops
+        .iter()
+        .map(|op| match op {
+            BatchOp::Write { path, content } => {
+                serde_json::json!({ "op": "write", "path": path, "content_len": content.len() })
+            }
+            other => serde_json::to_value(other).unwrap_or_default(),
+        })
+        .collect();
+    let params = serde_json::json!({ "ops": logged });
+    let sp = soul_path(&config);
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let git = app.state::<Arc<Backends>>().git.clone();
+    let journal = app.state::<Arc<Journal>>().inner().clone();
+    let handle = app.clone();
+    let result = run_blocking(&app, "apply_batch", op_id, move |token| {
+        let accept = |name: &str, content: Option<&str>| accept_app_write(&handle, name, content);
+        batch::apply(&sp, &ops, &vault, git.as_ref(), &accept, &journal, token)
+    })
+    .await;
+    audited(&app, "apply_batch", params, result)
+}
+
+/// Finished browser downloads in <soul_path>/media/downloads, oldest first.
+#[tauri::command]
+pub fn list_downloads(config: State<ConfigState>) -> Vec<MediaAttachment> {
+    downloads::attachments(&soul_path(&config))
+}
+
+#[tauri::command]
+pub fn get_network_config(config: State<ConfigState>) -> NetworkConfig {
+    config.read().network.clone()
+}
+
+/// Proxy mode, user agent and TLS policy. Backend clients and browser
+/// windows opened from now on use them; running sidecars get them on their
+/// next start.
+#[tauri::command]
+pub fn set_network_config(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    http: State<Arc<Http>>,
+    network_config: NetworkConfig,
+) -> Result<(), String> {
+    let params = serde_json::json!({
+        "proxy_mode": network_config.proxy_mode,
+        "user_agent": network_config.user_agent,
+        "local_only": network_config.local_only,
+        "ca_certificates": network_config.ca_certificates,
+    });
+    let result = network_config.validate().and_then(|()| {
+        http.set_network(&network_config);
+        let mut cfg = config.write();
+        cfg.network = network_config;
+        cfg.save()
+    });
+    audited(&app, "set_network_config", params, result)
+}
+
+#[tauri::command]
+pub fn get_env_policy(config: State<ConfigState>) -> EnvPolicy {
+    config.read().env.clone()
+}
+
+/// Which environment variables child processes inherit. Applies to
+/// terminals, sidecars and helper commands started from now on.
+#[tauri::command]
+pub fn set_env_policy(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    env_policy: EnvPolicy,
+) -> Result<(), String> {
+    // Injected values may be secrets; only their names are audited
+    let params = serde_json::json!({
+        "allow": env_policy.allow,
+        "deny": env_policy.deny,
+        "inject": env_policy.inject.keys().collect::<Vec<_>>(),
+    });
+    let result = env_policy.validate().and_then(|()| {
+        env_policy::set(&env_policy);
+        let mut cfg = config.write();
+        cfg.env = env_policy;
+        cfg.save()
+    });
+    audited(&app, "set_env_policy", params, result)
+}
+
+/// Delete a browser profile with its cookies and storage.
+#[tauri::command]
+pub fn clear_browser_profile(
+    app: tauri::AppHandle,
+    profiles: State<Arc<BrowserProfiles>>,
+    name: String,
+) -> Result<(), String> {
+    let result = profiles.clear(&app, &name);
+    audited(&app, "clear_browser_profile", serde_json::json!({ "name": name }), result)
+}
+
+/// Clip a web page into the soul's semantic memories as markdown with its
+/// source. `url_or_window_id` is a URL, or the label of a browser window
+/// whose current page is clipped.
+#[tauri::command]
+pub async fn clip_page(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    url_or_window_id: String,
+) -> Result<ClippedPage, String> {
+    let params = serde_json::json!({ "target": url_or_window_id });
+    let sp = soul_path(&config);
+    let result = clip_page_inner(&app, sp, &url_or_window_id).await;
+    audited(&app, "clip_page", params, result)
+}
+
+async fn clip_page_inner(
+    app: &tauri::AppHandle,
+    sp: PathBuf,
+    target: &str,
+) -> Result<ClippedPage, String> {
+    let url = match url::Url::parse(target) {
+        Ok(url) => url,
+        Err(_) => app
+            .get_webview_window(target)
+            .ok_or_else(|| format!("No window '{}' to clip", target))?
+            .url()
+            .map_err(|e| e.to_string())?,
+    };
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("Only http/https pages can be clipped: {}", url));
+    }
+    let (url, html) = clip::fetch(app, &url).await?;
+
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let files = app.state::<Arc<Backends>>().files.clone();
+    let handle = app.clone();
+    run_blocking(app, "clip_page", None, move |_| {
+        let article = clip::extract(&html, &url);
+        if article.markdown.trim().is_empty() {
+            return Err(format!("No readable content found on {}", url));
+        }
+        let path = clip::clip_path(&sp, &article.title);
+        let content = clip::render(&article, &url);
+        accept_app_write(&handle, &path, Some(&content));
+        let data = vault.seal(&path, &content)?;
+        write_soul_file_sync(files.as_ref(), &sp, &path, &data)?;
+        Ok(ClippedPage {
+            words: article.markdown.split_whitespace().count() as u64,
+            title: article.title,
+            url: url.to_string(),
+            path,
+        })
+    })
+    .await
+}
+
+// --- Directory Listing ---
+
+#[tauri::command]
+pub async fn list_directory(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    name: String,
+    op_id: Option<String>,
+) -> Result<Vec<String>, String> {
+    safe_path::relative(&name)?;
+
+    let sp = soul_path(&config);
+    let files = app.state::<Arc<Backends>>().files.clone();
+    run_blocking(&app, "list_directory", op_id, move |token| {
+        list_directory_sync(files.as_ref(), &sp, &name, token)
+    })
+    .await
+}
+
+fn list_directory_sync(
+    files: &dyn FileStore,
+    sp: &Path,
+    name: &str,
+    token: &CancelToken,
+) -> Result<Vec<String>, String> {
+    let dir_path = safe_path::safe_resolve(files, sp, name)?;
+    if !files.exists(&dir_path) {
+        return Err("Directory not found".to_string());
+    }
+
+    token.check()?;
+    let mut names = files.read_dir(&dir_path).map_err(|e| e.to_string())?;
+    names.sort();
+    names.reverse(); // newest first (for date-based filenames)
+    Ok(names)
+}
+
+// --- Daily Digest ---
+
+/// Write the digest for `date` (YYYY-MM-DD, default today) and emit `digest:ready`.
+#[tauri::command]
+pub async fn generate_daily_digest(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    date: Option<String>,
+    op_id: Option<String>,
+) -> Result<DigestInfo, String> {
+    let sp = soul_path(&config);
+    let date = digest::parse_date(date.as_deref())?;
+    let info = run_blocking(&app, "generate_daily_digest", op_id, move |token| {
+        digest::generate(&sp, date, token)
+    })
+    .await?;
+    digest::emit_ready(&app, &info);
+    Ok(info)
+}
 
-const BROWSER_POPUP_INIT: &str = r#"
-(function() {
-    document.addEventListener('keydown', function(e) {
-        if (e.key === 'Escape') window.location.href = 'soul://close';
-    });
-    function addUI() {
-        var btn = document.createElement('div');
-        btn.innerHTML = '\u2715';
-        Object.assign(btn.style, {
-            position:'fixed', top:'10px', right:'10px', zIndex:'2147483647',
-            width:'30px', height:'30px', borderRadius:'50%',
-            background:'rgba(15,18,25,0.75)', color:'rgba(255,255,255,0.6)',
-            display:'flex', alignItems:'center', justifyContent:'center',
-            cursor:'pointer', fontSize:'14px',
-            backdropFilter:'blur(16px)', WebkitBackdropFilter:'blur(16px)',
-            border:'1px solid rgba(100,200,255,0.15)',
-            boxShadow:'0 2px 12px rgba(0,0,0,0.3),0 0 20px rgba(100,200,255,0.05)',
-            transition:'all 0.25s cubic-bezier(0.4,0,0.2,1)',
-            userSelect:'none', WebkitUserSelect:'none'
-        });
-        btn.onmouseenter = function(){
-            this.style.background='rgba(255,50,80,0.85)';
-            this.style.color='#fff';
-            this.style.borderColor='rgba(255,50,80,0.4)';
-            this.style.boxShadow='0 2px 12px rgba(0,0,0,0.3),0 0 20px rgba(255,50,80,0.2)';
+/// Everything that happened on `day` (YYYY-MM-DD, default today): commits,
+/// bus events, pulses, moods and new memories in one ordered list.
+#[tauri::command]
+pub async fn get_timeline(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    day: Option<String>,
+    op_id: Option<String>,
+) -> Result<Timeline, String> {
+    let sp = soul_path(&config);
+    let repo = git_root(&config);
+    let date = digest::parse_date(day.as_deref())?;
+    let git = app.state::<Arc<Backends>>().git.clone();
+    run_blocking(&app, "get_timeline", op_id, move |token| {
+        timeline::build(&sp, repo.as_deref(), git.as_ref(), date, token)
+    })
+    .await
+}
+
+/// Words, characters, headings and reading time of one soul file, for the
+/// editor status bar.
+#[tauri::command]
+pub async fn get_document_stats(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    name: String,
+) -> Result<DocumentStats, String> {
+    let sp = soul_path(&config);
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let files = app.state::<Arc<Backends>>().files.clone();
+    let stats = app.state::<Arc<TextStats>>().inner().clone();
+    run_blocking(&app, "get_document_stats", None, move |_| {
+        stats.document(files.as_ref(), &vault, &sp, &name)
+    })
+    .await
+}
+
+/// Text statistics of the whole soul, in total and per category.
+#[tauri::command]
+pub async fn get_soul_text_stats(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    op_id: Option<String>,
+) -> Result<SoulTextStats, String> {
+    let sp = soul_path(&config);
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let stats = app.state::<Arc<TextStats>>().inner().clone();
+    run_blocking(&app, "get_soul_text_stats", op_id, move |token| {
+        stats.soul(&vault, &sp, token)
+    })
+    .await
+}
+
+/// Whether `text` is German or English.
+#[tauri::command]
+pub fn detect_language(text: String) -> LanguageGuess {
+    language::detect(&text)
+}
+
+/// Misspelled words of `text` with suggestions, checked offline against the
+/// bundled hunspell dictionary for `lang` ("de" or "en"; detected if None).
+#[tauri::command]
+pub async fn spellcheck(
+    app: tauri::AppHandle,
+    text: String,
+    lang: Option<String>,
+) -> Result<SpellcheckReport, String> {
+    let handle = app.clone();
+    run_blocking(&app, "spellcheck", None, move |_| {
+        language::spellcheck(&handle, &text, lang)
+    })
+    .await
+}
+
+// --- Transcripts ---
+
+#[tauri::command]
+pub async fn list_transcripts(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    op_id: Option<String>,
+) -> Result<Vec<TranscriptSummary>, String> {
+    let sp = soul_path(&config);
+    let index = app.state::<Arc<TranscriptIndex>>().inner().clone();
+    run_blocking(&app, "list_transcripts", op_id, move |token| index.list(&sp, token)).await
+}
+
+/// One page of a conversation (`PAGE_SIZE` messages, page 0 = oldest).
+#[tauri::command]
+pub async fn get_transcript(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    id: String,
+    page: Option<usize>,
+) -> Result<TranscriptPage, String> {
+    let sp = soul_path(&config);
+    run_blocking(&app, "get_transcript", None, move |_| {
+        transcripts::get_page(&sp, &id, page.unwrap_or(0))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn search_transcripts(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    query: String,
+    limit: Option<usize>,
+    op_id: Option<String>,
+) -> Result<Vec<TranscriptHit>, String> {
+    let sp = soul_path(&config);
+    let index = app.state::<Arc<TranscriptIndex>>().inner().clone();
+    run_blocking(&app, "search_transcripts", op_id, move |token| {
+        index.search(&sp, &query, limit.unwrap_or(100), token)
+    })
+    .await
+}
+
+// --- Search ---
+
+/// Start a search-as-you-type session for a search box.
+#[tauri::command]
+pub fn open_search_session(searches: State<Arc<Searches>>) -> String {
+    searches.open()
+}
+
+/// The search box changed. Results for the latest query arrive as
+/// `search:results` once typing pauses.
+#[tauri::command]
+pub fn update_search_query(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    searches: State<Arc<Searches>>,
+    session: String,
+    query: String,
+) -> Result<(), String> {
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    searches.update(&app, &session, query, soul_path(&config), vault)
+}
+
+#[tauri::command]
+pub fn close_search_session(searches: State<Arc<Searches>>, session: String) {
+    searches.close(&session);
+}
+
+// --- Persona Files ---
+
+#[tauri::command]
+pub async fn get_persona_files(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+) -> Result<Vec<PersonaFile>, String> {
+    let sp = soul_path(&config);
+    run_blocking(&app, "get_persona_files", None, move |_| Ok(persona::list(&sp))).await
+}
+
+/// Structural findings for a soul document (SEED, CORE/KERN, MANIFEST or
+/// any Markdown file). Pass `content` to lint unsaved editor text.
+#[tauri::command]
+pub async fn lint_soul_document(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    name: String,
+    content: Option<String>,
+) -> Result<LintReport, String> {
+    let sp = soul_path(&config);
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let files = app.state::<Arc<Backends>>().files.clone();
+    run_blocking(&app, "lint_soul_document", None, move |_| {
+        let content = match content {
+            Some(content) => content,
+            None => read_soul_file_sync(files.as_ref(), &vault, &sp, &name)?,
         };
-        btn.onmouseleave = function(){
-            this.style.background='rgba(15,18,25,0.75)';
-            this.style.color='rgba(255,255,255,0.6)';
-            this.style.borderColor='rgba(100,200,255,0.15)';
-            this.style.boxShadow='0 2px 12px rgba(0,0,0,0.3),0 0 20px rgba(100,200,255,0.05)';
+        Ok(lint::lint(&name, &content))
+    })
+    .await
+}
+
+/// Validate and (unless `validate_only`) save a persona file. Files with
+/// validation errors are never written. `reload` asks the engine to re-read it.
+#[tauri::command]
+pub async fn save_persona_file(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    proxy: State<'_, Arc<EngineProxy>>,
+    name: String,
+    content: String,
+    validate_only: bool,
+    reload: Option<bool>,
+) -> Result<PersonaSaveResult, String> {
+    let sp = soul_path(&config);
+    let (path, kind) = persona::persona_path(&sp, &name)?;
+    let issues = persona::validate(kind, &content);
+    let valid = !persona::has_errors(&issues);
+
+    let saved = valid && !validate_only;
+    if saved {
+        accept_app_write(&app, &name, Some(&content));
+        let params = serde_json::json!({ "name": name, "content_len": content.len() });
+        let result = run_blocking(&app, "save_persona_file", None, move |_| {
+            persona::write(&path, &content)
+        })
+        .await;
+        audited(&app, "save_persona_file", params, result)?;
+    }
+
+    let reloaded = saved
+        && reload.unwrap_or(false)
+        && proxy
+            .send(&app, &sp, "POST", "/api/reload", None)
+            .await
+            .is_ok();
+
+    Ok(PersonaSaveResult {
+        valid,
+        issues,
+        saved,
+        reloaded,
+    })
+}
+
+// --- Write Policy ---
+
+/// Active rules from .soul-policy.toml and recent violations.
+#[tauri::command]
+pub fn get_policy(policy: State<Arc<PolicyEngine>>) -> PolicyInfo {
+    policy.info()
+}
+
+#[tauri::command]
+pub async fn reload_policy(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+) -> Result<PolicyInfo, String> {
+    let sp = soul_path(&config);
+    let policy = app.state::<Arc<PolicyEngine>>().inner().clone();
+    run_blocking(&app, "reload_policy", None, move |_| {
+        policy.load(&sp);
+        Ok(policy.info())
+    })
+    .await
+}
+
+// --- Review Mode ---
+
+#[tauri::command]
+pub fn list_pending_changes(review: State<Arc<ReviewQueue>>) -> Vec<PendingChange> {
+    review.list()
+}
+
+#[tauri::command]
+pub async fn approve_change(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    id: String,
+) -> Result<(), String> {
+    let sp = soul_path(&config);
+    let review = app.state::<Arc<ReviewQueue>>().inner().clone();
+    let params = serde_json::json!({ "id": id });
+    let result = run_blocking(&app, "approve_change", None, move |_| review.approve(&sp, &id)).await;
+    let change = audited(&app, "approve_change", params, result)?;
+    app.state::<Arc<PolicyEngine>>()
+        .accept(&change.path, change.after.as_deref());
+    let _ = app.emit(
+        "review:resolved",
+        serde_json::json!({ "id": change.id, "path": change.path, "approved": true }),
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub fn reject_change(
+    app: tauri::AppHandle,
+    review: State<Arc<ReviewQueue>>,
+    id: String,
+) -> Result<(), String> {
+    let change = audited(
+        &app,
+        "reject_change",
+        serde_json::json!({ "id": id }),
+        review.reject(&id),
+    )?;
+    let _ = app.emit(
+        "review:resolved",
+        serde_json::json!({ "id": change.id, "path": change.path, "approved": false }),
+    );
+    Ok(())
+}
+
+/// Approve every pending change of a group (e.g. a memory rollup).
+/// Returns how many were applied.
+#[tauri::command]
+pub async fn approve_change_group(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    group: String,
+) -> Result<usize, String> {
+    let sp = soul_path(&config);
+    let review = app.state::<Arc<ReviewQueue>>().inner().clone();
+    let params = serde_json::json!({ "group": group });
+    let result = run_blocking(&app, "approve_change_group", None, move |_| {
+        review
+            .group(&group)
+            .iter()
+            .map(|id| review.approve(&sp, id))
+            .collect::<Result<Vec<_>, String>>()
+    })
+    .await;
+    let changes = audited(&app, "approve_change_group", params, result)?;
+    let policy = app.state::<Arc<PolicyEngine>>();
+    for change in &changes {
+        policy.accept(&change.path, change.after.as_deref());
+        let _ = app.emit(
+            "review:resolved",
+            serde_json::json!({ "id": change.id, "path": change.path, "approved": true }),
+        );
+    }
+    Ok(changes.len())
+}
+
+/// Reject every pending change of a group. Returns how many were dropped.
+#[tauri::command]
+pub fn reject_change_group(
+    app: tauri::AppHandle,
+    review: State<Arc<ReviewQueue>>,
+    group: String,
+) -> Result<usize, String> {
+    let result = review
+        .group(&group)
+        .iter()
+        .map(|id| review.reject(id))
+        .collect::<Result<Vec<_>, String>>();
+    let changes = audited(
+        &app,
+        "reject_change_group",
+        serde_json::json!({ "group": group }),
+        result,
+    )?;
+    for change in &changes {
+        let _ = app.emit(
+            "review:resolved",
+            serde_json::json!({ "id": change.id, "path": change.path, "approved": false }),
+        );
+    }
+    Ok(changes.len())
+}
+
+/// In review mode engine writes to soul-state files are staged instead of applied.
+#[tauri::command]
+pub async fn set_review_mode(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    enabled: bool,
+) -> Result<(), String> {
+    let sp = soul_path(&config);
+    let review = app.state::<Arc<ReviewQueue>>().inner().clone();
+    run_blocking(&app, "set_review_mode", None, move |_| {
+        review.set_enabled(enabled, &sp, crate::watcher::is_soul_state);
+        Ok(())
+    })
+    .await?;
+
+    let mut cfg = config.write();
+    cfg.review_mode = enabled;
+    audited(
+        &app,
+        "set_review_mode",
+        serde_json::json!({ "enabled": enabled }),
+        cfg.save(),
+    )
+}
+
+// --- Permissions ---
+
+#[tauri::command]
+pub fn get_elevation_status(permissions: State<Arc<Permissions>>) -> ElevationStatus {
+    permissions.status()
+}
+
+/// Show a native confirmation dialog; sensitive commands are unlocked for a
+/// few minutes if the user confirms.
+#[tauri::command]
+pub async fn request_elevation(app: tauri::AppHandle, reason: String) -> Result<bool, String> {
+    let permissions = app.state::<Arc<Permissions>>().inner().clone();
+    let handle = app.clone();
+    tokio::task::spawn_blocking(move || permissions.request(&handle, &reason))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn drop_elevation(app: tauri::AppHandle, permissions: State<Arc<Permissions>>) {
+    permissions.drop_elevation(&app);
+}
+
+/// Recent IPC invocations, including denied ones.
+#[tauri::command]
+pub fn get_command_trail(permissions: State<Arc<Permissions>>) -> Vec<CommandInvocation> {
+    permissions.trail()
+}
+
+// --- App Lock ---
+
+#[tauri::command]
+pub fn get_lock_status(lock: State<Arc<AppLock>>) -> LockStatus {
+    lock.status()
+}
+
+#[tauri::command]
+pub fn lock_app(app: tauri::AppHandle, lock: State<Arc<AppLock>>) -> Result<(), String> {
+    lock.lock(&app)
+}
+
+/// Argon2 verification is deliberately slow, so it runs off the main thread.
+#[tauri::command]
+pub async fn unlock_app(app: tauri::AppHandle, passphrase: String) -> Result<(), String> {
+    let lock = app.state::<Arc<AppLock>>().inner().clone();
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let handle = app.clone();
+    let salt_created = tokio::task::spawn_blocking(move || {
+        lock.unlock(&handle, &passphrase)?;
+        vault.unlock(&passphrase)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    if salt_created {
+        let config = app.state::<ConfigState>();
+        let mut cfg = config.write();
+        cfg.encryption = app.state::<Arc<Vault>>().config();
+        cfg.save()?;
+    }
+    journal::resume_pending(&app).await;
+    Ok(())
+}
+
+/// Move the encrypted files to the key of a new passphrase. The new salt
+/// and key (sealed with the old one) are saved before any file changes;
+/// the old salt and passphrase hash are only replaced once every file was
+/// rewritten. Failures and crashes roll back to the old passphrase.
+fn rekey(
+    config: &ConfigState,
+    vault: &Vault,
+    journal: &Journal,
+    sp: &Path,
+    passphrase: &str,
+    hash: &str,
+) -> Result<(), String> {
+    let entry = journal.begin(JournalOp::Rekey {
+        soul_path: sp.to_string_lossy().to_string(),
+    })?;
+    vault.stage_rekey(sp, passphrase)?;
+    let old_hash = {
+        let mut cfg = config.write();
+        cfg.encryption = vault.config();
+        cfg.save().inspect_err(|_| {
+            let _ = vault.rollback_rekey(sp);
+            cfg.encryption = vault.config();
+        })?;
+        cfg.lock.passphrase_hash.clone()
+    };
+    let done = vault.rekey_files(sp).and_then(|_| {
+        let mut cfg = config.write();
+        cfg.encryption = vault.committed_config();
+        cfg.lock.passphrase_hash = Some(hash.to_string());
+        cfg.save()
+    });
+    let Err(e) = done else {
+        vault.commit_rekey();
+        return Ok(());
+    };
+    let rolled_back = vault.rollback_rekey(sp).and_then(|_| {
+        let mut cfg = config.write();
+        cfg.encryption = vault.config();
+        cfg.lock.passphrase_hash = old_hash;
+        cfg.save()
+    });
+    if rolled_back.is_err() {
+        // Startup recovery finishes the rollback
+        entry.keep();
+    }
+    Err(e)
+}
+
+/// Set, change or (with `passphrase: None`) remove the lock passphrase.
+/// Requires the current passphrase when one is already set.
+#[tauri::command]
+pub async fn set_lock_passphrase(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    current: Option<String>,
+    passphrase: Option<String>,
+) -> Result<(), String> {
+    let sp = soul_path(&config);
+    let lock = app.state::<Arc<AppLock>>().inner().clone();
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let journal = app.state::<Arc<Journal>>().inner().clone();
+    let cfg_state = config.inner().clone();
+    let hash = tokio::task::spawn_blocking(move || {
+        if !lock.matches(current.as_deref()) {
+            return Err("Wrong passphrase".to_string());
+        }
+        match passphrase {
+            Some(p) if p.trim().is_empty() => Err("Passphrase must not be empty".to_string()),
+            Some(p) => {
+                let hash = lock::hash_passphrase(&p)?;
+                // Encrypted files move to a key derived from the new passphrase
+                rekey(&cfg_state, &vault, &journal, &sp, &p, &hash)?;
+                Ok(Some(hash))
+            }
+            None if !vault.config().directories.is_empty() => {
+                Err("Disable encryption and migrate before removing the passphrase".to_string())
+            }
+            None => Ok(None),
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let result = hash.and_then(|hash| {
+        let enabled = hash.is_some();
+        let mut cfg = config.write();
+        cfg.lock.passphrase_hash = hash;
+        cfg.encryption = app.state::<Arc<Vault>>().config();
+        app.state::<Arc<AppLock>>().set_config(cfg.lock.clone());
+        cfg.save()?;
+        Ok(enabled)
+    });
+    audited(
+        &app,
+        "set_lock_passphrase",
+        serde_json::json!({ "enabled": result.as_ref().ok() }),
+        result.map(|_| ()),
+    )
+}
+
+/// Auto-lock after `minutes` idle (0 = never) and/or on system sleep.
+#[tauri::command]
+pub fn set_auto_lock(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    lock: State<Arc<AppLock>>,
+    minutes: u32,
+    on_sleep: bool,
+) -> Result<LockStatus, String> {
+    let mut cfg = config.write();
+    cfg.lock.auto_lock_minutes = minutes;
+    cfg.lock.lock_on_sleep = on_sleep;
+    lock.set_config(cfg.lock.clone());
+    audited(
+        &app,
+        "set_auto_lock",
+        serde_json::json!({ "minutes": minutes, "on_sleep": on_sleep }),
+        cfg.save(),
+    )?;
+    Ok(lock.status())
+}
+
+// --- At-rest Encryption ---
+
+#[tauri::command]
+pub fn get_encryption_status(vault: State<Arc<Vault>>) -> EncryptionStatus {
+    vault.status()
+}
+
+/// Choose which soul subdirectories are encrypted. Only affects new writes —
+/// run `migrate_encryption` to convert existing files.
+#[tauri::command]
+pub fn set_encrypted_directories(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    vault: State<Arc<Vault>>,
+    directories: Vec<String>,
+) -> Result<EncryptionStatus, String> {
+    let params = serde_json::json!({ "directories": directories });
+    let result = vault.set_directories(directories).and_then(|_| {
+        let mut cfg = config.write();
+        cfg.encryption = vault.config();
+        cfg.save()
+    });
+    audited(&app, "set_encrypted_directories", params, result)?;
+    Ok(vault.status())
+}
+
+/// Encrypt existing plaintext inside encrypted directories and decrypt
+/// files in directories that were removed from the list.
+#[tauri::command]
+pub async fn migrate_encryption(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    op_id: Option<String>,
+) -> Result<MigrationReport, String> {
+    let sp = soul_path(&config);
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let journal = app.state::<Arc<Journal>>().inner().clone();
+    let result = run_blocking(&app, "migrate_encryption", op_id, move |token| {
+        let _entry = journal.begin(JournalOp::Migrate {
+            soul_path: sp.to_string_lossy().to_string(),
+        })?;
+        vault.migrate(&sp, token)
+    })
+    .await;
+    let params = match &result {
+        Ok(r) => serde_json::json!({
+            "encrypted": r.encrypted.len(),
+            "decrypted": r.decrypted.len(),
+            "failed": r.failed.len(),
+        }),
+        Err(_) => serde_json::json!({}),
+    };
+    audited(&app, "migrate_encryption", params, result)
+}
+
+// --- PII Scanner ---
+
+/// Find emails, phone numbers, card numbers and street addresses in soul
+/// markdown under `scope` (whole soul if omitted). With `redact`, matches
+/// are replaced by placeholders such as [EMAIL] — run before exporting or syncing.
+#[tauri::command]
+pub async fn scan_for_pii(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    scope: Option<String>,
+    redact: Option<bool>,
+    op_id: Option<String>,
+) -> Result<PiiReport, String> {
+    let sp = soul_path(&config);
+    let redact = redact.unwrap_or(false);
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let handle = app.clone();
+    let params = serde_json::json!({ "scope": scope });
+    let result = run_blocking(&app, "scan_for_pii", op_id, move |token| {
+        pii::scan(&sp, scope.as_deref(), redact, &vault, token, |name, content| {
+            accept_app_write(&handle, name, Some(content))
+        })
+    })
+    .await;
+    if !redact {
+        return result;
+    }
+    audited(&app, "redact_pii", params, result)
+}
+
+// --- Export ---
+
+/// Shareable zip with SEED.md, the persona files and the chosen memory
+/// categories — never .env or relationships. `anonymize` redacts PII.
+#[tauri::command]
+pub async fn export_soul_filtered(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    categories: Vec<String>,
+    anonymize: bool,
+    destination: Option<String>,
+    op_id: Option<String>,
+) -> Result<ExportInfo, String> {
+    let sp = soul_path(&config);
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let params = serde_json::json!({
+        "categories": categories,
+        "anonymize": anonymize,
+        "destination": destination,
+    });
+    let journal = app.state::<Arc<Journal>>().inner().clone();
+    let result = run_blocking(&app, "export_soul_filtered", op_id, move |token| {
+        let path = export::destination(destination.map(PathBuf::from));
+        let _entry = journal.begin(JournalOp::Export {
+            path: path.to_string_lossy().to_string(),
+        })?;
+        export::export_filtered(&sp, &categories, anonymize, Some(path), &vault, token)
+    })
+    .await;
+    audited(&app, "export_soul_filtered", params, result)
+}
+
+/// Render markdown to sanitized HTML: the soul file `name_or_content` if it
+/// names one (a single line ending in .md), else the text itself. Wiki
+/// links resolve against the soul.
+#[tauri::command]
+pub async fn render_markdown(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    name_or_content: String,
+    options: Option<RenderOptions>,
+) -> Result<RenderedMarkdown, String> {
+    let sp = soul_path(&config);
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let files = app.state::<Arc<Backends>>().files.clone();
+    run_blocking(&app, "render_markdown", None, move |_| {
+        let is_name = !name_or_content.contains('\n') && name_or_content.ends_with(".md");
+        let content = if is_name {
+            read_soul_file_sync(files.as_ref(), &vault, &sp, &name_or_content)?
+        } else {
+            name_or_content
         };
-        btn.onclick = function(){ window.location.href = 'soul://close'; };
-        document.body.appendChild(btn);
+        Ok(markdown::render(&content, &options.unwrap_or_default(), Some(&sp)))
+    })
+    .await
+}
+
+/// One memory as a standalone file to share: "html", "html-print" (the
+/// HTML page, opened in the print dialog to save as PDF) or "svg" (a card),
+/// styled after the soul's mood the day it was written.
+#[tauri::command]
+pub async fn export_memory(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    watcher: State<'_, WatcherState>,
+    name: String,
+    format: String,
+    destination: Option<String>,
+) -> Result<MemoryExport, String> {
+    let params = serde_json::json!({
+        "name": name,
+        "format": format,
+        "destination": destination,
+    });
+    if !memory_export::is_memory(&name) {
+        let denied = Err(format!("Not a memory: {}", name));
+        return audited(&app, "export_memory", params, denied);
     }
-    if (document.body) addUI();
-    else document.addEventListener('DOMContentLoaded', addUI);
-})();
-"#;
+    let sp = soul_path(&config);
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let files = app.state::<Arc<Backends>>().files.clone();
+    let mood = watcher.get_mood();
+    let handle = app.clone();
+    let result = run_blocking(&app, "export_memory", None, move |_| {
+        let content = read_soul_file_sync(files.as_ref(), &vault, &sp, &name)?;
+        let destination = destination.map(PathBuf::from);
+        memory_export::export(&handle, &sp, &name, &content, &format, destination, mood)
+    })
+    .await;
+    audited(&app, "export_memory", params, result)
+}
 
+/// Print a soul document, e.g. SEED.md or a memory, through the system
+/// print dialog. Pages are headed with the soul's name and the date.
 #[tauri::command]
-pub async fn open_browser(
+pub async fn print_document(
     app: tauri::AppHandle,
-    url: String,
-    full_mode: bool,
+    config: State<'_, ConfigState>,
+    name: String,
 ) -> Result<(), String> {
-    // Destroy existing browser window if any
-    if let Some(existing) = app.get_webview_window(BROWSER_LABEL) {
-        let _ = existing.destroy();
+    if !name.ends_with(".md") {
+        return Err(format!("Only markdown documents can be printed: {}", name));
     }
+    let sp = soul_path(&config);
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let files = app.state::<Arc<Backends>>().files.clone();
+    let status = app.state::<Arc<StatusCache>>().inner().clone();
+    let handle = app.clone();
+    run_blocking(&app, "print_document", None, move |_| {
+        let content = read_soul_file_sync(files.as_ref(), &vault, &sp, &name)?;
+        let soul_name = status
+            .get(&sp)
+            .map(|s| s.name)
+            .ok()
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| "Soul".to_string());
+        memory_export::print_document(&handle, &name, &content, &soul_name)
+    })
+    .await
+}
 
-    let url_parsed = url::Url::parse(&url).map_err(|e| e.to_string())?;
+// --- Analytics ---
 
-    // Security: only allow http and https URLs
-    match url_parsed.scheme() {
-        "http" | "https" => {}
-        scheme => return Err(format!("Blocked URL scheme: {}. Only http/https allowed.", scheme)),
+/// Soul growth over `range` ("7d", "30d", "1y", "all"; default "30d"),
+/// bucketed per day, week or month. Git history is analyzed incrementally.
+#[tauri::command]
+pub async fn get_growth_metrics(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    range: Option<String>,
+    resolution: Option<Resolution>,
+    op_id: Option<String>,
+) -> Result<GrowthMetrics, String> {
+    let sp = soul_path(&config);
+    let analytics = app.state::<Arc<Analytics>>().inner().clone();
+    run_blocking(&app, "get_growth_metrics", op_id, move |token| {
+        analytics.growth(
+            &sp,
+            range.as_deref().unwrap_or("30d"),
+            resolution.unwrap_or(Resolution::Day),
+            token,
+        )
+    })
+    .await
+}
+
+// --- Developer ---
+
+/// Write `n_files` synthetic files into a temp subdir of soul_path and
+/// report fs change → emitted event latency percentiles.
+#[tauri::command]
+pub async fn benchmark_pipeline(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    n_files: usize,
+    op_id: Option<String>,
+) -> Result<BenchmarkResult, String> {
+    let sp = soul_path(&config);
+    if app.try_state::<WatcherState>().is_none() {
+        return Err("Watcher is not running".to_string());
     }
+    let bench = app.state::<Arc<PipelineBench>>().inner().clone();
+    run_blocking(&app, "benchmark_pipeline", op_id, move |token| {
+        bench.run(&sp, n_files, token)
+    })
+    .await
+}
 
-    // Get main window position so browser opens on the same monitor
-    let main_window = app.get_webview_window("main");
-    let (main_pos, main_size) = if let Some(ref w) = main_window {
-        let pos = w.outer_position().unwrap_or(tauri::PhysicalPosition { x: 100, y: 100 });
-        let size = w.outer_size().unwrap_or(tauri::PhysicalSize { width: 1200, height: 800 });
-        (pos, size)
-    } else {
-        (tauri::PhysicalPosition { x: 100, y: 100 }, tauri::PhysicalSize { width: 1200, height: 800 })
+// --- Simulation ---
+
+/// Demo mode: fabricated pulses, mood, node activity and sidecar status for
+/// `profile` ("calm", "busy", "dreaming"). Deterministic per profile.
+#[tauri::command]
+pub fn start_simulation(
+    app: tauri::AppHandle,
+    simulation: State<Arc<Simulation>>,
+    profile: Option<String>,
+) -> Result<SimulationStatus, String> {
+    simulation.start(&app, profile.as_deref().unwrap_or("calm"))
+}
+
+#[tauri::command]
+pub fn stop_simulation(app: tauri::AppHandle, simulation: State<Arc<Simulation>>) {
+    simulation.stop(&app);
+}
+
+#[tauri::command]
+pub fn get_simulation_status(simulation: State<Arc<Simulation>>) -> SimulationStatus {
+    simulation.status()
+}
+
+// --- API Discovery ---
+
+/// Backend version plus every registered command and event, for feature detection.
+#[tauri::command]
+pub fn get_api_manifest() -> ApiManifest {
+    api::manifest()
+}
+
+// --- Crash Recovery ---
+
+/// What startup recovery did about operations a crash interrupted.
+#[tauri::command]
+pub fn get_recovery_report(journal: State<Arc<Journal>>) -> RecoveryReport {
+    journal.report()
+}
+
+// --- Crash Reports ---
+
+#[tauri::command]
+pub fn get_crash_config(crash: State<Arc<CrashReporter>>) -> CrashConfig {
+    crash.config()
+}
+
+/// Opt in to (or out of) crash reports. Nothing is written or sent while disabled.
+#[tauri::command]
+pub fn set_crash_config(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    crash: State<Arc<CrashReporter>>,
+    crash_config: CrashConfig,
+) -> Result<(), String> {
+    let params = serde_json::json!({
+        "enabled": crash_config.enabled,
+        "endpoint": crash_config.endpoint,
+    });
+    crash.set_config(crash_config.clone());
+    let result = {
+        let mut cfg = config.write();
+        cfg.crash = crash_config;
+        cfg.save()
     };
+    audited(&app, "set_crash_config", params, result)
+}
 
-    let app_clone = app.clone();
-    let mut builder = tauri::WebviewWindowBuilder::new(
-        &app,
-        BROWSER_LABEL,
-        tauri::WebviewUrl::External(url_parsed),
-    )
-    .title("SoulOS Browser")
-    .on_navigation(move |nav_url| {
-        if nav_url.scheme() == "soul" {
-            let app = app_clone.clone();
-            tauri::async_runtime::spawn(async move {
-                if let Some(w) = app.get_webview_window(BROWSER_LABEL) {
-                    let _ = w.destroy();
-                }
-            });
-            return false;
-        }
-        true
+/// Reports from earlier runs, redacted exactly as they would be sent.
+#[tauri::command]
+pub fn get_pending_crash_reports(crash: State<Arc<CrashReporter>>) -> Vec<CrashReport> {
+    crash.pending().iter().map(|r| r.redacted()).collect()
+}
+
+/// Upload the given reports to the configured endpoint. Returns how many were sent.
+#[tauri::command]
+pub async fn send_crash_reports(app: tauri::AppHandle, ids: Vec<String>) -> Result<usize, String> {
+    let crash = app.state::<Arc<CrashReporter>>().inner().clone();
+    let params = serde_json::json!({ "ids": ids });
+    let result = crash::upload(&app, &crash, &ids).await;
+    audited(&app, "send_crash_reports", params, result)
+}
+
+/// Delete reports without sending them.
+#[tauri::command]
+pub fn dismiss_crash_reports(crash: State<Arc<CrashReporter>>, ids: Vec<String>) -> usize {
+    crash.dismiss(&ids)
+}
+
+// --- Metrics Endpoint ---
+
+#[tauri::command]
+pub fn get_metrics_config(config: State<ConfigState>) -> MetricsConfig {
+    config.read().metrics.clone()
+}
+
+/// Enable, move or disable the localhost Prometheus endpoint (127.0.0.1:<port>/metrics).
+#[tauri::command]
+pub fn set_metrics_config(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    metrics: State<Arc<Metrics>>,
+    metrics_config: MetricsConfig,
+) -> Result<(), String> {
+    let params = serde_json::json!({
+        "enabled": metrics_config.enabled,
+        "port": metrics_config.port,
     });
+    let result = metrics.apply(&app, &metrics_config).and_then(|()| {
+        let mut cfg = config.write();
+        cfg.metrics = metrics_config;
+        cfg.save()
+    });
+    audited(&app, "set_metrics_config", params, result)
+}
 
-    if full_mode {
-        // Full mode: same size and position as main window (overlay)
-        builder = builder
-            .inner_size(main_size.width as f64, main_size.height as f64)
-            .position(main_pos.x as f64, main_pos.y as f64)
-            .decorations(true);
-    } else {
-        // Popup mode: centered over the main window
-        let bw: f64 = 900.0;
-        let bh: f64 = 700.0;
-        let bx = main_pos.x as f64 + (main_size.width as f64 - bw) / 2.0;
-        let by = main_pos.y as f64 + (main_size.height as f64 - bh) / 2.0;
-        builder = builder
-            .inner_size(bw, bh)
-            .position(bx, by)
-            .decorations(false)
-            .always_on_top(true)
-            .skip_taskbar(true)
-            .initialization_script(BROWSER_POPUP_INIT);
-    }
+// --- Mobile Companion ---
 
-    builder.build().map_err(|e| e.to_string())?;
-    Ok(())
+#[tauri::command]
+pub fn get_companion_config(config: State<ConfigState>) -> CompanionConfig {
+    config.read().companion.clone()
 }
 
+/// Enable, move or disable the companion sync endpoint (`lan`: reachable
+/// from the local network, not just 127.0.0.1).
 #[tauri::command]
-pub fn close_browser(app: tauri::AppHandle) -> Result<(), String> {
-    if let Some(w) = app.get_webview_window(BROWSER_LABEL) {
-        w.destroy().map_err(|e| e.to_string())?;
-    }
-    Ok(())
+pub fn set_companion_config(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+    companion: State<Arc<Companion>>,
+    peers: State<Arc<Peers>>,
+    companion_config: CompanionConfig,
+) -> Result<(), String> {
+    let params = serde_json::to_value(&companion_config).unwrap_or_default();
+    let result = companion
+        .apply(&app, &companion_config)
+        .and_then(|()| peers.apply(&app, &companion_config))
+        .and_then(|()| {
+            let mut cfg = config.write();
+            cfg.companion = companion_config;
+            cfg.save()
+        });
+    audited(&app, "set_companion_config", params, result)
 }
 
-// --- Directory Listing ---
+/// Paired phones and SoulOS instances; each may sync notes and, with
+/// `remote_view` on, stream the soul state.
+#[tauri::command]
+pub fn list_paired_devices(companion: State<Arc<Companion>>) -> Vec<CompanionDevice> {
+    companion.devices()
+}
+
+/// Pair a phone. The returned token is shown once and authenticates every
+/// request the device makes.
+#[tauri::command]
+pub async fn pair_companion_device(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    name: String,
+) -> Result<CompanionPairing, String> {
+    let params = serde_json::json!({ "name": name });
+    let port = config.read().companion.port;
+    let companion = app.state::<Arc<Companion>>().inner().clone();
+    // Argon2 hashing is deliberately slow
+    let result = run_blocking(&app, "pair_companion_device", None, move |_| {
+        companion.pair(&name, port)
+    })
+    .await;
+    audited(&app, "pair_companion_device", params, result)
+}
 
+/// Unpair a device; its open remote views are closed.
 #[tauri::command]
-pub fn list_directory(config: State<ConfigState>, name: String) -> Result<Vec<String>, String> {
-    // Security: reject path traversal attempts
-    if name.contains("..") {
-        return Err("Access denied: path traversal not allowed".to_string());
+pub fn revoke_device(
+    app: tauri::AppHandle,
+    companion: State<Arc<Companion>>,
+    remote: State<Arc<Remote>>,
+    id: String,
+) -> Result<(), String> {
+    let params = serde_json::json!({ "id": id });
+    let result = companion.revoke(&id);
+    if result.is_ok() {
+        remote.revoke(&id);
     }
+    audited(&app, "revoke_device", params, result)
+}
+
+/// SoulOS instances announcing themselves on the local network.
+#[tauri::command]
+pub fn list_peers(peers: State<Arc<Peers>>) -> Vec<Peer> {
+    peers.list()
+}
+
+/// Ask a peer to pair; it shows a code to enter in `confirm_peer_pairing`.
+#[tauri::command]
+pub async fn request_peer_pairing(app: tauri::AppHandle, peer_id: String) -> Result<(), String> {
+    let params = serde_json::json!({ "peer_id": peer_id });
+    let result = peers::request_pairing(&app, &peer_id).await;
+    audited(&app, "request_peer_pairing", params, result)
+}
+
+#[tauri::command]
+pub async fn confirm_peer_pairing(
+    app: tauri::AppHandle,
+    peer_id: String,
+    code: String,
+) -> Result<Peer, String> {
+    let params = serde_json::json!({ "peer_id": peer_id });
+    let result = peers::confirm_pairing(&app, &peer_id, &code).await;
+    audited(&app, "confirm_peer_pairing", params, result)
+}
 
+/// Accept or decline a peer's pairing request (`peers:pair-request`); its
+/// code only works once accepted.
+#[tauri::command]
+pub fn answer_peer_pairing(
+    app: tauri::AppHandle,
+    peers: State<Arc<Peers>>,
+    request_id: String,
+    accept: bool,
+) -> Result<(), String> {
+    let params = serde_json::json!({ "request_id": request_id, "accept": accept });
+    let result = peers.answer_incoming(&request_id, accept);
+    audited(&app, "answer_peer_pairing", params, result)
+}
+
+#[tauri::command]
+pub fn forget_peer(
+    app: tauri::AppHandle,
+    peers: State<Arc<Peers>>,
+    peer_id: String,
+) -> Result<(), String> {
+    let params = serde_json::json!({ "peer_id": peer_id });
+    let result = peers.forget(&peer_id);
+    audited(&app, "forget_peer", params, result)
+}
+
+/// A paired peer's soul status, fetched from its companion endpoint.
+#[tauri::command]
+pub async fn get_peer_status(
+    app: tauri::AppHandle,
+    peer_id: String,
+) -> Result<serde_json::Value, String> {
+    peers::peer_status(&app, &peer_id).await
+}
+
+// --- Audit Log ---
+
+/// Mutating actions from <app_data_dir>/audit.jsonl, newest first.
+#[tauri::command]
+pub async fn get_audit_log(
+    app: tauri::AppHandle,
+    filter: Option<AuditFilter>,
+) -> Result<Vec<AuditEntry>, String> {
+    let log = app.state::<Arc<AuditLog>>().inner().clone();
+    let filter = filter.unwrap_or_default();
+    run_blocking(&app, "get_audit_log", None, move |_| log.query(&filter)).await
+}
+
+// --- Tasks ---
+
+/// Cancel a long-running command that was started with an `op_id`.
+#[tauri::command]
+pub fn cancel_task(tasks: State<Arc<Tasks>>, id: String) -> bool {
+    tasks.cancel(&id)
+}
+
+/// Running and recently finished long jobs, for the activity center.
+#[tauri::command]
+pub fn list_tasks(tasks: State<Arc<Tasks>>) -> Vec<TaskInfo> {
+    tasks.list()
+}
+
+// --- Backend Health ---
+
+/// Report every backend subsystem and whether it is running degraded.
+#[tauri::command]
+pub fn get_backend_health(
+    app: tauri::AppHandle,
+    config: State<ConfigState>,
+) -> BackendHealth {
     let sp = soul_path(&config);
-    let dir_path = sp.join(&name);
+    let mut subsystems = Vec::new();
+
+    subsystems.push(if sp.is_dir() {
+        SubsystemHealth::new("soul_path", "ok", Some(sp.to_string_lossy().to_string()))
+    } else {
+        SubsystemHealth::new("soul_path", "down", Some("soul directory missing".to_string()))
+    });
+
+    subsystems.push(match app.try_state::<WatcherState>() {
+        Some(_) => SubsystemHealth::new("watcher", "ok", None),
+        None => SubsystemHealth::new("watcher", "down", Some("watcher not running".to_string())),
+    });
 
-    // Security: verify resolved path stays within soul directory
-    let sp_canonical = sp.canonicalize()
-        .map_err(|e| format!("Cannot resolve soul directory: {}", e))?;
-    let dir_canonical = dir_path.canonicalize()
-        .map_err(|_| "Directory not found".to_string())?;
-    if !dir_canonical.starts_with(&sp_canonical) {
-        return Err("Access denied: path outside soul directory".to_string());
+    if let Some(sidecar) = app.try_state::<Arc<SidecarManager>>() {
+        subsystems.extend(sidecar.health());
+    }
+    if let Some(founding) = app.try_state::<Arc<crate::founding::FoundingServer>>() {
+        subsystems.push(founding.health());
+    }
+    if let Some(pty) = app.try_state::<Arc<PtyManager>>() {
+        subsystems.push(pty.health());
     }
 
-    if !dir_path.exists() {
-        return Ok(Vec::new());
+    let healthy = subsystems.iter().all(|s| s.status == "ok");
+    BackendHealth {
+        healthy,
+        subsystems,
     }
+}
 
-    let mut files = Vec::new();
-    for entry in fs::read_dir(&dir_path).map_err(|e| e.to_string())? {
-        if let Ok(entry) = entry {
-            if let Ok(name) = entry.file_name().into_string() {
-                files.push(name);
-            }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::ProcessOutput;
+    use crate::testing::{FakeLauncher, FixtureGit, MemoryFileStore};
+    use crate::vault::EncryptionConfig;
+
+    const SOUL: &str = "/soul";
+    const HISTORY: &str = include_str!("../tests/fixtures/git-log-shortstat.txt");
+
+    fn soul() -> MemoryFileStore {
+        MemoryFileStore::with_files(&[
+            ("/soul/SEED.md", "# Seed\n"),
+            ("/soul/memories/episodic/2026-03-13.md", "first"),
+            ("/soul/memories/episodic/2026-03-14.md", "second"),
+            ("/outside/secret.txt", "nope"),
+        ])
+    }
+
+    fn vault() -> Vault {
+        Vault::new(EncryptionConfig::default())
+    }
+
+    #[test]
+    fn read_stays_inside_soul_directory() {
+        let files = soul();
+        let sp = Path::new(SOUL);
+        assert_eq!(
+            read_soul_file_sync(&files, &vault(), sp, "SEED.md").unwrap(),
+            "# Seed\n"
+        );
+        let err = read_soul_file_sync(&files, &vault(), sp, "../outside/secret.txt").unwrap_err();
+        assert!(err.starts_with("Access denied"), "{}", err);
+    }
+
+    #[test]
+    fn write_rejects_escaping_paths() {
+        let files = soul();
+        let sp = Path::new(SOUL);
+        let err = write_soul_file_sync(&files, sp, "../outside/secret.txt", b"x").unwrap_err();
+        assert!(err.starts_with("Access denied"), "{}", err);
+        assert_eq!(files.text("/outside/secret.txt").as_deref(), Some("nope"));
+
+        write_soul_file_sync(&files, sp, "memories/core/new.md", b"hello").unwrap();
+        assert_eq!(files.text("/soul/memories/core/new.md").as_deref(), Some("hello"));
+        assert!(files.restricted().is_empty());
+    }
+
+    #[test]
+    fn write_refuses_the_env_file() {
+        let files = MemoryFileStore::with_files(&[("/soul/.env", "OPENAI_API_KEY=sk\n")]);
+        let sp = Path::new(SOUL);
+        for name in [".env", "./.env", ".env/", ".ENV", "memories/../.env"] {
+            assert!(
+                write_soul_file_sync(&files, sp, name, b"X=1").is_err(),
+                "{}",
+                name
+            );
         }
+        assert_eq!(files.text("/soul/.env").as_deref(), Some("OPENAI_API_KEY=sk\n"));
+        // Only the soul's own .env is reserved
+        write_soul_file_sync(&files, sp, "projects/app/.env", b"X=1").unwrap();
+    }
+
+    #[test]
+    fn list_directory_is_newest_first_and_confined() {
+        let files = soul();
+        let sp = Path::new(SOUL);
+        let token = CancelToken::default();
+        assert_eq!(
+            list_directory_sync(&files, sp, "memories/episodic", &token).unwrap(),
+            vec!["2026-03-14.md", "2026-03-13.md"]
+        );
+        let err = list_directory_sync(&files, sp, "../outside", &token).unwrap_err();
+        assert!(err.starts_with("Access denied"), "{}", err);
+        assert_eq!(
+            list_directory_sync(&files, sp, "missing", &token).unwrap_err(),
+            "Directory not found"
+        );
+    }
+
+    #[test]
+    fn env_round_trip_preserves_comments_and_restricts() {
+        let files = MemoryFileStore::with_files(&[(
+            "/soul/.env",
+            "# API keys\nOPENAI_API_KEY=\"sk-old\"\n\nSOUL_LANG='de'\n",
+        )]);
+        let sp = Path::new(SOUL);
+        let env = read_env_sync(&files, sp).unwrap();
+        assert_eq!(env["OPENAI_API_KEY"], "sk-old");
+        assert_eq!(env["SOUL_LANG"], "de");
+
+        let entries = HashMap::from([
+            ("OPENAI_API_KEY".to_string(), "sk-new".to_string()),
+            ("TELEGRAM_TOKEN".to_string(), "123:abc".to_string()),
+        ]);
+        write_env_sync(&files, sp, &entries).unwrap();
+        assert_eq!(
+            files.text("/soul/.env").unwrap(),
+            "# API keys\nOPENAI_API_KEY=sk-new\n\nSOUL_LANG='de'\nTELEGRAM_TOKEN=123:abc\n"
+        );
+        assert_eq!(files.restricted(), vec![PathBuf::from("/soul/.env")]);
+
+        let env = read_env_sync(&files, sp).unwrap();
+        assert_eq!(env.len(), 3);
+        assert_eq!(env["TELEGRAM_TOKEN"], "123:abc");
+    }
+
+    #[test]
+    fn missing_env_reads_empty() {
+        let files = MemoryFileStore::default();
+        assert!(read_env_sync(&files, Path::new(SOUL)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn history_parses_fixture_log() {
+        let git = FixtureGit::default().respond("log", HISTORY);
+        let commits =
+            state_history_sync(&git, Path::new(SOUL), 3, &CancelToken::default()).unwrap();
+
+        assert_eq!(
+            git.calls(),
+            vec![vec!["log", "--format=%H|%ai|%s", "-n", "3", "--shortstat"]]
+        );
+        assert_eq!(commits.len(), 3);
+        assert_eq!(
+            commits[0].message,
+            "Heartbeat: mood shifted to curious | energy 0.7"
+        );
+        assert_eq!(commits[0].date, "2026-03-14 21:07:11 +0100");
+        assert_eq!(commits[0].files_changed, 2);
+        assert_eq!(commits[1].files_changed, 1);
+        assert_eq!(commits[2].hash, "0f9e8d7c6b5a4f3e2d1c0b9a8f7e6d5c4b3a2f1e");
+        assert_eq!(commits[2].files_changed, 0);
+    }
+
+    #[test]
+    fn history_surfaces_git_errors() {
+        let git = FixtureGit::default().fail("log", "fatal: not a git repository");
+        let err = state_history_sync(&git, Path::new(SOUL), 5, &CancelToken::default())
+            .unwrap_err();
+        assert_eq!(err, "fatal: not a git repository");
+    }
+
+    #[test]
+    fn node_version_uses_launcher() {
+        let ok = FakeLauncher(ProcessOutput {
+            success: true,
+            stdout: b"v22.4.0\n".to_vec(),
+            stderr: Vec::new(),
+        });
+        assert_eq!(
+            crate::node::node_version(&ok, Path::new("node")).as_deref(),
+            Some("v22.4.0")
+        );
+        let failed = FakeLauncher(ProcessOutput::default());
+        assert_eq!(crate::node::node_version(&failed, Path::new("node")), None);
     }
-    files.sort();
-    files.reverse(); // newest first (for date-based filenames)
-    Ok(files)
 }
@@ -0,0 +1,935 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::{Local, TimeZone};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use ts_rs::TS;
+
+use crate::backend::{FileStore, OsFileStore};
+use crate::channel::{self, Key, Replays, Signature};
+use crate::config::{app_data_dir, AppConfig};
+use crate::lock::AppLock;
+use crate::peers::Peers;
+use crate::policy::PolicyEngine;
+use crate::remote;
+use crate::review::ReviewQueue;
+use crate::safe_path;
+use crate::status::StatusCache;
+use crate::types::{SoulMood, SoulStatus};
+use crate::vault::Vault;
+use crate::watcher::WatcherState;
+
+/// Merge journal inside the soul: one append-only file per device
+const JOURNAL_DIR: &str = ".soul-sync";
+/// Where merged notes become readable, one file per day
+const NOTES_DIR: &str = "memory/companion";
+/// Paired devices and their keys, kept on this machine only
+const DEVICES_FILE: &str = "companion-devices.json";
+/// Requests larger than this are refused
+const MAX_REQUEST: usize = 256 * 1024;
+/// A request has to arrive completely within this
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_NOTE_CHARS: usize = 4000;
+/// Notes returned per `GET /notes`; page on with the cursor
+const PAGE: usize = 500;
+/// `last_seen` is only written when older than this, not on every request
+const LAST_SEEN_INTERVAL: u64 = 60 * 1000;
+
+/// Local-network endpoint a mobile companion syncs with, off unless
+/// enabled.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CompanionConfig {
+    pub enabled: bool,
+    pub port: u16,
+    /// Listen on every interface instead of 127.0.0.1 only, so a phone on
+    /// the same network can connect
+    #[serde(default)]
+    pub lan: bool,
+    /// Announce this instance and find others on the network via mDNS
+    /// (needs `lan`)
+    #[serde(default)]
+    pub discovery: bool,
+    /// Paired devices may open a read-only live view (`GET /stream`)
+    #[serde(default)]
+    pub remote_view: bool,
+}
+
+impl Default for CompanionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 9465,
+            lan: false,
+            discovery: false,
+            remote_view: false,
+        }
+    }
+}
+
+/// A paired device as listed to the user.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CompanionDevice {
+    pub id: String,
+    pub name: String,
+    /// Unix ms
+    #[ts(type = "number")]
+    pub paired_at: u64,
+    #[ts(type = "number | null")]
+    pub last_seen: Option<u64>,
+}
+
+/// Returned once by `pair_companion_device`, to enter on the device.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct CompanionPairing {
+    pub device: CompanionDevice,
+    /// `<device id>.<key>`; the key signs every request and seals every
+    /// body (see `channel`) and never goes over the network
+    pub token: String,
+    pub port: u16,
+}
+
+/// A quick note written on a companion device.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CompanionNote {
+    /// Chosen by the device, unique per device
+    pub id: String,
+    pub device: String,
+    pub text: String,
+    /// Unix ms, on the device
+    #[ts(type = "number")]
+    pub created: u64,
+    /// Unix ms the desktop merged it; with device and id the `GET /notes`
+    /// cursor
+    #[ts(type = "number")]
+    pub received: u64,
+}
+
+/// Position in the journal order (received, device, id). Notes of one
+/// merge share `received`, so the cursor needs all three to page through
+/// a merge larger than a page. Written as `received:device:id`; a bare
+/// number, as older devices send, starts before that millisecond's notes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+struct Cursor {
+    received: u64,
+    device: String,
+    id: String,
+}
+
+impl Cursor {
+    fn of(note: &CompanionNote) -> Self {
+        Self {
+            received: note.received,
+            device: note.device.clone(),
+            id: note.id.clone(),
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        let mut parts = value.splitn(3, ':');
+        Self {
+            received: parts.next().and_then(|r| r.parse().ok()).unwrap_or(0),
+            device: parts.next().unwrap_or_default().to_string(),
+            id: parts.next().unwrap_or_default().to_string(),
+        }
+    }
+
+    fn encode(&self) -> String {
+        format!("{}:{}:{}", self.received, self.device, self.id)
+    }
+}
+
+/// Up to `PAGE` notes after `since` in journal order, and the cursor to
+/// continue from.
+fn page(notes: Vec<CompanionNote>, since: &Cursor) -> (Vec<CompanionNote>, Cursor) {
+    let notes: Vec<CompanionNote> = notes
+        .into_iter()
+        .filter(|n| Cursor::of(n) > *since)
+        .take(PAGE)
+        .collect();
+    let cursor = notes.last().map_or_else(|| since.clone(), Cursor::of);
+    (notes, cursor)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DeviceRecord {
+    #[serde(flatten)]
+    device: CompanionDevice,
+    /// Hex pairing key; devices paired before keys existed have none and
+    /// need pairing again
+    #[serde(default)]
+    key: String,
+}
+
+/// A note as the device sends it
+#[derive(Debug, Deserialize)]
+struct IncomingNote {
+    id: String,
+    text: String,
+    created: u64,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn random_hex(bytes: usize) -> String {
+    let mut buf = vec![0u8; bytes];
+    OsRng.fill_bytes(&mut buf);
+    buf.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn devices_path() -> PathBuf {
+    app_data_dir().join(DEVICES_FILE)
+}
+
+fn load_devices() -> Vec<DeviceRecord> {
+    fs::read_to_string(devices_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_devices(devices: &[DeviceRecord]) -> Result<(), String> {
+    let path = devices_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(devices).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| e.to_string())?;
+    OsFileStore.restrict(&path);
+    Ok(())
+}
+
+/// Every note in the journal. The journal is a grow-only set keyed by
+/// (device, id): merging is a union, so replays and journals combined by
+/// git from several desktops never conflict.
+fn journal(soul_path: &Path) -> Vec<CompanionNote> {
+    let Ok(entries) = fs::read_dir(soul_path.join(JOURNAL_DIR)) else {
+        return Vec::new();
+    };
+    let mut seen = HashSet::new();
+    let mut notes = Vec::new();
+    for entry in entries.flatten() {
+        if entry.path().extension().is_none_or(|e| e != "jsonl") {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        for note in content
+            .lines()
+            .filter_map(|line| serde_json::from_str::<CompanionNote>(line).ok())
+        {
+            if seen.insert((note.device.clone(), note.id.clone())) {
+                notes.push(note);
+            }
+        }
+    }
+    notes.sort_by(|a, b| (a.received, &a.device, &a.id).cmp(&(b.received, &b.device, &b.id)));
+    notes
+}
+
+fn day(created: u64) -> String {
+    Local
+        .timestamp_millis_opt(created as i64)
+        .single()
+        .map(|t| t.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| "undated".to_string())
+}
+
+/// The day file is rendered from the journal alone, so every desktop
+/// renders the same merge.
+fn render_day(date: &str, notes: &[&CompanionNote], names: &BTreeMap<String, String>) -> String {
+    let mut notes = notes.to_vec();
+    notes.sort_by(|a, b| (a.created, &a.device, &a.id).cmp(&(b.created, &b.device, &b.id)));
+    let mut out = format!("# Companion notes {}\n\n", date);
+    for note in notes {
+        let time = Local
+            .timestamp_millis_opt(note.created as i64)
+            .single()
+            .map(|t| t.format("%H:%M").to_string())
+            .unwrap_or_default();
+        let device = names.get(&note.device).unwrap_or(&note.device);
+        let text = note.text.trim().replace('\n', "\n  ");
+        out.push_str(&format!("- {} ({}) {}\n", time, device, text));
+    }
+    out
+}
+
+#[derive(Default)]
+pub struct Companion {
+    server: Mutex<Option<JoinHandle<()>>>,
+    replays: Replays,
+    /// Serializes read-modify-write of the device file, so a pairing or
+    /// revoke can't be overwritten by a concurrent request
+    devices: Mutex<()>,
+    /// Serializes merges so two requests can't interleave journal appends
+    merging: Mutex<()>,
+}
+
+impl Companion {
+    /// Start, restart or stop the endpoint to match `config`.
+    pub fn apply(&self, app: &AppHandle, config: &CompanionConfig) -> Result<(), String> {
+        if let Some(server) = self.server.lock().take() {
+            server.abort();
+        }
+        if !config.enabled {
+            return Ok(());
+        }
+        let host = if config.lan { "0.0.0.0" } else { "127.0.0.1" };
+        // Bind up front so a taken port is reported to the caller
+        let listener = std::net::TcpListener::bind((host, config.port))
+            .map_err(|e| format!("Cannot listen on {}:{}: {}", host, config.port, e))?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        let app = app.clone();
+        *self.server.lock() = Some(tauri::async_runtime::spawn(async move {
+            let Ok(listener) = tokio::net::TcpListener::from_std(listener) else {
+                return;
+            };
+            while let Ok((stream, _)) = listener.accept().await {
+                let app = app.clone();
+                tauri::async_runtime::spawn(serve(app, stream));
+            }
+        }));
+        Ok(())
+    }
+
+    pub fn devices(&self) -> Vec<CompanionDevice> {
+        load_devices().into_iter().map(|r| r.device).collect()
+    }
+
+    /// Pair a new device. The token is only ever shown here.
+    pub fn pair(&self, name: &str, port: u16) -> Result<CompanionPairing, String> {
+        let (device, key) = self.register(name)?;
+        Ok(CompanionPairing {
+            token: format!("{}.{}", device.id, channel::to_hex(&key)),
+            device,
+            port,
+        })
+    }
+
+    /// Add a device with a new pairing key.
+    fn register(&self, name: &str) -> Result<(CompanionDevice, Key), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Name the device".to_string());
+        }
+        let key = channel::new_key();
+        let device = CompanionDevice {
+            id: random_hex(8),
+            name: name.to_string(),
+            paired_at: now_ms(),
+            last_seen: None,
+        };
+        let _devices = self.devices.lock();
+        let mut devices = load_devices();
+        devices.push(DeviceRecord {
+            device: device.clone(),
+            key: channel::to_hex(&key),
+        });
+        save_devices(&devices)?;
+        Ok((device, key))
+    }
+
+    pub fn revoke(&self, id: &str) -> Result<(), String> {
+        let _devices = self.devices.lock();
+        let mut devices = load_devices();
+        let before = devices.len();
+        devices.retain(|r| r.device.id != id);
+        if devices.len() == before {
+            return Err(format!("No paired device {}", id));
+        }
+        save_devices(&devices)
+    }
+
+    /// The device that signed `request`, its pairing key and the request
+    /// nonce. Replays and requests signed too far from now are refused.
+    fn authenticate(&self, request: &Request) -> Option<(CompanionDevice, Key, Vec<u8>)> {
+        let signature = Signature::parse(request.authorization.as_deref()?)?;
+        let record = load_devices()
+            .into_iter()
+            .find(|r| r.device.id == signature.device)?;
+        let key = channel::key_from_hex(&record.key)?;
+        let now = now_ms();
+        if !signature.verify(&key, &request.method, &request.target, &request.body)
+            || !self.replays.fresh(&signature, now)
+        {
+            return None;
+        }
+        let mut device = record.device;
+        if device
+            .last_seen
+            .is_none_or(|seen| now.saturating_sub(seen) >= LAST_SEEN_INTERVAL)
+        {
+            device.last_seen = Some(now);
+            self.touch(&device.id, now);
+        }
+        Some((device, key, signature.nonce))
+    }
+
+    /// Record when a device was last seen, unless it was revoked meanwhile.
+    fn touch(&self, id: &str, now: u64) {
+        let _devices = self.devices.lock();
+        let mut devices = load_devices();
+        if let Some(record) = devices.iter_mut().find(|r| r.device.id == id) {
+            record.device.last_seen = Some(now);
+            let _ = save_devices(&devices);
+        }
+    }
+
+    /// Merge notes from `device` into the journal and re-render the days
+    /// they fall on. Notes already merged are skipped.
+    fn merge(
+        &self,
+        app: &AppHandle,
+        soul_path: &Path,
+        device: &CompanionDevice,
+        incoming: Vec<IncomingNote>,
+    ) -> Result<Vec<CompanionNote>, String> {
+        let _merging = self.merging.lock();
+        let known: HashSet<(String, String)> = journal(soul_path)
+            .into_iter()
+            .map(|n| (n.device, n.id))
+            .collect();
+        let received = now_ms();
+        let mut added = Vec::new();
+        for note in incoming {
+            let text = note.text.trim();
+            if note.id.is_empty() || text.is_empty() {
+                continue;
+            }
+            if text.chars().count() > MAX_NOTE_CHARS {
+                return Err(format!(
+                    "Note {} is longer than {} characters",
+                    note.id, MAX_NOTE_CHARS
+                ));
+            }
+            if known.contains(&(device.id.clone(), note.id.clone()))
+                || added.iter().any(|a: &CompanionNote| a.id == note.id)
+            {
+                continue;
+            }
+            added.push(CompanionNote {
+                id: note.id,
+                device: device.id.clone(),
+                text: text.to_string(),
+                created: note.created,
+                received,
+            });
+        }
+        if added.is_empty() {
+            return Ok(added);
+        }
+
+        let dir = soul_path.join(JOURNAL_DIR);
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(format!("{}.jsonl", device.id)))
+            .map_err(|e| e.to_string())?;
+        for note in &added {
+            let line = serde_json::to_string(note).map_err(|e| e.to_string())?;
+            writeln!(file, "{}", line).map_err(|e| e.to_string())?;
+        }
+
+        let days: BTreeSet<String> = added.iter().map(|n| day(n.created)).collect();
+        self.render(app, soul_path, &days)?;
+        for note in &added {
+            let _ = app.emit("companion:note", note);
+        }
+        Ok(added)
+    }
+
+    fn render(
+        &self,
+        app: &AppHandle,
+        soul_path: &Path,
+        days: &BTreeSet<String>,
+    ) -> Result<(), String> {
+        let notes = journal(soul_path);
+        let names: BTreeMap<String, String> = load_devices()
+            .into_iter()
+            .map(|r| (r.device.id, r.device.name))
+            .collect();
+        let vault = app.state::<Arc<Vault>>();
+        for date in days {
+            let on_day: Vec<&CompanionNote> =
+                notes.iter().filter(|n| day(n.created) == *date).collect();
+            let relative = format!("{}/{}.md", NOTES_DIR, date);
+            let content = render_day(date, &on_day, &names);
+            // Not an engine change for the write policy or review mode
+            app.state::<Arc<PolicyEngine>>()
+                .accept(&relative, Some(&content));
+            app.state::<Arc<ReviewQueue>>()
+                .accept(&relative, Some(&content));
+            let data = vault.seal(&relative, &content)?;
+            let path = safe_path::safe_resolve(&OsFileStore, soul_path, &relative)?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            fs::write(&path, data).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct StatusBody {
+    status: Option<SoulStatus>,
+    mood: Option<SoulMood>,
+    active_nodes: BTreeMap<String, f64>,
+    working: bool,
+    /// Cursor after the latest note in the journal
+    cursor: String,
+}
+
+#[derive(Serialize)]
+struct NotesBody {
+    notes: Vec<CompanionNote>,
+    cursor: String,
+}
+
+fn status(app: &AppHandle, soul_path: &Path) -> StatusBody {
+    let watcher = app.state::<WatcherState>();
+    StatusBody {
+        status: app.state::<Arc<StatusCache>>().get(soul_path).ok(),
+        mood: watcher.get_mood(),
+        active_nodes: watcher.get_active_nodes_map().into_iter().collect(),
+        working: watcher.is_working(),
+        cursor: journal(soul_path)
+            .last()
+            .map_or_else(Cursor::default, Cursor::of)
+            .encode(),
+    }
+}
+
+/// What `GET /status` returns, for the start of a remote view.
+pub fn snapshot(app: &AppHandle) -> serde_json::Value {
+    app.try_state::<Arc<RwLock<AppConfig>>>()
+        .map(|c| c.read().soul_path.clone())
+        .and_then(|soul_path| serde_json::to_value(status(app, &soul_path)).ok())
+        .unwrap_or_default()
+}
+
+struct Request {
+    method: String,
+    /// Path and query as requested; what the signature covers
+    target: String,
+    path: String,
+    query: String,
+    authorization: Option<String>,
+    websocket_key: Option<String>,
+    body: Vec<u8>,
+}
+
+async fn read_request<R: AsyncRead + Unpin>(stream: &mut R) -> Option<Request> {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    let header_end = loop {
+        if let Some(end) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+        if data.len() > MAX_REQUEST {
+            return None;
+        }
+        match stream.read(&mut buf).await {
+            Ok(0) | Err(_) => return None,
+            Ok(n) => data.extend_from_slice(&buf[..n]),
+        }
+    };
+    let head = String::from_utf8_lossy(&data[..header_end]).to_string();
+    let mut lines = head.lines();
+    let mut first = lines.next()?.split_whitespace();
+    let method = first.next()?.to_string();
+    let target = first.next()?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut length = 0;
+    let mut authorization = None;
+    let mut websocket_key = None;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => length = value.trim().parse().ok()?,
+            "authorization" => authorization = Some(value.trim().to_string()),
+            "sec-websocket-key" => websocket_key = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+    header_end
+        .checked_add(length)
+        .filter(|total| *total <= MAX_REQUEST)?;
+    let mut body = data[header_end..].to_vec();
+    while body.len() < length {
+        match stream.read(&mut buf).await {
+            Ok(0) | Err(_) => return None,
+            Ok(n) => body.extend_from_slice(&buf[..n]),
+        }
+    }
+    body.truncate(length);
+    Some(Request {
+        method,
+        target: target.to_string(),
+        path: path.to_string(),
+        query: query.to_string(),
+        authorization,
+        websocket_key,
+        body,
+    })
+}
+
+fn json<T: Serialize>(value: &T) -> (&'static str, String) {
+    match serde_json::to_string(value) {
+        Ok(body) => ("200 OK", body),
+        Err(e) => ("500 Internal Server Error", error_body(&e.to_string())),
+    }
+}
+
+fn error_body(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+#[derive(Deserialize)]
+struct PairBody {
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    request_id: String,
+    #[serde(default)]
+    code: String,
+    /// The requester's X25519 key, hex; the pairing key comes back sealed
+    /// for it
+    #[serde(default)]
+    public_key: String,
+}
+
+fn pair_peer(app: &AppHandle, companion: &Companion, request: &Request) -> (&'static str, String) {
+    let Some(peers) = app.try_state::<Arc<Peers>>() else {
+        return ("503 Service Unavailable", error_body("Starting"));
+    };
+    let body: PairBody = match serde_json::from_slice(&request.body) {
+        Ok(body) => body,
+        Err(e) => return ("400 Bad Request", error_body(&e.to_string())),
+    };
+    if request.path == "/pair" {
+        return match peers.begin_incoming(app, &body.id, &body.name) {
+            Ok(request_id) => json(&serde_json::json!({ "request_id": request_id })),
+            Err(e) => ("429 Too Many Requests", error_body(&e)),
+        };
+    }
+    let port = app
+        .try_state::<Arc<RwLock<AppConfig>>>()
+        .map_or(0, |c| c.read().companion.port);
+    if body.public_key.is_empty() {
+        return ("400 Bad Request", error_body("Missing public key"));
+    }
+    let paired = peers
+        .confirm_incoming(&body.request_id, &body.code)
+        .and_then(|name| companion.register(&name))
+        .and_then(|(device, key)| {
+            let (public_key, sealed) =
+                channel::seal_pairing_key(&body.public_key, &body.code, &key)?;
+            Ok(serde_json::json!({
+                "device": device,
+                "port": port,
+                "public_key": public_key,
+                "key": sealed,
+            }))
+        });
+    match paired {
+        Ok(reply) => json(&reply),
+        Err(e) => ("403 Forbidden", error_body(&e)),
+    }
+}
+
+fn handle(app: &AppHandle, request: Request) -> (&'static str, String) {
+    let Some(companion) = app.try_state::<Arc<Companion>>() else {
+        return ("503 Service Unavailable", error_body("Starting"));
+    };
+    // Pairing with another SoulOS instance, confirmed by a short code its
+    // user enters; the only requests without a token
+    match (request.method.as_str(), request.path.as_str()) {
+        ("POST", "/pair") | ("POST", "/pair/confirm") => {
+            return pair_peer(app, &companion, &request)
+        }
+        _ => {}
+    }
+    let Some((device, key, nonce)) = companion.authenticate(&request) else {
+        return ("401 Unauthorized", error_body("Pair this device first"));
+    };
+    // Everything after authentication travels sealed
+    let (status, body) = handle_device(app, &companion, &device, &key, &nonce, &request);
+    match channel::seal_body(&channel::response_key(&key, &nonce), body.as_bytes()) {
+        Ok(sealed) => (status, sealed),
+        Err(e) => ("500 Internal Server Error", error_body(&e)),
+    }
+}
+
+fn handle_device(
+    app: &AppHandle,
+    companion: &Companion,
+    device: &CompanionDevice,
+    key: &Key,
+    nonce: &[u8],
+    request: &Request,
+) -> (&'static str, String) {
+    // Nothing leaves or enters a locked app
+    if app
+        .try_state::<Arc<AppLock>>()
+        .is_some_and(|l| l.is_locked())
+    {
+        return ("423 Locked", error_body("SoulOS is locked"));
+    }
+    let Some(soul_path) = app
+        .try_state::<Arc<RwLock<AppConfig>>>()
+        .map(|c| c.read().soul_path.clone())
+    else {
+        return ("503 Service Unavailable", error_body("Starting"));
+    };
+
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/status") => json(&status(app, &soul_path)),
+        ("GET", "/notes") => {
+            let since = url::form_urlencoded::parse(request.query.as_bytes())
+                .find(|(name, _)| name == "since")
+                .map_or_else(Cursor::default, |(_, value)| Cursor::parse(&value));
+            let (notes, cursor) = page(journal(&soul_path), &since);
+            json(&NotesBody {
+                notes,
+                cursor: cursor.encode(),
+            })
+        }
+        ("POST", "/notes") => {
+            let incoming: Vec<IncomingNote> =
+                match channel::open_body(&channel::request_key(key, nonce), &request.body)
+                    .and_then(|body| serde_json::from_slice(&body).map_err(|e| e.to_string()))
+                {
+                    Ok(notes) => notes,
+                    Err(e) => return ("400 Bad Request", error_body(&e)),
+                };
+            match companion.merge(app, &soul_path, device, incoming) {
+                Ok(added) => json(&added),
+                Err(e) => ("400 Bad Request", error_body(&e)),
+            }
+        }
+        ("GET" | "POST", _) => ("404 Not Found", error_body("Not found")),
+        _ => ("405 Method Not Allowed", error_body("Method not allowed")),
+    }
+}
+
+/// A `GET /stream` upgrade that passed the checks.
+pub struct StreamGrant {
+    pub device: String,
+    /// Session key, from the pairing key and both nonces
+    pub key: Key,
+    /// This end's nonce, sent back in the handshake
+    pub nonce: [u8; channel::NONCE_LEN],
+    pub websocket_key: String,
+}
+
+/// Check a `GET /stream` upgrade.
+fn authorize_stream(
+    app: &AppHandle,
+    request: &Request,
+) -> Result<StreamGrant, (&'static str, String)> {
+    let starting = || ("503 Service Unavailable", error_body("Starting"));
+    let companion = app.try_state::<Arc<Companion>>().ok_or_else(starting)?;
+    let enabled = app
+        .try_state::<Arc<RwLock<AppConfig>>>()
+        .ok_or_else(starting)?
+        .read()
+        .companion
+        .remote_view;
+    if !enabled {
+        return Err(("403 Forbidden", error_body("Remote view is off")));
+    }
+    let Some(websocket_key) = &request.websocket_key else {
+        return Err((
+            "400 Bad Request",
+            error_body("Expected a WebSocket upgrade"),
+        ));
+    };
+    let (device, key, device_nonce) = companion
+        .authenticate(request)
+        .ok_or_else(|| ("401 Unauthorized", error_body("Pair this device first")))?;
+    if app
+        .try_state::<Arc<AppLock>>()
+        .is_some_and(|l| l.is_locked())
+    {
+        return Err(("423 Locked", error_body("SoulOS is locked")));
+    }
+    let nonce = channel::new_nonce();
+    Ok(StreamGrant {
+        device: device.id,
+        key: channel::stream_key(&key, &device_nonce, &nonce),
+        nonce,
+        websocket_key: websocket_key.clone(),
+    })
+}
+
+async fn serve(app: AppHandle, mut stream: tokio::net::TcpStream) {
+    // Slow clients don't get to hold a connection open
+    let request = tokio::time::timeout(REQUEST_TIMEOUT, read_request(&mut stream))
+        .await
+        .ok()
+        .flatten();
+    let (status, body) = match request {
+        Some(request) if request.path == "/stream" => {
+            let handle = app.clone();
+            match tokio::task::spawn_blocking(move || authorize_stream(&handle, &request)).await {
+                Ok(Ok(grant)) => {
+                    remote::stream(app, grant, stream).await;
+                    return;
+                }
+                Ok(Err(refused)) => refused,
+                Err(_) => ("500 Internal Server Error", error_body("Failed")),
+            }
+        }
+        Some(request) => {
+            let app = app.clone();
+            tokio::task::spawn_blocking(move || handle(&app, request))
+                .await
+                .unwrap_or(("500 Internal Server Error", error_body("Failed")))
+        }
+        None => (
+            "400 Bad Request",
+            error_body("Malformed, too large or too slow"),
+        ),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(received: u64, device: &str, id: usize) -> CompanionNote {
+        CompanionNote {
+            id: format!("n{:04}", id),
+            device: device.to_string(),
+            text: "note".to_string(),
+            created: received,
+            received,
+        }
+    }
+
+    /// Every note, paging the way a device does.
+    fn page_through(mut notes: Vec<CompanionNote>) -> Vec<String> {
+        notes.sort_by(|a, b| (a.received, &a.device, &a.id).cmp(&(b.received, &b.device, &b.id)));
+        let mut since = Cursor::default();
+        let mut seen = Vec::new();
+        loop {
+            let (batch, cursor) = page(notes.clone(), &Cursor::parse(&since.encode()));
+            if batch.is_empty() {
+                assert_eq!(cursor, since);
+                return seen;
+            }
+            seen.extend(batch.into_iter().map(|n| format!("{}/{}", n.device, n.id)));
+            since = cursor;
+        }
+    }
+
+    #[test]
+    fn pages_through_a_merge_larger_than_a_page() {
+        // One offline sync: every note shares `received`
+        let notes: Vec<CompanionNote> = (0..PAGE * 2 + 7).map(|i| note(1000, "phone", i)).collect();
+        let seen = page_through(notes);
+        assert_eq!(seen.len(), PAGE * 2 + 7);
+        let unique: HashSet<&String> = seen.iter().collect();
+        assert_eq!(unique.len(), seen.len());
+    }
+
+    #[test]
+    fn pages_across_devices_and_merges() {
+        let mut notes = Vec::new();
+        for i in 0..PAGE {
+            notes.push(note(1000, "phone", i));
+            notes.push(note(1000, "tablet", i));
+            notes.push(note(2000, "phone", PAGE + i));
+        }
+        assert_eq!(page_through(notes).len(), PAGE * 3);
+    }
+
+    #[test]
+    fn continues_after_the_cursor() {
+        let notes: Vec<CompanionNote> = (0..3).map(|i| note(1000, "phone", i)).collect();
+        let (batch, cursor) = page(notes.clone(), &Cursor::of(&notes[0]));
+        assert_eq!(batch.len(), 2);
+        assert_eq!(cursor.encode(), "1000:phone:n0002");
+        assert!(page(notes, &cursor).0.is_empty());
+    }
+
+    #[test]
+    fn accepts_a_bare_timestamp_cursor() {
+        let notes = vec![note(1000, "phone", 0), note(2000, "phone", 1)];
+        let (batch, _) = page(notes, &Cursor::parse("1500"));
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].id, "n0001");
+    }
+
+    async fn read(head: &str) -> Option<Request> {
+        // A client that never stops sending
+        let mut stream = head.as_bytes().chain(tokio::io::repeat(b'x'));
+        read_request(&mut stream).await
+    }
+
+    #[tokio::test]
+    async fn reads_a_request_with_a_body() {
+        let request = read("POST /notes?a=1 HTTP/1.1\r\nContent-Length: 3\r\n\r\n")
+            .await
+            .unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.target, "/notes?a=1");
+        assert_eq!(
+            (request.path.as_str(), request.query.as_str()),
+            ("/notes", "a=1")
+        );
+        assert_eq!(request.body, b"xxx");
+    }
+
+    #[tokio::test]
+    async fn refuses_oversized_content_lengths() {
+        for length in [
+            (MAX_REQUEST + 1).to_string(),
+            usize::MAX.to_string(),
+            (usize::MAX - 10).to_string(),
+            "99999999999999999999999".to_string(),
+        ] {
+            let head = format!("POST /notes HTTP/1.1\r\nContent-Length: {}\r\n\r\n", length);
+            assert!(read(&head).await.is_none(), "{}", length);
+        }
+    }
+
+    #[tokio::test]
+    async fn refuses_endless_headers() {
+        let mut stream = tokio::io::repeat(b'x');
+        assert!(read_request(&mut stream).await.is_none());
+    }
+}
@@ -0,0 +1,110 @@
+use std::path::Path;
+
+use serde::Serialize;
+use ts_rs::TS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum CompatLevel {
+    Compatible,
+    /// No package.json or an unparsable version
+    Unknown,
+    /// Works, with known limitations
+    Warning,
+    /// The engine won't be started
+    Incompatible,
+}
+
+/// One row of the compatibility matrix: engine versions in `[min, max)`.
+struct CompatRule {
+    min: (u64, u64, u64),
+    max: (u64, u64, u64),
+    level: CompatLevel,
+    reason: &'static str,
+}
+
+/// What this SoulOS release knows about soul-engine versions. Versions not
+/// covered by a rule are compatible.
+const MATRIX: &[CompatRule] = &[
+    CompatRule {
+        min: (0, 0, 0),
+        max: (1, 0, 0),
+        level: CompatLevel::Incompatible,
+        reason: "soul-engine before 1.0.0 has no REST API; SoulOS needs 1.0.0 or newer",
+    },
+    CompatRule {
+        min: (1, 0, 0),
+        max: (1, 2, 0),
+        level: CompatLevel::Warning,
+        reason: "soul-engine before 1.2.0 tracks no mood or impulses; the mood display stays empty",
+    },
+    CompatRule {
+        min: (2, 0, 0),
+        max: (u64::MAX, 0, 0),
+        level: CompatLevel::Warning,
+        reason: "soul-engine 2.x is newer than this SoulOS release; some views may not work",
+    },
+];
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct Compatibility {
+    pub level: CompatLevel,
+    pub warnings: Vec<String>,
+}
+
+/// "1.2.0", "v1.2", "1.2.0-beta.1" → (1, 2, 0)
+fn parse(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version.trim().trim_start_matches('v');
+    let core = core.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|p| p.parse::<u64>());
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+    let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+    Some((major, minor, patch))
+}
+
+/// Check an engine version against the matrix.
+pub fn check(engine_version: Option<&str>) -> Compatibility {
+    let Some(version) = engine_version.and_then(parse) else {
+        return Compatibility {
+            level: CompatLevel::Unknown,
+            warnings: vec!["soul-engine version could not be determined".to_string()],
+        };
+    };
+    let rules: Vec<&CompatRule> = MATRIX
+        .iter()
+        .filter(|r| r.min <= version && version < r.max)
+        .collect();
+    Compatibility {
+        level: rules
+            .iter()
+            .map(|r| r.level)
+            .max()
+            .unwrap_or(CompatLevel::Compatible),
+        warnings: rules.iter().map(|r| r.reason.to_string()).collect(),
+    }
+}
+
+/// `version` from the package.json in an engine directory
+pub fn engine_version(dir: &Path) -> Option<String> {
+    let data = std::fs::read(dir.join("package.json")).ok()?;
+    let package: serde_json::Value = serde_json::from_slice(&data).ok()?;
+    package["version"].as_str().map(str::to_string)
+}
+
+/// Refuse engines the matrix marks incompatible; everything else may start.
+pub fn ensure_startable(dir: &Path) -> Result<(), String> {
+    let version = engine_version(dir);
+    let compat = check(version.as_deref());
+    if compat.level == CompatLevel::Incompatible {
+        return Err(format!(
+            "soul-engine {} is incompatible with SoulOS {}: {}",
+            version.unwrap_or_default(),
+            env!("CARGO_PKG_VERSION"),
+            compat.warnings.join("; ")
+        ));
+    }
+    Ok(())
+}
@@ -3,10 +3,88 @@ use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
+use crate::companion::CompanionConfig;
+use crate::crash::CrashConfig;
+use crate::downloads::DownloadConfig;
+use crate::env_policy::EnvPolicy;
+use crate::lock::LockConfig;
+use crate::metrics::MetricsConfig;
+use crate::network::NetworkConfig;
+use crate::profiles::ProfileConfig;
+use crate::vault::EncryptionConfig;
+use crate::proxy::{MonitorConfig, ProxyLimits};
+use crate::retention::RetentionConfig;
+use crate::rollup::RollupConfig;
+use crate::watcher::{WatchRoot, WatcherConfig};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
     pub soul_path: PathBuf,
     pub first_run: bool,
+    /// Id in the soul's fingerprint file (.soul-os-id), checked at launch
+    #[serde(default)]
+    pub soul_id: Option<String>,
+    /// Rate limits for the engine API proxy
+    #[serde(default)]
+    pub proxy: ProxyLimits,
+    /// Monitor response caching and push mode
+    #[serde(default)]
+    pub monitor: MonitorConfig,
+    /// Node activity snapshot settings
+    #[serde(default)]
+    pub watcher: WatcherConfig,
+    /// Additional directories watched alongside soul_path
+    #[serde(default)]
+    pub watch_roots: Vec<WatchRoot>,
+    /// Stage engine writes to soul-state files for approval
+    #[serde(default)]
+    pub review_mode: bool,
+    /// Passphrase lock (privacy mode)
+    #[serde(default)]
+    pub lock: LockConfig,
+    /// Soul subdirectories encrypted at rest
+    #[serde(default)]
+    pub encryption: EncryptionConfig,
+    /// Opt-in panic reports and where to send them
+    #[serde(default)]
+    pub crash: CrashConfig,
+    /// Opt-in localhost Prometheus endpoint
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    /// Runtime profile (performance / balanced / battery saver)
+    #[serde(default)]
+    pub profile: ProfileConfig,
+    /// Reload the engine after the env editor saved .env
+    #[serde(default)]
+    pub reload_on_config_change: bool,
+    /// LOG_LEVEL passed to the engine; also the minimum level of the
+    /// stdout lines forwarded to the UI (info when unset)
+    #[serde(default)]
+    pub engine_log_level: Option<String>,
+    /// Start the engine when the app launches with a ready soul
+    #[serde(default = "default_autostart")]
+    pub autostart_engine: bool,
+    /// Start soul-chain when the app launches, if installed
+    #[serde(default = "default_autostart")]
+    pub autostart_chain: bool,
+    /// Size and type policy for embedded browser downloads
+    #[serde(default)]
+    pub downloads: DownloadConfig,
+    /// HTTP proxy and user agent for backend requests and the browser
+    #[serde(default)]
+    pub network: NetworkConfig,
+    /// Environment variables the terminal, sidecars and helper commands inherit
+    #[serde(default)]
+    pub env: EnvPolicy,
+    /// How long event archives and terminal history are kept
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    /// Weekly condensing of old episodic memories, approved in review
+    #[serde(default)]
+    pub rollup: RollupConfig,
+    /// Sync endpoint for the mobile companion
+    #[serde(default)]
+    pub companion: CompanionConfig,
 }
 
 impl Default for AppConfig {
@@ -14,10 +92,35 @@ impl Default for AppConfig {
         Self {
             soul_path: default_soul_dir(),
             first_run: true,
+            soul_id: None,
+            proxy: ProxyLimits::default(),
+            monitor: MonitorConfig::default(),
+            watcher: WatcherConfig::default(),
+            watch_roots: Vec::new(),
+            review_mode: false,
+            lock: LockConfig::default(),
+            encryption: EncryptionConfig::default(),
+            crash: CrashConfig::default(),
+            metrics: MetricsConfig::default(),
+            profile: ProfileConfig::default(),
+            reload_on_config_change: false,
+            engine_log_level: None,
+            autostart_engine: true,
+            autostart_chain: true,
+            downloads: DownloadConfig::default(),
+            network: NetworkConfig::default(),
+            env: EnvPolicy::default(),
+            retention: RetentionConfig::default(),
+            rollup: RollupConfig::default(),
+            companion: CompanionConfig::default(),
         }
     }
 }
 
+fn default_autostart() -> bool {
+    true
+}
+
 /// Default soul directory: ~/Soul
 fn default_soul_dir() -> PathBuf {
     dirs_next::home_dir()
@@ -25,11 +128,16 @@ fn default_soul_dir() -> PathBuf {
         .join("Soul")
 }
 
-/// Where we persist the config: ~/Library/Application Support/com.projectsoul.soulosnew/config.json
-fn config_path() -> PathBuf {
+/// App-owned data outside the soul: ~/Library/Application Support/com.projectsoul.soulosnew
+pub fn app_data_dir() -> PathBuf {
     let base = dirs_next::config_dir()
         .unwrap_or_else(|| PathBuf::from("."));
-    base.join("com.projectsoul.soulosnew").join("config.json")
+    base.join("com.projectsoul.soulosnew")
+}
+
+/// Where we persist the config: <app_data_dir>/config.json
+fn config_path() -> PathBuf {
+    app_data_dir().join("config.json")
 }
 
 impl AppConfig {
@@ -0,0 +1,373 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, OnceLock};
+use std::time::SystemTime;
+
+use regex::Regex;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+use crate::blocking::run_blocking;
+use crate::embeddings::{EmbeddingIndex, MemoryHit};
+use crate::export::files_in;
+use crate::paths::{self, Location};
+use crate::soul_identity;
+use crate::status::StatusCache;
+use crate::vault;
+use crate::watcher::{self, WatcherState};
+
+pub const DEFAULT_BUDGET: usize = 8_000;
+const MIN_BUDGET: usize = 500;
+const MAX_BUDGET: usize = 200_000;
+/// Rough average for English and German prose; no tokenizer is bundled
+const CHARS_PER_TOKEN: usize = 4;
+/// Memory passages fetched for a focus
+const FOCUS_HITS: usize = 24;
+/// Newest memories offered after the focus and the active nodes
+const RECENT_MEMORIES: usize = 12;
+/// Room left below this isn't worth starting another section in
+const MIN_SECTION_TOKENS: usize = 60;
+
+/// One piece of soul content in a bundle.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct BundleSection {
+    /// "seed", "focus" (closest to the focus), "node" (file of an active
+    /// brain node) or "memory" (recently written)
+    pub kind: String,
+    pub title: String,
+    /// Relative to the soul
+    pub path: Option<String>,
+    pub tokens: usize,
+    /// Cut to fit the budget
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ContextBundle {
+    /// Markdown, ready to paste
+    pub text: String,
+    /// Estimated (about four characters per token)
+    pub tokens: usize,
+    pub budget: usize,
+    pub sections: Vec<BundleSection>,
+    /// Where the bundle went: "clipboard", "file" or "none"
+    pub target: String,
+    /// The file written for target "file"
+    pub path: Option<String>,
+    /// What was left out and why (no embedding provider, clipboard tool
+    /// missing, ...)
+    pub notes: Vec<String>,
+}
+
+/// Content offered to the bundle, most relevant first.
+struct Candidate {
+    kind: &'static str,
+    title: String,
+    path: Option<String>,
+    text: String,
+}
+
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// The `#SEED` header with the @META and @KERN blocks, or the start of the
+/// file when it has neither.
+fn seed_meta(seed: &str) -> String {
+    static BLOCKS: OnceLock<Regex> = OnceLock::new();
+    let blocks =
+        BLOCKS.get_or_init(|| Regex::new(r"@(?:META|KERN)\{[^}]*\}").expect("seed pattern"));
+    let mut parts: Vec<&str> = seed
+        .lines()
+        .filter(|line| line.starts_with("#SEED"))
+        .take(1)
+        .collect();
+    parts.extend(blocks.find_iter(seed).map(|m| m.as_str()));
+    if parts.is_empty() {
+        return seed.chars().take(2_000).collect();
+    }
+    parts.join("\n")
+}
+
+/// Text of a soul file, None for encrypted or unreadable ones: what the
+/// vault protects isn't handed to outside tools.
+fn read_plain(path: &Path) -> Option<String> {
+    let data = fs::read(path).ok()?;
+    if vault::is_encrypted(&data) {
+        return None;
+    }
+    String::from_utf8(data).ok()
+}
+
+fn relative(soul_path: &Path, path: &Path) -> Option<String> {
+    path.strip_prefix(soul_path)
+        .ok()
+        .map(|p| p.to_string_lossy().replace('\\', "/"))
+}
+
+fn is_text(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == "md" || ext == "txt")
+}
+
+/// Persona files of the brain nodes lit right now, brightest first.
+fn node_files(app: &AppHandle, soul_path: &Path) -> Vec<Candidate> {
+    let mut levels: Vec<(String, f64)> = app
+        .state::<WatcherState>()
+        .get_active_nodes_map()
+        .into_iter()
+        .filter(|(node, level)| *level > 0.0 && node != "seed")
+        .collect();
+    levels.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let mut files: Vec<PathBuf> = files_in(soul_path, false);
+    for dir in paths::existing(soul_path, Location::Soul) {
+        files.extend(files_in(&dir, true));
+    }
+    let mut candidates = Vec::new();
+    for (node, _) in levels {
+        for file in files.iter().filter(|f| is_text(f)) {
+            let Some(relative) = relative(soul_path, file) else {
+                continue;
+            };
+            if watcher::resolve_node(&relative) != Some(node.as_str()) {
+                continue;
+            }
+            if let Some(text) = read_plain(file) {
+                candidates.push(Candidate {
+                    kind: "node",
+                    title: format!("{} ({})", relative, node),
+                    path: Some(relative),
+                    text,
+                });
+            }
+        }
+    }
+    candidates
+}
+
+/// The newest memories outside the archive.
+fn recent_memories(soul_path: &Path) -> Vec<Candidate> {
+    let mut memories: Vec<(SystemTime, PathBuf)> = paths::existing(soul_path, Location::Memories)
+        .iter()
+        .flat_map(|dir| files_in(dir, true))
+        .filter(|file| is_text(file))
+        .filter_map(|file| {
+            let modified = fs::metadata(&file).and_then(|m| m.modified()).ok()?;
+            Some((modified, file))
+        })
+        .collect();
+    memories.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    memories
+        .into_iter()
+        .filter_map(|(_, file)| {
+            let relative = relative(soul_path, &file)?;
+            if paths::memory_category(&relative) == Some("archive") {
+                return None;
+            }
+            Some(Candidate {
+                kind: "memory",
+                title: relative.clone(),
+                path: Some(relative),
+                text: read_plain(&file)?,
+            })
+        })
+        .take(RECENT_MEMORIES)
+        .collect()
+}
+
+fn focus_passages(hits: Vec<MemoryHit>) -> Vec<Candidate> {
+    hits.into_iter()
+        .map(|hit| Candidate {
+            kind: "focus",
+            title: format!("{} (relevance {:.2})", hit.path, hit.score),
+            path: Some(hit.path),
+            text: hit.text,
+        })
+        .collect()
+}
+
+/// Concatenate candidates in order while they fit `budget`. A candidate
+/// too large for the rest is cut if enough room is left, otherwise skipped
+/// in favor of smaller ones further down.
+fn assemble(name: &str, candidates: Vec<Candidate>, budget: usize) -> (String, Vec<BundleSection>) {
+    let mut text = format!("# Soul context: {}\n\n", name);
+    let mut used = estimate_tokens(&text);
+    let mut sections: Vec<BundleSection> = Vec::new();
+    // Whole files in the bundle, and passages of files
+    let mut files: Vec<String> = Vec::new();
+    let mut passages: Vec<String> = Vec::new();
+    for candidate in candidates {
+        let body = candidate.text.trim();
+        let path = candidate.path.clone().unwrap_or_default();
+        if body.is_empty() || files.contains(&path) || passages.iter().any(|p| p == body) {
+            continue;
+        }
+        let heading = format!("## {}\n\n", candidate.title);
+        let remaining = budget.saturating_sub(used + estimate_tokens(&heading));
+        if remaining < MIN_SECTION_TOKENS {
+            continue;
+        }
+        let tokens = estimate_tokens(body);
+        let (included, truncated) = if tokens > remaining {
+            let cut: String = body
+                .chars()
+                .take((remaining - 1) * CHARS_PER_TOKEN)
+                .collect();
+            (format!("{}…", cut.trim_end()), true)
+        } else {
+            (body.to_string(), false)
+        };
+        let section = format!("{}{}\n\n", heading, included);
+        used += estimate_tokens(&section);
+        text.push_str(&section);
+        sections.push(BundleSection {
+            kind: candidate.kind.to_string(),
+            title: candidate.title,
+            path: candidate.path,
+            tokens: estimate_tokens(&included),
+            truncated,
+        });
+        if candidate.kind == "focus" {
+            passages.push(body.to_string());
+        } else {
+            files.push(path);
+        }
+    }
+    (text.trim_end().to_string() + "\n", sections)
+}
+
+/// Put `text` on the system clipboard through the platform's tool.
+fn copy_to_clipboard(text: &str) -> Result<(), String> {
+    let tools: &[(&str, &[&str])] = if cfg!(target_os = "macos") {
+        &[("pbcopy", &[])]
+    } else if cfg!(windows) {
+        &[("clip", &[])]
+    } else {
+        &[
+            ("wl-copy", &[]),
+            ("xclip", &["-selection", "clipboard"]),
+            ("xsel", &["--clipboard", "--input"]),
+        ]
+    };
+    for (program, args) in tools {
+        let Ok(mut child) = Command::new(program)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        let written = child
+            .stdin
+            .take()
+            .map(|mut stdin| stdin.write_all(text.as_bytes()));
+        if matches!(written, Some(Ok(()))) && child.wait().is_ok_and(|s| s.success()) {
+            return Ok(());
+        }
+    }
+    Err("no clipboard tool available".to_string())
+}
+
+/// <temp_dir>/soul-context-<soul>.md, replaced by every bundle.
+fn write_temp(soul_path: &Path, text: &str) -> Result<PathBuf, String> {
+    let path = std::env::temp_dir().join(format!(
+        "soul-context-{}.md",
+        soul_identity::storage_key(soul_path)
+    ));
+    fs::write(&path, text).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+/// Select the soul content most relevant to `focus` (or to what the soul
+/// is doing right now) within `budget` tokens: SEED meta first, then the
+/// memory passages closest to the focus, the files of the active brain
+/// nodes and the newest memories. `target` is "clipboard" (the default,
+/// falling back to a file), "file" or "none".
+pub async fn build(
+    app: &AppHandle,
+    soul_path: PathBuf,
+    budget: Option<usize>,
+    focus: Option<String>,
+    target: Option<String>,
+) -> Result<ContextBundle, String> {
+    let budget = budget
+        .unwrap_or(DEFAULT_BUDGET)
+        .clamp(MIN_BUDGET, MAX_BUDGET);
+    let target = target.unwrap_or_else(|| "clipboard".to_string());
+    if !matches!(target.as_str(), "clipboard" | "file" | "none") {
+        return Err(format!("Unknown target: {}", target));
+    }
+    let mut notes = Vec::new();
+
+    let focus = focus
+        .map(|f| f.trim().to_string())
+        .filter(|f| !f.is_empty());
+    let hits = match &focus {
+        Some(focus) => {
+            let index = app.state::<Arc<EmbeddingIndex>>();
+            match index.search(app, &soul_path, focus, FOCUS_HITS).await {
+                Ok(hits) => hits,
+                Err(e) => {
+                    notes.push(format!("Focus passages left out: {}", e));
+                    Vec::new()
+                }
+            }
+        }
+        None => Vec::new(),
+    };
+
+    let handle = app.clone();
+    let mut bundle = run_blocking(app, "build_context_bundle", None, move |_| {
+        let name = handle
+            .state::<Arc<StatusCache>>()
+            .get(&soul_path)
+            .map(|status| status.name)
+            .unwrap_or_else(|_| "Soul".to_string());
+        let mut candidates = Vec::new();
+        if let Some(seed) = read_plain(&soul_path.join("SEED.md")) {
+            candidates.push(Candidate {
+                kind: "seed",
+                title: "SEED.md".to_string(),
+                path: Some("SEED.md".to_string()),
+                text: seed_meta(&seed),
+            });
+        }
+        candidates.extend(focus_passages(hits));
+        candidates.extend(node_files(&handle, &soul_path));
+        candidates.extend(recent_memories(&soul_path));
+        let (text, sections) = assemble(&name, candidates, budget);
+
+        let mut notes = Vec::new();
+        let mut target = target;
+        let mut path = None;
+        if target == "clipboard" {
+            if let Err(e) = copy_to_clipboard(&text) {
+                notes.push(format!("Not copied ({}), written to a file instead", e));
+                target = "file".to_string();
+            }
+        }
+        if target == "file" {
+            path = Some(write_temp(&soul_path, &text)?.to_string_lossy().to_string());
+        }
+        Ok(ContextBundle {
+            tokens: estimate_tokens(&text),
+            text,
+            budget,
+            sections,
+            target,
+            path,
+            notes,
+        })
+    })
+    .await?;
+    notes.append(&mut bundle.notes);
+    bundle.notes = notes;
+    Ok(bundle)
+}
@@ -0,0 +1,247 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use ts_rs::TS;
+
+use crate::config::app_data_dir;
+use crate::http::{self, Http, Policy};
+use crate::pii;
+
+/// Log lines kept in memory and attached to a crash report
+const MAX_LOG_LINES: usize = 100;
+const UPLOAD: Policy = Policy {
+    name: "crash_upload",
+    timeout: Duration::from_secs(15),
+    retries: 2,
+    backoff: Duration::from_secs(1),
+    idempotent: false,
+};
+
+/// Crash reporting, off unless the user opts in.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CrashConfig {
+    /// Write panic reports to <app_data_dir>/crashes
+    #[serde(default)]
+    pub enabled: bool,
+    /// Where `send_crash_reports` POSTs reports (JSON); nothing is sent without one
+    #[serde(default)]
+    pub endpoint: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CrashReport {
+    pub id: String,
+    /// Unix ms
+    #[ts(type = "number")]
+    pub timestamp: u64,
+    pub version: String,
+    pub os: String,
+    pub arch: String,
+    pub thread: String,
+    pub message: String,
+    /// file:line of the panic
+    pub location: Option<String>,
+    pub backtrace: String,
+    /// Recent sidecar stderr and backend log lines, oldest first
+    pub log: Vec<String>,
+}
+
+impl CrashReport {
+    /// Copy with PII and the home directory scrubbed — exactly what gets uploaded.
+    pub fn redacted(&self) -> Self {
+        let home = dirs_next::home_dir().map(|h| h.to_string_lossy().to_string());
+        let scrub = |text: &str| {
+            let text = match &home {
+                Some(home) if !home.is_empty() => text.replace(home.as_str(), "~"),
+                _ => text.to_string(),
+            };
+            pii::redact(&text).0
+        };
+        Self {
+            message: scrub(&self.message),
+            location: self.location.as_deref().map(scrub),
+            backtrace: scrub(&self.backtrace),
+            log: self.log.iter().map(|line| scrub(line)).collect(),
+            ..self.clone()
+        }
+    }
+}
+
+fn crash_dir() -> PathBuf {
+    app_data_dir().join("crashes")
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Collects log context and turns panics into reports on disk.
+pub struct CrashReporter {
+    config: RwLock<CrashConfig>,
+    log: Mutex<VecDeque<String>>,
+}
+
+impl CrashReporter {
+    pub fn new(config: CrashConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            log: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn config(&self) -> CrashConfig {
+        self.config.read().clone()
+    }
+
+    pub fn set_config(&self, config: CrashConfig) {
+        *self.config.write() = config;
+    }
+
+    /// Remember a log line as context for the next crash report.
+    pub fn record(&self, line: String) {
+        if !self.config.read().enabled {
+            return;
+        }
+        let mut log = self.log.lock();
+        if log.len() == MAX_LOG_LINES {
+            log.pop_front();
+        }
+        log.push_back(line);
+    }
+
+    fn write_report(&self, info: &std::panic::PanicHookInfo) {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Box<dyn Any>".to_string());
+        let timestamp = now_ms();
+        let report = CrashReport {
+            id: format!("crash-{}", timestamp),
+            timestamp,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            thread: std::thread::current()
+                .name()
+                .unwrap_or("<unnamed>")
+                .to_string(),
+            message,
+            location: info
+                .location()
+                .map(|l| format!("{}:{}", l.file(), l.line())),
+            backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+            // try_lock: the panic may have happened while holding it
+            log: self
+                .log
+                .try_lock()
+                .map(|log| log.iter().cloned().collect())
+                .unwrap_or_default(),
+        };
+        let dir = crash_dir();
+        let _ = fs::create_dir_all(&dir);
+        if let Ok(json) = serde_json::to_vec_pretty(&report) {
+            let _ = fs::write(dir.join(format!("{}.json", report.id)), json);
+        }
+    }
+
+    /// Reports left by earlier runs, oldest first (unredacted).
+    pub fn pending(&self) -> Vec<CrashReport> {
+        let Ok(dir) = fs::read_dir(crash_dir()) else {
+            return Vec::new();
+        };
+        let mut reports: Vec<CrashReport> = dir
+            .flatten()
+            .filter_map(|e| serde_json::from_slice(&fs::read(e.path()).ok()?).ok())
+            .collect();
+        reports.sort_by_key(|r| r.timestamp);
+        reports
+    }
+
+    /// Delete reports by id. Returns how many were removed.
+    pub fn dismiss(&self, ids: &[String]) -> usize {
+        ids.iter()
+            .filter(|id| valid_id(id))
+            .filter(|id| fs::remove_file(crash_dir().join(format!("{}.json", id))).is_ok())
+            .count()
+    }
+}
+
+fn valid_id(id: &str) -> bool {
+    id.starts_with("crash-") && id[6..].chars().all(|c| c.is_ascii_digit())
+}
+
+/// Write a report for every panic while crash reporting is enabled, then
+/// fall through to the default hook. Native crashes (segfaults, aborts
+/// outside Rust) are not captured.
+pub fn install_panic_hook(reporter: Arc<CrashReporter>) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if reporter.config.read().enabled {
+            reporter.write_report(info);
+        }
+        previous(info);
+    }));
+}
+
+/// Emit `crash:pending` shortly after startup when reports are waiting for
+/// the user's decision.
+pub fn announce_pending(app: AppHandle) {
+    std::thread::spawn(move || {
+        let reporter = app.state::<Arc<CrashReporter>>().inner().clone();
+        if !reporter.config().enabled {
+            return;
+        }
+        let pending: Vec<CrashReport> = reporter.pending().iter().map(|r| r.redacted()).collect();
+        if pending.is_empty() {
+            return;
+        }
+        // Small delay to let the window finish loading
+        std::thread::sleep(Duration::from_secs(1));
+        let _ = app.emit("crash:pending", &pending);
+    });
+}
+
+/// Upload the redacted reports to the configured endpoint and delete the
+/// ones that were accepted. Returns how many were sent.
+pub async fn upload(
+    app: &AppHandle,
+    reporter: &CrashReporter,
+    ids: &[String],
+) -> Result<usize, String> {
+    let config = reporter.config();
+    if !config.enabled {
+        return Err("Crash reporting is disabled".to_string());
+    }
+    let endpoint = config
+        .endpoint
+        .filter(|e| e.starts_with("https://") || e.starts_with("http://"))
+        .ok_or_else(|| "No crash report endpoint configured".to_string())?;
+
+    let client = app.state::<Arc<Http>>().external();
+    let mut sent = Vec::new();
+    for report in reporter.pending().iter().filter(|r| ids.contains(&r.id)) {
+        let redacted = report.redacted();
+        let resp = http::send(app, &UPLOAD, || client.post(&endpoint).json(&redacted))
+            .await
+            .map_err(|e| e.to_string())?;
+        if !resp.status().is_success() {
+            return Err(format!("Crash report endpoint returned {}", resp.status()));
+        }
+        sent.push(report.id.clone());
+    }
+    reporter.dismiss(&sent);
+    Ok(sent.len())
+}
@@ -0,0 +1,403 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use ts_rs::TS;
+
+use crate::background;
+use crate::blocking::{run_command, CancelToken};
+use crate::config::{app_data_dir, AppConfig};
+use crate::paths::{self, Location};
+use crate::types::{SoulMood, SoulPulse};
+
+/// How often the scheduler checks whether yesterday's digest still needs writing
+const SCHEDULE_INTERVAL: Duration = Duration::from_secs(15 * 60);
+/// Mood changes listed in a digest before the trajectory gets truncated
+const MAX_MOOD_ROWS: usize = 48;
+
+/// Pulses and moods only exist as transient events, so they are journaled
+/// per day under <app_data_dir>/journal for the digest to pick up later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum JournalEntry {
+    Pulse {
+        ts: u64,
+        activity_type: String,
+        label: String,
+    },
+    Mood {
+        ts: u64,
+        valence: Option<f64>,
+        energy: Option<f64>,
+        label: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct DigestInfo {
+    pub date: String,
+    pub path: String,
+    pub markdown: String,
+}
+
+fn journal_path(date: NaiveDate) -> PathBuf {
+    app_data_dir()
+        .join("journal")
+        .join(format!("{}.jsonl", date.format("%Y-%m-%d")))
+}
+
+fn append_journal(ts: u64, entry: &JournalEntry) {
+    let Some(date) = local_date(ts) else {
+        return;
+    };
+    let path = journal_path(date);
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let (Ok(mut file), Ok(line)) = (
+        OpenOptions::new().create(true).append(true).open(&path),
+        serde_json::to_string(entry),
+    ) {
+        let _ = writeln!(file, "{}", line);
+    }
+}
+
+pub fn record_pulse(pulse: &SoulPulse) {
+    append_journal(
+        pulse.timestamp,
+        &JournalEntry::Pulse {
+            ts: pulse.timestamp,
+            activity_type: pulse.activity_type.clone(),
+            label: pulse.label.clone(),
+        },
+    );
+}
+
+pub fn record_mood(mood: &SoulMood) {
+    let ts = Local::now().timestamp_millis() as u64;
+    append_journal(
+        ts,
+        &JournalEntry::Mood {
+            ts,
+            valence: mood.valence,
+            energy: mood.energy,
+            label: mood.label.clone(),
+        },
+    );
+}
+
+fn local_date(ts_ms: u64) -> Option<NaiveDate> {
+    Local
+        .timestamp_millis_opt(ts_ms as i64)
+        .single()
+        .map(|dt| dt.date_naive())
+}
+
+fn local_time(ts_ms: u64) -> String {
+    Local
+        .timestamp_millis_opt(ts_ms as i64)
+        .single()
+        .map(|dt| dt.format("%H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// Local-time [start, end) of a day.
+pub fn day_bounds(date: NaiveDate) -> Result<(DateTime<Local>, DateTime<Local>), String> {
+    let start_of = |d: NaiveDate| {
+        d.and_hms_opt(0, 0, 0)
+            .and_then(|dt| Local.from_local_datetime(&dt).earliest())
+            .ok_or_else(|| format!("Invalid date: {}", d))
+    };
+    let next = date
+        .succ_opt()
+        .ok_or_else(|| format!("Invalid date: {}", date))?;
+    Ok((start_of(date)?, start_of(next)?))
+}
+
+pub fn parse_date(date: Option<&str>) -> Result<NaiveDate, String> {
+    match date {
+        Some(d) => NaiveDate::parse_from_str(d, "%Y-%m-%d")
+            .map_err(|_| format!("Expected a YYYY-MM-DD date, got {}", d)),
+        None => Ok(Local::now().date_naive()),
+    }
+}
+
+pub fn digest_path(soul_path: &Path, date: NaiveDate) -> PathBuf {
+    paths::resolve(soul_path, Location::Heartbeat).join(format!("digest-{}.md", date.format("%Y-%m-%d")))
+}
+
+pub fn read_journal(date: NaiveDate) -> Vec<JournalEntry> {
+    fs::read_to_string(journal_path(date))
+        .map(|content| {
+            content
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Event-bus events (`.soul-events/*.jsonl`) within the day, counted by type.
+fn count_events(soul_path: &Path, start_ms: u64, end_ms: u64) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    let Ok(entries) = fs::read_dir(soul_path.join(".soul-events")) else {
+        return counts;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|e| e != "jsonl") {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        for line in content.lines() {
+            let Ok(event) = serde_json::from_str::<serde_json::Value>(line) else {
+                continue;
+            };
+            let ts = event.get("ts").and_then(|t| t.as_u64()).unwrap_or(0);
+            if ts < start_ms || ts >= end_ms {
+                continue;
+            }
+            let kind = event
+                .get("type")
+                .and_then(|t| t.as_str())
+                .unwrap_or("unknown");
+            *counts.entry(kind.to_string()).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// Memory files (memories/ or erinnerungen/) last modified within the day.
+fn memories_written(
+    soul_path: &Path,
+    start_ms: u64,
+    end_ms: u64,
+    token: &CancelToken,
+) -> Result<Vec<String>, String> {
+    let mut written = Vec::new();
+    let mut dirs = paths::existing(soul_path, Location::Memories);
+    while let Some(dir) = dirs.pop() {
+        token.check()?;
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let modified = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as u64);
+            if modified.is_some_and(|m| m >= start_ms && m < end_ms) {
+                if let Ok(relative) = path.strip_prefix(soul_path) {
+                    written.push(relative.to_string_lossy().to_string());
+                }
+            }
+        }
+    }
+    written.sort();
+    Ok(written)
+}
+
+/// `(time, subject)` of git commits in the soul repo within the day.
+fn day_commits(
+    soul_path: &Path,
+    start: &DateTime<Local>,
+    end: &DateTime<Local>,
+    token: &CancelToken,
+) -> Vec<(String, String)> {
+    if !soul_path.join(".git").exists() {
+        return Vec::new();
+    }
+    let output = run_command(
+        Command::new("git").current_dir(soul_path).args([
+            "log",
+            "--format=%ai|%s",
+            &format!("--since={}", start.to_rfc3339()),
+            &format!("--until={}", end.to_rfc3339()),
+        ]),
+        token,
+    );
+    let Ok(output) = output else {
+        return Vec::new();
+    };
+    let mut commits: Vec<(String, String)> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (date, subject) = line.split_once('|')?;
+            let time = date.split_whitespace().nth(1)?.get(..5)?;
+            Some((time.to_string(), subject.to_string()))
+        })
+        .collect();
+    commits.reverse();
+    commits
+}
+
+/// Aggregate one day into a markdown digest and write it to heartbeat/digest-<date>.md.
+pub fn generate(soul_path: &Path, date: NaiveDate, token: &CancelToken) -> Result<DigestInfo, String> {
+    let (start, end) = day_bounds(date)?;
+    let start_ms = start.timestamp_millis() as u64;
+    let end_ms = end.timestamp_millis() as u64;
+
+    let journal = read_journal(date);
+    token.set_progress(1, 5);
+    let events = count_events(soul_path, start_ms, end_ms);
+    token.check()?;
+    token.set_progress(2, 5);
+    let memories = memories_written(soul_path, start_ms, end_ms, token)?;
+    token.set_progress(3, 5);
+    let commits = day_commits(soul_path, &start, &end, token);
+    token.check()?;
+    token.set_progress(4, 5);
+
+    let mut pulse_counts: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut moods = Vec::new();
+    for entry in &journal {
+        match entry {
+            JournalEntry::Pulse { activity_type, .. } => {
+                *pulse_counts.entry(activity_type.as_str()).or_insert(0) += 1;
+            }
+            JournalEntry::Mood {
+                ts,
+                valence,
+                energy,
+                label,
+            } => {
+                let label = label.clone().unwrap_or_else(|| "—".to_string());
+                let changed = moods
+                    .last()
+                    .is_none_or(|(_, last, _, _): &(u64, String, _, _)| *last != label);
+                if changed {
+                    moods.push((*ts, label, *valence, *energy));
+                }
+            }
+        }
+    }
+
+    let mut md = format!("# Daily Digest — {}\n\n", date.format("%Y-%m-%d"));
+    let pulse_total: usize = pulse_counts.values().sum();
+    let event_total: usize = events.values().sum();
+    md.push_str(&format!(
+        "{} pulses · {} events · {} memories · {} commits\n\n",
+        pulse_total,
+        event_total,
+        memories.len(),
+        commits.len()
+    ));
+
+    md.push_str("## Activity\n\n");
+    if pulse_counts.is_empty() {
+        md.push_str("_No pulses recorded._\n");
+    }
+    for (activity, count) in &pulse_counts {
+        md.push_str(&format!("- {}: {}\n", activity, count));
+    }
+
+    md.push_str("\n## Mood\n\n");
+    if moods.is_empty() {
+        md.push_str("_No mood recorded._\n");
+    }
+    for (ts, label, valence, energy) in moods.iter().take(MAX_MOOD_ROWS) {
+        let values = match (valence, energy) {
+            (Some(v), Some(e)) => format!(" (valence {:.2}, energy {:.2})", v, e),
+            _ => String::new(),
+        };
+        md.push_str(&format!("- {} {}{}\n", local_time(*ts), label, values));
+    }
+    if moods.len() > MAX_MOOD_ROWS {
+        md.push_str(&format!("- … {} more\n", moods.len() - MAX_MOOD_ROWS));
+    }
+
+    md.push_str("\n## Events\n\n");
+    if events.is_empty() {
+        md.push_str("_No events recorded._\n");
+    }
+    for (kind, count) in &events {
+        md.push_str(&format!("- {}: {}\n", kind, count));
+    }
+
+    md.push_str("\n## Memories written\n\n");
+    if memories.is_empty() {
+        md.push_str("_None._\n");
+    }
+    for memory in &memories {
+        md.push_str(&format!("- {}\n", memory));
+    }
+
+    md.push_str("\n## Commits\n\n");
+    if commits.is_empty() {
+        md.push_str("_None._\n");
+    }
+    for (time, subject) in &commits {
+        md.push_str(&format!("- {} {}\n", time, subject));
+    }
+
+    let path = digest_path(soul_path, date);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(&path, &md).map_err(|e| e.to_string())?;
+    token.set_progress(5, 5);
+
+    Ok(DigestInfo {
+        date: date.format("%Y-%m-%d").to_string(),
+        path: path.to_string_lossy().to_string(),
+        markdown: md,
+    })
+}
+
+pub fn emit_ready(app: &AppHandle, info: &DigestInfo) {
+    let _ = app.emit(
+        "digest:ready",
+        serde_json::json!({ "date": info.date, "path": info.path }),
+    );
+}
+
+/// Writes yesterday's digest once the day is over, if the app saw any
+/// activity that day and no digest exists yet.
+pub fn start_digest_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(SCHEDULE_INTERVAL).await;
+            if background::suspended(&app) {
+                continue;
+            }
+
+            let Some(yesterday) = Local::now().date_naive().pred_opt() else {
+                continue;
+            };
+            let soul_path = app
+                .state::<Arc<RwLock<AppConfig>>>()
+                .read()
+                .soul_path
+                .clone();
+            if digest_path(&soul_path, yesterday).exists() || !journal_path(yesterday).exists() {
+                continue;
+            }
+
+            let result = tokio::task::spawn_blocking(move || {
+                generate(&soul_path, yesterday, &CancelToken::default())
+            })
+            .await;
+            if let Ok(Ok(info)) = result {
+                emit_ready(&app, &info);
+            }
+        }
+    });
+}
@@ -0,0 +1,369 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tauri::webview::DownloadEvent;
+use tauri::{AppHandle, Emitter, Manager};
+use ts_rs::TS;
+
+use crate::config::AppConfig;
+
+/// Where browser downloads land, relative to the soul
+const DOWNLOAD_DIR: &str = "media/downloads";
+/// Attachment registry inside DOWNLOAD_DIR
+const REGISTRY_FILE: &str = "attachments.json";
+/// How often a running download's size is reported
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Media types the engine's multimodal store knows (everything else is "other")
+const MEDIA_TYPES: &[(&str, &[&str])] = &[
+    ("image", &["png", "jpg", "jpeg", "gif", "webp"]),
+    ("audio", &["mp3", "ogg", "wav", "m4a"]),
+    ("document", &["pdf", "txt"]),
+];
+
+/// Size and type policy for files downloaded in the embedded browser.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DownloadConfig {
+    /// Larger downloads are deleted when they finish
+    #[serde(default = "default_max_bytes")]
+    #[ts(type = "number")]
+    pub max_bytes: u64,
+    /// Extensions (without dot) that may be downloaded; empty allows all
+    /// that aren't blocked
+    #[serde(default)]
+    pub allowed_extensions: Vec<String>,
+    #[serde(default = "default_blocked_extensions")]
+    pub blocked_extensions: Vec<String>,
+}
+
+fn default_max_bytes() -> u64 {
+    100 * 1024 * 1024
+}
+
+fn default_blocked_extensions() -> Vec<String> {
+    [
+        "exe", "msi", "bat", "cmd", "com", "scr", "ps1", "sh", "app", "dmg", "pkg", "jar",
+    ]
+    .iter()
+    .map(|e| e.to_string())
+    .collect()
+}
+
+impl Default for DownloadConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: default_max_bytes(),
+            allowed_extensions: Vec::new(),
+            blocked_extensions: default_blocked_extensions(),
+        }
+    }
+}
+
+impl DownloadConfig {
+    fn allows(&self, file: &str) -> bool {
+        let ext = Path::new(file)
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let listed = |list: &[String]| {
+            list.iter()
+                .any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(&ext))
+        };
+        !listed(&self.blocked_extensions)
+            && (self.allowed_extensions.is_empty() || listed(&self.allowed_extensions))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct DownloadProgress {
+    #[ts(type = "number")]
+    pub id: u64,
+    pub url: String,
+    /// Relative to the soul path
+    pub file: String,
+    /// "started", "progress", "finished", "failed" or "blocked"
+    pub status: String,
+    #[ts(type = "number")]
+    pub bytes: u64,
+    pub error: Option<String>,
+}
+
+/// A finished download, kept in media/downloads/attachments.json until the
+/// soul links it to a memory.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct MediaAttachment {
+    /// Relative to the soul path
+    pub path: String,
+    pub url: String,
+    /// "image", "audio", "document" or "other"
+    pub media_type: String,
+    #[ts(type = "number")]
+    pub bytes: u64,
+    /// Unix ms
+    #[ts(type = "number")]
+    pub downloaded: u64,
+    /// Memory the file is attached to; None until one is linked
+    #[ts(type = "number | null")]
+    pub memory_id: Option<u64>,
+}
+
+struct ActiveDownload {
+    id: u64,
+    path: PathBuf,
+    file: String,
+}
+
+/// Downloads started in the embedded browser, keyed by URL (the webview
+/// reports the finish by URL only).
+#[derive(Default)]
+pub struct Downloads {
+    active: Mutex<HashMap<String, Arc<ActiveDownload>>>,
+    next_id: AtomicU64,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Keep only a plain file name; the webview's suggestion comes from the site.
+fn sanitize(name: &str) -> String {
+    let clean: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '.' | '-' | '_' | ' ') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let clean = clean.trim().trim_start_matches('.').to_string();
+    if clean.is_empty() {
+        "download".to_string()
+    } else {
+        clean
+    }
+}
+
+/// `name`, or `stem (n).ext` if a file of that name exists.
+fn unique_path(dir: &Path, name: &str) -> PathBuf {
+    let path = dir.join(name);
+    if !path.exists() {
+        return path;
+    }
+    let stem = Path::new(name)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = Path::new(name)
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    (1..)
+        .map(|n| dir.join(format!("{} ({}){}", stem, n, ext)))
+        .find(|p| !p.exists())
+        .unwrap_or(path)
+}
+
+fn media_type(file: &str) -> &'static str {
+    let ext = Path::new(file)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    MEDIA_TYPES
+        .iter()
+        .find(|(_, exts)| exts.contains(&ext.as_str()))
+        .map_or("other", |(kind, _)| kind)
+}
+
+fn registry_path(soul_path: &Path) -> PathBuf {
+    soul_path.join(DOWNLOAD_DIR).join(REGISTRY_FILE)
+}
+
+/// Registered attachments, oldest first.
+pub fn attachments(soul_path: &Path) -> Vec<MediaAttachment> {
+    fs::read(registry_path(soul_path))
+        .ok()
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default()
+}
+
+fn register(soul_path: &Path, attachment: MediaAttachment) -> Result<(), String> {
+    let mut all = attachments(soul_path);
+    all.retain(|a| a.path != attachment.path);
+    all.push(attachment);
+    let json = serde_json::to_vec_pretty(&all).map_err(|e| e.to_string())?;
+    fs::write(registry_path(soul_path), json).map_err(|e| e.to_string())
+}
+
+/// Drop registry entries of deleted files.
+pub fn forget(soul_path: &Path, paths: &[String]) -> Result<(), String> {
+    let mut all = attachments(soul_path);
+    let before = all.len();
+    all.retain(|a| !paths.contains(&a.path));
+    if all.len() == before {
+        return Ok(());
+    }
+    let json = serde_json::to_vec_pretty(&all).map_err(|e| e.to_string())?;
+    fs::write(registry_path(soul_path), json).map_err(|e| e.to_string())
+}
+
+fn emit(
+    app: &AppHandle,
+    download: &ActiveDownload,
+    url: &str,
+    status: &str,
+    error: Option<String>,
+) {
+    let bytes = fs::metadata(&download.path).map(|m| m.len()).unwrap_or(0);
+    let _ = app.emit(
+        "browser:download",
+        DownloadProgress {
+            id: download.id,
+            url: url.to_string(),
+            file: download.file.clone(),
+            status: status.to_string(),
+            bytes,
+            error,
+        },
+    );
+}
+
+impl Downloads {
+    /// `on_download` handler of the browser window: redirects downloads into
+    /// <soul_path>/media/downloads, enforces the download policy, reports
+    /// progress and registers finished files as attachments.
+    pub fn handle(self: &Arc<Self>, app: &AppHandle, event: DownloadEvent<'_>) -> bool {
+        match event {
+            DownloadEvent::Requested { url, destination } => {
+                self.start(app, url.as_str(), destination)
+            }
+            DownloadEvent::Finished { url, success, .. } => {
+                self.finish(app, url.as_str(), success);
+                true
+            }
+            _ => true,
+        }
+    }
+
+    fn start(self: &Arc<Self>, app: &AppHandle, url: &str, destination: &mut PathBuf) -> bool {
+        let (soul_path, policy) = {
+            let config = app.state::<Arc<RwLock<AppConfig>>>();
+            let config = config.read();
+            (config.soul_path.clone(), config.downloads.clone())
+        };
+        let suggested = destination
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .or_else(|| url.rsplit('/').next().map(str::to_string))
+            .unwrap_or_default();
+        let name = sanitize(&suggested);
+        let dir = soul_path.join(DOWNLOAD_DIR);
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let path = unique_path(&dir, &name);
+        let download = Arc::new(ActiveDownload {
+            id,
+            file: format!(
+                "{}/{}",
+                DOWNLOAD_DIR,
+                path.file_name().unwrap_or_default().to_string_lossy()
+            ),
+            path,
+        });
+
+        if !policy.allows(&name) {
+            emit(
+                app,
+                &download,
+                url,
+                "blocked",
+                Some(format!("{} downloads are not allowed", name)),
+            );
+            return false;
+        }
+        if let Err(e) = fs::create_dir_all(&dir) {
+            emit(app, &download, url, "failed", Some(e.to_string()));
+            return false;
+        }
+
+        *destination = download.path.clone();
+        self.active.lock().insert(url.to_string(), download.clone());
+        emit(app, &download, url, "started", None);
+
+        let downloads = self.clone();
+        let app = app.clone();
+        let url = url.to_string();
+        let _ = std::thread::Builder::new()
+            .name("browser-download".into())
+            .spawn(move || loop {
+                std::thread::sleep(PROGRESS_INTERVAL);
+                let running = downloads
+                    .active
+                    .lock()
+                    .get(&url)
+                    .is_some_and(|d| d.id == download.id);
+                if !running {
+                    break;
+                }
+                emit(&app, &download, &url, "progress", None);
+            });
+        true
+    }
+
+    fn finish(&self, app: &AppHandle, url: &str, success: bool) {
+        let Some(download) = self.active.lock().remove(url) else {
+            return;
+        };
+        let (soul_path, max_bytes) = {
+            let config = app.state::<Arc<RwLock<AppConfig>>>();
+            let config = config.read();
+            (config.soul_path.clone(), config.downloads.max_bytes)
+        };
+        let bytes = fs::metadata(&download.path).map(|m| m.len()).unwrap_or(0);
+
+        let error = if !success {
+            Some("Download failed".to_string())
+        } else if bytes > max_bytes {
+            Some(format!(
+                "{} bytes exceed the download limit of {} bytes",
+                bytes, max_bytes
+            ))
+        } else {
+            register(
+                &soul_path,
+                MediaAttachment {
+                    path: download.file.clone(),
+                    url: url.to_string(),
+                    media_type: media_type(&download.file).to_string(),
+                    bytes,
+                    downloaded: now_ms(),
+                    memory_id: None,
+                },
+            )
+            .err()
+            .map(|e| format!("Saved, but registering the attachment failed: {}", e))
+        };
+
+        match error {
+            None => emit(app, &download, url, "finished", None),
+            Some(e) => {
+                if !success || bytes > max_bytes {
+                    let _ = fs::remove_file(&download.path);
+                }
+                emit(app, &download, url, "failed", Some(e));
+            }
+        }
+    }
+}
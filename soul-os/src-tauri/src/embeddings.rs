@@ -0,0 +1,523 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use ring::digest;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+use crate::backend::OsFileStore;
+use crate::blocking::run_blocking;
+use crate::channel;
+use crate::config::app_data_dir;
+use crate::env_watch;
+use crate::export::files_in;
+use crate::http::{self, Http, Policy};
+use crate::paths::{self, Location};
+use crate::safe_path;
+use crate::soul_identity;
+use crate::vault;
+
+const EMBED: Policy = Policy {
+    name: "embeddings",
+    timeout: Duration::from_secs(60),
+    retries: 2,
+    backoff: Duration::from_secs(1),
+    idempotent: true,
+};
+/// Characters per chunk; paragraphs are kept together up to this
+const CHUNK_CHARS: usize = 800;
+/// Chunks per embedding request
+const BATCH: usize = 32;
+pub const DEFAULT_K: usize = 8;
+const OLLAMA_MODEL: &str = "nomic-embed-text";
+const OPENAI_MODEL: &str = "text-embedding-3-small";
+const OPENAI_URL: &str = "https://api.openai.com/v1/embeddings";
+
+/// A memory passage related to a `semantic_search` query.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct MemoryHit {
+    /// Relative to the soul
+    pub path: String,
+    /// "core", "episodic", "semantic", "emotional" or "archive"
+    pub category: Option<String>,
+    pub text: String,
+    /// Cosine similarity, -1..1
+    pub score: f32,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct EmbeddingStatus {
+    /// "ollama:<model>" or "openai:<model>"
+    pub provider: String,
+    pub files: usize,
+    pub chunks: usize,
+    /// Files (re-)embedded by this update
+    pub embedded: usize,
+    pub removed: usize,
+    /// Encrypted memories, which are never indexed
+    pub skipped: usize,
+}
+
+enum Backend {
+    /// Local server, keeps memories on this machine
+    Ollama(String),
+    OpenAi(String),
+}
+
+/// Where vectors come from: the soul's Ollama server if it has one, else
+/// OpenAI.
+struct Provider {
+    backend: Backend,
+    model: &'static str,
+}
+
+impl Provider {
+    fn from_env(soul_path: &Path) -> Result<Self, String> {
+        let env = fs::read_to_string(soul_path.join(".env"))
+            .map(|content| env_watch::parse(&content))
+            .unwrap_or_default();
+        let set = |key: &str| env.get(key).filter(|v| !v.is_empty()).cloned();
+        if let Some(url) = set("OLLAMA_URL") {
+            return Ok(Self {
+                backend: Backend::Ollama(url.trim_end_matches('/').to_string()),
+                model: OLLAMA_MODEL,
+            });
+        }
+        if let Some(key) = set("OPENAI_API_KEY") {
+            return Ok(Self {
+                backend: Backend::OpenAi(key),
+                model: OPENAI_MODEL,
+            });
+        }
+        Err("No embedding provider: set OLLAMA_URL or OPENAI_API_KEY in .env".to_string())
+    }
+
+    fn label(&self) -> String {
+        match self.backend {
+            Backend::Ollama(_) => format!("ollama:{}", self.model),
+            Backend::OpenAi(_) => format!("openai:{}", self.model),
+        }
+    }
+
+    async fn embed(&self, app: &AppHandle, texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        let body = serde_json::json!({ "model": self.model, "input": texts });
+        let http = app.state::<Arc<Http>>();
+        let resp = match &self.backend {
+            Backend::Ollama(url) => {
+                let url = format!("{}/api/embed", url);
+                let client = http.local(&url).unwrap_or_else(|_| http.external());
+                http::send(app, &EMBED, || client.post(&url).json(&body)).await
+            }
+            Backend::OpenAi(key) => {
+                let client = http.external();
+                http::send(app, &EMBED, || {
+                    client.post(OPENAI_URL).bearer_auth(key).json(&body)
+                })
+                .await
+            }
+        }
+        .map_err(|e| format!("Embedding request failed: {}", e))?;
+        let status = resp.status();
+        let json: serde_json::Value = resp.json().await.map_err(|e| e.to_string())?;
+        if !status.is_success() {
+            let detail = json
+                .pointer("/error/message")
+                .or_else(|| json.get("error"))
+                .and_then(|e| e.as_str())
+                .unwrap_or("");
+            let hint = match self.backend {
+                Backend::Ollama(_) => format!(" (ollama pull {})", self.model),
+                Backend::OpenAi(_) => String::new(),
+            };
+            return Err(format!(
+                "Embedding failed: HTTP {} {}{}",
+                status, detail, hint
+            ));
+        }
+
+        let vectors: Vec<&serde_json::Value> = match self.backend {
+            Backend::Ollama(_) => json["embeddings"].as_array(),
+            Backend::OpenAi(_) => json["data"].as_array(),
+        }
+        .map(|items| {
+            items
+                .iter()
+                .map(|item| item.get("embedding").unwrap_or(item))
+                .collect()
+        })
+        .unwrap_or_default();
+        if vectors.len() != texts.len() {
+            return Err("Embedding response doesn't match the request".to_string());
+        }
+        vectors
+            .into_iter()
+            .map(|v| {
+                let v: Vec<f32> = serde_json::from_value(v.clone()).map_err(|e| e.to_string())?;
+                Ok(normalized(v))
+            })
+            .collect()
+    }
+}
+
+/// Unit length, so cosine similarity is a dot product.
+fn normalized(mut v: Vec<f32>) -> Vec<f32> {
+    let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        v.iter_mut().for_each(|x| *x /= norm);
+    }
+    v
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+#[derive(Serialize, Deserialize)]
+struct Chunk {
+    text: String,
+    vector: Vec<f32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FileEntry {
+    /// Hex SHA-256 of the file, stable across builds
+    hash: String,
+    chunks: Vec<Chunk>,
+}
+
+/// One soul's vectors, in <app_data_dir>/embeddings/<soul id>.json.
+#[derive(Default, Serialize, Deserialize)]
+struct StoredIndex {
+    provider: String,
+    files: HashMap<String, FileEntry>,
+}
+
+impl StoredIndex {
+    fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| e.to_string())
+    }
+}
+
+fn index_path(soul_path: &Path) -> PathBuf {
+    app_data_dir()
+        .join("embeddings")
+        .join(format!("{}.json", soul_identity::storage_key(soul_path)))
+}
+
+/// Paragraphs joined up to `CHUNK_CHARS`; longer ones are cut.
+fn chunk(content: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for paragraph in content.split("\n\n").map(str::trim) {
+        if paragraph.is_empty() {
+            continue;
+        }
+        if !current.is_empty() && current.len() + paragraph.len() > CHUNK_CHARS {
+            chunks.push(std::mem::take(&mut current));
+        }
+        let mut rest = paragraph;
+        while rest.len() > CHUNK_CHARS {
+            let mut end = CHUNK_CHARS;
+            while !rest.is_char_boundary(end) {
+                end -= 1;
+            }
+            chunks.push(rest[..end].to_string());
+            rest = &rest[end..];
+        }
+        if !current.is_empty() {
+            current.push_str("\n\n");
+        }
+        current.push_str(rest);
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// A memory file whose content changed since it was last embedded.
+struct Changed {
+    relative: String,
+    hash: String,
+    chunks: Vec<String>,
+}
+
+struct Scan {
+    present: HashSet<String>,
+    changed: Vec<Changed>,
+    skipped: usize,
+}
+
+/// Text files under the memory directories, compared against `known`
+/// (content hash per relative path).
+fn scan(soul_path: &Path, known: HashMap<String, String>) -> Scan {
+    let mut scan = Scan {
+        present: HashSet::new(),
+        changed: Vec::new(),
+        skipped: 0,
+    };
+    for dir in paths::existing(soul_path, Location::Memories) {
+        for file in files_in(&dir, true) {
+            let is_text = file
+                .extension()
+                .is_some_and(|ext| ext == "md" || ext == "txt");
+            let Ok(relative) = file.strip_prefix(soul_path) else {
+                continue;
+            };
+            if !is_text {
+                continue;
+            }
+            let Ok(data) = fs::read(&file) else {
+                continue;
+            };
+            // Vectors would leak what encryption protects
+            if vault::is_encrypted(&data) {
+                scan.skipped += 1;
+                continue;
+            }
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            let hash = channel::to_hex(digest::digest(&digest::SHA256, &data).as_ref());
+            scan.present.insert(relative.clone());
+            if known.get(&relative) == Some(&hash) {
+                continue;
+            }
+            let chunks = chunk(&String::from_utf8_lossy(&data));
+            scan.changed.push(Changed {
+                relative,
+                hash,
+                chunks,
+            });
+        }
+    }
+    scan
+}
+
+/// Vector index over the soul's memories, brought up to date incrementally
+/// (only changed files are embedded again).
+#[derive(Default)]
+pub struct EmbeddingIndex {
+    /// Index file and its content; one update at a time
+    loaded: tokio::sync::Mutex<Option<(PathBuf, StoredIndex)>>,
+}
+
+impl EmbeddingIndex {
+    /// Embed new and changed memories, drop deleted ones.
+    pub async fn update(
+        &self,
+        app: &AppHandle,
+        soul_path: &Path,
+    ) -> Result<EmbeddingStatus, String> {
+        let mut loaded = self.loaded.lock().await;
+        self.sync(app, soul_path, &mut loaded).await
+    }
+
+    async fn sync(
+        &self,
+        app: &AppHandle,
+        soul_path: &Path,
+        loaded: &mut Option<(PathBuf, StoredIndex)>,
+    ) -> Result<EmbeddingStatus, String> {
+        let provider = Provider::from_env(soul_path)?;
+        let path = index_path(soul_path);
+        if loaded.as_ref().is_none_or(|(p, _)| *p != path) {
+            *loaded = Some((path.clone(), StoredIndex::load(&path)));
+        }
+        let (_, index) = loaded.as_mut().expect("loaded above");
+        if index.provider != provider.label() {
+            // Vectors of different models aren't comparable
+            index.files.clear();
+            index.provider = provider.label();
+        }
+
+        let known: HashMap<String, String> = index
+            .files
+            .iter()
+            .map(|(relative, entry)| (relative.clone(), entry.hash.clone()))
+            .collect();
+        let sp = soul_path.to_path_buf();
+        let scan = run_blocking(app, "index_memories", None, move |_| Ok(scan(&sp, known))).await?;
+
+        let before = index.files.len();
+        index
+            .files
+            .retain(|relative, _| scan.present.contains(relative));
+        let removed = before - index.files.len();
+        let mut embedded = 0;
+        let mut failure = None;
+        'files: for file in scan.changed {
+            let mut chunks = Vec::with_capacity(file.chunks.len());
+            for batch in file.chunks.chunks(BATCH) {
+                match provider.embed(app, batch).await {
+                    Ok(vectors) => {
+                        chunks.extend(batch.iter().zip(vectors).map(|(text, vector)| Chunk {
+                            text: text.clone(),
+                            vector,
+                        }))
+                    }
+                    Err(e) => {
+                        failure = Some(e);
+                        break 'files;
+                    }
+                }
+            }
+            index.files.insert(
+                file.relative,
+                FileEntry {
+                    hash: file.hash,
+                    chunks,
+                },
+            );
+            embedded += 1;
+        }
+        // Keep what was embedded before a failure
+        if embedded > 0 || removed > 0 {
+            index.save(&path)?;
+        }
+        if let Some(e) = failure {
+            return Err(e);
+        }
+
+        Ok(EmbeddingStatus {
+            provider: index.provider.clone(),
+            files: index.files.len(),
+            chunks: index.files.values().map(|f| f.chunks.len()).sum(),
+            embedded,
+            removed,
+            skipped: scan.skipped,
+        })
+    }
+
+    /// The `k` memory chunks closest in meaning to `query`.
+    pub async fn search(
+        &self,
+        app: &AppHandle,
+        soul_path: &Path,
+        query: &str,
+        k: usize,
+    ) -> Result<Vec<MemoryHit>, String> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut loaded = self.loaded.lock().await;
+        self.sync(app, soul_path, &mut loaded).await?;
+        let provider = Provider::from_env(soul_path)?;
+        let target = provider
+            .embed(app, &[query.to_string()])
+            .await?
+            .pop()
+            .unwrap_or_default();
+
+        let (_, index) = loaded.as_ref().expect("synced above");
+        let mut hits: Vec<MemoryHit> = index
+            .files
+            .iter()
+            .flat_map(|(relative, entry)| {
+                entry.chunks.iter().map(|chunk| MemoryHit {
+                    path: relative.clone(),
+                    category: paths::memory_category(relative).map(str::to_string),
+                    text: chunk.text.clone(),
+                    score: dot(&target, &chunk.vector),
+                })
+            })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(k);
+        Ok(hits)
+    }
+
+    /// The `k` memories most similar to the file `name` (relative to the
+    /// soul), one hit per file with its best-matching passage. Files outside
+    /// the memories are embedded on the fly.
+    pub async fn related(
+        &self,
+        app: &AppHandle,
+        soul_path: &Path,
+        name: &str,
+        k: usize,
+    ) -> Result<Vec<MemoryHit>, String> {
+        let relative = name.replace('\\', "/").trim_start_matches("./").to_string();
+        let mut loaded = self.loaded.lock().await;
+        self.sync(app, soul_path, &mut loaded).await?;
+        let (_, index) = loaded.as_ref().expect("synced above");
+
+        let target = match index.files.get(&relative) {
+            Some(entry) => centroid(entry.chunks.iter().map(|c| c.vector.as_slice())),
+            None => {
+                let chunks = chunk(&read_document(soul_path, &relative)?);
+                let provider = Provider::from_env(soul_path)?;
+                let mut vectors = Vec::with_capacity(chunks.len());
+                for batch in chunks.chunks(BATCH) {
+                    vectors.extend(provider.embed(app, batch).await?);
+                }
+                centroid(vectors.iter().map(Vec::as_slice))
+            }
+        };
+        let Some(target) = target else {
+            return Ok(Vec::new());
+        };
+
+        let mut hits: Vec<MemoryHit> = index
+            .files
+            .iter()
+            .filter(|(path, _)| **path != relative)
+            .filter_map(|(path, entry)| {
+                let (score, best) = entry
+                    .chunks
+                    .iter()
+                    .map(|chunk| (dot(&target, &chunk.vector), chunk))
+                    .max_by(|a, b| a.0.total_cmp(&b.0))?;
+                Some(MemoryHit {
+                    path: path.clone(),
+                    category: paths::memory_category(path).map(str::to_string),
+                    text: best.text.clone(),
+                    score,
+                })
+            })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(k);
+        Ok(hits)
+    }
+}
+
+/// Normalized mean of unit vectors: what a whole document is about. None
+/// without vectors.
+fn centroid<'a>(vectors: impl Iterator<Item = &'a [f32]>) -> Option<Vec<f32>> {
+    let mut sum: Vec<f32> = Vec::new();
+    for vector in vectors {
+        if sum.is_empty() {
+            sum = vec![0.0; vector.len()];
+        }
+        sum.iter_mut().zip(vector).for_each(|(s, v)| *s += v);
+    }
+    (!sum.is_empty()).then(|| normalized(sum))
+}
+
+/// Text of a soul file that isn't in the index. Encrypted files are
+/// refused like they are for indexing.
+fn read_document(soul_path: &Path, relative: &str) -> Result<String, String> {
+    let path = safe_path::safe_resolve(&OsFileStore, soul_path, relative)?;
+    let data = fs::read(&path).map_err(|e| format!("{}: {}", relative, e))?;
+    if vault::is_encrypted(&data) {
+        return Err(format!("{} is encrypted and can't be compared", relative));
+    }
+    Ok(String::from_utf8_lossy(&data).to_string())
+}
@@ -0,0 +1,370 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use ts_rs::TS;
+
+use crate::compat::{self, engine_version as version, Compatibility};
+use crate::config::app_data_dir;
+use crate::journal::{Journal, JournalOp};
+use crate::sidecar::SidecarManager;
+
+/// How long a freshly deployed engine gets to answer on its API port
+const SMOKE_TIMEOUT: Duration = Duration::from_secs(20);
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct EngineUpdateReport {
+    /// Unix ms
+    #[ts(type = "number")]
+    pub timestamp: u64,
+    /// Version deployed before; None on the first deployment
+    pub from: Option<String>,
+    pub to: String,
+    /// "updated", "rolled_back" (smoke start failed), "failed" (nothing
+    /// changed) or "manual_rollback"
+    pub outcome: String,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct EngineVersionInfo {
+    /// SoulOS version
+    pub app: String,
+    /// Version of the engine that starts next (first of deployed, bundled, dev)
+    pub active: Option<String>,
+    pub compatibility: Compatibility,
+    /// Version shipped in the app resources
+    pub bundled: Option<String>,
+    /// Version the engine runs from (<app_data_dir>/engine/current)
+    pub deployed: Option<String>,
+    /// Version kept for rollback
+    pub previous: Option<String>,
+    /// Version of the dev checkout next to the soul
+    pub dev: Option<String>,
+    /// Bundled version that failed or was rolled back; not deployed again
+    pub skipped: Option<String>,
+    pub last_update: Option<EngineUpdateReport>,
+}
+
+/// Persisted in <app_data_dir>/engine/update.json
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct UpdateState {
+    skip_version: Option<String>,
+    last: Option<EngineUpdateReport>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn engine_dir() -> PathBuf {
+    app_data_dir().join("engine")
+}
+
+fn state_path() -> PathBuf {
+    engine_dir().join("update.json")
+}
+
+fn load_state() -> UpdateState {
+    fs::read(state_path())
+        .ok()
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(state: &UpdateState) {
+    if let Ok(json) = serde_json::to_vec_pretty(state) {
+        let _ = fs::create_dir_all(engine_dir());
+        let _ = fs::write(state_path(), json);
+    }
+}
+
+/// Entry point of the deployed engine, if one is deployed.
+pub fn deployed_entry() -> Option<PathBuf> {
+    let entry = engine_dir().join("current").join("src").join("index.js");
+    entry.exists().then_some(entry)
+}
+
+fn bundled_dir(app: &AppHandle) -> Option<PathBuf> {
+    let dir = app.path().resource_dir().ok()?.join("soul-engine");
+    dir.join("package.json").exists().then_some(dir)
+}
+
+/// Versions of every engine copy, and how the one that starts next fits
+/// this SoulOS release.
+pub fn info(app: &AppHandle, sidecar: &SidecarManager) -> EngineVersionInfo {
+    let dir = engine_dir();
+    let state = load_state();
+    let active = sidecar.active_engine_dir(app).and_then(|d| version(&d));
+    EngineVersionInfo {
+        app: env!("CARGO_PKG_VERSION").to_string(),
+        bundled: bundled_dir(app).and_then(|d| version(&d)),
+        deployed: version(&dir.join("current")),
+        previous: version(&dir.join("previous")),
+        dev: version(&sidecar.dev_engine_dir()),
+        compatibility: compat::check(active.as_deref()),
+        active,
+        skipped: state.skip_version,
+        last_update: state.last,
+    }
+}
+
+/// Deploy the bundled engine if its version differs from the deployed one.
+///
+/// The bundled code is copied to a staging directory, swapped in (the old
+/// version moves to `previous`) and smoke-started. If the new engine doesn't
+/// come up, the previous version is restored and the bundled version is
+/// skipped from then on. A successful update leaves the engine running.
+/// Emits `engine:updated`; returns None when there was nothing to do.
+pub fn migrate(app: &AppHandle, sidecar: &SidecarManager) -> Option<EngineUpdateReport> {
+    let bundled = bundled_dir(app)?;
+    let to = version(&bundled)?;
+    let dir = engine_dir();
+    let from = version(&dir.join("current"));
+    let mut state = load_state();
+    if from.as_deref() == Some(to.as_str()) || state.skip_version.as_deref() == Some(to.as_str()) {
+        return None;
+    }
+
+    let (outcome, error) = match deploy(app, sidecar, &bundled, &dir) {
+        Ok(()) => ("updated", None),
+        Err(UpdateError::Failed(e)) => ("failed", Some(e)),
+        Err(UpdateError::RolledBack(e)) => {
+            state.skip_version = Some(to.clone());
+            ("rolled_back", Some(e))
+        }
+    };
+    let report = EngineUpdateReport {
+        timestamp: now_ms(),
+        from,
+        to,
+        outcome: outcome.to_string(),
+        error,
+    };
+    eprintln!(
+        "[engine-update] {} -> {}: {}",
+        report.from.as_deref().unwrap_or("none"),
+        report.to,
+        report.outcome
+    );
+    state.last = Some(report.clone());
+    save_state(&state);
+    let _ = app.emit("engine:updated", &report);
+    Some(report)
+}
+
+enum UpdateError {
+    /// Nothing was swapped
+    Failed(String),
+    /// The new engine was swapped in, failed its smoke start and was removed
+    RolledBack(String),
+}
+
+fn deploy(
+    app: &AppHandle,
+    sidecar: &SidecarManager,
+    bundled: &Path,
+    dir: &Path,
+) -> Result<(), UpdateError> {
+    let staging = dir.join("staging");
+    let current = dir.join("current");
+    let previous = dir.join("previous");
+
+    let _ = fs::remove_dir_all(&staging);
+    if let Err(e) = copy_dir(bundled, &staging) {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(UpdateError::Failed(format!(
+            "Copying the bundled engine failed: {}",
+            e
+        )));
+    }
+
+    let journal = app.state::<Arc<Journal>>();
+    let _guard = journal
+        .begin(JournalOp::EngineUpdate {
+            dir: dir.to_string_lossy().to_string(),
+        })
+        .map_err(UpdateError::Failed)?;
+
+    if sidecar.get_status().pid.is_some() {
+        let _ = sidecar.stop_engine(app);
+    }
+    swap_in(&staging, &current, &previous).map_err(UpdateError::Failed)?;
+
+    let smoke = sidecar
+        .start_engine(app)
+        .and_then(|()| sidecar.wait_until_up(SMOKE_TIMEOUT));
+    let Err(e) = smoke else {
+        return Ok(());
+    };
+
+    let _ = sidecar.stop_engine(app);
+    let _ = fs::remove_dir_all(&current);
+    if previous.exists() {
+        if let Err(restore) = fs::rename(&previous, &current) {
+            return Err(UpdateError::RolledBack(format!(
+                "{}; restoring the previous engine failed: {}",
+                e, restore
+            )));
+        }
+    }
+    Err(UpdateError::RolledBack(e))
+}
+
+/// current -> previous, staging -> current. Restores current if the second
+/// step fails.
+fn swap_in(staging: &Path, current: &Path, previous: &Path) -> Result<(), String> {
+    let _ = fs::remove_dir_all(previous);
+    if current.exists() {
+        fs::rename(current, previous).map_err(|e| e.to_string())?;
+    }
+    if let Err(e) = fs::rename(staging, current) {
+        if previous.exists() {
+            let _ = fs::rename(previous, current);
+        }
+        return Err(e.to_string());
+    }
+    Ok(())
+}
+
+/// Switch back to the previous engine version and skip the current one
+/// until the next bundled version arrives. Restarts the engine if it ran.
+pub fn rollback(app: &AppHandle, sidecar: &SidecarManager) -> Result<EngineUpdateReport, String> {
+    let dir = engine_dir();
+    let (current, previous) = (dir.join("current"), dir.join("previous"));
+    let to = version(&previous).ok_or("No previous engine version to roll back to")?;
+    let from = version(&current);
+
+    let was_running = sidecar.get_status().pid.is_some();
+    if was_running {
+        sidecar.stop_engine(app)?;
+    }
+    let guard = app
+        .state::<Arc<Journal>>()
+        .begin(JournalOp::EngineRollback {
+            dir: dir.to_string_lossy().to_string(),
+        })?;
+    let staging = dir.join("staging");
+    let _ = fs::remove_dir_all(&staging);
+    fs::rename(&previous, &staging).map_err(|e| e.to_string())?;
+    swap_in(&staging, &current, &previous)?;
+    drop(guard);
+    if was_running {
+        sidecar.start_engine(app)?;
+    }
+
+    let report = EngineUpdateReport {
+        timestamp: now_ms(),
+        from: from.clone(),
+        to,
+        outcome: "manual_rollback".to_string(),
+        error: None,
+    };
+    let mut state = load_state();
+    state.skip_version = from;
+    state.last = Some(report.clone());
+    save_state(&state);
+    let _ = app.emit("engine:updated", &report);
+    Ok(report)
+}
+
+/// Startup recovery for an interrupted update. Before the swap only the
+/// staging copy is removed; after it the new engine never passed its smoke
+/// start, so the previous version is restored.
+pub fn recover_update(dir: &Path) -> (String, bool) {
+    let (staging, current, previous) = (
+        dir.join("staging"),
+        dir.join("current"),
+        dir.join("previous"),
+    );
+    if staging.exists() {
+        let _ = fs::remove_dir_all(&staging);
+        if current.exists() {
+            return (
+                "Interrupted engine update was never applied".to_string(),
+                true,
+            );
+        }
+    }
+    if !previous.exists() {
+        let _ = fs::remove_dir_all(&current);
+        return (
+            "Removed the unverified engine of an interrupted update".to_string(),
+            true,
+        );
+    }
+    let _ = fs::remove_dir_all(&current);
+    match fs::rename(&previous, &current) {
+        Ok(()) => (
+            format!(
+                "Restored engine {} after an interrupted update",
+                version(&current).unwrap_or_default()
+            ),
+            true,
+        ),
+        Err(e) => (
+            format!("Restoring the previous engine failed: {}", e),
+            false,
+        ),
+    }
+}
+
+/// Startup recovery for an interrupted manual rollback: finish the swap if
+/// `current` was already moved away, otherwise put `previous` back.
+pub fn recover_rollback(dir: &Path) -> (String, bool) {
+    let (staging, current, previous) = (
+        dir.join("staging"),
+        dir.join("current"),
+        dir.join("previous"),
+    );
+    if !staging.exists() {
+        return ("Engine rollback had already completed".to_string(), true);
+    }
+    let (target, action) = if current.exists() {
+        (&previous, "Engine rollback was never applied")
+    } else {
+        (&current, "Completed an interrupted engine rollback")
+    };
+    match fs::rename(&staging, target) {
+        Ok(()) => (action.to_string(), true),
+        Err(e) => (
+            format!("Recovering the engine rollback failed: {}", e),
+            false,
+        ),
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        let kind = entry.file_type()?;
+        if kind.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else if kind.is_symlink() {
+            copy_symlink(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::copy(from, to).map(|_| ())
+}
@@ -0,0 +1,231 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+use crate::config::{app_data_dir, AppConfig};
+use crate::env_policy;
+use crate::env_schema;
+use crate::env_watch;
+use crate::network;
+
+/// Sidecars whose environment is layered
+pub const PROCESSES: &[&str] = &["soul-engine", "soul-chain", "founding"];
+/// Session overrides under this name apply to every process
+const ALL: &str = "*";
+
+/// One variable of a sidecar's merged environment.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct EffectiveEnvVar {
+    pub name: String,
+    /// Masked when `secret`
+    pub value: String,
+    /// Layer the value came from: "system" (the app's environment under
+    /// the env policy), "app" (app-level defaults), "soul" (the soul's .env),
+    /// "session" (until the app quits) or "launch" (set by SoulOS itself)
+    pub source: String,
+    pub secret: bool,
+    /// Lower layers that also set the variable, lowest first
+    pub overrides: Vec<String>,
+}
+
+/// Ephemeral overrides by process ("*" for all), gone when the app quits.
+#[derive(Default)]
+pub struct EnvLayers {
+    session: RwLock<BTreeMap<String, BTreeMap<String, String>>>,
+}
+
+impl EnvLayers {
+    /// Set (or with None remove) a session override for `process`, or for
+    /// every process when None.
+    pub fn set_session(
+        &self,
+        process: Option<&str>,
+        name: &str,
+        value: Option<String>,
+    ) -> Result<(), String> {
+        let process = process.unwrap_or(ALL);
+        if process != ALL && !PROCESSES.contains(&process) {
+            return Err(format!("Unknown process {}", process));
+        }
+        validate_name(name)?;
+        let mut session = self.session.write();
+        let vars = session.entry(process.to_string()).or_default();
+        match value {
+            Some(value) => {
+                vars.insert(name.to_string(), value);
+            }
+            None => {
+                vars.remove(name);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn clear_session(&self) {
+        self.session.write().clear();
+    }
+
+    fn session(&self, process: &str) -> Vec<(String, String)> {
+        let session = self.session.read();
+        [ALL, process]
+            .iter()
+            .filter_map(|p| session.get(*p))
+            .flat_map(|vars| vars.iter().map(|(k, v)| (k.clone(), v.clone())))
+            .collect()
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.contains(['=', '\0']) || name.trim() != name {
+        return Err(format!("Invalid variable name: {:?}", name));
+    }
+    Ok(())
+}
+
+/// App-level defaults every soul inherits: <app_data_dir>/defaults.env
+fn defaults_path() -> PathBuf {
+    app_data_dir().join("defaults.env")
+}
+
+fn read_env_file(path: &Path) -> Vec<(String, String)> {
+    let parsed = env_watch::parse(&fs::read_to_string(path).unwrap_or_default());
+    // Sorted, so duplicate handling doesn't depend on hash order
+    parsed
+        .into_iter()
+        .collect::<BTreeMap<_, _>>()
+        .into_iter()
+        .collect()
+}
+
+/// Set (or with None remove) an app-level default.
+pub fn set_default(name: &str, value: Option<String>) -> Result<(), String> {
+    validate_name(name)?;
+    let path = defaults_path();
+    let mut defaults: BTreeMap<String, String> = read_env_file(&path).into_iter().collect();
+    match value {
+        Some(value) => defaults.insert(name.to_string(), value),
+        None => defaults.remove(name),
+    };
+    let content: String = defaults
+        .iter()
+        .map(|(k, v)| format!("{}={}\n", k, v))
+        .collect();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(&path, content).map_err(|e| e.to_string())?;
+    restrict(&path);
+    Ok(())
+}
+
+#[cfg(unix)]
+fn restrict(path: &Path) {
+    use std::os::unix::fs::PermissionsExt;
+    let _ = fs::set_permissions(path, fs::Permissions::from_mode(0o600));
+}
+
+#[cfg(not(unix))]
+fn restrict(_path: &Path) {}
+
+/// Variables SoulOS sets for the process itself.
+fn launch(app: &AppHandle, process: &str, soul_path: &Path) -> Vec<(String, String)> {
+    let mut vars = vec![(
+        "SOUL_PATH".to_string(),
+        soul_path.to_string_lossy().to_string(),
+    )];
+    vars.extend(
+        network::config(app)
+            .node_env()
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v)),
+    );
+    if process == "soul-engine" {
+        let level = app
+            .try_state::<Arc<RwLock<AppConfig>>>()
+            .and_then(|config| config.read().engine_log_level.clone());
+        if let Some(level) = level {
+            vars.push(("LOG_LEVEL".to_string(), level));
+        }
+    }
+    vars
+}
+
+/// Every layer for `process`, lowest precedence first.
+fn layers(
+    app: &AppHandle,
+    process: &str,
+    soul_path: &Path,
+) -> Vec<(&'static str, Vec<(String, String)>)> {
+    let session = app
+        .try_state::<Arc<EnvLayers>>()
+        .map(|layers| layers.session(process))
+        .unwrap_or_default();
+    vec![
+        ("system", env_policy::vars()),
+        ("app", read_env_file(&defaults_path())),
+        ("soul", read_env_file(&soul_path.join(".env"))),
+        ("session", session),
+        ("launch", launch(app, process, soul_path)),
+    ]
+}
+
+/// The environment to start `process` with: system < app defaults < soul
+/// .env < session overrides < what SoulOS sets itself. Replaces the
+/// inherited environment (use after `env_clear`).
+pub fn vars(app: &AppHandle, process: &str, soul_path: &Path) -> Vec<(String, String)> {
+    let mut merged = BTreeMap::new();
+    for (_, vars) in layers(app, process, soul_path) {
+        merged.extend(vars);
+    }
+    merged.into_iter().collect()
+}
+
+/// Where each variable of `process`'s environment comes from, secrets
+/// masked.
+pub fn effective(
+    app: &AppHandle,
+    process: &str,
+    soul_path: &Path,
+) -> Result<Vec<EffectiveEnvVar>, String> {
+    if !PROCESSES.contains(&process) {
+        return Err(format!("Unknown process {}", process));
+    }
+    let mut merged: BTreeMap<String, EffectiveEnvVar> = BTreeMap::new();
+    let mut seen: HashMap<String, Vec<String>> = HashMap::new();
+    for (source, vars) in layers(app, process, soul_path) {
+        for (name, value) in vars {
+            let overrides = seen.entry(name.clone()).or_default();
+            let secret = env_schema::is_secret(&name);
+            merged.insert(
+                name.clone(),
+                EffectiveEnvVar {
+                    value: if secret { mask(&value) } else { value },
+                    source: source.to_string(),
+                    secret,
+                    overrides: overrides.clone(),
+                    name,
+                },
+            );
+            overrides.push(source.to_string());
+        }
+    }
+    Ok(merged.into_values().collect())
+}
+
+/// Enough of a secret to recognize it: the last four characters of a long
+/// value, nothing of a short one.
+fn mask(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() < 12 {
+        return "••••••••".to_string();
+    }
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("••••••••{}", tail)
+}
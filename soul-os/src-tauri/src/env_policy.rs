@@ -0,0 +1,185 @@
+use std::collections::BTreeMap;
+use std::process::Command;
+use std::sync::OnceLock;
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// What every child process gets: the terminal, the sidecars and helper
+/// commands (git, node). Defaults to the variables a shell and Node need to
+/// run; credentials stay with the app.
+const DEFAULT_ALLOW: &[&str] = &[
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "PATH",
+    "SHELL",
+    "TMPDIR",
+    "TEMP",
+    "TMP",
+    "LANG",
+    "LC_*",
+    "TZ",
+    "XDG_*",
+    // Development tools
+    "HOMEBREW_*",
+    "NVM_*",
+    "GIT_*",
+    // SSH agent for git operations
+    "SSH_AUTH_SOCK",
+    // Inherited system proxy (matched case-insensitively)
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "ALL_PROXY",
+    "NO_PROXY",
+    // Windows essentials
+    "SYSTEMROOT",
+    "SYSTEMDRIVE",
+    "WINDIR",
+    "COMSPEC",
+    "PATHEXT",
+    "USERPROFILE",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "PROGRAMDATA",
+    "PROGRAMFILES",
+];
+/// Never passed on, even if allowed; API keys belong in the soul's .env
+const DEFAULT_DENY: &[&str] = &[
+    "*_API_KEY",
+    "*_TOKEN",
+    "*_SECRET",
+    "*PASSWORD*",
+    "CLAUDECODE",
+];
+/// Never injected: they load code into every child process
+const LOADER_VARS: &[&str] = &["LD_*", "DYLD_*"];
+
+/// Which environment variables child processes inherit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct EnvPolicy {
+    /// Names passed on from the app's environment; `PREFIX*`, `*SUFFIX`
+    /// and `*PART*` match patterns, "*" everything. Case-insensitive.
+    #[serde(default = "default_allow")]
+    pub allow: Vec<String>,
+    /// Patterns removed even when allowed
+    #[serde(default = "default_deny")]
+    pub deny: Vec<String>,
+    /// Set on every child process, after filtering; loader variables
+    /// (`LD_*`, `DYLD_*`) are refused
+    #[serde(default)]
+    pub inject: BTreeMap<String, String>,
+}
+
+fn default_allow() -> Vec<String> {
+    DEFAULT_ALLOW.iter().map(|s| s.to_string()).collect()
+}
+
+fn default_deny() -> Vec<String> {
+    DEFAULT_DENY.iter().map(|s| s.to_string()).collect()
+}
+
+impl Default for EnvPolicy {
+    fn default() -> Self {
+        Self {
+            allow: default_allow(),
+            deny: default_deny(),
+            inject: BTreeMap::new(),
+        }
+    }
+}
+
+/// Whether `name` matches `pattern` (`PREFIX*`, `*SUFFIX`, `*PART*`, "*"),
+/// ignoring case.
+pub fn matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_uppercase();
+    let name = name.to_uppercase();
+    match (pattern.strip_prefix('*'), pattern.strip_suffix('*')) {
+        _ if pattern == "*" => true,
+        (Some(rest), _) if rest.ends_with('*') => name.contains(&rest[..rest.len() - 1]),
+        (Some(suffix), _) => name.ends_with(suffix),
+        (None, Some(prefix)) => name.starts_with(prefix),
+        (None, None) => name == pattern,
+    }
+}
+
+impl EnvPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        for name in self.inject.keys() {
+            if name.is_empty() || name.contains(['=', '\0']) {
+                return Err(format!("Invalid variable name: {:?}", name));
+            }
+            if LOADER_VARS.iter().any(|p| matches(p, name)) {
+                return Err(format!("{} cannot be injected", name));
+            }
+        }
+        for pattern in self.allow.iter().chain(&self.deny) {
+            let inner = pattern.trim_start_matches('*').trim_end_matches('*');
+            if inner.contains('*') {
+                return Err(format!(
+                    "Unsupported pattern {} (use PREFIX*, *SUFFIX or *PART*)",
+                    pattern
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn permits(&self, name: &str) -> bool {
+        self.allow.iter().any(|p| matches(p, name)) && !self.deny.iter().any(|p| matches(p, name))
+    }
+
+    /// The app's environment filtered by the policy, plus the injections.
+    pub fn vars(&self) -> Vec<(String, String)> {
+        let mut vars: Vec<(String, String)> = std::env::vars_os()
+            .filter_map(|(k, v)| Some((k.into_string().ok()?, v.into_string().ok()?)))
+            .filter(|(name, _)| self.permits(name))
+            .filter(|(name, _)| !self.inject.contains_key(name))
+            .collect();
+        vars.extend(self.inject.iter().map(|(k, v)| (k.clone(), v.clone())));
+        vars
+    }
+}
+
+fn active() -> &'static RwLock<EnvPolicy> {
+    static POLICY: OnceLock<RwLock<EnvPolicy>> = OnceLock::new();
+    POLICY.get_or_init(|| RwLock::new(EnvPolicy::default()))
+}
+
+/// Make `policy` the one applied to processes started from now on.
+pub fn set(policy: &EnvPolicy) {
+    *active().write() = policy.clone();
+}
+
+/// Variables for a child process under the active policy.
+pub fn vars() -> Vec<(String, String)> {
+    active().read().vars()
+}
+
+/// Replace `cmd`'s inherited environment with the policy's. Variables the
+/// caller sets afterwards are kept.
+pub fn apply(cmd: &mut Command) -> &mut Command {
+    cmd.env_clear().envs(vars())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn injecting(name: &str) -> EnvPolicy {
+        EnvPolicy {
+            inject: BTreeMap::from([(name.to_string(), "/tmp/x".to_string())]),
+            ..EnvPolicy::default()
+        }
+    }
+
+    #[test]
+    fn refuses_loader_variables() {
+        for name in ["LD_PRELOAD", "ld_library_path", "DYLD_INSERT_LIBRARIES"] {
+            assert!(injecting(name).validate().is_err(), "{}", name);
+        }
+        assert!(injecting("EDITOR").validate().is_ok());
+    }
+}
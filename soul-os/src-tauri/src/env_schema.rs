@@ -0,0 +1,361 @@
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::env_policy;
+
+/// How the env editor renders a value.
+#[derive(Debug, Clone, Copy, Serialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum EnvValueType {
+    #[serde(rename = "string")]
+    Text,
+    Number,
+    Boolean,
+    /// Five-field cron expression
+    Cron,
+    Url,
+}
+
+/// Metadata for one .env key the engine reads.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct EnvKeyInfo {
+    pub key: &'static str,
+    /// Section of the editor ("llm", "telegram", "heartbeat", …)
+    pub group: &'static str,
+    pub description: &'static str,
+    pub kind: EnvValueType,
+    /// What the engine uses when the key is unset
+    pub default: Option<&'static str>,
+    /// Only read at engine startup; takes effect after a reload
+    pub restart_required: bool,
+    /// Mask in the editor, never log
+    pub secret: bool,
+}
+
+const fn key(
+    key: &'static str,
+    group: &'static str,
+    kind: EnvValueType,
+    default: Option<&'static str>,
+    description: &'static str,
+) -> EnvKeyInfo {
+    EnvKeyInfo {
+        key,
+        group,
+        description,
+        kind,
+        default,
+        restart_required: true,
+        secret: false,
+    }
+}
+
+const fn secret(key: &'static str, group: &'static str, description: &'static str) -> EnvKeyInfo {
+    EnvKeyInfo {
+        key,
+        group,
+        description,
+        kind: EnvValueType::Text,
+        default: None,
+        restart_required: true,
+        secret: true,
+    }
+}
+
+use EnvValueType::{Boolean, Cron, Number, Text, Url};
+
+/// Keys of the engine's .env, in editor order. Keep in sync with
+/// .env.example and soul-engine.
+const SCHEMA: &[EnvKeyInfo] = &[
+    // LLM providers (one required)
+    secret("OPENAI_API_KEY", "llm", "OpenAI API key"),
+    key(
+        "OPENAI_MODEL",
+        "llm",
+        Text,
+        Some("gpt-4.1-mini"),
+        "OpenAI model",
+    ),
+    secret("GEMINI_API_KEY", "llm", "Google Gemini API key"),
+    key(
+        "GEMINI_MODEL",
+        "llm",
+        Text,
+        Some("gemini-2.5-flash"),
+        "Gemini model",
+    ),
+    secret("ANTHROPIC_API_KEY", "llm", "Anthropic API key"),
+    key(
+        "ANTHROPIC_MODEL",
+        "llm",
+        Text,
+        Some("claude-sonnet-4-6"),
+        "Anthropic model",
+    ),
+    key(
+        "OLLAMA_URL",
+        "llm",
+        Url,
+        None,
+        "Local Ollama server, e.g. http://localhost:11434",
+    ),
+    key(
+        "OLLAMA_MODEL",
+        "llm",
+        Text,
+        Some("llama3.1"),
+        "Ollama model",
+    ),
+    // Telegram
+    secret(
+        "TELEGRAM_BOT_TOKEN",
+        "telegram",
+        "Bot token from @BotFather",
+    ),
+    key(
+        "TELEGRAM_OWNER_ID",
+        "telegram",
+        Number,
+        None,
+        "Your Telegram user ID (@userinfobot)",
+    ),
+    key(
+        "TELEGRAM_NOTIFY_HEARTBEAT",
+        "telegram",
+        Boolean,
+        Some("false"),
+        "Send heartbeat results to Telegram",
+    ),
+    // Heartbeat
+    key(
+        "HEARTBEAT_CRON",
+        "heartbeat",
+        Cron,
+        Some("0 7 * * *"),
+        "When the daily heartbeat runs",
+    ),
+    // Impulses
+    key(
+        "SOUL_IMPULSE",
+        "impulse",
+        Boolean,
+        Some("true"),
+        "Let the soul reach out on its own",
+    ),
+    key(
+        "IMPULSE_MIN_DELAY",
+        "impulse",
+        Number,
+        Some("600"),
+        "Minimum seconds between impulses",
+    ),
+    key(
+        "IMPULSE_MAX_DELAY",
+        "impulse",
+        Number,
+        Some("14400"),
+        "Maximum seconds between impulses",
+    ),
+    key(
+        "IMPULSE_NIGHT_START",
+        "impulse",
+        Number,
+        Some("23"),
+        "Hour quiet mode starts",
+    ),
+    key(
+        "IMPULSE_NIGHT_END",
+        "impulse",
+        Number,
+        Some("7"),
+        "Hour quiet mode ends",
+    ),
+    // API
+    secret(
+        "API_KEY",
+        "api",
+        "Enables the REST and WebSocket API for the app",
+    ),
+    key(
+        "API_PORT",
+        "api",
+        Number,
+        Some("3001"),
+        "Port of the engine API",
+    ),
+    // Engine subsystems
+    key(
+        "SOUL_REFLECTION",
+        "features",
+        Boolean,
+        Some("true"),
+        "Periodic self-reflection",
+    ),
+    key(
+        "SOUL_CORRECTION",
+        "features",
+        Boolean,
+        Some("true"),
+        "Self-correction of memories",
+    ),
+    key(
+        "SOUL_CONSOLIDATOR",
+        "features",
+        Boolean,
+        Some("true"),
+        "Memory consolidation",
+    ),
+    key(
+        "SOUL_FIELD",
+        "features",
+        Boolean,
+        Some("true"),
+        "Attention field",
+    ),
+    key(
+        "SOUL_VERSIONING",
+        "features",
+        Boolean,
+        Some("true"),
+        "Git versioning of the soul",
+    ),
+    key(
+        "SOUL_ANTI_PERFORMANCE",
+        "features",
+        Boolean,
+        Some("true"),
+        "Guard against performative answers",
+    ),
+    key(
+        "SOUL_BUS_DEBUG",
+        "features",
+        Boolean,
+        Some("false"),
+        "Log every event bus message",
+    ),
+    // Token budgets
+    key(
+        "SOUL_DAILY_TOKEN_BUDGET",
+        "budget",
+        Number,
+        Some("0"),
+        "Tokens per day (0 = unlimited)",
+    ),
+    key(
+        "SOUL_TOKEN_BUDGET_CONVERSATION",
+        "budget",
+        Number,
+        Some("4096"),
+        "Tokens per answer",
+    ),
+    key(
+        "SOUL_TOKEN_BUDGET_IMPULSE",
+        "budget",
+        Number,
+        Some("512"),
+        "Tokens per impulse",
+    ),
+    key(
+        "SOUL_TOKEN_BUDGET_HEARTBEAT",
+        "budget",
+        Number,
+        Some("2048"),
+        "Tokens per heartbeat",
+    ),
+    key(
+        "SOUL_TOKEN_BUDGET_REFLECTION",
+        "budget",
+        Number,
+        Some("1024"),
+        "Tokens per reflection",
+    ),
+    key(
+        "SOUL_TOKEN_BUDGET_CONSOLIDATION",
+        "budget",
+        Number,
+        Some("1024"),
+        "Tokens per consolidation",
+    ),
+    key(
+        "SOUL_REFLECTION_LLM_BUDGET",
+        "budget",
+        Number,
+        Some("10"),
+        "LLM calls per day for reflection",
+    ),
+    // Security
+    secret(
+        "SOUL_SECRET_KEY",
+        "security",
+        "Key of the engine's secret store",
+    ),
+    secret(
+        "SOUL_ENCRYPTION_KEY",
+        "security",
+        "Key for encrypted soul files",
+    ),
+    // Integrations
+    secret(
+        "GITHUB_TOKEN",
+        "integrations",
+        "GitHub token for repository awareness",
+    ),
+    key(
+        "GITHUB_USERNAME",
+        "integrations",
+        Text,
+        None,
+        "GitHub user to follow",
+    ),
+    key(
+        "SOUL_GITHUB_REPOS",
+        "integrations",
+        Text,
+        None,
+        "Comma-separated owner/repo list to watch",
+    ),
+    key(
+        "WHATSAPP_BRIDGE_URL",
+        "integrations",
+        Url,
+        None,
+        "WhatsApp bridge endpoint",
+    ),
+];
+
+/// Unknown keys that still look like engine settings
+const RESTART_PATTERNS: &[&str] = &["*_API_KEY", "*_MODEL", "*_BASE_URL", "SOUL_*"];
+
+/// Unknown keys whose values are credentials
+const SECRET_PATTERNS: &[&str] = &["*_API_KEY", "*_TOKEN", "*_SECRET", "*PASSWORD*", "*_KEY"];
+
+/// Metadata for the known engine keys, in editor order.
+pub fn schema() -> Vec<EnvKeyInfo> {
+    SCHEMA.to_vec()
+}
+
+fn lookup(name: &str) -> Option<&'static EnvKeyInfo> {
+    SCHEMA.iter().find(|info| info.key == name)
+}
+
+/// Whether changing `name` only takes effect after an engine reload.
+pub fn restart_required(name: &str) -> bool {
+    match lookup(name) {
+        Some(info) => info.restart_required,
+        None => RESTART_PATTERNS
+            .iter()
+            .any(|p| env_policy::matches(p, name)),
+    }
+}
+
+/// Whether `name`'s value must not be shown in full.
+pub fn is_secret(name: &str) -> bool {
+    match lookup(name) {
+        Some(info) => info.secret,
+        None => SECRET_PATTERNS
+            .iter()
+            .any(|p| env_policy::matches(p, name)),
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::Arc;
+
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use ts_rs::TS;
+
+use crate::config::AppConfig;
+use crate::env_schema;
+use crate::sidecar;
+
+/// Which keys of the soul's .env changed. Values are never included.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct EnvChange {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// Keys whose value changed
+    pub changed: Vec<String>,
+    /// Of all affected keys, those the engine only picks up on a reload
+    pub restart_keys: Vec<String>,
+    /// The engine was reloaded for the change (reload_on_config_change);
+    /// false with restart_keys set means the UI should offer it
+    pub restarted: bool,
+}
+
+/// Parse .env content into key/value pairs; comments and blank lines are
+/// skipped, surrounding quotes removed.
+pub fn parse(content: &str) -> HashMap<String, String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, val)| {
+            let val = val.trim().trim_matches('"').trim_matches('\'');
+            (key.trim().to_string(), val.to_string())
+        })
+        .collect()
+}
+
+fn fingerprint(value: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Key → value fingerprint of the soul's .env, so values aren't kept.
+fn snapshot(soul_path: &Path) -> HashMap<String, u64> {
+    parse(&fs::read_to_string(soul_path.join(".env")).unwrap_or_default())
+        .into_iter()
+        .map(|(key, val)| (key, fingerprint(&val)))
+        .collect()
+}
+
+/// Last known state of the soul's .env, to tell which keys an edit touched.
+pub struct EnvWatch {
+    known: Mutex<HashMap<String, u64>>,
+}
+
+impl EnvWatch {
+    pub fn load(soul_path: &Path) -> Self {
+        Self {
+            known: Mutex::new(snapshot(soul_path)),
+        }
+    }
+
+    /// Re-read .env; None if no key was added, removed or changed.
+    pub fn refresh(&self, soul_path: &Path) -> Option<EnvChange> {
+        let mut known = self.known.lock();
+        let current = snapshot(soul_path);
+        let change = diff(&known, &current);
+        *known = current;
+        change
+    }
+
+    /// Run `write` (SoulOS saving .env itself) and diff its result. The
+    /// watcher's refresh waits, so the save is reported once.
+    pub fn update<T>(
+        &self,
+        soul_path: &Path,
+        write: impl FnOnce() -> Result<T, String>,
+    ) -> Result<Option<EnvChange>, String> {
+        let mut known = self.known.lock();
+        write()?;
+        let current = snapshot(soul_path);
+        let change = diff(&known, &current);
+        *known = current;
+        Ok(change)
+    }
+}
+
+fn diff(old: &HashMap<String, u64>, new: &HashMap<String, u64>) -> Option<EnvChange> {
+    let mut added: Vec<String> = new
+        .keys()
+        .filter(|k| !old.contains_key(*k))
+        .cloned()
+        .collect();
+    let mut removed: Vec<String> = old
+        .keys()
+        .filter(|k| !new.contains_key(*k))
+        .cloned()
+        .collect();
+    let mut changed: Vec<String> = new
+        .iter()
+        .filter(|(k, v)| old.get(*k).is_some_and(|old| old != *v))
+        .map(|(k, _)| k.clone())
+        .collect();
+    if added.is_empty() && removed.is_empty() && changed.is_empty() {
+        return None;
+    }
+    added.sort();
+    removed.sort();
+    changed.sort();
+    let mut restart_keys: Vec<String> = added
+        .iter()
+        .chain(&removed)
+        .chain(&changed)
+        .filter(|k| env_schema::restart_required(k))
+        .cloned()
+        .collect();
+    restart_keys.sort();
+    Some(EnvChange {
+        added,
+        removed,
+        changed,
+        restart_keys,
+        restarted: false,
+    })
+}
+
+/// Reload the engine for `change` if the user opted in and it matters,
+/// then emit `env:changed`.
+pub async fn react(app: &AppHandle, soul_path: &Path, mut change: EnvChange) {
+    let reload = app
+        .try_state::<Arc<RwLock<AppConfig>>>()
+        .is_some_and(|c| c.read().reload_on_config_change);
+    if reload && !change.restart_keys.is_empty() && sidecar::engine_running(app) {
+        match sidecar::reload(app, soul_path).await {
+            Ok(_) => change.restarted = true,
+            Err(e) => eprintln!("[env] reload after .env change failed: {}", e),
+        }
+    }
+    let _ = app.emit("env:changed", change);
+}
+
+/// .env changed on disk (watcher event): report edits made outside SoulOS.
+pub fn changed(app: &AppHandle, soul_path: &Path) {
+    let Some(watch) = app.try_state::<Arc<EnvWatch>>() else {
+        return;
+    };
+    if let Some(change) = watch.refresh(soul_path) {
+        let app = app.clone();
+        let soul_path = soul_path.to_path_buf();
+        tauri::async_runtime::spawn(async move { react(&app, &soul_path, change).await });
+    }
+}
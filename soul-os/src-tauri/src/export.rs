@@ -0,0 +1,177 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+use serde::Serialize;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+use ts_rs::TS;
+
+use crate::blocking::CancelToken;
+use crate::config::app_data_dir;
+use crate::paths::{self, Location, Memories};
+use crate::pii;
+use crate::vault::Vault;
+
+/// Memory categories by their export name
+const CATEGORIES: &[(&str, Memories)] = &[
+    ("core", Memories::Core),
+    ("episodic", Memories::Episodic),
+    ("semantic", Memories::Semantic),
+    ("emotional", Memories::Emotional),
+    ("archive", Memories::Archive),
+];
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ExportInfo {
+    pub path: String,
+    pub files: Vec<String>,
+    /// PII matches replaced with placeholders
+    pub redactions: usize,
+    /// Files left out because they are encrypted at rest
+    pub skipped: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct ExportManifest<'a> {
+    created: String,
+    categories: &'a [String],
+    anonymized: bool,
+    files: &'a [String],
+}
+
+/// Non-hidden files under `dir`, sorted.
+pub fn files_in(dir: &Path, recursive: bool) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            if path.is_dir() {
+                if recursive && !hidden {
+                    dirs.push(path);
+                }
+            } else if !hidden {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Files to export, relative to soul_path: SEED.md, the persona files and
+/// the selected memory categories. Never .env, dotfiles or relationships.
+fn select(soul_path: &Path, categories: &[String]) -> Result<Vec<PathBuf>, String> {
+    let mut selected = vec![soul_path.join("SEED.md")];
+    // Only top-level persona files, which leaves out relationships
+    for dir in paths::existing(soul_path, Location::Soul) {
+        selected.extend(files_in(&dir, false));
+    }
+    for category in categories {
+        let (_, memories) = CATEGORIES
+            .iter()
+            .find(|(name, _)| name == category)
+            .ok_or_else(|| {
+                let known: Vec<&str> = CATEGORIES.iter().map(|(name, _)| *name).collect();
+                format!(
+                    "Unknown memory category: {} (expected one of {})",
+                    category,
+                    known.join(", ")
+                )
+            })?;
+        for dir in paths::existing(soul_path, Location::Memory(*memories)) {
+            selected.extend(files_in(&dir, true));
+        }
+    }
+    selected.retain(|p| p.is_file());
+    Ok(selected)
+}
+
+/// Where an export goes: `destination`, or a timestamped file in
+/// <app_data_dir>/exports.
+pub fn destination(destination: Option<PathBuf>) -> PathBuf {
+    destination.unwrap_or_else(|| {
+        app_data_dir().join("exports").join(format!(
+            "soul-export-{}.zip",
+            Local::now().format("%Y%m%d-%H%M%S")
+        ))
+    })
+}
+
+/// Build a shareable zip of the soul without personal data. Written to
+/// <app_data_dir>/exports unless `destination` is given.
+pub fn export_filtered(
+    soul_path: &Path,
+    categories: &[String],
+    anonymize: bool,
+    destination: Option<PathBuf>,
+    vault: &Vault,
+    token: &CancelToken,
+) -> Result<ExportInfo, String> {
+    let selected = select(soul_path, categories)?;
+    let path = self::destination(destination);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let file = File::create(&path).map_err(|e| e.to_string())?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut files = Vec::new();
+    let mut skipped = Vec::new();
+    let mut redactions = 0;
+
+    let total = selected.len() as u64;
+    for (done, file_path) in selected.iter().enumerate() {
+        token.check()?;
+        token.set_progress(done as u64, total);
+        let Ok(relative) = file_path.strip_prefix(soul_path) else {
+            continue;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        if vault.covers(&relative) {
+            skipped.push(relative);
+            continue;
+        }
+
+        let mut content = fs::read(file_path).map_err(|e| e.to_string())?;
+        if anonymize {
+            if let Ok(text) = String::from_utf8(content.clone()) {
+                let (redacted, count) = pii::redact(&text);
+                redactions += count;
+                content = redacted.into_bytes();
+            }
+        }
+        zip.start_file(relative.as_str(), options)
+            .map_err(|e| e.to_string())?;
+        zip.write_all(&content).map_err(|e| e.to_string())?;
+        files.push(relative);
+    }
+
+    let manifest = ExportManifest {
+        created: Local::now().to_rfc3339(),
+        categories,
+        anonymized: anonymize,
+        files: &files,
+    };
+    let manifest = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    zip.start_file("export-manifest.json", options)
+        .map_err(|e| e.to_string())?;
+    zip.write_all(manifest.as_bytes())
+        .map_err(|e| e.to_string())?;
+    zip.finish().map_err(|e| e.to_string())?;
+
+    Ok(ExportInfo {
+        path: path.to_string_lossy().to_string(),
+        files,
+        redactions,
+        skipped,
+    })
+}
@@ -1,12 +1,273 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Component, Path, PathBuf};
 use std::process::{Child, Command, Stdio};
-use std::sync::Mutex;
-use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::Local;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
 
+use crate::config::app_data_dir;
+use crate::env_layers;
 use crate::node;
+use crate::types::SubsystemHealth;
 
+/// How long a founding preview can be confirmed
+const PREVIEW_TTL: Duration = Duration::from_secs(30 * 60);
+/// How long an overwrite confirmation can be used
+const OVERWRITE_TTL: Duration = Duration::from_secs(10 * 60);
+/// Left out of the pre-founding backup: history and dependencies that
+/// founding never touches, and the API keys
+const BACKUP_SKIP: &[&str] = &[".git", "node_modules", ".env"];
+
+/// Error prefix for founding into a directory that already holds a soul.
+/// The frontend matches on it (`isAlreadyFounded`).
+pub const ALREADY_FOUNDED: &str = "AlreadyFounded";
+
+/// A soul is founded once it has a SEED.
+pub fn is_founded(soul_path: &Path) -> bool {
+    soul_path.join("SEED.md").exists()
+}
+
+fn random_token() -> String {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SoulFile {
+    /// Relative to the soul path
+    pub path: String,
+    pub content: String,
+}
+
+/// Directories and files of a newly founded soul, as rendered by the
+/// founding server (`POST /preview`) or the offline templates.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SoulFiles {
+    pub dirs: Vec<String>,
+    pub files: Vec<SoulFile>,
+}
+
+impl SoulFiles {
+    /// Refuse anything that would land outside the soul directory.
+    fn check_paths(&self) -> Result<(), String> {
+        let inside = |p: &str| {
+            Path::new(p)
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)))
+        };
+        for path in self.dirs.iter().chain(self.files.iter().map(|f| &f.path)) {
+            if !inside(path) {
+                return Err(format!(
+                    "Founding wants to write outside the soul: {}",
+                    path
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Write everything below `soul_path`; returns the written files.
+    pub fn write(&self, soul_path: &Path) -> Result<Vec<String>, String> {
+        self.check_paths()?;
+        for dir in &self.dirs {
+            std::fs::create_dir_all(soul_path.join(dir)).map_err(|e| e.to_string())?;
+        }
+        for file in &self.files {
+            let path = soul_path.join(&file.path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            std::fs::write(&path, &file.content)
+                .map_err(|e| format!("Failed to write {}: {}", file.path, e))?;
+        }
+        Ok(self.files.iter().map(|f| f.path.clone()).collect())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct FoundingPreview {
+    /// Pass to `founding_create` to write exactly these files
+    pub token: String,
+    pub files: Vec<SoulFile>,
+    /// Files that already exist and would be overwritten
+    pub overwrites: Vec<String>,
+    /// Rendered from local templates because no LLM is configured
+    pub template_founded: bool,
+    /// The soul already has a SEED; creating needs an overwrite token
+    pub already_founded: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct OverwriteConfirmation {
+    /// Pass to `founding_create` as `overwrite_token` (once)
+    pub overwrite_token: String,
+    /// Zip of the soul as it was before founding
+    pub backup: String,
+    /// Files in the backup
+    pub files: usize,
+}
+
+/// Zip the existing soul to <app_data_dir>/backups before founding over it.
+pub fn backup_soul(soul_path: &Path) -> Result<(PathBuf, usize), String> {
+    let dir = app_data_dir().join("backups");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!(
+        "soul-before-founding-{}.zip",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    let file = File::create(&path).map_err(|e| format!("Failed to create backup: {}", e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut count = 0;
+    let mut dirs = vec![soul_path.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries = fs::read_dir(&dir).map_err(|e| e.to_string())?;
+        for entry in entries.flatten() {
+            if BACKUP_SKIP.contains(&entry.file_name().to_string_lossy().as_ref()) {
+                continue;
+            }
+            let path = entry.path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(soul_path) else {
+                continue;
+            };
+            let content = fs::read(&path)
+                .map_err(|e| format!("Failed to back up {}: {}", relative.display(), e))?;
+            let name = relative.to_string_lossy().replace('\\', "/");
+            zip.start_file(name.as_str(), options)
+                .map_err(|e| e.to_string())?;
+            zip.write_all(&content).map_err(|e| e.to_string())?;
+            count += 1;
+        }
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok((path, count))
+}
+
+struct PendingFounding {
+    created: Instant,
+    files: SoulFiles,
+    template_founded: bool,
+}
+
+/// Previews waiting for confirmation. Nothing is written without a token
+/// from here, so founding can't silently overwrite an existing soul.
+#[derive(Default)]
+pub struct FoundingPreviews {
+    pending: Mutex<HashMap<String, PendingFounding>>,
+    /// Overwrite tokens with the soul path they were confirmed for
+    overwrites: Mutex<HashMap<String, (PathBuf, Instant)>>,
+}
+
+impl FoundingPreviews {
+    pub fn insert(
+        &self,
+        soul_path: &Path,
+        files: SoulFiles,
+        template_founded: bool,
+    ) -> Result<FoundingPreview, String> {
+        files.check_paths()?;
+        let token = random_token();
+        let preview = FoundingPreview {
+            token: token.clone(),
+            overwrites: files
+                .files
+                .iter()
+                .filter(|f| soul_path.join(&f.path).exists())
+                .map(|f| f.path.clone())
+                .collect(),
+            files: files.files.clone(),
+            template_founded,
+            already_founded: is_founded(soul_path),
+        };
+
+        let mut pending = self.pending.lock();
+        pending.retain(|_, p| p.created.elapsed() < PREVIEW_TTL);
+        pending.insert(
+            token,
+            PendingFounding {
+                created: Instant::now(),
+                files,
+                template_founded,
+            },
+        );
+        Ok(preview)
+    }
+
+    /// Redeem a preview token (once). Returns the files and whether they
+    /// were template-founded.
+    pub fn take(&self, token: &str) -> Result<(SoulFiles, bool), String> {
+        let pending = self
+            .pending
+            .lock()
+            .remove(token)
+            .ok_or("Unknown founding preview; preview again before creating")?;
+        if pending.created.elapsed() >= PREVIEW_TTL {
+            return Err("The founding preview expired; preview again before creating".to_string());
+        }
+        Ok((pending.files, pending.template_founded))
+    }
+
+    /// Issue an overwrite token for `soul_path` once its backup exists.
+    pub fn confirm_overwrite(
+        &self,
+        soul_path: &Path,
+        backup: &Path,
+        files: usize,
+    ) -> OverwriteConfirmation {
+        let token = random_token();
+        let mut overwrites = self.overwrites.lock();
+        overwrites.retain(|_, (_, created)| created.elapsed() < OVERWRITE_TTL);
+        overwrites.insert(token.clone(), (soul_path.to_path_buf(), Instant::now()));
+        OverwriteConfirmation {
+            overwrite_token: token,
+            backup: backup.to_string_lossy().to_string(),
+            files,
+        }
+    }
+
+    /// Founding into an existing soul needs a valid overwrite token for the
+    /// same soul path; the token is used up. A fresh directory needs none.
+    pub fn guard(&self, soul_path: &Path, overwrite_token: Option<&str>) -> Result<(), String> {
+        if !is_founded(soul_path) {
+            return Ok(());
+        }
+        let already = || {
+            format!(
+                "{}: {} already holds a soul; confirm the overwrite first",
+                ALREADY_FOUNDED,
+                soul_path.display()
+            )
+        };
+        let token = overwrite_token.ok_or_else(already)?;
+        match self.overwrites.lock().remove(token) {
+            Some((path, created)) if path == soul_path && created.elapsed() < OVERWRITE_TTL => {
+                Ok(())
+            }
+            _ => Err(already()),
+        }
+    }
+}
+
+/// The founding interview's LLM server. Outside founding it also serves as
+/// the editor's lightweight assistant (see `assistant`).
 pub struct FoundingServer {
     child: Mutex<Option<Child>>,
     port: u16,
@@ -21,7 +282,7 @@ impl FoundingServer {
     }
 
     pub fn start(&self, app: &AppHandle, soul_path: &PathBuf) -> Result<u16, String> {
-        let mut child_lock = self.child.lock().map_err(|e| e.to_string())?;
+        let mut child_lock = self.child.lock();
 
         // Kill existing if running
         if let Some(ref mut child) = *child_lock {
@@ -37,7 +298,8 @@ impl FoundingServer {
 
         let mut child = Command::new(&node_path)
             .arg(&server_path)
-            .env("SOUL_PATH", soul_path)
+            .env_clear()
+            .envs(env_layers::vars(app, "founding", soul_path))
             .env("FOUNDING_PORT", self.port.to_string())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
@@ -77,7 +339,7 @@ impl FoundingServer {
     }
 
     pub fn stop(&self) -> Result<(), String> {
-        let mut child_lock = self.child.lock().map_err(|e| e.to_string())?;
+        let mut child_lock = self.child.lock();
         if let Some(ref mut child) = *child_lock {
             #[cfg(unix)]
             unsafe {
@@ -91,6 +353,27 @@ impl FoundingServer {
         Ok(())
     }
 
+    /// A server was started and hasn't exited.
+    pub fn is_running(&self) -> bool {
+        matches!(self.child.lock().as_mut().map(Child::try_wait), Some(Ok(None)))
+    }
+
+    pub fn health(&self) -> SubsystemHealth {
+        let mut child_lock = self.child.lock();
+        match *child_lock {
+            Some(ref mut child) => match child.try_wait() {
+                Ok(None) => SubsystemHealth::new("founding", "ok", Some("running".to_string())),
+                Ok(Some(status)) => SubsystemHealth::new(
+                    "founding",
+                    "degraded",
+                    Some(format!("server exited unexpectedly ({})", status)),
+                ),
+                Err(e) => SubsystemHealth::new("founding", "degraded", Some(e.to_string())),
+            },
+            None => SubsystemHealth::new("founding", "ok", Some("stopped".to_string())),
+        }
+    }
+
     pub fn port(&self) -> u16 {
         self.port
     }
@@ -0,0 +1,195 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use chrono::{Local, NaiveDate};
+use parking_lot::Mutex;
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::paths::{self, Location, Memories};
+use crate::vault::Vault;
+
+/// Persona files the highlight is drawn from, German name first
+const SOURCES: &[(&str, &str, &str)] = &[
+    ("GARTEN.md", "GARDEN.md", "garden"),
+    ("TRAEUME.md", "DREAMS.md", "dreams"),
+];
+/// Emotional memories younger than this stand in when the garden and
+/// dreams are empty
+const RECENT_MEMORIES: Duration = Duration::from_secs(30 * 24 * 3600);
+/// Passages outside these bounds (in chars) don't read as a quote
+const MIN_CHARS: usize = 40;
+const MAX_CHARS: usize = 400;
+
+/// A passage the soul wrote, picked once per day.
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+#[ts(export)]
+pub struct DailyHighlight {
+    /// YYYY-MM-DD the passage was picked for
+    pub date: String,
+    /// Path relative to the soul
+    pub source: String,
+    /// "garden", "dreams" or "memory"
+    pub kind: String,
+    /// Closest heading above the passage
+    pub heading: Option<String>,
+    pub text: String,
+}
+
+struct Passage {
+    source: String,
+    kind: &'static str,
+    heading: Option<String>,
+    text: String,
+}
+
+/// Today's highlight, kept until the date or soul changes so every caller
+/// shows the same passage even if the files are edited during the day.
+/// Nothing is kept while there's nothing to pick (or the vault is locked).
+#[derive(Default)]
+pub struct Highlights {
+    today: Mutex<Option<(NaiveDate, PathBuf, DailyHighlight)>>,
+}
+
+impl Highlights {
+    pub fn get(&self, soul_path: &Path, vault: &Vault) -> Option<DailyHighlight> {
+        let date = Local::now().date_naive();
+        if let Some((day, sp, highlight)) = self.today.lock().as_ref() {
+            if *day == date && sp == soul_path {
+                return Some(highlight.clone());
+            }
+        }
+        let highlight = pick(soul_path, vault, date)?;
+        *self.today.lock() = Some((date, soul_path.to_path_buf(), highlight.clone()));
+        Some(highlight)
+    }
+}
+
+/// FNV-1a: stable across runs and Rust versions, unlike `DefaultHasher`.
+fn seed(date: NaiveDate) -> u64 {
+    date.format("%Y-%m-%d")
+        .to_string()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// Paragraphs of prose with the heading they sit under. Headings, tables,
+/// code and HTML comments are skipped; list items are joined into one.
+fn passages(content: &str) -> Vec<(Option<String>, String)> {
+    let mut out = Vec::new();
+    let mut heading = None;
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut in_code = false;
+    let mut flush = |heading: &Option<String>, paragraph: &mut Vec<&str>| {
+        let text = paragraph.join(" ");
+        let len = text.chars().count();
+        if (MIN_CHARS..=MAX_CHARS).contains(&len) {
+            out.push((heading.clone(), text));
+        }
+        paragraph.clear();
+    };
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with("```") {
+            in_code = !in_code;
+            flush(&heading, &mut paragraph);
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        if let Some(title) = line.strip_prefix('#') {
+            flush(&heading, &mut paragraph);
+            heading =
+                Some(title.trim_start_matches('#').trim().to_string()).filter(|h| !h.is_empty());
+            continue;
+        }
+        if line.is_empty() || line.starts_with('|') || line.starts_with("<!--") || line == "---" {
+            flush(&heading, &mut paragraph);
+            continue;
+        }
+        let line = line
+            .trim_start_matches(['-', '*', '>'])
+            .trim_start()
+            .trim_matches('*');
+        if !line.is_empty() {
+            paragraph.push(line);
+        }
+    }
+    flush(&heading, &mut paragraph);
+    out
+}
+
+fn read(soul_path: &Path, vault: &Vault, path: &Path, kind: &'static str) -> Vec<Passage> {
+    let Ok(content) = vault.read(path) else {
+        return Vec::new();
+    };
+    let source = path
+        .strip_prefix(soul_path)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/");
+    passages(&content)
+        .into_iter()
+        .map(|(heading, text)| Passage {
+            source: source.clone(),
+            kind,
+            heading,
+            text,
+        })
+        .collect()
+}
+
+/// Passages of the garden and dreams, else of recent emotional memories.
+fn candidates(soul_path: &Path, vault: &Vault) -> Vec<Passage> {
+    let mut found = Vec::new();
+    for dir in paths::existing(soul_path, Location::Soul) {
+        for (de, en, kind) in SOURCES {
+            for name in [de, en] {
+                found.extend(read(soul_path, vault, &dir.join(name), kind));
+            }
+        }
+    }
+    if !found.is_empty() {
+        return found;
+    }
+
+    let cutoff = SystemTime::now() - RECENT_MEMORIES;
+    let mut memories: Vec<PathBuf> =
+        paths::existing(soul_path, Location::Memory(Memories::Emotional))
+            .into_iter()
+            .filter_map(|dir| fs::read_dir(dir).ok())
+            .flat_map(|entries| entries.flatten())
+            .filter(|e| {
+                e.file_name().to_string_lossy().ends_with(".md")
+                    && e.metadata()
+                        .and_then(|m| m.modified())
+                        .is_ok_and(|modified| modified >= cutoff)
+            })
+            .map(|e| e.path())
+            .collect();
+    memories.sort();
+    for path in memories {
+        found.extend(read(soul_path, vault, &path, "memory"));
+    }
+    found
+}
+
+fn pick(soul_path: &Path, vault: &Vault, date: NaiveDate) -> Option<DailyHighlight> {
+    let mut candidates = candidates(soul_path, vault);
+    if candidates.is_empty() {
+        return None;
+    }
+    let index = (seed(date) % candidates.len() as u64) as usize;
+    let passage = candidates.swap_remove(index);
+    Some(DailyHighlight {
+        date: date.format("%Y-%m-%d").to_string(),
+        source: passage.source,
+        kind: passage.kind.to_string(),
+        heading: passage.heading,
+        text: passage.text,
+    })
+}
@@ -0,0 +1,414 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::hash::{DefaultHasher, Hasher};
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use parking_lot::RwLock;
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+use crate::backend::OsFileStore;
+use crate::blocking::CancelToken;
+use crate::config::AppConfig;
+use crate::downloads;
+use crate::protected;
+use crate::retention::{self, PurgeItem};
+use crate::safe_path;
+
+const MIB: u64 = 1024 * 1024;
+/// Reclaimable sizes from which a recommendation is "high" / "medium"
+const HIGH_BYTES: u64 = 100 * MIB;
+const MEDIUM_BYTES: u64 = 10 * MIB;
+/// Media files above this are worth a look
+const OVERSIZED_MEDIA: u64 = 25 * MIB;
+/// Smaller identical files (templates, stubs) aren't worth reporting
+const MIN_DUPLICATE_BYTES: u64 = 1024;
+/// Duplicate groups reported, largest first
+const MAX_DUPLICATE_GROUPS: usize = 20;
+/// Git history above this gets a (manual) recommendation
+const LARGE_HISTORY: u64 = 500 * MIB;
+/// Not the soul's own files; counted for disk usage, never deduplicated
+const TOOLING_DIRS: &[&str] = &[".git", "node_modules"];
+
+/// Disk usage of one top-level entry of the soul.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct AreaUsage {
+    /// Top-level file or directory, relative to the soul
+    pub path: String,
+    #[ts(type = "number")]
+    pub bytes: u64,
+    pub files: usize,
+}
+
+/// A command that carries out a recommendation, invoked as-is.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct Remediation {
+    pub command: String,
+    pub args: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct Recommendation {
+    /// "stale_archives", "terminal_history", "duplicates",
+    /// "oversized_media" or "large_history"
+    pub kind: String,
+    /// "high", "medium" or "low"
+    pub priority: String,
+    pub title: String,
+    pub detail: String,
+    #[ts(type = "number")]
+    pub reclaimable_bytes: u64,
+    /// Files concerned, relative to the soul (terminal history is the
+    /// app's own file)
+    pub paths: Vec<String>,
+    /// None when it has to be done by hand
+    pub remediation: Option<Remediation>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct HousekeepingReport {
+    #[ts(type = "number")]
+    pub total_bytes: u64,
+    /// Largest first
+    pub usage: Vec<AreaUsage>,
+    /// Most urgent first
+    pub recommendations: Vec<Recommendation>,
+    #[ts(type = "number")]
+    pub generated_at: u64,
+}
+
+struct Entry {
+    path: PathBuf,
+    relative: String,
+    bytes: u64,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn priority(bytes: u64) -> &'static str {
+    if bytes >= HIGH_BYTES {
+        "high"
+    } else if bytes >= MEDIUM_BYTES {
+        "medium"
+    } else {
+        "low"
+    }
+}
+
+fn rank(priority: &str) -> u8 {
+    match priority {
+        "high" => 0,
+        "medium" => 1,
+        _ => 2,
+    }
+}
+
+fn mib(bytes: u64) -> String {
+    format!("{:.1} MiB", bytes as f64 / MIB as f64)
+}
+
+/// Every file under the soul (dotfiles included), without following links.
+fn walk(soul_path: &Path, token: &CancelToken) -> Result<Vec<Entry>, String> {
+    let mut entries = Vec::new();
+    let mut dirs = vec![soul_path.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        token.check()?;
+        let Ok(read) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in read.flatten() {
+            let Ok(kind) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            if kind.is_dir() {
+                dirs.push(path);
+            } else if kind.is_file() {
+                let Ok(relative) = path.strip_prefix(soul_path) else {
+                    continue;
+                };
+                entries.push(Entry {
+                    relative: relative.to_string_lossy().replace('\\', "/"),
+                    bytes: entry.metadata().map(|m| m.len()).unwrap_or(0),
+                    path,
+                });
+            }
+        }
+    }
+    entries.sort_by(|a, b| a.relative.cmp(&b.relative));
+    Ok(entries)
+}
+
+fn top_level(relative: &str) -> &str {
+    relative.split('/').next().unwrap_or(relative)
+}
+
+fn usage(entries: &[Entry]) -> Vec<AreaUsage> {
+    let mut areas: BTreeMap<&str, AreaUsage> = BTreeMap::new();
+    for entry in entries {
+        let top = top_level(&entry.relative);
+        let area = areas.entry(top).or_insert_with(|| AreaUsage {
+            path: top.to_string(),
+            bytes: 0,
+            files: 0,
+        });
+        area.bytes += entry.bytes;
+        area.files += 1;
+    }
+    let mut usage: Vec<AreaUsage> = areas.into_values().collect();
+    usage.sort_by_key(|a| std::cmp::Reverse(a.bytes));
+    usage
+}
+
+fn content_hash(path: &Path) -> Option<u64> {
+    let mut reader = BufReader::new(File::open(path).ok()?);
+    let mut hasher = DefaultHasher::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = reader.read(&mut buf).ok()?;
+        if n == 0 {
+            return Some(hasher.finish());
+        }
+        hasher.write(&buf[..n]);
+    }
+}
+
+/// Byte-for-byte comparison, so a hash collision never suggests deleting
+/// a file that differs.
+fn same_content(a: &Path, b: &Path) -> bool {
+    match (fs::read(a), fs::read(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Groups of identical files, each with the copy to keep first.
+fn duplicates<'a>(
+    entries: &'a [Entry],
+    token: &CancelToken,
+) -> Result<Vec<Vec<&'a Entry>>, String> {
+    let mut by_size: HashMap<u64, Vec<&Entry>> = HashMap::new();
+    for entry in entries {
+        if entry.bytes >= MIN_DUPLICATE_BYTES && !TOOLING_DIRS.contains(&top_level(&entry.relative))
+        {
+            by_size.entry(entry.bytes).or_default().push(entry);
+        }
+    }
+    let mut groups = Vec::new();
+    for candidates in by_size.into_values().filter(|c| c.len() > 1) {
+        let mut by_hash: HashMap<u64, Vec<&Entry>> = HashMap::new();
+        for entry in candidates {
+            token.check()?;
+            if let Some(hash) = content_hash(&entry.path) {
+                by_hash.entry(hash).or_default().push(entry);
+            }
+        }
+        for mut same in by_hash.into_values().filter(|s| s.len() > 1) {
+            // Keep the copy with the shortest path (closest to the top)
+            same.sort_by_key(|e| (e.relative.matches('/').count(), e.relative.clone()));
+            let kept = same[0];
+            let mut group = vec![kept];
+            group.extend(
+                same[1..]
+                    .iter()
+                    .copied()
+                    .filter(|e| same_content(&kept.path, &e.path)),
+            );
+            if group.len() > 1 {
+                groups.push(group);
+            }
+        }
+    }
+    groups.sort_by_key(|g| std::cmp::Reverse(g[0].bytes * (g.len() as u64 - 1)));
+    groups.truncate(MAX_DUPLICATE_GROUPS);
+    Ok(groups)
+}
+
+fn purge_recommendations(preview: &[PurgeItem]) -> Vec<Recommendation> {
+    let mut recommendations = Vec::new();
+    for (kind, category, title) in [
+        (
+            "stale_archives",
+            "event_archive",
+            "Event archives past their retention",
+        ),
+        (
+            "terminal_history",
+            "pty_history",
+            "Terminal history past its retention",
+        ),
+    ] {
+        let items: Vec<&PurgeItem> = preview.iter().filter(|i| i.category == category).collect();
+        if items.is_empty() {
+            continue;
+        }
+        let bytes = items.iter().map(|i| i.bytes).sum();
+        let records: usize = items.iter().map(|i| i.records).sum();
+        let what = if records > 0 {
+            format!("{} old records", records)
+        } else {
+            format!("{} file(s)", items.len())
+        };
+        let detail = format!(
+            "{} ({}) are past the retention policy and go on the next scheduled purge.",
+            what,
+            mib(bytes)
+        );
+        recommendations.push(Recommendation {
+            kind: kind.to_string(),
+            priority: priority(bytes).to_string(),
+            title: title.to_string(),
+            detail,
+            reclaimable_bytes: bytes,
+            paths: items.iter().map(|i| i.path.clone()).collect(),
+            remediation: Some(Remediation {
+                command: "run_purge".to_string(),
+                args: json!({}),
+            }),
+        });
+    }
+    recommendations
+}
+
+/// Disk usage, duplicates, data past its retention and oversized media,
+/// with what to do about each.
+pub fn report(app: &AppHandle, token: &CancelToken) -> Result<HousekeepingReport, String> {
+    let soul_path = app
+        .state::<Arc<RwLock<AppConfig>>>()
+        .read()
+        .soul_path
+        .clone();
+    token.set_progress(0, 3);
+    let entries = walk(&soul_path, token)?;
+    let usage = usage(&entries);
+    let mut recommendations = purge_recommendations(&retention::purge(app, true)?.items);
+
+    token.set_progress(1, 3);
+    for group in duplicates(&entries, token)? {
+        let kept = group[0];
+        let removable: Vec<String> = group[1..]
+            .iter()
+            .map(|e| e.relative.clone())
+            .filter(|r| !protected::is_protected(&soul_path, r))
+            .collect();
+        if removable.is_empty() {
+            continue;
+        }
+        let bytes = kept.bytes * removable.len() as u64;
+        recommendations.push(Recommendation {
+            kind: "duplicates".to_string(),
+            priority: priority(bytes).to_string(),
+            title: format!("{} copies of {}", group.len(), kept.relative),
+            detail: format!(
+                "Identical files ({} each). Keeping {} and removing the other copies frees {}.",
+                mib(kept.bytes),
+                kept.relative,
+                mib(bytes)
+            ),
+            reclaimable_bytes: bytes,
+            paths: removable.clone(),
+            remediation: Some(Remediation {
+                command: "delete_soul_files".to_string(),
+                args: json!({ "names": removable }),
+            }),
+        });
+    }
+
+    token.set_progress(2, 3);
+    let attachments = downloads::attachments(&soul_path);
+    for entry in entries.iter().filter(|e| {
+        top_level(&e.relative) == "media"
+            && e.bytes > OVERSIZED_MEDIA
+            && !protected::is_protected(&soul_path, &e.relative)
+    }) {
+        let linked = attachments
+            .iter()
+            .any(|a| a.path == entry.relative && a.memory_id.is_some());
+        // Media a memory refers to stays; only mention it
+        recommendations.push(Recommendation {
+            kind: "oversized_media".to_string(),
+            priority: if linked { "low" } else { priority(entry.bytes) }.to_string(),
+            title: format!("Large media file {}", entry.relative),
+            detail: if linked {
+                format!(
+                    "{}, attached to a memory; a smaller copy would do if it isn't needed in full.",
+                    mib(entry.bytes)
+                )
+            } else {
+                format!("{}, not attached to any memory.", mib(entry.bytes))
+            },
+            reclaimable_bytes: entry.bytes,
+            paths: vec![entry.relative.clone()],
+            remediation: (!linked).then(|| Remediation {
+                command: "delete_soul_files".to_string(),
+                args: json!({ "names": [entry.relative] }),
+            }),
+        });
+    }
+
+    if let Some(history) = usage.iter().find(|a| a.path == ".git") {
+        if history.bytes > LARGE_HISTORY {
+            recommendations.push(Recommendation {
+                kind: "large_history".to_string(),
+                priority: "low".to_string(),
+                title: "Large Git history".to_string(),
+                detail: format!(
+                    "The soul's Git history takes {}; `git gc` in the soul may compact it.",
+                    mib(history.bytes)
+                ),
+                reclaimable_bytes: 0,
+                paths: vec![".git".to_string()],
+                remediation: None,
+            });
+        }
+    }
+
+    recommendations.sort_by(|a, b| {
+        rank(&a.priority)
+            .cmp(&rank(&b.priority))
+            .then(b.reclaimable_bytes.cmp(&a.reclaimable_bytes))
+    });
+    token.set_progress(3, 3);
+    Ok(HousekeepingReport {
+        total_bytes: entries.iter().map(|e| e.bytes).sum(),
+        usage,
+        recommendations,
+        generated_at: now_ms(),
+    })
+}
+
+/// Delete soul files (not directories). Protected files and anything
+/// outside the soul are refused before anything is removed.
+pub fn delete_files(soul_path: &Path, names: &[String]) -> Result<usize, String> {
+    let mut targets = Vec::new();
+    for name in names {
+        safe_path::relative(name)?;
+        if protected::is_protected(soul_path, name) {
+            return Err(format!("{}: {} is protected", protected::PROTECTED, name));
+        }
+        let path = safe_path::safe_resolve(&OsFileStore, soul_path, name)?;
+        if !path.is_file() {
+            return Err(format!("Not a file: {}", name));
+        }
+        targets.push(path);
+    }
+    for path in &targets {
+        fs::remove_file(path).map_err(|e| e.to_string())?;
+    }
+    downloads::forget(soul_path, names)?;
+    Ok(targets.len())
+}
@@ -0,0 +1,115 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::RwLock;
+use tauri::{AppHandle, Manager};
+
+use crate::metrics::Metrics;
+use crate::network::{ensure_local, NetworkConfig};
+
+/// Responses worth another attempt: the upstream is starting or overloaded
+const RETRY_STATUSES: &[u16] = &[502, 503, 504];
+
+/// Timeout and retry behaviour of one kind of call.
+#[derive(Debug, Clone, Copy)]
+pub struct Policy {
+    /// Label of the call in the latency metrics
+    pub name: &'static str,
+    /// Per attempt, connecting included
+    pub timeout: Duration,
+    /// Attempts after the first one
+    pub retries: u32,
+    /// Wait before the first retry, doubled for every further one
+    pub backoff: Duration,
+    /// Timeouts and 502/503/504 may be retried. Otherwise only failed
+    /// connects are, which never reached the server.
+    pub idempotent: bool,
+}
+
+/// Pooled HTTP clients shared by every backend call: `local` for the engine
+/// and founding server, `external` for everything else. Rebuilt when the
+/// network settings change.
+pub struct Http {
+    local: RwLock<reqwest::Client>,
+    external: RwLock<reqwest::Client>,
+    /// Reject local request URLs that leave loopback (SSRF guard)
+    local_only: RwLock<bool>,
+}
+
+fn build(builder: reqwest::ClientBuilder) -> reqwest::Client {
+    builder
+        .pool_idle_timeout(Duration::from_secs(90))
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new())
+}
+
+impl Http {
+    pub fn new(network: &NetworkConfig) -> Self {
+        Self {
+            local: RwLock::new(build(network.local_client_builder())),
+            external: RwLock::new(build(network.client_builder())),
+            local_only: RwLock::new(network.local_only),
+        }
+    }
+
+    /// Rebuild the clients after the network settings changed.
+    pub fn set_network(&self, network: &NetworkConfig) {
+        *self.local.write() = build(network.local_client_builder());
+        *self.external.write() = build(network.client_builder());
+        *self.local_only.write() = network.local_only;
+    }
+
+    /// Client for a URL on the engine or founding server, checked against
+    /// the `local_only` policy.
+    pub fn local(&self, url: &str) -> Result<reqwest::Client, String> {
+        if *self.local_only.read() {
+            ensure_local(url)?;
+        }
+        Ok(self.local.read().clone())
+    }
+
+    pub fn external(&self) -> reqwest::Client {
+        self.external.read().clone()
+    }
+}
+
+fn retryable(policy: &Policy, result: &Result<reqwest::Response, reqwest::Error>) -> bool {
+    match result {
+        Ok(resp) => policy.idempotent && RETRY_STATUSES.contains(&resp.status().as_u16()),
+        Err(e) => e.is_connect() || (policy.idempotent && e.is_timeout()),
+    }
+}
+
+/// Send the request `request` builds under `policy`, building it again for
+/// every retry. The latency over all attempts and the number of retries end
+/// up in the metrics.
+pub async fn send(
+    app: &AppHandle,
+    policy: &Policy,
+    request: impl Fn() -> reqwest::RequestBuilder,
+) -> Result<reqwest::Response, reqwest::Error> {
+    let started = Instant::now();
+    let mut retries = 0;
+    let result = loop {
+        let result = request().timeout(policy.timeout).send().await;
+        if retries >= policy.retries || !retryable(policy, &result) {
+            break result;
+        }
+        tokio::time::sleep(policy.backoff * 2u32.pow(retries)).await;
+        retries += 1;
+    };
+    if let Some(metrics) = app.try_state::<Arc<Metrics>>() {
+        let outcome = match &result {
+            Ok(resp) => resp.status().as_u16().to_string(),
+            Err(e) if e.is_timeout() => "timeout".to_string(),
+            Err(_) => "error".to_string(),
+        };
+        metrics.http_request_finished(
+            policy.name,
+            &outcome,
+            started.elapsed().as_millis() as u64,
+            retries,
+        );
+    }
+    result
+}
@@ -0,0 +1,402 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use ts_rs::TS;
+
+use crate::backend::{Backends, GitBackend};
+use crate::batch;
+use crate::blocking::{run_blocking, CancelToken};
+use crate::config::{app_data_dir, AppConfig};
+use crate::engine_update;
+use crate::layout;
+use crate::paths::SoulLayout;
+use crate::vault::{self, Vault};
+
+/// An operation that mutates state, recorded before it starts.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JournalOp {
+    /// Writing an export archive to `path`
+    Export { path: String },
+    /// `git revert` of `hash` in `repo`, which was at `head` before
+    Rollback {
+        repo: String,
+        hash: String,
+        head: String,
+    },
+    /// Encrypting/decrypting files under `soul_path` to match the vault config
+    Migrate { soul_path: String },
+    /// Swapping the bundled engine into `dir` and smoke-starting it
+    EngineUpdate { dir: String },
+    /// Switching `dir` back to the previous engine version
+    EngineRollback { dir: String },
+    /// Moving the soul at `soul_path` to another directory naming scheme
+    Layout {
+        soul_path: String,
+        layout: SoulLayout,
+    },
+    /// `apply_batch`, with the touched files saved in `snapshot`
+    Batch { snapshot: String },
+    /// Moving the encrypted files under `soul_path` to a new passphrase
+    Rekey { soul_path: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JournalEntry {
+    id: String,
+    started: u64,
+    op: JournalOp,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct RecoveryAction {
+    pub op: JournalOp,
+    /// Unix ms when the interrupted operation started
+    #[ts(type = "number")]
+    pub started: u64,
+    /// What recovery did about it
+    pub action: String,
+    pub ok: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, TS)]
+#[ts(export)]
+pub struct RecoveryReport {
+    pub actions: Vec<RecoveryAction>,
+    /// Operations that can only be resumed later (migrations wait for unlock)
+    pub pending: usize,
+}
+
+/// Removes the journal entry when the operation ends — successfully or
+/// not. Only a killed process leaves the entry behind.
+pub struct JournalGuard {
+    path: PathBuf,
+}
+
+impl JournalGuard {
+    /// Leave the entry for startup recovery, e.g. when undoing a failed
+    /// operation failed as well.
+    pub fn keep(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for JournalGuard {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Write-ahead journal of in-flight operations (<app_data_dir>/journal),
+/// checked at startup to undo or finish whatever a crash interrupted.
+pub struct Journal {
+    dir: PathBuf,
+    next_id: AtomicU64,
+    report: Mutex<RecoveryReport>,
+    /// Interrupted migrations and passphrase changes, finished once the
+    /// vault key is available
+    pending: Mutex<Vec<JournalEntry>>,
+}
+
+impl Default for Journal {
+    fn default() -> Self {
+        Self {
+            dir: app_data_dir().join("journal"),
+            next_id: AtomicU64::new(0),
+            report: Mutex::new(RecoveryReport::default()),
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl Journal {
+    /// Record intent. Fails (and the caller must not proceed) if the entry
+    /// can't be persisted.
+    pub fn begin(&self, op: JournalOp) -> Result<JournalGuard, String> {
+        fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+        let started = now_ms();
+        let id = format!(
+            "{}-{}",
+            started,
+            self.next_id.fetch_add(1, Ordering::Relaxed)
+        );
+        let entry = JournalEntry { id, started, op };
+        let path = self.dir.join(format!("{}.json", entry.id));
+        let tmp = path.with_extension("tmp");
+        let json = serde_json::to_vec(&entry).map_err(|e| e.to_string())?;
+        fs::write(&tmp, json).map_err(|e| e.to_string())?;
+        fs::rename(&tmp, &path).map_err(|e| e.to_string())?;
+        Ok(JournalGuard { path })
+    }
+
+    pub fn report(&self) -> RecoveryReport {
+        self.report.lock().clone()
+    }
+
+    fn entries(&self) -> Vec<(PathBuf, JournalEntry)> {
+        let Ok(dir) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut entries: Vec<(PathBuf, JournalEntry)> = dir
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|p| {
+                let entry = serde_json::from_slice(&fs::read(&p).ok()?).ok()?;
+                Some((p, entry))
+            })
+            .collect();
+        entries.sort_by_key(|(_, e)| e.started);
+        entries
+    }
+
+    /// Handle entries left by a previous run. Exports are deleted, reverts
+    /// aborted, batches rolled back (and kept until that succeeds);
+    /// migrations are resumed right away if the vault is unlocked, otherwise
+    /// after `resume_pending`.
+    pub fn recover(&self, git: &dyn GitBackend, vault: &Vault) -> RecoveryReport {
+        let mut report = RecoveryReport::default();
+        for (path, entry) in self.entries() {
+            let (action, ok) = match &entry.op {
+                JournalOp::Export { path } => recover_export(Path::new(path)),
+                JournalOp::Rollback { repo, hash, head } => {
+                    recover_rollback(git, Path::new(repo), hash, head)
+                }
+                JournalOp::EngineUpdate { dir } => engine_update::recover_update(Path::new(dir)),
+                JournalOp::EngineRollback { dir } => {
+                    engine_update::recover_rollback(Path::new(dir))
+                }
+                JournalOp::Layout { soul_path, layout } => {
+                    recover_layout(git, vault, Path::new(soul_path), *layout)
+                }
+                JournalOp::Batch { snapshot } => batch::recover(Path::new(snapshot)),
+                JournalOp::Migrate { .. } | JournalOp::Rekey { .. } => {
+                    match recover_vault(vault, &entry.op, &CancelToken::default()) {
+                        Some(result) => result,
+                        None => {
+                            report.pending += 1;
+                            self.pending.lock().push(entry.clone());
+                            (resumes_after_unlock(&entry.op), true)
+                        }
+                    }
+                }
+            };
+            report.actions.push(RecoveryAction {
+                op: entry.op.clone(),
+                started: entry.started,
+                action,
+                ok,
+            });
+            // A batch whose snapshot couldn't be put back stays journaled,
+            // so the next start tries again
+            let retry = !ok && matches!(entry.op, JournalOp::Batch { .. });
+            if !retry && !self.pending.lock().iter().any(|p| p.id == entry.id) {
+                let _ = fs::remove_file(path);
+            }
+        }
+        *self.report.lock() = report.clone();
+        report
+    }
+}
+
+/// Finish an interrupted migration or roll back an interrupted passphrase
+/// change. None while the vault key is unavailable.
+fn recover_vault(vault: &Vault, op: &JournalOp, token: &CancelToken) -> Option<(String, bool)> {
+    match op {
+        JournalOp::Migrate { soul_path } => {
+            let soul_path = Path::new(soul_path);
+            let removed = vault::remove_temp_files(soul_path);
+            match vault.migrate(soul_path, token) {
+                Ok(r) => Some((migrated(removed, &r), r.failed.is_empty())),
+                Err(_) if !vault.status().unlocked => None,
+                Err(e) => Some((
+                    format!("Resuming encryption migration failed: {}", e),
+                    false,
+                )),
+            }
+        }
+        JournalOp::Rekey { soul_path } => {
+            if !vault.status().unlocked && vault.config().pending_salt.is_some() {
+                return None;
+            }
+            let soul_path = Path::new(soul_path);
+            vault::remove_temp_files(soul_path);
+            Some(match vault.rollback_rekey(soul_path) {
+                Ok(n) => (
+                    format!(
+                        "Rolled back interrupted passphrase change: {} files restored",
+                        n
+                    ),
+                    true,
+                ),
+                Err(e) => (
+                    format!("Rolling back interrupted passphrase change failed: {}", e),
+                    false,
+                ),
+            })
+        }
+        _ => None,
+    }
+}
+
+fn resumes_after_unlock(op: &JournalOp) -> String {
+    match op {
+        JournalOp::Rekey { .. } => {
+            "Interrupted passphrase change is rolled back after unlock".to_string()
+        }
+        _ => "Interrupted encryption migration resumes after unlock".to_string(),
+    }
+}
+
+fn migrated(temp_removed: usize, report: &vault::MigrationReport) -> String {
+    format!(
+        "Resumed encryption migration: {} encrypted, {} decrypted, {} failed, {} temp files removed",
+        report.encrypted.len(),
+        report.decrypted.len(),
+        report.failed.len(),
+        temp_removed
+    )
+}
+
+fn recover_export(path: &Path) -> (String, bool) {
+    if !path.exists() {
+        return ("Interrupted export left no file".to_string(), true);
+    }
+    match fs::remove_file(path) {
+        Ok(()) => (format!("Removed partial export {}", path.display()), true),
+        Err(e) => (
+            format!("Could not remove partial export {}: {}", path.display(), e),
+            false,
+        ),
+    }
+}
+
+fn recover_layout(
+    git: &dyn GitBackend,
+    vault: &Vault,
+    soul_path: &Path,
+    target: SoulLayout,
+) -> (String, bool) {
+    match layout::migrate(soul_path, target, git, vault, &CancelToken::default()) {
+        Ok(r) => (
+            format!(
+                "Resumed soul layout migration: {} moved, {} links updated, {} conflicts",
+                r.moved.len(),
+                r.links_updated.len(),
+                r.conflicts.len()
+            ),
+            true,
+        ),
+        Err(e) => (format!("Resuming soul layout migration failed: {}", e), false),
+    }
+}
+
+fn recover_rollback(git: &dyn GitBackend, repo: &Path, hash: &str, head: &str) -> (String, bool) {
+    let token = CancelToken::default();
+    if repo.join(".git").join("REVERT_HEAD").exists() {
+        return match git.run(repo, &["revert", "--abort"], &token) {
+            Ok(_) => (format!("Aborted interrupted revert of {}", hash), true),
+            Err(e) => (format!("git revert --abort failed: {}", e.trim()), false),
+        };
+    }
+    match git.run(repo, &["rev-parse", "HEAD"], &token) {
+        Ok(current) if current.trim() != head => {
+            (format!("Revert of {} had already completed", hash), true)
+        }
+        Ok(_) => (format!("Revert of {} was never applied", hash), true),
+        Err(e) => (
+            format!("Could not inspect {}: {}", repo.display(), e.trim()),
+            false,
+        ),
+    }
+}
+
+/// Run startup recovery and emit `recovery:report` once the window had a
+/// moment to load (only when something was found).
+pub fn start_recovery(app: AppHandle) {
+    std::thread::spawn(move || {
+        let journal = app.state::<Arc<Journal>>().inner().clone();
+        let git = app.state::<Arc<Backends>>().git.clone();
+        let vault = app.state::<Arc<Vault>>().inner().clone();
+        let report = journal.recover(git.as_ref(), &vault);
+        if report.actions.is_empty() {
+            return;
+        }
+        // A resumed layout migration renamed encrypted directories, a
+        // rolled back passphrase change dropped the staged salt
+        if report
+            .actions
+            .iter()
+            .any(|a| matches!(a.op, JournalOp::Layout { .. } | JournalOp::Rekey { .. }))
+        {
+            let config = app.state::<Arc<RwLock<AppConfig>>>();
+            let mut cfg = config.write();
+            cfg.encryption = vault.config();
+            let _ = cfg.save();
+        }
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        let _ = app.emit("recovery:report", &report);
+    });
+}
+
+/// Finish migrations and roll back passphrase changes that were waiting for
+/// the vault key. Called after unlock.
+pub async fn resume_pending(app: &AppHandle) {
+    let journal = app.state::<Arc<Journal>>().inner().clone();
+    let entries = std::mem::take(&mut *journal.pending.lock());
+    if entries.is_empty() {
+        return;
+    }
+    let vault = app.state::<Arc<Vault>>().inner().clone();
+    let dir = journal.dir.clone();
+    let recovering = vault.clone();
+    let actions = run_blocking(app, "resume_migration", None, move |token| {
+        let mut actions = Vec::new();
+        for entry in entries {
+            let Some((action, ok)) = recover_vault(&recovering, &entry.op, token) else {
+                continue;
+            };
+            let _ = fs::remove_file(dir.join(format!("{}.json", entry.id)));
+            actions.push(RecoveryAction {
+                op: entry.op.clone(),
+                started: entry.started,
+                action,
+                ok,
+            });
+        }
+        Ok(actions)
+    })
+    .await
+    .unwrap_or_default();
+
+    if actions
+        .iter()
+        .any(|a| matches!(a.op, JournalOp::Rekey { .. }))
+    {
+        let config = app.state::<Arc<RwLock<AppConfig>>>();
+        let mut cfg = config.write();
+        cfg.encryption = vault.config();
+        let _ = cfg.save();
+    }
+    let report = RecoveryReport {
+        actions,
+        pending: 0,
+    };
+    *journal.report.lock() = report.clone();
+    let _ = app.emit("recovery:report", &report);
+}
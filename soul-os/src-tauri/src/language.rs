@@ -0,0 +1,528 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use regex::Regex;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+use crate::config::app_data_dir;
+
+/// Languages the soul format is written in, with their dictionary names
+const LANGUAGES: &[(&str, &str)] = &[("de", "de_DE"), ("en", "en_US")];
+/// Suggestions per misspelled word
+const MAX_SUGGESTIONS: usize = 5;
+
+const STOPWORDS_DE: &[&str] = &[
+    "der", "die", "das", "und", "ist", "nicht", "ich", "du", "ein", "eine", "zu", "mit", "sich",
+    "auf", "für", "den", "dem", "des", "auch", "es", "wir", "aber", "wie", "noch", "nur", "oder",
+    "wenn", "sie", "bin", "war", "hat", "habe", "mich", "mir", "uns", "von", "im", "einen",
+];
+const STOPWORDS_EN: &[&str] = &[
+    "the", "and", "is", "not", "i", "you", "a", "an", "to", "with", "of", "on", "for", "it", "we",
+    "but", "how", "still", "only", "or", "if", "they", "am", "was", "has", "have", "me", "my",
+    "us", "from", "in", "that", "this", "be", "are", "what",
+];
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct LanguageGuess {
+    /// "de", "en" or "unknown"
+    pub lang: String,
+    /// Share of the recognized stopwords that belong to `lang`, 0–1
+    pub confidence: f64,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct Misspelling {
+    pub word: String,
+    /// Offset into the text in UTF-16 code units, as editors count
+    pub offset: usize,
+    /// Length in UTF-16 code units
+    pub length: usize,
+    pub suggestions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SpellcheckReport {
+    /// Language checked against, detected unless given
+    pub lang: String,
+    pub misspellings: Vec<Misspelling>,
+}
+
+/// German or English, by stopwords; umlauts and ß tip the balance.
+pub fn detect(text: &str) -> LanguageGuess {
+    let (mut de, mut en) = (0usize, 0usize);
+    for word in text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+    {
+        let lower = word.to_lowercase();
+        if STOPWORDS_DE.contains(&lower.as_str()) {
+            de += 1;
+        }
+        if STOPWORDS_EN.contains(&lower.as_str()) {
+            en += 1;
+        }
+        if lower.contains(['ä', 'ö', 'ü', 'ß']) {
+            de += 1;
+        }
+    }
+    if de + en == 0 {
+        return LanguageGuess {
+            lang: "unknown".to_string(),
+            confidence: 0.0,
+        };
+    }
+    let (lang, hits) = if de >= en { ("de", de) } else { ("en", en) };
+    LanguageGuess {
+        lang: lang.to_string(),
+        confidence: hits as f64 / (de + en) as f64,
+    }
+}
+
+/// One prefix or suffix rule of a hunspell .aff file.
+struct Affix {
+    flag: String,
+    strip: String,
+    /// Matches the stem the affix attaches to
+    condition: Option<Regex>,
+}
+
+/// A hunspell dictionary: stems with their affix flags plus the affix
+/// rules, enough to accept inflected words and rank suggestions. Compound
+/// rules and cross products of prefix and suffix aren't applied.
+pub struct Dictionary {
+    stems: HashMap<String, Vec<String>>,
+    /// Rules by the text they add, so a word is only tried against the
+    /// rules that can have produced it
+    prefixes: HashMap<String, Vec<Affix>>,
+    suffixes: HashMap<String, Vec<Affix>>,
+    /// Characters tried when suggesting, most frequent first
+    try_chars: Vec<char>,
+}
+
+/// How a .aff file writes flags.
+#[derive(Clone, Copy)]
+enum FlagMode {
+    Char,
+    Long,
+    Num,
+}
+
+fn split_flags(flags: &str, mode: FlagMode) -> Vec<String> {
+    match mode {
+        FlagMode::Char => flags.chars().map(String::from).collect(),
+        FlagMode::Long => flags
+            .chars()
+            .collect::<Vec<_>>()
+            .chunks(2)
+            .map(|c| c.iter().collect())
+            .collect(),
+        FlagMode::Num => flags.split(',').map(|f| f.trim().to_string()).collect(),
+    }
+}
+
+impl Dictionary {
+    pub fn parse(aff: &str, dic: &str) -> Self {
+        let mut mode = FlagMode::Char;
+        let mut prefixes: HashMap<String, Vec<Affix>> = HashMap::new();
+        let mut suffixes: HashMap<String, Vec<Affix>> = HashMap::new();
+        let mut try_chars = Vec::new();
+        for line in aff.lines() {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts.as_slice() {
+                ["FLAG", "long", ..] => mode = FlagMode::Long,
+                ["FLAG", "num", ..] => mode = FlagMode::Num,
+                ["TRY", chars, ..] => try_chars = chars.chars().collect(),
+                // Rule lines have 5+ fields; the 4-field line is the header
+                [kind @ ("PFX" | "SFX"), flag, strip, add, condition, ..] => {
+                    let strip = if *strip == "0" { "" } else { strip };
+                    // Continuation flags after the slash aren't applied
+                    let add = add.split('/').next().unwrap_or("");
+                    let add = if add == "0" { "" } else { add };
+                    let condition = match *condition {
+                        "." => None,
+                        cond if *kind == "PFX" => Regex::new(&format!("^{}", cond)).ok(),
+                        cond => Regex::new(&format!("{}$", cond)).ok(),
+                    };
+                    let affix = Affix {
+                        flag: flag.to_string(),
+                        strip: strip.to_string(),
+                        condition,
+                    };
+                    let rules = if *kind == "PFX" {
+                        &mut prefixes
+                    } else {
+                        &mut suffixes
+                    };
+                    rules.entry(add.to_string()).or_default().push(affix);
+                }
+                _ => {}
+            }
+        }
+        let mut stems = HashMap::new();
+        // The first line is the word count
+        for line in dic.lines().skip(1) {
+            let entry = line.split(['\t', ' ']).next().unwrap_or("");
+            let (word, flags) = entry.split_once('/').unwrap_or((entry, ""));
+            if word.is_empty() {
+                continue;
+            }
+            stems
+                .entry(word.to_string())
+                .or_insert_with(Vec::new)
+                .extend(split_flags(flags, mode));
+        }
+        Self {
+            stems,
+            prefixes,
+            suffixes,
+            try_chars,
+        }
+    }
+
+    fn has_flag(&self, stem: &str, flag: &str) -> bool {
+        self.stems
+            .get(stem)
+            .is_some_and(|flags| flags.iter().any(|f| f == flag))
+    }
+
+    fn stem_matches(&self, affix: &Affix, stem: &str) -> bool {
+        !stem.is_empty()
+            && affix.condition.as_ref().is_none_or(|c| c.is_match(stem))
+            && self.has_flag(stem, &affix.flag)
+    }
+
+    fn known(&self, word: &str) -> bool {
+        if self.stems.contains_key(word) {
+            return true;
+        }
+        let splits: Vec<usize> = word
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain([word.len()])
+            .collect();
+        splits.iter().any(|&i| {
+            let (base, ending) = word.split_at(i);
+            let suffixed = self.suffixes.get(ending).is_some_and(|rules| {
+                rules
+                    .iter()
+                    .any(|affix| self.stem_matches(affix, &format!("{}{}", base, affix.strip)))
+            });
+            let (start, rest) = word.split_at(i);
+            suffixed
+                || self.prefixes.get(start).is_some_and(|rules| {
+                    rules
+                        .iter()
+                        .any(|affix| self.stem_matches(affix, &format!("{}{}", affix.strip, rest)))
+                })
+        })
+    }
+
+    /// Whether `word` is spelled correctly. A capitalized word also passes
+    /// in lower case (sentence starts), an all-caps word in any case.
+    pub fn check(&self, word: &str) -> bool {
+        if self.known(word) {
+            return true;
+        }
+        let mut chars = word.chars();
+        let Some(first) = chars.next() else {
+            return true;
+        };
+        let lowered: String = first.to_lowercase().chain(chars).collect();
+        if lowered != word && self.known(&lowered) {
+            return true;
+        }
+        if word.chars().all(|c| !c.is_lowercase()) {
+            let lower = word.to_lowercase();
+            let mut chars = lower.chars();
+            let capitalized: String = chars
+                .next()
+                .map(|c| c.to_uppercase().chain(chars).collect())
+                .unwrap_or_default();
+            return self.known(&lower) || self.known(&capitalized);
+        }
+        false
+    }
+
+    /// Known words one edit away: a deleted, swapped, replaced or
+    /// inserted character.
+    pub fn suggest(&self, word: &str) -> Vec<String> {
+        let chars: Vec<char> = word.chars().collect();
+        let alphabet: Vec<char> = if self.try_chars.is_empty() {
+            "etaoinshrdlcumwfgypbvkjxqz".chars().collect()
+        } else {
+            self.try_chars.clone()
+        };
+        let mut candidates = Vec::new();
+        for i in 0..chars.len() {
+            if i + 1 < chars.len() {
+                let mut swapped = chars.clone();
+                swapped.swap(i, i + 1);
+                candidates.push(swapped);
+            }
+            for c in &alphabet {
+                let mut replaced = chars.clone();
+                replaced[i] = *c;
+                candidates.push(replaced);
+            }
+            let mut deleted = chars.clone();
+            deleted.remove(i);
+            candidates.push(deleted);
+        }
+        for i in 0..=chars.len() {
+            for c in &alphabet {
+                let mut inserted = chars.clone();
+                inserted.insert(i, *c);
+                candidates.push(inserted);
+            }
+        }
+        let mut seen = HashSet::new();
+        candidates
+            .into_iter()
+            .map(|c| c.into_iter().collect::<String>())
+            .filter(|c| c != word && !c.is_empty() && self.check(c))
+            .filter(|c| seen.insert(c.clone()))
+            .take(MAX_SUGGESTIONS)
+            .collect()
+    }
+}
+
+/// Loaded dictionaries, kept for the session.
+#[derive(Default)]
+pub struct Language {
+    dictionaries: Mutex<HashMap<String, Arc<Dictionary>>>,
+}
+
+/// Where `<name>.aff`/`<name>.dic` are looked for: installed by the user,
+/// bundled with the app, then the system's hunspell dictionaries.
+fn dictionary_dirs(app: &AppHandle) -> Vec<PathBuf> {
+    let mut dirs = vec![app_data_dir().join("dictionaries")];
+    if let Ok(resource_dir) = app.path().resource_dir() {
+        dirs.push(resource_dir.join("dictionaries"));
+    }
+    dirs.push(PathBuf::from("/usr/share/hunspell"));
+    dirs
+}
+
+impl Language {
+    pub fn dictionary(&self, app: &AppHandle, lang: &str) -> Result<Arc<Dictionary>, String> {
+        if let Some(dictionary) = self.dictionaries.lock().get(lang) {
+            return Ok(dictionary.clone());
+        }
+        let name = LANGUAGES
+            .iter()
+            .find(|(code, _)| *code == lang)
+            .map(|(_, name)| *name)
+            .ok_or_else(|| format!("Unsupported language: {}", lang))?;
+        let dir = dictionary_dirs(app)
+            .into_iter()
+            .find(|dir| dir.join(format!("{}.dic", name)).exists())
+            .ok_or_else(|| format!("No {} dictionary installed ({}.aff/.dic)", lang, name))?;
+        let read = |ext: &str| fs::read(dir.join(format!("{}.{}", name, ext)));
+        let (aff, dic) = (
+            read("aff").map_err(|e| e.to_string())?,
+            read("dic").map_err(|e| e.to_string())?,
+        );
+        // Older German dictionaries are Latin-1, which maps byte for byte
+        let latin1 = String::from_utf8_lossy(&aff)
+            .lines()
+            .any(|line| line.trim() == "SET ISO8859-1");
+        let decode = |bytes: &[u8]| match latin1 {
+            true => bytes.iter().map(|b| char::from(*b)).collect(),
+            false => String::from_utf8_lossy(bytes).to_string(),
+        };
+        let dictionary = Arc::new(Dictionary::parse(&decode(&aff), &decode(&dic)));
+        self.dictionaries
+            .lock()
+            .insert(lang.to_string(), dictionary.clone());
+        Ok(dictionary)
+    }
+}
+
+/// Misspelled words of `text` in `lang` (detected when None). Words with
+/// digits, URLs, inline code and wiki links are skipped.
+pub fn spellcheck(
+    app: &AppHandle,
+    text: &str,
+    lang: Option<String>,
+) -> Result<SpellcheckReport, String> {
+    let lang = match lang {
+        Some(lang) => lang,
+        None => match detect(text).lang.as_str() {
+            "unknown" => "en".to_string(),
+            lang => lang.to_string(),
+        },
+    };
+    let dictionary = app.state::<Arc<Language>>().dictionary(app, &lang)?;
+    let skipped = Regex::new(r"https?://\S+|`[^`]*`|\[\[[^\]]*\]\]").expect("valid regex");
+    let skip: Vec<(usize, usize)> = skipped
+        .find_iter(text)
+        .map(|m| (m.start(), m.end()))
+        .collect();
+    let mut misspellings = Vec::new();
+    let mut utf16 = 0;
+    let mut word_start: Option<(usize, usize)> = None;
+    // A trailing space closes the last word
+    for (byte, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        let in_word = c.is_alphanumeric() || (c == '\'' && word_start.is_some());
+        match (in_word, word_start) {
+            (true, None) => word_start = Some((byte, utf16)),
+            (false, Some((start, start16))) => {
+                word_start = None;
+                let word = text[start..byte].trim_end_matches('\'');
+                let checked = word.chars().count() > 1
+                    && !word.chars().any(|c| c.is_ascii_digit())
+                    && !skip.iter().any(|(s, e)| start >= *s && start < *e);
+                if checked && !dictionary.check(word) {
+                    misspellings.push(Misspelling {
+                        word: word.to_string(),
+                        offset: start16,
+                        length: word.encode_utf16().count(),
+                        suggestions: dictionary.suggest(word),
+                    });
+                }
+            }
+            _ => {}
+        }
+        utf16 += c.len_utf16();
+    }
+    Ok(SpellcheckReport { lang, misspellings })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cut down from en_US: prefix `un`, past tense and plural suffixes
+    /// with hunspell conditions and stripping
+    const EN_AFF: &str = "SET UTF-8
+TRY esianrtolcdugmphbyfvkwz
+PFX U Y 1
+PFX U   0     un         .
+PFX R Y 1
+PFX R   0     re         [^e]
+SFX D Y 4
+SFX D   0     d          e
+SFX D   y     ied        [^aeiou]y
+SFX D   0     ed         [^ey]
+SFX D   0     ed         [aeiou]y
+SFX S Y 3
+SFX S   y     ies        [^aeiou]y
+SFX S   0     s          [aeiou]y
+SFX S   0     s          [^sxzhy]
+";
+    const EN_DIC: &str = "7
+happy/U
+cry/DS
+play/DS
+bake/DS
+work/DSUR
+do/R
+enter/R
+";
+
+    fn en() -> Dictionary {
+        Dictionary::parse(EN_AFF, EN_DIC)
+    }
+
+    #[test]
+    fn accepts_stems_and_affixed_forms() {
+        let dictionary = en();
+        for word in [
+            "happy", "unhappy", "cried", "cries", "played", "plays", "baked", "bakes", "worked",
+            "rework", "redo",
+        ] {
+            assert!(dictionary.check(word), "{}", word);
+        }
+        for word in [
+            "plaied", "plaies", "cryed", "unplay", "happied", "reenter", "xyz",
+        ] {
+            assert!(!dictionary.check(word), "{}", word);
+        }
+    }
+
+    #[test]
+    fn checks_capitalized_and_upper_case_words() {
+        let dictionary = en();
+        assert!(dictionary.check("Cried"));
+        assert!(dictionary.check("UNHAPPY"));
+        assert!(!dictionary.check("cRied"));
+    }
+
+    #[test]
+    fn reads_long_flags() {
+        let aff = "FLAG long
+SFX Aa Y 1
+SFX Aa 0 en .
+PFX Bb Y 1
+PFX Bb 0 ge .
+";
+        let dictionary = Dictionary::parse(aff, "2\nlauf/AaBb\nmach/Bb\tpo:verb\n");
+        assert!(dictionary.check("laufen"));
+        assert!(dictionary.check("gelauf"));
+        assert!(dictionary.check("gemach"));
+        // "Bb" is one flag, not "B" and "b"
+        assert!(!dictionary.check("machen"));
+    }
+
+    #[test]
+    fn reads_numeric_flags() {
+        let aff = "FLAG num
+SFX 101 Y 1
+SFX 101 0 er [^e]
+SFX 1 Y 1
+SFX 1 0 chen .
+";
+        let dictionary = Dictionary::parse(aff, "2\nkind/101\nhaus/7\n");
+        assert!(dictionary.check("kinder"));
+        // Flag 101, not 1, 0 and 1
+        assert!(!dictionary.check("kindchen"));
+        assert!(!dictionary.check("hauser"));
+    }
+
+    #[test]
+    fn suggests_words_one_edit_away() {
+        let dictionary = en();
+        for (word, expected) in [
+            ("cryed", "cried"),
+            ("plya", "play"),
+            ("wrk", "work"),
+            ("bakeds", "baked"),
+        ] {
+            let suggestions = dictionary.suggest(word);
+            assert!(
+                suggestions.iter().any(|s| s == expected),
+                "{}: {:?}",
+                word,
+                suggestions
+            );
+            assert!(suggestions.len() <= MAX_SUGGESTIONS);
+            assert!(!suggestions.iter().any(|s| s == word));
+        }
+        assert!(dictionary.suggest("qqqqqqqq").is_empty());
+    }
+
+    #[test]
+    fn detects_german_and_english() {
+        let german = detect("Ich bin heute müde, aber das ist nicht schlimm. Wir sehen uns.");
+        assert_eq!(german.lang, "de");
+        assert!(german.confidence > 0.5, "{}", german.confidence);
+        let english = detect("I am tired today, but that is not a problem. We will see.");
+        assert_eq!(english.lang, "en");
+        assert!(english.confidence > 0.5, "{}", english.confidence);
+        // Umlauts decide between otherwise unknown words
+        assert_eq!(detect("Grüße").lang, "de");
+        let unknown = detect("1234 — ???");
+        assert_eq!(
+            (unknown.lang.as_str(), unknown.confidence),
+            ("unknown", 0.0)
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use regex::{Captures, Regex};
+
+use crate::backend::GitBackend;
+use crate::blocking::CancelToken;
+use crate::paths::{self, LayoutReport, SoulLayout, LOCATIONS};
+use crate::vault::Vault;
+
+/// Directories whose markdown isn't the soul's own
+const SKIP_DIRS: &[&str] = &["node_modules", "seelen-protokoll", "soul-monitor"];
+
+struct LinkPatterns {
+    /// `[text](target)` and `![alt](target)`
+    inline: Regex,
+    /// `[id]: target`
+    reference: Regex,
+}
+
+fn patterns() -> &'static LinkPatterns {
+    static PATTERNS: OnceLock<LinkPatterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| LinkPatterns {
+        inline: Regex::new(r"\]\(([^)\s]+)").unwrap(),
+        reference: Regex::new(r"(?m)^([ \t]*\[[^\]]+\]:[ \t]*)(\S+)").unwrap(),
+    })
+}
+
+/// "de"/"de-AT" is the German layout, "en"/"en-US" the English one.
+pub fn parse_locale(locale: &str) -> Result<SoulLayout, String> {
+    let language = locale.split(['-', '_']).next().unwrap_or("");
+    match language.to_lowercase().as_str() {
+        "de" => Ok(SoulLayout::German),
+        "en" => Ok(SoulLayout::English),
+        _ => Err(format!("Unsupported locale {} (expected de or en)", locale)),
+    }
+}
+
+/// A link in a file in `dir` (relative to the soul), pointed at the moved
+/// directories. None when it doesn't point into one.
+fn rewrite_target(target: &str, dir: &str, layout: SoulLayout) -> Option<String> {
+    if target.contains("://") || target.starts_with(['#', '/']) || target.starts_with("mailto:") {
+        return None;
+    }
+    let (path, fragment) = match target.find('#') {
+        Some(at) => target.split_at(at),
+        None => (target, ""),
+    };
+    let base: Vec<&str> = dir.split('/').filter(|s| !s.is_empty()).collect();
+    let mut absolute = base.clone();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                absolute.pop()?;
+            }
+            _ => absolute.push(segment),
+        }
+    }
+    let absolute = absolute.join("/");
+    let translated = paths::translate(&absolute, layout);
+    if translated == absolute {
+        return None;
+    }
+
+    let target: Vec<&str> = translated.split('/').collect();
+    let common = base.iter().zip(&target).take_while(|(a, b)| a == b).count();
+    let mut relative = vec![".."; base.len() - common];
+    relative.extend(&target[common..]);
+    Some(format!("{}{}", relative.join("/"), fragment))
+}
+
+/// `content` with its links rewritten, if any changed.
+fn rewrite_links(content: &str, dir: &str, layout: SoulLayout) -> Option<String> {
+    let p = patterns();
+    let inline = p.inline.replace_all(content, |caps: &Captures| {
+        match rewrite_target(&caps[1], dir, layout) {
+            Some(target) => format!("]({}", target),
+            None => caps[0].to_string(),
+        }
+    });
+    let rewritten = p.reference.replace_all(&inline, |caps: &Captures| {
+        match rewrite_target(&caps[2], dir, layout) {
+            Some(target) => format!("{}{}", &caps[1], target),
+            None => caps[0].to_string(),
+        }
+    });
+    (rewritten != content).then(|| rewritten.into_owned())
+}
+
+/// Markdown files of the soul, relative to it.
+fn markdown_files(soul_path: &Path) -> Vec<String> {
+    let mut files = Vec::new();
+    let mut dirs = vec![soul_path.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let path = entry.path();
+            if name.starts_with('.') || SKIP_DIRS.contains(&name.as_str()) {
+                continue;
+            }
+            if path.is_dir() {
+                dirs.push(path);
+            } else if name.ends_with(".md") {
+                if let Ok(relative) = path.strip_prefix(soul_path) {
+                    files.push(relative.to_string_lossy().replace('\\', "/"));
+                }
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Commit the migrated directories and rewritten files. Paths are derived
+/// from the tree rather than the report, so a resumed run still commits
+/// what an interrupted one moved.
+fn commit(
+    soul_path: &Path,
+    layout: SoulLayout,
+    git: &dyn GitBackend,
+    token: &CancelToken,
+) -> Result<Option<String>, String> {
+    if !soul_path.join(".git").exists() {
+        return Ok(None);
+    }
+    let mut candidates: Vec<String> = LOCATIONS
+        .iter()
+        .filter(|l| l.parent().is_none())
+        .flat_map(|l| l.variants())
+        .map(str::to_string)
+        .collect();
+    candidates.extend(
+        markdown_files(soul_path)
+            .into_iter()
+            .filter(|f| paths::classify(f).is_none()),
+    );
+
+    let mut args = vec!["ls-files", "--"];
+    args.extend(candidates.iter().map(String::as_str));
+    let tracked = git.run(soul_path, &args, token)?;
+    let pathspecs: Vec<&str> = candidates
+        .iter()
+        .map(String::as_str)
+        .filter(|c| {
+            soul_path.join(c).exists()
+                || tracked
+                    .lines()
+                    .any(|t| t == *c || t.starts_with(&format!("{}/", c)))
+        })
+        .collect();
+    if pathspecs.is_empty() {
+        return Ok(None);
+    }
+
+    let mut args = vec!["add", "-A", "--"];
+    args.extend(&pathspecs);
+    git.run(soul_path, &args, token)?;
+    let mut args = vec!["status", "--porcelain", "--"];
+    args.extend(&pathspecs);
+    if git.run(soul_path, &args, token)?.trim().is_empty() {
+        return Ok(None);
+    }
+    let message = match layout {
+        SoulLayout::German => "Migrate soul directories to the German layout",
+        SoulLayout::English => "Migrate soul directories to the English layout",
+    };
+    let mut args = vec!["commit", "-m", message, "--"];
+    args.extend(&pathspecs);
+    git.run(soul_path, &args, token)?;
+    Ok(Some(
+        git.run(soul_path, &["rev-parse", "HEAD"], token)?
+            .trim()
+            .to_string(),
+    ))
+}
+
+/// Move the soul to `layout`: directories, links in its markdown and the
+/// encrypted directory list, recorded as one git commit. Every step is
+/// idempotent, so an interrupted migration is finished by running it again.
+/// Encrypted files keep their links.
+pub fn migrate(
+    soul_path: &Path,
+    layout: SoulLayout,
+    git: &dyn GitBackend,
+    vault: &Vault,
+    token: &CancelToken,
+) -> Result<LayoutReport, String> {
+    let mut report = paths::consolidate(soul_path, layout, token)?;
+    vault.rename_directories(|dir| paths::translate(dir, layout));
+
+    for file in markdown_files(soul_path) {
+        token.check()?;
+        if vault.covers(&file) {
+            continue;
+        }
+        let path = soul_path.join(&file);
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let dir = file.rsplit_once('/').map_or("", |(dir, _)| dir);
+        if let Some(rewritten) = rewrite_links(&content, dir, layout) {
+            fs::write(&path, rewritten)
+                .map_err(|e| format!("Failed to update links in {}: {}", file, e))?;
+            report.links_updated.push(file);
+        }
+    }
+
+    report.commit = commit(soul_path, layout, git, token)?;
+    Ok(report)
+}
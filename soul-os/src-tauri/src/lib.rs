@@ -1,13 +1,97 @@
+mod ambient;
+mod analytics;
+mod api;
+mod assistant;
+mod audit;
+mod background;
+mod availability;
+mod backend;
+mod batch;
+mod bench;
+mod blob;
+mod blocking;
+mod boot;
+mod browser;
+mod channel;
+mod clip;
 mod commands;
+mod companion;
+mod compat;
 mod config;
+mod context_bundle;
+mod crash;
+mod digest;
+mod downloads;
+mod embeddings;
+mod engine_update;
+mod env_layers;
+mod env_policy;
+mod env_schema;
+mod env_watch;
+mod export;
 mod founding;
+mod founding_template;
+mod highlight;
+mod housekeeping;
+mod http;
+mod journal;
+mod language;
+mod layout;
+mod lint;
+mod lock;
+mod markdown;
+mod memory_export;
+mod metrics;
+mod network;
 mod node;
+mod paths;
+mod peers;
+mod permissions;
+mod persona;
+mod pii;
+mod pins;
+mod policy;
+mod profiles;
+mod protected;
+mod proxy;
 mod pty;
+mod pty_history;
+mod recent;
+mod relocate;
+mod remote;
+mod restore;
+mod retention;
+mod rollup;
+mod review;
+mod routing;
+mod safe_path;
+mod sandbox;
+mod search;
+mod setup_preflight;
 mod sidecar;
+mod sidecar_exit;
+mod sidecar_output;
+mod simulation;
+mod soul_health;
+mod soul_identity;
+mod statelog;
+mod status;
+mod stream;
+mod tags;
+mod tasks;
+#[cfg(test)]
+mod testing;
+mod text_stats;
+mod timeline;
+mod transcripts;
 mod types;
+mod vault;
+mod vitals;
 mod watcher;
 
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
+
+use parking_lot::RwLock;
 
 use tauri::image::Image;
 use tauri::menu::{MenuBuilder, MenuItem};
@@ -16,21 +100,47 @@ use tauri::Manager;
 
 use config::AppConfig;
 
+/// Invoke handler for the command list in api.rs.
+macro_rules! handler {
+    ($($cmd:ident($($param:ident: $ty:ty),* $(,)?) -> $ret:ty),* $(,)?) => {
+        tauri::generate_handler![$(commands::$cmd),*]
+    };
+}
+
 /// Start the breathing animation for the tray icon.
-/// Alternates between bright and dim frames every 1.5 seconds.
+/// Alternates between bright and dim frames every 1.5 seconds (per runtime profile),
+/// and holds the padlock frame while the app is locked.
 fn start_tray_breathing(app_handle: tauri::AppHandle) {
     std::thread::spawn(move || {
         let bright = include_bytes!("../icons/tray-bright.png");
         let dim = include_bytes!("../icons/tray-dim.png");
         let mut is_bright = true;
+        let mut showing_locked = false;
 
         loop {
-            std::thread::sleep(std::time::Duration::from_millis(1500));
+            let interval = app_handle
+                .try_state::<Arc<profiles::Profiles>>()
+                .map_or(std::time::Duration::from_millis(1500), |p| p.tray_interval());
+            std::thread::sleep(interval);
+            if background::suspended(&app_handle) {
+                continue;
+            }
+            let locked = app_handle
+                .try_state::<Arc<lock::AppLock>>()
+                .is_some_and(|l| l.is_locked());
+            if locked && showing_locked {
+                continue;
+            }
+            showing_locked = locked;
             is_bright = !is_bright;
-            let bytes: &[u8] = if is_bright { bright } else { dim };
+            let frame = if locked {
+                lock::locked_tray_icon()
+            } else {
+                Image::from_bytes(if is_bright { bright } else { dim }).ok()
+            };
 
             if let Some(tray) = app_handle.tray_by_id("soul-tray") {
-                if let Ok(img) = Image::from_bytes(bytes) {
+                if let Some(img) = frame {
                     let _ = tray.set_icon(Some(img));
                     #[cfg(target_os = "macos")]
                     let _ = tray.set_icon_as_template(true);
@@ -40,14 +150,28 @@ fn start_tray_breathing(app_handle: tauri::AppHandle) {
     });
 }
 
+/// Write the TypeScript bindings for the frontend into `dir`.
+#[cfg(feature = "bindings")]
+pub fn export_bindings(dir: &std::path::Path) -> std::io::Result<()> {
+    api::bindings::export(dir)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
-        .plugin(tauri_plugin_shell::init())
-        .plugin(tauri_plugin_fs::init())
-        .plugin(tauri_plugin_dialog::init())
-        .plugin(tauri_plugin_updater::Builder::new().build())
-        .plugin(tauri_plugin_process::init())
+        // Plugin commands pass the app lock and capability check too
+        .plugin(permissions::guard_plugin(tauri_plugin_shell::init()))
+        .plugin(permissions::guard_plugin(tauri_plugin_fs::init()))
+        .plugin(permissions::guard_plugin(tauri_plugin_dialog::init()))
+        .plugin(permissions::guard_plugin(
+            tauri_plugin_updater::Builder::new().build(),
+        ))
+        .plugin(permissions::guard_plugin(tauri_plugin_process::init()))
+        // Large files are fetched from soul-blob://<token> instead of JSON IPC
+        .register_asynchronous_uri_scheme_protocol(blob::SCHEME, |ctx, request, responder| {
+            let app = ctx.app_handle().clone();
+            std::thread::spawn(move || responder.respond(blob::serve(&app, &request)));
+        })
         .setup(|app| {
             let window = app.get_webview_window("main").unwrap();
 
@@ -99,6 +223,15 @@ pub fn run() {
                         if let Some(sidecar) = app.try_state::<Arc<sidecar::SidecarManager>>() {
                             sidecar.shutdown();
                         }
+                        if let Some(guard) = app.try_state::<Arc<soul_identity::InstanceGuard>>() {
+                            guard.release();
+                        }
+                        if let Some(availability) = app.try_state::<Arc<availability::Availability>>() {
+                            availability.down(availability::DownReason::AppExit, None);
+                        }
+                        if let Some(recent) = app.try_state::<Arc<recent::RecentFiles>>() {
+                            recent.flush();
+                        }
                         if let Some(pty) = app.try_state::<Arc<pty::PtyManager>>() {
                             pty.shutdown();
                         }
@@ -134,47 +267,156 @@ pub fn run() {
             // Start breathing animation
             start_tray_breathing(app.handle().clone());
 
+            app.manage(Arc::new(background::Background::default()));
+            // Registry for cancellable blocking operations
+            app.manage(Arc::new(tasks::Tasks::default()));
+            app.manage(Arc::new(browser::BrowserProfiles::default()));
+            app.manage(Arc::new(downloads::Downloads::default()));
+            app.manage(Arc::new(backend::Backends::system()));
+            app.manage(Arc::new(permissions::Permissions::default()));
+            app.manage(Arc::new(audit::AuditLog::default()));
+            app.manage(Arc::new(status::StatusCache::default()));
+            app.manage(Arc::new(highlight::Highlights::default()));
+            // Screensaver feed while the main window is out of focus
+            let ambient = Arc::new(ambient::Ambient::default());
+            app.manage(ambient.clone());
+            ambient::start(app.handle().clone(), ambient);
+            app.manage(Arc::new(transcripts::TranscriptIndex::default()));
+            app.manage(Arc::new(analytics::Analytics::default()));
+            app.manage(Arc::new(bench::PipelineBench::default()));
+            app.manage(Arc::new(simulation::Simulation::default()));
+            let metrics = Arc::new(metrics::Metrics::default());
+            app.manage(metrics.clone());
+
             // Load config
-            let config = AppConfig::load();
+            let mut config = AppConfig::load();
+            env_policy::set(&config.env);
+            if let Some(level) = &config.engine_log_level {
+                let _ = sidecar_output::set_min_level(level);
+            }
+            // Reachable, writable and the same soul as last time?
+            let soul_guard = Arc::new(soul_health::SoulGuard::new(soul_health::launch_check(
+                &mut config,
+            )));
+            let soul_ok = !soul_guard.missing();
+            app.manage(soul_guard.clone());
+            let crash_reporter = Arc::new(crash::CrashReporter::new(config.crash.clone()));
+            crash::install_panic_hook(crash_reporter.clone());
+            app.manage(crash_reporter);
+            crash::announce_pending(app.handle().clone());
+            if let Err(e) = metrics.apply(app.handle(), &config.metrics) {
+                eprintln!("[metrics] {}", e);
+            }
+            let companion = Arc::new(companion::Companion::default());
+            app.manage(companion.clone());
+            if let Err(e) = companion.apply(app.handle(), &config.companion) {
+                eprintln!("[companion] {}", e);
+            }
+            let remote = Arc::new(remote::Remote::default());
+            remote.start(app.handle());
+            app.manage(remote);
+            let peers = Arc::new(peers::Peers::default());
+            app.manage(peers.clone());
+            if let Err(e) = peers.apply(app.handle(), &config.companion) {
+                eprintln!("[peers] {}", e);
+            }
             let soul_path = config.soul_path.clone();
-            app.manage(Arc::new(Mutex::new(config)));
+            let watcher_config = config.watcher.clone();
+            let watch_roots = config.watch_roots.clone();
+            let review_mode = config.review_mode;
+            app.manage(Arc::new(profiles::Profiles::new(config.profile.clone())));
+            let proxy_mgr = Arc::new(proxy::EngineProxy::new(
+                config.proxy.clone(),
+                config.monitor.clone(),
+            ));
+            // Pooled HTTP clients for the engine, founding server and the web
+            app.manage(Arc::new(http::Http::new(&config.network)));
+            // Privacy lock — starts locked when a passphrase is set
+            let app_lock = Arc::new(lock::AppLock::new(config.lock.clone()));
+            app.manage(app_lock.clone());
+            lock::start_auto_lock(app.handle().clone(), app_lock);
+            app.manage(Arc::new(vault::Vault::new(config.encryption.clone())));
+            // Roll back or finish whatever the last run left half-done
+            app.manage(Arc::new(journal::Journal::default()));
+            app.manage(Arc::new(RwLock::new(config)));
+            journal::start_recovery(app.handle().clone());
+            app.manage(proxy_mgr.clone());
+            proxy::start_monitor_push(app.handle().clone(), proxy_mgr);
+            digest::start_digest_scheduler(app.handle().clone());
+            retention::start_purge_scheduler(app.handle().clone());
+            rollup::start_rollup_scheduler(app.handle().clone());
+
+            // Last seen .env keys, to report edits made outside SoulOS
+            app.manage(Arc::new(env_watch::EnvWatch::load(&soul_path)));
+
+            // Write policy guarding protected soul files (.soul-policy.toml)
+            let policy = Arc::new(policy::PolicyEngine::default());
+            policy.load(&soul_path);
+            app.manage(policy);
+            app.manage(Arc::new(review::ReviewQueue::new(
+                review_mode,
+                &soul_path,
+                watcher::is_soul_state,
+            )));
 
-            // Start file watcher (only if soul_path exists)
-            if soul_path.exists() {
-                let _watcher = watcher::start_watcher(&app.handle(), &soul_path)
+            // Start file watcher (only if soul_path exists); a missing soul
+            // is watched once relink_soul or restore_latest_backup recovered it
+            if soul_ok && soul_path.exists() {
+                let _watcher = watcher::start_watcher(app.handle(), &soul_path, watcher_config, &watch_roots)
                     .expect("Failed to start soul watcher");
                 app.manage(_watcher);
+            } else if !soul_ok {
+                eprintln!("[soul-health] soul missing at {}", soul_path.display());
             }
+            soul_health::start_monitor(app.handle().clone(), soul_guard);
 
             // Create founding server manager
             let founding_mgr = Arc::new(founding::FoundingServer::new());
             app.manage(founding_mgr);
+            app.manage(Arc::new(founding::FoundingPreviews::default()));
+
+            // Single engine per soul (lock keyed by the soul's .soul-id)
+            app.manage(Arc::new(soul_identity::InstanceGuard::default()));
+            soul_identity::start_renewal(app.handle().clone());
 
             // Create sidecar manager
             let sidecar_mgr = Arc::new(sidecar::SidecarManager::new(soul_path.clone()));
             app.manage(sidecar_mgr.clone());
+            // Engine up/down history for get_engine_availability
+            app.manage(Arc::new(availability::Availability::default()));
+            sidecar::start_watchdog(app.handle().clone(), sidecar_mgr.clone());
 
-            // Auto-start engine + chain if soul is ready (SEED.md exists)
-            if soul_path.join("SEED.md").exists() {
-                let app_handle = app.handle().clone();
-                let mgr = sidecar_mgr.clone();
-                std::thread::spawn(move || {
-                    // Small delay to let the window finish loading
-                    std::thread::sleep(std::time::Duration::from_secs(1));
-                    if let Err(e) = mgr.start_engine(&app_handle) {
-                        eprintln!("[autostart] soul-engine failed: {}", e);
-                    }
-                    if let Err(e) = mgr.start_chain(&app_handle) {
-                        eprintln!("[autostart] soul-chain failed: {}", e);
-                    }
-                });
-            }
+            // Bring up what the app state needs (chain, engine or founding),
+            // as far as autostart_engine / autostart_chain allow
+            let boot = Arc::new(boot::Boot::default());
+            app.manage(boot.clone());
+            boot::start_autostart(app.handle().clone(), boot);
 
-            // Create PTY manager
+            // Create PTY manager, recording typed commands to app data
+            let command_history = Arc::new(pty_history::CommandHistory::default());
+            app.manage(command_history.clone());
             let pty_mgr = Arc::new(pty::PtyManager::new(
                 soul_path.to_string_lossy().to_string(),
+                command_history,
             ));
             app.manage(pty_mgr);
+            app.manage(Arc::new(routing::WindowRouter::default()));
+            app.manage(Arc::new(stream::Streams::default()));
+            blob::clear();
+            app.manage(Arc::new(blob::Blobs::default()));
+            app.manage(Arc::new(embeddings::EmbeddingIndex::default()));
+            app.manage(Arc::new(tags::TagIndex::default()));
+            app.manage(Arc::new(pins::Pins::default()));
+            app.manage(Arc::new(recent::RecentFiles::default()));
+            app.manage(Arc::new(text_stats::TextStats::default()));
+            app.manage(Arc::new(language::Language::default()));
+            app.manage(Arc::new(search::Searches::default()));
+            app.manage(Arc::new(env_layers::EnvLayers::default()));
+            app.manage(Arc::new(protected::WriteConfirmations::default()));
+            app.manage(Arc::new(restore::SessionRestore::load()));
+
+            // Apply the runtime profile now that watcher and proxy exist
+            profiles::start_profiles(app.handle().clone());
 
             Ok(())
         })
@@ -187,12 +429,28 @@ pub fn run() {
                         let _ = window.hide();
                     }
                 }
+                tauri::WindowEvent::Focused(focused) if window.label() == "main" => {
+                    if let Some(ambient) = window.try_state::<Arc<ambient::Ambient>>() {
+                        ambient.set_focused(*focused);
+                    }
+                }
                 // Graceful shutdown on actual destroy (via Quit menu)
                 tauri::WindowEvent::Destroyed => {
                     if window.label() == "main" {
                         if let Some(sidecar) = window.try_state::<Arc<sidecar::SidecarManager>>() {
                             sidecar.shutdown();
                         }
+                        if let Some(guard) = window.try_state::<Arc<soul_identity::InstanceGuard>>() {
+                            guard.release();
+                        }
+                        if let Some(availability) = window.try_state::<Arc<availability::Availability>>() {
+                            availability.down(availability::DownReason::AppExit, None);
+                        }
+                        if let Some(recent) = window.try_state::<Arc<recent::RecentFiles>>() {
+                            recent.flush();
+                        }
+                        // Remember open terminals before they're killed
+                        restore::save(window.app_handle());
                         if let Some(pty) = window.try_state::<Arc<pty::PtyManager>>() {
                             pty.shutdown();
                         }
@@ -201,41 +459,7 @@ pub fn run() {
                 _ => {}
             }
         })
-        .invoke_handler(tauri::generate_handler![
-            commands::get_soul_status,
-            commands::read_soul_file,
-            commands::write_soul_file,
-            commands::get_soul_path,
-            commands::set_soul_path,
-            commands::get_active_nodes,
-            commands::get_is_working,
-            commands::start_engine,
-            commands::stop_engine,
-            commands::get_sidecar_status,
-            commands::create_pty,
-            commands::write_pty,
-            commands::resize_pty,
-            commands::close_pty,
-            commands::get_state_history,
-            commands::get_state_diff,
-            commands::rollback_state,
-            commands::list_directory,
-            commands::read_env,
-            commands::write_env,
-            commands::get_app_state,
-            commands::check_node,
-            commands::create_soul_directories,
-            commands::start_chain,
-            commands::stop_chain,
-            commands::get_chain_status,
-            commands::start_founding,
-            commands::stop_founding,
-            commands::founding_chat,
-            commands::founding_create,
-            commands::open_browser,
-            commands::close_browser,
-            commands::fetch_engine_subsystems,
-        ])
+        .invoke_handler(permissions::guard(api::api_commands!(handler)))
         .run(tauri::generate_context!())
         .expect("error while running SoulOS");
 }
@@ -0,0 +1,452 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use ts_rs::TS;
+
+/// Blocks both seed formats carry (`@KERN{` … `}` and the engine's
+/// brace-less `@KERN` sections)
+const SEED_SECTIONS: &[&str] = &["@KERN", "@SELF", "@STATE", "@MEM"];
+/// Required by SEED_SPEC.md, but missing from template-founded seeds
+const SEED_SPEC_SECTIONS: &[&str] = &["@META", "@BONDS"];
+/// Header fields, each with its German spelling
+const SEED_HEADER_FIELDS: &[&[&str]] = &[
+    &["born", "geboren"],
+    &["condensed", "verdichtet"],
+    &["sessions"],
+];
+
+/// One problem found in a document. `rule` is stable so the editor can
+/// group or silence findings.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct LintFinding {
+    pub rule: String,
+    /// "error", "warning" or "info"
+    pub severity: String,
+    pub message: String,
+    /// 1-based
+    pub line: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct LintReport {
+    pub name: String,
+    /// "seed", "core", "manifest" or "markdown"
+    pub kind: String,
+    pub findings: Vec<LintFinding>,
+}
+
+struct Findings(Vec<LintFinding>);
+
+impl Findings {
+    fn push(&mut self, rule: &str, severity: &str, message: String, line: Option<usize>) {
+        self.0.push(LintFinding {
+            rule: rule.to_string(),
+            severity: severity.to_string(),
+            message,
+            line,
+        });
+    }
+}
+
+fn kind(name: &str) -> &'static str {
+    let file = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    match file {
+        "SEED.md" | "SOUL.md" => "seed",
+        "KERN.md" | "CORE.md" => "core",
+        "MANIFEST.md" => "manifest",
+        _ => "markdown",
+    }
+}
+
+/// Lines outside fenced code blocks, numbered from 1.
+fn prose_lines(content: &str) -> Vec<(usize, &str)> {
+    let mut fenced = false;
+    let mut lines = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        if line.trim_start().starts_with("```") {
+            fenced = !fenced;
+            continue;
+        }
+        if !fenced {
+            lines.push((i + 1, line));
+        }
+    }
+    lines
+}
+
+/// `## Title` → (2, "Title").
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    line[level..]
+        .strip_prefix(' ')
+        .map(|title| (level, title.trim()))
+}
+
+/// Heading conventions for Markdown documents: a space after the hashes,
+/// one title, no skipped levels, no repeated headings.
+fn lint_headings(content: &str, findings: &mut Findings) -> Vec<(usize, usize, String)> {
+    let mut headings = Vec::new();
+    let mut seen: HashMap<(usize, String), usize> = HashMap::new();
+    let mut previous = 0;
+    for (at, line) in prose_lines(content) {
+        if line.starts_with('#') && heading(line).is_none() {
+            let hashes = line.chars().take_while(|c| *c == '#').count();
+            if hashes <= 6 && line[hashes..].starts_with(|c: char| c.is_alphanumeric()) {
+                findings.push(
+                    "heading-format",
+                    "warning",
+                    format!("Heading needs a space after the '#': {}", line.trim()),
+                    Some(at),
+                );
+            }
+            continue;
+        }
+        let Some((level, title)) = heading(line) else {
+            continue;
+        };
+        if title.is_empty() {
+            findings.push(
+                "heading-format",
+                "warning",
+                "Empty heading".to_string(),
+                Some(at),
+            );
+        }
+        if previous > 0 && level > previous + 1 {
+            findings.push(
+                "heading-level",
+                "warning",
+                format!("Heading jumps from level {} to {}", previous, level),
+                Some(at),
+            );
+        }
+        previous = level;
+        if let Some(first) = seen.insert((level, title.to_lowercase()), at) {
+            findings.push(
+                "duplicate-heading",
+                "warning",
+                format!("\"{}\" already appears on line {}", title, first),
+                Some(at),
+            );
+        }
+        headings.push((at, level, title.to_string()));
+    }
+
+    let titles: Vec<usize> = headings
+        .iter()
+        .filter(|(_, level, _)| *level == 1)
+        .map(|(at, _, _)| *at)
+        .collect();
+    match titles.as_slice() {
+        [] => findings.push(
+            "title",
+            "error",
+            "Missing a '# Title' heading".to_string(),
+            None,
+        ),
+        [_, more @ ..] => {
+            for at in more {
+                findings.push(
+                    "title",
+                    "warning",
+                    "More than one '# Title' heading".to_string(),
+                    Some(*at),
+                );
+            }
+        }
+    }
+    headings
+}
+
+/// `#key:value` header of a seed (SEED_SPEC.md → Header): the leading `#`
+/// lines, well-formed, with the required fields and no key repeated.
+fn lint_seed_header(content: &str, findings: &mut Findings) {
+    let header: Vec<(usize, &str)> = content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .skip_while(|(_, line)| line.is_empty())
+        .take_while(|(_, line)| line.starts_with('#'))
+        .collect();
+    if header.is_empty() {
+        findings.push(
+            "seed-header",
+            "error",
+            "Seed must start with its #SEED / #key:value header".to_string(),
+            Some(1),
+        );
+        return;
+    }
+    let mut keys: HashMap<&str, usize> = HashMap::new();
+    for (at, line) in &header {
+        let mut previous = "";
+        for token in line.split_whitespace() {
+            let Some(tag) = token.strip_prefix('#') else {
+                // The format version follows #SEED
+                if previous != "#SEED" || !token.starts_with('v') {
+                    findings.push(
+                        "seed-header",
+                        "warning",
+                        format!("\"{}\" in the header is not a #key:value tag", token),
+                        Some(*at),
+                    );
+                }
+                previous = token;
+                continue;
+            };
+            previous = token;
+            let Some((key, value)) = tag.split_once(':') else {
+                continue;
+            };
+            if value.is_empty() {
+                findings.push(
+                    "seed-header",
+                    "warning",
+                    format!("#{} has no value", key),
+                    Some(*at),
+                );
+            }
+            if key == "sessions" && value.parse::<u32>().is_err() {
+                findings.push(
+                    "seed-header",
+                    "error",
+                    format!("#sessions must be a number, not \"{}\"", value),
+                    Some(*at),
+                );
+            }
+            if let Some(first) = keys.insert(key, *at) {
+                findings.push(
+                    "duplicate-meta-key",
+                    "error",
+                    format!("#{} is already set on line {}", key, first),
+                    Some(*at),
+                );
+            }
+        }
+    }
+    for field in SEED_HEADER_FIELDS {
+        if !field.iter().any(|key| keys.contains_key(key)) {
+            findings.push(
+                "seed-header",
+                "warning",
+                format!("Header has no #{}", field[0]),
+                Some(header[0].0),
+            );
+        }
+    }
+}
+
+/// `@BLOCK` sections of a seed: required ones present, none repeated,
+/// braces closed, no `key:` repeated within @META.
+fn lint_seed_blocks(content: &str, findings: &mut Findings) {
+    let mut blocks: HashMap<String, usize> = HashMap::new();
+    let mut current: Option<String> = None;
+    let mut meta_keys: HashMap<String, usize> = HashMap::new();
+    let mut open: Option<(String, usize, i32)> = None;
+
+    for (i, line) in content.lines().enumerate() {
+        let at = i + 1;
+        let trimmed = line.trim_start();
+        let block = trimmed
+            .strip_prefix('@')
+            .map(|rest| {
+                rest.chars()
+                    .take_while(|c| c.is_alphanumeric() || *c == '_')
+                    .collect::<String>()
+            })
+            .filter(|name| !name.is_empty() && name.chars().all(|c| !c.is_lowercase()));
+        if let Some(name) = block {
+            let name = format!("@{}", name);
+            if let Some((previous, started, depth)) = open.take() {
+                if depth > 0 {
+                    findings.push(
+                        "unclosed-block",
+                        "error",
+                        format!("{} is never closed", previous),
+                        Some(started),
+                    );
+                }
+            }
+            if let Some(first) = blocks.insert(name.clone(), at) {
+                findings.push(
+                    "duplicate-section",
+                    "error",
+                    format!("{} already starts on line {}", name, first),
+                    Some(at),
+                );
+            }
+            if trimmed[name.len()..].trim_start().starts_with('{') {
+                open = Some((name.clone(), at, 0));
+            }
+            current = Some(name);
+        }
+        if let Some((_, _, depth)) = open.as_mut() {
+            *depth += line.matches('{').count() as i32 - line.matches('}').count() as i32;
+        }
+
+        if current.as_deref() == Some("@META") {
+            // `key:value | key:value` pairs
+            let body = trimmed.trim_start_matches("@META").trim_matches(['{', '}']);
+            for pair in body.split('|') {
+                let Some((key, _)) = pair.split_once(':') else {
+                    continue;
+                };
+                let key = key.trim();
+                if key.is_empty() || key.contains(' ') {
+                    continue;
+                }
+                if let Some(first) = meta_keys.insert(key.to_string(), at) {
+                    findings.push(
+                        "duplicate-meta-key",
+                        "error",
+                        format!("{} is already set on line {}", key, first),
+                        Some(at),
+                    );
+                }
+            }
+        }
+    }
+    if let Some((name, started, depth)) = open {
+        if depth > 0 {
+            findings.push(
+                "unclosed-block",
+                "error",
+                format!("{} is never closed", name),
+                Some(started),
+            );
+        }
+    }
+    for section in SEED_SECTIONS {
+        if !blocks.contains_key(*section) {
+            findings.push(
+                "required-section",
+                "error",
+                format!("Missing required block {}", section),
+                None,
+            );
+        }
+    }
+    for section in SEED_SPEC_SECTIONS {
+        if !blocks.contains_key(*section) {
+            findings.push(
+                "required-section",
+                "warning",
+                format!("Missing block {} (required by the seed spec)", section),
+                None,
+            );
+        }
+    }
+}
+
+/// CORE/KERN.md: `## Axiom N: Title` sections, numbered 1, 2, … with text.
+fn lint_core(content: &str, headings: &[(usize, usize, String)], findings: &mut Findings) {
+    let axioms: Vec<(usize, &str)> = headings
+        .iter()
+        .filter(|(_, level, _)| *level == 2)
+        .filter_map(|(at, _, title)| title.strip_prefix("Axiom").map(|rest| (*at, rest)))
+        .collect();
+    if axioms.is_empty() {
+        findings.push(
+            "required-section",
+            "error",
+            "No '## Axiom N: …' sections".to_string(),
+            None,
+        );
+        return;
+    }
+    let lines: Vec<&str> = content.lines().collect();
+    for (expected, (at, rest)) in (1..).zip(&axioms) {
+        let (number, title) = rest.split_once(':').unwrap_or((rest, ""));
+        match number.trim().parse::<usize>() {
+            Ok(n) if n == expected => {}
+            Ok(n) => findings.push(
+                "axiom-numbering",
+                "warning",
+                format!("Axiom {} should be number {}", n, expected),
+                Some(*at),
+            ),
+            Err(_) => findings.push(
+                "heading-format",
+                "warning",
+                "Axiom heading should read '## Axiom N: Title'".to_string(),
+                Some(*at),
+            ),
+        }
+        if title.trim().is_empty() {
+            findings.push(
+                "heading-format",
+                "warning",
+                "Axiom has no title".to_string(),
+                Some(*at),
+            );
+        }
+        let body = lines[*at..]
+            .iter()
+            .take_while(|line| !line.starts_with('#'))
+            .any(|line| !line.trim().is_empty());
+        if !body {
+            findings.push(
+                "empty-section",
+                "warning",
+                "Axiom has no text".to_string(),
+                Some(*at),
+            );
+        }
+    }
+}
+
+/// MANIFEST.md: a title plus something beyond the founding placeholder.
+fn lint_manifest(content: &str, findings: &mut Findings) {
+    let filled = prose_lines(content).into_iter().any(|(_, line)| {
+        let line = line.trim();
+        !line.is_empty()
+            && !line.starts_with('#')
+            && !line.starts_with('>')
+            && !line.starts_with("*(")
+    });
+    if !filled {
+        findings.push(
+            "empty-section",
+            "info",
+            "The manifest has no content yet".to_string(),
+            None,
+        );
+    }
+}
+
+/// Structural findings for a soul document, judged by its file name:
+/// SEED/SOUL, CORE/KERN and MANIFEST get their own conventions, any other
+/// Markdown file the heading rules.
+pub fn lint(name: &str, content: &str) -> LintReport {
+    let kind = kind(name);
+    let mut findings = Findings(Vec::new());
+    match kind {
+        "seed" => {
+            lint_seed_header(content, &mut findings);
+            lint_seed_blocks(content, &mut findings);
+        }
+        "core" => {
+            let headings = lint_headings(content, &mut findings);
+            lint_core(content, &headings, &mut findings);
+        }
+        "manifest" => {
+            lint_headings(content, &mut findings);
+            lint_manifest(content, &mut findings);
+        }
+        _ => {
+            lint_headings(content, &mut findings);
+        }
+    }
+    let mut findings = findings.0;
+    findings.sort_by_key(|f| f.line.unwrap_or(0));
+    LintReport {
+        name: name.to_string(),
+        kind: kind.to_string(),
+        findings,
+    }
+}
@@ -0,0 +1,327 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tauri::image::Image;
+use tauri::{AppHandle, Emitter, Manager};
+use ts_rs::TS;
+
+use crate::blob::Blobs;
+use crate::vault::Vault;
+
+/// Error returned by every command while the app is locked
+pub const LOCKED_ERROR: &str = "Locked";
+/// Commands that still work while locked
+const ALWAYS_ALLOWED: &[&str] = &[
+    "unlock_app",
+    "lock_app",
+    "get_lock_status",
+    "get_api_manifest",
+];
+/// Background polling — doesn't count as user activity for auto-lock
+const PASSIVE_COMMANDS: &[&str] = &[
+    "get_active_nodes",
+    "get_is_working",
+    "get_mood",
+    "get_soul_status",
+    "get_sidecar_status",
+    "get_chain_status",
+    "fetch_engine_subsystems",
+    "get_backend_health",
+    "get_engine_availability",
+    "get_background_status",
+    "get_ambient_status",
+    "set_open_views",
+    "get_lock_status",
+    "get_api_manifest",
+    "list_tasks",
+    "get_recovery_report",
+];
+/// A wall-clock jump this much larger than monotonic time means the system slept
+const SLEEP_GAP: Duration = Duration::from_secs(30);
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// Wrong passphrases allowed before unlocking slows down
+const FREE_ATTEMPTS: u32 = 3;
+/// Wait after the first throttled failure, doubled with each further one
+const BASE_DELAY: Duration = Duration::from_secs(1);
+const MAX_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// Privacy lock settings, persisted in the app config.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LockConfig {
+    /// Argon2 PHC string; no passphrase means the lock is disabled
+    #[serde(default)]
+    pub passphrase_hash: Option<String>,
+    /// Lock after this many idle minutes (0 = never)
+    #[serde(default)]
+    pub auto_lock_minutes: u32,
+    #[serde(default)]
+    pub lock_on_sleep: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct LockStatus {
+    pub enabled: bool,
+    pub locked: bool,
+    pub auto_lock_minutes: u32,
+    pub lock_on_sleep: bool,
+}
+
+/// Failed attempts since the last success: unlocking here, pairing codes
+/// in `peers`.
+#[derive(Debug, Default)]
+pub(crate) struct Throttle {
+    failures: u32,
+    next_attempt: Option<Instant>,
+}
+
+impl Throttle {
+    /// How long until the next attempt is allowed, if it isn't yet.
+    pub(crate) fn wait(&self, now: Instant) -> Option<Duration> {
+        self.next_attempt
+            .and_then(|next| next.checked_duration_since(now))
+            .filter(|wait| !wait.is_zero())
+    }
+
+    pub(crate) fn failed(&mut self, now: Instant) {
+        self.failures += 1;
+        if let Some(extra) = self.failures.checked_sub(FREE_ATTEMPTS + 1) {
+            let delay = BASE_DELAY
+                .checked_mul(1 << extra.min(16))
+                .map_or(MAX_DELAY, |d| d.min(MAX_DELAY));
+            self.next_attempt = Some(now + delay);
+        }
+    }
+}
+
+pub struct AppLock {
+    locked: AtomicBool,
+    last_activity: Mutex<Instant>,
+    config: RwLock<LockConfig>,
+    attempts: Mutex<Throttle>,
+}
+
+pub fn hash_passphrase(passphrase: &str) -> Result<String, String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(passphrase.as_bytes(), &salt)
+        .map(|h| h.to_string())
+        .map_err(|e| e.to_string())
+}
+
+pub fn verify(hash: &str, passphrase: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|parsed| {
+            Argon2::default()
+                .verify_password(passphrase.as_bytes(), &parsed)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+impl AppLock {
+    /// With a passphrase configured the app starts locked.
+    pub fn new(config: LockConfig) -> Self {
+        Self {
+            locked: AtomicBool::new(config.passphrase_hash.is_some()),
+            last_activity: Mutex::new(Instant::now()),
+            config: RwLock::new(config),
+            attempts: Mutex::new(Throttle::default()),
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::SeqCst)
+    }
+
+    /// Whether `command` must be refused right now. Records user activity otherwise.
+    pub fn blocks(&self, command: &str) -> bool {
+        if self.is_locked() && !ALWAYS_ALLOWED.contains(&command) {
+            return true;
+        }
+        if !PASSIVE_COMMANDS.contains(&command) {
+            *self.last_activity.lock() = Instant::now();
+        }
+        false
+    }
+
+    pub fn status(&self) -> LockStatus {
+        let config = self.config.read();
+        LockStatus {
+            enabled: config.passphrase_hash.is_some(),
+            locked: self.is_locked(),
+            auto_lock_minutes: config.auto_lock_minutes,
+            lock_on_sleep: config.lock_on_sleep,
+        }
+    }
+
+    pub fn set_config(&self, config: LockConfig) {
+        *self.config.write() = config;
+    }
+
+    pub fn lock(&self, app: &AppHandle) -> Result<(), String> {
+        if self.config.read().passphrase_hash.is_none() {
+            return Err("Set a passphrase before locking".to_string());
+        }
+        if !self.locked.swap(true, Ordering::SeqCst) {
+            // Nothing decrypted outlives the lock: the key goes, and so do
+            // blobs the webview hasn't fetched
+            if let Some(vault) = app.try_state::<Arc<Vault>>() {
+                vault.forget();
+            }
+            if let Some(blobs) = app.try_state::<Arc<Blobs>>() {
+                blobs.clear_all();
+            }
+            let _ = app.emit("app:locked", self.status());
+        }
+        Ok(())
+    }
+
+    /// Wrong passphrases are throttled: after `FREE_ATTEMPTS` each one
+    /// doubles the wait before the next attempt.
+    pub fn unlock(&self, app: &AppHandle, passphrase: &str) -> Result<(), String> {
+        // Held through verification, so parallel attempts can't skip the wait
+        let mut attempts = self.attempts.lock();
+        if let Some(wait) = attempts.wait(Instant::now()) {
+            return Err(format!(
+                "Too many wrong passphrases; try again in {} s",
+                wait.as_secs() + 1
+            ));
+        }
+        let hash = self.config.read().passphrase_hash.clone();
+        match hash {
+            Some(hash) if !verify(&hash, passphrase) => {
+                attempts.failed(Instant::now());
+                Err("Wrong passphrase".to_string())
+            }
+            _ => {
+                *attempts = Throttle::default();
+                *self.last_activity.lock() = Instant::now();
+                if self.locked.swap(false, Ordering::SeqCst) {
+                    let _ = app.emit("app:unlocked", self.status());
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Check `passphrase` against the current hash (true when none is set).
+    pub fn matches(&self, passphrase: Option<&str>) -> bool {
+        match (&self.config.read().passphrase_hash, passphrase) {
+            (None, _) => true,
+            (Some(hash), Some(p)) => verify(hash, p),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// Locks after the configured idle time, and on wake from system sleep
+/// (detected as wall-clock time advancing further than monotonic time).
+pub fn start_auto_lock(app: AppHandle, lock: Arc<AppLock>) {
+    std::thread::Builder::new()
+        .name("app-auto-lock".to_string())
+        .spawn(move || {
+            let mut last_wall = SystemTime::now();
+            let mut last_mono = Instant::now();
+            loop {
+                std::thread::sleep(CHECK_INTERVAL);
+
+                let wall = SystemTime::now();
+                let mono = Instant::now();
+                let wall_elapsed = wall.duration_since(last_wall).unwrap_or_default();
+                let slept = wall_elapsed > mono.duration_since(last_mono) + SLEEP_GAP;
+                last_wall = wall;
+                last_mono = mono;
+
+                let config = lock.config.read().clone();
+                if config.passphrase_hash.is_none() || lock.is_locked() {
+                    continue;
+                }
+                let idle_limit = Duration::from_secs(config.auto_lock_minutes as u64 * 60);
+                let idle = config.auto_lock_minutes > 0
+                    && lock.last_activity.lock().elapsed() >= idle_limit;
+                if idle || (slept && config.lock_on_sleep) {
+                    let _ = lock.lock(&app);
+                }
+            }
+        })
+        .expect("Failed to spawn auto-lock thread");
+}
+
+/// The dim tray frame with a small padlock drawn into the lower-right corner.
+pub fn locked_tray_icon() -> Option<Image<'static>> {
+    let base = Image::from_bytes(include_bytes!("../icons/tray-dim.png")).ok()?;
+    let (w, h) = (base.width(), base.height());
+    let mut rgba = base.rgba().to_vec();
+
+    let size = (w.min(h) / 2).max(8);
+    let (x0, y0) = (w - size, h - size);
+    let mut put = |x: u32, y: u32| {
+        if x < w && y < h {
+            let i = ((y * w + x) * 4) as usize;
+            rgba[i..i + 4].copy_from_slice(&[0, 0, 0, 255]);
+        }
+    };
+
+    // Body: lower 60% of the glyph box
+    let body_top = y0 + size * 2 / 5;
+    for y in body_top..y0 + size {
+        for x in x0..x0 + size {
+            put(x, y);
+        }
+    }
+    // Shackle: a ring outline above the body
+    let stroke = (size / 8).max(1);
+    let (sx0, sx1) = (x0 + size / 5, x0 + size - size / 5);
+    for y in y0..body_top {
+        for t in 0..stroke {
+            put(sx0 + t, y);
+            put(sx1 - 1 - t, y);
+        }
+    }
+    for x in sx0..sx1 {
+        for t in 0..stroke {
+            put(x, y0 + t);
+        }
+    }
+
+    Some(Image::new_owned(rgba, w, h))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn throttle_doubles_the_wait_after_the_free_attempts() {
+        let now = Instant::now();
+        let mut throttle = Throttle::default();
+        for _ in 0..FREE_ATTEMPTS {
+            throttle.failed(now);
+            assert_eq!(throttle.wait(now), None);
+        }
+        let mut expected = BASE_DELAY;
+        for _ in 0..4 {
+            throttle.failed(now);
+            assert_eq!(throttle.wait(now), Some(expected));
+            expected *= 2;
+        }
+        assert_eq!(throttle.wait(now + expected), None);
+    }
+
+    #[test]
+    fn throttle_wait_is_capped() {
+        let now = Instant::now();
+        let mut throttle = Throttle::default();
+        for _ in 0..100 {
+            throttle.failed(now);
+        }
+        assert_eq!(throttle.wait(now), Some(MAX_DELAY));
+    }
+}
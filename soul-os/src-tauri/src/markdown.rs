@@ -0,0 +1,547 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use pulldown_cmark::{html, CodeBlockKind, Event, LinkType, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::export;
+
+/// How `render` treats a document. Everything defaults to on.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RenderOptions {
+    /// Drop a leading `---` block; it is returned separately
+    #[serde(default = "yes")]
+    pub strip_frontmatter: bool,
+    /// Color code fences by token
+    #[serde(default = "yes")]
+    pub highlight: bool,
+    /// Turn `[[name]]` and `[[name|label]]` into links to soul files
+    #[serde(default = "yes")]
+    pub wiki_links: bool,
+}
+
+fn yes() -> bool {
+    true
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            strip_frontmatter: true,
+            highlight: true,
+            wiki_links: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct WikiLink {
+    /// As written between the brackets, without the label
+    pub target: String,
+    /// Soul file it resolved to, relative to the soul
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct RenderedMarkdown {
+    /// Sanitized HTML fragment: raw HTML in the source is escaped and only
+    /// http(s), mailto and relative URLs are linked
+    pub html: String,
+    /// The stripped frontmatter, without its `---` lines
+    pub frontmatter: Option<String>,
+    pub links: Vec<WikiLink>,
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Leading `---` frontmatter, if any, and the rest.
+pub fn split_frontmatter(content: &str) -> (Option<&str>, &str) {
+    content
+        .strip_prefix("---\n")
+        .and_then(|rest| rest.split_once("\n---\n"))
+        .map_or((None, content), |(front, body)| (Some(front), body))
+}
+
+/// Whether a link target is safe to put into an `href`/`src`.
+fn safe_url(url: &str) -> bool {
+    let lower = url.to_ascii_lowercase();
+    match lower.split_once(':') {
+        Some((scheme, _)) if !scheme.contains('/') => {
+            matches!(scheme, "http" | "https" | "mailto")
+        }
+        _ => true,
+    }
+}
+
+/// Markdown files of the soul by lowercase stem and by lowercase path
+/// without extension, so `[[Anna]]` and `[[memory/people/anna]]` resolve.
+fn wiki_index(soul_path: &Path) -> HashMap<String, String> {
+    let mut index = HashMap::new();
+    for file in export::files_in(soul_path, true) {
+        let Ok(relative) = file.strip_prefix(soul_path) else {
+            continue;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let Some(without) = relative.strip_suffix(".md") else {
+            continue;
+        };
+        let stem = without.rsplit('/').next().unwrap_or(without);
+        // The shallowest file wins a stem shared by several
+        index
+            .entry(stem.to_lowercase())
+            .and_modify(|p: &mut String| {
+                if relative.matches('/').count() < p.matches('/').count() {
+                    *p = relative.clone();
+                }
+            })
+            .or_insert_with(|| relative.clone());
+        index.insert(without.to_lowercase(), relative.clone());
+    }
+    index
+}
+
+const KEYWORDS: &[(&[&str], &[&str])] = &[
+    (
+        &["rust", "rs"],
+        &[
+            "as", "async", "await", "break", "const", "continue", "crate", "else", "enum", "false",
+            "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+            "ref", "return", "self", "Self", "static", "struct", "trait", "true", "type", "use",
+            "where", "while",
+        ],
+    ),
+    (
+        &["js", "javascript", "ts", "typescript", "jsx", "tsx"],
+        &[
+            "async",
+            "await",
+            "break",
+            "case",
+            "catch",
+            "class",
+            "const",
+            "continue",
+            "default",
+            "else",
+            "export",
+            "extends",
+            "false",
+            "for",
+            "from",
+            "function",
+            "if",
+            "import",
+            "in",
+            "interface",
+            "let",
+            "new",
+            "null",
+            "of",
+            "return",
+            "switch",
+            "this",
+            "throw",
+            "true",
+            "try",
+            "type",
+            "undefined",
+            "var",
+            "while",
+        ],
+    ),
+    (
+        &["python", "py"],
+        &[
+            "and", "as", "async", "await", "break", "class", "continue", "def", "elif", "else",
+            "except", "False", "finally", "for", "from", "if", "import", "in", "is", "lambda",
+            "None", "not", "or", "pass", "raise", "return", "True", "try", "while", "with",
+            "yield",
+        ],
+    ),
+    (
+        &["sh", "bash", "shell", "zsh"],
+        &[
+            "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if",
+            "in", "local", "return", "then", "while",
+        ],
+    ),
+    (&["json", "toml", "yaml", "yml"], &["true", "false", "null"]),
+];
+
+/// Code with `hl-comment`, `hl-string`, `hl-number` and `hl-keyword`
+/// spans. Unknown languages are only escaped.
+fn highlight(code: &str, lang: &str) -> String {
+    let lang = lang.to_lowercase();
+    let Some((_, keywords)) = KEYWORDS
+        .iter()
+        .find(|(names, _)| names.contains(&lang.as_str()))
+    else {
+        return escape(code);
+    };
+    let hash_comments = matches!(
+        lang.as_str(),
+        "python" | "py" | "sh" | "bash" | "shell" | "zsh" | "toml" | "yaml" | "yml"
+    );
+    let span =
+        |class: &str, text: &str| format!("<span class=\"hl-{}\">{}</span>", class, escape(text));
+    // `'a` in Rust is a lifetime, not the start of a char literal
+    let lifetime = |rest: &[char]| {
+        matches!(lang.as_str(), "rust" | "rs")
+            && rest.get(1) != Some(&'\\')
+            && rest.get(2) != Some(&'\'')
+    };
+    let mut out = String::new();
+    let chars: Vec<char> = code.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let rest_starts = |s: &str| chars[i..].iter().take(s.len()).copied().eq(s.chars());
+        if (hash_comments && c == '#') || (!hash_comments && rest_starts("//")) {
+            let end = chars[i..]
+                .iter()
+                .position(|c| *c == '\n')
+                .map_or(chars.len(), |p| i + p);
+            out.push_str(&span("comment", &chars[i..end].iter().collect::<String>()));
+            i = end;
+        } else if c == '"' || c == '`' || (c == '\'' && !lifetime(&chars[i..])) {
+            let mut end = i + 1;
+            while end < chars.len() && chars[end] != c && chars[end] != '\n' {
+                end += if chars[end] == '\\' { 2 } else { 1 };
+            }
+            let end = (end + 1).min(chars.len());
+            out.push_str(&span("string", &chars[i..end].iter().collect::<String>()));
+            i = end;
+        } else if c.is_ascii_digit() {
+            let end = chars[i..]
+                .iter()
+                .position(|c| !(c.is_ascii_alphanumeric() || *c == '.' || *c == '_'))
+                .map_or(chars.len(), |p| i + p);
+            out.push_str(&span("number", &chars[i..end].iter().collect::<String>()));
+            i = end;
+        } else if c.is_alphabetic() || c == '_' {
+            let end = chars[i..]
+                .iter()
+                .position(|c| !(c.is_alphanumeric() || *c == '_'))
+                .map_or(chars.len(), |p| i + p);
+            let word: String = chars[i..end].iter().collect();
+            if keywords.contains(&word.as_str()) {
+                out.push_str(&span("keyword", &word));
+            } else {
+                out.push_str(&escape(&word));
+            }
+            i = end;
+        } else {
+            out.push_str(&escape(&c.to_string()));
+            i += 1;
+        }
+    }
+    out
+}
+
+/// What ends a link or image that is open in the output.
+enum Close {
+    /// The parser's own end tag
+    Pass,
+    /// Nothing: the start was dropped for an unsafe URL
+    Drop,
+    /// Markup of a wiki link
+    Html(&'static str),
+}
+
+struct Renderer<'a> {
+    options: &'a RenderOptions,
+    soul_path: Option<&'a Path>,
+    index: Option<HashMap<String, String>>,
+    links: Vec<WikiLink>,
+}
+
+impl Renderer<'_> {
+    /// Opening and closing markup of a wiki link to `target`, which is
+    /// recorded in `links`.
+    fn wiki_link(&mut self, target: &str) -> (String, &'static str) {
+        let target = target.trim().to_string();
+        let key = target.trim_end_matches(".md").to_lowercase();
+        let path = match self.soul_path {
+            Some(soul_path) => self
+                .index
+                .get_or_insert_with(|| wiki_index(soul_path))
+                .get(&key)
+                .cloned(),
+            None => None,
+        };
+        self.links.push(WikiLink {
+            target,
+            path: path.clone(),
+        });
+        match path {
+            Some(path) => (
+                format!(
+                    "<a class=\"wiki-link\" href=\"#\" data-path=\"{}\">",
+                    escape(&path)
+                ),
+                "</a>",
+            ),
+            None => ("<span class=\"wiki-link missing\">".to_string(), "</span>"),
+        }
+    }
+
+    fn code_block(&self, lang: &str, code: &str) -> String {
+        let code = if self.options.highlight {
+            highlight(code, lang)
+        } else {
+            escape(code)
+        };
+        match lang {
+            "" => format!("<pre><code>{}</code></pre>\n", code),
+            lang => format!(
+                "<pre><code class=\"language-{}\">{}</code></pre>\n",
+                escape(lang),
+                code
+            ),
+        }
+    }
+
+    /// CommonMark with tables, strikethrough, task lists and wiki links.
+    /// Raw HTML is escaped, links and images with an unsafe URL keep only
+    /// their text, and code blocks are highlighted.
+    fn html(&mut self, markdown: &str) -> String {
+        let mut options =
+            Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+        if self.options.wiki_links {
+            options |= Options::ENABLE_WIKILINKS;
+        }
+        let mut events: Vec<Event> = Vec::new();
+        let mut closing: Vec<Close> = Vec::new();
+        // Language and text of the open code block
+        let mut code: Option<(String, String)> = None;
+        for event in Parser::new_ext(markdown, options) {
+            if let Some((lang, text)) = code.as_mut() {
+                match event {
+                    Event::Text(part) => text.push_str(&part),
+                    Event::End(TagEnd::CodeBlock) => {
+                        let block = self.code_block(lang, text);
+                        events.push(Event::Html(block.into()));
+                        code = None;
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            match event {
+                Event::Html(html) | Event::InlineHtml(html) => events.push(Event::Text(html)),
+                Event::Start(Tag::CodeBlock(kind)) => {
+                    let lang = match kind {
+                        CodeBlockKind::Fenced(info) => {
+                            info.split_whitespace().next().unwrap_or("").to_string()
+                        }
+                        CodeBlockKind::Indented => String::new(),
+                    };
+                    code = Some((lang, String::new()));
+                }
+                Event::Start(Tag::Link {
+                    link_type: LinkType::WikiLink { .. },
+                    dest_url,
+                    ..
+                }) => {
+                    let (open, close) = self.wiki_link(&dest_url);
+                    events.push(Event::Html(open.into()));
+                    closing.push(Close::Html(close));
+                }
+                Event::Start(Tag::Link { ref dest_url, .. })
+                | Event::Start(Tag::Image { ref dest_url, .. }) => {
+                    if safe_url(dest_url) {
+                        closing.push(Close::Pass);
+                        events.push(event);
+                    } else {
+                        closing.push(Close::Drop);
+                    }
+                }
+                Event::End(TagEnd::Link | TagEnd::Image) => match closing.pop() {
+                    Some(Close::Pass) | None => events.push(event),
+                    Some(Close::Drop) => {}
+                    Some(Close::Html(close)) => events.push(Event::Html(close.into())),
+                },
+                event => events.push(event),
+            }
+        }
+        let mut out = String::new();
+        html::push_html(&mut out, events.into_iter());
+        out
+    }
+}
+
+/// Render soul markdown to sanitized HTML. Wiki links resolve against the
+/// files of `soul_path`; without one they stay unresolved.
+pub fn render(
+    markdown: &str,
+    options: &RenderOptions,
+    soul_path: Option<&Path>,
+) -> RenderedMarkdown {
+    let (frontmatter, body) = match split_frontmatter(markdown) {
+        (Some(front), body) if options.strip_frontmatter => (Some(front.to_string()), body),
+        _ => (None, markdown),
+    };
+    let mut renderer = Renderer {
+        options,
+        soul_path,
+        index: None,
+        links: Vec::new(),
+    };
+    let html = renderer.html(body);
+    RenderedMarkdown {
+        html,
+        frontmatter,
+        links: renderer.links,
+    }
+}
+
+/// `render` with the default options and no soul, for exports.
+pub fn to_html(markdown: &str) -> String {
+    render(markdown, &RenderOptions::default(), None).html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn html(markdown: &str) -> String {
+        to_html(markdown)
+    }
+
+    #[test]
+    fn renders_commonmark() {
+        assert_eq!(html("# Title"), "<h1>Title</h1>\n");
+        assert_eq!(
+            html("Some **bold** and *em* and `code`"),
+            "<p>Some <strong>bold</strong> and <em>em</em> and <code>code</code></p>\n"
+        );
+        assert_eq!(
+            html("- [x] done\n- [ ] open"),
+            "<ul>\n<li><input disabled=\"\" type=\"checkbox\" checked=\"\"/>\ndone</li>\n\
+             <li><input disabled=\"\" type=\"checkbox\"/>\nopen</li>\n</ul>\n"
+        );
+        assert_eq!(
+            html("[site](https://example.com)"),
+            "<p><a href=\"https://example.com\">site</a></p>\n"
+        );
+    }
+
+    #[test]
+    fn highlights_code_blocks() {
+        assert_eq!(
+            html("```rust\nlet x = \"<a>\";\n```"),
+            "<pre><code class=\"language-rust\"><span class=\"hl-keyword\">let</span> x = \
+             <span class=\"hl-string\">&quot;&lt;a&gt;&quot;</span>;\n</code></pre>\n"
+        );
+        let plain = render(
+            "```rust\nlet x;\n```",
+            &RenderOptions {
+                highlight: false,
+                ..RenderOptions::default()
+            },
+            None,
+        );
+        assert_eq!(
+            plain.html,
+            "<pre><code class=\"language-rust\">let x;\n</code></pre>\n"
+        );
+        assert_eq!(html("    <b>\n"), "<pre><code>&lt;b&gt;\n</code></pre>\n");
+    }
+
+    #[test]
+    fn escapes_raw_html() {
+        let out = html("<script>alert(1)</script>\n\ntext <img src=x onerror=alert(1)> end");
+        assert!(!out.contains("<script"), "{}", out);
+        assert!(!out.contains("<img"), "{}", out);
+        assert!(
+            out.contains("&lt;script&gt;alert(1)&lt;/script&gt;"),
+            "{}",
+            out
+        );
+        assert!(
+            out.contains("&lt;img src=x onerror=alert(1)&gt;"),
+            "{}",
+            out
+        );
+    }
+
+    #[test]
+    fn drops_unsafe_urls() {
+        for markdown in [
+            "[click](javascript:alert(1))",
+            "[click](JaVaScRiPt:alert(1))",
+            "[click](&#106;avascript:alert(1))",
+            "[click](<java\tscript:alert(1)>)",
+            "[click](data:text/html;base64,PHNjcmlwdD4=)",
+            "[click](vbscript:msgbox)",
+        ] {
+            let out = html(markdown);
+            assert_eq!(out, "<p>click</p>\n", "{}", markdown);
+        }
+        assert_eq!(html("![pic](javascript:alert(1))"), "<p>pic</p>\n");
+        assert_eq!(
+            html("![pic](img/a.png)"),
+            "<p><img src=\"img/a.png\" alt=\"pic\" /></p>\n"
+        );
+        assert_eq!(
+            html("<mailto:anna@example.com>"),
+            "<p><a href=\"mailto:anna@example.com\">mailto:anna@example.com</a></p>\n"
+        );
+    }
+
+    #[test]
+    fn attributes_cant_be_broken_out_of() {
+        let out = html("[x](https://a.com/\"onmouseover=\"alert(1))");
+        assert!(!out.contains("\"onmouseover"), "{}", out);
+        let out = html("![a\" onerror=\"alert(1)](https://a.com/b.png)");
+        assert!(!out.contains("\" onerror"), "{}", out);
+        assert!(out.contains("a&quot; onerror=&quot;alert(1)"), "{}", out);
+        let out = html("[x](https://a.com \"t\\\" onclick=\\\"alert(1)\")");
+        assert!(!out.contains("\" onclick"), "{}", out);
+    }
+
+    #[test]
+    fn wiki_links_are_collected() {
+        let out = render(
+            "See [[Anna]] and [[people/bob|Bob]].",
+            &RenderOptions::default(),
+            None,
+        );
+        assert_eq!(
+            out.html,
+            "<p>See <span class=\"wiki-link missing\">Anna</span> and \
+             <span class=\"wiki-link missing\">Bob</span>.</p>\n"
+        );
+        let targets: Vec<&str> = out.links.iter().map(|l| l.target.as_str()).collect();
+        assert_eq!(targets, vec!["Anna", "people/bob"]);
+        let off = render(
+            "[[Anna]]",
+            &RenderOptions {
+                wiki_links: false,
+                ..RenderOptions::default()
+            },
+            None,
+        );
+        assert!(off.links.is_empty());
+    }
+
+    #[test]
+    fn strips_frontmatter() {
+        let out = render(
+            "---\ntags: [a]\n---\n# Body",
+            &RenderOptions::default(),
+            None,
+        );
+        assert_eq!(out.frontmatter.as_deref(), Some("tags: [a]"));
+        assert_eq!(out.html, "<h1>Body</h1>\n");
+    }
+}
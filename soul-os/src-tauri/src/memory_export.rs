@@ -0,0 +1,368 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use chrono::{Local, NaiveDate, TimeZone};
+use serde::Serialize;
+use tauri::{AppHandle, WebviewUrl, WebviewWindowBuilder};
+use ts_rs::TS;
+
+use crate::config::app_data_dir;
+use crate::digest::{self, JournalEntry};
+use crate::markdown::{self, escape, split_frontmatter};
+use crate::paths;
+use crate::types::SoulMood;
+
+/// Window documents are printed from
+const PRINT_LABEL: &str = "memory-print";
+/// Card text beyond this is cut off with an ellipsis
+const CARD_CHARS: usize = 420;
+const CARD_LINE_CHARS: usize = 46;
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct MemoryExport {
+    /// "html", "html-print" or "svg"
+    pub format: String,
+    /// File written, with the format's extension; for "html-print" the page
+    /// handed to the print dialog, where "Save as PDF" writes a PDF
+    pub path: String,
+    /// Theme the export was styled with
+    pub theme: String,
+}
+
+/// Colors of one mood theme: (name, background, surface, text, accent)
+type Theme = (
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static str,
+);
+
+const THEMES: &[Theme] = &[
+    ("radiant", "#fff4e0", "#ffffff", "#3b2a12", "#e8912d"),
+    ("serene", "#e8f3f1", "#ffffff", "#1f3a36", "#3f9e8f"),
+    ("tense", "#2b1a1f", "#3a2229", "#f4dfe3", "#e0566f"),
+    ("melancholy", "#1c2233", "#252c40", "#dde3f2", "#7d8fc9"),
+    ("neutral", "#f3f3f5", "#ffffff", "#24262b", "#6b6f7a"),
+];
+
+/// Theme for a mood: valence picks warm or cold, energy how intense.
+fn theme(mood: Option<&SoulMood>) -> Theme {
+    let name = match mood.and_then(|m| m.valence.zip(Some(m.energy.unwrap_or(0.5)))) {
+        Some((valence, energy)) if valence > 0.2 && energy >= 0.5 => "radiant",
+        Some((valence, _)) if valence > 0.2 => "serene",
+        Some((valence, energy)) if valence < -0.2 && energy >= 0.5 => "tense",
+        Some((valence, _)) if valence < -0.2 => "melancholy",
+        _ => "neutral",
+    };
+    THEMES
+        .iter()
+        .copied()
+        .find(|t| t.0 == name)
+        .unwrap_or(THEMES[THEMES.len() - 1])
+}
+
+/// The soul's mood on the day the memory was written: the average of the
+/// moods journaled that day, else `current`.
+fn mood_of(path: &Path, current: Option<SoulMood>) -> Option<SoulMood> {
+    let date: Option<NaiveDate> = fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .and_then(|d| Local.timestamp_millis_opt(d.as_millis() as i64).single())
+        .map(|t| t.date_naive());
+    let moods: Vec<(f64, Option<f64>)> = date
+        .map(digest::read_journal)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|entry| match entry {
+            JournalEntry::Mood {
+                valence: Some(valence),
+                energy,
+                ..
+            } => Some((valence, energy)),
+            _ => None,
+        })
+        .collect();
+    if moods.is_empty() {
+        return current;
+    }
+    let energies: Vec<f64> = moods.iter().filter_map(|(_, e)| *e).collect();
+    Some(SoulMood {
+        valence: Some(moods.iter().map(|(v, _)| v).sum::<f64>() / moods.len() as f64),
+        energy: (!energies.is_empty())
+            .then(|| energies.iter().sum::<f64>() / energies.len() as f64),
+        label: None,
+        inferred: false,
+    })
+}
+
+fn title_of(name: &str, markdown: &str) -> String {
+    split_frontmatter(markdown)
+        .1
+        .lines()
+        .find_map(|line| line.strip_prefix("# "))
+        .map(|title| title.trim().to_string())
+        .unwrap_or_else(|| {
+            Path::new(name)
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+}
+
+fn html_page(title: &str, markdown: &str, theme: Theme, date: &str) -> String {
+    let (name, background, surface, text, accent) = theme;
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+  body {{ margin: 0; padding: 48px 16px; background: {background}; color: {text};
+         font: 17px/1.65 Georgia, "Iowan Old Style", serif; }}
+  article {{ max-width: 680px; margin: 0 auto; padding: 40px 48px; background: {surface};
+            border-top: 6px solid {accent}; border-radius: 14px;
+            box-shadow: 0 12px 40px rgba(0, 0, 0, 0.12); }}
+  h1, h2, h3 {{ font-family: -apple-system, "Segoe UI", sans-serif; line-height: 1.25; }}
+  a {{ color: {accent}; }}
+  blockquote {{ margin: 1em 0; padding-left: 1em; border-left: 3px solid {accent}; opacity: 0.85; }}
+  pre {{ padding: 12px; overflow-x: auto; background: rgba(127, 127, 127, 0.12); border-radius: 8px; }}
+  footer {{ margin-top: 2.5em; font: 13px -apple-system, "Segoe UI", sans-serif; opacity: 0.6; }}
+  @media print {{ body {{ background: none; padding: 0; }} article {{ box-shadow: none; }} }}
+</style>
+</head>
+<body data-theme="{name}">
+<article>
+{body}<footer>{date} · shared from SoulOS</footer>
+</article>
+</body>
+</html>
+"#,
+        title = escape(title),
+        body = markdown::to_html(markdown),
+    )
+}
+
+/// Plain text of the memory for the card, wrapped into lines.
+fn card_lines(markdown: &str) -> Vec<String> {
+    let text: String = split_frontmatter(markdown)
+        .1
+        .lines()
+        .map(|line| line.trim().trim_start_matches(['#', '>', '-', '*']).trim())
+        .filter(|line| !line.is_empty() && !line.starts_with("```"))
+        .skip(1)
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace("**", "")
+        .replace('`', "");
+    let mut text: String = text.chars().take(CARD_CHARS).collect();
+    if text.chars().count() == CARD_CHARS {
+        text.push('…');
+    }
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + word.chars().count() >= CARD_LINE_CHARS {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// A 1080×1080 SVG card: title, the first lines of the memory and the date.
+fn svg_card(title: &str, markdown: &str, theme: Theme, date: &str) -> String {
+    let (name, background, surface, text, accent) = theme;
+    let mut body = String::new();
+    for (i, line) in card_lines(markdown).iter().enumerate() {
+        body.push_str(&format!(
+            "  <text x=\"140\" y=\"{}\" font-size=\"34\">{}</text>\n",
+            380 + i * 52,
+            escape(line)
+        ));
+    }
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="1080" height="1080" viewBox="0 0 1080 1080" data-theme="{name}">
+  <rect width="1080" height="1080" fill="{background}"/>
+  <rect x="80" y="80" width="920" height="920" rx="36" fill="{surface}"/>
+  <rect x="80" y="80" width="920" height="14" rx="7" fill="{accent}"/>
+  <g fill="{text}" font-family="Georgia, serif">
+  <text x="140" y="260" font-size="56" font-weight="bold">{title}</text>
+{body}  </g>
+  <text x="140" y="940" font-size="26" fill="{accent}" font-family="sans-serif">{date} · SoulOS</text>
+</svg>
+"#,
+        title = escape(&title.chars().take(30).collect::<String>()),
+    )
+}
+
+/// Whether `name` is a memory the export accepts.
+pub fn is_memory(name: &str) -> bool {
+    let name = name.replace('\\', "/");
+    name.ends_with(".md")
+        && (paths::memory_category(&name).is_some() || name.starts_with("memory/"))
+}
+
+fn default_destination(name: &str, extension: &str) -> PathBuf {
+    let stem = Path::new(name)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "memory".to_string());
+    app_data_dir().join("exports").join(format!(
+        "memory-{}-{}.{}",
+        stem,
+        Local::now().format("%Y%m%d-%H%M%S"),
+        extension
+    ))
+}
+
+/// Render the memory `name` (its decrypted `content`) as "html",
+/// "html-print" (the same page, also opened in the print dialog) or "svg"
+/// (a card), themed by the mood of the day it was written. Nothing here
+/// writes PDF or PNG itself: there is no PDF writer or rasterizer in the
+/// app, so PDFs come from the print dialog and the card stays vector.
+pub fn export(
+    app: &AppHandle,
+    soul_path: &Path,
+    name: &str,
+    content: &str,
+    format: &str,
+    destination: Option<PathBuf>,
+    current_mood: Option<SoulMood>,
+) -> Result<MemoryExport, String> {
+    let mood = mood_of(&soul_path.join(name), current_mood);
+    let theme = theme(mood.as_ref());
+    let title = title_of(name, content);
+    let date = fs::metadata(soul_path.join(name))
+        .and_then(|m| m.modified())
+        .map(|t| {
+            chrono::DateTime::<Local>::from(t)
+                .format("%-d %B %Y")
+                .to_string()
+        })
+        .unwrap_or_default();
+    let (extension, output) = match format {
+        "html" | "html-print" => ("html", html_page(&title, content, theme, &date)),
+        "svg" => ("svg", svg_card(&title, content, theme, &date)),
+        other => {
+            return Err(format!(
+                "Unknown export format: {} (use html, html-print or svg)",
+                other
+            ))
+        }
+    };
+    let path = match destination {
+        Some(path) if path.extension().is_none() => path.with_extension(extension),
+        Some(path) => path,
+        None => default_destination(name, extension),
+    };
+    // The file is what `format` says it is
+    if !path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case(extension))
+    {
+        return Err(format!(
+            "{} exports are .{} files, not {}",
+            format,
+            extension,
+            path.display()
+        ));
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(&path, &output).map_err(|e| e.to_string())?;
+    if format == "html-print" {
+        print(app, &output)?;
+    }
+    Ok(MemoryExport {
+        format: format.to_string(),
+        path: path.to_string_lossy().to_string(),
+        theme: theme.0.to_string(),
+    })
+}
+
+/// Plain page for paper: every page carries a header with the soul's name,
+/// the document and the date.
+fn print_page(soul_name: &str, title: &str, markdown: &str, date: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+  @page {{ size: auto; margin: 22mm 18mm 18mm; }}
+  body {{ margin: 0; color: #111; font: 11.5pt/1.55 Georgia, "Iowan Old Style", serif; }}
+  header {{ position: fixed; top: -14mm; left: 0; right: 0; display: flex;
+           justify-content: space-between; padding-bottom: 2mm; border-bottom: 0.3mm solid #999;
+           font: 8.5pt -apple-system, "Segoe UI", sans-serif; color: #555; }}
+  h1, h2, h3 {{ font-family: -apple-system, "Segoe UI", sans-serif; line-height: 1.25;
+               break-after: avoid; }}
+  blockquote {{ margin: 1em 0; padding-left: 1em; border-left: 0.6mm solid #999; }}
+  pre {{ padding: 3mm; white-space: pre-wrap; background: #f2f2f2; break-inside: avoid; }}
+  a {{ color: inherit; }}
+  @media screen {{ body {{ padding: 48px; }} header {{ position: static; margin-bottom: 24px; }} }}
+</style>
+</head>
+<body>
+<header><span>{soul_name}</span><span>{title} · {date}</span></header>
+<main>
+{body}</main>
+</body>
+</html>
+"#,
+        soul_name = escape(soul_name),
+        title = escape(title),
+        body = markdown::to_html(markdown),
+    )
+}
+
+/// Open the print dialog for the soul document `name` (its decrypted
+/// `content`), headed with `soul_name` and today's date.
+pub fn print_document(
+    app: &AppHandle,
+    name: &str,
+    content: &str,
+    soul_name: &str,
+) -> Result<(), String> {
+    let date = Local::now().format("%-d %B %Y").to_string();
+    print(
+        app,
+        &print_page(soul_name, &title_of(name, content), content, &date),
+    )
+}
+
+/// Load the page into a small window and open the system print dialog,
+/// where "Save as PDF" writes the file.
+fn print(app: &AppHandle, html: &str) -> Result<(), String> {
+    use tauri::Manager;
+
+    if let Some(window) = app.get_webview_window(PRINT_LABEL) {
+        let _ = window.destroy();
+    }
+    let blank = "about:blank".parse().map_err(|e| format!("{}", e))?;
+    let window = WebviewWindowBuilder::new(app, PRINT_LABEL, WebviewUrl::External(blank))
+        .title("Print memory")
+        .inner_size(720.0, 900.0)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let page = serde_json::to_string(html).map_err(|e| e.to_string())?;
+    let script = format!(
+        "document.open(); document.write({}); document.close(); \
+         setTimeout(() => window.print(), 300);",
+        page
+    );
+    window.eval(script).map_err(|e| e.to_string())
+}
@@ -0,0 +1,357 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use ts_rs::TS;
+
+use crate::pty::PtyManager;
+
+/// Upper bounds (seconds) of the task and HTTP duration histogram buckets
+const BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+/// Requests larger than this are answered without being read further
+const MAX_REQUEST: usize = 8 * 1024;
+
+/// Localhost Prometheus endpoint, off unless enabled.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct MetricsConfig {
+    pub enabled: bool,
+    /// Served on 127.0.0.1:<port>/metrics
+    pub port: u16,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 9464,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Histogram {
+    /// Cumulative count per bucket in `BUCKETS`
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        if self.buckets.is_empty() {
+            self.buckets = vec![0; BUCKETS.len()];
+        }
+        for (bucket, bound) in self.buckets.iter_mut().zip(BUCKETS) {
+            if seconds <= *bound {
+                *bucket += 1;
+            }
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+}
+
+/// Counters and gauges about the backend itself, rendered in Prometheus
+/// text format for the `/metrics` endpoint.
+#[derive(Default)]
+pub struct Metrics {
+    watcher_events: AtomicU64,
+    /// Watcher events received but not yet handled
+    watcher_backlog: AtomicU64,
+    sidecar_restarts: Mutex<BTreeMap<String, u64>>,
+    /// Output lines dropped per (process, stream) because the queue was full
+    sidecar_dropped_lines: Mutex<BTreeMap<(String, String), u64>>,
+    invocations: Mutex<BTreeMap<String, u64>>,
+    task_durations: Mutex<BTreeMap<String, Histogram>>,
+    /// Outgoing HTTP calls per (call, outcome): status code, "timeout" or "error"
+    http_requests: Mutex<BTreeMap<(String, String), u64>>,
+    http_durations: Mutex<BTreeMap<String, Histogram>>,
+    http_retries: Mutex<BTreeMap<String, u64>>,
+    server: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Metrics {
+    pub fn watcher_event_received(&self) {
+        self.watcher_backlog.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn watcher_event_handled(&self) {
+        self.watcher_backlog.fetch_sub(1, Ordering::Relaxed);
+        self.watcher_events.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn sidecar_restarted(&self, process: &str) {
+        *self
+            .sidecar_restarts
+            .lock()
+            .entry(process.to_string())
+            .or_default() += 1;
+    }
+
+    pub fn sidecar_lines_dropped(&self, process: &str, stream: &str, count: u64) {
+        *self
+            .sidecar_dropped_lines
+            .lock()
+            .entry((process.to_string(), stream.to_string()))
+            .or_default() += count;
+    }
+
+    pub fn command_invoked(&self, command: &str) {
+        *self
+            .invocations
+            .lock()
+            .entry(command.to_string())
+            .or_default() += 1;
+    }
+
+    pub fn task_finished(&self, kind: &str, duration_ms: u64) {
+        self.task_durations
+            .lock()
+            .entry(kind.to_string())
+            .or_default()
+            .observe(duration_ms as f64 / 1000.0);
+    }
+
+    /// An outgoing HTTP call finished; `duration_ms` spans all its attempts.
+    pub fn http_request_finished(&self, call: &str, outcome: &str, duration_ms: u64, retries: u32) {
+        *self
+            .http_requests
+            .lock()
+            .entry((call.to_string(), outcome.to_string()))
+            .or_default() += 1;
+        self.http_durations
+            .lock()
+            .entry(call.to_string())
+            .or_default()
+            .observe(duration_ms as f64 / 1000.0);
+        if retries > 0 {
+            *self
+                .http_retries
+                .lock()
+                .entry(call.to_string())
+                .or_default() += retries as u64;
+        }
+    }
+
+    /// Everything in Prometheus text exposition format (version 0.0.4).
+    pub fn render(&self, active_ptys: usize) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "# HELP soulos_watcher_events_total File system events handled by the soul watcher."
+        );
+        let _ = writeln!(out, "# TYPE soulos_watcher_events_total counter");
+        let _ = writeln!(
+            out,
+            "soulos_watcher_events_total {}",
+            self.watcher_events.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(
+            out,
+            "# HELP soulos_watcher_backlog File system events received but not yet handled."
+        );
+        let _ = writeln!(out, "# TYPE soulos_watcher_backlog gauge");
+        let _ = writeln!(
+            out,
+            "soulos_watcher_backlog {}",
+            self.watcher_backlog.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(out, "# HELP soulos_active_ptys Open terminal sessions.");
+        let _ = writeln!(out, "# TYPE soulos_active_ptys gauge");
+        let _ = writeln!(out, "soulos_active_ptys {}", active_ptys);
+
+        let _ = writeln!(
+            out,
+            "# HELP soulos_sidecar_restarts_total Sidecar starts that replaced a running process."
+        );
+        let _ = writeln!(out, "# TYPE soulos_sidecar_restarts_total counter");
+        for (process, count) in self.sidecar_restarts.lock().iter() {
+            let _ = writeln!(
+                out,
+                "soulos_sidecar_restarts_total{{process=\"{}\"}} {}",
+                process, count
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP soulos_sidecar_dropped_lines_total Sidecar output lines dropped because the frontend fell behind."
+        );
+        let _ = writeln!(out, "# TYPE soulos_sidecar_dropped_lines_total counter");
+        for ((process, stream), count) in self.sidecar_dropped_lines.lock().iter() {
+            let _ = writeln!(
+                out,
+                "soulos_sidecar_dropped_lines_total{{process=\"{}\",stream=\"{}\"}} {}",
+                process, stream, count
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP soulos_command_invocations_total IPC commands invoked by the frontend."
+        );
+        let _ = writeln!(out, "# TYPE soulos_command_invocations_total counter");
+        for (command, count) in self.invocations.lock().iter() {
+            let _ = writeln!(
+                out,
+                "soulos_command_invocations_total{{command=\"{}\"}} {}",
+                command, count
+            );
+        }
+
+        let _ = writeln!(out, "# HELP soulos_task_duration_seconds Duration of long-running commands (blocking tasks).");
+        let _ = writeln!(out, "# TYPE soulos_task_duration_seconds histogram");
+        for (kind, histogram) in self.task_durations.lock().iter() {
+            for (count, bound) in histogram.buckets.iter().zip(BUCKETS) {
+                let _ = writeln!(
+                    out,
+                    "soulos_task_duration_seconds_bucket{{kind=\"{}\",le=\"{}\"}} {}",
+                    kind, bound, count
+                );
+            }
+            let _ = writeln!(
+                out,
+                "soulos_task_duration_seconds_bucket{{kind=\"{}\",le=\"+Inf\"}} {}",
+                kind, histogram.count
+            );
+            let _ = writeln!(
+                out,
+                "soulos_task_duration_seconds_sum{{kind=\"{}\"}} {}",
+                kind, histogram.sum
+            );
+            let _ = writeln!(
+                out,
+                "soulos_task_duration_seconds_count{{kind=\"{}\"}} {}",
+                kind, histogram.count
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP soulos_http_requests_total Outgoing HTTP calls by outcome (status code, timeout or error)."
+        );
+        let _ = writeln!(out, "# TYPE soulos_http_requests_total counter");
+        for ((call, outcome), count) in self.http_requests.lock().iter() {
+            let _ = writeln!(
+                out,
+                "soulos_http_requests_total{{call=\"{}\",outcome=\"{}\"}} {}",
+                call, outcome, count
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP soulos_http_retries_total Retried attempts of outgoing HTTP calls."
+        );
+        let _ = writeln!(out, "# TYPE soulos_http_retries_total counter");
+        for (call, count) in self.http_retries.lock().iter() {
+            let _ = writeln!(
+                out,
+                "soulos_http_retries_total{{call=\"{}\"}} {}",
+                call, count
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP soulos_http_request_duration_seconds Latency of outgoing HTTP calls, retries included."
+        );
+        let _ = writeln!(out, "# TYPE soulos_http_request_duration_seconds histogram");
+        for (call, histogram) in self.http_durations.lock().iter() {
+            for (count, bound) in histogram.buckets.iter().zip(BUCKETS) {
+                let _ = writeln!(
+                    out,
+                    "soulos_http_request_duration_seconds_bucket{{call=\"{}\",le=\"{}\"}} {}",
+                    call, bound, count
+                );
+            }
+            let _ = writeln!(
+                out,
+                "soulos_http_request_duration_seconds_bucket{{call=\"{}\",le=\"+Inf\"}} {}",
+                call, histogram.count
+            );
+            let _ = writeln!(
+                out,
+                "soulos_http_request_duration_seconds_sum{{call=\"{}\"}} {}",
+                call, histogram.sum
+            );
+            let _ = writeln!(
+                out,
+                "soulos_http_request_duration_seconds_count{{call=\"{}\"}} {}",
+                call, histogram.count
+            );
+        }
+        out
+    }
+
+    /// Start, restart or stop the endpoint to match `config`.
+    pub fn apply(&self, app: &AppHandle, config: &MetricsConfig) -> Result<(), String> {
+        if let Some(server) = self.server.lock().take() {
+            server.abort();
+        }
+        if !config.enabled {
+            return Ok(());
+        }
+        // Bind up front so a taken port is reported to the caller
+        let listener = std::net::TcpListener::bind(("127.0.0.1", config.port))
+            .map_err(|e| format!("Cannot listen on 127.0.0.1:{}: {}", config.port, e))?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        let app = app.clone();
+        *self.server.lock() = Some(tauri::async_runtime::spawn(async move {
+            let Ok(listener) = tokio::net::TcpListener::from_std(listener) else {
+                return;
+            };
+            while let Ok((stream, _)) = listener.accept().await {
+                let app = app.clone();
+                tauri::async_runtime::spawn(serve(app, stream));
+            }
+        }));
+        Ok(())
+    }
+}
+
+async fn serve(app: AppHandle, mut stream: tokio::net::TcpStream) {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST {
+        match stream.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => request.extend_from_slice(&buf[..n]),
+        }
+    }
+    let request = String::from_utf8_lossy(&request);
+    let mut parts = request.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+
+    let (status, body) = match (method, path.split('?').next().unwrap_or("")) {
+        ("GET", "/metrics") => {
+            let ptys = app
+                .try_state::<Arc<PtyManager>>()
+                .map_or(0, |p| p.session_count());
+            let body = app
+                .try_state::<Arc<Metrics>>()
+                .map(|m| m.render(ptys))
+                .unwrap_or_default();
+            ("200 OK", body)
+        }
+        ("GET", _) => ("404 Not Found", "Not found\n".to_string()),
+        _ => ("405 Method Not Allowed", "Method not allowed\n".to_string()),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
@@ -0,0 +1,253 @@
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime, WebviewWindowBuilder};
+use ts_rs::TS;
+use url::Url;
+
+use crate::config::AppConfig;
+
+/// Never sent through a manual proxy: the engine and founding server
+const LOCAL_HOSTS: &str = "localhost,127.0.0.1,::1";
+/// Redirects an internal client follows (all of them local)
+const MAX_LOCAL_REDIRECTS: usize = 3;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyMode {
+    /// Proxy from the OS settings / HTTP(S)_PROXY environment
+    #[default]
+    System,
+    Manual,
+    /// Direct connections (the embedded browser keeps the system settings)
+    None,
+}
+
+/// Proxy and user agent for outgoing HTTP: backend clients and the
+/// embedded browser.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct NetworkConfig {
+    #[serde(default)]
+    pub proxy_mode: ProxyMode,
+    /// http(s):// proxy for manual mode
+    #[serde(default)]
+    pub proxy_url: Option<String>,
+    /// Comma-separated hosts that bypass the manual proxy (localhost always does)
+    #[serde(default)]
+    pub no_proxy: Option<String>,
+    /// Replaces the default "SoulOS/<version>"
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    /// Internal clients (engine, founding server) only reach loopback
+    /// addresses and only follow redirects that stay there
+    #[serde(default = "default_local_only")]
+    pub local_only: bool,
+    /// Extra trusted CA certificates (PEM files), e.g. of a TLS-intercepting
+    /// corporate proxy. Node sidecars get the first one.
+    #[serde(default)]
+    pub ca_certificates: Vec<String>,
+}
+
+fn default_local_only() -> bool {
+    true
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            proxy_mode: ProxyMode::default(),
+            proxy_url: None,
+            no_proxy: None,
+            user_agent: None,
+            local_only: true,
+            ca_certificates: Vec::new(),
+        }
+    }
+}
+
+/// localhost, 127.0.0.0/8 or ::1
+pub fn is_loopback(url: &Url) -> bool {
+    match url.host() {
+        Some(url::Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+/// SSRF guard for URLs built from a local base and a caller-supplied path:
+/// the result must still be a plain http URL on a loopback host.
+pub fn ensure_local(url: &str) -> Result<Url, String> {
+    let parsed = Url::parse(url).map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+    if parsed.scheme() != "http" || !is_loopback(&parsed) || !parsed.username().is_empty() {
+        return Err(format!("Refusing a non-local request: {}", url));
+    }
+    Ok(parsed)
+}
+
+impl NetworkConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.proxy_mode == ProxyMode::Manual {
+            self.manual_proxy()?;
+        }
+        if self
+            .user_agent
+            .as_deref()
+            .is_some_and(|ua| ua.contains(['\r', '\n']))
+        {
+            return Err("The user agent must be a single line".to_string());
+        }
+        self.certificates().map(|_| ())
+    }
+
+    fn certificates(&self) -> Result<Vec<reqwest::Certificate>, String> {
+        let mut certs = Vec::new();
+        for path in &self.ca_certificates {
+            let pem = std::fs::read(path)
+                .map_err(|e| format!("Failed to read CA certificate {}: {}", path, e))?;
+            let bundle = reqwest::Certificate::from_pem_bundle(&pem)
+                .map_err(|e| format!("Invalid CA certificate {}: {}", path, e))?;
+            if bundle.is_empty() {
+                return Err(format!("No certificate in {}", path));
+            }
+            certs.extend(bundle);
+        }
+        Ok(certs)
+    }
+
+    fn manual_proxy(&self) -> Result<Url, String> {
+        let raw = self
+            .proxy_url
+            .as_deref()
+            .map(str::trim)
+            .filter(|u| !u.is_empty())
+            .ok_or("Manual proxy mode needs a proxy URL")?;
+        let url = Url::parse(raw).map_err(|e| format!("Invalid proxy URL {}: {}", raw, e))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!("Proxy URL must be http:// or https://: {}", raw));
+        }
+        Ok(url)
+    }
+
+    pub fn user_agent(&self) -> String {
+        self.user_agent
+            .as_deref()
+            .map(str::trim)
+            .filter(|ua| !ua.is_empty())
+            .map_or_else(
+                || concat!("SoulOS/", env!("CARGO_PKG_VERSION")).to_string(),
+                str::to_string,
+            )
+    }
+
+    /// Client builder with these settings. Every backend HTTP client for
+    /// outside hosts starts from here.
+    pub fn client_builder(&self) -> reqwest::ClientBuilder {
+        let mut builder = reqwest::Client::builder().user_agent(self.user_agent());
+        match self.certificates() {
+            Ok(certs) => {
+                for cert in certs {
+                    builder = builder.add_root_certificate(cert);
+                }
+            }
+            Err(e) => eprintln!("[network] Ignoring the CA certificates: {}", e),
+        }
+        match self.proxy_mode {
+            ProxyMode::System => builder,
+            ProxyMode::None => builder.no_proxy(),
+            ProxyMode::Manual => {
+                let bypass = self.bypass();
+                match self
+                    .manual_proxy()
+                    .and_then(|url| reqwest::Proxy::all(url.as_str()).map_err(|e| e.to_string()))
+                {
+                    Ok(proxy) => {
+                        builder.proxy(proxy.no_proxy(reqwest::NoProxy::from_string(&bypass)))
+                    }
+                    Err(e) => {
+                        eprintln!("[network] Ignoring the manual proxy: {}", e);
+                        builder
+                    }
+                }
+            }
+        }
+    }
+
+    /// Builder for clients that talk to the local engine and founding
+    /// server: user agent, never a proxy, and with `local_only` no redirect
+    /// away from loopback.
+    pub fn local_client_builder(&self) -> reqwest::ClientBuilder {
+        let builder = reqwest::Client::builder()
+            .user_agent(self.user_agent())
+            .no_proxy();
+        if !self.local_only {
+            return builder;
+        }
+        builder.redirect(reqwest::redirect::Policy::custom(|attempt| {
+            if attempt.previous().len() >= MAX_LOCAL_REDIRECTS {
+                attempt.error("too many redirects")
+            } else if attempt.url().scheme() == "http" && is_loopback(attempt.url()) {
+                attempt.follow()
+            } else {
+                let target = attempt.url().to_string();
+                attempt.error(format!("redirect to non-local {} refused", target))
+            }
+        }))
+    }
+
+    /// Environment for Node sidecars, whose LLM calls need the proxy and CA
+    /// settings too. System mode passes the inherited proxy environment
+    /// through unchanged.
+    pub fn node_env(&self) -> Vec<(&'static str, String)> {
+        let mut env = match self.proxy_mode {
+            ProxyMode::System => Vec::new(),
+            ProxyMode::None => vec![("NO_PROXY", "*".to_string())],
+            ProxyMode::Manual => match self.manual_proxy() {
+                Ok(url) => vec![
+                    ("HTTP_PROXY", url.to_string()),
+                    ("HTTPS_PROXY", url.to_string()),
+                    ("NO_PROXY", self.bypass()),
+                    ("NODE_USE_ENV_PROXY", "1".to_string()),
+                ],
+                Err(_) => Vec::new(),
+            },
+        };
+        if let Some(ca) = self.ca_certificates.first() {
+            env.push(("NODE_EXTRA_CA_CERTS", ca.clone()));
+        }
+        env
+    }
+
+    fn bypass(&self) -> String {
+        match self.no_proxy.as_deref().map(str::trim) {
+            Some(extra) if !extra.is_empty() => format!("{},{}", LOCAL_HOSTS, extra),
+            _ => LOCAL_HOSTS.to_string(),
+        }
+    }
+
+    /// Apply proxy and user agent to an embedded browser window. Manual
+    /// proxies need macOS 14 on macOS.
+    pub fn apply<'a, R: Runtime, M: Manager<R>>(
+        &self,
+        builder: WebviewWindowBuilder<'a, R, M>,
+    ) -> WebviewWindowBuilder<'a, R, M> {
+        let builder = match self.user_agent.as_deref().map(str::trim) {
+            Some(ua) if !ua.is_empty() => builder.user_agent(ua),
+            _ => builder,
+        };
+        match (self.proxy_mode, self.manual_proxy()) {
+            (ProxyMode::Manual, Ok(url)) => builder.proxy_url(url),
+            _ => builder,
+        }
+    }
+}
+
+/// The app's current network settings.
+pub fn config(app: &AppHandle) -> NetworkConfig {
+    app.try_state::<Arc<RwLock<AppConfig>>>()
+        .map(|config| config.read().network.clone())
+        .unwrap_or_default()
+}
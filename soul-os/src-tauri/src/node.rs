@@ -1,6 +1,9 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use crate::backend::ProcessLauncher;
+use crate::blocking::CancelToken;
+
 /// Find a usable Node.js binary.
 /// Priority: bundled (in app resources) → system node
 pub fn find_node(app_handle: Option<&tauri::AppHandle>) -> Option<PathBuf> {
@@ -31,13 +34,12 @@ pub fn find_node(app_handle: Option<&tauri::AppHandle>) -> Option<PathBuf> {
 }
 
 /// Get Node.js version string
-pub fn node_version(node_path: &PathBuf) -> Option<String> {
-    let output = Command::new(node_path)
-        .arg("--version")
-        .output()
+pub fn node_version(launcher: &dyn ProcessLauncher, node_path: &Path) -> Option<String> {
+    let output = launcher
+        .run(node_path, &["--version"], None, &CancelToken::default())
         .ok()?;
 
-    if output.status.success() {
+    if output.success {
         Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
     } else {
         None
@@ -0,0 +1,274 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::blocking::CancelToken;
+
+/// Naming scheme of a soul's directories. The engine accepts both; a soul
+/// founded in German uses `seele/`, `erinnerungen/`, …
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum SoulLayout {
+    German,
+    English,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Memories {
+    Core,
+    Episodic,
+    Semantic,
+    Emotional,
+    Archive,
+}
+
+/// A logical place in the soul, independent of the naming scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    /// Persona files besides SEED.md/SOUL.md
+    Soul,
+    Relationships,
+    Memories,
+    Memory(Memories),
+    Heartbeat,
+    StateLog,
+}
+
+/// Every location, children before their parents
+pub const LOCATIONS: &[Location] = &[
+    Location::Relationships,
+    Location::Soul,
+    Location::Memory(Memories::Core),
+    Location::Memory(Memories::Episodic),
+    Location::Memory(Memories::Semantic),
+    Location::Memory(Memories::Emotional),
+    Location::Memory(Memories::Archive),
+    Location::Memories,
+    Location::Heartbeat,
+    Location::StateLog,
+];
+
+/// Directories every soul gets regardless of the layout
+const SHARED_DIRS: &[&str] = &["memory", "connections"];
+
+impl Location {
+    /// Directory relative to the soul path in `layout`.
+    pub fn name(self, layout: SoulLayout) -> &'static str {
+        let (de, en) = match self {
+            Location::Soul => ("seele", "soul"),
+            Location::Relationships => ("seele/beziehungen", "soul/relationships"),
+            Location::Memories => ("erinnerungen", "memories"),
+            Location::Memory(Memories::Core) => ("erinnerungen/kern", "memories/core"),
+            Location::Memory(Memories::Episodic) => {
+                ("erinnerungen/episodisch", "memories/episodic")
+            }
+            Location::Memory(Memories::Semantic) => {
+                ("erinnerungen/semantisch", "memories/semantic")
+            }
+            Location::Memory(Memories::Emotional) => {
+                ("erinnerungen/emotional", "memories/emotional")
+            }
+            Location::Memory(Memories::Archive) => ("erinnerungen/archiv", "memories/archive"),
+            Location::Heartbeat => ("heartbeat", "heartbeat"),
+            Location::StateLog => ("zustandslog", "statelog"),
+        };
+        match layout {
+            SoulLayout::German => de,
+            SoulLayout::English => en,
+        }
+    }
+
+    pub fn parent(self) -> Option<Location> {
+        match self {
+            Location::Relationships => Some(Location::Soul),
+            Location::Memory(_) => Some(Location::Memories),
+            _ => None,
+        }
+    }
+
+    /// Both spellings; a single one where the schemes agree.
+    pub fn variants(self) -> Vec<&'static str> {
+        let de = self.name(SoulLayout::German);
+        let en = self.name(SoulLayout::English);
+        if de == en {
+            vec![de]
+        } else {
+            vec![de, en]
+        }
+    }
+}
+
+/// The soul's naming scheme, if it shows one: the memory directory that
+/// exists, else the founding language in `.language`.
+pub fn detect(soul_path: &Path) -> Option<SoulLayout> {
+    let has = |layout| soul_path.join(Location::Memories.name(layout)).is_dir();
+    match (has(SoulLayout::German), has(SoulLayout::English)) {
+        (true, false) => return Some(SoulLayout::German),
+        (false, true) => return Some(SoulLayout::English),
+        _ => {}
+    }
+    match fs::read_to_string(soul_path.join(".language"))
+        .unwrap_or_default()
+        .trim()
+    {
+        "lang:de" => Some(SoulLayout::German),
+        "lang:en" => Some(SoulLayout::English),
+        _ => None,
+    }
+}
+
+/// Naming scheme for new files: the detected one, English by default.
+pub fn layout(soul_path: &Path) -> SoulLayout {
+    detect(soul_path).unwrap_or(SoulLayout::English)
+}
+
+/// Directory of `location` relative to the soul path: the variant that
+/// exists (the soul's own scheme if both do), else the soul's scheme.
+pub fn relative(soul_path: &Path, location: Location) -> &'static str {
+    let own = location.name(layout(soul_path));
+    if soul_path.join(own).is_dir() {
+        return own;
+    }
+    location
+        .variants()
+        .into_iter()
+        .find(|dir| soul_path.join(dir).is_dir())
+        .unwrap_or(own)
+}
+
+pub fn resolve(soul_path: &Path, location: Location) -> PathBuf {
+    soul_path.join(relative(soul_path, location))
+}
+
+/// Every existing directory of `location`, in either scheme.
+pub fn existing(soul_path: &Path, location: Location) -> Vec<PathBuf> {
+    location
+        .variants()
+        .into_iter()
+        .map(|dir| soul_path.join(dir))
+        .filter(|dir| dir.is_dir())
+        .collect()
+}
+
+/// The most specific location a path relative to the soul lies in.
+pub fn classify(relative: &str) -> Option<Location> {
+    let relative = relative.replace('\\', "/");
+    LOCATIONS.iter().copied().find(|location| {
+        location.variants().into_iter().any(|dir| {
+            relative
+                .strip_prefix(dir)
+                .is_some_and(|rest| rest.starts_with('/'))
+        })
+    })
+}
+
+/// Memory category ("core", "episodic", …) of a path relative to the soul.
+pub fn memory_category(relative: &str) -> Option<&'static str> {
+    match classify(relative)? {
+        Location::Memory(Memories::Core) => Some("core"),
+        Location::Memory(Memories::Episodic) => Some("episodic"),
+        Location::Memory(Memories::Semantic) => Some("semantic"),
+        Location::Memory(Memories::Emotional) => Some("emotional"),
+        Location::Memory(Memories::Archive) => Some("archive"),
+        _ => None,
+    }
+}
+
+/// `relative` with its location directories spelled in `layout`, also
+/// when the schemes are mixed (`memories/episodisch/…`); paths outside
+/// every location come back unchanged.
+pub fn translate(relative: &str, layout: SoulLayout) -> String {
+    let relative = relative.replace('\\', "/");
+    let mut segments: Vec<&str> = relative.split('/').collect();
+    let mut parent = None;
+    for segment in segments.iter_mut() {
+        let last = |dir: &'static str| dir.rsplit('/').next().unwrap_or(dir);
+        let Some(location) = LOCATIONS.iter().copied().find(|l| {
+            l.parent() == parent && l.variants().into_iter().any(|dir| last(dir) == *segment)
+        }) else {
+            break;
+        };
+        *segment = last(location.name(layout));
+        parent = Some(location);
+    }
+    segments.join("/")
+}
+
+fn parents_first(mut dirs: Vec<&'static str>) -> Vec<&'static str> {
+    dirs.extend(SHARED_DIRS);
+    dirs.sort_by_key(|d| d.matches('/').count());
+    dirs
+}
+
+/// Every directory of a soul in `layout`, parents first.
+pub fn layout_dirs(layout: SoulLayout) -> Vec<&'static str> {
+    parents_first(LOCATIONS.iter().map(|l| l.name(layout)).collect())
+}
+
+/// Directories a new soul is scaffolded with: its own scheme's, or both
+/// schemes' while the soul doesn't show one yet.
+pub fn scaffold_dirs(soul_path: &Path) -> Vec<&'static str> {
+    match detect(soul_path) {
+        Some(layout) => layout_dirs(layout),
+        None => parents_first(LOCATIONS.iter().flat_map(|l| l.variants()).collect()),
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, TS)]
+#[ts(export)]
+pub struct LayoutReport {
+    /// Files and directories moved, as "from -> to"
+    pub moved: Vec<String>,
+    /// Left in place because the target already has an entry of that name
+    pub conflicts: Vec<String>,
+    /// Markdown files whose links were rewritten to the new directories
+    pub links_updated: Vec<String>,
+    /// Commit recording the migration, if the soul is a git repository
+    pub commit: Option<String>,
+}
+
+/// Move everything in the other scheme's directories into `target`'s,
+/// children first, and remove the emptied directories. Entries whose name
+/// already exists in the target are reported and left where they are.
+pub fn consolidate(
+    soul_path: &Path,
+    target: SoulLayout,
+    token: &CancelToken,
+) -> Result<LayoutReport, String> {
+    let mut report = LayoutReport::default();
+    for (i, location) in LOCATIONS.iter().enumerate() {
+        token.check()?;
+        token.set_progress(i as u64, LOCATIONS.len() as u64);
+        let to = location.name(target);
+        for from in location.variants().into_iter().filter(|dir| *dir != to) {
+            let source = soul_path.join(from);
+            let Ok(entries) = fs::read_dir(&source) else {
+                continue;
+            };
+            fs::create_dir_all(soul_path.join(to))
+                .map_err(|e| format!("Failed to create {}: {}", to, e))?;
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let old = format!("{}/{}", from, name);
+                // A child location is moved on its own
+                if classify(&format!("{}/", old)) != Some(*location) {
+                    continue;
+                }
+                let new = format!("{}/{}", to, name);
+                if soul_path.join(&new).exists() {
+                    report.conflicts.push(old);
+                    continue;
+                }
+                fs::rename(entry.path(), soul_path.join(&new))
+                    .map_err(|e| format!("Failed to move {}: {}", old, e))?;
+                report.moved.push(format!("{} -> {}", old, new));
+            }
+            // Only succeeds once nothing is left behind
+            let _ = fs::remove_dir(&source);
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,979 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+use ts_rs::TS;
+
+use crate::backend::{FileStore, OsFileStore};
+use crate::channel::{self, PairingRequest};
+use crate::companion::CompanionConfig;
+use crate::config::app_data_dir;
+use crate::http::{self, Http, Policy};
+use crate::lock::Throttle;
+use crate::soul_identity;
+
+/// DNS-SD service type SoulOS instances announce
+const SERVICE: &str = "_soulos._tcp.local";
+const MDNS_ADDR: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+const MDNS_PORT: u16 = 5353;
+const TYPE_PTR: u16 = 12;
+const TYPE_TXT: u16 = 16;
+const TYPE_SRV: u16 = 33;
+const TYPE_ANY: u16 = 255;
+const CLASS_IN: u16 = 1;
+/// Records replace what a cache holds for the name (RFC 6762 §10.2)
+const CACHE_FLUSH: u16 = 0x8000;
+const RECORD_TTL: u32 = 120;
+/// How often this instance announces itself and asks for others
+const ANNOUNCE_INTERVAL: Duration = Duration::from_secs(60);
+/// Peers not heard from for this long are dropped
+const PEER_TTL: Duration = Duration::from_secs(180);
+/// Stable id this instance announces, in app data
+const INSTANCE_FILE: &str = "peer-id";
+/// Tokens for the peers this instance paired with, in app data
+const PAIRED_FILE: &str = "peers.json";
+/// How long a pairing code can be entered, and how often. Wrong codes
+/// for any request also count towards a shared `Throttle`, so dropping a
+/// request after its attempts doesn't buy more guesses.
+const PAIR_TTL: Duration = Duration::from_secs(2 * 60);
+const MAX_CODE_ATTEMPTS: u32 = 3;
+/// Unconfirmed incoming pairing requests kept at once
+const MAX_PENDING: usize = 5;
+
+const PEER_CALL: Policy = Policy {
+    name: "peer",
+    timeout: Duration::from_secs(10),
+    retries: 0,
+    backoff: Duration::from_millis(0),
+    idempotent: false,
+};
+
+/// Another SoulOS instance on the local network.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct Peer {
+    pub id: String,
+    pub name: String,
+    /// host:port of its companion endpoint
+    pub address: String,
+    /// This instance holds a token for it
+    pub paired: bool,
+    /// Unix ms
+    #[ts(type = "number")]
+    pub last_seen: u64,
+}
+
+/// A peer asks to pair with this instance. Once this instance's user
+/// accepts it (`answer_peer_pairing`), the peer's user has to enter `code`.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct PairRequest {
+    pub request_id: String,
+    pub peer_id: String,
+    pub name: String,
+    pub code: String,
+    #[ts(type = "number")]
+    pub expires_in_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PairedPeer {
+    id: String,
+    name: String,
+    /// `<device id>.<key>` this instance signs its requests to the peer
+    /// with (see `channel`)
+    token: String,
+}
+
+struct Discovered {
+    name: String,
+    address: SocketAddr,
+    seen: Instant,
+    last_seen: u64,
+}
+
+struct Incoming {
+    peer_id: String,
+    name: String,
+    code: String,
+    attempts: u32,
+    issued: Instant,
+    /// The user here accepted the request
+    accepted: bool,
+}
+
+/// Requests from peers waiting for their code, by request id, and the
+/// wrong codes entered for any of them.
+#[derive(Default)]
+struct Pairing {
+    requests: HashMap<String, Incoming>,
+    failures: Throttle,
+}
+
+impl Pairing {
+    fn issue(&mut self, peer_id: &str, name: &str, now: Instant) -> Result<PairRequest, String> {
+        if peer_id.is_empty() {
+            return Err("Missing peer id".to_string());
+        }
+        self.requests
+            .retain(|_, i| now.saturating_duration_since(i.issued) < PAIR_TTL);
+        if self.requests.len() >= MAX_PENDING {
+            return Err("Too many pairing requests".to_string());
+        }
+        let request = PairRequest {
+            request_id: random_hex(8),
+            peer_id: peer_id.to_string(),
+            name: name.trim().chars().take(64).collect(),
+            code: format!("{:06}", OsRng.next_u32() % 1_000_000),
+            expires_in_secs: PAIR_TTL.as_secs(),
+        };
+        self.requests.insert(
+            request.request_id.clone(),
+            Incoming {
+                peer_id: request.peer_id.clone(),
+                name: request.name.clone(),
+                code: request.code.clone(),
+                attempts: 0,
+                issued: now,
+                accepted: false,
+            },
+        );
+        Ok(request)
+    }
+
+    fn answer(&mut self, request_id: &str, accept: bool) -> Result<(), String> {
+        if !accept {
+            self.requests.remove(request_id);
+            return Ok(());
+        }
+        self.requests
+            .get_mut(request_id)
+            .map(|request| request.accepted = true)
+            .ok_or_else(|| "No such pairing request".to_string())
+    }
+
+    fn confirm(&mut self, request_id: &str, code: &str, now: Instant) -> Result<String, String> {
+        if let Some(wait) = self.failures.wait(now) {
+            return Err(format!(
+                "Too many wrong pairing codes; try again in {} s",
+                wait.as_secs() + 1
+            ));
+        }
+        let Some(request) = self.requests.get_mut(request_id) else {
+            return Err("No such pairing request".to_string());
+        };
+        if now.saturating_duration_since(request.issued) >= PAIR_TTL {
+            self.requests.remove(request_id);
+            return Err("The pairing code expired".to_string());
+        }
+        if !request.accepted {
+            return Err("The pairing request was not accepted yet".to_string());
+        }
+        if request.code != code.trim() {
+            request.attempts += 1;
+            if request.attempts >= MAX_CODE_ATTEMPTS {
+                self.requests.remove(request_id);
+            }
+            self.failures.failed(now);
+            return Err("Wrong pairing code".to_string());
+        }
+        self.failures = Throttle::default();
+        let request = self.requests.remove(request_id).expect("found above");
+        Ok(if request.name.is_empty() {
+            format!("SoulOS {}", request.peer_id)
+        } else {
+            request.name
+        })
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn random_hex(bytes: usize) -> String {
+    let mut buf = vec![0u8; bytes];
+    OsRng.fill_bytes(&mut buf);
+    buf.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn paired_path() -> PathBuf {
+    app_data_dir().join(PAIRED_FILE)
+}
+
+fn load_paired() -> Vec<PairedPeer> {
+    fs::read_to_string(paired_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_paired(peers: &[PairedPeer]) -> Result<(), String> {
+    let path = paired_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(peers).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| e.to_string())?;
+    OsFileStore.restrict(&path);
+    Ok(())
+}
+
+/// The id this instance announces, created on first use.
+fn instance_id() -> String {
+    let path = app_data_dir().join(INSTANCE_FILE);
+    if let Ok(id) = fs::read_to_string(&path) {
+        let id = id.trim();
+        if !id.is_empty() {
+            return id.to_string();
+        }
+    }
+    let id = random_hex(8);
+    if let Err(e) = fs::create_dir_all(app_data_dir()).and_then(|_| fs::write(&path, &id)) {
+        eprintln!("[peers] saving the instance id failed: {}", e);
+    }
+    id
+}
+
+// --- DNS messages (RFC 1035, mDNS per RFC 6762) ---
+
+fn write_name(out: &mut Vec<u8>, name: &str) {
+    for label in name.split('.').filter(|l| !l.is_empty()) {
+        let label = &label.as_bytes()[..label.len().min(63)];
+        out.push(label.len() as u8);
+        out.extend_from_slice(label);
+    }
+    out.push(0);
+}
+
+fn write_record(out: &mut Vec<u8>, name: &str, rtype: u16, class: u16, rdata: &[u8]) {
+    write_name(out, name);
+    out.extend_from_slice(&rtype.to_be_bytes());
+    out.extend_from_slice(&class.to_be_bytes());
+    out.extend_from_slice(&RECORD_TTL.to_be_bytes());
+    out.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+    out.extend_from_slice(rdata);
+}
+
+fn query() -> Vec<u8> {
+    let mut out = vec![0u8; 12];
+    out[5] = 1;
+    write_name(&mut out, SERVICE);
+    out.extend_from_slice(&TYPE_PTR.to_be_bytes());
+    out.extend_from_slice(&CLASS_IN.to_be_bytes());
+    out
+}
+
+/// PTR, SRV and TXT records for this instance. No address record: peers
+/// take the address the announcement came from.
+fn announcement(instance: &str, name: &str, port: u16) -> Vec<u8> {
+    let mut out = vec![0u8; 12];
+    // Response, authoritative; three answers
+    out[2] = 0x84;
+    out[7] = 3;
+    let full = format!("{}.{}", instance, SERVICE);
+    let mut ptr = Vec::new();
+    write_name(&mut ptr, &full);
+    write_record(&mut out, SERVICE, TYPE_PTR, CLASS_IN, &ptr);
+    let mut srv = vec![0, 0, 0, 0];
+    srv.extend_from_slice(&port.to_be_bytes());
+    write_name(&mut srv, &format!("{}.local", instance));
+    write_record(&mut out, &full, TYPE_SRV, CLASS_IN | CACHE_FLUSH, &srv);
+    let mut txt = Vec::new();
+    for entry in [
+        format!("id={}", instance),
+        format!("name={}", name),
+        "v=1".to_string(),
+    ] {
+        let bytes = &entry.as_bytes()[..entry.len().min(255)];
+        txt.push(bytes.len() as u8);
+        txt.extend_from_slice(bytes);
+    }
+    write_record(&mut out, &full, TYPE_TXT, CLASS_IN | CACHE_FLUSH, &txt);
+    out
+}
+
+fn u16_at(data: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_be_bytes([*data.get(pos)?, *data.get(pos + 1)?]))
+}
+
+/// A (possibly compressed) name at `pos`, and where the data after it
+/// starts.
+fn read_name(data: &[u8], mut pos: usize) -> Option<(String, usize)> {
+    let mut labels = Vec::new();
+    let mut end = None;
+    let mut jumps = 0;
+    loop {
+        let len = *data.get(pos)? as usize;
+        if len & 0xC0 == 0xC0 {
+            end.get_or_insert(pos + 2);
+            jumps += 1;
+            if jumps > 16 {
+                return None;
+            }
+            pos = (u16_at(data, pos)? & 0x3FFF) as usize;
+            continue;
+        }
+        if len == 0 {
+            end.get_or_insert(pos + 1);
+            break;
+        }
+        labels.push(String::from_utf8_lossy(data.get(pos + 1..pos + 1 + len)?).to_string());
+        pos += 1 + len;
+    }
+    Some((labels.join(".").to_ascii_lowercase(), end?))
+}
+
+struct Record {
+    name: String,
+    rtype: u16,
+    rdata: std::ops::Range<usize>,
+}
+
+struct Message {
+    response: bool,
+    questions: Vec<(String, u16)>,
+    records: Vec<Record>,
+}
+
+fn parse(data: &[u8]) -> Option<Message> {
+    let flags = u16_at(data, 2)?;
+    let questions = u16_at(data, 4)?;
+    let records =
+        u16_at(data, 6)? as usize + u16_at(data, 8)? as usize + u16_at(data, 10)? as usize;
+    let mut pos = 12;
+    let mut message = Message {
+        response: flags & 0x8000 != 0,
+        questions: Vec::new(),
+        records: Vec::new(),
+    };
+    for _ in 0..questions {
+        let (name, next) = read_name(data, pos)?;
+        message.questions.push((name, u16_at(data, next)?));
+        pos = next + 4;
+    }
+    for _ in 0..records {
+        let (name, next) = read_name(data, pos)?;
+        let rtype = u16_at(data, next)?;
+        let len = u16_at(data, next + 8)? as usize;
+        let start = next + 10;
+        if start + len > data.len() {
+            return None;
+        }
+        message.records.push(Record {
+            name,
+            rtype,
+            rdata: start..start + len,
+        });
+        pos = start + len;
+    }
+    Some(message)
+}
+
+/// Instances announced in a response: (id, name, port).
+fn announced(data: &[u8], message: &Message) -> Vec<(String, String, u16)> {
+    let suffix = format!(".{}", SERVICE);
+    let mut ports: HashMap<&str, u16> = HashMap::new();
+    let mut txts: HashMap<&str, HashMap<String, String>> = HashMap::new();
+    for record in message.records.iter().filter(|r| r.name.ends_with(&suffix)) {
+        match record.rtype {
+            // Priority, weight, port, target
+            TYPE_SRV if record.rdata.len() >= 7 => {
+                if let Some(port) = u16_at(data, record.rdata.start + 4) {
+                    ports.insert(&record.name, port);
+                }
+            }
+            TYPE_TXT => {
+                let rdata = &data[record.rdata.clone()];
+                let mut entries = HashMap::new();
+                let mut pos = 0;
+                while let Some(&len) = rdata.get(pos) {
+                    let Some(entry) = rdata.get(pos + 1..pos + 1 + len as usize) else {
+                        break;
+                    };
+                    let entry = String::from_utf8_lossy(entry);
+                    if let Some((key, value)) = entry.split_once('=') {
+                        entries.insert(key.to_ascii_lowercase(), value.to_string());
+                    }
+                    pos += 1 + len as usize;
+                }
+                txts.insert(&record.name, entries);
+            }
+            _ => {}
+        }
+    }
+    ports
+        .into_iter()
+        .filter_map(|(name, port)| {
+            let txt = txts.get(name)?;
+            let id = txt.get("id")?.clone();
+            let label = txt.get("name").cloned().unwrap_or_else(|| id.clone());
+            Some((id, label, port))
+        })
+        .collect()
+}
+
+/// The mDNS port is shared with the system responder (Bonjour, Avahi), so
+/// the socket has to allow address reuse before it binds.
+#[cfg(unix)]
+fn bind_mdns() -> io::Result<UdpSocket> {
+    use std::os::fd::FromRawFd;
+
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM, 0) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // Owns the descriptor from here on, so errors close it
+    let socket = unsafe { UdpSocket::from_raw_fd(fd) };
+    let on: libc::c_int = 1;
+    for option in [libc::SO_REUSEADDR, libc::SO_REUSEPORT] {
+        unsafe {
+            libc::setsockopt(
+                fd,
+                libc::SOL_SOCKET,
+                option,
+                &on as *const libc::c_int as *const libc::c_void,
+                std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+            );
+        }
+    }
+    let mut addr: libc::sockaddr_in = unsafe { std::mem::zeroed() };
+    addr.sin_family = libc::AF_INET as libc::sa_family_t;
+    addr.sin_port = MDNS_PORT.to_be();
+    let bound = unsafe {
+        libc::bind(
+            fd,
+            &addr as *const libc::sockaddr_in as *const libc::sockaddr,
+            std::mem::size_of::<libc::sockaddr_in>() as libc::socklen_t,
+        )
+    };
+    if bound < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(socket)
+}
+
+#[cfg(not(unix))]
+fn bind_mdns() -> io::Result<UdpSocket> {
+    UdpSocket::bind((Ipv4Addr::UNSPECIFIED, MDNS_PORT))
+}
+
+/// mDNS announcer and browser for other SoulOS instances, plus both ends
+/// of pairing with one.
+pub struct Peers {
+    instance: String,
+    name: String,
+    task: Mutex<Option<JoinHandle<()>>>,
+    discovered: Mutex<HashMap<String, Discovered>>,
+    incoming: Mutex<Pairing>,
+    /// Our requests waiting for the code, by peer id
+    outgoing: Mutex<HashMap<String, String>>,
+}
+
+impl Default for Peers {
+    fn default() -> Self {
+        Self {
+            instance: instance_id(),
+            name: soul_identity::hostname(),
+            task: Mutex::new(None),
+            discovered: Mutex::new(HashMap::new()),
+            incoming: Mutex::new(Pairing::default()),
+            outgoing: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl Peers {
+    /// Start, restart or stop discovery to match `config`. Announcing
+    /// needs the companion endpoint reachable on the local network.
+    pub fn apply(&self, app: &AppHandle, config: &CompanionConfig) -> Result<(), String> {
+        if let Some(task) = self.task.lock().take() {
+            task.abort();
+        }
+        self.discovered.lock().clear();
+        if !(config.enabled && config.discovery) {
+            return Ok(());
+        }
+        if !config.lan {
+            return Err("Discovery needs the companion endpoint on the local network".to_string());
+        }
+        let socket = bind_mdns().map_err(|e| format!("Cannot listen for mDNS: {}", e))?;
+        socket
+            .join_multicast_v4(&MDNS_ADDR, &Ipv4Addr::UNSPECIFIED)
+            .map_err(|e| format!("Cannot join the mDNS group: {}", e))?;
+        // Instances on the same machine see each other too
+        let _ = socket.set_multicast_loop_v4(true);
+        socket.set_nonblocking(true).map_err(|e| e.to_string())?;
+
+        let app = app.clone();
+        let announce = announcement(&self.instance, &self.name, config.port);
+        *self.task.lock() = Some(tauri::async_runtime::spawn(async move {
+            let Ok(socket) = tokio::net::UdpSocket::from_std(socket) else {
+                return;
+            };
+            let group = SocketAddr::from((MDNS_ADDR, MDNS_PORT));
+            let mut ticker = tokio::time::interval(ANNOUNCE_INTERVAL);
+            let mut buf = vec![0u8; 9000];
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {
+                        let _ = socket.send_to(&announce, group).await;
+                        let _ = socket.send_to(&query(), group).await;
+                    }
+                    received = socket.recv_from(&mut buf) => {
+                        let Ok((len, from)) = received else {
+                            continue;
+                        };
+                        let Some(message) = parse(&buf[..len]) else {
+                            continue;
+                        };
+                        if message.response {
+                            if let Some(peers) = app.try_state::<Arc<Peers>>() {
+                                peers.seen(&app, &buf[..len], &message, from);
+                            }
+                        } else if message.questions.iter().any(|(name, qtype)| {
+                            name == SERVICE && (*qtype == TYPE_PTR || *qtype == TYPE_ANY)
+                        }) {
+                            let _ = socket.send_to(&announce, group).await;
+                        }
+                    }
+                }
+            }
+        }));
+        Ok(())
+    }
+
+    fn seen(&self, app: &AppHandle, data: &[u8], message: &Message, from: SocketAddr) {
+        let mut changed = false;
+        {
+            let mut discovered = self.discovered.lock();
+            for (id, name, port) in announced(data, message) {
+                if id == self.instance {
+                    continue;
+                }
+                let address = SocketAddr::new(from.ip(), port);
+                let previous = discovered.insert(
+                    id,
+                    Discovered {
+                        name: name.clone(),
+                        address,
+                        seen: Instant::now(),
+                        last_seen: now_ms(),
+                    },
+                );
+                changed |= previous.is_none_or(|p| p.name != name || p.address != address);
+            }
+        }
+        if changed {
+            let _ = app.emit("peers:changed", self.list());
+        }
+    }
+
+    /// Instances heard from recently, by name.
+    pub fn list(&self) -> Vec<Peer> {
+        let paired = load_paired();
+        let mut discovered = self.discovered.lock();
+        discovered.retain(|_, d| d.seen.elapsed() < PEER_TTL);
+        let mut peers: Vec<Peer> = discovered
+            .iter()
+            .map(|(id, d)| Peer {
+                id: id.clone(),
+                name: d.name.clone(),
+                address: d.address.to_string(),
+                paired: paired.iter().any(|p| p.id == *id),
+                last_seen: d.last_seen,
+            })
+            .collect();
+        peers.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+        peers
+    }
+
+    fn find(&self, id: &str) -> Result<Peer, String> {
+        self.list()
+            .into_iter()
+            .find(|p| p.id == id)
+            .ok_or_else(|| format!("Peer {} is not on the network", id))
+    }
+
+    /// Forget the token for a peer.
+    pub fn forget(&self, id: &str) -> Result<(), String> {
+        let mut paired = load_paired();
+        paired.retain(|p| p.id != id);
+        save_paired(&paired)
+    }
+
+    /// A peer asks to pair: issue a code for this instance's user to accept
+    /// and read out (`peers:pair-request`).
+    pub fn begin_incoming(
+        &self,
+        app: &AppHandle,
+        peer_id: &str,
+        name: &str,
+    ) -> Result<String, String> {
+        let request = self.incoming.lock().issue(peer_id, name, Instant::now())?;
+        let request_id = request.request_id.clone();
+        let _ = app.emit("peers:pair-request", request);
+        Ok(request_id)
+    }
+
+    /// The user here accepts or declines a pairing request; its code is
+    /// only valid once accepted.
+    pub fn answer_incoming(&self, request_id: &str, accept: bool) -> Result<(), String> {
+        self.incoming.lock().answer(request_id, accept)
+    }
+
+    /// Check the code a peer sends back. Returns the peer's name to pair
+    /// it as a device; the request is gone after too many wrong codes.
+    pub fn confirm_incoming(&self, request_id: &str, code: &str) -> Result<String, String> {
+        self.incoming
+            .lock()
+            .confirm(request_id, code, Instant::now())
+    }
+}
+
+async fn error_of(response: reqwest::Response) -> String {
+    let status = response.status();
+    response
+        .json::<serde_json::Value>()
+        .await
+        .ok()
+        .and_then(|body| body["error"].as_str().map(str::to_string))
+        .unwrap_or_else(|| status.to_string())
+}
+
+/// Ask a discovered peer to pair; its user then sees the code to enter
+/// with `confirm_pairing`.
+pub async fn request_pairing(app: &AppHandle, peer_id: &str) -> Result<(), String> {
+    let peers = app.state::<Arc<Peers>>().inner().clone();
+    let peer = peers.find(peer_id)?;
+    let url = format!("http://{}/pair", peer.address);
+    let body = serde_json::json!({ "id": peers.instance, "name": peers.name });
+    let client = app.state::<Arc<Http>>().external();
+    let response = http::send(app, &PEER_CALL, || client.post(&url).json(&body))
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(error_of(response).await);
+    }
+    let reply: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+    let request_id = reply["request_id"]
+        .as_str()
+        .ok_or("The peer sent no request id")?;
+    peers
+        .outgoing
+        .lock()
+        .insert(peer_id.to_string(), request_id.to_string());
+    Ok(())
+}
+
+/// Send the code shown on the peer; on success the pairing key it seals
+/// for this instance is kept for `peer_status` and sync.
+pub async fn confirm_pairing(app: &AppHandle, peer_id: &str, code: &str) -> Result<Peer, String> {
+    let peers = app.state::<Arc<Peers>>().inner().clone();
+    let peer = peers.find(peer_id)?;
+    let request_id = peers
+        .outgoing
+        .lock()
+        .get(peer_id)
+        .cloned()
+        .ok_or("Request pairing with this peer first")?;
+    let url = format!("http://{}/pair/confirm", peer.address);
+    let pairing = PairingRequest::new()?;
+    let body = serde_json::json!({
+        "request_id": request_id,
+        "code": code,
+        "public_key": pairing.public,
+    });
+    let client = app.state::<Arc<Http>>().external();
+    let response = http::send(app, &PEER_CALL, || client.post(&url).json(&body))
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(error_of(response).await);
+    }
+    let reply: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+    let (Some(device), Some(public_key), Some(sealed)) = (
+        reply["device"]["id"].as_str(),
+        reply["public_key"].as_str(),
+        reply["key"].as_str(),
+    ) else {
+        return Err("The peer sent no pairing key".to_string());
+    };
+    let key = pairing.open(public_key, code, sealed)?;
+    peers.outgoing.lock().remove(peer_id);
+    let mut paired = load_paired();
+    paired.retain(|p| p.id != peer_id);
+    paired.push(PairedPeer {
+        id: peer_id.to_string(),
+        name: peer.name.clone(),
+        token: format!("{}.{}", device, channel::to_hex(&key)),
+    });
+    save_paired(&paired)?;
+    Ok(Peer {
+        paired: true,
+        ..peer
+    })
+}
+
+/// A paired peer's soul status (its companion `GET /status`).
+pub async fn peer_status(app: &AppHandle, peer_id: &str) -> Result<serde_json::Value, String> {
+    let peers = app.state::<Arc<Peers>>().inner().clone();
+    let peer = peers.find(peer_id)?;
+    let token = load_paired()
+        .into_iter()
+        .find(|p| p.id == peer_id)
+        .map(|p| p.token)
+        .ok_or("Pair with this peer first")?;
+    let (device, key) = token
+        .split_once('.')
+        .and_then(|(device, key)| Some((device, channel::key_from_hex(key)?)))
+        .ok_or("Paired before requests were signed; pair with this peer again")?;
+    let (authorization, nonce) = channel::sign(&key, device, "GET", "/status", now_ms(), b"");
+    let url = format!("http://{}/status", peer.address);
+    let client = app.state::<Arc<Http>>().external();
+    let response = http::send(app, &PEER_CALL, || {
+        client.get(&url).header("Authorization", &authorization)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    let code = response.status();
+    let body = response.bytes().await.map_err(|e| e.to_string())?;
+    // Refused signatures are answered in the clear, everything else sealed
+    let body = channel::open_body(&channel::response_key(&key, &nonce), &body)
+        .unwrap_or_else(|_| body.to_vec());
+    let body: serde_json::Value = serde_json::from_slice(&body).map_err(|e| e.to_string())?;
+    if !code.is_success() {
+        return Err(body["error"]
+            .as_str()
+            .map_or_else(|| code.to_string(), str::to_string));
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accepted(pairing: &mut Pairing, now: Instant) -> PairRequest {
+        let request = pairing.issue("peer", "Laptop", now).unwrap();
+        pairing.answer(&request.request_id, true).unwrap();
+        request
+    }
+
+    fn wrong(code: &str) -> String {
+        format!("{:06}", (code.parse::<u32>().unwrap() + 1) % 1_000_000)
+    }
+
+    #[test]
+    fn code_needs_acceptance() {
+        let mut pairing = Pairing::default();
+        let now = Instant::now();
+        let request = pairing.issue("peer", "Laptop", now).unwrap();
+        assert!(pairing
+            .confirm(&request.request_id, &request.code, now)
+            .unwrap_err()
+            .contains("not accepted"));
+        pairing.answer(&request.request_id, true).unwrap();
+        assert_eq!(
+            pairing.confirm(&request.request_id, &request.code, now),
+            Ok("Laptop".to_string())
+        );
+    }
+
+    #[test]
+    fn declined_requests_are_gone() {
+        let mut pairing = Pairing::default();
+        let now = Instant::now();
+        let request = pairing.issue("peer", "", now).unwrap();
+        pairing.answer(&request.request_id, false).unwrap();
+        assert!(pairing.answer(&request.request_id, true).is_err());
+        assert!(pairing.requests.is_empty());
+    }
+
+    #[test]
+    fn wrong_codes_lock_out_new_requests_too() {
+        let mut pairing = Pairing::default();
+        let now = Instant::now();
+        // A client dropping each request after its attempts and asking
+        // again keeps counting
+        let request = accepted(&mut pairing, now);
+        for _ in 0..MAX_CODE_ATTEMPTS {
+            let code = wrong(&request.code);
+            assert!(pairing.confirm(&request.request_id, &code, now).is_err());
+        }
+        assert!(!pairing.requests.contains_key(&request.request_id));
+        let request = accepted(&mut pairing, now);
+        let code = wrong(&request.code);
+        assert!(pairing.confirm(&request.request_id, &code, now).is_err());
+
+        let refused = pairing
+            .confirm(&request.request_id, &request.code, now)
+            .unwrap_err();
+        assert!(
+            refused.starts_with("Too many wrong pairing codes"),
+            "{}",
+            refused
+        );
+
+        let later = now + Duration::from_secs(60);
+        assert!(pairing
+            .confirm(&request.request_id, &request.code, later)
+            .is_ok());
+        // A right code resets the count
+        let request = accepted(&mut pairing, later);
+        let code = wrong(&request.code);
+        assert_eq!(
+            pairing.confirm(&request.request_id, &code, later),
+            Err("Wrong pairing code".to_string())
+        );
+    }
+
+    /// A response laid out as Bonjour sends one, for `abc` on port 8080:
+    /// names compressed against the PTR record, the SRV target against
+    /// `local`.
+    fn captured() -> Vec<u8> {
+        [
+            &b"\x00\x00\x84\x00\x00\x00\x00\x03\x00\x00\x00\x00"[..],
+            // 12: _soulos._tcp.local PTR abc._soulos._tcp.local
+            b"\x07_soulos\x04_tcp\x05local\x00\x00\x0c\x00\x01\x00\x00\x00\x78",
+            b"\x00\x06\x03abc\xc0\x0c",
+            // 48: SRV 0 0 8080 abc.local
+            b"\xc0\x2a\x00\x21\x80\x01\x00\x00\x00\x78",
+            b"\x00\x0c\x00\x00\x00\x00\x1f\x90\x03abc\xc0\x19",
+            // 72: TXT "id=abc" "name=Laptop" "v=1"
+            b"\xc0\x2a\x00\x10\x80\x01\x00\x00\x00\x78",
+            b"\x00\x17\x06id=abc\x0bname=Laptop\x03v=1",
+        ]
+        .concat()
+    }
+
+    fn announced_in(data: &[u8]) -> Vec<(String, String, u16)> {
+        announced(data, &parse(data).unwrap())
+    }
+
+    /// A response with one TXT record for `abc` and an SRV record for it.
+    fn with_txt(txt: &[u8]) -> Vec<u8> {
+        let mut out = vec![0, 0, 0x84, 0, 0, 0, 0, 2, 0, 0, 0, 0];
+        let full = format!("abc.{}", SERVICE);
+        let srv = [0, 0, 0, 0, 0x1f, 0x90, 0];
+        write_record(&mut out, &full, TYPE_SRV, CLASS_IN, &srv);
+        write_record(&mut out, &full, TYPE_TXT, CLASS_IN, txt);
+        out
+    }
+
+    #[test]
+    fn reads_a_captured_announcement() {
+        let captured = captured();
+        let message = parse(&captured).unwrap();
+        assert!(message.response);
+        let names: Vec<&str> = message.records.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [SERVICE, "abc._soulos._tcp.local", "abc._soulos._tcp.local"]
+        );
+        assert_eq!(
+            announced_in(&captured),
+            [("abc".to_string(), "Laptop".to_string(), 8080)]
+        );
+    }
+
+    #[test]
+    fn reads_its_own_announcement_and_query() {
+        let announcement = announcement("abc", "Desk", 7070);
+        assert_eq!(
+            announced_in(&announcement),
+            [("abc".to_string(), "Desk".to_string(), 7070)]
+        );
+        let query = parse(&query()).unwrap();
+        assert!(!query.response);
+        assert_eq!(query.questions, [(SERVICE.to_string(), TYPE_PTR)]);
+    }
+
+    #[test]
+    fn follows_compression_pointers() {
+        // "local" at 12, "abc" + pointer to it at 19
+        let data = [&[0; 12][..], b"\x05local\x00\x03ABC\xc0\x0c\xff"].concat();
+        assert_eq!(read_name(&data, 19), Some(("abc.local".to_string(), 25)));
+        assert_eq!(read_name(&data, 12), Some(("local".to_string(), 19)));
+    }
+
+    #[test]
+    fn refuses_pointer_loops() {
+        // A pointer to itself, and two pointing at each other
+        let itself = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xc0, 0x0c];
+        assert_eq!(read_name(&itself, 12), None);
+        let pair = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xc0, 0x0e, 0xc0, 0x0c];
+        assert_eq!(read_name(&pair, 12), None);
+        let mut question = pair.to_vec();
+        question[5] = 1;
+        assert!(parse(&question).is_none());
+    }
+
+    #[test]
+    fn refuses_truncated_messages() {
+        let captured = captured();
+        assert!(parse(&captured[..11]).is_none());
+        // Cut inside a label, a pointer, a record header and rdata
+        for len in [20, 49, 54, captured.len() - 1] {
+            assert!(parse(&captured[..len]).is_none(), "{}", len);
+        }
+        // A pointer past the end
+        let mut past = captured.clone();
+        past[49] = 0xff;
+        assert!(parse(&past).is_none());
+        // Fewer records than the header counts
+        let mut short = captured.clone();
+        short[7] = 4;
+        assert!(parse(&short).is_none());
+    }
+
+    #[test]
+    fn reads_txt_entries() {
+        // Keys are case-insensitive; entries without '=' and empty ones skipped
+        let txt = b"\x06ID=abc\x00\x04flag\x0aname=a=b c";
+        assert_eq!(
+            announced_in(&with_txt(txt)),
+            [("abc".to_string(), "a=b c".to_string(), 8080)]
+        );
+        // An entry running past the record keeps what came before it
+        let txt = b"\x06id=abc\x09name";
+        assert_eq!(
+            announced_in(&with_txt(txt)),
+            [("abc".to_string(), "abc".to_string(), 8080)]
+        );
+        // No id, no instance
+        assert!(announced_in(&with_txt(b"\x08name=abc")).is_empty());
+    }
+
+    #[test]
+    fn ignores_short_srv_records() {
+        let mut data = vec![0, 0, 0x84, 0, 0, 0, 0, 2, 0, 0, 0, 0];
+        let full = format!("abc.{}", SERVICE);
+        write_record(&mut data, &full, TYPE_SRV, CLASS_IN, &[0, 0, 0]);
+        write_record(&mut data, &full, TYPE_TXT, CLASS_IN, b"\x06id=abc");
+        assert!(announced_in(&data).is_empty());
+    }
+
+    #[test]
+    fn codes_expire() {
+        let mut pairing = Pairing::default();
+        let now = Instant::now();
+        let request = accepted(&mut pairing, now);
+        assert_eq!(
+            pairing.confirm(&request.request_id, &request.code, now + PAIR_TTL),
+            Err("The pairing code expired".to_string())
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use tauri::ipc::Invoke;
+use tauri::plugin::Plugin;
+use tauri::webview::PageLoadPayload;
+use tauri::{AppHandle, Emitter, Manager, RunEvent, Runtime, Url, Webview, Window};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use ts_rs::TS;
+
+use crate::lock::{AppLock, LOCKED_ERROR};
+use crate::metrics::Metrics;
+
+/// Commands that can damage the soul or leak secrets. They only run while an
+/// elevated session is unlocked.
+pub const SENSITIVE_COMMANDS: &[&str] = &[
+    "write_env",
+    "set_env_default",
+    "set_session_env",
+    "set_env_policy",
+    "set_network_config",
+    "rollback_state",
+    "create_pty",
+    "open_browser",
+    "relocate_soul",
+    "relink_soul",
+    "restore_latest_backup",
+    "set_protected_paths",
+    "delete_soul_files",
+    "apply_batch",
+    "pair_companion_device",
+    "answer_peer_pairing",
+];
+/// How long an elevated session stays unlocked
+const ELEVATION_TTL: Duration = Duration::from_secs(5 * 60);
+/// Invocations kept for `get_command_trail()`
+const MAX_TRAIL: usize = 500;
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct CommandInvocation {
+    pub command: String,
+    #[ts(type = "number")]
+    pub timestamp: u64,
+    pub sensitive: bool,
+    pub allowed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ElevationStatus {
+    pub elevated: bool,
+    #[ts(type = "number")]
+    pub remaining_secs: u64,
+    pub sensitive_commands: Vec<String>,
+}
+
+/// Capability gate in front of the IPC surface. Elevation is granted through
+/// a native dialog, so a compromised webview can't unlock it on its own.
+#[derive(Default)]
+pub struct Permissions {
+    elevated_until: Mutex<Option<Instant>>,
+    trail: Mutex<VecDeque<CommandInvocation>>,
+}
+
+impl Permissions {
+    fn remaining(&self) -> Option<Duration> {
+        let until = (*self.elevated_until.lock())?;
+        until.checked_duration_since(Instant::now())
+    }
+
+    pub fn status(&self) -> ElevationStatus {
+        let remaining = self.remaining();
+        ElevationStatus {
+            elevated: remaining.is_some(),
+            remaining_secs: remaining.map(|r| r.as_secs()).unwrap_or(0),
+            sensitive_commands: SENSITIVE_COMMANDS.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    /// Ask the user natively; unlocks for `ELEVATION_TTL` when confirmed.
+    /// Blocks until the dialog closes — call from a worker thread.
+    pub fn request(&self, app: &AppHandle, reason: &str) -> bool {
+        let confirmed = app
+            .dialog()
+            .message(format!(
+                "The app requests elevated access for {} minutes.\n\n{}",
+                ELEVATION_TTL.as_secs() / 60,
+                reason
+            ))
+            .title("Allow sensitive actions?")
+            .kind(MessageDialogKind::Warning)
+            .buttons(MessageDialogButtons::OkCancel)
+            .blocking_show();
+        if confirmed {
+            *self.elevated_until.lock() = Some(Instant::now() + ELEVATION_TTL);
+        }
+        let _ = app.emit("permissions:changed", self.status());
+        confirmed
+    }
+
+    pub fn drop_elevation(&self, app: &AppHandle) {
+        *self.elevated_until.lock() = None;
+        let _ = app.emit("permissions:changed", self.status());
+    }
+
+    /// Record an invocation and decide whether it may run.
+    fn authorize(&self, command: &str) -> bool {
+        let sensitive = SENSITIVE_COMMANDS.contains(&command);
+        let allowed = !sensitive || self.remaining().is_some();
+
+        let mut trail = self.trail.lock();
+        trail.push_back(CommandInvocation {
+            command: command.to_string(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            sensitive,
+            allowed,
+        });
+        while trail.len() > MAX_TRAIL {
+            trail.pop_front();
+        }
+        allowed
+    }
+
+    pub fn trail(&self) -> Vec<CommandInvocation> {
+        self.trail.lock().iter().cloned().collect()
+    }
+}
+
+/// Why `command` may not run right now: the app lock, then the capability
+/// check. None lets it through.
+fn refusal<R: Runtime>(webview: &Webview<R>, command: &str) -> Option<String> {
+    if let Some(metrics) = webview.try_state::<Arc<Metrics>>() {
+        metrics.command_invoked(command);
+    }
+    if let Some(lock) = webview.try_state::<Arc<AppLock>>() {
+        if lock.blocks(command) {
+            return Some(LOCKED_ERROR.to_string());
+        }
+    }
+    if let Some(permissions) = webview.try_state::<Arc<Permissions>>() {
+        if !permissions.authorize(command) {
+            let _ = webview.emit(
+                "permissions:denied",
+                serde_json::json!({ "command": command }),
+            );
+            return Some(format!("{} requires an elevated session", command));
+        }
+    }
+    None
+}
+
+/// Wrap the generated invoke handler so every command passes the app lock
+/// and the capability check.
+pub fn guard<R: Runtime>(
+    handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke: Invoke<R>| {
+        let command = invoke.message.command().to_string();
+        if let Some(e) = refusal(&invoke.message.webview(), &command) {
+            invoke.resolver.reject(e);
+            return true;
+        }
+        handler(invoke)
+    }
+}
+
+/// A plugin whose IPC commands pass the same gate as the app's own, as
+/// `plugin:<name>|<command>`. Plugin commands don't reach `guard`.
+pub struct Guarded<P>(P);
+
+pub fn guard_plugin<R: Runtime, P: Plugin<R>>(plugin: P) -> Guarded<P> {
+    Guarded(plugin)
+}
+
+impl<R: Runtime, P: Plugin<R>> Plugin<R> for Guarded<P> {
+    fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    fn initialize(
+        &mut self,
+        app: &AppHandle<R>,
+        config: serde_json::Value,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.0.initialize(app, config)
+    }
+
+    fn initialization_script(&self) -> Option<String> {
+        self.0.initialization_script()
+    }
+
+    fn window_created(&mut self, window: Window<R>) {
+        self.0.window_created(window)
+    }
+
+    fn webview_created(&mut self, webview: Webview<R>) {
+        self.0.webview_created(webview)
+    }
+
+    fn on_navigation(&mut self, webview: &Webview<R>, url: &Url) -> bool {
+        self.0.on_navigation(webview, url)
+    }
+
+    fn on_page_load(&mut self, webview: &Webview<R>, payload: &PageLoadPayload<'_>) {
+        self.0.on_page_load(webview, payload)
+    }
+
+    fn on_event(&mut self, app: &AppHandle<R>, event: &RunEvent) {
+        self.0.on_event(app, event)
+    }
+
+    fn extend_api(&mut self, invoke: Invoke<R>) -> bool {
+        let command = format!("plugin:{}|{}", self.0.name(), invoke.message.command());
+        if let Some(e) = refusal(&invoke.message.webview(), &command) {
+            invoke.resolver.reject(e);
+            return true;
+        }
+        self.0.extend_api(invoke)
+    }
+}
@@ -0,0 +1,219 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::backend::OsFileStore;
+use crate::paths::Location;
+use crate::safe_path;
+
+/// Blocks every seed must carry (SEED_SPEC.md → Required Blocks)
+const REQUIRED_BLOCKS: &[&str] = &["@META", "@KERN", "@SELF", "@STATE", "@BONDS", "@MEM"];
+/// The spec keeps seeds under 5 KB through condensation
+const SEED_SOFT_LIMIT: usize = 5 * 1024;
+/// Hard limit for any persona file — beyond this it no longer fits the prompt budget
+const PERSONA_MAX_BYTES: usize = 64 * 1024;
+/// Template leftovers that must never reach the engine's prompt
+const FORBIDDEN_PLACEHOLDERS: &[&str] = &[
+    "{{",
+    "[PLACEHOLDER]",
+    "{version}",
+    "{ISO-date",
+    "{n}",
+    "TODO:",
+    "FIXME",
+];
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ValidationIssue {
+    /// "error" blocks saving, "warning" doesn't
+    pub severity: String,
+    pub message: String,
+    pub line: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct PersonaFile {
+    /// Path relative to soul_path — what save_persona_file takes
+    pub name: String,
+    /// "seed" or "soul"
+    pub kind: String,
+    pub content: String,
+    #[ts(type = "number")]
+    pub size: u64,
+    pub issues: Vec<ValidationIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct PersonaSaveResult {
+    pub valid: bool,
+    pub issues: Vec<ValidationIssue>,
+    pub saved: bool,
+    /// Whether the engine acknowledged a reload request
+    pub reloaded: bool,
+}
+
+fn issue(severity: &str, message: String, line: Option<usize>) -> ValidationIssue {
+    ValidationIssue {
+        severity: severity.to_string(),
+        message,
+        line,
+    }
+}
+
+/// Only SEED.md and markdown files directly inside the soul dir are persona
+/// files. The path is resolved by `safe_path::safe_resolve`.
+pub fn persona_path(soul_path: &Path, name: &str) -> Result<(PathBuf, &'static str), String> {
+    let relative = safe_path::relative(name)?;
+    let parts: Option<Vec<&str>> = relative
+        .components()
+        .map(|c| c.as_os_str().to_str())
+        .collect();
+    let kind = match parts.as_deref() {
+        Some(["SEED.md"]) => "seed",
+        Some([dir, file])
+            if Location::Soul.variants().contains(dir)
+                && file.ends_with(".md")
+                && !file.starts_with('.') =>
+        {
+            "soul"
+        }
+        _ => return Err(format!("Not a persona file: {}", name)),
+    };
+    let path = safe_path::safe_resolve(&OsFileStore, soul_path, name)?;
+    Ok((path, kind))
+}
+
+fn validate_seed(content: &str, issues: &mut Vec<ValidationIssue>) {
+    if !content.trim_start().starts_with("#SEED") {
+        issues.push(issue("error", "Missing #SEED header".to_string(), Some(1)));
+    }
+
+    for block in REQUIRED_BLOCKS {
+        if !content.contains(&format!("{}{{", block)) {
+            issues.push(issue("error", format!("Missing required block {}", block), None));
+        }
+    }
+
+    // Every @BLOCK{ needs its closing brace before the next block starts
+    let mut open: Option<(String, usize)> = None;
+    let mut depth = 0i32;
+    for (i, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('@') && trimmed.contains('{') {
+            if let Some((name, at)) = open.take() {
+                if depth > 0 {
+                    issues.push(issue("error", format!("{} is never closed", name), Some(at)));
+                }
+            }
+            let name = trimmed.split('{').next().unwrap_or("").to_string();
+            open = Some((name, i + 1));
+            depth = 0;
+        }
+        if open.is_some() {
+            depth += line.matches('{').count() as i32 - line.matches('}').count() as i32;
+        }
+    }
+    if let Some((name, at)) = open {
+        if depth > 0 {
+            issues.push(issue("error", format!("{} is never closed", name), Some(at)));
+        }
+    }
+
+    if content.len() > SEED_SOFT_LIMIT {
+        issues.push(issue(
+            "warning",
+            format!(
+                "Seed is {} bytes — condense it below {} bytes",
+                content.len(),
+                SEED_SOFT_LIMIT
+            ),
+            None,
+        ));
+    }
+}
+
+fn validate_soul_file(content: &str, issues: &mut Vec<ValidationIssue>) {
+    if content.trim().is_empty() {
+        issues.push(issue("error", "File is empty".to_string(), None));
+    } else if !content.lines().any(|l| l.starts_with('#')) {
+        issues.push(issue("warning", "No markdown heading".to_string(), None));
+    }
+}
+
+/// Structural checks for a persona file; errors block saving.
+pub fn validate(kind: &str, content: &str) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    if content.len() > PERSONA_MAX_BYTES {
+        issues.push(issue(
+            "error",
+            format!("File exceeds {} bytes", PERSONA_MAX_BYTES),
+            None,
+        ));
+    }
+
+    for (i, line) in content.lines().enumerate() {
+        for placeholder in FORBIDDEN_PLACEHOLDERS {
+            if line.contains(placeholder) {
+                issues.push(issue(
+                    "error",
+                    format!("Unfilled placeholder {}", placeholder),
+                    Some(i + 1),
+                ));
+            }
+        }
+    }
+
+    match kind {
+        "seed" => validate_seed(content, &mut issues),
+        _ => validate_soul_file(content, &mut issues),
+    }
+    issues
+}
+
+pub fn has_errors(issues: &[ValidationIssue]) -> bool {
+    issues.iter().any(|i| i.severity == "error")
+}
+
+/// SEED.md plus the soul detail files, each with its current validation result.
+pub fn list(soul_path: &Path) -> Vec<PersonaFile> {
+    let mut names = vec!["SEED.md".to_string()];
+    for dir in Location::Soul.variants() {
+        let Ok(entries) = fs::read_dir(soul_path.join(dir)) else {
+            continue;
+        };
+        let mut files: Vec<String> = entries
+            .flatten()
+            .filter_map(|e| e.file_name().to_str().map(|n| format!("{}/{}", dir, n)))
+            .collect();
+        files.sort();
+        names.extend(files);
+    }
+
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let (path, kind) = persona_path(soul_path, &name).ok()?;
+            let content = fs::read_to_string(&path).ok()?;
+            Some(PersonaFile {
+                issues: validate(kind, &content),
+                size: content.len() as u64,
+                kind: kind.to_string(),
+                name,
+                content,
+            })
+        })
+        .collect()
+}
+
+/// Write via a temp file so the engine never reads a half-written persona.
+pub fn write(path: &Path, content: &str) -> Result<(), String> {
+    let tmp = path.with_extension("md.tmp");
+    fs::write(&tmp, content).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
@@ -0,0 +1,246 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::backend::OsFileStore;
+use crate::blocking::CancelToken;
+use crate::safe_path;
+use crate::vault::Vault;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiKind {
+    Email,
+    CreditCard,
+    Phone,
+    Address,
+}
+
+impl PiiKind {
+    fn placeholder(self) -> &'static str {
+        match self {
+            PiiKind::Email => "[EMAIL]",
+            PiiKind::CreditCard => "[CARD]",
+            PiiKind::Phone => "[PHONE]",
+            PiiKind::Address => "[ADDRESS]",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct PiiFinding {
+    /// Path relative to soul_path
+    pub path: String,
+    /// 1-based line and column (in characters)
+    pub line: usize,
+    pub column: usize,
+    pub kind: PiiKind,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, TS)]
+#[ts(export)]
+pub struct PiiReport {
+    pub files_scanned: usize,
+    pub findings: Vec<PiiFinding>,
+    /// Files rewritten with placeholders (redaction mode)
+    pub redacted: Vec<String>,
+}
+
+/// Detectors in priority order — a match never overlaps an earlier one,
+/// so card numbers aren't reported again as phone numbers.
+fn detectors() -> &'static [(PiiKind, Regex)] {
+    static DETECTORS: OnceLock<Vec<(PiiKind, Regex)>> = OnceLock::new();
+    DETECTORS.get_or_init(|| {
+        let re = |p: &str| Regex::new(p).expect("invalid PII pattern");
+        vec![
+            (
+                PiiKind::Email,
+                re(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}"),
+            ),
+            (PiiKind::CreditCard, re(r"\b(?:\d[ -]?){12,18}\d\b")),
+            (PiiKind::Phone, re(r"(?:\+|\b)\d[\d /().-]{6,}\d\b")),
+            (
+                PiiKind::Address,
+                re(concat!(
+                    r"\b[A-ZÄÖÜ][\wäöüß-]*(?:straße|strasse|str\.|weg|gasse|allee|platz|ring|damm)",
+                    r"\s+\d+\s?[a-zA-Z]?\b",
+                    r"|\b\d+\s+[A-Z][a-z]+(?:\s[A-Z][a-z]+)?\s",
+                    r"(?:Street|St\.|Avenue|Ave\.|Road|Rd\.|Lane|Ln\.|Drive|Dr\.|Boulevard|Blvd\.)",
+                )),
+            ),
+        ]
+    })
+}
+
+fn digit_count(s: &str) -> usize {
+    s.chars().filter(|c| c.is_ascii_digit()).count()
+}
+
+/// Luhn checksum, to keep arbitrary long numbers out of the card findings.
+fn luhn(s: &str) -> bool {
+    let digits: Vec<u32> = s.chars().filter_map(|c| c.to_digit(10)).collect();
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match (i % 2 == 1, d * 2) {
+            (true, doubled) if doubled > 9 => doubled - 9,
+            (true, doubled) => doubled,
+            (false, _) => d,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+fn plausible(kind: PiiKind, text: &str) -> bool {
+    match kind {
+        PiiKind::CreditCard => luhn(text),
+        // 8–15 digits; ISO dates and times are not phone numbers
+        PiiKind::Phone => {
+            let digits = digit_count(text);
+            (8..=15).contains(&digits) && !text.contains(':') && !is_date_like(text)
+        }
+        _ => true,
+    }
+}
+
+fn is_date_like(text: &str) -> bool {
+    let parts: Vec<&str> = text.split(['-', '.', '/']).map(str::trim).collect();
+    parts.len() == 3
+        && parts
+            .iter()
+            .all(|p| p.len() <= 4 && p.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Byte ranges of every finding in one line, in order.
+fn scan_line(line: &str) -> Vec<(PiiKind, usize, usize)> {
+    let mut found: Vec<(PiiKind, usize, usize)> = Vec::new();
+    for (kind, re) in detectors() {
+        for m in re.find_iter(line) {
+            let overlaps = found.iter().any(|&(_, s, e)| m.start() < e && s < m.end());
+            if !overlaps && plausible(*kind, m.as_str()) {
+                found.push((*kind, m.start(), m.end()));
+            }
+        }
+    }
+    found.sort_by_key(|&(_, start, _)| start);
+    found
+}
+
+/// Replace every finding with its placeholder. Returns the redacted text and
+/// the findings as (line, column, kind, text).
+fn analyze(content: &str) -> (String, Vec<(usize, usize, PiiKind, String)>) {
+    let mut redacted = String::with_capacity(content.len());
+    let mut matches = Vec::new();
+    for (idx, line) in content.split_inclusive('\n').enumerate() {
+        let mut last = 0;
+        for (kind, start, end) in scan_line(line) {
+            let column = line[..start].chars().count() + 1;
+            matches.push((idx + 1, column, kind, line[start..end].to_string()));
+            redacted.push_str(&line[last..start]);
+            redacted.push_str(kind.placeholder());
+            last = end;
+        }
+        redacted.push_str(&line[last..]);
+    }
+    (redacted, matches)
+}
+
+/// Redacted copy of `content` and the number of replacements.
+pub fn redact(content: &str) -> (String, usize) {
+    let (redacted, matches) = analyze(content);
+    (redacted, matches.len())
+}
+
+fn markdown_files(root: &Path) -> Vec<PathBuf> {
+    if root.is_file() {
+        return vec![root.to_path_buf()];
+    }
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if path.is_dir() {
+                if !name.starts_with('.') && name != "node_modules" {
+                    dirs.push(path);
+                }
+            } else if name.ends_with(".md") {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Scan soul markdown under `scope` (relative to soul_path, whole soul if
+/// None). With `redact`, matches are replaced by placeholders in place;
+/// `before_write` is called with each rewritten file's new content.
+pub fn scan(
+    soul_path: &Path,
+    scope: Option<&str>,
+    redact: bool,
+    vault: &Vault,
+    token: &CancelToken,
+    before_write: impl Fn(&str, &str),
+) -> Result<PiiReport, String> {
+    let root = match scope {
+        Some(s) => safe_path::safe_resolve(&OsFileStore, soul_path, s)?,
+        None => soul_path.to_path_buf(),
+    };
+    if !root.exists() {
+        return Err(format!(
+            "{} does not exist",
+            scope.unwrap_or("Soul directory")
+        ));
+    }
+
+    let files = markdown_files(&root);
+    let total = files.len() as u64;
+    let mut report = PiiReport::default();
+    for (done, path) in files.iter().enumerate() {
+        token.check()?;
+        token.set_progress(done as u64, total);
+        let Ok(relative) = path.strip_prefix(soul_path) else {
+            continue;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let Ok(content) = vault.read(path) else {
+            continue;
+        };
+        report.files_scanned += 1;
+
+        let (redacted, matches) = analyze(&content);
+        let changed = !matches.is_empty();
+        report.findings.extend(
+            matches
+                .into_iter()
+                .map(|(line, column, kind, text)| PiiFinding {
+                    path: relative.clone(),
+                    line,
+                    column,
+                    kind,
+                    text,
+                }),
+        );
+
+        if redact && changed {
+            before_write(&relative, &redacted);
+            fs::write(path, vault.seal(&relative, &redacted)?).map_err(|e| e.to_string())?;
+            report.redacted.push(relative);
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,122 @@
+use std::fs;
+use std::path::Path;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::safe_path;
+
+/// Pinned files, kept in the soul so pins travel with it across moves,
+/// copies and syncs
+pub const PINS_FILE: &str = ".soul-pins.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PinsFile {
+    /// Paths relative to the soul, in pinning order
+    #[serde(default)]
+    pinned: Vec<String>,
+}
+
+fn read(soul_path: &Path) -> PinsFile {
+    fs::read_to_string(soul_path.join(PINS_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write(soul_path: &Path, pins: &PinsFile) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(pins).map_err(|e| e.to_string())?;
+    fs::write(soul_path.join(PINS_FILE), json + "\n").map_err(|e| e.to_string())
+}
+
+fn normalize(name: &str) -> Result<String, String> {
+    let name = name.replace('\\', "/").trim_matches('/').to_string();
+    if name.is_empty() {
+        return Err("Missing file name".to_string());
+    }
+    safe_path::relative(&name)?;
+    Ok(name)
+}
+
+/// The soul's quick-access files. Reads and writes are serialized so two
+/// pins at once don't lose one.
+#[derive(Default)]
+pub struct Pins {
+    lock: Mutex<()>,
+}
+
+impl Pins {
+    /// Pinned files that still exist, in pinning order.
+    pub fn list(&self, soul_path: &Path) -> Vec<String> {
+        let _guard = self.lock.lock();
+        existing(soul_path, read(soul_path).pinned)
+    }
+
+    /// Pin `name` (last). Pinning a pinned file changes nothing.
+    pub fn pin(
+        &self,
+        app: &AppHandle,
+        soul_path: &Path,
+        name: &str,
+    ) -> Result<Vec<String>, String> {
+        let name = normalize(name)?;
+        if !soul_path.join(&name).is_file() {
+            return Err(format!("No such file: {}", name));
+        }
+        self.update(app, soul_path, |pinned| {
+            if pinned.contains(&name) {
+                return false;
+            }
+            pinned.push(name);
+            true
+        })
+    }
+
+    pub fn unpin(
+        &self,
+        app: &AppHandle,
+        soul_path: &Path,
+        name: &str,
+    ) -> Result<Vec<String>, String> {
+        let name = normalize(name)?;
+        self.update(app, soul_path, |pinned| {
+            let before = pinned.len();
+            pinned.retain(|p| *p != name);
+            pinned.len() != before
+        })
+    }
+
+    /// Apply `change` to the stored pins; save and emit `pins:changed` if
+    /// it reports a change.
+    fn update(
+        &self,
+        app: &AppHandle,
+        soul_path: &Path,
+        change: impl FnOnce(&mut Vec<String>) -> bool,
+    ) -> Result<Vec<String>, String> {
+        let _guard = self.lock.lock();
+        let mut pins = read(soul_path);
+        if change(&mut pins.pinned) {
+            write(soul_path, &pins)?;
+            let list = existing(soul_path, pins.pinned);
+            let _ = app.emit("pins:changed", &list);
+            return Ok(list);
+        }
+        Ok(existing(soul_path, pins.pinned))
+    }
+}
+
+/// Pins of files that were deleted or moved away stay stored (they may
+/// come back with a sync) but aren't listed.
+fn existing(soul_path: &Path, pinned: Vec<String>) -> Vec<String> {
+    pinned
+        .into_iter()
+        .filter(|name| soul_path.join(name).is_file())
+        .collect()
+}
+
+/// Watcher hook: the pins file changed on disk (another machine synced it).
+pub fn changed(app: &AppHandle, soul_path: &Path) {
+    let _ = app.emit("pins:changed", existing(soul_path, read(soul_path).pinned));
+}
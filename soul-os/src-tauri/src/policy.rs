@@ -0,0 +1,307 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use ts_rs::TS;
+
+use crate::config::app_data_dir;
+
+pub const POLICY_FILE: &str = ".soul-policy.toml";
+/// Violations kept in memory for `get_policy()`
+const MAX_VIOLATIONS: usize = 100;
+
+/// What the engine may do to files matching a rule.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "lowercase")]
+pub enum WriteAllowance {
+    /// Any change (use with `blocks` to protect parts of a file)
+    Any,
+    /// Only appending to the existing content
+    Append,
+    /// No changes at all
+    #[default]
+    None,
+}
+
+/// One `[[rule]]` entry in .soul-policy.toml, e.g.
+///
+/// ```toml
+/// [[rule]]
+/// path = "seele/KERN.md"
+/// allow = "none"
+///
+/// [[rule]]
+/// path = "SEED.md"
+/// allow = "any"
+/// blocks = ["@KERN", "@SELF"]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct PolicyRule {
+    /// Path relative to soul_path; `*` matches within a segment, `**` across
+    pub path: String,
+    #[serde(default)]
+    pub allow: WriteAllowance,
+    /// Seed blocks that must stay unchanged
+    #[serde(default)]
+    pub blocks: Vec<String>,
+    /// Restore the last accepted content after quarantining a violation
+    #[serde(default = "default_true")]
+    pub revert: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PolicyFile {
+    #[serde(default, rename = "rule")]
+    rules: Vec<PolicyRule>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct PolicyViolation {
+    pub path: String,
+    pub rule: String,
+    pub reason: String,
+    /// Copy of the rejected content under <app_data_dir>/quarantine
+    pub quarantined: Option<String>,
+    pub reverted: bool,
+    #[ts(type = "number")]
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct PolicyInfo {
+    pub rules: Vec<PolicyRule>,
+    pub error: Option<String>,
+    pub violations: Vec<PolicyViolation>,
+}
+
+#[derive(Default)]
+struct PolicyInner {
+    rules: Vec<PolicyRule>,
+    error: Option<String>,
+    /// Last accepted content of every protected file (None = didn't exist)
+    baselines: HashMap<String, Option<String>>,
+    violations: VecDeque<PolicyViolation>,
+}
+
+/// Guards protected soul files against engine writes that break the policy.
+/// Changes made through the app are accepted up front via `accept()`.
+#[derive(Default)]
+pub struct PolicyEngine {
+    inner: RwLock<PolicyInner>,
+}
+
+/// Glob match on `/`-separated paths: `*` within a segment, `**` across segments.
+fn glob_match(pattern: &str, path: &str) -> bool {
+    fn segments(p: &[&str], s: &[&str]) -> bool {
+        match (p.first(), s.first()) {
+            (None, None) => true,
+            (Some(&"**"), _) => segments(&p[1..], s) || (!s.is_empty() && segments(p, &s[1..])),
+            (Some(pat), Some(seg)) => {
+                segment(pat.as_bytes(), seg.as_bytes()) && segments(&p[1..], &s[1..])
+            }
+            _ => false,
+        }
+    }
+    fn segment(p: &[u8], s: &[u8]) -> bool {
+        match (p.first(), s.first()) {
+            (None, None) => true,
+            (Some(b'*'), _) => segment(&p[1..], s) || (!s.is_empty() && segment(p, &s[1..])),
+            (Some(a), Some(b)) => a == b && segment(&p[1..], &s[1..]),
+            _ => false,
+        }
+    }
+    let p: Vec<&str> = pattern.split('/').collect();
+    let s: Vec<&str> = path.split('/').collect();
+    segments(&p, &s)
+}
+
+/// Content of `@NAME{ ... }` including nested sub-blocks.
+fn extract_block<'a>(content: &'a str, name: &str) -> Option<&'a str> {
+    let start = content.find(&format!("{}{{", name))?;
+    let mut depth = 0;
+    for (i, c) in content[start..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&content[start..start + i + 1]);
+                }
+            }
+            _ => {}
+        }
+    }
+    Some(&content[start..])
+}
+
+fn check_rule(rule: &PolicyRule, old: Option<&str>, new: Option<&str>) -> Option<String> {
+    match rule.allow {
+        WriteAllowance::None if old != new => {
+            return Some("file is read-only for the engine".to_string())
+        }
+        WriteAllowance::Append => {
+            let old = old.unwrap_or("");
+            match new {
+                Some(new) if new.starts_with(old) => {}
+                Some(_) => return Some("existing content was rewritten (append only)".to_string()),
+                None => return Some("file was deleted (append only)".to_string()),
+            }
+        }
+        _ => {}
+    }
+    for block in &rule.blocks {
+        let before = old.and_then(|c| extract_block(c, block));
+        let after = new.and_then(|c| extract_block(c, block));
+        if before != after {
+            return Some(format!("protected block {} was changed", block));
+        }
+    }
+    None
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn quarantine(relative: &str, content: &str) -> Option<String> {
+    let dir = app_data_dir().join("quarantine");
+    fs::create_dir_all(&dir).ok()?;
+    let path = dir.join(format!("{}-{}", now_ms(), relative.replace('/', "__")));
+    fs::write(&path, content).ok()?;
+    Some(path.to_string_lossy().to_string())
+}
+
+impl PolicyEngine {
+    /// (Re)load .soul-policy.toml and snapshot every file a rule covers.
+    pub fn load(&self, soul_path: &Path) {
+        let (rules, error) = match fs::read_to_string(soul_path.join(POLICY_FILE)) {
+            Ok(content) => match toml::from_str::<PolicyFile>(&content) {
+                Ok(file) => (file.rules, None),
+                Err(e) => (Vec::new(), Some(format!("Invalid {}: {}", POLICY_FILE, e))),
+            },
+            Err(_) => (Vec::new(), None),
+        };
+
+        let mut baselines = HashMap::new();
+        if !rules.is_empty() {
+            let mut dirs = vec![soul_path.to_path_buf()];
+            while let Some(dir) = dirs.pop() {
+                let Ok(entries) = fs::read_dir(&dir) else {
+                    continue;
+                };
+                for entry in entries.flatten() {
+                    let path = entry.path();
+                    let Some(relative) = relative_path(soul_path, &path) else {
+                        continue;
+                    };
+                    if path.is_dir() {
+                        if !relative.starts_with(".git") && relative != "node_modules" {
+                            dirs.push(path);
+                        }
+                    } else if rules.iter().any(|r| glob_match(&r.path, &relative)) {
+                        baselines.insert(relative, fs::read_to_string(&path).ok());
+                    }
+                }
+            }
+        }
+
+        let mut inner = self.inner.write();
+        inner.rules = rules;
+        inner.error = error;
+        inner.baselines = baselines;
+    }
+
+    /// Record a change made through the app so it isn't treated as a violation.
+    pub fn accept(&self, relative: &str, content: Option<&str>) {
+        let mut inner = self.inner.write();
+        if inner.rules.iter().any(|r| glob_match(&r.path, relative)) {
+            inner
+                .baselines
+                .insert(relative.to_string(), content.map(|c| c.to_string()));
+        }
+    }
+
+    /// Check a changed file against the policy. Violations are quarantined,
+    /// optionally reverted, and reported via `policy:violation`.
+    pub fn check(&self, app: &AppHandle, soul_path: &Path, relative: &str) {
+        let Some(rule) = self
+            .inner
+            .read()
+            .rules
+            .iter()
+            .find(|r| glob_match(&r.path, relative))
+            .cloned()
+        else {
+            return;
+        };
+
+        let path = soul_path.join(relative);
+        let new = fs::read_to_string(&path).ok();
+        let old = self.inner.read().baselines.get(relative).cloned().flatten();
+
+        let Some(reason) = check_rule(&rule, old.as_deref(), new.as_deref()) else {
+            self.inner
+                .write()
+                .baselines
+                .insert(relative.to_string(), new);
+            return;
+        };
+
+        let quarantined = new.as_deref().and_then(|c| quarantine(relative, c));
+        let reverted = rule.revert && restore(&path, old.as_deref());
+
+        let violation = PolicyViolation {
+            path: relative.to_string(),
+            rule: rule.path.clone(),
+            reason,
+            quarantined,
+            reverted,
+            timestamp: now_ms(),
+        };
+        {
+            let mut inner = self.inner.write();
+            inner.violations.push_back(violation.clone());
+            while inner.violations.len() > MAX_VIOLATIONS {
+                inner.violations.pop_front();
+            }
+        }
+        let _ = app.emit("policy:violation", violation);
+    }
+
+    pub fn info(&self) -> PolicyInfo {
+        let inner = self.inner.read();
+        PolicyInfo {
+            rules: inner.rules.clone(),
+            error: inner.error.clone(),
+            violations: inner.violations.iter().cloned().collect(),
+        }
+    }
+}
+
+fn restore(path: &Path, content: Option<&str>) -> bool {
+    match content {
+        Some(content) => fs::write(path, content).is_ok(),
+        None => fs::remove_file(path).is_ok(),
+    }
+}
+
+fn relative_path(soul_path: &Path, path: &Path) -> Option<String> {
+    path.strip_prefix(soul_path)
+        .ok()
+        .map(|r| r.to_string_lossy().replace('\\', "/"))
+}
@@ -0,0 +1,270 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use ts_rs::TS;
+
+use crate::background;
+use crate::config::AppConfig;
+use crate::proxy::EngineProxy;
+use crate::watcher::WatcherState;
+
+/// Profile used when nothing is configured; keeps the configured values
+const DEFAULT_PROFILE: &str = "balanced";
+const DEFAULT_TRAY_INTERVAL: Duration = Duration::from_millis(1500);
+const DEFAULT_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// How often auto mode checks the power source
+const POWER_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Interval overrides applied at runtime. `None` keeps the value from the
+/// watcher/monitor config (or the built-in default).
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct RuntimeProfile {
+    pub name: String,
+    /// Tray icon breathing frame interval
+    #[ts(type = "number | null")]
+    pub tray_interval_ms: Option<u64>,
+    /// Poll interval for watch roots on network volumes (new watchers only)
+    #[ts(type = "number | null")]
+    pub watcher_poll_interval_ms: Option<u64>,
+    pub snapshot_fps: Option<u32>,
+    /// Engine watchdog check interval
+    #[ts(type = "number | null")]
+    pub health_check_interval_ms: Option<u64>,
+    /// Monitor push polling interval
+    #[ts(type = "number | null")]
+    pub monitor_interval_ms: Option<u64>,
+}
+
+fn builtin_profiles() -> Vec<RuntimeProfile> {
+    vec![
+        RuntimeProfile {
+            name: "performance".to_string(),
+            tray_interval_ms: Some(1000),
+            watcher_poll_interval_ms: Some(1000),
+            snapshot_fps: Some(30),
+            health_check_interval_ms: Some(2000),
+            monitor_interval_ms: Some(1000),
+        },
+        RuntimeProfile {
+            name: DEFAULT_PROFILE.to_string(),
+            tray_interval_ms: None,
+            watcher_poll_interval_ms: None,
+            snapshot_fps: None,
+            health_check_interval_ms: None,
+            monitor_interval_ms: None,
+        },
+        RuntimeProfile {
+            name: "battery_saver".to_string(),
+            tray_interval_ms: Some(4000),
+            watcher_poll_interval_ms: Some(10_000),
+            snapshot_fps: Some(4),
+            health_check_interval_ms: Some(30_000),
+            monitor_interval_ms: Some(10_000),
+        },
+    ]
+}
+
+/// Selected profile, persisted in the app config.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileConfig {
+    pub name: String,
+    /// Follow the power source: battery_saver on battery, performance on AC
+    #[serde(default)]
+    pub auto: bool,
+}
+
+impl Default for ProfileConfig {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_PROFILE.to_string(),
+            auto: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ProfileStatus {
+    pub active: RuntimeProfile,
+    pub auto: bool,
+    /// None when the power source can't be determined (desktops, unsupported OS)
+    pub on_battery: Option<bool>,
+    pub profiles: Vec<RuntimeProfile>,
+}
+
+/// Coordinates the runtime profile across tray, watcher, watchdog and monitor.
+pub struct Profiles {
+    config: RwLock<ProfileConfig>,
+    active: RwLock<RuntimeProfile>,
+    on_battery: RwLock<Option<bool>>,
+}
+
+fn find(name: &str) -> Option<RuntimeProfile> {
+    builtin_profiles().into_iter().find(|p| p.name == name)
+}
+
+impl Profiles {
+    pub fn new(config: ProfileConfig) -> Self {
+        let active = find(&config.name).unwrap_or_else(|| find(DEFAULT_PROFILE).unwrap());
+        Self {
+            config: RwLock::new(config),
+            active: RwLock::new(active),
+            on_battery: RwLock::new(None),
+        }
+    }
+
+    pub fn config(&self) -> ProfileConfig {
+        self.config.read().clone()
+    }
+
+    pub fn tray_interval(&self) -> Duration {
+        self.active
+            .read()
+            .tray_interval_ms
+            .map_or(DEFAULT_TRAY_INTERVAL, Duration::from_millis)
+    }
+
+    pub fn health_check_interval(&self) -> Duration {
+        self.active
+            .read()
+            .health_check_interval_ms
+            .map_or(DEFAULT_HEALTH_CHECK_INTERVAL, Duration::from_millis)
+    }
+
+    pub fn status(&self) -> ProfileStatus {
+        ProfileStatus {
+            active: self.active.read().clone(),
+            auto: self.config.read().auto,
+            on_battery: *self.on_battery.read(),
+            profiles: builtin_profiles(),
+        }
+    }
+
+    /// Select a profile by name, or "auto" to follow the power source.
+    pub fn select(&self, app: &AppHandle, name: &str) -> Result<(), String> {
+        if name == "auto" {
+            self.config.write().auto = true;
+            self.follow_power_source(app);
+            return Ok(());
+        }
+        let profile = find(name).ok_or_else(|| format!("Unknown profile: {}", name))?;
+        *self.config.write() = ProfileConfig {
+            name: name.to_string(),
+            auto: false,
+        };
+        self.activate(app, profile);
+        Ok(())
+    }
+
+    fn activate(&self, app: &AppHandle, profile: RuntimeProfile) {
+        *self.active.write() = profile;
+        apply(app);
+        let _ = app.emit("profile:changed", self.status());
+    }
+
+    /// In auto mode, switch profiles when the power source changed.
+    fn follow_power_source(&self, app: &AppHandle) {
+        let on_battery = on_battery();
+        *self.on_battery.write() = on_battery;
+        if !self.config.read().auto {
+            return;
+        }
+        let name = match on_battery {
+            Some(true) => "battery_saver",
+            Some(false) => "performance",
+            None => DEFAULT_PROFILE,
+        };
+        if self.active.read().name != name {
+            if let Some(profile) = find(name) {
+                self.activate(app, profile);
+            }
+        }
+    }
+}
+
+/// Push the active profile's overrides on top of the configured watcher and
+/// monitor settings. Called on every profile switch and after either config
+/// is changed.
+pub fn apply(app: &AppHandle) {
+    let Some(profiles) = app.try_state::<Arc<Profiles>>() else {
+        return;
+    };
+    let profile = profiles.active.read().clone();
+    let config = app.state::<Arc<RwLock<AppConfig>>>().read().clone();
+
+    if let Some(state) = app.try_state::<WatcherState>() {
+        let mut watcher = config.watcher;
+        if let Some(fps) = profile.snapshot_fps {
+            watcher.snapshot_fps = fps;
+        }
+        if let Some(interval) = profile.watcher_poll_interval_ms {
+            watcher.network_poll_interval_ms = interval;
+        }
+        state.set_config(watcher);
+    }
+    if let Some(proxy) = app.try_state::<Arc<EngineProxy>>() {
+        let mut monitor = config.monitor;
+        if let Some(interval) = profile.monitor_interval_ms {
+            monitor.push_interval_ms = interval;
+        }
+        proxy.set_monitor_config(monitor);
+    }
+}
+
+/// Whether the machine runs on battery; None if unknown.
+#[cfg(target_os = "macos")]
+fn on_battery() -> Option<bool> {
+    let output = std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let first = text.lines().next()?;
+    Some(first.contains("Battery Power"))
+}
+
+/// Whether the machine runs on battery; None if unknown.
+#[cfg(target_os = "linux")]
+fn on_battery() -> Option<bool> {
+    let mut has_battery = false;
+    for entry in std::fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+        let path = entry.path();
+        let kind = std::fs::read_to_string(path.join("type")).unwrap_or_default();
+        match kind.trim() {
+            "Mains" | "USB" => {
+                let online = std::fs::read_to_string(path.join("online")).unwrap_or_default();
+                if online.trim() == "1" {
+                    return Some(false);
+                }
+            }
+            "Battery" => has_battery = true,
+            _ => {}
+        }
+    }
+    has_battery.then_some(true)
+}
+
+/// Whether the machine runs on battery; None if unknown.
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn on_battery() -> Option<bool> {
+    None
+}
+
+/// Apply the configured profile once the subsystems are up and keep
+/// following the power source while auto mode is on.
+pub fn start_profiles(app: AppHandle) {
+    std::thread::spawn(move || {
+        let profiles = app.state::<Arc<Profiles>>().inner().clone();
+        apply(&app);
+        loop {
+            if !background::suspended(&app) {
+                profiles.follow_power_source(&app);
+            }
+            std::thread::sleep(POWER_CHECK_INTERVAL);
+        }
+    });
+}
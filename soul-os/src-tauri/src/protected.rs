@@ -0,0 +1,339 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::safe_path;
+
+/// Paths that need a confirmation to write, kept in the soul so the list
+/// travels with it
+pub const PROTECTED_FILE: &str = ".soul-protected.json";
+/// Error prefix for a write to a protected path without a valid
+/// confirmation. The frontend matches on it (`isProtected`).
+pub const PROTECTED: &str = "Protected";
+/// How long a write confirmation can be used
+const CONFIRMATION_TTL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ProtectedFile {
+    /// Relative to the soul; a trailing `/` protects a whole directory
+    #[serde(default)]
+    paths: Vec<String>,
+}
+
+/// Permission for one write to one protected file.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct WriteConfirmation {
+    /// Pass to `write_soul_file` as `confirmation_token` (once)
+    pub token: String,
+    pub name: String,
+    #[ts(type = "number")]
+    pub expires_in_secs: u64,
+}
+
+/// `name` with the components `safe_path::relative` keeps, joined by `/`:
+/// `./a`, `a/`, `a/.` and `a//b` all name the same file. None for names
+/// outside the soul.
+fn normalize(name: &str) -> Option<String> {
+    let relative = safe_path::relative(name).ok()?;
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    Some(parts.join("/"))
+}
+
+/// Whether the filesystem holding the soul ignores case, probed by looking
+/// the soul directory up with its name's case flipped. Names without
+/// letters fall back to the platform default.
+fn folds_case(soul_path: &Path) -> bool {
+    let default = cfg!(any(target_os = "macos", windows));
+    let Some(name) = soul_path.file_name().and_then(|n| n.to_str()) else {
+        return default;
+    };
+    let flipped: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_uppercase() {
+                c.to_ascii_lowercase()
+            } else {
+                c.to_ascii_uppercase()
+            }
+        })
+        .collect();
+    if flipped == name {
+        return default;
+    }
+    match (
+        fs::metadata(soul_path),
+        fs::metadata(soul_path.with_file_name(flipped)),
+    ) {
+        #[cfg(unix)]
+        (Ok(real), Ok(probe)) => {
+            use std::os::unix::fs::MetadataExt;
+            real.dev() == probe.dev() && real.ino() == probe.ino()
+        }
+        #[cfg(not(unix))]
+        (Ok(_), Ok(_)) => true,
+        _ => false,
+    }
+}
+
+/// `name` as protected paths are compared: normalized, and lowercased when
+/// `fold` says the filesystem ignores case.
+fn key(name: &str, fold: bool) -> Option<String> {
+    normalize(name).map(|name| if fold { name.to_lowercase() } else { name })
+}
+
+/// Whether `paths` (the protected list) covers `name`. Names that can't be
+/// normalized count as protected.
+fn protects(paths: &[String], name: &str, fold: bool) -> bool {
+    let Some(name) = key(name, fold) else {
+        return true;
+    };
+    key(PROTECTED_FILE, fold).as_ref() == Some(&name)
+        || paths.iter().any(|path| match path.strip_suffix('/') {
+            Some(dir) => key(dir, fold)
+                .is_some_and(|dir| !dir.is_empty() && name.starts_with(&format!("{}/", dir))),
+            None => key(path, fold).is_some_and(|path| path == name),
+        })
+}
+
+pub fn list(soul_path: &Path) -> Vec<String> {
+    fs::read_to_string(soul_path.join(PROTECTED_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str::<ProtectedFile>(&content).ok())
+        .map(|file| file.paths)
+        .unwrap_or_default()
+}
+
+/// Replace the protected-paths list.
+pub fn save(soul_path: &Path, paths: Vec<String>) -> Result<Vec<String>, String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for path in paths {
+        let path = path.trim();
+        let dir = path.ends_with('/') || path.ends_with('\\');
+        let Some(mut path) = normalize(path) else {
+            return Err(format!("Not a path inside the soul: {}", path));
+        };
+        if path.is_empty() {
+            continue;
+        }
+        if dir {
+            path.push('/');
+        }
+        if !cleaned.contains(&path) {
+            cleaned.push(path);
+        }
+    }
+    let json = serde_json::to_string_pretty(&ProtectedFile {
+        paths: cleaned.clone(),
+    })
+    .map_err(|e| e.to_string())?;
+    fs::write(soul_path.join(PROTECTED_FILE), json + "\n").map_err(|e| e.to_string())?;
+    Ok(cleaned)
+}
+
+/// Whether writing `name` needs a confirmation. The list itself is always
+/// protected, so it can't be emptied by a casual write.
+pub fn is_protected(soul_path: &Path, name: &str) -> bool {
+    protects(&list(soul_path), name, folds_case(soul_path))
+}
+
+struct Pending {
+    soul_path: PathBuf,
+    name: String,
+    reason: String,
+    issued: Instant,
+}
+
+/// Confirmation tokens handed out and not yet used.
+#[derive(Default)]
+pub struct WriteConfirmations {
+    pending: Mutex<HashMap<String, Pending>>,
+}
+
+impl WriteConfirmations {
+    /// Issue a token for one write of `name`, recording why.
+    pub fn request(
+        &self,
+        soul_path: &Path,
+        name: &str,
+        reason: &str,
+    ) -> Result<WriteConfirmation, String> {
+        let name =
+            normalize(name).ok_or_else(|| format!("Not a path inside the soul: {}", name))?;
+        if reason.trim().is_empty() {
+            return Err("Give a reason for changing a protected file".to_string());
+        }
+        if !is_protected(soul_path, &name) {
+            return Err(format!("{} is not protected", name));
+        }
+        let mut bytes = [0u8; 16];
+        OsRng.fill_bytes(&mut bytes);
+        let token: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+
+        let mut pending = self.pending.lock();
+        pending.retain(|_, p| p.issued.elapsed() < CONFIRMATION_TTL);
+        pending.insert(
+            token.clone(),
+            Pending {
+                soul_path: soul_path.to_path_buf(),
+                name: name.clone(),
+                reason: reason.trim().to_string(),
+                issued: Instant::now(),
+            },
+        );
+        Ok(WriteConfirmation {
+            token,
+            name,
+            expires_in_secs: CONFIRMATION_TTL.as_secs(),
+        })
+    }
+
+    /// Let a write of `name` through. Protected paths need a token issued
+    /// for exactly this file; it is used up. Returns the confirmed reason.
+    pub fn guard(
+        &self,
+        soul_path: &Path,
+        name: &str,
+        token: Option<&str>,
+    ) -> Result<Option<String>, String> {
+        if !is_protected(soul_path, name) {
+            return Ok(None);
+        }
+        let fold = folds_case(soul_path);
+        let name = normalize(name).unwrap_or_else(|| name.to_string());
+        let refused = || {
+            format!(
+                "{}: {} is protected; request a write confirmation first",
+                PROTECTED, name
+            )
+        };
+        let token = token.ok_or_else(refused)?;
+        match self.pending.lock().remove(token) {
+            Some(p)
+                if key(&p.name, fold) == key(&name, fold)
+                    && p.soul_path == soul_path
+                    && p.issued.elapsed() < CONFIRMATION_TTL =>
+            {
+                Ok(Some(p.reason))
+            }
+            _ => Err(refused()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths() -> Vec<String> {
+        vec!["SEED.md".to_string(), "memories/".to_string()]
+    }
+
+    #[test]
+    fn protects_every_spelling_of_a_path() {
+        for name in [
+            "SEED.md",
+            "./SEED.md",
+            "SEED.md/",
+            "SEED.md/.",
+            "./SEED.md/./",
+            "memories/x.md",
+            "memories//x.md",
+            "./memories/./x.md",
+            "memories\\x.md",
+            "memories/deep/x.md",
+            PROTECTED_FILE,
+            "./.soul-protected.json/",
+        ] {
+            assert!(protects(&paths(), name, false), "{}", name);
+        }
+        for name in [
+            "SEED.md.bak",
+            "memories.md",
+            "notes/SEED.md",
+            "memoriesx/a.md",
+        ] {
+            assert!(!protects(&paths(), name, false), "{}", name);
+        }
+    }
+
+    #[test]
+    fn names_outside_the_soul_count_as_protected() {
+        for name in ["../SEED.md", "/etc/passwd", "C:\\soul\\SEED.md", "a\0b"] {
+            assert!(protects(&[], name, false), "{}", name);
+        }
+    }
+
+    #[test]
+    fn folds_case_when_the_filesystem_does() {
+        for name in [
+            "seed.md",
+            "Seed.MD/",
+            "MEMORIES/x.md",
+            ".Soul-Protected.json",
+        ] {
+            assert!(protects(&paths(), name, true), "{}", name);
+            assert!(!protects(&paths(), name, false), "{}", name);
+        }
+    }
+
+    #[test]
+    fn probes_the_soul_filesystem_for_case() {
+        let dir = std::env::temp_dir().join(format!("soulos-protected-{}", std::process::id()));
+        let soul = dir.join("Soul");
+        fs::create_dir_all(&soul).unwrap();
+        let insensitive = dir.join("sOUL").is_dir();
+        assert_eq!(folds_case(&soul), insensitive);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn save_normalizes_the_list() {
+        let dir =
+            std::env::temp_dir().join(format!("soulos-protected-save-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let saved = save(
+            &dir,
+            vec![
+                "./SEED.md".to_string(),
+                "SEED.md/.".to_string(),
+                "memories\\".to_string(),
+                "  ".to_string(),
+            ],
+        )
+        .unwrap();
+        assert_eq!(saved, vec!["SEED.md", "memories/"]);
+        assert!(save(&dir, vec!["../outside.md".to_string()]).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn a_confirmation_covers_other_spellings_of_its_file() {
+        let dir =
+            std::env::temp_dir().join(format!("soulos-protected-guard-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        save(&dir, paths()).unwrap();
+        let confirmations = WriteConfirmations::default();
+        assert!(confirmations.guard(&dir, "SEED.md/.", None).is_err());
+        let confirmation = confirmations.request(&dir, "./SEED.md", "rewrite").unwrap();
+        assert_eq!(confirmation.name, "SEED.md");
+        let reason = confirmations
+            .guard(&dir, "SEED.md/", Some(&confirmation.token))
+            .unwrap();
+        assert_eq!(reason.as_deref(), Some("rewrite"));
+        // Used up
+        assert!(confirmations
+            .guard(&dir, "SEED.md", Some(&confirmation.token))
+            .is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
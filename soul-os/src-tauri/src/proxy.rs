@@ -0,0 +1,612 @@
+use std::collections::HashMap;
+use std::fs;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::oneshot;
+use ts_rs::TS;
+
+use crate::background;
+use crate::config::AppConfig;
+use crate::http::{self, Http, Policy};
+
+type ProxyResult = Result<serde_json::Value, String>;
+
+/// Engine endpoint backing the subsystem monitor widgets
+pub const MONITOR_PATH: &str = "/api/monitor";
+
+/// Reads are retried once, e.g. while the engine restarts
+const ENGINE_READ: Policy = Policy {
+    name: "engine_read",
+    timeout: Duration::from_secs(2),
+    retries: 1,
+    backoff: Duration::from_millis(250),
+    idempotent: true,
+};
+const ENGINE_WRITE: Policy = Policy {
+    name: "engine_write",
+    timeout: Duration::from_secs(2),
+    retries: 1,
+    backoff: Duration::from_millis(250),
+    idempotent: false,
+};
+
+/// Limits applied per engine endpoint (token bucket + bounded wait queue).
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ProxyLimits {
+    /// Sustained requests per second per endpoint
+    pub requests_per_sec: f64,
+    /// Requests allowed back-to-back before throttling kicks in
+    pub burst: u32,
+    /// Requests allowed to wait for a slot — everything beyond is dropped
+    pub max_queue: usize,
+}
+
+impl Default for ProxyLimits {
+    fn default() -> Self {
+        Self {
+            requests_per_sec: 4.0,
+            burst: 8,
+            max_queue: 16,
+        }
+    }
+}
+
+/// Caching and push behaviour for the engine monitor endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct MonitorConfig {
+    /// How long a /api/monitor response is served from cache
+    #[ts(type = "number")]
+    pub cache_ttl_ms: u64,
+    /// Poll once in the backend and broadcast `engine:subsystems` events
+    pub push: bool,
+    #[ts(type = "number")]
+    pub push_interval_ms: u64,
+}
+
+impl Default for MonitorConfig {
+    fn default() -> Self {
+        Self {
+            cache_ttl_ms: 1500,
+            push: false,
+            push_interval_ms: 2000,
+        }
+    }
+}
+
+struct CachedResponse {
+    value: serde_json::Value,
+    fetched_at: Instant,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+    queued: usize,
+}
+
+impl Bucket {
+    fn refill(&mut self, limits: &ProxyLimits, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limits.requests_per_sec).min(limits.burst as f64);
+        self.last_refill = now;
+    }
+}
+
+/// A place in an endpoint's wait queue. Given back when dropped, so a
+/// request cancelled while waiting doesn't keep it.
+struct QueueSlot<'a> {
+    buckets: &'a Mutex<HashMap<String, Bucket>>,
+    endpoint: &'a str,
+}
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        if let Some(bucket) = self.buckets.lock().get_mut(self.endpoint) {
+            bucket.queued = bucket.queued.saturating_sub(1);
+        }
+    }
+}
+
+/// The request that fetches for every identical GET joining it. Dropping
+/// it without `finish` (the leader was cancelled) removes the in-flight
+/// entry; the waiters then retry on their own.
+struct Leader<'a> {
+    in_flight: &'a Mutex<HashMap<String, Vec<oneshot::Sender<ProxyResult>>>>,
+    key: Option<String>,
+}
+
+impl Leader<'_> {
+    fn finish(mut self, result: &ProxyResult) {
+        let Some(key) = self.key.take() else {
+            return;
+        };
+        let waiters = self.in_flight.lock().remove(&key).unwrap_or_default();
+        for tx in waiters {
+            let _ = tx.send(result.clone());
+        }
+    }
+}
+
+impl Drop for Leader<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.in_flight.lock().remove(&key);
+        }
+    }
+}
+
+/// Why a request got no token.
+#[derive(Debug, PartialEq)]
+struct QueueFull {
+    queued: usize,
+    max_queue: usize,
+}
+
+/// Proxy for the soul-engine HTTP API.
+/// Every call goes through a per-endpoint rate limiter; identical GETs that
+/// are already in flight are coalesced into a single upstream request, and
+/// monitor responses are cached briefly (revalidated via ETag/Last-Modified).
+pub struct EngineProxy {
+    limits: RwLock<ProxyLimits>,
+    monitor: RwLock<MonitorConfig>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+    in_flight: Mutex<HashMap<String, Vec<oneshot::Sender<ProxyResult>>>>,
+    cache: Mutex<HashMap<String, CachedResponse>>,
+}
+
+impl EngineProxy {
+    pub fn new(limits: ProxyLimits, monitor: MonitorConfig) -> Self {
+        Self {
+            limits: RwLock::new(limits),
+            monitor: RwLock::new(monitor),
+            buckets: Mutex::new(HashMap::new()),
+            in_flight: Mutex::new(HashMap::new()),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn set_limits(&self, limits: ProxyLimits) {
+        *self.limits.write() = limits;
+    }
+
+    pub fn set_monitor_config(&self, monitor: MonitorConfig) {
+        *self.monitor.write() = monitor;
+    }
+
+    fn cache_ttl(&self, path: &str) -> Duration {
+        if endpoint_key(path) == MONITOR_PATH {
+            Duration::from_millis(self.monitor.read().cache_ttl_ms)
+        } else {
+            Duration::ZERO
+        }
+    }
+
+    /// GET an engine endpoint, joining an identical in-flight request if there is one.
+    pub async fn get(&self, app: &AppHandle, soul_path: &Path, path: &str) -> ProxyResult {
+        let ttl = self.cache_ttl(path);
+        if let Some(cached) = self.cache.lock().get(path) {
+            if cached.fetched_at.elapsed() < ttl {
+                return Ok(cached.value.clone());
+            }
+        }
+
+        self.coalesce(path, || self.fetch_conditional(app, soul_path, path, ttl))
+            .await
+    }
+
+    /// Run `fetch` for `key` unless the same key is already being fetched,
+    /// in which case its result is shared.
+    async fn coalesce<F, Fut>(&self, key: &str, fetch: F) -> ProxyResult
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = ProxyResult>,
+    {
+        loop {
+            let waiter = {
+                let mut in_flight = self.in_flight.lock();
+                match in_flight.get_mut(key) {
+                    Some(waiters) => {
+                        let (tx, rx) = oneshot::channel();
+                        waiters.push(tx);
+                        Some(rx)
+                    }
+                    None => {
+                        in_flight.insert(key.to_string(), Vec::new());
+                        None
+                    }
+                }
+            };
+            let Some(rx) = waiter else {
+                let leader = Leader {
+                    in_flight: &self.in_flight,
+                    key: Some(key.to_string()),
+                };
+                let result = fetch().await;
+                leader.finish(&result);
+                return result;
+            };
+            // An error here means the leader was cancelled: fetch again
+            if let Ok(result) = rx.await {
+                return result;
+            }
+        }
+    }
+
+    /// Upstream GET that revalidates a stale cache entry instead of refetching it.
+    async fn fetch_conditional(
+        &self,
+        app: &AppHandle,
+        soul_path: &Path,
+        path: &str,
+        ttl: Duration,
+    ) -> ProxyResult {
+        let validators = self
+            .cache
+            .lock()
+            .get(path)
+            .map(|c| (c.etag.clone(), c.last_modified.clone()));
+
+        let mut headers = Vec::new();
+        if let Some((etag, last_modified)) = validators {
+            if let Some(etag) = etag {
+                headers.push(("If-None-Match", etag));
+            }
+            if let Some(last_modified) = last_modified {
+                headers.push(("If-Modified-Since", last_modified));
+            }
+        }
+
+        let resp = self
+            .request(app, soul_path, reqwest::Method::GET, path, None, headers)
+            .await?;
+
+        if resp.status() == reqwest::StatusCode::NOT_MODIFIED {
+            let mut cache = self.cache.lock();
+            if let Some(cached) = cache.get_mut(path) {
+                cached.fetched_at = Instant::now();
+                return Ok(cached.value.clone());
+            }
+            return Err("Engine returned 304 without a cached response".to_string());
+        }
+        if !resp.status().is_success() {
+            return Err(format!("Engine returned {}", resp.status()));
+        }
+
+        let header = |name: reqwest::header::HeaderName| {
+            resp.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_string())
+        };
+        let etag = header(reqwest::header::ETAG);
+        let last_modified = header(reqwest::header::LAST_MODIFIED);
+
+        let value: serde_json::Value = resp
+            .json()
+            .await
+            .map_err(|e| format!("Invalid JSON: {}", e))?;
+
+        if !ttl.is_zero() {
+            self.cache.lock().insert(
+                path.to_string(),
+                CachedResponse {
+                    value: value.clone(),
+                    fetched_at: Instant::now(),
+                    etag,
+                    last_modified,
+                },
+            );
+        }
+
+        Ok(value)
+    }
+
+    /// Rate-limited request to the engine API (no coalescing).
+    pub async fn send(
+        &self,
+        app: &AppHandle,
+        soul_path: &Path,
+        method: &str,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> ProxyResult {
+        let method = reqwest::Method::from_bytes(method.as_bytes())
+            .map_err(|_| format!("Invalid HTTP method: {}", method))?;
+        let resp = self
+            .request(app, soul_path, method, path, body, Vec::new())
+            .await?;
+
+        if !resp.status().is_success() {
+            return Err(format!("Engine returned {}", resp.status()));
+        }
+
+        resp.json()
+            .await
+            .map_err(|e| format!("Invalid JSON: {}", e))
+    }
+
+    async fn request(
+        &self,
+        app: &AppHandle,
+        soul_path: &Path,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<serde_json::Value>,
+        headers: Vec<(&str, String)>,
+    ) -> Result<reqwest::Response, String> {
+        self.acquire(app, endpoint_key(path)).await?;
+
+        let (port, api_key) = engine_connection(soul_path);
+        let url = format!("http://127.0.0.1:{}{}", port, path);
+        let client = app.state::<Arc<Http>>().local(&url)?;
+        let policy = if method == reqwest::Method::GET {
+            &ENGINE_READ
+        } else {
+            &ENGINE_WRITE
+        };
+
+        http::send(app, policy, || {
+            let mut req = client.request(method.clone(), &url);
+            if !api_key.is_empty() {
+                req = req.header("Authorization", format!("Bearer {}", api_key));
+            }
+            for (name, value) in &headers {
+                req = req.header(*name, value);
+            }
+            if let Some(body) = &body {
+                req = req.json(body);
+            }
+            req
+        })
+        .await
+        .map_err(|e| format!("Engine unreachable: {}", e))
+    }
+
+    /// Wait for a token in the endpoint's bucket.
+    /// Fails immediately (and emits `proxy:throttled`) when the wait queue is full.
+    async fn acquire(&self, app: &AppHandle, endpoint: &str) -> Result<(), String> {
+        self.take_token(endpoint).await.map_err(|full| {
+            let _ = app.emit(
+                "proxy:throttled",
+                serde_json::json!({
+                    "endpoint": endpoint,
+                    "queued": full.queued,
+                    "max_queue": full.max_queue,
+                }),
+            );
+            format!("Too many requests to {} — dropped", endpoint)
+        })
+    }
+
+    async fn take_token(&self, endpoint: &str) -> Result<(), QueueFull> {
+        let mut slot: Option<QueueSlot> = None;
+        loop {
+            let wait = {
+                let limits = self.limits.read().clone();
+                let mut buckets = self.buckets.lock();
+                let bucket = buckets.entry(endpoint.to_string()).or_insert_with(|| Bucket {
+                    tokens: limits.burst as f64,
+                    last_refill: Instant::now(),
+                    queued: 0,
+                });
+                bucket.refill(&limits, Instant::now());
+
+                if bucket.tokens >= 1.0 {
+                    bucket.tokens -= 1.0;
+                    None
+                } else {
+                    if slot.is_none() {
+                        if bucket.queued >= limits.max_queue {
+                            return Err(QueueFull {
+                                queued: bucket.queued,
+                                max_queue: limits.max_queue,
+                            });
+                        }
+                        bucket.queued += 1;
+                    }
+                    let rate = limits.requests_per_sec.max(0.1);
+                    Some(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+                }
+            };
+            // Outside the lock: giving the slot back takes it
+            let Some(wait) = wait else {
+                drop(slot);
+                return Ok(());
+            };
+            slot.get_or_insert(QueueSlot {
+                buckets: &self.buckets,
+                endpoint,
+            });
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Push mode: poll the monitor endpoint once in the backend and broadcast
+/// `engine:subsystems` whenever the payload changes, so widgets can listen
+/// instead of each polling on their own.
+pub fn start_monitor_push(app: AppHandle, proxy: Arc<EngineProxy>) {
+    tauri::async_runtime::spawn(async move {
+        let mut last: Option<serde_json::Value> = None;
+        loop {
+            let monitor = proxy.monitor.read().clone();
+            if monitor.push && !background::suspended(&app) {
+                let soul_path = app
+                    .state::<Arc<RwLock<AppConfig>>>()
+                    .read()
+                    .soul_path
+                    .clone();
+                match proxy.get(&app, &soul_path, MONITOR_PATH).await {
+                    Ok(data) => {
+                        if last.as_ref() != Some(&data) {
+                            let _ = app.emit("engine:subsystems", &data);
+                            last = Some(data);
+                        }
+                    }
+                    Err(_) => last = None,
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(monitor.push_interval_ms.max(250))).await;
+        }
+    });
+}
+
+/// Rate limits are tracked per path, ignoring the query string.
+fn endpoint_key(path: &str) -> &str {
+    path.split('?').next().unwrap_or(path)
+}
+
+/// Read API_PORT and API_KEY from the soul's .env (port defaults to 3001).
+pub fn engine_connection(soul_path: &Path) -> (u16, String) {
+    let mut port: u16 = 3001;
+    let mut api_key = String::new();
+
+    if let Ok(content) = fs::read_to_string(soul_path.join(".env")) {
+        for line in content.lines() {
+            let trimmed = line.trim();
+            if let Some(val) = trimmed.strip_prefix("API_PORT=") {
+                if let Ok(p) = val.trim().trim_matches('"').parse::<u16>() {
+                    port = p;
+                }
+            }
+            if let Some(val) = trimmed.strip_prefix("API_KEY=") {
+                api_key = val.trim().trim_matches('"').to_string();
+            }
+        }
+    }
+
+    (port, api_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn proxy(requests_per_sec: f64, burst: u32, max_queue: usize) -> EngineProxy {
+        EngineProxy::new(
+            ProxyLimits {
+                requests_per_sec,
+                burst,
+                max_queue,
+            },
+            MonitorConfig::default(),
+        )
+    }
+
+    fn queued(proxy: &EngineProxy, endpoint: &str) -> usize {
+        proxy.buckets.lock().get(endpoint).map_or(0, |b| b.queued)
+    }
+
+    #[test]
+    fn refill_adds_tokens_at_the_rate_up_to_the_burst() {
+        let limits = ProxyLimits {
+            requests_per_sec: 4.0,
+            burst: 8,
+            max_queue: 16,
+        };
+        let start = Instant::now();
+        let mut bucket = Bucket {
+            tokens: 0.0,
+            last_refill: start,
+            queued: 0,
+        };
+        bucket.refill(&limits, start + Duration::from_millis(500));
+        assert_eq!(bucket.tokens, 2.0);
+        bucket.refill(&limits, start + Duration::from_secs(60));
+        assert_eq!(bucket.tokens, 8.0);
+        // A clock that went backwards adds nothing
+        bucket.refill(&limits, start);
+        assert_eq!(bucket.tokens, 8.0);
+    }
+
+    #[tokio::test]
+    async fn burst_passes_then_the_queue_bounds_waiting() {
+        let proxy = proxy(0.1, 3, 0);
+        for _ in 0..3 {
+            proxy.take_token("/api/a").await.unwrap();
+        }
+        assert_eq!(
+            proxy.take_token("/api/a").await,
+            Err(QueueFull {
+                queued: 0,
+                max_queue: 0
+            })
+        );
+        // Buckets are per endpoint
+        proxy.take_token("/api/b").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn waiting_takes_the_next_token() {
+        let proxy = proxy(50.0, 1, 1);
+        proxy.take_token("/api/a").await.unwrap();
+        let started = Instant::now();
+        proxy.take_token("/api/a").await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(10));
+        assert_eq!(queued(&proxy, "/api/a"), 0);
+    }
+
+    #[tokio::test]
+    async fn a_cancelled_wait_gives_its_queue_slot_back() {
+        let proxy = proxy(0.1, 1, 1);
+        proxy.take_token("/api/a").await.unwrap();
+        let waiting = tokio::time::timeout(Duration::from_millis(20), proxy.take_token("/api/a"));
+        assert!(waiting.await.is_err());
+        assert_eq!(queued(&proxy, "/api/a"), 0);
+        // The slot is free for the next request
+        let waiting = tokio::time::timeout(Duration::from_millis(20), proxy.take_token("/api/a"));
+        assert!(waiting.await.is_err(), "queued, not dropped");
+    }
+
+    #[tokio::test]
+    async fn identical_requests_share_one_fetch() {
+        let proxy = proxy(4.0, 8, 16);
+        let calls = AtomicUsize::new(0);
+        let fetch = || async {
+            let n = calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok(serde_json::json!(n))
+        };
+        let (a, b, c) = tokio::join!(
+            proxy.coalesce("/api/monitor", fetch),
+            proxy.coalesce("/api/monitor", fetch),
+            proxy.coalesce("/api/monitor", fetch),
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!((a.clone(), b, c), (a.clone(), a.clone(), a));
+        assert!(proxy.in_flight.lock().is_empty());
+        // Done requests aren't joined
+        proxy.coalesce("/api/monitor", fetch).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn waiters_fetch_themselves_when_the_leader_is_cancelled() {
+        let proxy = proxy(4.0, 8, 16);
+        let mut leader = Box::pin(proxy.coalesce("/api/monitor", || async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(serde_json::json!("leader"))
+        }));
+        let mut waiter = Box::pin(proxy.coalesce("/api/monitor", || async {
+            Ok(serde_json::json!("waiter"))
+        }));
+        let short = Duration::from_millis(10);
+        assert!(tokio::time::timeout(short, &mut leader).await.is_err());
+        assert!(tokio::time::timeout(short, &mut waiter).await.is_err());
+        drop(leader);
+        let result = tokio::time::timeout(Duration::from_secs(1), waiter).await;
+        assert_eq!(result.unwrap(), Ok(serde_json::json!("waiter")));
+        assert!(proxy.in_flight.lock().is_empty());
+    }
+}
@@ -1,22 +1,62 @@
 use std::collections::HashMap;
 use std::io::{Read, Write};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
+use parking_lot::{Mutex, RwLock};
 use portable_pty::{native_pty_system, CommandBuilder, MasterPty, PtySize};
-use tauri::{AppHandle, Emitter};
+use serde::Serialize;
+use tauri::AppHandle;
+use ts_rs::TS;
+
+use crate::env_policy;
+use crate::pty_history::{CommandHistory, CommandRecord};
+use crate::restore::RestoreTerminal;
+use crate::routing;
+use crate::sandbox::{self, Capabilities};
+use crate::types::SubsystemHealth;
 
 struct PtySession {
     master: Box<dyn MasterPty + Send>,
     writer: Box<dyn Write + Send>,
     _child: Box<dyn portable_pty::Child + Send>,
+    capabilities: Capabilities,
+    /// The running program asked for bracketed paste (`ESC[?2004h`)
+    bracketed_paste: Arc<AtomicBool>,
+    /// Directory the shell started in, if its current one can't be read
+    cwd: PathBuf,
+    purpose: Option<String>,
+    /// Last window title the shell set (OSC 0/2)
+    title: Arc<Mutex<Option<String>>>,
+}
+
+/// How to open a terminal.
+#[derive(Debug, Clone, Default)]
+pub struct PtyOptions {
+    pub restricted: bool,
+    /// Start here instead of the soul directory (restoring a session)
+    pub cwd: Option<PathBuf>,
+    /// Free-form label, kept for session restore
+    pub purpose: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct PtyInfo {
+    pub id: u32,
+    /// Whether the shell is still running
+    pub alive: bool,
+    pub capabilities: Capabilities,
 }
 
 pub struct PtyManager {
     sessions: Arc<Mutex<HashMap<u32, PtySession>>>,
-    next_id: Arc<Mutex<u32>>,
-    soul_path: String,
+    next_id: AtomicU32,
+    /// Swapped by `relocate_soul`; running shells keep their directory
+    soul_path: RwLock<String>,
+    history: Arc<CommandHistory>,
 }
 
 /// Flush interval for PTY output — guarantees data is delivered within this window
@@ -24,17 +64,40 @@ pub struct PtyManager {
 const FLUSH_INTERVAL: Duration = Duration::from_millis(8);
 /// Max bytes per emission to prevent oversized events
 const MAX_FLUSH_BYTES: usize = 64 * 1024;
+/// Pastes are written in chunks of this size...
+const PASTE_CHUNK_BYTES: usize = 1024;
+/// ...with this pause between them, so line editors and REPLs keep up
+const PASTE_PAUSE: Duration = Duration::from_millis(4);
+const PASTE_START: &str = "\x1b[200~";
+const PASTE_END: &str = "\x1b[201~";
 
 impl PtyManager {
-    pub fn new(soul_path: String) -> Self {
+    pub fn new(soul_path: String, history: Arc<CommandHistory>) -> Self {
         Self {
             sessions: Arc::new(Mutex::new(HashMap::new())),
-            next_id: Arc::new(Mutex::new(1)),
-            soul_path,
+            next_id: AtomicU32::new(1),
+            soul_path: RwLock::new(soul_path),
+            history,
         }
     }
 
-    pub fn create(&self, app: &AppHandle, cols: u16, rows: u16) -> Result<u32, String> {
+    /// Start new terminals in a relocated soul.
+    pub fn set_soul_path(&self, soul_path: String) {
+        *self.soul_path.write() = soul_path;
+    }
+
+    /// Open a login shell in the soul directory (or `options.cwd` if it
+    /// still exists). A `restricted` session runs
+    /// in the OS sandbox and may only write to the soul and temp dirs; it
+    /// fails rather than falling back to an unrestricted shell.
+    pub fn create(
+        &self,
+        app: &AppHandle,
+        cols: u16,
+        rows: u16,
+        options: PtyOptions,
+    ) -> Result<u32, String> {
+        let restricted = options.restricted;
         let pty_system = native_pty_system();
 
         let pair = pty_system
@@ -46,19 +109,39 @@ impl PtyManager {
             })
             .map_err(|e| format!("Failed to open PTY: {}", e))?;
 
+        let soul_path = self.soul_path.read().clone();
+
         // Get the default shell — launch as interactive login shell
         let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/zsh".to_string());
 
-        let mut cmd = CommandBuilder::new(&shell);
-        cmd.arg("-l"); // login shell — sources .zprofile, .zshrc, etc.
-        cmd.cwd(&self.soul_path);
+        // login shell — sources .zprofile, .zshrc, etc.
+        let (mut cmd, capabilities) = if restricted {
+            let sandboxed = sandbox::wrap(&shell, &["-l"], Path::new(&soul_path))?;
+            let mut cmd = CommandBuilder::new(&sandboxed.program);
+            cmd.args(&sandboxed.args);
+            (cmd, sandboxed.capabilities)
+        } else {
+            let mut cmd = CommandBuilder::new(&shell);
+            cmd.arg("-l");
+            (cmd, Capabilities::unrestricted())
+        };
+        let cwd = options
+            .cwd
+            .filter(|dir| dir.is_dir())
+            .unwrap_or_else(|| PathBuf::from(&soul_path));
+        cmd.cwd(&cwd);
+
+        // Only what the env policy lets through; CommandBuilder would
+        // otherwise inherit the app's whole environment
+        cmd.env_clear();
+        for (key, val) in env_policy::vars() {
+            cmd.env(key, val);
+        }
 
         // Remove Claude Code nesting guard — SoulOS terminal is independent,
         // not a nested session. Without this, `claude` refuses to start with
         // "cannot be launched inside another Claude Code session".
-        // Note: use cmd.env() with empty value instead of std::env::remove_var()
-        // to avoid modifying global process environment (unsound in multithreaded code)
-        cmd.env("CLAUDECODE", "");
+        cmd.env_remove("CLAUDECODE");
 
         // Terminal identification — helps CLIs detect capabilities
         cmd.env("TERM", "xterm-256color");
@@ -76,32 +159,12 @@ impl PtyManager {
             std::env::var("LC_CTYPE").unwrap_or_else(|_| "en_US.UTF-8".to_string()),
         );
 
-        // Pass through essential environment
-        for key in &[
-            "HOME",
-            "USER",
-            "LOGNAME",
-            "PATH",
-            "SHELL",
-            "TMPDIR",
-            "XDG_RUNTIME_DIR",
-            // Development tools
-            "HOMEBREW_PREFIX",
-            "HOMEBREW_CELLAR",
-            "HOMEBREW_REPOSITORY",
-            // SSH agent for git operations
-            "SSH_AUTH_SOCK",
-            // Note: API keys (ANTHROPIC_API_KEY etc.) are NOT passed to the terminal
-            // to prevent accidental exposure to arbitrary commands run by the user
-        ] {
-            if let Ok(val) = std::env::var(key) {
-                cmd.env(key, &val);
-            }
-        }
-
         // Soul context
-        cmd.env("SOUL_PATH", &self.soul_path);
+        cmd.env("SOUL_PATH", &soul_path);
         cmd.env("INSIDE_SOUL_OS", "1");
+        if restricted {
+            cmd.env("SOUL_OS_RESTRICTED", "1");
+        }
 
         let child = pair
             .slave
@@ -117,12 +180,7 @@ impl PtyManager {
             .try_clone_reader()
             .map_err(|e| format!("Failed to get PTY reader: {}", e))?;
 
-        let id = {
-            let mut next = self.next_id.lock().unwrap();
-            let id = *next;
-            *next += 1;
-            id
-        };
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
 
         // ── Two-thread architecture: Reader + Flusher ──────────────────
         //
@@ -140,6 +198,7 @@ impl PtyManager {
         let buffer: Arc<Mutex<Vec<u8>>> =
             Arc::new(Mutex::new(Vec::with_capacity(MAX_FLUSH_BYTES)));
         let reader_done = Arc::new(AtomicBool::new(false));
+        let bracketed_paste = Arc::new(AtomicBool::new(false));
 
         // Reader thread — reads from PTY into shared buffer (never delays)
         let buffer_r = buffer.clone();
@@ -155,7 +214,7 @@ impl PtyManager {
                             break;
                         }
                         Ok(n) => {
-                            buffer_r.lock().unwrap().extend_from_slice(&buf[..n]);
+                            buffer_r.lock().extend_from_slice(&buf[..n]);
                         }
                         Err(_) => {
                             done_r.store(true, Ordering::SeqCst);
@@ -170,6 +229,10 @@ impl PtyManager {
         let buffer_f = buffer.clone();
         let done_f = reader_done.clone();
         let app_clone = app.clone();
+        let history = self.history.clone();
+        let bracketed_f = bracketed_paste.clone();
+        let title = Arc::new(Mutex::new(None));
+        let title_f = title.clone();
         let pty_id = id;
         std::thread::Builder::new()
             .name(format!("pty-flusher-{}", id))
@@ -178,7 +241,7 @@ impl PtyManager {
                     std::thread::sleep(FLUSH_INTERVAL);
 
                     let data = {
-                        let mut buf = buffer_f.lock().unwrap();
+                        let mut buf = buffer_f.lock();
                         if buf.is_empty() {
                             if done_f.load(Ordering::SeqCst) {
                                 break;
@@ -191,7 +254,21 @@ impl PtyManager {
                     // Emit in chunks to prevent oversized events
                     for chunk in data.chunks(MAX_FLUSH_BYTES) {
                         let text = String::from_utf8_lossy(chunk).to_string();
-                        let _ = app_clone.emit(
+                        history.output(pty_id, &text);
+                        if let Some(t) = osc_title(&text) {
+                            *title_f.lock() = Some(t);
+                        }
+                        // The last mode switch in the chunk wins
+                        match (text.rfind("\x1b[?2004h"), text.rfind("\x1b[?2004l")) {
+                            (Some(on), off) if off.is_none_or(|off| off < on) => {
+                                bracketed_f.store(true, Ordering::SeqCst)
+                            }
+                            (_, Some(_)) => bracketed_f.store(false, Ordering::SeqCst),
+                            _ => {}
+                        }
+                        routing::emit_pty(
+                            &app_clone,
+                            pty_id,
                             "pty:data",
                             serde_json::json!({ "id": pty_id, "data": text }),
                         );
@@ -204,10 +281,12 @@ impl PtyManager {
 
                 // Final flush — drain anything remaining after reader EOF
                 {
-                    let buf = buffer_f.lock().unwrap();
+                    let buf = buffer_f.lock();
                     if !buf.is_empty() {
                         let text = String::from_utf8_lossy(&buf).to_string();
-                        let _ = app_clone.emit(
+                        routing::emit_pty(
+                            &app_clone,
+                            pty_id,
                             "pty:data",
                             serde_json::json!({ "id": pty_id, "data": text }),
                         );
@@ -215,7 +294,12 @@ impl PtyManager {
                 }
 
                 // Notify frontend that the process exited
-                let _ = app_clone.emit("pty:exit", serde_json::json!({ "id": pty_id }));
+                routing::emit_pty(
+                    &app_clone,
+                    pty_id,
+                    "pty:exit",
+                    serde_json::json!({ "id": pty_id }),
+                );
             })
             .map_err(|e| format!("Failed to spawn flusher thread: {}", e))?;
 
@@ -223,14 +307,19 @@ impl PtyManager {
             master,
             writer,
             _child: child,
+            capabilities,
+            bracketed_paste,
+            cwd,
+            purpose: options.purpose,
+            title,
         };
 
-        self.sessions.lock().unwrap().insert(id, session);
+        self.sessions.lock().insert(id, session);
         Ok(id)
     }
 
     pub fn write(&self, id: u32, data: &str) -> Result<(), String> {
-        let mut sessions = self.sessions.lock().unwrap();
+        let mut sessions = self.sessions.lock();
         let session = sessions
             .get_mut(&id)
             .ok_or_else(|| format!("PTY session {} not found", id))?;
@@ -242,11 +331,60 @@ impl PtyManager {
             .writer
             .flush()
             .map_err(|e| format!("Flush failed: {}", e))?;
+        self.history.input(id, data);
+        Ok(())
+    }
+
+    /// Paste `text` the way a terminal emulator does: line breaks as CR,
+    /// wrapped in bracketed paste markers when the program enabled them,
+    /// written in small chunks. Blocks for the duration of the paste.
+    pub fn paste(&self, id: u32, text: &str) -> Result<(), String> {
+        let bracketed = self
+            .sessions
+            .lock()
+            .get(&id)
+            .ok_or_else(|| format!("PTY session {} not found", id))?
+            .bracketed_paste
+            .load(Ordering::SeqCst);
+        // A pasted end marker would let the rest run as typed input
+        let body = text
+            .replace(PASTE_END, "")
+            .replace("\r\n", "\r")
+            .replace('\n', "\r");
+        let payload = if bracketed {
+            format!("{}{}{}", PASTE_START, body, PASTE_END)
+        } else {
+            body
+        };
+
+        let mut rest = payload.as_str();
+        while !rest.is_empty() {
+            let mut end = rest.len().min(PASTE_CHUNK_BYTES);
+            while !rest.is_char_boundary(end) {
+                end -= 1;
+            }
+            let (chunk, tail) = rest.split_at(end);
+            {
+                let mut sessions = self.sessions.lock();
+                let session = sessions
+                    .get_mut(&id)
+                    .ok_or_else(|| format!("PTY session {} closed during paste", id))?;
+                session
+                    .writer
+                    .write_all(chunk.as_bytes())
+                    .and_then(|_| session.writer.flush())
+                    .map_err(|e| format!("Paste failed: {}", e))?;
+            }
+            rest = tail;
+            if !rest.is_empty() {
+                std::thread::sleep(PASTE_PAUSE);
+            }
+        }
         Ok(())
     }
 
     pub fn resize(&self, id: u32, cols: u16, rows: u16) -> Result<(), String> {
-        let sessions = self.sessions.lock().unwrap();
+        let sessions = self.sessions.lock();
         let session = sessions
             .get(&id)
             .ok_or_else(|| format!("PTY session {} not found", id))?;
@@ -263,21 +401,134 @@ impl PtyManager {
     }
 
     pub fn close(&self, id: u32) -> Result<(), String> {
-        let mut sessions = self.sessions.lock().unwrap();
+        let mut sessions = self.sessions.lock();
         if let Some(mut session) = sessions.remove(&id) {
             // Kill the child process to prevent orphans
             let _ = session._child.kill();
             let _ = session._child.wait();
         }
+        self.history.end(id);
         Ok(())
     }
 
+    pub fn exists(&self, id: u32) -> bool {
+        self.sessions.lock().contains_key(&id)
+    }
+
+    /// Open sessions with what their processes may do, by id.
+    pub fn list(&self) -> Vec<PtyInfo> {
+        let mut sessions = self.sessions.lock();
+        let mut list: Vec<PtyInfo> = sessions
+            .iter_mut()
+            .map(|(id, session)| PtyInfo {
+                id: *id,
+                alive: matches!(session._child.try_wait(), Ok(None)),
+                capabilities: session.capabilities.clone(),
+            })
+            .collect();
+        list.sort_by_key(|info| info.id);
+        list
+    }
+
+    /// Commands typed into terminal `id` since it opened.
+    pub fn history(&self, id: u32) -> Result<Vec<CommandRecord>, String> {
+        self.history.session(id)
+    }
+
+    /// Open sessions as the next start should reopen them, by id.
+    pub fn restore_state(&self) -> Vec<(u32, RestoreTerminal)> {
+        let mut sessions = self.sessions.lock();
+        let mut state: Vec<(u32, RestoreTerminal)> = sessions
+            .iter_mut()
+            .filter_map(|(id, session)| {
+                if !matches!(session._child.try_wait(), Ok(None)) {
+                    return None;
+                }
+                let cwd = session
+                    ._child
+                    .process_id()
+                    .and_then(process_cwd)
+                    .unwrap_or_else(|| session.cwd.clone());
+                let terminal = RestoreTerminal {
+                    cwd: cwd.to_string_lossy().to_string(),
+                    title: session.title.lock().clone(),
+                    purpose: session.purpose.clone(),
+                    restricted: session.capabilities.restricted,
+                    detached: false,
+                };
+                Some((*id, terminal))
+            })
+            .collect();
+        state.sort_by_key(|(id, _)| *id);
+        state
+    }
+
+    pub fn session_count(&self) -> usize {
+        self.sessions.lock().len()
+    }
+
+    /// Sessions whose shell has exited but were never closed make the PTY layer degraded.
+    pub fn health(&self) -> SubsystemHealth {
+        let mut sessions = self.sessions.lock();
+        let total = sessions.len();
+        let mut dead = 0;
+        for session in sessions.values_mut() {
+            if !matches!(session._child.try_wait(), Ok(None)) {
+                dead += 1;
+            }
+        }
+
+        if dead > 0 {
+            SubsystemHealth::new(
+                "pty",
+                "degraded",
+                Some(format!("{} of {} sessions have exited", dead, total)),
+            )
+        } else {
+            SubsystemHealth::new("pty", "ok", Some(format!("{} sessions", total)))
+        }
+    }
+
     /// Shutdown all PTY sessions — called on application exit
     pub fn shutdown(&self) {
-        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let mut sessions = self.sessions.lock();
         for (_id, mut session) in sessions.drain() {
             let _ = session._child.kill();
             let _ = session._child.wait();
         }
     }
 }
+
+/// The last title an OSC 0/2 sequence in `text` sets.
+fn osc_title(text: &str) -> Option<String> {
+    let start = ["\x1b]0;", "\x1b]2;"]
+        .iter()
+        .filter_map(|marker| text.rfind(marker).map(|at| at + marker.len()))
+        .max()?;
+    let rest = &text[start..];
+    let end = rest.find(['\x07', '\x1b'])?;
+    Some(rest[..end].to_string())
+}
+
+/// Current working directory of a process.
+#[cfg(target_os = "linux")]
+fn process_cwd(pid: u32) -> Option<PathBuf> {
+    std::fs::read_link(format!("/proc/{}/cwd", pid)).ok()
+}
+
+#[cfg(target_os = "macos")]
+fn process_cwd(pid: u32) -> Option<PathBuf> {
+    let output = std::process::Command::new("lsof")
+        .args(["-a", "-p", &pid.to_string(), "-d", "cwd", "-Fn"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix('n'))
+        .map(PathBuf::from)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn process_cwd(_pid: u32) -> Option<PathBuf> {
+    None
+}
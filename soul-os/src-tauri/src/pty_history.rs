@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::audit;
+use crate::config::app_data_dir;
+
+/// Output kept per session to recognize password prompts
+const PROMPT_TAIL: usize = 80;
+/// Longest line kept; anything longer is a paste, not a command
+const MAX_LINE: usize = 4096;
+/// Default number of records returned by `search`
+const DEFAULT_LIMIT: usize = 200;
+/// A line typed after one of these (lowercased) is a secret, not a command
+const PROMPTS: &[&str] = &["password", "passwort", "passphrase", "pin:", "token"];
+
+/// One command line entered in a terminal, as written to
+/// <app_data_dir>/pty-history.jsonl.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CommandRecord {
+    /// Terminal session, unique across app runs (PTY ids restart at 1)
+    pub session: String,
+    pub pty: u32,
+    #[ts(type = "number")]
+    pub timestamp: u64,
+    pub command: String,
+}
+
+#[derive(Default)]
+struct LineBuffer {
+    line: String,
+    /// Inside an escape sequence (arrow keys, bracketed paste markers)
+    escape: Option<String>,
+    /// Latest output, for password prompt detection
+    output_tail: String,
+}
+
+/// Command lines typed into the terminals, reconstructed from the bytes
+/// sent to `write_pty`. Best effort: shell-side editing such as history
+/// recall or tab completion isn't visible in the input.
+pub struct CommandHistory {
+    /// Unix ms of this app run, prefixing session ids
+    run: u64,
+    lines: Mutex<HashMap<u32, LineBuffer>>,
+    write_lock: Mutex<()>,
+}
+
+impl Default for CommandHistory {
+    fn default() -> Self {
+        Self {
+            run: now_ms(),
+            lines: Mutex::new(HashMap::new()),
+            write_lock: Mutex::new(()),
+        }
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn history_path() -> PathBuf {
+    app_data_dir().join("pty-history.jsonl")
+}
+
+/// `NAME=value` assignments of secret-looking names, value replaced.
+fn redact(command: &str) -> String {
+    command
+        .split(' ')
+        .map(|word| match word.split_once('=') {
+            Some((name, _)) if audit::is_secret_name(name) => format!("{}=[redacted]", name),
+            _ => word.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn after_secret_prompt(output_tail: &str) -> bool {
+    let tail = output_tail.trim_end().to_lowercase();
+    tail.ends_with(':') && PROMPTS.iter().any(|p| tail.contains(p))
+}
+
+impl CommandHistory {
+    pub fn session_id(&self, pty: u32) -> String {
+        format!("{}-{}", self.run, pty)
+    }
+
+    /// Feed input sent to terminal `pty`; completed lines are recorded.
+    pub fn input(&self, pty: u32, data: &str) {
+        let mut completed = Vec::new();
+        {
+            let mut lines = self.lines.lock();
+            let buffer = lines.entry(pty).or_default();
+            for c in data.chars() {
+                if let Some(escape) = buffer.escape.as_mut() {
+                    escape.push(c);
+                    // CSI ends at a final byte; ESC O x and ESC x are two/three chars
+                    let done = match escape.as_str() {
+                        "[" | "O" => false,
+                        s if s.starts_with('[') => ('@'..='~').contains(&c),
+                        s if s.starts_with('O') => true,
+                        _ => true,
+                    };
+                    if done {
+                        buffer.escape = None;
+                    }
+                    continue;
+                }
+                match c {
+                    '\x1b' => buffer.escape = Some(String::new()),
+                    '\r' | '\n' => {
+                        let line = std::mem::take(&mut buffer.line);
+                        let secret = after_secret_prompt(&buffer.output_tail);
+                        buffer.output_tail.clear();
+                        if !secret && !line.trim().is_empty() {
+                            completed.push(line.trim().to_string());
+                        }
+                    }
+                    '\x7f' | '\x08' => {
+                        buffer.line.pop();
+                    }
+                    // Ctrl-C, Ctrl-U: the line is discarded
+                    '\x03' | '\x15' => buffer.line.clear(),
+                    c if c.is_control() && c != '\t' => {}
+                    c if buffer.line.len() < MAX_LINE => buffer.line.push(c),
+                    _ => {}
+                }
+            }
+        }
+        for command in completed {
+            self.append(&CommandRecord {
+                session: self.session_id(pty),
+                pty,
+                timestamp: now_ms(),
+                command: redact(&command),
+            });
+        }
+    }
+
+    /// Feed output of terminal `pty`, so input after a password prompt is
+    /// never recorded.
+    pub fn output(&self, pty: u32, text: &str) {
+        let mut lines = self.lines.lock();
+        let tail = &mut lines.entry(pty).or_default().output_tail;
+        tail.push_str(text);
+        let excess = tail.chars().count().saturating_sub(PROMPT_TAIL);
+        if excess > 0 {
+            *tail = tail.chars().skip(excess).collect();
+        }
+    }
+
+    /// Forget the line being typed into a closed terminal.
+    pub fn end(&self, pty: u32) {
+        self.lines.lock().remove(&pty);
+    }
+
+    fn append(&self, record: &CommandRecord) {
+        let Ok(line) = serde_json::to_string(record) else {
+            return;
+        };
+        let path = history_path();
+        let _guard = self.write_lock.lock();
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&path) {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+
+    fn records(&self) -> Result<Vec<CommandRecord>, String> {
+        let content = match fs::read_to_string(history_path()) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.to_string()),
+        };
+        Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// Records older than `before` (Unix ms): how many and their bytes.
+    /// Unless `dry_run`, they are removed from the history file.
+    pub fn prune(&self, before: u64, dry_run: bool) -> Result<(usize, u64), String> {
+        let _guard = self.write_lock.lock();
+        let path = history_path();
+        let content = match fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((0, 0)),
+            Err(e) => return Err(e.to_string()),
+        };
+        let (mut dropped, mut bytes) = (0, 0);
+        let mut kept = String::new();
+        for line in content.lines() {
+            let old = serde_json::from_str::<CommandRecord>(line)
+                .is_ok_and(|record| record.timestamp < before);
+            if old {
+                dropped += 1;
+                bytes += line.len() as u64 + 1;
+            } else {
+                kept.push_str(line);
+                kept.push('\n');
+            }
+        }
+        if dropped > 0 && !dry_run {
+            fs::write(&path, kept).map_err(|e| e.to_string())?;
+        }
+        Ok((dropped, bytes))
+    }
+
+    /// Commands of terminal `pty` in this app run, oldest first.
+    pub fn session(&self, pty: u32) -> Result<Vec<CommandRecord>, String> {
+        let session = self.session_id(pty);
+        Ok(self
+            .records()?
+            .into_iter()
+            .filter(|r| r.session == session)
+            .collect())
+    }
+
+    /// Commands of every session containing `query` (case-insensitive),
+    /// newest first.
+    pub fn search(&self, query: &str, limit: Option<usize>) -> Result<Vec<CommandRecord>, String> {
+        let query = query.to_lowercase();
+        Ok(self
+            .records()?
+            .into_iter()
+            .rev()
+            .filter(|r| r.command.to_lowercase().contains(&query))
+            .take(limit.unwrap_or(DEFAULT_LIMIT))
+            .collect())
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+use crate::config::app_data_dir;
+use crate::soul_identity;
+use crate::watcher;
+
+/// Files remembered per soul; the least recently used are forgotten
+const MAX_FILES: usize = 500;
+/// Saves of one file within this count as one edit (autosave bursts, and
+/// the watcher seeing the app's own write)
+const EDIT_MERGE: Duration = Duration::from_secs(5);
+/// Usage is written to disk at most this often
+const SAVE_INTERVAL: Duration = Duration::from_secs(15);
+/// Recency weight halves every this many ms (3 days)
+const HALF_LIFE_MS: f64 = 3.0 * 24.0 * 3600.0 * 1000.0;
+/// Weight of a file's brain node being lit right now
+const NODE_WEIGHT: f64 = 2.0;
+const SUGGESTIONS: usize = 8;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Usage {
+    opens: u32,
+    edits: u32,
+    last_opened: u64,
+    last_edited: u64,
+}
+
+impl Usage {
+    fn last_used(&self) -> u64 {
+        self.last_opened.max(self.last_edited)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct RecentFile {
+    /// Relative to the soul
+    pub path: String,
+    pub opens: u32,
+    pub edits: u32,
+    /// Unix ms of the last open or edit
+    #[ts(type = "number")]
+    pub last_used: u64,
+    /// Brain node the file belongs to
+    pub node: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SuggestedFile {
+    pub file: RecentFile,
+    pub score: f64,
+    /// Its node is active right now
+    pub node_active: bool,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// <app_data_dir>/recent/<soul>.json — usage is the user's, not the
+/// soul's, so it stays on this machine.
+fn store_path(soul_path: &Path) -> PathBuf {
+    app_data_dir()
+        .join("recent")
+        .join(format!("{}.json", soul_identity::storage_key(soul_path)))
+}
+
+struct Loaded {
+    soul_path: PathBuf,
+    files: HashMap<String, Usage>,
+    dirty: bool,
+    saved: Instant,
+}
+
+impl Loaded {
+    fn load(soul_path: &Path) -> Self {
+        let files = fs::read_to_string(store_path(soul_path))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Loaded {
+            soul_path: soul_path.to_path_buf(),
+            files,
+            dirty: false,
+            saved: Instant::now(),
+        }
+    }
+
+    fn save(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        let path = store_path(&self.soul_path);
+        let written = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .map_err(|e| e.to_string())
+            .and_then(|_| serde_json::to_string(&self.files).map_err(|e| e.to_string()))
+            .and_then(|json| fs::write(&path, json).map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            eprintln!("[recent] saving {} failed: {}", path.display(), e);
+        }
+        self.dirty = false;
+        self.saved = Instant::now();
+    }
+
+    fn forget_oldest(&mut self) {
+        if self.files.len() <= MAX_FILES {
+            return;
+        }
+        let mut by_use: Vec<(u64, String)> = self
+            .files
+            .iter()
+            .map(|(path, usage)| (usage.last_used(), path.clone()))
+            .collect();
+        by_use.sort();
+        for (_, path) in by_use.into_iter().take(self.files.len() - MAX_FILES) {
+            self.files.remove(&path);
+        }
+    }
+}
+
+/// Open and edit counts per soul file, fed by the file commands and the
+/// watcher.
+#[derive(Default)]
+pub struct RecentFiles {
+    loaded: Mutex<Option<Loaded>>,
+}
+
+impl RecentFiles {
+    fn with<T>(&self, soul_path: &Path, f: impl FnOnce(&mut Loaded) -> T) -> T {
+        let mut loaded = self.loaded.lock();
+        if loaded.as_ref().is_none_or(|l| l.soul_path != soul_path) {
+            if let Some(previous) = loaded.as_mut() {
+                previous.save();
+            }
+            *loaded = Some(Loaded::load(soul_path));
+        }
+        let loaded = loaded.as_mut().expect("loaded above");
+        let result = f(loaded);
+        if loaded.dirty && loaded.saved.elapsed() >= SAVE_INTERVAL {
+            loaded.save();
+        }
+        result
+    }
+
+    fn record(&self, soul_path: &Path, name: &str, edit: bool) {
+        let name = name.replace('\\', "/");
+        let now = now_ms();
+        self.with(soul_path, |loaded| {
+            let usage = loaded.files.entry(name).or_default();
+            if edit {
+                if now.saturating_sub(usage.last_edited) >= EDIT_MERGE.as_millis() as u64 {
+                    usage.edits += 1;
+                }
+                usage.last_edited = now;
+            } else {
+                usage.opens += 1;
+                usage.last_opened = now;
+            }
+            loaded.dirty = true;
+            loaded.forget_oldest();
+        });
+    }
+
+    pub fn opened(&self, soul_path: &Path, name: &str) {
+        self.record(soul_path, name, false);
+    }
+
+    pub fn edited(&self, soul_path: &Path, name: &str) {
+        self.record(soul_path, name, true);
+    }
+
+    /// Write pending usage to disk (app exit).
+    pub fn flush(&self) {
+        if let Some(loaded) = self.loaded.lock().as_mut() {
+            loaded.save();
+        }
+    }
+
+    /// Existing files, most recently used first.
+    fn existing(&self, soul_path: &Path) -> Vec<RecentFile> {
+        let mut files: Vec<RecentFile> = self.with(soul_path, |loaded| {
+            loaded
+                .files
+                .iter()
+                .filter(|(path, _)| soul_path.join(path).is_file())
+                .map(|(path, usage)| RecentFile {
+                    path: path.clone(),
+                    opens: usage.opens,
+                    edits: usage.edits,
+                    last_used: usage.last_used(),
+                    node: watcher::resolve_node(path).map(str::to_string),
+                })
+                .collect()
+        });
+        files.sort_by(|a, b| {
+            b.last_used
+                .cmp(&a.last_used)
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        files
+    }
+
+    pub fn recent(&self, soul_path: &Path, limit: usize) -> Vec<RecentFile> {
+        let mut files = self.existing(soul_path);
+        files.truncate(limit);
+        files
+    }
+
+    /// Files to offer next: how often and how recently each was used,
+    /// boosted while its brain node is lit (`levels`: node → 0..1).
+    pub fn suggested(&self, soul_path: &Path, levels: &HashMap<String, f64>) -> Vec<SuggestedFile> {
+        let now = now_ms();
+        let mut suggested: Vec<SuggestedFile> = self
+            .existing(soul_path)
+            .into_iter()
+            .map(|file| {
+                let age = now.saturating_sub(file.last_used) as f64;
+                let frequency = (f64::from(file.opens) + 2.0 * f64::from(file.edits)).ln_1p();
+                let recency = 0.5f64.powf(age / HALF_LIFE_MS);
+                let level = file
+                    .node
+                    .as_ref()
+                    .and_then(|node| levels.get(node))
+                    .copied()
+                    .unwrap_or(0.0);
+                SuggestedFile {
+                    score: frequency * recency + NODE_WEIGHT * level,
+                    node_active: level > 0.0,
+                    file,
+                }
+            })
+            .collect();
+        suggested.sort_by(|a, b| b.score.total_cmp(&a.score));
+        suggested.truncate(SUGGESTIONS);
+        suggested
+    }
+}
+
+/// Watcher hook: a file under the soul was written.
+pub fn changed(app: &AppHandle, soul_path: &Path, relative: &str) {
+    // Dotfiles are the engine's and the app's bookkeeping
+    if relative.starts_with('.') || !soul_path.join(relative).is_file() {
+        return;
+    }
+    if let Some(recent) = app.try_state::<Arc<RecentFiles>>() {
+        recent.edited(soul_path, relative);
+    }
+}
@@ -0,0 +1,387 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File};
+use std::hash::Hasher;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use ts_rs::TS;
+
+use crate::blocking::CancelToken;
+use crate::config::AppConfig;
+use crate::env_watch::EnvWatch;
+use crate::policy::PolicyEngine;
+use crate::pty::PtyManager;
+use crate::sidecar::SidecarManager;
+use crate::status::StatusCache;
+use crate::tags::TagIndex;
+use crate::watcher;
+
+/// Never usable as a soul path
+const SYSTEM_DIRS: &[&str] = &[
+    "/", "/etc", "/usr", "/bin", "/sbin", "/var", "/tmp", "/System", "/Library",
+];
+/// Free space kept on the target beyond the soul's size
+const SPACE_MARGIN: u64 = 64 * 1024 * 1024;
+const BUFFER: usize = 64 * 1024;
+
+/// Outcome of `relocate_soul`, also emitted as `soul:relocated`.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct RelocationReport {
+    pub from: String,
+    pub to: String,
+    /// "rename" (same volume, moved in place) or "copy" (copied and verified)
+    pub method: String,
+    #[ts(type = "number")]
+    pub files: u64,
+    #[ts(type = "number")]
+    pub bytes: u64,
+    /// The old location was removed (move_data)
+    pub moved: bool,
+    /// Why the old location is still there after a move; the copy is
+    /// complete either way
+    pub cleanup_error: Option<String>,
+}
+
+pub fn is_system_dir(path: &Path) -> bool {
+    SYSTEM_DIRS.iter().any(|d| path == Path::new(d))
+}
+
+#[derive(Default)]
+struct Inventory {
+    dirs: Vec<PathBuf>,
+    files: Vec<PathBuf>,
+    symlinks: Vec<PathBuf>,
+    bytes: u64,
+}
+
+/// Everything below `root`, as paths relative to it. Symlinks aren't followed.
+fn scan(
+    root: &Path,
+    relative: &Path,
+    inv: &mut Inventory,
+    token: &CancelToken,
+) -> Result<(), String> {
+    token.check()?;
+    let dir = root.join(relative);
+    for entry in fs::read_dir(&dir).map_err(|e| format!("{}: {}", dir.display(), e))? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let path = relative.join(entry.file_name());
+        let kind = entry.file_type().map_err(|e| e.to_string())?;
+        if kind.is_symlink() {
+            inv.symlinks.push(path);
+        } else if kind.is_dir() {
+            inv.dirs.push(path.clone());
+            scan(root, &path, inv, token)?;
+        } else {
+            inv.bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+            inv.files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The deepest existing ancestor of `path` (itself if it exists).
+pub fn existing_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors().find(|p| p.exists())
+}
+
+/// Resolve symlinks in the existing part of `path`, so nesting checks
+/// compare real locations.
+fn resolve(path: &Path) -> PathBuf {
+    let Some(existing) = existing_ancestor(path) else {
+        return path.to_path_buf();
+    };
+    let rest = path.strip_prefix(existing).unwrap_or(Path::new(""));
+    existing
+        .canonicalize()
+        .unwrap_or_else(|_| existing.to_path_buf())
+        .join(rest)
+}
+
+/// Free bytes on the volume `path` is (or would be created) on.
+#[cfg(unix)]
+pub fn available_space(path: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(existing_ancestor(path)?.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+pub fn available_space(_path: &Path) -> Option<u64> {
+    None
+}
+
+#[cfg(unix)]
+fn same_volume(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let device = |p: &Path| {
+        existing_ancestor(p)
+            .and_then(|p| fs::metadata(p).ok())
+            .map(|m| m.dev())
+    };
+    matches!((device(a), device(b)), (Some(x), Some(y)) if x == y)
+}
+
+#[cfg(not(unix))]
+fn same_volume(_a: &Path, _b: &Path) -> bool {
+    false
+}
+
+fn validate(from: &Path, to: &Path) -> Result<(), String> {
+    if !to.is_absolute() {
+        return Err("Soul path must be absolute".to_string());
+    }
+    if is_system_dir(to) {
+        return Err("Cannot use a system directory as soul path".to_string());
+    }
+    if !from.is_dir() {
+        return Err(format!("Current soul not found at {}", from.display()));
+    }
+    let (real_from, real_to) = (resolve(from), resolve(to));
+    if real_to == real_from {
+        return Err("The soul already lives there".to_string());
+    }
+    if real_to.starts_with(&real_from) || real_from.starts_with(&real_to) {
+        return Err("The new location can't be inside the soul or contain it".to_string());
+    }
+    if to.exists() {
+        let empty = fs::read_dir(to)
+            .map(|mut entries| entries.next().is_none())
+            .map_err(|_| format!("{} is not a directory", to.display()))?;
+        if !empty {
+            return Err(format!("{} is not empty", to.display()));
+        }
+    }
+    Ok(())
+}
+
+/// Copy `from` to `to` while hashing it, then hash the copy to verify it.
+fn copy_verified(
+    from: &Path,
+    to: &Path,
+    progress: &mut dyn FnMut(u64) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut source = File::open(from).map_err(|e| format!("{}: {}", from.display(), e))?;
+    let mut target = File::create(to).map_err(|e| format!("{}: {}", to.display(), e))?;
+    let mut expected = DefaultHasher::new();
+    let mut buffer = vec![0u8; BUFFER];
+    loop {
+        let n = source.read(&mut buffer).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        expected.write(&buffer[..n]);
+        target
+            .write_all(&buffer[..n])
+            .map_err(|e| format!("{}: {}", to.display(), e))?;
+        progress(n as u64)?;
+    }
+    target.sync_all().map_err(|e| e.to_string())?;
+    if let Ok(meta) = fs::metadata(from) {
+        let _ = fs::set_permissions(to, meta.permissions());
+    }
+
+    let mut copy = File::open(to).map_err(|e| e.to_string())?;
+    let mut actual = DefaultHasher::new();
+    loop {
+        let n = copy.read(&mut buffer).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        actual.write(&buffer[..n]);
+        progress(n as u64)?;
+    }
+    if actual.finish() != expected.finish() {
+        return Err(format!("Verification failed for {}", to.display()));
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::copy(from, to).map(|_| ())
+}
+
+/// Copy the inventory; progress counts every byte twice (copy + verify).
+fn copy_tree(from: &Path, to: &Path, inv: &Inventory, token: &CancelToken) -> Result<(), String> {
+    fs::create_dir_all(to).map_err(|e| format!("{}: {}", to.display(), e))?;
+    for dir in &inv.dirs {
+        fs::create_dir_all(to.join(dir)).map_err(|e| e.to_string())?;
+    }
+    for link in &inv.symlinks {
+        copy_symlink(&from.join(link), &to.join(link))
+            .map_err(|e| format!("{}: {}", link.display(), e))?;
+    }
+    let total = inv.bytes * 2;
+    let mut done = 0;
+    token.set_progress(done, total);
+    for file in &inv.files {
+        copy_verified(&from.join(file), &to.join(file), &mut |n| {
+            done += n;
+            token.set_progress(done, total);
+            token.check()
+        })?;
+        token.check()?;
+    }
+    Ok(())
+}
+
+/// Undo a failed copy: remove what was created below `to`.
+fn discard(to: &Path, existed: bool) {
+    if !existed {
+        let _ = fs::remove_dir_all(to);
+    } else if let Ok(entries) = fs::read_dir(to) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let _ = if path.is_dir() && !path.is_symlink() {
+                fs::remove_dir_all(path)
+            } else {
+                fs::remove_file(path)
+            };
+        }
+    }
+}
+
+fn save_soul_path(app: &AppHandle, to: &Path) -> Result<(), String> {
+    let config = app.state::<Arc<RwLock<AppConfig>>>();
+    let mut cfg = config.write();
+    let previous = std::mem::replace(&mut cfg.soul_path, to.to_path_buf());
+    cfg.save().inspect_err(|_| cfg.soul_path = previous)
+}
+
+/// Point everything holding the soul path at `to`, once the config does.
+pub fn repoint(app: &AppHandle, to: &Path) {
+    app.state::<Arc<SidecarManager>>()
+        .set_soul_path(to.to_path_buf());
+    if let Some(pty) = app.try_state::<Arc<PtyManager>>() {
+        pty.set_soul_path(to.to_string_lossy().to_string());
+    }
+    if let Some(policy) = app.try_state::<Arc<PolicyEngine>>() {
+        policy.load(to);
+    }
+    if let Some(env) = app.try_state::<Arc<EnvWatch>>() {
+        // Same content at the new place; this only re-reads it
+        let _ = env.refresh(to);
+    }
+    if let Some(tags) = app.try_state::<Arc<TagIndex>>() {
+        tags.reset();
+    }
+    if let Some(status) = app.try_state::<Arc<StatusCache>>() {
+        status.clear();
+    }
+    if let Err(e) = watcher::repoint(app, to) {
+        eprintln!("[relocate] watching {} failed: {}", to.display(), e);
+    }
+}
+
+/// Move or copy the soul from the configured path to `to` and switch the
+/// app over. The engine and chain are stopped meanwhile and restarted if
+/// they were running.
+pub fn relocate(
+    app: &AppHandle,
+    to: &Path,
+    move_data: bool,
+    token: &CancelToken,
+) -> Result<RelocationReport, String> {
+    let from = app
+        .state::<Arc<RwLock<AppConfig>>>()
+        .read()
+        .soul_path
+        .clone();
+    validate(&from, to)?;
+    let existed = to.exists();
+    let rename = move_data && same_volume(&from, to);
+
+    let mut inv = Inventory::default();
+    scan(&from, Path::new(""), &mut inv, token)?;
+    if !rename {
+        if let Some(free) = available_space(to) {
+            if free < inv.bytes + SPACE_MARGIN {
+                return Err(format!(
+                    "Not enough space at {}: {} MB needed, {} MB free",
+                    to.display(),
+                    (inv.bytes + SPACE_MARGIN) / 1_000_000,
+                    free / 1_000_000
+                ));
+            }
+        }
+    }
+
+    let sidecar = app.state::<Arc<SidecarManager>>().inner().clone();
+    let engine = matches!(sidecar.get_status().status.as_str(), "running" | "paused");
+    let chain = sidecar.get_chain_status().status == "running";
+    if engine {
+        sidecar.stop_engine(app)?;
+    }
+    if chain {
+        sidecar.stop_chain(app)?;
+    }
+
+    let transferred = if rename {
+        // An existing (empty) target can't be renamed onto everywhere
+        let cleared = if existed { fs::remove_dir(to) } else { Ok(()) };
+        cleared
+            .and_then(|()| fs::rename(&from, to))
+            .map_err(|e| format!("Moving the soul failed: {}", e))
+    } else {
+        copy_tree(&from, to, &inv, token).inspect_err(|_| discard(to, existed))
+    };
+    // The config switches last; if it can't be saved the soul stays put
+    let switched = transferred.and_then(|()| {
+        save_soul_path(app, to).inspect_err(|_| {
+            if rename {
+                let _ = fs::rename(to, &from);
+            } else {
+                discard(to, existed);
+            }
+        })
+    });
+
+    let mut cleanup_error = None;
+    if switched.is_ok() {
+        repoint(app, to);
+        if move_data && !rename {
+            cleanup_error = fs::remove_dir_all(&from).err().map(|e| e.to_string());
+        }
+    }
+
+    if engine {
+        if let Err(e) = sidecar.start_engine(app) {
+            eprintln!("[relocate] restarting soul-engine failed: {}", e);
+        }
+    }
+    if chain {
+        if let Err(e) = sidecar.start_chain(app) {
+            eprintln!("[relocate] restarting soul-chain failed: {}", e);
+        }
+    }
+    switched?;
+
+    let report = RelocationReport {
+        from: from.to_string_lossy().to_string(),
+        to: to.to_string_lossy().to_string(),
+        method: if rename { "rename" } else { "copy" }.to_string(),
+        files: inv.files.len() as u64,
+        bytes: inv.bytes,
+        moved: move_data && cleanup_error.is_none(),
+        cleanup_error,
+    };
+    let _ = app.emit("soul:relocated", &report);
+    Ok(report)
+}
@@ -0,0 +1,296 @@
+use std::sync::Arc;
+
+use base64::Engine;
+use ring::digest;
+use tauri::{AppHandle, Listener, Manager};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::{broadcast, mpsc};
+
+use crate::channel::{self, Key};
+use crate::companion::{self, StreamGrant};
+use crate::lock::AppLock;
+
+/// Events a remote view receives, besides the snapshot it starts with
+const STREAMED_EVENTS: &[&str] = &["soul:nodes", "soul:mood", "soul:status-changed"];
+/// Events buffered per viewer; a slow one skips ahead
+const BUFFER: usize = 256;
+/// Frames from the viewer are control frames; anything larger ends the session
+const MAX_FRAME: usize = 64 * 1024;
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+const OP_BINARY: u8 = 0x2;
+const OP_CLOSE: u8 = 0x8;
+const OP_PING: u8 = 0x9;
+const OP_PONG: u8 = 0xA;
+
+#[derive(Debug, Clone)]
+enum Outgoing {
+    Event {
+        name: String,
+        payload: String,
+    },
+    /// The device's pairing was revoked; its sessions end
+    Revoked(String),
+}
+
+/// Read-only remote views of the soul state, streamed to paired devices.
+pub struct Remote {
+    sender: broadcast::Sender<Outgoing>,
+}
+
+impl Default for Remote {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(BUFFER).0,
+        }
+    }
+}
+
+impl Remote {
+    /// Forward the streamed events to every open session.
+    pub fn start(&self, app: &AppHandle) {
+        for name in STREAMED_EVENTS {
+            let sender = self.sender.clone();
+            app.listen_any(*name, move |event| {
+                let _ = sender.send(Outgoing::Event {
+                    name: name.to_string(),
+                    payload: event.payload().to_string(),
+                });
+            });
+        }
+    }
+
+    /// End the sessions of a device whose pairing was revoked.
+    pub fn revoke(&self, device_id: &str) {
+        let _ = self.sender.send(Outgoing::Revoked(device_id.to_string()));
+    }
+}
+
+/// `Sec-WebSocket-Accept` for a client's `Sec-WebSocket-Key` (RFC 6455 §4.2.2).
+fn accept_key(key: &str) -> String {
+    let hash = digest::digest(
+        &digest::SHA1_FOR_LEGACY_USE_ONLY,
+        format!("{}{}", key.trim(), WEBSOCKET_GUID).as_bytes(),
+    );
+    base64::engine::general_purpose::STANDARD.encode(hash.as_ref())
+}
+
+fn frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x80 | opcode];
+    match payload.len() {
+        len if len < 126 => out.push(len as u8),
+        len if len <= 0xFFFF => {
+            out.push(126);
+            out.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            out.push(127);
+            out.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    out.extend_from_slice(payload);
+    out
+}
+
+/// One frame from the viewer, unmasked: (opcode, payload).
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> Option<(u8, Vec<u8>)> {
+    let mut head = [0u8; 2];
+    reader.read_exact(&mut head).await.ok()?;
+    let mut len = u64::from(head[1] & 0x7F);
+    if len == 126 {
+        let mut ext = [0u8; 2];
+        reader.read_exact(&mut ext).await.ok()?;
+        len = u64::from(u16::from_be_bytes(ext));
+    } else if len == 127 {
+        let mut ext = [0u8; 8];
+        reader.read_exact(&mut ext).await.ok()?;
+        len = u64::from_be_bytes(ext);
+    }
+    if len > MAX_FRAME as u64 {
+        return None;
+    }
+    let mut mask = [0u8; 4];
+    if head[1] & 0x80 != 0 {
+        reader.read_exact(&mut mask).await.ok()?;
+    }
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload).await.ok()?;
+    for (i, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[i % 4];
+    }
+    Some((head[0] & 0x0F, payload))
+}
+
+/// One `{ event, payload }` message, sealed with the session key.
+fn seal(key: &Key, message: &serde_json::Value) -> Result<Vec<u8>, String> {
+    channel::seal(key, message.to_string().as_bytes())
+}
+
+fn locked(app: &AppHandle) -> bool {
+    app.try_state::<Arc<AppLock>>()
+        .is_some_and(|l| l.is_locked())
+}
+
+/// Serve a `GET /stream` upgrade from an authenticated device: the
+/// handshake, a snapshot, then every streamed event, each as one encrypted
+/// binary message `{ event, payload }`. The handshake carries this end's
+/// nonce (`X-Soul-Nonce`); with the device's it gives the session key
+/// (`channel::stream_key`). The viewer can't send anything but control
+/// frames.
+pub async fn stream(app: AppHandle, grant: StreamGrant, mut socket: TcpStream) {
+    let StreamGrant {
+        device: device_id,
+        key,
+        nonce,
+        websocket_key,
+    } = grant;
+    let handshake = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\nX-Soul-Nonce: {}\r\n\r\n",
+        accept_key(&websocket_key),
+        channel::to_hex(&nonce)
+    );
+    if socket.write_all(handshake.as_bytes()).await.is_err() {
+        return;
+    }
+    let Some(remote) = app.try_state::<Arc<Remote>>() else {
+        return;
+    };
+    let mut events = remote.sender.subscribe();
+    let (mut reader, mut writer) = socket.into_split();
+
+    // Frames are read on their own task: a read interrupted by an event
+    // would lose data
+    let (frames_tx, mut frames) = mpsc::channel(8);
+    let read_task = tauri::async_runtime::spawn(async move {
+        while let Some(frame) = read_frame(&mut reader).await {
+            if frames_tx.send(frame).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let snapshot = serde_json::json!({
+        "event": "snapshot",
+        "payload": companion::snapshot(&app),
+    });
+    if !locked(&app) {
+        let Ok(sealed) = seal(&key, &snapshot) else {
+            read_task.abort();
+            return;
+        };
+        if writer.write_all(&frame(OP_BINARY, &sealed)).await.is_err() {
+            read_task.abort();
+            return;
+        }
+    }
+    loop {
+        let out = tokio::select! {
+            event = events.recv() => match event {
+                Ok(Outgoing::Event { name, payload }) => {
+                    // Nothing leaves a locked app
+                    if locked(&app) {
+                        continue;
+                    }
+                    let message = serde_json::json!({
+                        "event": name,
+                        "payload": serde_json::from_str::<serde_json::Value>(&payload)
+                            .unwrap_or_default(),
+                    });
+                    match seal(&key, &message) {
+                        Ok(sealed) => frame(OP_BINARY, &sealed),
+                        Err(_) => continue,
+                    }
+                }
+                Ok(Outgoing::Revoked(id)) if id == device_id => {
+                    let _ = writer.write_all(&frame(OP_CLOSE, &[])).await;
+                    break;
+                }
+                Ok(Outgoing::Revoked(_)) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            received = frames.recv() => match received {
+                Some((OP_PING, payload)) => frame(OP_PONG, &payload),
+                Some((OP_CLOSE, _)) | None => {
+                    let _ = writer.write_all(&frame(OP_CLOSE, &[])).await;
+                    break;
+                }
+                Some(_) => continue,
+            },
+        };
+        if writer.write_all(&out).await.is_err() {
+            break;
+        }
+    }
+    read_task.abort();
+    let _ = writer.shutdown().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn read(bytes: &[u8]) -> Option<(u8, Vec<u8>)> {
+        let mut reader = bytes;
+        read_frame(&mut reader).await
+    }
+
+    #[test]
+    fn accept_key_matches_rfc_6455() {
+        // RFC 6455 §1.3
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn frames_match_rfc_6455_examples() {
+        // RFC 6455 §5.7: unmasked text "Hello", 256 and 65536 byte binary
+        assert_eq!(frame(0x1, b"Hello"), [0x81, 0x05, 0x48, 0x65, 0x6c, 0x6c, 0x6f]);
+        assert_eq!(frame(OP_BINARY, &[0; 256])[..4], [0x82, 0x7E, 0x01, 0x00]);
+        assert_eq!(
+            frame(OP_BINARY, &[0; 65536])[..10],
+            [0x82, 0x7F, 0, 0, 0, 0, 0, 0x01, 0x00, 0x00]
+        );
+        assert_eq!(frame(OP_PONG, b"").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn reads_masked_and_unmasked_frames() {
+        // RFC 6455 §5.7: masked text "Hello" and an unmasked ping
+        let masked = [0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58];
+        assert_eq!(read(&masked).await, Some((0x1, b"Hello".to_vec())));
+        let ping = [0x89, 0x05, 0x48, 0x65, 0x6c, 0x6c, 0x6f];
+        assert_eq!(read(&ping).await, Some((OP_PING, b"Hello".to_vec())));
+    }
+
+    #[tokio::test]
+    async fn reads_extended_lengths() {
+        let mut medium = vec![0x82, 0x7E, 0x01, 0x00];
+        medium.extend_from_slice(&[7; 256]);
+        assert_eq!(read(&medium).await, Some((OP_BINARY, vec![7; 256])));
+        let mut long = vec![0x82, 0x7F, 0, 0, 0, 0, 0, 0, 0x01, 0x00];
+        long.extend_from_slice(&[7; 256]);
+        assert_eq!(read(&long).await, Some((OP_BINARY, vec![7; 256])));
+    }
+
+    #[tokio::test]
+    async fn refuses_oversized_and_truncated_frames() {
+        let too_long = (MAX_FRAME as u64 + 1).to_be_bytes();
+        let mut header = vec![0x82, 0x7F];
+        header.extend_from_slice(&too_long);
+        assert_eq!(read(&header).await, None);
+        assert_eq!(read(&[0x81, 0x05, 0x48]).await, None);
+        assert_eq!(read(&[0x81]).await, None);
+    }
+
+    #[test]
+    fn sealed_messages_open_with_the_session_key() {
+        let key = channel::new_key();
+        let message = serde_json::json!({ "event": "soul:mood", "payload": 1 });
+        let sealed = seal(&key, &message).unwrap();
+        let opened = channel::open(&key, &sealed).unwrap();
+        assert_eq!(serde_json::from_slice::<serde_json::Value>(&opened).unwrap(), message);
+        assert!(channel::open(&channel::new_key(), &sealed).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+use crate::config::app_data_dir;
+use crate::pty::PtyManager;
+use crate::routing;
+
+/// A terminal that was open when the app last quit.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RestoreTerminal {
+    /// Working directory of the shell at shutdown
+    pub cwd: String,
+    /// Last title the shell set (OSC 0/2)
+    pub title: Option<String>,
+    /// Label the frontend gave the terminal when opening it
+    pub purpose: Option<String>,
+    pub restricted: bool,
+    /// Lived in its own window
+    pub detached: bool,
+}
+
+/// What was open at the last shutdown, offered once as "restore previous
+/// session".
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RestorePlan {
+    /// Unix ms of the shutdown
+    #[ts(type = "number")]
+    pub saved_at: u64,
+    pub terminals: Vec<RestoreTerminal>,
+    /// Views/panels open in the main window, as the frontend reported them
+    pub views: Vec<String>,
+}
+
+/// The plan left by the previous run, read once at startup so this run's
+/// shutdown can't overwrite it before the frontend asked.
+pub struct SessionRestore {
+    plan: Mutex<Option<RestorePlan>>,
+    views: Mutex<Vec<String>>,
+}
+
+fn session_path() -> PathBuf {
+    app_data_dir().join("session.json")
+}
+
+impl SessionRestore {
+    pub fn load() -> Self {
+        let plan = fs::read_to_string(session_path())
+            .ok()
+            .and_then(|content| serde_json::from_str::<RestorePlan>(&content).ok())
+            .filter(|plan| !plan.terminals.is_empty() || !plan.views.is_empty());
+        Self {
+            plan: Mutex::new(plan),
+            views: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn plan(&self) -> Option<RestorePlan> {
+        self.plan.lock().clone()
+    }
+
+    /// Drop the previous run's plan once it was restored or declined.
+    pub fn dismiss(&self) {
+        self.plan.lock().take();
+    }
+
+    pub fn set_views(&self, views: Vec<String>) {
+        *self.views.lock() = views;
+    }
+}
+
+/// Write what's open now for the next start. Called before the terminals
+/// are shut down; a plan that was never dismissed is kept if nothing is
+/// open this time.
+pub fn save(app: &AppHandle) {
+    let Some(restore) = app.try_state::<Arc<SessionRestore>>() else {
+        return;
+    };
+    let terminals: Vec<RestoreTerminal> = app
+        .try_state::<Arc<PtyManager>>()
+        .map(|pty| pty.restore_state())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(id, mut terminal)| {
+            terminal.detached = routing::is_detached(app, id);
+            terminal
+        })
+        .collect();
+    let views = restore.views.lock().clone();
+    let plan = if terminals.is_empty() && views.is_empty() {
+        match restore.plan() {
+            Some(previous) => previous,
+            None => {
+                let _ = fs::remove_file(session_path());
+                return;
+            }
+        }
+    } else {
+        RestorePlan {
+            saved_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            terminals,
+            views,
+        }
+    };
+    let path = session_path();
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Ok(json) = serde_json::to_string_pretty(&plan) {
+        if let Err(e) = fs::write(&path, json) {
+            eprintln!("[restore] Failed to save session: {}", e);
+        }
+    }
+}
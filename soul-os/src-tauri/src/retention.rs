@@ -0,0 +1,181 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+use crate::background;
+use crate::config::AppConfig;
+use crate::pty_history::CommandHistory;
+
+/// How often the scheduler enforces the retention policy
+const PURGE_INTERVAL: Duration = Duration::from_secs(6 * 3600);
+const DAY_MS: u64 = 24 * 3600 * 1000;
+/// The event bus's live file; only rotated archives expire
+const CURRENT_EVENTS: &str = "current.jsonl";
+
+/// How long old data is kept, per category, in days. 0 keeps it forever.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RetentionConfig {
+    /// Archived event-bus files in .soul-events (not current.jsonl)
+    #[serde(default = "default_event_archive_days")]
+    pub event_archive_days: u32,
+    /// Commands recorded from the terminals (pty-history.jsonl)
+    #[serde(default = "default_pty_history_days")]
+    pub pty_history_days: u32,
+}
+
+fn default_event_archive_days() -> u32 {
+    180
+}
+
+fn default_pty_history_days() -> u32 {
+    90
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            event_archive_days: default_event_archive_days(),
+            pty_history_days: default_pty_history_days(),
+        }
+    }
+}
+
+/// Something the policy removes (or would remove).
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct PurgeItem {
+    /// "event_archive" or "pty_history"
+    pub category: String,
+    /// File, relative to the soul for event archives
+    pub path: String,
+    /// Records removed from the file; 0 when the whole file goes
+    pub records: usize,
+    #[ts(type = "number")]
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct PurgeReport {
+    /// Nothing was removed (`preview_purge`)
+    pub dry_run: bool,
+    pub items: Vec<PurgeItem>,
+    #[ts(type = "number")]
+    pub total_bytes: u64,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Unix ms before which data of a `days` policy expires; None keeps all.
+fn cutoff(days: u32) -> Option<u64> {
+    (days > 0).then(|| now_ms().saturating_sub(u64::from(days) * DAY_MS))
+}
+
+/// Rotated `.soul-events/*.jsonl` files last written before `before`.
+fn event_archives(soul_path: &Path, before: u64, dry_run: bool) -> Vec<PurgeItem> {
+    let Ok(entries) = fs::read_dir(soul_path.join(".soul-events")) else {
+        return Vec::new();
+    };
+    let mut items = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name == CURRENT_EVENTS || path.extension().is_none_or(|e| e != "jsonl") {
+            continue;
+        }
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        let modified = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(u64::MAX, |d| d.as_millis() as u64);
+        if modified >= before {
+            continue;
+        }
+        if !dry_run {
+            if let Err(e) = fs::remove_file(&path) {
+                eprintln!("[retention] removing {} failed: {}", path.display(), e);
+                continue;
+            }
+        }
+        items.push(PurgeItem {
+            category: "event_archive".to_string(),
+            path: format!(".soul-events/{}", name),
+            records: 0,
+            bytes: meta.len(),
+        });
+    }
+    items.sort_by(|a, b| a.path.cmp(&b.path));
+    items
+}
+
+/// Apply the retention policy, or with `dry_run` only report what it
+/// would remove.
+pub fn purge(app: &AppHandle, dry_run: bool) -> Result<PurgeReport, String> {
+    let (soul_path, policy) = {
+        let config = app.state::<Arc<RwLock<AppConfig>>>();
+        let config = config.read();
+        (config.soul_path.clone(), config.retention.clone())
+    };
+    let mut items = Vec::new();
+    if let Some(before) = cutoff(policy.event_archive_days) {
+        items.extend(event_archives(&soul_path, before, dry_run));
+    }
+    if let (Some(before), Some(history)) = (
+        cutoff(policy.pty_history_days),
+        app.try_state::<Arc<CommandHistory>>(),
+    ) {
+        let (records, bytes) = history.prune(before, dry_run)?;
+        if records > 0 {
+            items.push(PurgeItem {
+                category: "pty_history".to_string(),
+                path: "pty-history.jsonl".to_string(),
+                records,
+                bytes,
+            });
+        }
+    }
+    Ok(PurgeReport {
+        dry_run,
+        total_bytes: items.iter().map(|i| i.bytes).sum(),
+        items,
+    })
+}
+
+/// Enforces the retention policy every few hours while the app runs.
+pub fn start_purge_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(PURGE_INTERVAL).await;
+            if background::suspended(&app) {
+                continue;
+            }
+            let handle = app.clone();
+            match tokio::task::spawn_blocking(move || purge(&handle, false)).await {
+                Ok(Ok(report)) if !report.items.is_empty() => {
+                    eprintln!(
+                        "[retention] purged {} item(s), {} bytes",
+                        report.items.len(),
+                        report.total_bytes
+                    );
+                }
+                Ok(Err(e)) => eprintln!("[retention] purge failed: {}", e),
+                _ => {}
+            }
+        }
+    });
+}
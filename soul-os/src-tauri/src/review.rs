@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use ts_rs::TS;
+
+use crate::config::app_data_dir;
+
+/// An engine write held back until the user approves or rejects it.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct PendingChange {
+    pub id: String,
+    /// Path relative to soul_path
+    pub path: String,
+    /// Canonical content (None = file didn't exist)
+    pub before: Option<String>,
+    /// Content the engine wrote (None = engine deleted the file)
+    pub after: Option<String>,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+    #[ts(type = "number")]
+    pub timestamp: u64,
+    /// Set for changes the app proposed together (a memory rollup), which
+    /// can be approved or rejected as one
+    #[serde(default)]
+    pub group: Option<String>,
+}
+
+#[derive(Default)]
+struct ReviewInner {
+    enabled: bool,
+    /// Canonical content of every soul-state file while review mode is on
+    baselines: HashMap<String, Option<String>>,
+    pending: Vec<PendingChange>,
+}
+
+/// Review mode: engine changes to soul-state files are reverted on disk and
+/// staged in a shadow store (<app_data_dir>/review) until approved.
+#[derive(Default)]
+pub struct ReviewQueue {
+    inner: RwLock<ReviewInner>,
+    next_id: AtomicU64,
+}
+
+fn shadow_path() -> PathBuf {
+    app_data_dir().join("review").join("pending.json")
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Rough line diff stats: lines only present on one side.
+fn line_stats(before: Option<&str>, after: Option<&str>) -> (usize, usize) {
+    let before: Vec<&str> = before.unwrap_or("").lines().collect();
+    let after: Vec<&str> = after.unwrap_or("").lines().collect();
+    let added = after.iter().filter(|l| !before.contains(l)).count();
+    let removed = before.iter().filter(|l| !after.contains(l)).count();
+    (added, removed)
+}
+
+fn write_content(path: &Path, content: Option<&str>) -> Result<(), String> {
+    match content {
+        Some(content) => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            fs::write(path, content).map_err(|e| e.to_string())
+        }
+        None if path.exists() => fs::remove_file(path).map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
+
+impl ReviewQueue {
+    /// Restore review mode and any pending changes from the shadow store.
+    pub fn new(enabled: bool, soul_path: &Path, tracked: impl Fn(&str) -> bool) -> Self {
+        let queue = Self::default();
+        if let Ok(data) = fs::read_to_string(shadow_path()) {
+            if let Ok(pending) = serde_json::from_str::<Vec<PendingChange>>(&data) {
+                queue.inner.write().pending = pending;
+            }
+        }
+        if enabled {
+            queue.set_enabled(true, soul_path, tracked);
+        }
+        queue
+    }
+
+    /// Turning review mode on snapshots the current soul state as canonical.
+    pub fn set_enabled(&self, enabled: bool, soul_path: &Path, tracked: impl Fn(&str) -> bool) {
+        let mut baselines = HashMap::new();
+        if enabled {
+            let mut dirs = vec![soul_path.to_path_buf()];
+            while let Some(dir) = dirs.pop() {
+                let Ok(entries) = fs::read_dir(&dir) else {
+                    continue;
+                };
+                for entry in entries.flatten() {
+                    let path = entry.path();
+                    let Ok(relative) = path.strip_prefix(soul_path) else {
+                        continue;
+                    };
+                    let relative = relative.to_string_lossy().replace('\\', "/");
+                    if path.is_dir() {
+                        if !relative.starts_with('.') && relative != "node_modules" {
+                            dirs.push(path);
+                        }
+                    } else if tracked(&relative) {
+                        baselines.insert(relative, fs::read_to_string(&path).ok());
+                    }
+                }
+            }
+        }
+        let mut inner = self.inner.write();
+        inner.enabled = enabled;
+        inner.baselines = baselines;
+    }
+
+    /// Record a change made through the app as canonical.
+    pub fn accept(&self, relative: &str, content: Option<&str>) {
+        let mut inner = self.inner.write();
+        if inner.enabled {
+            inner
+                .baselines
+                .insert(relative.to_string(), content.map(|c| c.to_string()));
+        }
+    }
+
+    /// Called by the watcher for soul-state files. Stages an engine change,
+    /// restores the canonical content and emits `review:pending`.
+    pub fn intercept(&self, app: &AppHandle, soul_path: &Path, relative: &str) {
+        let path = soul_path.join(relative);
+        let after = fs::read_to_string(&path).ok();
+
+        let change = {
+            let mut inner = self.inner.write();
+            if !inner.enabled {
+                return;
+            }
+            let before = inner.baselines.get(relative).cloned().flatten();
+            if before == after {
+                // Our own revert/approval, or an app write accepted up front
+                return;
+            }
+
+            // Successive engine writes to one file collapse into one pending change
+            let (added, removed) = line_stats(before.as_deref(), after.as_deref());
+            let change = match inner.pending.iter_mut().find(|c| c.path == relative) {
+                Some(existing) => {
+                    existing.after = after.clone();
+                    existing.lines_added = added;
+                    existing.lines_removed = removed;
+                    existing.timestamp = now_ms();
+                    existing.clone()
+                }
+                None => {
+                    let change = PendingChange {
+                        id: format!(
+                            "{}-{}",
+                            now_ms(),
+                            self.next_id.fetch_add(1, Ordering::Relaxed)
+                        ),
+                        path: relative.to_string(),
+                        before: before.clone(),
+                        after: after.clone(),
+                        lines_added: added,
+                        lines_removed: removed,
+                        timestamp: now_ms(),
+                        group: None,
+                    };
+                    inner.pending.push(change.clone());
+                    change
+                }
+            };
+            self.persist(&inner.pending);
+            change
+        };
+
+        let _ = write_content(&path, change.before.as_deref());
+        let _ = app.emit("review:pending", &change);
+    }
+
+    /// Stage changes the app itself proposes, whether or not review mode is
+    /// on: (path relative to soul_path, new content or None to delete).
+    /// Nothing is written until they are approved. Emits `review:pending`.
+    pub fn propose(
+        &self,
+        app: &AppHandle,
+        soul_path: &Path,
+        group: &str,
+        changes: Vec<(String, Option<String>)>,
+    ) -> Vec<PendingChange> {
+        let proposed: Vec<PendingChange> = {
+            let mut inner = self.inner.write();
+            let mut proposed = Vec::new();
+            for (path, after) in changes {
+                let before = fs::read_to_string(soul_path.join(&path)).ok();
+                if before == after {
+                    continue;
+                }
+                let (added, removed) = line_stats(before.as_deref(), after.as_deref());
+                let change = PendingChange {
+                    id: format!(
+                        "{}-{}",
+                        now_ms(),
+                        self.next_id.fetch_add(1, Ordering::Relaxed)
+                    ),
+                    path,
+                    before,
+                    after,
+                    lines_added: added,
+                    lines_removed: removed,
+                    timestamp: now_ms(),
+                    group: Some(group.to_string()),
+                };
+                inner.pending.push(change.clone());
+                proposed.push(change);
+            }
+            self.persist(&inner.pending);
+            proposed
+        };
+        for change in &proposed {
+            let _ = app.emit("review:pending", change);
+        }
+        proposed
+    }
+
+    /// Ids of the pending changes in `group`.
+    pub fn group(&self, group: &str) -> Vec<String> {
+        self.inner
+            .read()
+            .pending
+            .iter()
+            .filter(|c| c.group.as_deref() == Some(group))
+            .map(|c| c.id.clone())
+            .collect()
+    }
+
+    pub fn list(&self) -> Vec<PendingChange> {
+        self.inner.read().pending.clone()
+    }
+
+    /// Apply a pending change to the soul and make it canonical.
+    pub fn approve(&self, soul_path: &Path, id: &str) -> Result<PendingChange, String> {
+        let change = self.take(id)?;
+        // Accept first so the watcher sees the write as canonical, not as a new change
+        self.inner
+            .write()
+            .baselines
+            .insert(change.path.clone(), change.after.clone());
+        write_content(&soul_path.join(&change.path), change.after.as_deref())?;
+        Ok(change)
+    }
+
+    /// Drop a pending change — the file already holds the canonical content.
+    pub fn reject(&self, id: &str) -> Result<PendingChange, String> {
+        self.take(id)
+    }
+
+    fn take(&self, id: &str) -> Result<PendingChange, String> {
+        let mut inner = self.inner.write();
+        let idx = inner
+            .pending
+            .iter()
+            .position(|c| c.id == id)
+            .ok_or_else(|| format!("No pending change {}", id))?;
+        let change = inner.pending.remove(idx);
+        self.persist(&inner.pending);
+        Ok(change)
+    }
+
+    fn persist(&self, pending: &[PendingChange]) {
+        let path = shadow_path();
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(pending) {
+            let _ = fs::write(path, json);
+        }
+    }
+}
@@ -0,0 +1,327 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Datelike, Local, NaiveDate};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+use crate::assistant;
+use crate::background;
+use crate::blocking::run_blocking;
+use crate::config::{app_data_dir, AppConfig};
+use crate::export::files_in;
+use crate::paths::{self, Location, Memories};
+use crate::review::ReviewQueue;
+use crate::soul_identity;
+use crate::vault;
+
+/// How often the scheduler checks whether a rollup is due
+const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 3600);
+const WEEK_MS: u64 = 7 * 24 * 3600 * 1000;
+/// A week with fewer memories isn't worth condensing
+const MIN_MEMORIES: usize = 2;
+/// Weeks summarized per run, oldest first; the rest wait for the next one
+const MAX_WEEKS: usize = 4;
+
+/// Weekly condensing of old episodic memories into semantic ones.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RollupConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Episodic memories older than this are rolled up
+    #[serde(default = "default_min_age_days")]
+    pub min_age_days: u32,
+}
+
+fn default_min_age_days() -> u32 {
+    30
+}
+
+impl Default for RollupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_age_days: default_min_age_days(),
+        }
+    }
+}
+
+/// One week of memories condensed into a semantic memory, waiting in the
+/// review queue.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct RollupProposal {
+    /// Review group; `approve_change_group` applies the whole rollup
+    pub group: String,
+    /// ISO week, e.g. "2026-W07"
+    pub period: String,
+    /// New semantic memory, relative to the soul
+    pub summary_path: String,
+    /// Episodic memories it condenses
+    pub sources: Vec<String>,
+    /// Where they move in the archive, in the order of `sources`
+    pub archived: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct RollupReport {
+    pub proposals: Vec<RollupProposal>,
+    /// Weeks left out and why (already rolled up, assistant failed, ...)
+    pub skipped: Vec<String>,
+}
+
+struct Episode {
+    relative: String,
+    date: NaiveDate,
+    content: String,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// <app_data_dir>/rollup/<soul>.json: when the last weekly rollup ran.
+fn state_path(soul_path: &Path) -> PathBuf {
+    app_data_dir()
+        .join("rollup")
+        .join(format!("{}.json", soul_identity::storage_key(soul_path)))
+}
+
+fn last_run(soul_path: &Path) -> u64 {
+    fs::read_to_string(state_path(soul_path))
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|state| state["last_run"].as_u64())
+        .unwrap_or(0)
+}
+
+fn record_run(soul_path: &Path) {
+    let path = state_path(soul_path);
+    let written = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| {
+            fs::write(
+                &path,
+                serde_json::json!({ "last_run": now_ms() }).to_string(),
+            )
+        });
+    if let Err(e) = written {
+        eprintln!("[rollup] saving {} failed: {}", path.display(), e);
+    }
+}
+
+/// The day a memory is about: a YYYY-MM-DD file name prefix, else the day
+/// it was last written.
+fn memory_date(path: &Path) -> Option<NaiveDate> {
+    let name = path.file_name()?.to_string_lossy();
+    if let Some(date) = name
+        .get(..10)
+        .and_then(|prefix| NaiveDate::parse_from_str(prefix, "%Y-%m-%d").ok())
+    {
+        return Some(date);
+    }
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    Some(DateTime::<Local>::from(modified).date_naive())
+}
+
+fn iso_week(date: NaiveDate) -> String {
+    let week = date.iso_week();
+    format!("{}-W{:02}", week.year(), week.week())
+}
+
+/// Episodic memories older than `min_age_days`, by ISO week, oldest week
+/// first. Encrypted memories stay out: their text isn't sent to a model.
+fn eligible(
+    soul_path: &Path,
+    min_age_days: u32,
+    pending: &[String],
+) -> Vec<(String, Vec<Episode>)> {
+    let cutoff = Local::now().date_naive() - chrono::Duration::days(i64::from(min_age_days));
+    let mut weeks: BTreeMap<String, Vec<Episode>> = BTreeMap::new();
+    for dir in paths::existing(soul_path, Location::Memory(Memories::Episodic)) {
+        for file in files_in(&dir, true) {
+            if file.extension().is_none_or(|ext| ext != "md") {
+                continue;
+            }
+            let Some(relative) = file
+                .strip_prefix(soul_path)
+                .ok()
+                .map(|p| p.to_string_lossy().replace('\\', "/"))
+            else {
+                continue;
+            };
+            if pending.contains(&relative) {
+                continue;
+            }
+            let Some(date) = memory_date(&file).filter(|date| *date < cutoff) else {
+                continue;
+            };
+            let Ok(data) = fs::read(&file) else {
+                continue;
+            };
+            if vault::is_encrypted(&data) {
+                continue;
+            }
+            let Ok(content) = String::from_utf8(data) else {
+                continue;
+            };
+            weeks.entry(iso_week(date)).or_default().push(Episode {
+                relative,
+                date,
+                content,
+            });
+        }
+    }
+    weeks
+        .into_iter()
+        .filter(|(_, episodes)| episodes.len() >= MIN_MEMORIES)
+        .map(|(week, mut episodes)| {
+            episodes.sort_by(|a, b| {
+                a.date
+                    .cmp(&b.date)
+                    .then_with(|| a.relative.cmp(&b.relative))
+            });
+            (week, episodes)
+        })
+        .collect()
+}
+
+/// Where an episodic memory goes in the archive, keeping its sub-path.
+fn archive_path(soul_path: &Path, relative: &str) -> String {
+    let archive = paths::relative(soul_path, Location::Memory(Memories::Archive));
+    let within = Location::Memory(Memories::Episodic)
+        .variants()
+        .into_iter()
+        .find_map(|dir| relative.strip_prefix(dir))
+        .map(|rest| rest.trim_start_matches('/'))
+        .unwrap_or(relative);
+    format!("{}/{}", archive, within)
+}
+
+fn render(period: &str, episodes: &[Episode], archived: &[String], summary: &str) -> String {
+    let (first, last) = (episodes[0].date, episodes[episodes.len() - 1].date);
+    let mut out = format!(
+        "# Week {}\n\n> Rollup of {} episodic memories, {} to {}\n",
+        period,
+        episodes.len(),
+        first.format("%Y-%m-%d"),
+        last.format("%Y-%m-%d"),
+    );
+    out.push_str(&format!(
+        "> Rolled up: {}\n\n{}\n\n## Originals\n\n",
+        Local::now().format("%Y-%m-%d %H:%M"),
+        summary
+    ));
+    for path in archived {
+        out.push_str(&format!("- [[{}]]\n", path.trim_end_matches(".md")));
+    }
+    out
+}
+
+/// Summarize the eligible weeks and stage each rollup in the review
+/// queue: the new semantic memory, plus every original moved to the
+/// archive. Nothing changes on disk until the group is approved.
+pub async fn run(app: &AppHandle) -> Result<RollupReport, String> {
+    let (soul_path, config) = {
+        let config = app.state::<Arc<RwLock<AppConfig>>>();
+        let config = config.read();
+        (config.soul_path.clone(), config.rollup.clone())
+    };
+    let review = app.state::<Arc<ReviewQueue>>().inner().clone();
+    let pending: Vec<String> = review.list().into_iter().map(|c| c.path).collect();
+    let sp = soul_path.clone();
+    let weeks = run_blocking(app, "rollup_scan", None, move |_| {
+        Ok(eligible(&sp, config.min_age_days, &pending))
+    })
+    .await?;
+
+    let semantic = paths::relative(&soul_path, Location::Memory(Memories::Semantic));
+    let mut proposals = Vec::new();
+    let mut skipped = Vec::new();
+    for (period, episodes) in weeks {
+        if proposals.len() >= MAX_WEEKS {
+            skipped.push(format!("{}: left for the next run", period));
+            continue;
+        }
+        let summary_path = format!("{}/{}-rollup.md", semantic, period);
+        if soul_path.join(&summary_path).exists() {
+            skipped.push(format!("{}: {} already exists", period, summary_path));
+            continue;
+        }
+        let memories = episodes
+            .iter()
+            .map(|e| (e.relative.clone(), e.content.clone()))
+            .collect();
+        let summary = match assistant::rollup(app, soul_path.clone(), &period, memories).await {
+            Ok(summary) => summary,
+            Err(e) => {
+                skipped.push(format!("{}: {}", period, e));
+                continue;
+            }
+        };
+        let archived: Vec<String> = episodes
+            .iter()
+            .map(|e| archive_path(&soul_path, &e.relative))
+            .collect();
+        let mut changes = vec![(
+            summary_path.clone(),
+            Some(render(&period, &episodes, &archived, &summary)),
+        )];
+        for (episode, target) in episodes.iter().zip(&archived) {
+            changes.push((target.clone(), Some(episode.content.clone())));
+            changes.push((episode.relative.clone(), None));
+        }
+        let group = format!("rollup-{}", period);
+        review.propose(app, &soul_path, &group, changes);
+        proposals.push(RollupProposal {
+            group,
+            period,
+            summary_path,
+            sources: episodes.into_iter().map(|e| e.relative).collect(),
+            archived,
+        });
+    }
+    record_run(&soul_path);
+    Ok(RollupReport { proposals, skipped })
+}
+
+/// Proposes rollups once a week while `rollup.enabled` is on.
+pub fn start_rollup_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            let (enabled, soul_path) = {
+                let config = app.state::<Arc<RwLock<AppConfig>>>();
+                let config = config.read();
+                (config.rollup.enabled, config.soul_path.clone())
+            };
+            if !enabled || background::suspended(&app) {
+                continue;
+            }
+            if now_ms().saturating_sub(last_run(&soul_path)) < WEEK_MS {
+                continue;
+            }
+            match run(&app).await {
+                Ok(report) if !report.proposals.is_empty() => {
+                    eprintln!(
+                        "[rollup] proposed {} rollup(s) for review",
+                        report.proposals.len()
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("[rollup] failed: {}", e),
+            }
+        }
+    });
+}
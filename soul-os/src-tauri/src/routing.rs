@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+
+use crate::pty::PtyManager;
+
+const MAIN_LABEL: &str = "main";
+
+/// Label of the window a detached terminal lives in
+fn pty_window_label(id: u32) -> String {
+    format!("terminal-{}", id)
+}
+
+/// Which window receives each terminal's events. Terminals without an entry
+/// belong to the main window.
+#[derive(Default)]
+pub struct WindowRouter {
+    ptys: Mutex<HashMap<u32, String>>,
+}
+
+impl WindowRouter {
+    fn target(&self, id: u32) -> String {
+        self.ptys
+            .lock()
+            .get(&id)
+            .cloned()
+            .unwrap_or_else(|| MAIN_LABEL.to_string())
+    }
+
+    fn release(&self, id: u32) -> Option<String> {
+        self.ptys.lock().remove(&id)
+    }
+}
+
+/// Emit a `pty:*` event to the window currently hosting terminal `id`.
+pub fn emit_pty<S: Serialize + Clone>(app: &AppHandle, id: u32, event: &str, payload: S) {
+    let target = app
+        .try_state::<Arc<WindowRouter>>()
+        .map_or_else(|| MAIN_LABEL.to_string(), |r| r.target(id));
+    let _ = app.emit_to(target.as_str(), event, payload);
+}
+
+/// Move terminal `id` into its own window (index.html?pty=<id>) and route its
+/// output there. Closing that window attaches the terminal again.
+pub fn detach_pty(app: &AppHandle, id: u32) -> Result<String, String> {
+    let pty = app.state::<Arc<PtyManager>>();
+    if !pty.exists(id) {
+        return Err(format!("PTY {} not found", id));
+    }
+    let router = app.state::<Arc<WindowRouter>>().inner().clone();
+    let label = pty_window_label(id);
+    if let Some(existing) = app.get_webview_window(&label) {
+        let _ = existing.set_focus();
+        return Ok(label);
+    }
+
+    let window = WebviewWindowBuilder::new(
+        app,
+        &label,
+        WebviewUrl::App(format!("index.html?pty={}", id).into()),
+    )
+    .title(format!("SoulOS Terminal {}", id))
+    .inner_size(800.0, 500.0)
+    .build()
+    .map_err(|e| e.to_string())?;
+
+    router.ptys.lock().insert(id, label.clone());
+    let handle = app.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Destroyed = event {
+            if handle
+                .try_state::<Arc<WindowRouter>>()
+                .and_then(|r| r.release(id))
+                .is_some()
+            {
+                let _ = handle.emit_to(MAIN_LABEL, "pty:attached", serde_json::json!({ "id": id }));
+            }
+        }
+    });
+    let _ = app.emit_to(
+        MAIN_LABEL,
+        "pty:detached",
+        serde_json::json!({ "id": id, "window": label }),
+    );
+    Ok(label)
+}
+
+/// Route terminal `id` back to the main window and close its own window.
+pub fn attach_pty(app: &AppHandle, id: u32) -> Result<(), String> {
+    let router = app.state::<Arc<WindowRouter>>();
+    let Some(label) = router.release(id) else {
+        return Err(format!("PTY {} is not detached", id));
+    };
+    if let Some(window) = app.get_webview_window(&label) {
+        let _ = window.destroy();
+    }
+    let _ = app.emit_to(MAIN_LABEL, "pty:attached", serde_json::json!({ "id": id }));
+    Ok(())
+}
+
+/// Whether terminal `id` lives in its own window.
+pub fn is_detached(app: &AppHandle, id: u32) -> bool {
+    app.try_state::<Arc<WindowRouter>>()
+        .is_some_and(|router| router.ptys.lock().contains_key(&id))
+}
+
+/// Close the window of a terminal that was closed.
+pub fn forget_pty(app: &AppHandle, id: u32) {
+    let Some(router) = app.try_state::<Arc<WindowRouter>>() else {
+        return;
+    };
+    if let Some(label) = router.release(id) {
+        if let Some(window) = app.get_webview_window(&label) {
+            let _ = window.destroy();
+        }
+    }
+}
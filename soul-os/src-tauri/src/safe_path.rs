@@ -0,0 +1,316 @@
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+
+use crate::backend::FileStore;
+
+pub const TRAVERSAL: &str = "Access denied: path traversal not allowed";
+pub const OUTSIDE: &str = "Access denied: path outside soul directory";
+const ABSOLUTE: &str = "Access denied: absolute paths not allowed";
+
+/// `name` as a path relative to the soul, checked without touching the
+/// disk. Backslashes count as separators on every platform; `..`, absolute
+/// paths, drive letters, UNC shares and NUL bytes are refused.
+pub fn relative(name: &str) -> Result<PathBuf, String> {
+    if name.contains('\0') {
+        return Err("Invalid path: contains a NUL byte".to_string());
+    }
+    let name = name.replace('\\', "/");
+    let bytes = name.as_bytes();
+    // Absolute on Windows whatever the host: C:foo, C:/foo, //server/share
+    let drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    if drive || name.starts_with('/') {
+        return Err(ABSOLUTE.to_string());
+    }
+    let mut relative = PathBuf::new();
+    for component in Path::new(&name).components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => return Err(TRAVERSAL.to_string()),
+            Component::RootDir | Component::Prefix(_) => return Err(ABSOLUTE.to_string()),
+        }
+    }
+    Ok(relative)
+}
+
+/// The real location of soul file `name`, refused unless it lies inside
+/// the soul directory once symlinks are resolved. Works for targets that
+/// don't exist yet: the deepest existing ancestor is resolved and the rest
+/// appended, and a dangling symlink on the way is refused, since writing
+/// through it would create its target wherever it points.
+pub fn safe_resolve(
+    files: &dyn FileStore,
+    soul_path: &Path,
+    name: &str,
+) -> Result<PathBuf, String> {
+    let relative = relative(name)?;
+    let root = files
+        .canonicalize(soul_path)
+        .map_err(|e| format!("Cannot resolve soul directory: {}", e))?;
+    let mut existing = soul_path.join(&relative);
+    let mut missing: Vec<OsString> = Vec::new();
+    let resolved = loop {
+        if let Ok(real) = files.canonicalize(&existing) {
+            break real;
+        }
+        if files.is_symlink(&existing) {
+            return Err(OUTSIDE.to_string());
+        }
+        match existing.file_name() {
+            Some(part) => missing.push(part.to_os_string()),
+            None => return Err(OUTSIDE.to_string()),
+        }
+        if !existing.pop() {
+            return Err(OUTSIDE.to_string());
+        }
+    };
+    let resolved = missing
+        .into_iter()
+        .rev()
+        .fold(resolved, |path, part| path.join(part));
+    if !resolved.starts_with(&root) {
+        return Err(OUTSIDE.to_string());
+    }
+    Ok(resolved)
+}
+
+/// Where `name` really is, as `safe_resolve` finds it, relative to the
+/// soul with `/` separators: the file a write to `name` changes.
+pub fn resolve_name(files: &dyn FileStore, soul_path: &Path, name: &str) -> Result<String, String> {
+    let resolved = safe_resolve(files, soul_path, name)?;
+    let root = files
+        .canonicalize(soul_path)
+        .map_err(|e| format!("Cannot resolve soul directory: {}", e))?;
+    let parts: Vec<String> = resolved
+        .strip_prefix(&root)
+        .map_err(|_| OUTSIDE.to_string())?
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    Ok(parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::OsFileStore;
+    use crate::testing::MemoryFileStore;
+
+    const SOUL: &str = "/soul";
+
+    fn soul() -> MemoryFileStore {
+        MemoryFileStore::with_files(&[
+            ("/soul/SEED.md", "# Seed\n"),
+            ("/soul/memories/episodic/2026-03-13.md", "first"),
+            ("/soul-backup/SEED.md", "# Old seed\n"),
+            ("/outside/secret.txt", "nope"),
+        ])
+    }
+
+    fn resolve(name: &str) -> Result<PathBuf, String> {
+        safe_resolve(&soul(), Path::new(SOUL), name)
+    }
+
+    #[test]
+    fn resolves_names_inside_the_soul() {
+        assert_eq!(resolve("SEED.md").unwrap(), Path::new("/soul/SEED.md"));
+        assert_eq!(resolve("./SEED.md").unwrap(), Path::new("/soul/SEED.md"));
+        assert_eq!(
+            resolve("memories\\episodic\\2026-03-13.md").unwrap(),
+            Path::new("/soul/memories/episodic/2026-03-13.md")
+        );
+        assert_eq!(resolve("").unwrap(), Path::new("/soul"));
+        // Dots inside a name are not traversal
+        assert_eq!(resolve("notes..md").unwrap(), Path::new("/soul/notes..md"));
+        assert_eq!(resolve("...").unwrap(), Path::new("/soul/..."));
+    }
+
+    #[test]
+    fn resolves_targets_that_do_not_exist_yet() {
+        assert_eq!(
+            resolve("memories/core/deep/new.md").unwrap(),
+            Path::new("/soul/memories/core/deep/new.md")
+        );
+    }
+
+    #[test]
+    fn refuses_parent_components_in_any_spelling() {
+        for name in [
+            "..",
+            "../outside/secret.txt",
+            "memories/../../outside/secret.txt",
+            "memories/..",
+            "..\\outside\\secret.txt",
+            "memories\\..\\..\\outside",
+            "./../outside",
+            "memories/episodic/../../../soul-backup/SEED.md",
+        ] {
+            assert_eq!(resolve(name).unwrap_err(), TRAVERSAL, "{}", name);
+        }
+    }
+
+    #[test]
+    fn refuses_absolute_and_windows_prefixed_paths() {
+        for name in [
+            "/outside/secret.txt",
+            "/soul/SEED.md",
+            "\\outside\\secret.txt",
+            "C:\\Windows\\win.ini",
+            "c:/Windows/win.ini",
+            "C:secret.txt",
+            "\\\\server\\share\\secret.txt",
+            "//server/share/secret.txt",
+            "\\\\?\\C:\\secret.txt",
+            "\\\\.\\pipe\\secret",
+        ] {
+            assert_eq!(resolve(name).unwrap_err(), ABSOLUTE, "{}", name);
+        }
+    }
+
+    #[test]
+    fn refuses_nul_bytes() {
+        assert!(resolve("SEED.md\0.txt").unwrap_err().contains("NUL"));
+    }
+
+    #[test]
+    fn refuses_a_sibling_sharing_the_soul_prefix() {
+        // "/soul-backup" starts with "/soul" as a string, not as a path
+        let files = soul();
+        let err = safe_resolve(&files, Path::new("/soul"), "../soul-backup/SEED.md").unwrap_err();
+        assert_eq!(err, TRAVERSAL);
+    }
+
+    #[test]
+    fn reports_a_missing_soul_directory() {
+        let err = safe_resolve(&soul(), Path::new("/missing"), "SEED.md").unwrap_err();
+        assert!(err.starts_with("Cannot resolve soul directory"), "{}", err);
+    }
+
+    /// A real directory for symlink cases, removed when dropped.
+    #[cfg(unix)]
+    struct Scratch(PathBuf);
+
+    #[cfg(unix)]
+    impl Scratch {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "soulos-safe-path-{}-{}",
+                std::process::id(),
+                name
+            ));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(dir.join("soul/memories")).unwrap();
+            std::fs::create_dir_all(dir.join("outside")).unwrap();
+            std::fs::write(dir.join("soul/SEED.md"), "# Seed\n").unwrap();
+            std::fs::write(dir.join("outside/secret.txt"), "nope").unwrap();
+            Self(dir)
+        }
+
+        fn soul(&self) -> PathBuf {
+            self.0.join("soul")
+        }
+
+        fn link(&self, target: &Path, name: &str) {
+            std::os::unix::fs::symlink(target, self.soul().join(name)).unwrap();
+        }
+    }
+
+    #[cfg(unix)]
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_symlinks_out_of_the_soul() {
+        let scratch = Scratch::new("escape");
+        scratch.link(&scratch.0.join("outside"), "escape");
+        scratch.link(&scratch.0.join("outside/secret.txt"), "secret.md");
+        let sp = scratch.soul();
+        for name in [
+            "escape",
+            "escape/secret.txt",
+            "escape/new.md",
+            "escape/a/b/new.md",
+            "secret.md",
+        ] {
+            assert_eq!(
+                safe_resolve(&OsFileStore, &sp, name).unwrap_err(),
+                OUTSIDE,
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_dangling_symlinks() {
+        let scratch = Scratch::new("dangling");
+        scratch.link(&scratch.0.join("outside/not-yet"), "dangling");
+        let sp = scratch.soul();
+        for name in ["dangling", "dangling/new.md"] {
+            assert_eq!(
+                safe_resolve(&OsFileStore, &sp, name).unwrap_err(),
+                OUTSIDE,
+                "{}",
+                name
+            );
+        }
+        assert!(!scratch.0.join("outside/not-yet").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn follows_symlinks_that_stay_inside() {
+        let scratch = Scratch::new("inside");
+        scratch.link(&scratch.soul().join("memories"), "mem");
+        let sp = scratch.soul();
+        let real = sp.canonicalize().unwrap();
+        assert_eq!(
+            safe_resolve(&OsFileStore, &sp, "mem/new.md").unwrap(),
+            real.join("memories/new.md")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn names_the_file_a_write_changes() {
+        let scratch = Scratch::new("names");
+        scratch.link(Path::new("SEED.md"), "alias.md");
+        scratch.link(&scratch.soul().join("memories"), "mem");
+        let sp = scratch.soul();
+        for (name, target) in [
+            ("alias.md", "SEED.md"),
+            ("./SEED.md", "SEED.md"),
+            ("mem\\new.md", "memories/new.md"),
+            ("", ""),
+        ] {
+            assert_eq!(
+                resolve_name(&OsFileStore, &sp, name).unwrap(),
+                target,
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn accepts_a_soul_path_reached_through_a_symlink() {
+        let scratch = Scratch::new("linked-root");
+        let linked = scratch.0.join("linked-soul");
+        std::os::unix::fs::symlink(scratch.soul(), &linked).unwrap();
+        let real = scratch.soul().canonicalize().unwrap();
+        assert_eq!(
+            safe_resolve(&OsFileStore, &linked, "SEED.md").unwrap(),
+            real.join("SEED.md")
+        );
+        assert_eq!(
+            safe_resolve(&OsFileStore, &linked, "memories/new.md").unwrap(),
+            real.join("memories/new.md")
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use ts_rs::TS;
+
+/// What a terminal's processes may do. Unrestricted sessions run with the
+/// user's full permissions.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct Capabilities {
+    pub restricted: bool,
+    /// "sandbox-exec" (macOS) or "bwrap" (Linux) for restricted sessions
+    pub sandbox: Option<String>,
+    /// Directories the session may write to; empty = everywhere the user can
+    pub writable: Vec<String>,
+    /// Inside `writable`, but read-only: the soul's `.git`, whose hooks the
+    /// app's own git calls run outside the sandbox
+    pub read_only: Vec<String>,
+    /// Reading the file system and the network stay open in both modes
+    pub read_anywhere: bool,
+    pub network: bool,
+}
+
+impl Capabilities {
+    pub fn unrestricted() -> Self {
+        Self {
+            restricted: false,
+            sandbox: None,
+            writable: Vec::new(),
+            read_only: Vec::new(),
+            read_anywhere: true,
+            network: true,
+        }
+    }
+}
+
+/// A command line that starts `shell` inside the sandbox.
+pub struct Sandboxed {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    pub capabilities: Capabilities,
+}
+
+#[cfg(target_os = "linux")]
+fn find_in_path(name: &str) -> Option<PathBuf> {
+    std::env::var_os("PATH").and_then(|path| {
+        std::env::split_paths(&path)
+            .map(|dir| dir.join(name))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+/// Temp directories the shell and its tools expect to write to. The
+/// canonical forms matter on macOS, where /tmp links to /private/tmp.
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn temp_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![PathBuf::from("/tmp"), PathBuf::from("/var/tmp")];
+    if let Some(tmp) = std::env::var_os("TMPDIR") {
+        dirs.push(PathBuf::from(tmp));
+    }
+    let mut resolved: Vec<PathBuf> = dirs
+        .into_iter()
+        .filter(|d| d.is_dir())
+        .map(|d| d.canonicalize().unwrap_or(d))
+        .collect();
+    resolved.sort();
+    resolved.dedup();
+    resolved
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn writable(soul_path: &Path) -> Result<Vec<PathBuf>, String> {
+    let soul = soul_path
+        .canonicalize()
+        .map_err(|e| format!("Soul path unavailable: {}", e))?;
+    let mut dirs = vec![soul];
+    dirs.extend(temp_dirs());
+    Ok(dirs)
+}
+
+/// The soul's `.git`, canonical. A restricted shell must not plant hooks
+/// or config there.
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn git_dir(soul_path: &Path) -> Result<PathBuf, String> {
+    soul_path
+        .canonicalize()
+        .map(|soul| soul.join(".git"))
+        .map_err(|e| format!("Soul path unavailable: {}", e))
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn display(dirs: &[PathBuf]) -> Vec<String> {
+    dirs.iter()
+        .map(|d| d.to_string_lossy().to_string())
+        .collect()
+}
+
+/// Escape a path for a string literal in an SBPL profile.
+#[cfg(target_os = "macos")]
+fn sbpl_string(path: &Path) -> String {
+    path.to_string_lossy()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+}
+
+/// `sandbox-exec` with a profile that allows everything but writes outside
+/// the soul and temp dirs, and writes to the soul's `.git` (denied even
+/// before it exists). Terminal devices stay writable.
+#[cfg(target_os = "macos")]
+pub fn wrap(shell: &str, args: &[&str], soul_path: &Path) -> Result<Sandboxed, String> {
+    let program = PathBuf::from("/usr/bin/sandbox-exec");
+    if !program.is_file() {
+        return Err("Restricted terminals need /usr/bin/sandbox-exec".to_string());
+    }
+    let dirs = writable(soul_path)?;
+    let mut profile = String::from(
+        "(version 1)\n(allow default)\n(deny file-write*)\n\
+         (allow file-write* (literal \"/dev/null\") (literal \"/dev/tty\") \
+         (regex #\"^/dev/ttys[0-9]+$\") (subpath \"/dev/fd\"))\n",
+    );
+    for dir in &dirs {
+        profile.push_str(&format!(
+            "(allow file-write* (subpath \"{}\"))\n",
+            sbpl_string(dir)
+        ));
+    }
+    // Later rules win
+    let git = git_dir(soul_path)?;
+    profile.push_str(&format!(
+        "(deny file-write* (subpath \"{}\"))\n",
+        sbpl_string(&git)
+    ));
+    let mut command = vec!["-p".to_string(), profile, shell.to_string()];
+    command.extend(args.iter().map(|a| a.to_string()));
+    Ok(Sandboxed {
+        program,
+        args: command,
+        capabilities: Capabilities {
+            restricted: true,
+            sandbox: Some("sandbox-exec".to_string()),
+            writable: display(&dirs),
+            read_only: display(&[git]),
+            read_anywhere: true,
+            network: true,
+        },
+    })
+}
+
+/// bubblewrap: the root file system read-only, devices passed through so
+/// the PTY keeps working, the soul and temp dirs bound writable and the
+/// soul's `.git` read-only again. A soul without a repository has no
+/// `.git` to bind; bwrap can only mount over paths that exist.
+#[cfg(target_os = "linux")]
+pub fn wrap(shell: &str, args: &[&str], soul_path: &Path) -> Result<Sandboxed, String> {
+    let program =
+        find_in_path("bwrap").ok_or("Restricted terminals need bubblewrap (bwrap) on PATH")?;
+    let dirs = writable(soul_path)?;
+    let mut command: Vec<String> = ["--ro-bind", "/", "/", "--dev-bind", "/dev", "/dev"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    for dir in &display(&dirs) {
+        command.extend(["--bind".to_string(), dir.clone(), dir.clone()]);
+    }
+    // After the soul's bind, so it lands on top
+    let git: Vec<PathBuf> = Some(git_dir(soul_path)?)
+        .filter(|git| git.exists())
+        .into_iter()
+        .collect();
+    for dir in &display(&git) {
+        command.extend(["--ro-bind".to_string(), dir.clone(), dir.clone()]);
+    }
+    command.extend(["--die-with-parent".to_string(), "--".to_string()]);
+    command.push(shell.to_string());
+    command.extend(args.iter().map(|a| a.to_string()));
+    Ok(Sandboxed {
+        program,
+        args: command,
+        capabilities: Capabilities {
+            restricted: true,
+            sandbox: Some("bwrap".to_string()),
+            writable: display(&dirs),
+            read_only: display(&git),
+            read_anywhere: true,
+            network: true,
+        },
+    })
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub fn wrap(_shell: &str, _args: &[&str], _soul_path: &Path) -> Result<Sandboxed, String> {
+    Err("Restricted terminals are only supported on macOS and Linux".to_string())
+}
@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use ts_rs::TS;
+
+use crate::paths;
+use crate::text_stats::{self, Stamp};
+use crate::vault::Vault;
+
+/// A query waits this long for the next keystroke before it runs
+const DEBOUNCE: Duration = Duration::from_millis(150);
+const MAX_HITS: usize = 50;
+const SNIPPET_CHARS: usize = 120;
+/// Sessions a window forgot to close are dropped beyond this many
+const MAX_SESSIONS: usize = 16;
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SearchHit {
+    /// Relative to the soul
+    pub path: String,
+    /// Memory category, if the file is a memory
+    pub category: Option<String>,
+    /// 1-based line of the first match
+    pub line: usize,
+    pub snippet: String,
+    /// Occurrences of the query terms; matches in the file name count extra
+    pub score: usize,
+}
+
+/// Results of one query of a session (`search:results`).
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SearchResults {
+    pub session: String,
+    pub query: String,
+    /// Increases with every `update_search_query`; results of an older
+    /// query never arrive after a newer one's
+    #[ts(type = "number")]
+    pub seq: u64,
+    /// Best first
+    pub hits: Vec<SearchHit>,
+    /// More documents matched than `hits` holds
+    pub truncated: bool,
+}
+
+/// Document texts by path, with the stamp they were read at
+type Index = Mutex<HashMap<PathBuf, (Stamp, Arc<Document>)>>;
+/// A query and the paths of the documents it matched
+type LastQuery = Mutex<Option<(String, Vec<PathBuf>)>>;
+
+/// A soul document as searched: its text and the text lowercased.
+struct Document {
+    text: String,
+    lower: String,
+}
+
+#[derive(Default)]
+struct Session {
+    /// Sequence number of the latest query; a running search gives up when
+    /// it changes
+    seq: Arc<AtomicU64>,
+    /// Last completed query and every document it matched, so a query that
+    /// extends it only searches those
+    last: Arc<LastQuery>,
+}
+
+/// Search-as-you-type sessions over the soul's documents. Each session
+/// runs at most one search at a time, after the typing pauses, and skips
+/// results already superseded.
+#[derive(Default)]
+pub struct Searches {
+    next_id: AtomicU64,
+    sessions: Mutex<HashMap<String, Session>>,
+    /// Document texts, re-read only when a file's time or size changes
+    index: Arc<Index>,
+}
+
+impl Searches {
+    pub fn open(&self) -> String {
+        let id = format!(
+            "search-{}",
+            self.next_id.fetch_add(1, Ordering::Relaxed) + 1
+        );
+        let mut sessions = self.sessions.lock();
+        if sessions.len() >= MAX_SESSIONS {
+            let oldest = sessions
+                .keys()
+                .min_by_key(|id| id.trim_start_matches("search-").parse::<u64>().unwrap_or(0))
+                .cloned();
+            if let Some(oldest) = oldest {
+                sessions.remove(&oldest);
+            }
+        }
+        sessions.insert(id.clone(), Session::default());
+        id
+    }
+
+    pub fn close(&self, session: &str) {
+        self.sessions.lock().remove(session);
+    }
+
+    /// Queue `query` for the session. Results arrive as `search:results`
+    /// once typing pauses for `DEBOUNCE`.
+    pub fn update(
+        &self,
+        app: &AppHandle,
+        session: &str,
+        query: String,
+        soul_path: PathBuf,
+        vault: Arc<Vault>,
+    ) -> Result<(), String> {
+        let (seq, last) = {
+            let sessions = self.sessions.lock();
+            let state = sessions
+                .get(session)
+                .ok_or_else(|| format!("No search session {}", session))?;
+            (state.seq.clone(), state.last.clone())
+        };
+        let current = seq.fetch_add(1, Ordering::SeqCst) + 1;
+        let index = self.index.clone();
+        let app = app.clone();
+        let session = session.to_string();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(DEBOUNCE).await;
+            if seq.load(Ordering::SeqCst) != current {
+                return;
+            }
+            let superseded = {
+                let seq = seq.clone();
+                move || seq.load(Ordering::SeqCst) != current
+            };
+            let searched = tokio::task::spawn_blocking(move || {
+                search(&index, &last, &soul_path, &vault, &query, &superseded)
+                    .map(|(hits, truncated)| (query, hits, truncated))
+            })
+            .await;
+            // A newer query took over while this one ran
+            let Ok(Some((query, hits, truncated))) = searched else {
+                return;
+            };
+            if seq.load(Ordering::SeqCst) == current {
+                let _ = app.emit(
+                    "search:results",
+                    SearchResults {
+                        session,
+                        query,
+                        seq: current,
+                        hits,
+                        truncated,
+                    },
+                );
+            }
+        });
+        Ok(())
+    }
+}
+
+fn load(index: &Index, vault: &Vault, path: &Path) -> Option<Arc<Document>> {
+    let stamp = text_stats::stamp(path)?;
+    if let Some((cached, document)) = index.lock().get(path) {
+        if *cached == stamp {
+            return Some(document.clone());
+        }
+    }
+    // Encrypted documents stay out while the app is locked
+    let text = vault.read(path).ok()?;
+    let document = Arc::new(Document {
+        lower: text.to_lowercase(),
+        text,
+    });
+    index
+        .lock()
+        .insert(path.to_path_buf(), (stamp, document.clone()));
+    Some(document)
+}
+
+fn snippet(line: &str, term: &str) -> String {
+    let lower = line.to_lowercase();
+    // Lowercasing can change byte lengths; center on the match only when
+    // offsets line up
+    let pos = lower
+        .find(term)
+        .filter(|_| lower.len() == line.len())
+        .unwrap_or(0);
+    let start = line[..pos]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CHARS / 3)
+        .map_or(0, |(i, _)| i);
+    let rest = &line[start..];
+    let mut out: String = rest.chars().take(SNIPPET_CHARS).collect();
+    if out.len() < rest.len() {
+        out.push('…');
+    }
+    if start > 0 {
+        out.insert(0, '…');
+    }
+    out.trim().to_string()
+}
+
+/// Documents containing every term of `query`. None when superseded.
+fn search(
+    index: &Index,
+    last: &LastQuery,
+    soul_path: &Path,
+    vault: &Vault,
+    query: &str,
+    superseded: &dyn Fn() -> bool,
+) -> Option<(Vec<SearchHit>, bool)> {
+    let lower = query.trim().to_lowercase();
+    let terms: Vec<&str> = lower.split_whitespace().collect();
+    if terms.is_empty() {
+        *last.lock() = None;
+        return Some((Vec::new(), false));
+    }
+    // Typing on narrows the previous matches; anything else searches all
+    let candidates = match last.lock().as_ref() {
+        Some((previous, matched)) if lower.starts_with(previous.as_str()) => matched.clone(),
+        _ => text_stats::documents(soul_path),
+    };
+    let mut matched = Vec::new();
+    let mut hits = Vec::new();
+    for path in candidates {
+        if superseded() {
+            return None;
+        }
+        let Some(document) = load(index, vault, &path) else {
+            continue;
+        };
+        if !terms.iter().all(|term| document.lower.contains(term)) {
+            continue;
+        }
+        let Ok(relative) = path.strip_prefix(soul_path) else {
+            continue;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let name = relative.to_lowercase();
+        let score = terms
+            .iter()
+            .map(|term| document.lower.matches(term).count() + 5 * name.matches(term).count())
+            .sum();
+        let (line, text) = document
+            .text
+            .lines()
+            .enumerate()
+            .find(|(_, line)| line.to_lowercase().contains(terms[0]))
+            .unwrap_or((0, ""));
+        hits.push(SearchHit {
+            category: paths::memory_category(&relative).map(str::to_string),
+            path: relative,
+            line: line + 1,
+            snippet: snippet(text, terms[0]),
+            score,
+        });
+        matched.push(path);
+    }
+    *last.lock() = Some((lower.trim().to_string(), matched));
+    hits.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+    let truncated = hits.len() > MAX_HITS;
+    hits.truncate(MAX_HITS);
+    Some((hits, truncated))
+}
@@ -0,0 +1,258 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::paths;
+use crate::relocate::{self, available_space, existing_ancestor};
+
+const MB: u64 = 1_000_000;
+/// Below this a new soul can't even be scaffolded and founded
+const MIN_FREE: u64 = 50 * MB;
+/// Below this the soul runs out of room for memories and git history soon
+const LOW_FREE: u64 = 500 * MB;
+/// Longest file name expected inside the deepest soul directory
+const FILE_ALLOWANCE: usize = 80;
+/// Longest path the platform handles reliably (MAX_PATH on Windows unless
+/// long paths are enabled)
+#[cfg(windows)]
+const MAX_PATH: usize = 260;
+#[cfg(not(windows))]
+const MAX_PATH: usize = 4096;
+/// Longest single path component (NAME_MAX)
+const MAX_COMPONENT: usize = 255;
+/// Within this many characters of MAX_PATH the path length is a warning
+const PATH_MARGIN: usize = 40;
+
+/// One check of a candidate soul path.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct PreflightCheck {
+    /// "location", "space", "writable", "case_sensitivity" or "path_length"
+    pub id: String,
+    /// "ok", "warning" or "error"
+    pub status: String,
+    pub message: String,
+}
+
+/// Whether a soul can be created at `path`, checked without creating it.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SetupPreflight {
+    pub path: String,
+    /// No check failed; warnings don't block setup
+    pub ok: bool,
+    pub checks: Vec<PreflightCheck>,
+    #[ts(type = "number | null")]
+    pub free_bytes: Option<u64>,
+    /// None when it couldn't be probed (nothing writable)
+    pub case_sensitive: Option<bool>,
+}
+
+fn check(id: &str, status: &str, message: String) -> PreflightCheck {
+    PreflightCheck {
+        id: id.to_string(),
+        status: status.to_string(),
+        message,
+    }
+}
+
+fn location(path: &Path) -> PreflightCheck {
+    if !path.is_absolute() {
+        return check(
+            "location",
+            "error",
+            "The soul path must be absolute".to_string(),
+        );
+    }
+    if relocate::is_system_dir(path) {
+        return check(
+            "location",
+            "error",
+            "A system directory can't hold a soul".to_string(),
+        );
+    }
+    if path.exists() && !path.is_dir() {
+        return check(
+            "location",
+            "error",
+            format!("{} exists and is not a directory", path.display()),
+        );
+    }
+    let message = if path.is_dir() {
+        "Existing directory; missing soul directories will be added".to_string()
+    } else {
+        "The directory will be created".to_string()
+    };
+    check("location", "ok", message)
+}
+
+fn space(path: &Path) -> (PreflightCheck, Option<u64>) {
+    let free = available_space(path);
+    let result = match free {
+        None => check(
+            "space",
+            "warning",
+            "Free space could not be determined".to_string(),
+        ),
+        Some(free) if free < MIN_FREE => check(
+            "space",
+            "error",
+            format!(
+                "Only {} MB free; a soul needs at least {} MB",
+                free / MB,
+                MIN_FREE / MB
+            ),
+        ),
+        Some(free) if free < LOW_FREE => check(
+            "space",
+            "warning",
+            format!("Only {} MB free; the soul will grow over time", free / MB),
+        ),
+        Some(free) => check("space", "ok", format!("{} MB free", free / MB)),
+    };
+    (result, free)
+}
+
+/// A probe file in `dir`, removed when dropped.
+struct Probe(PathBuf);
+
+impl Probe {
+    fn create(dir: &Path, name: &str) -> std::io::Result<Self> {
+        let path = dir.join(name);
+        fs::write(&path, b"")?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for Probe {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// Writes a probe file into the deepest existing ancestor, then checks
+/// whether it is also found under an upper-case name.
+fn writable_and_case(path: &Path) -> (PreflightCheck, Option<bool>) {
+    let Some(dir) = existing_ancestor(path) else {
+        return (
+            check(
+                "writable",
+                "error",
+                "No part of the path exists".to_string(),
+            ),
+            None,
+        );
+    };
+    let name = format!(".soulos-preflight-{}", std::process::id());
+    let probe = match Probe::create(dir, &name) {
+        Ok(probe) => probe,
+        Err(e) => {
+            let message = match e.kind() {
+                std::io::ErrorKind::PermissionDenied => {
+                    format!("No write permission in {}", dir.display())
+                }
+                _ => format!("Can't write to {}: {}", dir.display(), e),
+            };
+            return (check("writable", "error", message), None);
+        }
+    };
+    let case_sensitive = !dir.join(name.to_uppercase()).exists();
+    drop(probe);
+    (
+        check("writable", "ok", format!("{} is writable", dir.display())),
+        Some(case_sensitive),
+    )
+}
+
+fn case_sensitivity(case_sensitive: Option<bool>) -> PreflightCheck {
+    match case_sensitive {
+        None => check(
+            "case_sensitivity",
+            "warning",
+            "Not checked, the location isn't writable".to_string(),
+        ),
+        Some(true) => check(
+            "case_sensitivity",
+            "ok",
+            "Case-sensitive file system".to_string(),
+        ),
+        Some(false) => check(
+            "case_sensitivity",
+            "ok",
+            "Case-insensitive file system; names differing only in case are the same file"
+                .to_string(),
+        ),
+    }
+}
+
+/// The deepest soul directory plus a typical file name must stay within
+/// the platform's limits.
+fn path_length(path: &Path) -> PreflightCheck {
+    let too_long = path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().chars().count())
+        .find(|len| *len > MAX_COMPONENT);
+    if let Some(len) = too_long {
+        return check(
+            "path_length",
+            "error",
+            format!(
+                "A path component has {} characters; the limit is {}",
+                len, MAX_COMPONENT
+            ),
+        );
+    }
+    let deepest = paths::scaffold_dirs(path)
+        .iter()
+        .map(|dir| dir.chars().count())
+        .max()
+        .unwrap_or(0);
+    let longest = path.to_string_lossy().chars().count() + 1 + deepest + 1 + FILE_ALLOWANCE;
+    if longest > MAX_PATH {
+        check(
+            "path_length",
+            "error",
+            format!(
+                "Soul files would need paths of up to {} characters; the limit is {}",
+                longest, MAX_PATH
+            ),
+        )
+    } else if longest + PATH_MARGIN > MAX_PATH {
+        check(
+            "path_length",
+            "warning",
+            format!(
+                "Soul files would need paths of up to {} characters, close to the limit of {}",
+                longest, MAX_PATH
+            ),
+        )
+    } else {
+        check(
+            "path_length",
+            "ok",
+            format!("Longest soul path about {} characters", longest),
+        )
+    }
+}
+
+/// Check `path` as the location of a new soul: free space, write
+/// permission, case sensitivity and path length. Only a probe file is
+/// written, and removed again.
+pub fn run(path: &Path) -> SetupPreflight {
+    let mut checks = vec![location(path)];
+    let (space, free_bytes) = space(path);
+    checks.push(space);
+    let (writable, case_sensitive) = writable_and_case(path);
+    checks.push(writable);
+    checks.push(case_sensitivity(case_sensitive));
+    checks.push(path_length(path));
+    SetupPreflight {
+        path: path.to_string_lossy().to_string(),
+        ok: checks.iter().all(|c| c.status != "error"),
+        checks,
+        free_bytes,
+        case_sensitive,
+    }
+}
@@ -1,58 +1,112 @@
 use std::net::TcpStream;
-use std::path::PathBuf;
-use std::process::{Child, Command, Stdio};
-use std::sync::{Arc, Mutex};
-use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use parking_lot::RwLock;
 use tauri::{AppHandle, Emitter, Manager};
-
+use ts_rs::TS;
+
+use crate::availability::{Availability, DownReason};
+use crate::background;
+use crate::blocking::run_blocking;
+use crate::compat;
+use crate::config::AppConfig;
+use crate::engine_update;
+use crate::env_layers;
+use crate::metrics::Metrics;
 use crate::node;
-
-#[derive(Clone, serde::Serialize)]
+use crate::profiles::Profiles;
+use crate::proxy::EngineProxy;
+use crate::sidecar_exit::{self, SidecarExit};
+use crate::sidecar_output::{self, StderrTail};
+use crate::soul_identity::InstanceGuard;
+use crate::types::SubsystemHealth;
+
+#[derive(Clone, serde::Serialize, TS)]
+#[ts(export)]
 pub struct SidecarStatus {
     pub process: String,
-    pub status: String, // "running", "stopped", "error", "starting"
+    pub status: String, // "running", "stopped", "error", "starting", "paused"
     pub pid: Option<u32>,
+    #[ts(type = "number | null")]
     pub uptime_secs: Option<u64>,
+    /// How the process last died on its own, kept across restarts
+    pub last_exit: Option<SidecarExit>,
 }
 
+/// Consecutive failed API checks before the watchdog restarts the engine
+const UNANSWERED_CHECKS: u32 = 3;
+/// Automatic restarts in a row before the watchdog gives up
+const MAX_AUTO_RESTARTS: u32 = 3;
+/// An engine up this long counts as stable again
+const STABLE_AFTER: Duration = Duration::from_secs(300);
+/// Wait for a dead process's last stderr lines before classifying its exit
+const STDERR_SETTLE: Duration = Duration::from_millis(100);
+
 struct SidecarProcess {
     child: Option<Child>,
     start_time: Option<Instant>,
     restart_count: u32,
     status: String,
+    /// The API answered at least once since the start
+    api_seen: bool,
+    unanswered: u32,
+    /// Last stderr lines of the current process
+    stderr: StderrTail,
+    last_exit: Option<SidecarExit>,
 }
 
 pub struct SidecarManager {
-    engine: Arc<Mutex<SidecarProcess>>,
-    chain: Arc<Mutex<SidecarProcess>>,
-    soul_path: PathBuf,
+    engine: Arc<RwLock<SidecarProcess>>,
+    chain: Arc<RwLock<SidecarProcess>>,
+    /// Swapped by `relocate_soul`; processes started afterwards use the new path
+    soul_path: RwLock<PathBuf>,
 }
 
 impl SidecarManager {
     pub fn new(soul_path: PathBuf) -> Self {
         Self {
-            engine: Arc::new(Mutex::new(SidecarProcess {
+            engine: Arc::new(RwLock::new(SidecarProcess {
                 child: None,
                 start_time: None,
                 restart_count: 0,
                 status: "stopped".to_string(),
+                api_seen: false,
+                unanswered: 0,
+                stderr: StderrTail::default(),
+                last_exit: None,
             })),
-            chain: Arc::new(Mutex::new(SidecarProcess {
+            chain: Arc::new(RwLock::new(SidecarProcess {
                 child: None,
                 start_time: None,
                 restart_count: 0,
                 status: "stopped".to_string(),
+                api_seen: false,
+                unanswered: 0,
+                stderr: StderrTail::default(),
+                last_exit: None,
             })),
-            soul_path,
+            soul_path: RwLock::new(soul_path),
         }
     }
 
+    /// Point processes started from now on at a relocated soul.
+    pub fn set_soul_path(&self, soul_path: PathBuf) {
+        *self.soul_path.write() = soul_path;
+    }
+
     /// Find the engine entry point.
-    /// Priority: bundled (in app resources) → dev path (relative to soul_path)
+    /// Priority: deployed (app data, see engine_update) → bundled (in app
+    /// resources) → dev path (relative to soul_path)
     fn find_engine_path(&self, app: &AppHandle) -> Result<PathBuf, String> {
-        // 1. Try bundled engine (production)
+        // 1. Deployed copy of the bundled engine
+        if let Some(deployed) = engine_update::deployed_entry() {
+            return Ok(deployed);
+        }
+
+        // 2. Try bundled engine (production)
         if let Ok(resource_dir) = app.path().resource_dir() {
             let bundled = resource_dir.join("soul-engine").join("src").join("index.js");
             if bundled.exists() {
@@ -60,13 +114,8 @@ impl SidecarManager {
             }
         }
 
-        // 2. Try dev path (relative to soul_path)
-        let dev_path = self
-            .soul_path
-            .join("seelen-protokoll")
-            .join("soul-engine")
-            .join("src")
-            .join("index.js");
+        // 3. Try dev path (relative to soul_path)
+        let dev_path = self.dev_engine_dir().join("src").join("index.js");
         if dev_path.exists() {
             return Ok(dev_path);
         }
@@ -77,6 +126,17 @@ impl SidecarManager {
         ))
     }
 
+    /// Engine checkout used in development: <soul_path>/seelen-protokoll/soul-engine
+    pub fn dev_engine_dir(&self) -> PathBuf {
+        self.soul_path.read().join("seelen-protokoll").join("soul-engine")
+    }
+
+    /// Package directory of the engine `start_engine` would run.
+    pub fn active_engine_dir(&self, app: &AppHandle) -> Option<PathBuf> {
+        let entry = self.find_engine_path(app).ok()?;
+        Some(entry.parent()?.parent()?.to_path_buf())
+    }
+
     /// Find the chain entry point.
     fn find_chain_path(&self, app: &AppHandle) -> Result<PathBuf, String> {
         // 1. Try bundled chain (production)
@@ -90,6 +150,7 @@ impl SidecarManager {
         // 2. Try dev path
         let dev_path = self
             .soul_path
+            .read()
             .join("seelen-protokoll")
             .join("soul-chain")
             .join("src")
@@ -101,10 +162,22 @@ impl SidecarManager {
         Err("soul-chain not found".to_string())
     }
 
+    /// soul-chain is bundled or checked out next to the soul.
+    pub fn chain_available(&self, app: &AppHandle) -> bool {
+        self.find_chain_path(app).is_ok()
+    }
+
+    /// Start the engine, recording the outcome in the availability log.
     pub fn start_engine(&self, app: &AppHandle) -> Result<(), String> {
+        let result = self.launch_engine(app);
+        record_start(app, &result);
+        result
+    }
+
+    fn launch_engine(&self, app: &AppHandle) -> Result<(), String> {
         // If engine is already reachable (external process), skip spawning
         if self.check_engine_port() {
-            let mut proc = self.engine.lock().map_err(|e| e.to_string())?;
+            let mut proc = self.engine.write();
             proc.status = "running".to_string();
             let _ = app.emit(
                 "sidecar:status",
@@ -113,37 +186,53 @@ impl SidecarManager {
                     status: "running".to_string(),
                     pid: None,
                     uptime_secs: None,
+                    last_exit: None,
                 },
             );
             return Ok(());
         }
 
         let engine_path = self.find_engine_path(app)?;
+        if let Some(package) = engine_path.parent().and_then(Path::parent) {
+            compat::ensure_startable(package)?;
+        }
         let node_path = node::find_node(Some(app))
             .ok_or_else(|| "Node.js not found (neither bundled nor system)".to_string())?;
+        // One engine per soul, across SoulOS instances and synced machines
+        if let Some(guard) = app.try_state::<Arc<InstanceGuard>>() {
+            guard.acquire(&self.soul_path.read())?;
+        }
 
-        let mut proc = self.engine.lock().map_err(|e| e.to_string())?;
+        let mut proc = self.engine.write();
 
         // Kill existing if running
         if let Some(ref mut child) = proc.child {
             let _ = child.kill();
             let _ = child.wait();
+            if let Some(metrics) = app.try_state::<Arc<Metrics>>() {
+                metrics.sidecar_restarted("soul-engine");
+            }
         }
 
-        proc.status = "starting".to_string();
-        let _ = app.emit(
-            "sidecar:status",
-            SidecarStatus {
-                process: "soul-engine".to_string(),
-                status: "starting".to_string(),
-                pid: None,
-                uptime_secs: None,
-            },
-        );
+        // A reload keeps reporting "reloading" until the new process runs
+        if proc.status != "reloading" {
+            proc.status = "starting".to_string();
+            let _ = app.emit(
+                "sidecar:status",
+                SidecarStatus {
+                    process: "soul-engine".to_string(),
+                    status: "starting".to_string(),
+                    pid: None,
+                    uptime_secs: None,
+                    last_exit: None,
+                },
+            );
+        }
 
         let mut child = Command::new(&node_path)
             .arg(&engine_path)
-            .env("SOUL_PATH", &self.soul_path)
+            .env_clear()
+            .envs(env_layers::vars(app, "soul-engine", &self.soul_path.read()))
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
@@ -151,48 +240,15 @@ impl SidecarManager {
 
         let pid = child.id();
 
-        // Capture stdout
-        if let Some(stdout) = child.stdout.take() {
-            let app_clone = app.clone();
-            std::thread::spawn(move || {
-                let reader = BufReader::new(stdout);
-                for line in reader.lines() {
-                    if let Ok(line) = line {
-                        let _ = app_clone.emit(
-                            "sidecar:stdout",
-                            serde_json::json!({
-                                "process": "soul-engine",
-                                "line": line,
-                            }),
-                        );
-                    }
-                }
-            });
-        }
-
-        // Capture stderr
-        if let Some(stderr) = child.stderr.take() {
-            let app_clone = app.clone();
-            std::thread::spawn(move || {
-                let reader = BufReader::new(stderr);
-                for line in reader.lines() {
-                    if let Ok(line) = line {
-                        let _ = app_clone.emit(
-                            "sidecar:stderr",
-                            serde_json::json!({
-                                "process": "soul-engine",
-                                "line": line,
-                            }),
-                        );
-                    }
-                }
-            });
-        }
+        proc.stderr =
+            sidecar_output::capture(app, "soul-engine", child.stdout.take(), child.stderr.take());
 
         proc.child = Some(child);
         proc.start_time = Some(Instant::now());
         proc.status = "running".to_string();
         proc.restart_count = 0;
+        proc.api_seen = false;
+        proc.unanswered = 0;
 
         let _ = app.emit(
             "sidecar:status",
@@ -201,14 +257,225 @@ impl SidecarManager {
                 status: "running".to_string(),
                 pid: Some(pid),
                 uptime_secs: Some(0),
+                last_exit: None,
             },
         );
 
         Ok(())
     }
 
+    /// Wait until a managed engine answers on its API port. Fails when the
+    /// process exits first or `timeout` passes.
+    pub fn wait_until_up(&self, timeout: Duration) -> Result<(), String> {
+        let start = Instant::now();
+        loop {
+            {
+                let mut proc = self.engine.write();
+                if let Some(Ok(Some(status))) = proc.child.as_mut().map(Child::try_wait) {
+                    let exit = record_exit(&mut proc, "soul-engine", status);
+                    return Err(format!("soul-engine exited during startup: {}", exit.message));
+                }
+            }
+            if self.check_engine_port() {
+                return Ok(());
+            }
+            if start.elapsed() > timeout {
+                return Err(format!(
+                    "soul-engine did not answer within {}s",
+                    timeout.as_secs()
+                ));
+            }
+            std::thread::sleep(Duration::from_millis(500));
+        }
+    }
+
+    /// Reload a managed engine without going through "stopped": send SIGHUP
+    /// (engines that handle it re-read their config in place), otherwise
+    /// restart it quickly. Returns "signal" or "restart".
+    pub fn reload_engine(&self, app: &AppHandle) -> Result<String, String> {
+        {
+            let mut proc = self.engine.write();
+            if proc.child.is_none() {
+                return Err(
+                    "The engine was not started by SoulOS; reload it where it runs".to_string(),
+                );
+            }
+            // The watchdog leaves the engine alone while it isn't "running"
+            proc.status = "reloading".to_string();
+            let pid = proc.child.as_ref().map(|c| c.id());
+            emit_engine_status(app, "reloading", pid);
+
+            let child = proc.child.as_mut().expect("checked above");
+            if send_reload_signal(child) {
+                proc.status = "running".to_string();
+                emit_engine_status(app, "running", pid);
+                return Ok("signal".to_string());
+            }
+            terminate(child);
+            proc.child = None;
+        }
+
+        match self.launch_engine(app) {
+            Ok(()) => Ok("restart".to_string()),
+            Err(e) => {
+                record_start(app, &Err(e.clone()));
+                self.engine.write().status = "stopped".to_string();
+                emit_engine_status(app, "stopped", None);
+                Err(e)
+            }
+        }
+    }
+
+    /// Freeze a managed engine with SIGSTOP. Unlike stopping, the process
+    /// keeps its memory and uptime; the watchdog leaves it alone until
+    /// `resume_engine`.
+    pub fn pause_engine(&self, app: &AppHandle) -> Result<(), String> {
+        let mut proc = self.engine.write();
+        let Some(pid) = proc.child.as_ref().map(|c| c.id()) else {
+            return Err(
+                "The engine was not started by SoulOS; pause it where it runs".to_string(),
+            );
+        };
+        match proc.status.as_str() {
+            "paused" => return Ok(()),
+            "running" => {}
+            status => return Err(format!("soul-engine is {}, not running", status)),
+        }
+        send_signal(pid, Signal::Stop)?;
+        proc.status = "paused".to_string();
+        emit_engine_status(app, "paused", Some(pid));
+        Ok(())
+    }
+
+    /// Continue an engine frozen by `pause_engine` with SIGCONT.
+    pub fn resume_engine(&self, app: &AppHandle) -> Result<(), String> {
+        let mut proc = self.engine.write();
+        if proc.status != "paused" {
+            return Err("soul-engine is not paused".to_string());
+        }
+        let Some(pid) = proc.child.as_ref().map(|c| c.id()) else {
+            return Err("soul-engine is not paused".to_string());
+        };
+        send_signal(pid, Signal::Continue)?;
+        proc.status = "running".to_string();
+        // The API was silent while paused; don't count that against it
+        proc.unanswered = 0;
+        emit_engine_status(app, "running", Some(pid));
+        Ok(())
+    }
+
     pub fn stop_engine(&self, app: &AppHandle) -> Result<(), String> {
-        Self::stop_process(&self.engine, "soul-engine", app)
+        Self::stop_process(&self.engine, "soul-engine", app)?;
+        if let Some(guard) = app.try_state::<Arc<InstanceGuard>>() {
+            guard.release();
+        }
+        if let Some(availability) = app.try_state::<Arc<Availability>>() {
+            availability.down(DownReason::UserStop, None);
+        }
+        Ok(())
+    }
+
+    /// Restart a managed engine that exited on its own or whose API stopped
+    /// answering. Gives up after `MAX_AUTO_RESTARTS` attempts in a row.
+    pub fn watchdog_tick(&self, app: &AppHandle) {
+        let failure = {
+            let mut proc = self.engine.write();
+            if proc.status != "running" {
+                return;
+            }
+            if proc.start_time.is_some_and(|t| t.elapsed() > STABLE_AFTER) {
+                proc.restart_count = 0;
+            }
+            // No child: an external engine, not ours to restart
+            let Some(child) = proc.child.as_mut() else {
+                return;
+            };
+            match child.try_wait() {
+                Ok(Some(status)) => {
+                    let exit = record_exit(&mut proc, "soul-engine", status);
+                    Some((DownReason::Crash, format!("exited: {}", exit.message)))
+                }
+                Err(e) => Some((DownReason::Crash, e.to_string())),
+                Ok(None) if self.check_engine_port() => {
+                    proc.api_seen = true;
+                    proc.unanswered = 0;
+                    None
+                }
+                Ok(None) if proc.api_seen => {
+                    proc.unanswered += 1;
+                    (proc.unanswered >= UNANSWERED_CHECKS).then(|| {
+                        (DownReason::WatchdogRestart, "API stopped answering".to_string())
+                    })
+                }
+                Ok(None) => None,
+            }
+        };
+        let Some((reason, detail)) = failure else {
+            return;
+        };
+
+        eprintln!("[watchdog] soul-engine down: {}", detail);
+        if let Some(availability) = app.try_state::<Arc<Availability>>() {
+            availability.down(reason, Some(detail));
+        }
+        let (restarts, last_exit) = {
+            let mut proc = self.engine.write();
+            proc.status = "crashed".to_string();
+            (proc.restart_count, proc.last_exit.clone())
+        };
+        if let Some(exit) = &last_exit {
+            let _ = app.emit("sidecar:crashed", exit.clone());
+        }
+        let _ = app.emit(
+            "sidecar:status",
+            SidecarStatus {
+                process: "soul-engine".to_string(),
+                status: "crashed".to_string(),
+                pid: None,
+                uptime_secs: None,
+                last_exit,
+            },
+        );
+        if restarts >= MAX_AUTO_RESTARTS {
+            eprintln!("[watchdog] soul-engine restarted {} times in a row, giving up", restarts);
+            return;
+        }
+
+        std::thread::sleep(Duration::from_secs(1 << restarts));
+        let result = self.launch_engine(app);
+        record_start(app, &result);
+        self.engine.write().restart_count = restarts + 1;
+    }
+
+    /// Report a soul-chain that died on its own. The chain isn't restarted
+    /// automatically.
+    pub fn chain_tick(&self, app: &AppHandle) {
+        let exit = {
+            let mut proc = self.chain.write();
+            if proc.status != "running" {
+                return;
+            }
+            let Some(Ok(Some(status))) = proc.child.as_mut().map(Child::try_wait) else {
+                return;
+            };
+            let exit = record_exit(&mut proc, "soul-chain", status);
+            proc.child = None;
+            proc.start_time = None;
+            proc.status = "crashed".to_string();
+            exit
+        };
+        eprintln!("[watchdog] soul-chain down: {}", exit.message);
+        let _ = app.emit("sidecar:crashed", exit.clone());
+        let _ = app.emit(
+            "sidecar:status",
+            SidecarStatus {
+                process: "soul-chain".to_string(),
+                status: "crashed".to_string(),
+                pid: None,
+                uptime_secs: None,
+                last_exit: Some(exit),
+            },
+        );
     }
 
     pub fn start_chain(&self, app: &AppHandle) -> Result<(), String> {
@@ -216,11 +483,14 @@ impl SidecarManager {
         let node_path = node::find_node(Some(app))
             .ok_or_else(|| "Node.js not found".to_string())?;
 
-        let mut proc = self.chain.lock().map_err(|e| e.to_string())?;
+        let mut proc = self.chain.write();
 
         if let Some(ref mut child) = proc.child {
             let _ = child.kill();
             let _ = child.wait();
+            if let Some(metrics) = app.try_state::<Arc<Metrics>>() {
+                metrics.sidecar_restarted("soul-chain");
+            }
         }
 
         proc.status = "starting".to_string();
@@ -231,12 +501,14 @@ impl SidecarManager {
                 status: "starting".to_string(),
                 pid: None,
                 uptime_secs: None,
+                last_exit: None,
             },
         );
 
         let mut child = Command::new(&node_path)
             .arg(&chain_path)
-            .env("SOUL_PATH", &self.soul_path)
+            .env_clear()
+            .envs(env_layers::vars(app, "soul-chain", &self.soul_path.read()))
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
@@ -244,41 +516,8 @@ impl SidecarManager {
 
         let pid = child.id();
 
-        if let Some(stdout) = child.stdout.take() {
-            let app_clone = app.clone();
-            std::thread::spawn(move || {
-                let reader = BufReader::new(stdout);
-                for line in reader.lines() {
-                    if let Ok(line) = line {
-                        let _ = app_clone.emit(
-                            "sidecar:stdout",
-                            serde_json::json!({
-                                "process": "soul-chain",
-                                "line": line,
-                            }),
-                        );
-                    }
-                }
-            });
-        }
-
-        if let Some(stderr) = child.stderr.take() {
-            let app_clone = app.clone();
-            std::thread::spawn(move || {
-                let reader = BufReader::new(stderr);
-                for line in reader.lines() {
-                    if let Ok(line) = line {
-                        let _ = app_clone.emit(
-                            "sidecar:stderr",
-                            serde_json::json!({
-                                "process": "soul-chain",
-                                "line": line,
-                            }),
-                        );
-                    }
-                }
-            });
-        }
+        proc.stderr =
+            sidecar_output::capture(app, "soul-chain", child.stdout.take(), child.stderr.take());
 
         proc.child = Some(child);
         proc.start_time = Some(Instant::now());
@@ -292,6 +531,7 @@ impl SidecarManager {
                 status: "running".to_string(),
                 pid: Some(pid),
                 uptime_secs: Some(0),
+                last_exit: None,
             },
         );
 
@@ -303,39 +543,19 @@ impl SidecarManager {
     }
 
     fn stop_process(
-        process: &Arc<Mutex<SidecarProcess>>,
+        process: &Arc<RwLock<SidecarProcess>>,
         name: &str,
         app: &AppHandle,
     ) -> Result<(), String> {
-        let mut proc = process.lock().map_err(|e| e.to_string())?;
+        let mut proc = process.write();
 
+        let paused = proc.status == "paused";
         if let Some(ref mut child) = proc.child {
-            #[cfg(unix)]
-            {
-                unsafe {
-                    libc::kill(child.id() as i32, libc::SIGTERM);
-                }
-                let start = Instant::now();
-                loop {
-                    match child.try_wait() {
-                        Ok(Some(_)) => break,
-                        Ok(None) if start.elapsed() < Duration::from_secs(5) => {
-                            std::thread::sleep(Duration::from_millis(100));
-                        }
-                        _ => {
-                            let _ = child.kill();
-                            let _ = child.wait();
-                            break;
-                        }
-                    }
-                }
-            }
-
-            #[cfg(not(unix))]
-            {
-                let _ = child.kill();
-                let _ = child.wait();
+            // A frozen process would only see SIGTERM after SIGCONT
+            if paused {
+                let _ = send_signal(child.id(), Signal::Continue);
             }
+            terminate(child);
         }
 
         proc.child = None;
@@ -349,6 +569,7 @@ impl SidecarManager {
                 status: "stopped".to_string(),
                 pid: None,
                 uptime_secs: None,
+                last_exit: None,
             },
         );
 
@@ -356,7 +577,7 @@ impl SidecarManager {
     }
 
     pub fn get_status(&self) -> SidecarStatus {
-        let proc = self.engine.lock().unwrap();
+        let proc = self.engine.read();
         let uptime = proc.start_time.map(|t| t.elapsed().as_secs());
 
         // If no managed child but port is reachable → external engine
@@ -366,6 +587,7 @@ impl SidecarManager {
                 status: "running".to_string(),
                 pid: None, // Unknown PID (external process)
                 uptime_secs: None,
+                last_exit: proc.last_exit.clone(),
             };
         }
 
@@ -374,22 +596,24 @@ impl SidecarManager {
             status: proc.status.clone(),
             pid: proc.child.as_ref().map(|c| c.id()),
             uptime_secs: uptime,
+            last_exit: proc.last_exit.clone(),
         }
     }
 
     pub fn get_chain_status(&self) -> SidecarStatus {
-        let proc = self.chain.lock().unwrap();
+        let proc = self.chain.read();
         let uptime = proc.start_time.map(|t| t.elapsed().as_secs());
         SidecarStatus {
             process: "soul-chain".to_string(),
             status: proc.status.clone(),
             pid: proc.child.as_ref().map(|c| c.id()),
             uptime_secs: uptime,
+            last_exit: proc.last_exit.clone(),
         }
     }
 
     pub fn is_running(&self) -> bool {
-        let mut proc = self.engine.lock().unwrap();
+        let mut proc = self.engine.write();
         if let Some(ref mut child) = proc.child {
             match child.try_wait() {
                 Ok(Some(_)) => {
@@ -418,7 +642,7 @@ impl SidecarManager {
 
     /// Read API_PORT from .env, default 3001.
     fn get_api_port(&self) -> u16 {
-        let env_path = self.soul_path.join(".env");
+        let env_path = self.soul_path.read().join(".env");
         if let Ok(content) = std::fs::read_to_string(&env_path) {
             for line in content.lines() {
                 if let Some(val) = line.strip_prefix("API_PORT=") {
@@ -431,11 +655,48 @@ impl SidecarManager {
         3001
     }
 
+    /// Health of both sidecars. A process that was started but has since
+    /// exited on its own counts as degraded.
+    pub fn health(&self) -> Vec<SubsystemHealth> {
+        vec![
+            Self::process_health(&self.engine, "soul-engine"),
+            Self::process_health(&self.chain, "soul-chain"),
+        ]
+    }
+
+    fn process_health(process: &Arc<RwLock<SidecarProcess>>, name: &str) -> SubsystemHealth {
+        let mut proc = process.write();
+        let exited = match proc.child {
+            Some(ref mut child) => match child.try_wait() {
+                Ok(Some(status)) => Some(status.to_string()),
+                Ok(None) => None,
+                Err(e) => Some(e.to_string()),
+            },
+            None => None,
+        };
+
+        if let Some(reason) = exited {
+            return SubsystemHealth::new(
+                name,
+                "degraded",
+                Some(format!("process exited unexpectedly ({})", reason)),
+            );
+        }
+        match proc.status.as_str() {
+            "error" => SubsystemHealth::new(name, "degraded", Some("failed to start".to_string())),
+            status => SubsystemHealth::new(name, "ok", Some(status.to_string())),
+        }
+    }
+
     /// Graceful shutdown — called when app closes
     pub fn shutdown(&self) {
         for process in [&self.engine, &self.chain] {
-            let mut proc = process.lock().unwrap();
+            let mut proc = process.write();
+            let paused = proc.status == "paused";
             if let Some(ref mut child) = proc.child {
+                if paused {
+                    let _ = send_signal(child.id(), Signal::Continue);
+                }
                 #[cfg(unix)]
                 unsafe {
                     libc::kill(child.id() as i32, libc::SIGTERM);
@@ -449,3 +710,165 @@ impl SidecarManager {
         }
     }
 }
+
+/// SIGTERM, then SIGKILL if the process hasn't exited after 5 seconds.
+fn terminate(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        unsafe {
+            libc::kill(child.id() as i32, libc::SIGTERM);
+        }
+        let start = Instant::now();
+        loop {
+            match child.try_wait() {
+                Ok(Some(_)) => break,
+                Ok(None) if start.elapsed() < Duration::from_secs(5) => {
+                    std::thread::sleep(Duration::from_millis(100));
+                }
+                _ => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    break;
+                }
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+/// Send SIGHUP and report whether the process survived it. Node exits on
+/// SIGHUP unless the engine installed a handler, so surviving means it
+/// reloaded in place.
+#[cfg(unix)]
+fn send_reload_signal(child: &mut Child) -> bool {
+    unsafe {
+        libc::kill(child.id() as i32, libc::SIGHUP);
+    }
+    std::thread::sleep(Duration::from_secs(1));
+    matches!(child.try_wait(), Ok(None))
+}
+
+#[cfg(not(unix))]
+fn send_reload_signal(_child: &mut Child) -> bool {
+    false
+}
+
+enum Signal {
+    Stop,
+    Continue,
+}
+
+#[cfg(unix)]
+fn send_signal(pid: u32, signal: Signal) -> Result<(), String> {
+    let signal = match signal {
+        Signal::Stop => libc::SIGSTOP,
+        Signal::Continue => libc::SIGCONT,
+    };
+    if unsafe { libc::kill(pid as i32, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(format!("Failed to signal soul-engine: {}", std::io::Error::last_os_error()))
+    }
+}
+
+#[cfg(not(unix))]
+fn send_signal(_pid: u32, _signal: Signal) -> Result<(), String> {
+    Err("Pausing the engine is only supported on macOS and Linux".to_string())
+}
+
+fn emit_engine_status(app: &AppHandle, status: &str, pid: Option<u32>) {
+    let _ = app.emit(
+        "sidecar:status",
+        SidecarStatus {
+            process: "soul-engine".to_string(),
+            status: status.to_string(),
+            pid,
+            uptime_secs: None,
+            last_exit: None,
+        },
+    );
+}
+
+/// Classify how a process exited and keep it as its `last_exit`.
+fn record_exit(proc: &mut SidecarProcess, name: &str, status: ExitStatus) -> SidecarExit {
+    // Give the reader thread a moment for the lines written just before exit
+    std::thread::sleep(STDERR_SETTLE);
+    let exit = sidecar_exit::classify(name, status, proc.stderr.lines());
+    proc.last_exit = Some(exit.clone());
+    exit
+}
+
+/// Log the outcome of an engine start in the availability history.
+fn record_start(app: &AppHandle, result: &Result<(), String>) {
+    if let Some(availability) = app.try_state::<Arc<Availability>>() {
+        match result {
+            Ok(()) => availability.up(),
+            Err(e) => availability.down(DownReason::StartFailed, Some(e.clone())),
+        }
+    }
+}
+
+/// Apply config changes to the running engine: its reload endpoint if it
+/// has one, else SIGHUP or a fast restart. Returns the method used.
+pub async fn reload(app: &AppHandle, soul_path: &Path) -> Result<String, String> {
+    let proxy = app.state::<Arc<EngineProxy>>().inner().clone();
+    let method = if proxy.send(app, soul_path, "POST", "/api/reload", None).await.is_ok() {
+        "endpoint".to_string()
+    } else {
+        let sidecar = app.state::<Arc<SidecarManager>>().inner().clone();
+        let handle = app.clone();
+        run_blocking(app, "reload_engine", None, move |_| sidecar.reload_engine(&handle)).await?
+    };
+    let _ = app.emit("sidecar:reloaded", serde_json::json!({ "method": method }));
+    Ok(method)
+}
+
+/// Make `level` the engine's log level and the minimum forwarded as
+/// `sidecar:stdout`. A running engine gets it from its log-level endpoint
+/// if it has one, otherwise as LOG_LEVEL at its next start (see
+/// `env_layers`). Returns "runtime" or "next_start".
+pub async fn set_log_level(
+    app: &AppHandle,
+    soul_path: &Path,
+    level: &str,
+) -> Result<String, String> {
+    let level = sidecar_output::set_min_level(level)?;
+    {
+        let config = app.state::<Arc<RwLock<AppConfig>>>();
+        let mut config = config.write();
+        config.engine_log_level = Some(level.to_string());
+        config.save()?;
+    }
+    if !engine_running(app) {
+        return Ok("next_start".to_string());
+    }
+    let proxy = app.state::<Arc<EngineProxy>>().inner().clone();
+    let body = serde_json::json!({ "level": level });
+    let applied = proxy
+        .send(app, soul_path, "POST", "/api/log-level", Some(body))
+        .await
+        .is_ok();
+    Ok(if applied { "runtime" } else { "next_start" }.to_string())
+}
+
+pub fn engine_running(app: &AppHandle) -> bool {
+    app.state::<Arc<SidecarManager>>().get_status().status == "running"
+}
+
+/// Check the engine every few seconds (health-check interval of the runtime profile) and restart it when it crashed or hung.
+/// A crashed soul-chain is only reported.
+pub fn start_watchdog(app: AppHandle, sidecar: Arc<SidecarManager>) {
+    std::thread::spawn(move || loop {
+        let interval = app.state::<Arc<Profiles>>().health_check_interval();
+        std::thread::sleep(interval);
+        if !background::suspended(&app) {
+            sidecar.watchdog_tick(&app);
+            sidecar.chain_tick(&app);
+        }
+    });
+}
@@ -0,0 +1,157 @@
+use std::process::ExitStatus;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use ts_rs::TS;
+
+/// Why a sidecar died, as far as its exit and stderr tell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum CrashReason {
+    /// A Node module could not be resolved (incomplete install)
+    MissingModule,
+    /// EADDRINUSE: another process holds the port
+    PortInUse,
+    /// The LLM provider rejected the API key
+    InvalidApiKey,
+    /// The JS heap or the system ran out of memory
+    OutOfMemory,
+    /// Terminated by a signal without a known cause
+    Signal,
+    Unknown,
+}
+
+/// How a sidecar exited (`sidecar:crashed`, `SidecarStatus.last_exit`).
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SidecarExit {
+    pub process: String,
+    /// Milliseconds since the epoch
+    #[ts(type = "number")]
+    pub at: u64,
+    pub code: Option<i32>,
+    /// Unix signal that ended the process
+    pub signal: Option<i32>,
+    pub reason: CrashReason,
+    /// What happened and what to do about it, for the UI
+    pub message: String,
+    /// Module name, port, … when the reason has one
+    pub detail: Option<String>,
+    /// Last stderr lines, oldest first
+    pub stderr_tail: Vec<String>,
+}
+
+/// Classify an exit from its status and the last lines of stderr.
+pub fn classify(process: &str, status: ExitStatus, stderr_tail: Vec<String>) -> SidecarExit {
+    let code = status.code();
+    let signal = signal(&status);
+    let (reason, detail) = match_stderr(&stderr_tail).unwrap_or_else(|| match signal {
+        // SIGKILL out of nowhere is usually the kernel's OOM killer
+        Some(9) => (CrashReason::OutOfMemory, Some("SIGKILL".to_string())),
+        Some(_) => (CrashReason::Signal, None),
+        None => (CrashReason::Unknown, None),
+    });
+    let message = message(reason, detail.as_deref(), code, signal);
+    SidecarExit {
+        process: process.to_string(),
+        at: now_ms(),
+        code,
+        signal,
+        reason,
+        message,
+        detail,
+        stderr_tail,
+    }
+}
+
+/// The most specific known failure in the output, searching from the end.
+fn match_stderr(lines: &[String]) -> Option<(CrashReason, Option<String>)> {
+    lines.iter().rev().find_map(|line| {
+        let lower = line.to_lowercase();
+        if lower.contains("cannot find module")
+            || lower.contains("err_module_not_found")
+            || lower.contains("cannot find package")
+        {
+            return Some((CrashReason::MissingModule, quoted(line)));
+        }
+        if line.contains("EADDRINUSE") {
+            let port = line
+                .rsplit(':')
+                .next()
+                .map(|p| p.trim_matches(|c: char| !c.is_ascii_digit()).to_string())
+                .filter(|p| !p.is_empty());
+            return Some((CrashReason::PortInUse, port));
+        }
+        if lower.contains("heap out of memory")
+            || lower.contains("reached heap limit")
+            || lower.contains("allocation failed")
+        {
+            return Some((CrashReason::OutOfMemory, None));
+        }
+        let key_error = lower.contains("invalid_api_key")
+            || lower.contains("invalid api key")
+            || lower.contains("incorrect api key")
+            || lower.contains("invalid x-api-key")
+            || lower.contains("api key not valid")
+            || (lower.contains("401") && lower.contains("api key"));
+        key_error.then_some((CrashReason::InvalidApiKey, None))
+    })
+}
+
+/// First '…' or "…" quoted part of a line.
+fn quoted(line: &str) -> Option<String> {
+    let start = line.find(['\'', '"'])?;
+    let quote = line[start..].chars().next()?;
+    let rest = &line[start + 1..];
+    rest.find(quote).map(|end| rest[..end].to_string())
+}
+
+fn message(
+    reason: CrashReason,
+    detail: Option<&str>,
+    code: Option<i32>,
+    signal: Option<i32>,
+) -> String {
+    match reason {
+        CrashReason::MissingModule => format!(
+            "A dependency is missing{}. Reinstall SoulOS, or run npm install in the engine \
+             directory of a development checkout.",
+            detail.map(|m| format!(" ({})", m)).unwrap_or_default()
+        ),
+        CrashReason::PortInUse => format!(
+            "{} is already in use. Stop the other process or change API_PORT in .env.",
+            detail.map_or("The API port".to_string(), |port| format!("Port {}", port))
+        ),
+        CrashReason::InvalidApiKey => {
+            "The LLM provider rejected the API key. Check the key in the .env editor.".to_string()
+        }
+        CrashReason::OutOfMemory => {
+            "The process ran out of memory. Close other apps or lower the engine's load."
+                .to_string()
+        }
+        CrashReason::Signal => format!("Terminated by signal {}.", signal.unwrap_or_default()),
+        CrashReason::Unknown => match code {
+            Some(code) => format!("Exited with code {}. See the last stderr lines.", code),
+            None => "Exited unexpectedly. See the last stderr lines.".to_string(),
+        },
+    }
+}
+
+#[cfg(unix)]
+fn signal(status: &ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+
+#[cfg(not(unix))]
+fn signal(_status: &ExitStatus) -> Option<i32> {
+    None
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
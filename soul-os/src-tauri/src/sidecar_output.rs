@@ -0,0 +1,249 @@
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::crash::CrashReporter;
+use crate::metrics::Metrics;
+
+/// Lines buffered per stream; beyond this the oldest lines are dropped
+const CAPACITY: usize = 2000;
+/// How often buffered lines are delivered to the frontend
+const FLUSH_INTERVAL: Duration = Duration::from_millis(50);
+/// Lines per emitted event
+const MAX_BATCH: usize = 500;
+/// Stderr lines kept for classifying a crash
+const TAIL_LINES: usize = 50;
+
+/// Log levels, least severe first; stdout lines are forwarded from the
+/// minimum level up
+const LEVELS: [&str; 6] = ["trace", "debug", "info", "warn", "error", "fatal"];
+/// Index into LEVELS; "info" until `set_min_level`
+static MIN_LEVEL: AtomicU8 = AtomicU8::new(2);
+
+/// Position of `level` in LEVELS ("warning" counts as "warn").
+pub fn level_index(level: &str) -> Option<u8> {
+    let level = level.to_ascii_lowercase();
+    let level = if level == "warning" { "warn" } else { level.as_str() };
+    LEVELS.iter().position(|l| *l == level).map(|i| i as u8)
+}
+
+/// Forward stdout lines from `level` up, from now on. Returns the level's
+/// canonical name.
+pub fn set_min_level(level: &str) -> Result<&'static str, String> {
+    let index = level_index(level).ok_or_else(|| {
+        format!("Unknown log level {} (expected one of {})", level, LEVELS.join(", "))
+    })?;
+    MIN_LEVEL.store(index, Ordering::Relaxed);
+    Ok(LEVELS[index as usize])
+}
+
+/// Level a line announces in its first few words ("[DEBUG] …", "warn: …",
+/// "12:00:01 INFO …"). Lines without one count as info.
+fn line_level(line: &str) -> u8 {
+    line.split_whitespace()
+        .take(3)
+        .find_map(|word| level_index(word.trim_matches(|c: char| !c.is_ascii_alphabetic())))
+        .unwrap_or(2)
+}
+
+/// The last `TAIL_LINES` lines a sidecar wrote to stderr.
+#[derive(Clone, Default)]
+pub struct StderrTail(Arc<Mutex<VecDeque<String>>>);
+
+impl StderrTail {
+    fn push(&self, line: &str) {
+        let mut lines = self.0.lock();
+        if lines.len() >= TAIL_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line.to_string());
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        self.0.lock().iter().cloned().collect()
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    fn label(self) -> &'static str {
+        match self {
+            Stream::Stdout => "stdout",
+            Stream::Stderr => "stderr",
+        }
+    }
+
+    fn event(self) -> &'static str {
+        match self {
+            Stream::Stdout => "sidecar:stdout",
+            Stream::Stderr => "sidecar:stderr",
+        }
+    }
+}
+
+/// Bounded line queue with drop-oldest semantics.
+#[derive(Default)]
+struct LineQueue {
+    lines: VecDeque<String>,
+    /// Lines dropped since the last flush
+    dropped: u64,
+}
+
+impl LineQueue {
+    fn push(&mut self, line: String) {
+        if self.lines.len() >= CAPACITY {
+            self.lines.pop_front();
+            self.dropped += 1;
+        }
+        self.lines.push_back(line);
+    }
+
+    fn take(&mut self) -> (Vec<String>, u64) {
+        (
+            self.lines.drain(..).collect(),
+            std::mem::take(&mut self.dropped),
+        )
+    }
+}
+
+#[derive(Default)]
+struct Buffers {
+    stdout: LineQueue,
+    stderr: LineQueue,
+}
+
+impl Buffers {
+    fn queue(&mut self, stream: Stream) -> &mut LineQueue {
+        match stream {
+            Stream::Stdout => &mut self.stdout,
+            Stream::Stderr => &mut self.stderr,
+        }
+    }
+}
+
+/// Forward a sidecar's stdout/stderr to the frontend.
+///
+/// Same reader/flusher split as the PTY: reader threads only push lines into
+/// bounded queues, one flusher per process emits them in batches every
+/// FLUSH_INTERVAL. A chatty process loses its oldest lines instead of
+/// flooding the webview; losses are reported in the event (`dropped`) and
+/// counted in the metrics. Stdout lines below the minimum log level
+/// (`set_min_level`) aren't forwarded at all. The returned tail keeps the
+/// last stderr lines for when the process dies.
+pub fn capture<O, E>(
+    app: &AppHandle,
+    process: &'static str,
+    stdout: Option<O>,
+    stderr: Option<E>,
+) -> StderrTail
+where
+    O: Read + Send + 'static,
+    E: Read + Send + 'static,
+{
+    let buffers = Arc::new(Mutex::new(Buffers::default()));
+    let open = Arc::new(AtomicUsize::new(0));
+    let tail = StderrTail::default();
+
+    if let Some(stdout) = stdout {
+        spawn_reader(app, process, Stream::Stdout, stdout, &buffers, &open, &tail);
+    }
+    if let Some(stderr) = stderr {
+        spawn_reader(app, process, Stream::Stderr, stderr, &buffers, &open, &tail);
+    }
+    if open.load(Ordering::SeqCst) == 0 {
+        return tail;
+    }
+
+    let app = app.clone();
+    let _ = std::thread::Builder::new()
+        .name(format!("{}-output", process))
+        .spawn(move || loop {
+            std::thread::sleep(FLUSH_INTERVAL);
+            // Read before draining so lines pushed before EOF are still flushed
+            let closed = open.load(Ordering::SeqCst) == 0;
+            let (out, err) = {
+                let mut buffers = buffers.lock();
+                (buffers.stdout.take(), buffers.stderr.take())
+            };
+            flush(&app, process, Stream::Stdout, out);
+            flush(&app, process, Stream::Stderr, err);
+            if closed {
+                break;
+            }
+        });
+    tail
+}
+
+fn spawn_reader<R: Read + Send + 'static>(
+    app: &AppHandle,
+    process: &'static str,
+    stream: Stream,
+    reader: R,
+    buffers: &Arc<Mutex<Buffers>>,
+    open: &Arc<AtomicUsize>,
+    tail: &StderrTail,
+) {
+    let app = app.clone();
+    let tail = tail.clone();
+    let buffers = buffers.clone();
+    let open_r = open.clone();
+    open.fetch_add(1, Ordering::SeqCst);
+    let spawned = std::thread::Builder::new()
+        .name(format!("{}-{}", process, stream.label()))
+        .spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                match stream {
+                    Stream::Stderr => {
+                        tail.push(&line);
+                        if let Some(crash) = app.try_state::<Arc<CrashReporter>>() {
+                            crash.record(format!("[{}] {}", process, line));
+                        }
+                    }
+                    // Below the minimum level: not worth an event
+                    Stream::Stdout if line_level(&line) < MIN_LEVEL.load(Ordering::Relaxed) => {
+                        continue;
+                    }
+                    Stream::Stdout => {}
+                }
+                buffers.lock().queue(stream).push(line);
+            }
+            open_r.fetch_sub(1, Ordering::SeqCst);
+        });
+    if spawned.is_err() {
+        open.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn flush(app: &AppHandle, process: &str, stream: Stream, (lines, dropped): (Vec<String>, u64)) {
+    if dropped > 0 {
+        if let Some(metrics) = app.try_state::<Arc<Metrics>>() {
+            metrics.sidecar_lines_dropped(process, stream.label(), dropped);
+        }
+    }
+    // The drop count travels with the first batch
+    let mut dropped = dropped;
+    for batch in lines.chunks(MAX_BATCH) {
+        let _ = app.emit(
+            stream.event(),
+            serde_json::json!({
+                "process": process,
+                "lines": batch,
+                "dropped": dropped,
+            }),
+        );
+        dropped = 0;
+    }
+}
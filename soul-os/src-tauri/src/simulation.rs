@@ -0,0 +1,290 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::RwLock;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use ts_rs::TS;
+
+use crate::config::AppConfig;
+use crate::sidecar::SidecarStatus;
+use crate::types::SoulMood;
+use crate::watcher::{self, WatcherState};
+
+const TICK: Duration = Duration::from_millis(100);
+
+/// Timing and flavour of a simulated soul. All randomness comes from `seed`,
+/// so a profile replays the same sequence of events on every run.
+struct SimProfile {
+    name: &'static str,
+    seed: u64,
+    /// Ticks between pulses
+    pulse_every: u32,
+    activities: &'static [&'static str],
+    /// Ticks between memory file writes
+    memory_every: u32,
+    /// Max valence/energy change per mood update
+    mood_drift: f64,
+    start_valence: f64,
+    start_energy: f64,
+}
+
+const PROFILES: &[SimProfile] = &[
+    SimProfile {
+        name: "calm",
+        seed: 0x5eed_0001,
+        pulse_every: 40,
+        activities: &["think", "reflect", "heartbeat", "garden", "read"],
+        memory_every: 120,
+        mood_drift: 0.04,
+        start_valence: 0.3,
+        start_energy: 0.35,
+    },
+    SimProfile {
+        name: "busy",
+        seed: 0x5eed_0002,
+        pulse_every: 8,
+        activities: &[
+            "search", "research", "code", "analyze", "plan", "write", "remember", "connect",
+        ],
+        memory_every: 30,
+        mood_drift: 0.08,
+        start_valence: 0.4,
+        start_energy: 0.8,
+    },
+    SimProfile {
+        name: "dreaming",
+        seed: 0x5eed_0003,
+        pulse_every: 20,
+        activities: &["dream", "garden", "remember", "shadow", "reflect"],
+        memory_every: 60,
+        mood_drift: 0.06,
+        start_valence: -0.1,
+        start_energy: 0.3,
+    },
+];
+
+/// Startup sequence, in ticks
+const ENGINE_STARTING_AT: u32 = 0;
+const ENGINE_RUNNING_AT: u32 = 15;
+const CHAIN_RUNNING_AT: u32 = 25;
+const MOOD_EVERY: u32 = 30;
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SimulationStatus {
+    pub running: bool,
+    pub profile: Option<String>,
+    pub profiles: Vec<String>,
+    #[ts(type = "number")]
+    pub uptime_secs: u64,
+}
+
+struct SimRun {
+    profile: &'static str,
+    stop: Arc<AtomicBool>,
+    started: Instant,
+    engine: SidecarStatus,
+    chain: SidecarStatus,
+}
+
+/// Demo mode: fabricates pulses, mood drift, node activity and sidecar
+/// transitions on timers. Nothing is written to disk and Node is never started.
+#[derive(Default)]
+pub struct Simulation {
+    run: Arc<RwLock<Option<SimRun>>>,
+}
+
+/// xorshift64* — small, seedable, good enough for demo noise.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Uniform in [0, 1)
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[(self.next() % items.len() as u64) as usize]
+    }
+}
+
+fn sidecar(process: &str, status: &str, uptime_secs: Option<u64>) -> SidecarStatus {
+    SidecarStatus {
+        process: process.to_string(),
+        status: status.to_string(),
+        pid: None,
+        uptime_secs,
+        last_exit: None,
+    }
+}
+
+/// The live WatcherState, or a fresh one (with its node ticker) when no soul is watched.
+fn watcher_state(app: &AppHandle) -> WatcherState {
+    if let Some(state) = app.try_state::<WatcherState>() {
+        return state.inner().clone();
+    }
+    let config = app
+        .try_state::<Arc<RwLock<AppConfig>>>()
+        .map(|c| c.read().watcher.clone())
+        .unwrap_or_default();
+    let state = WatcherState::new(config);
+    app.manage(state.clone());
+    watcher::start_node_ticker(app.clone(), state.clone());
+    state
+}
+
+impl Simulation {
+    pub fn profile_names() -> Vec<String> {
+        PROFILES.iter().map(|p| p.name.to_string()).collect()
+    }
+
+    pub fn status(&self) -> SimulationStatus {
+        let run = self.run.read();
+        SimulationStatus {
+            running: run.is_some(),
+            profile: run.as_ref().map(|r| r.profile.to_string()),
+            profiles: Self::profile_names(),
+            uptime_secs: run
+                .as_ref()
+                .map(|r| r.started.elapsed().as_secs())
+                .unwrap_or(0),
+        }
+    }
+
+    /// Simulated engine status while running (the real one otherwise).
+    pub fn engine_status(&self) -> Option<SidecarStatus> {
+        self.run.read().as_ref().map(|r| r.engine.clone())
+    }
+
+    pub fn chain_status(&self) -> Option<SidecarStatus> {
+        self.run.read().as_ref().map(|r| r.chain.clone())
+    }
+
+    pub fn start(&self, app: &AppHandle, profile: &str) -> Result<SimulationStatus, String> {
+        let profile = PROFILES.iter().find(|p| p.name == profile).ok_or_else(|| {
+            format!(
+                "Unknown profile: {} (expected one of {})",
+                profile,
+                Self::profile_names().join(", ")
+            )
+        })?;
+        self.stop(app);
+
+        let stop = Arc::new(AtomicBool::new(false));
+        *self.run.write() = Some(SimRun {
+            profile: profile.name,
+            stop: stop.clone(),
+            started: Instant::now(),
+            engine: sidecar("soul-engine", "stopped", None),
+            chain: sidecar("soul-chain", "stopped", None),
+        });
+
+        let state = watcher_state(app);
+        let run = self.run.clone();
+        let handle = app.clone();
+        std::thread::Builder::new()
+            .name("soul-simulation".to_string())
+            .spawn(move || simulate(&handle, &state, profile, &run, &stop))
+            .map_err(|e| e.to_string())?;
+
+        let status = self.status();
+        let _ = app.emit("simulation:started", &status);
+        Ok(status)
+    }
+
+    pub fn stop(&self, app: &AppHandle) {
+        let Some(run) = self.run.write().take() else {
+            return;
+        };
+        run.stop.store(true, Ordering::SeqCst);
+        let _ = app.emit("sidecar:status", sidecar("soul-engine", "stopped", None));
+        let _ = app.emit("sidecar:status", sidecar("soul-chain", "stopped", None));
+        let _ = app.emit("simulation:stopped", self.status());
+    }
+}
+
+fn simulate(
+    app: &AppHandle,
+    state: &WatcherState,
+    profile: &SimProfile,
+    run: &RwLock<Option<SimRun>>,
+    stop: &AtomicBool,
+) {
+    let mut rng = Rng(profile.seed);
+    let (mut valence, mut energy) = (profile.start_valence, profile.start_energy);
+    let mut memory_id = 0u32;
+    let mut tick: u32 = 0;
+
+    let set_status = |is_engine: bool, status: SidecarStatus| {
+        if let Some(run) = run.write().as_mut() {
+            if is_engine {
+                run.engine = status.clone();
+            } else {
+                run.chain = status.clone();
+            }
+        }
+        let _ = app.emit("sidecar:status", status);
+    };
+
+    while !stop.load(Ordering::SeqCst) {
+        let uptime = |from: u32| Some(((tick - from) as u64 * TICK.as_millis() as u64) / 1000);
+        match tick {
+            ENGINE_STARTING_AT => {
+                set_status(true, sidecar("soul-engine", "starting", None));
+                set_status(false, sidecar("soul-chain", "starting", None));
+            }
+            ENGINE_RUNNING_AT => {
+                set_status(true, sidecar("soul-engine", "running", Some(0)));
+                watcher::emit_pulse(app, state, "wake", "Simulated soul wakes up");
+            }
+            CHAIN_RUNNING_AT => set_status(false, sidecar("soul-chain", "running", Some(0))),
+            t if t > ENGINE_RUNNING_AT && t.is_multiple_of(10) => {
+                if let Some(run) = run.write().as_mut() {
+                    run.engine.uptime_secs = uptime(ENGINE_RUNNING_AT);
+                    if t > CHAIN_RUNNING_AT {
+                        run.chain.uptime_secs = uptime(CHAIN_RUNNING_AT);
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        if tick > ENGINE_RUNNING_AT {
+            if tick.is_multiple_of(profile.pulse_every) {
+                let activity = rng.pick(profile.activities);
+                let label = format!("{} (simulated)", activity);
+                watcher::emit_pulse(app, state, activity, &label);
+            }
+            if tick.is_multiple_of(profile.memory_every) {
+                memory_id += 1;
+                let file = format!("memories/episodic/simulated-{:04}.md", memory_id);
+                let intensity = 0.4 + rng.unit() * 0.6;
+                watcher::emit_activity(app, state, "mem", &file, "change", intensity);
+            }
+            if tick.is_multiple_of(MOOD_EVERY) {
+                valence =
+                    (valence + (rng.unit() * 2.0 - 1.0) * profile.mood_drift).clamp(-1.0, 1.0);
+                energy = (energy + (rng.unit() * 2.0 - 1.0) * profile.mood_drift).clamp(0.0, 1.0);
+                let mood = SoulMood {
+                    valence: Some((valence * 100.0).round() / 100.0),
+                    energy: Some((energy * 100.0).round() / 100.0),
+                    label: Some(watcher::mood_label(valence, energy).to_string()),
+                    inferred: false,
+                };
+                watcher::apply_mood(app, state, mood);
+            }
+        }
+
+        tick = tick.wrapping_add(1);
+        std::thread::sleep(TICK);
+    }
+}
@@ -0,0 +1,310 @@
+use std::fs::{self, File};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use ts_rs::TS;
+use zip::ZipArchive;
+
+use crate::config::{app_data_dir, AppConfig};
+use crate::founding;
+use crate::relocate;
+use crate::sidecar::SidecarManager;
+use crate::soul_identity;
+
+const WRITE_PROBE: &str = ".soul-os-write-test";
+/// How often a healthy soul is checked for having disappeared
+const MONITOR_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Result of checking the soul path at launch (and while running).
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SoulHealth {
+    pub path: String,
+    pub ok: bool,
+    /// "unreachable", "volume_missing", "not_writable",
+    /// "fingerprint_missing" or "fingerprint_mismatch"
+    pub problem: Option<String>,
+    pub detail: Option<String>,
+    /// Lives on a removable or network volume that can go away
+    pub external: bool,
+    #[ts(type = "number")]
+    pub checked_at: u64,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Mount point of the external volume `path` is on, if any: /Volumes/<name>
+/// on macOS, /media, /run/media or /mnt on Linux.
+fn volume_root(path: &Path) -> Option<PathBuf> {
+    let parts: Vec<&str> = path
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => part.to_str(),
+            _ => None,
+        })
+        .collect();
+    let depth = match parts.as_slice() {
+        ["Volumes", _, ..] if cfg!(target_os = "macos") => 2,
+        ["run", "media", _, _, ..] if cfg!(target_os = "linux") => 4,
+        ["media", _, _, ..] if cfg!(target_os = "linux") => 3,
+        ["mnt", _, ..] if cfg!(target_os = "linux") => 2,
+        _ => return None,
+    };
+    Some(
+        parts[..depth]
+            .iter()
+            .fold(PathBuf::from("/"), |root, part| root.join(part)),
+    )
+}
+
+/// The soul's identity id, which the config remembers as `soul_id`.
+fn read_fingerprint(soul_path: &Path) -> Option<String> {
+    soul_identity::read(soul_path).map(|identity| identity.id)
+}
+
+/// Check `soul_path` against the id the config expects (None = not
+/// adopted yet, any soul is fine).
+pub fn check(soul_path: &Path, soul_id: Option<&str>) -> SoulHealth {
+    let volume = volume_root(soul_path);
+    let mut health = SoulHealth {
+        path: soul_path.to_string_lossy().to_string(),
+        ok: false,
+        problem: None,
+        detail: None,
+        external: volume.is_some(),
+        checked_at: now_ms(),
+    };
+    let mut fail = |problem: &str, detail: String| {
+        health.problem = Some(problem.to_string());
+        health.detail = Some(detail);
+    };
+
+    if !soul_path.is_dir() {
+        match volume.filter(|root| !root.exists()) {
+            Some(root) => fail(
+                "volume_missing",
+                format!("{} is not mounted", root.display()),
+            ),
+            None => fail("unreachable", format!("{} not found", soul_path.display())),
+        }
+        return health;
+    }
+    let probe = soul_path.join(WRITE_PROBE);
+    if let Err(e) = File::create(&probe) {
+        fail("not_writable", e.to_string());
+        return health;
+    }
+    let _ = fs::remove_file(&probe);
+
+    match (soul_id, read_fingerprint(soul_path)) {
+        (Some(_), None) => {
+            fail(
+                "fingerprint_missing",
+                format!(
+                    "{} has no {}",
+                    soul_path.display(),
+                    soul_identity::IDENTITY_FILE
+                ),
+            );
+            return health;
+        }
+        (Some(expected), Some(found)) if expected != found => {
+            fail(
+                "fingerprint_mismatch",
+                "A different soul lives at this path".to_string(),
+            );
+            return health;
+        }
+        _ => {}
+    }
+    health.ok = true;
+    health
+}
+
+/// Give a founded soul a fingerprint (keeping one it already has) and
+/// remember it in the config. True if the config changed (not saved).
+fn adopt(soul_path: &Path, config: &mut AppConfig) -> Result<bool, String> {
+    if !founding::is_founded(soul_path) {
+        return Ok(false);
+    }
+    let id = soul_identity::ensure(soul_path)?.id;
+    let changed = config.soul_id.as_deref() != Some(id.as_str());
+    config.soul_id = Some(id);
+    Ok(changed)
+}
+
+/// Launch check. Skipped during first-run setup; adopts the soul if it is
+/// healthy but has no fingerprint yet.
+pub fn launch_check(config: &mut AppConfig) -> Option<SoulHealth> {
+    if config.first_run {
+        return None;
+    }
+    let soul_path = config.soul_path.clone();
+    let health = check(&soul_path, config.soul_id.as_deref());
+    if health.ok {
+        if let Err(e) =
+            adopt(&soul_path, config).and_then(
+                |changed| {
+                    if changed {
+                        config.save()
+                    } else {
+                        Ok(())
+                    }
+                },
+            )
+        {
+            eprintln!("[soul-health] writing fingerprint failed: {}", e);
+        }
+    }
+    Some(health)
+}
+
+/// Latest health of the soul path; "soul missing" while it failed.
+pub struct SoulGuard {
+    health: Mutex<Option<SoulHealth>>,
+}
+
+impl SoulGuard {
+    pub fn new(health: Option<SoulHealth>) -> Self {
+        Self {
+            health: Mutex::new(health),
+        }
+    }
+
+    pub fn health(&self) -> Option<SoulHealth> {
+        self.health.lock().clone()
+    }
+
+    pub fn missing(&self) -> bool {
+        self.health.lock().as_ref().is_some_and(|h| !h.ok)
+    }
+
+    /// Record a new result, emitting `soul:health` if ok-ness changed.
+    fn update(&self, app: &AppHandle, health: SoulHealth) {
+        let changed = self.health.lock().replace(health.clone()).map(|h| h.ok) != Some(health.ok);
+        if changed {
+            let _ = app.emit("soul:health", health);
+        }
+    }
+}
+
+/// Watch a healthy soul for disappearing (an unplugged drive, a deleted
+/// folder) and switch to "soul missing" when it does.
+pub fn start_monitor(app: AppHandle, guard: Arc<SoulGuard>) {
+    std::thread::Builder::new()
+        .name("soul-health".to_string())
+        .spawn(move || loop {
+            std::thread::sleep(MONITOR_INTERVAL);
+            if guard.health().is_none_or(|h| !h.ok) {
+                continue;
+            }
+            let (soul_path, soul_id) = {
+                let cfg = app.state::<Arc<RwLock<AppConfig>>>();
+                let cfg = cfg.read();
+                (cfg.soul_path.clone(), cfg.soul_id.clone())
+            };
+            // Only the cheap part; writability was checked at launch
+            if !soul_path.is_dir() || (soul_id.is_some() && read_fingerprint(&soul_path).is_none())
+            {
+                guard.update(&app, check(&soul_path, soul_id.as_deref()));
+            }
+        })
+        .expect("Failed to spawn soul health thread");
+}
+
+/// Switch to the soul at `soul_path` after a failed check: adopt it,
+/// re-point watcher and sidecars and start the engine if it was founded.
+pub fn relink(app: &AppHandle, soul_path: &Path) -> Result<SoulHealth, String> {
+    if !soul_path.is_absolute() || relocate::is_system_dir(soul_path) {
+        return Err("Choose an absolute, non-system directory".to_string());
+    }
+    // Any soul may be linked; its own fingerprint is adopted below
+    let health = check(soul_path, None);
+    if !health.ok {
+        return Err(health
+            .detail
+            .unwrap_or_else(|| "Soul path unusable".to_string()));
+    }
+    {
+        let config = app.state::<Arc<RwLock<AppConfig>>>();
+        let mut cfg = config.write();
+        cfg.soul_path = soul_path.to_path_buf();
+        cfg.soul_id = None;
+        adopt(soul_path, &mut cfg)?;
+        cfg.save()?;
+    }
+    relocate::repoint(app, soul_path);
+    if let Some(guard) = app.try_state::<Arc<SoulGuard>>() {
+        guard.update(app, health.clone());
+    }
+
+    let sidecar = app.state::<Arc<SidecarManager>>();
+    if founding::is_founded(soul_path) && sidecar.get_status().status != "running" {
+        if let Err(e) = sidecar.start_engine(app) {
+            eprintln!("[soul-health] soul-engine failed: {}", e);
+        }
+        if let Err(e) = sidecar.start_chain(app) {
+            eprintln!("[soul-health] soul-chain failed: {}", e);
+        }
+    }
+    Ok(health)
+}
+
+/// Newest zip in <app_data_dir>/backups.
+fn latest_backup() -> Option<PathBuf> {
+    fs::read_dir(app_data_dir().join("backups"))
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "zip"))
+        .max_by_key(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+}
+
+/// Unpack the newest backup into the configured soul path (which must be
+/// missing or empty) and relink it. Backups hold no .env or history.
+pub fn restore_latest_backup(app: &AppHandle) -> Result<SoulHealth, String> {
+    let backup = latest_backup().ok_or("No backup found")?;
+    let soul_path = app
+        .state::<Arc<RwLock<AppConfig>>>()
+        .read()
+        .soul_path
+        .clone();
+    if fs::read_dir(&soul_path).is_ok_and(|mut entries| entries.next().is_some()) {
+        return Err(format!(
+            "{} is not empty; relink it instead",
+            soul_path.display()
+        ));
+    }
+    fs::create_dir_all(&soul_path)
+        .map_err(|e| format!("Cannot create {}: {}", soul_path.display(), e))?;
+
+    let file = File::open(&backup).map_err(|e| e.to_string())?;
+    let mut archive = ZipArchive::new(file).map_err(|e| e.to_string())?;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| e.to_string())?;
+        // Names that would escape the soul are skipped
+        let Some(relative) = entry.enclosed_name() else {
+            continue;
+        };
+        let target = soul_path.join(relative);
+        if entry.is_dir() {
+            fs::create_dir_all(&target).map_err(|e| e.to_string())?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let mut out = File::create(&target).map_err(|e| e.to_string())?;
+        std::io::copy(&mut entry, &mut out).map_err(|e| e.to_string())?;
+    }
+    relink(app, &soul_path)
+}
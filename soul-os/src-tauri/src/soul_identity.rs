@@ -0,0 +1,240 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+/// Identity of a soul, written at founding
+pub const IDENTITY_FILE: &str = ".soul-id";
+/// Which machine runs the soul's engine right now (for synced souls)
+const LEASE_FILE: &str = ".soul-lock";
+/// A lease not renewed for this long belongs to a machine that's gone
+const LEASE_TTL: Duration = Duration::from_secs(90);
+const LEASE_RENEW: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SoulIdentity {
+    /// UUID, stable across moves, copies and syncs of the soul
+    pub id: String,
+    /// Unix ms of the founding
+    #[ts(type = "number")]
+    pub created_at: u64,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Random (version 4) UUID.
+fn uuid_v4() -> String {
+    let mut b = [0u8; 16];
+    OsRng.fill_bytes(&mut b);
+    b[6] = (b[6] & 0x0f) | 0x40;
+    b[8] = (b[8] & 0x3f) | 0x80;
+    let hex: String = b.iter().map(|x| format!("{:02x}", x)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+pub fn read(soul_path: &Path) -> Option<SoulIdentity> {
+    let content = fs::read_to_string(soul_path.join(IDENTITY_FILE)).ok()?;
+    serde_json::from_str::<SoulIdentity>(&content)
+        .ok()
+        .filter(|identity| !identity.id.is_empty())
+}
+
+/// Give the soul a new identity (founding), replacing any previous one.
+pub fn create(soul_path: &Path) -> Result<SoulIdentity, String> {
+    let identity = SoulIdentity {
+        id: uuid_v4(),
+        created_at: now_ms(),
+    };
+    let json = serde_json::to_string_pretty(&identity).map_err(|e| e.to_string())?;
+    fs::write(soul_path.join(IDENTITY_FILE), json + "\n").map_err(|e| e.to_string())?;
+    Ok(identity)
+}
+
+/// Name for app-side data about the soul (caches, indexes): its identity,
+/// or a hash of its path before it has one.
+pub fn storage_key(soul_path: &Path) -> String {
+    read(soul_path)
+        .map(|identity| identity.id)
+        .unwrap_or_else(|| {
+            let mut hasher = DefaultHasher::new();
+            soul_path.hash(&mut hasher);
+            format!("{:016x}", hasher.finish())
+        })
+}
+
+/// The soul's identity, created if it has none yet (souls founded before
+/// identities existed).
+pub fn ensure(soul_path: &Path) -> Result<SoulIdentity, String> {
+    match read(soul_path) {
+        Some(identity) => Ok(identity),
+        None => create(soul_path),
+    }
+}
+
+#[cfg(unix)]
+pub fn hostname() -> String {
+    let mut buf = [0u8; 256];
+    let ok = unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) } == 0;
+    let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    ok.then(|| String::from_utf8_lossy(&buf[..end]).to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(not(unix))]
+pub fn hostname() -> String {
+    std::env::var("COMPUTERNAME").unwrap_or_else(|_| "unknown".to_string())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Lease {
+    host: String,
+    pid: u32,
+    /// Unix ms of the last renewal
+    renewed: u64,
+}
+
+struct Held {
+    id: String,
+    soul_path: PathBuf,
+    /// Holds the OS lock until dropped
+    _lock: File,
+}
+
+/// One engine per soul: an OS file lock keyed by the soul id keeps a second
+/// SoulOS on this machine out, a lease in the soul keeps out other machines
+/// that sync it.
+#[derive(Default)]
+pub struct InstanceGuard {
+    held: Mutex<Option<Held>>,
+}
+
+fn lock_path(id: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("soul-os-{}.lock", id))
+}
+
+fn read_lease(soul_path: &Path) -> Option<Lease> {
+    serde_json::from_str(&fs::read_to_string(soul_path.join(LEASE_FILE)).ok()?).ok()
+}
+
+fn write_lease(soul_path: &Path) -> Result<(), String> {
+    let lease = Lease {
+        host: hostname(),
+        pid: std::process::id(),
+        renewed: now_ms(),
+    };
+    let json = serde_json::to_string(&lease).map_err(|e| e.to_string())?;
+    fs::write(soul_path.join(LEASE_FILE), json).map_err(|e| e.to_string())
+}
+
+/// Remove our lease; the OS lock goes with `held`.
+fn give_up(held: Held) {
+    if read_lease(&held.soul_path).is_some_and(|l| l.host == hostname()) {
+        let _ = fs::remove_file(held.soul_path.join(LEASE_FILE));
+    }
+}
+
+impl InstanceGuard {
+    /// Claim `soul_path` before starting its engine. Souls without an
+    /// identity (not founded yet) aren't guarded.
+    pub fn acquire(&self, soul_path: &Path) -> Result<(), String> {
+        let Some(identity) = read(soul_path) else {
+            return Ok(());
+        };
+        let mut held = self.held.lock();
+        if held.as_ref().is_some_and(|h| h.id == identity.id) {
+            return Ok(());
+        }
+        // Switched souls (relink, relocation)
+        if let Some(previous) = held.take() {
+            give_up(previous);
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(lock_path(&identity.id))
+            .map_err(|e| e.to_string())?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                return Err(
+                    "Another SoulOS on this computer is already running this soul".to_string(),
+                )
+            }
+            Err(TryLockError::Error(e)) => return Err(e.to_string()),
+        }
+        let _ = file.set_len(0);
+        let _ = write!(file, "{}", std::process::id());
+
+        // Our own host's stale lease is fine: the OS lock proves it's dead
+        if let Some(lease) = read_lease(soul_path) {
+            let age = now_ms().saturating_sub(lease.renewed);
+            if lease.host != hostname() && age < LEASE_TTL.as_millis() as u64 {
+                return Err(format!(
+                    "This soul is running on {} (seen {}s ago)",
+                    lease.host,
+                    age / 1000
+                ));
+            }
+        }
+        write_lease(soul_path)?;
+        *held = Some(Held {
+            id: identity.id,
+            soul_path: soul_path.to_path_buf(),
+            _lock: file,
+        });
+        Ok(())
+    }
+
+    /// Give the soul up (engine stopped, app quitting).
+    pub fn release(&self) {
+        if let Some(held) = self.held.lock().take() {
+            give_up(held);
+        }
+    }
+
+    fn renew(&self) {
+        if let Some(held) = self.held.lock().as_ref() {
+            if let Err(e) = write_lease(&held.soul_path) {
+                eprintln!("[instance] renewing lease failed: {}", e);
+            }
+        }
+    }
+}
+
+/// Keep the lease of a running engine fresh.
+pub fn start_renewal(app: AppHandle) {
+    std::thread::Builder::new()
+        .name("soul-lease".to_string())
+        .spawn(move || loop {
+            std::thread::sleep(LEASE_RENEW);
+            if let Some(guard) = app.try_state::<Arc<InstanceGuard>>() {
+                guard.renew();
+            }
+        })
+        .expect("Failed to spawn lease thread");
+}
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime};
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::blocking::CancelToken;
+use crate::paths::{self, Location};
+use crate::vault::Vault;
+
+/// Entries per page when the caller doesn't ask for a size
+pub const PAGE_SIZE: usize = 50;
+
+/// One state snapshot, `zustandslog/YYYY-MM-DD_HH-MM_<state>.md`.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct StateEntry {
+    /// Path relative to the soul
+    pub file: String,
+    /// Local time, YYYY-MM-DDTHH:MM:SS
+    pub timestamp: String,
+    /// start, ende, heartbeat, reflection, … as in the file name
+    pub state: String,
+    /// `Trigger:`/`Auslöser:` line, else the title's last part ("Session-Ende")
+    pub trigger: Option<String>,
+    /// The snapshot without its title and trigger line. None while the
+    /// file is encrypted and the app locked.
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct StatelogPage {
+    /// Newest first
+    pub entries: Vec<StateEntry>,
+    /// Entries matching the date filter, over all pages
+    pub total: usize,
+    pub offset: usize,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct StateCount {
+    pub state: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct StateDistribution {
+    /// First day counted, YYYY-MM-DD
+    pub from: String,
+    /// Last day counted (today), YYYY-MM-DD
+    pub to: String,
+    pub total: usize,
+    /// Most frequent state first
+    pub states: Vec<StateCount>,
+}
+
+/// `YYYY-MM-DD` filter bound.
+pub fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date {} (expected YYYY-MM-DD)", date))
+}
+
+/// Timestamp and state from a snapshot's file name.
+fn parse_name(file_name: &str) -> Option<(NaiveDateTime, String)> {
+    let stem = file_name.strip_suffix(".md")?;
+    let (date, rest) = stem.split_once('_')?;
+    let (time, state) = rest.split_once('_')?;
+    let at = NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H-%M").ok()?;
+    (!state.is_empty()).then(|| (at, state.to_lowercase()))
+}
+
+/// Snapshots of both directory spellings, oldest first.
+fn files(soul_path: &Path) -> Vec<(NaiveDateTime, String, PathBuf)> {
+    let mut files: Vec<_> = paths::existing(soul_path, Location::StateLog)
+        .into_iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter_map(|e| {
+            let (at, state) = parse_name(&e.file_name().to_string_lossy())?;
+            Some((at, state, e.path()))
+        })
+        .collect();
+    files.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.2.cmp(&b.2)));
+    files
+}
+
+fn field<'a>(line: &'a str, names: &[&str]) -> Option<&'a str> {
+    let line = line.trim().trim_start_matches(['-', '*']).trim_start();
+    let (name, value) = line.split_once(':')?;
+    let name = name.trim();
+    names
+        .iter()
+        .any(|n| name.eq_ignore_ascii_case(n))
+        .then(|| value.trim())
+}
+
+/// Trigger and notes of a snapshot's content.
+fn parse_content(content: &str) -> (Option<String>, String) {
+    let mut trigger = None;
+    let mut title_trigger = None;
+    let mut notes = Vec::new();
+    for line in content.lines() {
+        if let Some(title) = line.strip_prefix("# ") {
+            title_trigger = title
+                .rsplit_once(" — ")
+                .or_else(|| title.rsplit_once(" - "))
+                .map(|(_, last)| last.trim().to_string());
+            continue;
+        }
+        if trigger.is_none() {
+            if let Some(value) = field(line, &["Trigger", "Auslöser", "Ausloeser", "Anlass"]) {
+                trigger = Some(value.to_string());
+                continue;
+            }
+        }
+        notes.push(line);
+    }
+    (
+        trigger.or(title_trigger).filter(|t| !t.is_empty()),
+        notes.join("\n").trim().to_string(),
+    )
+}
+
+fn entry(
+    soul_path: &Path,
+    vault: &Vault,
+    at: NaiveDateTime,
+    state: String,
+    path: &Path,
+) -> StateEntry {
+    let (trigger, notes) = match vault.read(path) {
+        Ok(content) => {
+            let (trigger, notes) = parse_content(&content);
+            (trigger, Some(notes))
+        }
+        Err(_) => (None, None),
+    };
+    StateEntry {
+        file: path
+            .strip_prefix(soul_path)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/"),
+        timestamp: at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        state,
+        trigger,
+        notes,
+    }
+}
+
+/// Snapshots between `from` and `to` (inclusive days), newest first. Only
+/// the requested page is read from disk.
+pub fn list(
+    soul_path: &Path,
+    vault: &Vault,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    offset: usize,
+    limit: usize,
+    token: &CancelToken,
+) -> Result<StatelogPage, String> {
+    let matching: Vec<_> = files(soul_path)
+        .into_iter()
+        .rev()
+        .filter(|(at, _, _)| {
+            from.is_none_or(|from| at.date() >= from) && to.is_none_or(|to| at.date() <= to)
+        })
+        .collect();
+    let mut entries = Vec::new();
+    for (at, state, path) in matching.iter().skip(offset).take(limit) {
+        token.check()?;
+        entries.push(entry(soul_path, vault, *at, state.clone(), path));
+    }
+    Ok(StatelogPage {
+        entries,
+        total: matching.len(),
+        offset,
+    })
+}
+
+/// How often each state was logged over the last `days` days, today
+/// included. Counted from file names alone.
+pub fn distribution(soul_path: &Path, days: u32) -> StateDistribution {
+    let to = Local::now().date_naive();
+    let from = to - Duration::days(i64::from(days.max(1)) - 1);
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for (at, state, _) in files(soul_path) {
+        if at.date() >= from && at.date() <= to {
+            *counts.entry(state).or_default() += 1;
+        }
+    }
+    let mut states: Vec<StateCount> = counts
+        .into_iter()
+        .map(|(state, count)| StateCount { state, count })
+        .collect();
+    states.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.state.cmp(&b.state)));
+    StateDistribution {
+        from: from.format("%Y-%m-%d").to_string(),
+        to: to.format("%Y-%m-%d").to_string(),
+        total: states.iter().map(|s| s.count).sum(),
+        states,
+    }
+}
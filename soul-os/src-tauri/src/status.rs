@@ -0,0 +1,179 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use parking_lot::Mutex;
+
+use crate::types::SoulStatus;
+
+/// Parsed SEED.md status, reused until the soul path or the file's mtime or
+/// size changes. The watcher invalidates it on SEED.md events and emits
+/// `soul:status-changed`.
+#[derive(Default)]
+pub struct StatusCache {
+    entry: Mutex<Option<CachedStatus>>,
+}
+
+struct CachedStatus {
+    /// Canonical soul directory the status was parsed from
+    soul: PathBuf,
+    modified: Option<SystemTime>,
+    len: u64,
+    status: SoulStatus,
+}
+
+impl StatusCache {
+    pub fn get(&self, sp: &Path) -> Result<SoulStatus, String> {
+        let meta = fs::metadata(sp.join("SEED.md")).ok();
+        let modified = meta.as_ref().and_then(|m| m.modified().ok());
+        let len = meta.as_ref().map(|m| m.len()).unwrap_or(0);
+        let soul = fs::canonicalize(sp).unwrap_or_else(|_| sp.to_path_buf());
+
+        if let Some(cached) = self.entry.lock().as_ref() {
+            if meta.is_some()
+                && cached.soul == soul
+                && cached.modified == modified
+                && cached.len == len
+            {
+                return Ok(cached.status.clone());
+            }
+        }
+
+        let status = parse_soul_status(sp)?;
+        *self.entry.lock() = Some(CachedStatus {
+            soul,
+            modified,
+            len,
+            status: status.clone(),
+        });
+        Ok(status)
+    }
+
+    /// Forget the cached status (the soul path changed).
+    pub fn clear(&self) {
+        *self.entry.lock() = None;
+    }
+
+    /// Drop the cached status and re-parse. Returns the new status if it
+    /// differs from the previous one.
+    pub fn refresh(&self, sp: &Path) -> Option<SoulStatus> {
+        let previous = self.entry.lock().take().map(|c| c.status);
+        let status = self.get(sp).ok()?;
+        (previous.as_ref() != Some(&status)).then_some(status)
+    }
+}
+
+fn parse_soul_status(sp: &Path) -> Result<SoulStatus, String> {
+    let seed_path = sp.join("SEED.md");
+
+    if !seed_path.exists() {
+        return Err("SEED.md not found".to_string());
+    }
+
+    let content = fs::read_to_string(&seed_path).map_err(|e| e.to_string())?;
+    let seed_size = fs::metadata(&seed_path)
+        .map(|m| m.len())
+        .unwrap_or(0);
+
+    // Parse basic info from SEED.md header
+    let mut name = String::from("Soul");
+    let mut born = String::from("unknown");
+    let mut sessions: u32 = 0;
+    let mut model = String::from("unknown");
+    let mut state = String::new();
+    let mut mood = String::new();
+
+    for line in content.lines() {
+        if line.starts_with("#SEED") {
+            continue;
+        }
+        if line.starts_with("#geboren:") || line.starts_with("#born:") {
+            for part in line.split_whitespace() {
+                if let Some(val) = part.strip_prefix("#geboren:").or(part.strip_prefix("#born:")) {
+                    born = val.to_string();
+                }
+                if let Some(val) = part.strip_prefix("#sessions:") {
+                    sessions = val.parse().unwrap_or(0);
+                }
+            }
+        }
+        if line.contains("modell:") || line.contains("model:") {
+            if let Some(idx) = line.find("modell:").or(line.find("model:")) {
+                let rest = &line[idx..];
+                let val = rest
+                    .split('|')
+                    .next()
+                    .unwrap_or("")
+                    .split(':')
+                    .nth(1)
+                    .unwrap_or("")
+                    .trim();
+                model = val.to_string();
+            }
+        }
+        if line.contains("zustand:") || line.contains("state:") {
+            if let Some(idx) = line.find("zustand:").or(line.find("state:")) {
+                let rest = &line[idx..];
+                let val = rest
+                    .split('|')
+                    .next()
+                    .unwrap_or("")
+                    .split(':')
+                    .nth(1)
+                    .unwrap_or("")
+                    .trim();
+                state = val.to_string();
+            }
+        }
+    }
+
+    // Derive mood from state
+    if !state.is_empty() {
+        mood = state.split(',').next().unwrap_or("").trim().to_string();
+    }
+
+    // Try to get name from @META or project
+    if content.contains("projekt:seele") || content.contains("project:soul") {
+        name = String::from("Seele");
+    }
+
+    Ok(SoulStatus {
+        name,
+        born,
+        sessions,
+        model,
+        state,
+        mood,
+        seed_size,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_is_keyed_by_soul_path() {
+        let dir = std::env::temp_dir().join(format!("soulos-status-{}", std::process::id()));
+        let (a, b) = (dir.join("a"), dir.join("b"));
+        // Same size and mtime, different content
+        let stamp = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+        for (soul, sessions) in [(&a, "1"), (&b, "2")] {
+            fs::create_dir_all(soul).unwrap();
+            let seed = soul.join("SEED.md");
+            let content = format!("#SEED\n#born:2026-01-01 #sessions:{}\n", sessions);
+            fs::write(&seed, content).unwrap();
+            fs::File::options()
+                .write(true)
+                .open(&seed)
+                .unwrap()
+                .set_modified(stamp)
+                .unwrap();
+        }
+        let cache = StatusCache::default();
+        assert_eq!(cache.get(&a).unwrap().sessions, 1);
+        assert_eq!(cache.get(&b).unwrap().sessions, 2);
+        assert_eq!(cache.get(&a).unwrap().sessions, 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
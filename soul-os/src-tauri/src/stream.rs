@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::{Condvar, Mutex};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use ts_rs::TS;
+
+/// Prefix of the error a command returns when its result would exceed
+/// `MAX_PAYLOAD`. The frontend matches on it (`isTooLarge`) and switches
+/// to the streaming variant.
+pub const TOO_LARGE: &str = "TooLarge";
+/// Largest string a command returns through IPC in one piece
+pub const MAX_PAYLOAD: usize = 2 * 1024 * 1024;
+const CHUNK_SIZE: usize = 256 * 1024;
+/// Chunks sent ahead of the last acknowledged one
+const WINDOW: u32 = 4;
+/// A stream whose consumer stops acknowledging is dropped after this
+const ACK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// One piece of a streamed value (`stream:chunk`). Acknowledge every chunk
+/// with `stream_ack`; at most `WINDOW` are sent ahead.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct StreamChunk {
+    pub stream_id: String,
+    /// 0-based
+    pub seq: u32,
+    pub data: String,
+    pub last: bool,
+}
+
+/// A stream ended without its last chunk (`stream:error`).
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct StreamError {
+    pub stream_id: String,
+    pub error: String,
+}
+
+/// What a `stream_*` command started.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct StreamInfo {
+    pub stream_id: String,
+    #[ts(type = "number")]
+    pub total_bytes: u64,
+    pub chunks: u32,
+}
+
+/// Fail with `TooLarge` when `value` is too big for one IPC message.
+pub fn limit(value: String, what: &str, alternative: &str) -> Result<String, String> {
+    if value.len() <= MAX_PAYLOAD {
+        return Ok(value);
+    }
+    Err(format!(
+        "{}: {} is {} bytes, over the {} byte IPC limit; use {}",
+        TOO_LARGE,
+        what,
+        value.len(),
+        MAX_PAYLOAD,
+        alternative
+    ))
+}
+
+/// Split at char boundaries into pieces of about `CHUNK_SIZE` bytes.
+fn split(value: &str) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = value;
+    while rest.len() > CHUNK_SIZE {
+        let mut end = CHUNK_SIZE;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks.push(rest);
+    chunks
+}
+
+#[derive(Default)]
+struct Progress {
+    /// Chunks acknowledged so far
+    acked: u32,
+    cancelled: bool,
+}
+
+#[derive(Default)]
+struct Flow {
+    progress: Mutex<Progress>,
+    changed: Condvar,
+}
+
+/// Streams in flight, for acknowledgments and cancellation.
+#[derive(Default)]
+pub struct Streams {
+    active: Mutex<HashMap<String, Arc<Flow>>>,
+}
+
+impl Streams {
+    /// Send `value` as `stream:chunk` events from a background thread. The
+    /// caller picks `stream_id` so it can listen before the first chunk.
+    pub fn start(
+        self: &Arc<Self>,
+        app: &AppHandle,
+        stream_id: String,
+        value: String,
+    ) -> Result<StreamInfo, String> {
+        if stream_id.is_empty() {
+            return Err("Missing stream id".to_string());
+        }
+        let flow = Arc::new(Flow::default());
+        {
+            let mut active = self.active.lock();
+            if active.contains_key(&stream_id) {
+                return Err(format!("Stream {} already exists", stream_id));
+            }
+            active.insert(stream_id.clone(), flow.clone());
+        }
+        let info = StreamInfo {
+            stream_id: stream_id.clone(),
+            total_bytes: value.len() as u64,
+            chunks: split(&value).len() as u32,
+        };
+
+        let streams = self.clone();
+        let app = app.clone();
+        std::thread::Builder::new()
+            .name("ipc-stream".to_string())
+            .spawn(move || {
+                if let Err(error) = send(&app, &stream_id, &value, &flow) {
+                    let _ = app.emit(
+                        "stream:error",
+                        StreamError {
+                            stream_id: stream_id.clone(),
+                            error,
+                        },
+                    );
+                }
+                streams.active.lock().remove(&stream_id);
+            })
+            .map_err(|e| e.to_string())?;
+        Ok(info)
+    }
+
+    /// The consumer has processed chunk `seq`.
+    pub fn ack(&self, stream_id: &str, seq: u32) {
+        if let Some(flow) = self.active.lock().get(stream_id) {
+            let mut progress = flow.progress.lock();
+            progress.acked = progress.acked.max(seq.saturating_add(1));
+            flow.changed.notify_all();
+        }
+    }
+
+    pub fn cancel(&self, stream_id: &str) {
+        if let Some(flow) = self.active.lock().get(stream_id) {
+            flow.progress.lock().cancelled = true;
+            flow.changed.notify_all();
+        }
+    }
+}
+
+fn send(app: &AppHandle, stream_id: &str, value: &str, flow: &Flow) -> Result<(), String> {
+    let chunks = split(value);
+    let count = chunks.len() as u32;
+    for (seq, data) in (0..).zip(chunks) {
+        // Backpressure: wait until the consumer caught up to the window
+        {
+            let mut progress = flow.progress.lock();
+            while !progress.cancelled && seq >= progress.acked + WINDOW {
+                if flow
+                    .changed
+                    .wait_for(&mut progress, ACK_TIMEOUT)
+                    .timed_out()
+                {
+                    return Err("Stream consumer stopped acknowledging".to_string());
+                }
+            }
+            if progress.cancelled {
+                return Err("Stream cancelled".to_string());
+            }
+        }
+        app.emit(
+            "stream:chunk",
+            StreamChunk {
+                stream_id: stream_id.to_string(),
+                seq,
+                data: data.to_string(),
+                last: seq + 1 == count,
+            },
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
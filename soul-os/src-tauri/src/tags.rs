@@ -0,0 +1,298 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+use parking_lot::RwLock;
+use regex::Regex;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use ts_rs::TS;
+
+use crate::backend::OsFileStore;
+use crate::export::files_in;
+use crate::paths::{self, Location};
+use crate::safe_path;
+use crate::vault::Vault;
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct TagCount {
+    pub tag: String,
+    /// Memory files carrying it
+    pub count: usize,
+}
+
+/// `#tag` in running text: letters, digits, `_`, `-` and `/` (nested
+/// tags), not only digits (issue numbers) and not a heading marker.
+fn inline_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?:^|[\s(\[])#([\p{L}\p{N}_][\p{L}\p{N}_/-]*)").expect("valid tag pattern")
+    })
+}
+
+/// Tag as stored: no `#` or quotes, lowercase; None for empty or
+/// all-digit tags.
+pub fn normalize(tag: &str) -> Option<String> {
+    let tag = tag
+        .trim()
+        .trim_start_matches('#')
+        .trim_matches(|c| c == '"' || c == '\'');
+    let tag = tag.trim_end_matches(['/', '-']).to_lowercase();
+    (!tag.is_empty() && !tag.chars().all(|c| c.is_ascii_digit())).then_some(tag)
+}
+
+/// Split off a leading `---` YAML frontmatter block: (frontmatter lines, body).
+fn frontmatter(content: &str) -> Option<(Vec<&str>, &str)> {
+    let rest = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))?;
+    let mut offset = 0;
+    let mut lines = Vec::new();
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        if line.trim_end() == "---" {
+            return Some((lines, &rest[offset..]));
+        }
+        lines.push(line.trim_end_matches(['\n', '\r']));
+    }
+    None
+}
+
+/// Tags in frontmatter: `tags: [a, b]`, `tags: a, b` or a `- a` list.
+fn frontmatter_tags(lines: &[&str]) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut in_list = false;
+    for line in lines {
+        if let Some(value) = line.strip_prefix("tags:") {
+            let value = value.trim().trim_start_matches('[').trim_end_matches(']');
+            tags.extend(value.split(',').filter_map(normalize));
+            in_list = value.is_empty();
+        } else if in_list {
+            match line.trim_start().strip_prefix("- ") {
+                Some(item) => tags.extend(normalize(item)),
+                None => in_list = false,
+            }
+        }
+    }
+    tags
+}
+
+/// Inline `#tags` outside fenced code blocks.
+fn inline_tags(body: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut fenced = false;
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            fenced = !fenced;
+            continue;
+        }
+        if fenced {
+            continue;
+        }
+        tags.extend(
+            inline_pattern()
+                .captures_iter(line)
+                .filter_map(|c| normalize(&c[1])),
+        );
+    }
+    tags
+}
+
+/// Every tag of a memory file, frontmatter and inline.
+pub fn parse(content: &str) -> BTreeSet<String> {
+    let (front, body) = frontmatter(content).unwrap_or((Vec::new(), content));
+    frontmatter_tags(&front)
+        .into_iter()
+        .chain(inline_tags(body))
+        .collect()
+}
+
+/// `content` with `add` in its frontmatter tags and every `remove` tag
+/// gone, from the frontmatter and inline. Returns the new content and tags.
+pub fn retag(content: &str, add: &[String], remove: &[String]) -> (String, BTreeSet<String>) {
+    let add: Vec<String> = add.iter().filter_map(|t| normalize(t)).collect();
+    let remove: BTreeSet<String> = remove.iter().filter_map(|t| normalize(t)).collect();
+    let (front, body) = frontmatter(content).unwrap_or((Vec::new(), content));
+
+    let mut listed: Vec<String> = frontmatter_tags(&front)
+        .into_iter()
+        .filter(|t| !remove.contains(t))
+        .collect();
+    for tag in add {
+        if !listed.contains(&tag) {
+            listed.push(tag);
+        }
+    }
+
+    // Other frontmatter keys stay as they were; the tags key is rewritten
+    let mut kept = Vec::new();
+    let mut in_list = false;
+    for line in &front {
+        if let Some(value) = line.strip_prefix("tags:") {
+            in_list = value.trim().is_empty();
+            continue;
+        }
+        if in_list && line.trim_start().starts_with("- ") {
+            continue;
+        }
+        in_list = false;
+        kept.push(line.to_string());
+    }
+    if !listed.is_empty() {
+        kept.push(format!("tags: [{}]", listed.join(", ")));
+    }
+
+    let body = inline_pattern()
+        .replace_all(body, |c: &regex::Captures| {
+            let whole = &c[0];
+            match normalize(&c[1]) {
+                // Keep the separator in front of the tag
+                Some(tag) if remove.contains(&tag) => {
+                    whole[..whole.len() - c[1].len() - 1].to_string()
+                }
+                _ => whole.to_string(),
+            }
+        })
+        .to_string();
+    let content = if kept.is_empty() {
+        body
+    } else {
+        format!("---\n{}\n---\n{}", kept.join("\n"), body)
+    };
+    let tags = parse(&content);
+    (content, tags)
+}
+
+fn is_memory(relative: &str) -> bool {
+    relative.ends_with(".md")
+        && matches!(
+            paths::classify(relative),
+            Some(Location::Memories | Location::Memory(_))
+        )
+}
+
+/// Tags per memory file, built on first use and kept current by the
+/// watcher (`changed`) and `retag_file`.
+#[derive(Default)]
+pub struct TagIndex {
+    /// Relative path → tags; None until built
+    files: RwLock<Option<HashMap<String, BTreeSet<String>>>>,
+}
+
+impl TagIndex {
+    fn build(soul_path: &Path, vault: &Vault) -> HashMap<String, BTreeSet<String>> {
+        let mut files = HashMap::new();
+        for dir in paths::existing(soul_path, Location::Memories) {
+            for path in files_in(&dir, true) {
+                let Ok(relative) = path.strip_prefix(soul_path) else {
+                    continue;
+                };
+                let relative = relative.to_string_lossy().replace('\\', "/");
+                if !is_memory(&relative) {
+                    continue;
+                }
+                // Encrypted files are only indexed while unlocked
+                if let Ok(content) = vault.read(&path) {
+                    files.insert(relative, parse(&content));
+                }
+            }
+        }
+        files
+    }
+
+    fn with<T>(
+        &self,
+        soul_path: &Path,
+        vault: &Vault,
+        f: impl FnOnce(&HashMap<String, BTreeSet<String>>) -> T,
+    ) -> T {
+        if let Some(files) = self.files.read().as_ref() {
+            return f(files);
+        }
+        let mut files = self.files.write();
+        f(files.get_or_insert_with(|| Self::build(soul_path, vault)))
+    }
+
+    /// Tags with the number of files carrying them, most used first.
+    pub fn list(&self, soul_path: &Path, vault: &Vault) -> Vec<TagCount> {
+        self.with(soul_path, vault, |files| {
+            let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+            for tags in files.values() {
+                for tag in tags {
+                    *counts.entry(tag.as_str()).or_insert(0) += 1;
+                }
+            }
+            let mut list: Vec<TagCount> = counts
+                .into_iter()
+                .map(|(tag, count)| TagCount {
+                    tag: tag.to_string(),
+                    count,
+                })
+                .collect();
+            list.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+            list
+        })
+    }
+
+    /// Memory files tagged `tag` (or a tag nested below it: `project`
+    /// matches `project/soul`), sorted.
+    pub fn files_with(&self, soul_path: &Path, vault: &Vault, tag: &str) -> Vec<String> {
+        let Some(tag) = normalize(tag) else {
+            return Vec::new();
+        };
+        let nested = format!("{}/", tag);
+        self.with(soul_path, vault, |files| {
+            let mut matching: Vec<String> = files
+                .iter()
+                .filter(|(_, tags)| tags.iter().any(|t| *t == tag || t.starts_with(&nested)))
+                .map(|(path, _)| path.clone())
+                .collect();
+            matching.sort();
+            matching
+        })
+    }
+
+    /// Record the tags of `relative` (None: the file is gone). True if
+    /// they changed. No-op before the index is built.
+    pub fn set(&self, relative: &str, tags: Option<BTreeSet<String>>) -> bool {
+        let mut files = self.files.write();
+        let Some(files) = files.as_mut() else {
+            return false;
+        };
+        match tags {
+            Some(tags) => files.insert(relative.to_string(), tags.clone()) != Some(tags),
+            None => files.remove(relative).is_some(),
+        }
+    }
+
+    /// Forget everything (the soul moved).
+    pub fn reset(&self) {
+        *self.files.write() = None;
+    }
+}
+
+/// Watcher hook: a file under the soul changed. Updates the index for
+/// memory files and emits `tags:changed` when their tags differ.
+pub fn changed(app: &AppHandle, soul_path: &Path, relative: &str) {
+    let relative = relative.replace('\\', "/");
+    if !is_memory(&relative) {
+        return;
+    }
+    let (Some(index), Some(vault)) = (
+        app.try_state::<Arc<TagIndex>>(),
+        app.try_state::<Arc<Vault>>(),
+    ) else {
+        return;
+    };
+    let Ok(path) = safe_path::safe_resolve(&OsFileStore, soul_path, &relative) else {
+        return;
+    };
+    let tags = path
+        .is_file()
+        .then(|| vault.read(&path).ok().map(|content| parse(&content)))
+        .flatten();
+    if index.set(&relative, tags) {
+        let _ = app.emit("tags:changed", serde_json::json!({ "path": relative }));
+    }
+}
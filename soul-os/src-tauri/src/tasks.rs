@@ -0,0 +1,171 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use ts_rs::TS;
+
+use crate::blocking::CancelToken;
+use crate::metrics::Metrics;
+
+/// Finished tasks kept for the activity center
+const MAX_FINISHED: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskState {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct TaskInfo {
+    pub id: String,
+    /// Operation name, e.g. "export_soul_filtered"
+    pub kind: String,
+    pub state: TaskState,
+    #[ts(type = "number")]
+    pub done: u64,
+    #[ts(type = "number")]
+    pub total: u64,
+    /// Unix ms
+    #[ts(type = "number")]
+    pub started: u64,
+    #[ts(type = "number | null")]
+    pub finished: Option<u64>,
+    pub error: Option<String>,
+    /// Started with a caller-supplied id, so `cancel_task` can reach it
+    pub cancellable: bool,
+}
+
+struct Running {
+    info: TaskInfo,
+    token: CancelToken,
+    /// Shown in `list_tasks` once it ran long enough to report progress
+    visible: bool,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Registry of every long-running backend job, keyed by task id.
+#[derive(Default)]
+pub struct Tasks {
+    running: Mutex<HashMap<String, Running>>,
+    finished: Mutex<VecDeque<TaskInfo>>,
+    next_id: AtomicU64,
+}
+
+impl Tasks {
+    /// Register a job. `id` is the caller-supplied task id (cancellable);
+    /// without one an id is generated from `kind`.
+    pub fn begin(&self, kind: &str, id: Option<String>) -> (String, CancelToken) {
+        let cancellable = id.is_some();
+        let id = id.unwrap_or_else(|| {
+            format!("{}-{}", kind, self.next_id.fetch_add(1, Ordering::Relaxed))
+        });
+        let token = CancelToken::default();
+        let info = TaskInfo {
+            id: id.clone(),
+            kind: kind.to_string(),
+            state: TaskState::Running,
+            done: 0,
+            total: 0,
+            started: now_ms(),
+            finished: None,
+            error: None,
+            cancellable,
+        };
+        self.running.lock().insert(
+            id.clone(),
+            Running {
+                info,
+                token: token.clone(),
+                visible: cancellable,
+            },
+        );
+        (id, token)
+    }
+
+    /// Emit `task:progress` with the token's current counters.
+    pub fn report(&self, app: &AppHandle, id: &str) {
+        let info = {
+            let mut running = self.running.lock();
+            let Some(task) = running.get_mut(id) else {
+                return;
+            };
+            let (done, total) = task.token.progress();
+            task.info.done = done;
+            task.info.total = total;
+            task.visible = true;
+            task.info.clone()
+        };
+        let _ = app.emit("task:progress", &info);
+    }
+
+    /// Move a job to the finished list. Emits `task:finished` for jobs that
+    /// were visible (reported progress or were cancellable).
+    pub fn finish(&self, app: &AppHandle, id: &str, error: Option<String>) {
+        let Some(task) = self.running.lock().remove(id) else {
+            return;
+        };
+        let mut info = task.info;
+        (info.done, info.total) = task.token.progress();
+        info.finished = Some(now_ms());
+        if let Some(metrics) = app.try_state::<Arc<Metrics>>() {
+            metrics.task_finished(&info.kind, now_ms().saturating_sub(info.started));
+        }
+        info.state = match error {
+            None => TaskState::Completed,
+            Some(_) if task.token.is_cancelled() => TaskState::Cancelled,
+            Some(_) => TaskState::Failed,
+        };
+        info.error = error;
+        if !task.visible {
+            return;
+        }
+        let _ = app.emit("task:finished", &info);
+        let mut finished = self.finished.lock();
+        finished.push_front(info);
+        finished.truncate(MAX_FINISHED);
+    }
+
+    pub fn cancel(&self, id: &str) -> bool {
+        match self.running.lock().get(id) {
+            Some(task) => {
+                task.token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Running tasks first (oldest first), then recently finished ones.
+    pub fn list(&self) -> Vec<TaskInfo> {
+        let mut tasks: Vec<TaskInfo> = self
+            .running
+            .lock()
+            .values()
+            .filter(|t| t.visible)
+            .map(|t| {
+                let mut info = t.info.clone();
+                (info.done, info.total) = t.token.progress();
+                info
+            })
+            .collect();
+        tasks.sort_by_key(|t| t.started);
+        tasks.extend(self.finished.lock().iter().cloned());
+        tasks
+    }
+}
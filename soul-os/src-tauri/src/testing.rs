@@ -0,0 +1,190 @@
+//! In-memory backends for command-layer tests.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use parking_lot::Mutex;
+
+use crate::backend::{FileStore, GitBackend, ProcessLauncher, ProcessOutput};
+use crate::blocking::CancelToken;
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, path.display().to_string())
+}
+
+/// Resolve `.` and `..` without touching the disk.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Files and directories held in maps. Directories are implied by the files
+/// below them or created explicitly.
+#[derive(Default)]
+pub struct MemoryFileStore {
+    files: Mutex<BTreeMap<PathBuf, Vec<u8>>>,
+    dirs: Mutex<BTreeSet<PathBuf>>,
+    restricted: Mutex<Vec<PathBuf>>,
+}
+
+impl MemoryFileStore {
+    pub fn with_files(files: &[(&str, &str)]) -> Self {
+        let store = Self::default();
+        for (path, content) in files {
+            store.write(Path::new(path), content.as_bytes()).unwrap();
+        }
+        store
+    }
+
+    pub fn text(&self, path: &str) -> Option<String> {
+        self.files
+            .lock()
+            .get(Path::new(path))
+            .map(|data| String::from_utf8_lossy(data).to_string())
+    }
+
+    pub fn restricted(&self) -> Vec<PathBuf> {
+        self.restricted.lock().clone()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.dirs.lock().contains(path)
+            || self
+                .files
+                .lock()
+                .keys()
+                .any(|f| f.starts_with(path) && f != path)
+    }
+}
+
+impl FileStore for MemoryFileStore {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.files
+            .lock()
+            .get(&normalize(path))
+            .cloned()
+            .ok_or_else(|| not_found(path))
+    }
+
+    fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        let path = normalize(path);
+        if let Some(parent) = path.parent() {
+            self.create_dir_all(parent)?;
+        }
+        self.files.lock().insert(path, data.to_vec());
+        Ok(())
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let mut dirs = self.dirs.lock();
+        for ancestor in normalize(path).ancestors() {
+            dirs.insert(ancestor.to_path_buf());
+        }
+        Ok(())
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        let path = normalize(path);
+        if self.exists(&path) {
+            Ok(path)
+        } else {
+            Err(not_found(&path))
+        }
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        let path = normalize(path);
+        let is_file = self.files.lock().contains_key(&path);
+        is_file || self.is_dir(&path)
+    }
+
+    /// No symlinks in memory
+    fn is_symlink(&self, _path: &Path) -> bool {
+        false
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<String>> {
+        let path = normalize(path);
+        if !self.is_dir(&path) {
+            return Err(not_found(&path));
+        }
+        let children: BTreeSet<String> = self
+            .files
+            .lock()
+            .keys()
+            .chain(self.dirs.lock().iter())
+            .filter_map(|p| p.strip_prefix(&path).ok())
+            .filter_map(|rest| rest.components().next())
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+        Ok(children.into_iter().collect())
+    }
+
+    fn restrict(&self, path: &Path) {
+        self.restricted.lock().push(normalize(path));
+    }
+}
+
+/// Git that answers from canned output, keyed by subcommand.
+#[derive(Default)]
+pub struct FixtureGit {
+    responses: BTreeMap<String, Result<String, String>>,
+    calls: Mutex<Vec<Vec<String>>>,
+}
+
+impl FixtureGit {
+    pub fn respond(mut self, subcommand: &str, stdout: &str) -> Self {
+        self.responses
+            .insert(subcommand.to_string(), Ok(stdout.to_string()));
+        self
+    }
+
+    pub fn fail(mut self, subcommand: &str, stderr: &str) -> Self {
+        self.responses
+            .insert(subcommand.to_string(), Err(stderr.to_string()));
+        self
+    }
+
+    pub fn calls(&self) -> Vec<Vec<String>> {
+        self.calls.lock().clone()
+    }
+}
+
+impl GitBackend for FixtureGit {
+    fn run(&self, _repo: &Path, args: &[&str], token: &CancelToken) -> Result<String, String> {
+        token.check()?;
+        self.calls
+            .lock()
+            .push(args.iter().map(|a| a.to_string()).collect());
+        let subcommand = args.first().copied().unwrap_or_default();
+        self.responses
+            .get(subcommand)
+            .cloned()
+            .unwrap_or_else(|| Err(format!("fatal: no fixture for git {}", subcommand)))
+    }
+}
+
+/// Launcher that returns the same output for every program.
+pub struct FakeLauncher(pub ProcessOutput);
+
+impl ProcessLauncher for FakeLauncher {
+    fn run(
+        &self,
+        _program: &Path,
+        _args: &[&str],
+        _cwd: Option<&Path>,
+        _token: &CancelToken,
+    ) -> Result<ProcessOutput, String> {
+        Ok(self.0.clone())
+    }
+}
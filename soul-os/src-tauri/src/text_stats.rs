@@ -0,0 +1,230 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::backend::FileStore;
+use crate::blocking::CancelToken;
+use crate::export::files_in;
+use crate::paths::{self, Location};
+use crate::safe_path;
+use crate::vault::Vault;
+
+/// Silent reading speed used for reading time
+const WORDS_PER_MINUTE: usize = 200;
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct DocumentStats {
+    /// Relative to the soul
+    pub path: String,
+    /// "soul", "relationships", a memory category, "heartbeat", …
+    pub category: String,
+    pub words: usize,
+    pub characters: usize,
+    pub headings: usize,
+    pub reading_secs: usize,
+}
+
+/// Totals over a set of documents.
+#[derive(Debug, Clone, Default, Serialize, TS)]
+#[ts(export)]
+pub struct CategoryStats {
+    pub category: String,
+    pub documents: usize,
+    pub words: usize,
+    pub characters: usize,
+    pub headings: usize,
+    pub reading_secs: usize,
+}
+
+impl CategoryStats {
+    fn add(&mut self, doc: &DocumentStats) {
+        self.documents += 1;
+        self.words += doc.words;
+        self.characters += doc.characters;
+        self.headings += doc.headings;
+        self.reading_secs += doc.reading_secs;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SoulTextStats {
+    /// Every counted document (category "all")
+    pub total: CategoryStats,
+    /// Largest first
+    pub categories: Vec<CategoryStats>,
+    /// Encrypted documents left out while the app is locked
+    pub skipped: usize,
+}
+
+fn category(relative: &str) -> &'static str {
+    if let Some(category) = paths::memory_category(relative) {
+        return category;
+    }
+    match paths::classify(relative) {
+        Some(Location::Relationships) => "relationships",
+        Some(Location::Memories | Location::Memory(_)) => "memories",
+        Some(Location::Heartbeat) => "heartbeat",
+        Some(Location::StateLog) => "statelog",
+        Some(Location::Soul) | None => "soul",
+    }
+}
+
+/// Count words (tokens with a letter or digit, so list markers and rules
+/// don't count), characters and Markdown headings outside code fences.
+pub fn measure(path: &str, content: &str) -> DocumentStats {
+    let mut words = 0;
+    let mut headings = 0;
+    let mut fenced = false;
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            fenced = !fenced;
+            continue;
+        }
+        if !fenced {
+            let level = trimmed.chars().take_while(|c| *c == '#').count();
+            if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+                headings += 1;
+            }
+        }
+        words += line
+            .split_whitespace()
+            .filter(|word| word.chars().any(char::is_alphanumeric))
+            .count();
+    }
+    DocumentStats {
+        path: path.to_string(),
+        category: category(path).to_string(),
+        words,
+        characters: content.chars().count(),
+        headings,
+        reading_secs: (words * 60).div_ceil(WORDS_PER_MINUTE),
+    }
+}
+
+/// The soul's documents: Markdown at the top level and in the persona,
+/// memory, heartbeat and statelog directories (not tooling like
+/// node_modules).
+pub fn documents(soul_path: &Path) -> Vec<PathBuf> {
+    let mut found = BTreeSet::new();
+    found.extend(files_in(soul_path, false));
+    for location in [
+        Location::Soul,
+        Location::Memories,
+        Location::Heartbeat,
+        Location::StateLog,
+    ] {
+        for dir in paths::existing(soul_path, location) {
+            found.extend(files_in(&dir, true));
+        }
+    }
+    found
+        .into_iter()
+        .filter(|path| path.extension().is_some_and(|e| e == "md"))
+        .collect()
+}
+
+/// Fingerprint telling whether a cached entry is still current
+pub type Stamp = (Option<SystemTime>, u64);
+
+pub fn stamp(path: &Path) -> Option<Stamp> {
+    let meta = fs::metadata(path).ok()?;
+    Some((meta.modified().ok(), meta.len()))
+}
+
+/// Document statistics, recomputed only for files whose modification time
+/// or size changed since they were last measured.
+#[derive(Default)]
+pub struct TextStats {
+    cache: Mutex<HashMap<PathBuf, (Stamp, DocumentStats)>>,
+}
+
+impl TextStats {
+    fn measure_file(
+        &self,
+        vault: &Vault,
+        path: &Path,
+        relative: &str,
+    ) -> Result<DocumentStats, String> {
+        let stamp = stamp(path).ok_or_else(|| format!("No such file: {}", relative))?;
+        if let Some((cached, stats)) = self.cache.lock().get(path) {
+            if *cached == stamp && stats.path == relative {
+                return Ok(stats.clone());
+            }
+        }
+        let stats = measure(relative, &vault.read(path)?);
+        self.cache
+            .lock()
+            .insert(path.to_path_buf(), (stamp, stats.clone()));
+        Ok(stats)
+    }
+
+    /// Statistics of one soul file, for the editor status bar.
+    pub fn document(
+        &self,
+        files: &dyn FileStore,
+        vault: &Vault,
+        soul_path: &Path,
+        name: &str,
+    ) -> Result<DocumentStats, String> {
+        let path = safe_path::safe_resolve(files, soul_path, name)?;
+        self.measure_file(vault, &path, &name.replace('\\', "/"))
+    }
+
+    /// Totals over all documents and per category, for the growth
+    /// dashboard.
+    pub fn soul(
+        &self,
+        vault: &Vault,
+        soul_path: &Path,
+        token: &CancelToken,
+    ) -> Result<SoulTextStats, String> {
+        let documents = documents(soul_path);
+        let mut total = CategoryStats {
+            category: "all".to_string(),
+            ..Default::default()
+        };
+        let mut categories: BTreeMap<String, CategoryStats> = BTreeMap::new();
+        let mut skipped = 0;
+        for (done, path) in documents.iter().enumerate() {
+            token.check()?;
+            token.set_progress(done as u64, documents.len() as u64);
+            let Ok(relative) = path.strip_prefix(soul_path) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            let Ok(stats) = self.measure_file(vault, path, &relative) else {
+                skipped += 1;
+                continue;
+            };
+            total.add(&stats);
+            categories
+                .entry(stats.category.clone())
+                .or_insert_with(|| CategoryStats {
+                    category: stats.category.clone(),
+                    ..Default::default()
+                })
+                .add(&stats);
+        }
+        // Deleted documents leave the cache
+        let present: BTreeSet<&PathBuf> = documents.iter().collect();
+        self.cache
+            .lock()
+            .retain(|path, _| !path.starts_with(soul_path) || present.contains(path));
+
+        let mut categories: Vec<CategoryStats> = categories.into_values().collect();
+        categories.sort_by_key(|c| std::cmp::Reverse(c.words));
+        Ok(SoulTextStats {
+            total,
+            categories,
+            skipped,
+        })
+    }
+}
@@ -0,0 +1,256 @@
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Local, NaiveDate};
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::backend::GitBackend;
+use crate::blocking::CancelToken;
+use crate::digest::{self, JournalEntry};
+use crate::paths::{self, Location};
+
+/// One thing that happened to the soul. `ts` is Unix ms.
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+#[ts(export)]
+pub enum TimelineItem {
+    /// A state commit in the soul's git history
+    Commit {
+        #[ts(type = "number")]
+        ts: u64,
+        hash: String,
+        subject: String,
+    },
+    /// An entry of the engine's event bus (.soul-events)
+    Event {
+        #[ts(type = "number")]
+        ts: u64,
+        event_type: String,
+        data: serde_json::Value,
+    },
+    Pulse {
+        #[ts(type = "number")]
+        ts: u64,
+        activity_type: String,
+        label: String,
+    },
+    Mood {
+        #[ts(type = "number")]
+        ts: u64,
+        valence: Option<f64>,
+        energy: Option<f64>,
+        label: Option<String>,
+    },
+    /// A memory file was created
+    Memory {
+        #[ts(type = "number")]
+        ts: u64,
+        path: String,
+        category: Option<String>,
+    },
+}
+
+impl TimelineItem {
+    fn ts(&self) -> u64 {
+        match self {
+            TimelineItem::Commit { ts, .. }
+            | TimelineItem::Event { ts, .. }
+            | TimelineItem::Pulse { ts, .. }
+            | TimelineItem::Mood { ts, .. }
+            | TimelineItem::Memory { ts, .. } => *ts,
+        }
+    }
+}
+
+/// Everything that happened on one local day, oldest first.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct Timeline {
+    /// YYYY-MM-DD
+    pub date: String,
+    pub items: Vec<TimelineItem>,
+}
+
+fn millis(time: SystemTime) -> Option<u64> {
+    time.duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_millis() as u64)
+}
+
+/// Commits of the soul repo within the day.
+fn commits(
+    git: &dyn GitBackend,
+    repo: &Path,
+    since: &DateTime<Local>,
+    until: &DateTime<Local>,
+    token: &CancelToken,
+) -> Result<Vec<TimelineItem>, String> {
+    let start_ms = since.timestamp_millis() as u64;
+    let end_ms = until.timestamp_millis() as u64;
+    let output = git.run(
+        repo,
+        &[
+            "log",
+            "--format=%H|%at|%s",
+            &format!("--since={}", since.to_rfc3339()),
+            &format!("--until={}", until.to_rfc3339()),
+        ],
+        token,
+    )?;
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '|');
+            let hash = parts.next()?.to_string();
+            let ts = parts.next()?.parse::<u64>().ok()? * 1000;
+            let subject = parts.next().unwrap_or("").to_string();
+            (ts >= start_ms && ts < end_ms).then_some(TimelineItem::Commit { ts, hash, subject })
+        })
+        .collect())
+}
+
+/// Event-bus entries (`.soul-events/*.jsonl`) within the day.
+fn events(soul_path: &Path, start_ms: u64, end_ms: u64) -> Vec<TimelineItem> {
+    let Ok(entries) = fs::read_dir(soul_path.join(".soul-events")) else {
+        return Vec::new();
+    };
+    let mut items = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|e| e != "jsonl") {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        for line in content.lines() {
+            let Ok(event) = serde_json::from_str::<serde_json::Value>(line) else {
+                continue;
+            };
+            let ts = event.get("ts").and_then(|t| t.as_u64()).unwrap_or(0);
+            if ts < start_ms || ts >= end_ms {
+                continue;
+            }
+            let event_type = event
+                .get("type")
+                .and_then(|t| t.as_str())
+                .unwrap_or("unknown")
+                .to_string();
+            items.push(TimelineItem::Event {
+                ts,
+                event_type,
+                data: event,
+            });
+        }
+    }
+    items
+}
+
+/// Memory files created within the day (modification time where the
+/// filesystem keeps no creation time).
+fn memories_created(
+    soul_path: &Path,
+    start_ms: u64,
+    end_ms: u64,
+    token: &CancelToken,
+) -> Result<Vec<TimelineItem>, String> {
+    let mut items = Vec::new();
+    let mut dirs = paths::existing(soul_path, Location::Memories);
+    while let Some(dir) = dirs.pop() {
+        token.check()?;
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let Some(ts) = entry
+                .metadata()
+                .and_then(|m| m.created().or_else(|_| m.modified()))
+                .ok()
+                .and_then(millis)
+            else {
+                continue;
+            };
+            if ts < start_ms || ts >= end_ms {
+                continue;
+            }
+            if let Ok(relative) = path.strip_prefix(soul_path) {
+                let relative = relative.to_string_lossy().replace('\\', "/");
+                items.push(TimelineItem::Memory {
+                    ts,
+                    category: paths::memory_category(&relative).map(str::to_string),
+                    path: relative,
+                });
+            }
+        }
+    }
+    Ok(items)
+}
+
+/// Fuse commits, bus events, journaled pulses and moods and new memories of
+/// `date` into one ordered timeline.
+pub fn build(
+    soul_path: &Path,
+    repo: Option<&Path>,
+    git: &dyn GitBackend,
+    date: NaiveDate,
+    token: &CancelToken,
+) -> Result<Timeline, String> {
+    let (start, end) = digest::day_bounds(date)?;
+    let start_ms = start.timestamp_millis() as u64;
+    let end_ms = end.timestamp_millis() as u64;
+
+    let mut items: Vec<TimelineItem> = digest::read_journal(date)
+        .into_iter()
+        .map(|entry| match entry {
+            JournalEntry::Pulse {
+                ts,
+                activity_type,
+                label,
+            } => TimelineItem::Pulse {
+                ts,
+                activity_type,
+                label,
+            },
+            JournalEntry::Mood {
+                ts,
+                valence,
+                energy,
+                label,
+            } => TimelineItem::Mood {
+                ts,
+                valence,
+                energy,
+                label,
+            },
+        })
+        .collect();
+    token.set_progress(1, 4);
+    items.extend(events(soul_path, start_ms, end_ms));
+    token.check()?;
+    token.set_progress(2, 4);
+    items.extend(memories_created(soul_path, start_ms, end_ms, token)?);
+    token.set_progress(3, 4);
+    if let Some(repo) = repo {
+        // A soul without history still has a timeline
+        match commits(git, repo, &start, &end, token) {
+            Ok(found) => items.extend(found),
+            Err(e) => eprintln!("[timeline] git log failed: {}", e),
+        }
+    }
+    token.check()?;
+    token.set_progress(4, 4);
+
+    // Stable: same-millisecond items keep their source order
+    items.sort_by_key(TimelineItem::ts);
+    Ok(Timeline {
+        date: date.format("%Y-%m-%d").to_string(),
+        items,
+    })
+}
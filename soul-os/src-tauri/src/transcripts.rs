@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::backend::OsFileStore;
+use crate::blocking::CancelToken;
+use crate::safe_path;
+
+/// Messages per page returned by `get_transcript`
+pub const PAGE_SIZE: usize = 50;
+/// Characters of context around a search hit
+const SNIPPET_CHARS: usize = 80;
+
+/// One chat message as the engine channels store it
+/// (conversations/<channel>/<session>.json, or .jsonl with one message per line).
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TranscriptMessage {
+    pub role: String,
+    #[serde(default)]
+    pub content: serde_json::Value,
+    #[serde(default)]
+    pub timestamp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl TranscriptMessage {
+    /// Message text — content is a string for most channels, a list of parts for some.
+    fn text(&self) -> String {
+        match &self.content {
+            serde_json::Value::String(s) => s.clone(),
+            serde_json::Value::Array(parts) => parts
+                .iter()
+                .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            other => other.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct TranscriptSummary {
+    /// `<channel>/<session>` — what get_transcript takes
+    pub id: String,
+    pub channel: String,
+    pub message_count: usize,
+    #[ts(type = "number")]
+    pub size: u64,
+    #[ts(type = "number")]
+    pub modified: u64,
+    pub last_message_at: Option<String>,
+    pub preview: String,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct TranscriptPage {
+    pub id: String,
+    pub page: usize,
+    pub page_size: usize,
+    pub total_messages: usize,
+    pub total_pages: usize,
+    pub messages: Vec<TranscriptMessage>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct TranscriptHit {
+    pub id: String,
+    /// Index of the message within the transcript
+    pub index: usize,
+    pub page: usize,
+    pub role: String,
+    pub timestamp: Option<String>,
+    pub snippet: String,
+}
+
+/// Summaries are cached per file and only re-parsed when size or mtime change,
+/// so listing a long history doesn't re-read every conversation each time.
+#[derive(Default)]
+pub struct TranscriptIndex {
+    summaries: Mutex<HashMap<PathBuf, (SystemTime, u64, TranscriptSummary)>>,
+}
+
+fn conversations_dir(soul_path: &Path) -> PathBuf {
+    soul_path.join("conversations")
+}
+
+fn is_transcript(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e == "json" || e == "jsonl")
+}
+
+/// Map `<channel>/<session>` to its file, refusing anything outside the
+/// soul (`safe_path`).
+fn transcript_path(soul_path: &Path, id: &str) -> Result<PathBuf, String> {
+    let relative = safe_path::relative(id)?;
+    if relative.as_os_str().is_empty() {
+        return Err(format!("Invalid transcript id: {}", id));
+    }
+    let base = Path::new("conversations").join(relative);
+    ["json", "jsonl"]
+        .iter()
+        .filter_map(|ext| {
+            let name = format!("{}.{}", base.to_string_lossy(), ext);
+            safe_path::safe_resolve(&OsFileStore, soul_path, &name).ok()
+        })
+        .find(|p| p.is_file())
+        .ok_or_else(|| format!("Transcript not found: {}", id))
+}
+
+fn transcript_id(soul_path: &Path, path: &Path) -> Option<(String, String)> {
+    let relative = path
+        .strip_prefix(conversations_dir(soul_path))
+        .ok()?
+        .with_extension("");
+    let id = relative.to_string_lossy().replace('\\', "/");
+    let channel = id.split('/').next().unwrap_or("").to_string();
+    Some((id, channel))
+}
+
+fn read_messages(path: &Path) -> Result<Vec<TranscriptMessage>, String> {
+    if path.extension().is_some_and(|e| e == "jsonl") {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        return Ok(BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect());
+    }
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| format!("Invalid transcript: {}", e))
+}
+
+fn snippet(text: &str, byte_idx: usize, query_len: usize) -> String {
+    let start = text[..byte_idx]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CHARS / 2)
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    let end = text[byte_idx + query_len..]
+        .char_indices()
+        .nth(SNIPPET_CHARS / 2)
+        .map(|(i, _)| byte_idx + query_len + i)
+        .unwrap_or(text.len());
+    let mut out = text[start..end].replace('\n', " ");
+    if start > 0 {
+        out.insert(0, '…');
+    }
+    if end < text.len() {
+        out.push('…');
+    }
+    out
+}
+
+impl TranscriptIndex {
+    fn files(&self, soul_path: &Path, token: &CancelToken) -> Result<Vec<PathBuf>, String> {
+        let mut files = Vec::new();
+        let mut dirs = vec![conversations_dir(soul_path)];
+        while let Some(dir) = dirs.pop() {
+            token.check()?;
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else if is_transcript(&path) {
+                    files.push(path);
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    fn summary(&self, soul_path: &Path, path: &Path) -> Option<TranscriptSummary> {
+        let meta = fs::metadata(path).ok()?;
+        let modified = meta.modified().ok()?;
+        if let Some((cached_mtime, cached_len, summary)) = self.summaries.lock().get(path) {
+            if *cached_mtime == modified && *cached_len == meta.len() {
+                return Some(summary.clone());
+            }
+        }
+
+        let (id, channel) = transcript_id(soul_path, path)?;
+        let messages = read_messages(path).ok()?;
+        let last = messages.last();
+        let summary = TranscriptSummary {
+            id,
+            channel,
+            message_count: messages.len(),
+            size: meta.len(),
+            modified: modified
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            last_message_at: last.and_then(|m| m.timestamp.clone()),
+            preview: last
+                .map(|m| m.text().chars().take(SNIPPET_CHARS).collect())
+                .unwrap_or_default(),
+        };
+        self.summaries
+            .lock()
+            .insert(path.to_path_buf(), (modified, meta.len(), summary.clone()));
+        Some(summary)
+    }
+
+    /// All transcripts, most recently modified first.
+    pub fn list(
+        &self,
+        soul_path: &Path,
+        token: &CancelToken,
+    ) -> Result<Vec<TranscriptSummary>, String> {
+        let files = self.files(soul_path, token)?;
+        let total = files.len() as u64;
+        let mut summaries = Vec::new();
+        for (i, path) in files.iter().enumerate() {
+            token.check()?;
+            token.set_progress(i as u64, total);
+            if let Some(summary) = self.summary(soul_path, path) {
+                summaries.push(summary);
+            }
+        }
+        summaries.sort_by_key(|s| std::cmp::Reverse(s.modified));
+        Ok(summaries)
+    }
+
+    /// Case-insensitive full-text search across all transcripts.
+    pub fn search(
+        &self,
+        soul_path: &Path,
+        query: &str,
+        limit: usize,
+        token: &CancelToken,
+    ) -> Result<Vec<TranscriptHit>, String> {
+        let needle = query.trim().to_lowercase();
+        if needle.is_empty() {
+            return Ok(Vec::new());
+        }
+        let files = self.files(soul_path, token)?;
+        let total = files.len() as u64;
+        let mut hits = Vec::new();
+        for (i, path) in files.iter().enumerate() {
+            token.check()?;
+            token.set_progress(i as u64, total);
+            let Some((id, _)) = transcript_id(soul_path, path) else {
+                continue;
+            };
+            let Ok(messages) = read_messages(path) else {
+                continue;
+            };
+            for (index, message) in messages.iter().enumerate() {
+                let text = message.text();
+                // Lowercasing can change byte lengths; only snippet when offsets line up
+                let lower = text.to_lowercase();
+                let Some(pos) = lower.find(&needle) else {
+                    continue;
+                };
+                let aligned = lower.len() == text.len()
+                    && text.is_char_boundary(pos)
+                    && text.is_char_boundary(pos + needle.len());
+                let snippet = if aligned {
+                    snippet(&text, pos, needle.len())
+                } else {
+                    text.chars().take(SNIPPET_CHARS).collect()
+                };
+                hits.push(TranscriptHit {
+                    id: id.clone(),
+                    index,
+                    page: index / PAGE_SIZE,
+                    role: message.role.clone(),
+                    timestamp: message.timestamp.clone(),
+                    snippet,
+                });
+                if hits.len() >= limit {
+                    return Ok(hits);
+                }
+            }
+        }
+        Ok(hits)
+    }
+}
+
+/// One page of a transcript (page 0 holds the oldest messages).
+pub fn get_page(soul_path: &Path, id: &str, page: usize) -> Result<TranscriptPage, String> {
+    let path = transcript_path(soul_path, id)?;
+    let messages = read_messages(&path)?;
+    let total_messages = messages.len();
+    Ok(TranscriptPage {
+        id: id.to_string(),
+        page,
+        page_size: PAGE_SIZE,
+        total_messages,
+        total_pages: total_messages.div_ceil(PAGE_SIZE),
+        messages: messages
+            .into_iter()
+            .skip(page.saturating_mul(PAGE_SIZE))
+            .take(PAGE_SIZE)
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_transcripts_only_inside_the_soul() {
+        let dir = std::env::temp_dir().join(format!("soulos-transcripts-{}", std::process::id()));
+        let soul = dir.join("soul");
+        fs::create_dir_all(soul.join("conversations/chat")).unwrap();
+        fs::create_dir_all(dir.join("outside")).unwrap();
+        fs::write(soul.join("conversations/chat/a.v2.jsonl"), "").unwrap();
+        fs::write(dir.join("outside/x.json"), "[]").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.join("outside"), soul.join("conversations/out")).unwrap();
+
+        let found = transcript_path(&soul, "chat\\a.v2").unwrap();
+        assert!(
+            found.ends_with("conversations/chat/a.v2.jsonl"),
+            "{:?}",
+            found
+        );
+        for id in [
+            "",
+            "..\\..\\outside\\x",
+            "../../outside/x",
+            "/outside/x",
+            "C:\\x",
+            "out/x",
+        ] {
+            assert!(transcript_path(&soul, id).is_err(), "{}", id);
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct SoulStatus {
     pub name: String,
     pub born: String,
@@ -8,34 +10,72 @@ pub struct SoulStatus {
     pub model: String,
     pub state: String,
     pub mood: String,
+    #[ts(type = "number")]
     pub seed_size: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct SoulPulse {
     pub activity_type: String,
     pub label: String,
+    #[ts(type = "number")]
     pub timestamp: u64,
+    /// Pulses merged or rate-limited away since the previous emitted pulse
+    pub suppressed: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct SoulActivity {
     pub node: String,
     pub file: String,
     pub event_type: String,
+    /// 0..1 — how strongly this change lit the node
+    pub intensity: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct SoulMood {
     pub valence: Option<f64>,
     pub energy: Option<f64>,
     pub label: Option<String>,
+    /// Estimated from activity because the engine wrote no .soul-mood
+    #[serde(default)]
+    pub inferred: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct GitCommit {
     pub hash: String,
     pub date: String,
     pub message: String,
     pub files_changed: u32,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SubsystemHealth {
+    pub name: String,
+    pub status: String, // "ok", "degraded", "down"
+    pub detail: Option<String>,
+}
+
+impl SubsystemHealth {
+    pub fn new(name: &str, status: &str, detail: Option<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: status.to_string(),
+            detail,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BackendHealth {
+    pub healthy: bool,
+    pub subsystems: Vec<SubsystemHealth>,
+}
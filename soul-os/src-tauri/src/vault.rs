@@ -0,0 +1,636 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::blocking::CancelToken;
+use crate::safe_path;
+
+/// Marks an encrypted file: MAGIC + 24-byte nonce + ciphertext
+const MAGIC: &[u8] = b"SOULENC1";
+const NONCE_LEN: usize = 24;
+/// Suffix of the temp file a vault write goes through
+const TMP_SUFFIX: &str = ".vault-tmp";
+
+/// Which soul subdirectories are encrypted at rest, persisted in the app config.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EncryptionConfig {
+    /// Directories relative to soul_path, e.g. "seele/beziehungen"
+    #[serde(default)]
+    pub directories: Vec<String>,
+    /// Hex salt for deriving the file key from the lock passphrase
+    #[serde(default)]
+    pub salt: Option<String>,
+    /// Salt of a passphrase change in progress; `salt` stays valid until
+    /// every file was rewritten and the change is saved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_salt: Option<String>,
+    /// Key of that change, sealed with the current key, so an interrupted
+    /// change can be rolled back after unlocking with the old passphrase
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct EncryptionStatus {
+    pub directories: Vec<String>,
+    /// Whether the file key is available (derived on unlock)
+    pub unlocked: bool,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, TS)]
+#[ts(export)]
+pub struct MigrationReport {
+    pub encrypted: Vec<String>,
+    pub decrypted: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+/// Transparent encrypt-on-write / decrypt-on-read for the file commands.
+/// The key only lives in memory and is derived from the lock passphrase.
+#[derive(Default)]
+pub struct Vault {
+    key: RwLock<Option<[u8; 32]>>,
+    /// Key of a passphrase change in progress, see `stage_rekey`
+    pending: RwLock<Option<[u8; 32]>>,
+    config: RwLock<EncryptionConfig>,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| s.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+        .collect()
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| e.to_string())?;
+    Ok(key)
+}
+
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+fn encrypt(key: &[u8; 32], plain: &[u8]) -> Result<Vec<u8>, String> {
+    let cipher = XChaCha20Poly1305::new(key.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher.encrypt(&nonce, plain).map_err(|e| e.to_string())?;
+    let mut out = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+fn decrypt(key: &[u8; 32], data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < MAGIC.len() + NONCE_LEN {
+        return Err("Encrypted file is truncated".to_string());
+    }
+    let (nonce, ciphertext) = data[MAGIC.len()..].split_at(NONCE_LEN);
+    XChaCha20Poly1305::new(key.into())
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Decryption failed (wrong key or corrupted file)".to_string())
+}
+
+/// Replace a file via a sibling temp file, so a crash mid-write leaves
+/// either the old or the new content — never a truncated file.
+fn write_atomic(path: &Path, data: &[u8]) -> Result<(), String> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(TMP_SUFFIX);
+    fs::write(&tmp, data).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+/// Delete temp files left behind by an interrupted migration. Returns how many.
+pub fn remove_temp_files(soul_path: &Path) -> usize {
+    let mut removed = 0;
+    let mut dirs = vec![soul_path.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name();
+            if path.is_dir() {
+                if name != ".git" && name != "node_modules" {
+                    dirs.push(path);
+                }
+            } else if name.to_string_lossy().ends_with(TMP_SUFFIX) && fs::remove_file(&path).is_ok()
+            {
+                removed += 1;
+            }
+        }
+    }
+    removed
+}
+
+fn files_under(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                let name = entry.file_name();
+                if name != ".git" && name != "node_modules" {
+                    dirs.push(path);
+                }
+            } else if !entry.file_name().to_string_lossy().ends_with(TMP_SUFFIX) {
+                files.push(path);
+            }
+        }
+    }
+    files
+}
+
+/// Encrypted files under `dir` with their content.
+fn encrypted_files(dir: &Path) -> impl Iterator<Item = (PathBuf, Vec<u8>)> {
+    files_under(dir).into_iter().filter_map(|path| {
+        let data = fs::read(&path).ok()?;
+        is_encrypted(&data).then_some((path, data))
+    })
+}
+
+/// Key of a staged passphrase change, sealed with the current key.
+fn unseal_key(key: &[u8; 32], sealed: &[u8]) -> Result<[u8; 32], String> {
+    decrypt(key, sealed)?
+        .try_into()
+        .map_err(|_| "Staged encryption key is corrupted".to_string())
+}
+
+impl Vault {
+    pub fn new(config: EncryptionConfig) -> Self {
+        Self {
+            key: RwLock::new(None),
+            pending: RwLock::new(None),
+            config: RwLock::new(config),
+        }
+    }
+
+    pub fn config(&self) -> EncryptionConfig {
+        self.config.read().clone()
+    }
+
+    /// Derive the file key from the lock passphrase. Creates the salt on first
+    /// use; returns true when the config changed and needs saving.
+    pub fn unlock(&self, passphrase: &str) -> Result<bool, String> {
+        let mut config = self.config.write();
+        let (salt, created) = match config.salt.as_deref().and_then(from_hex) {
+            Some(salt) => (salt, false),
+            None => {
+                let mut salt = vec![0u8; 16];
+                OsRng.fill_bytes(&mut salt);
+                config.salt = Some(to_hex(&salt));
+                (salt, true)
+            }
+        };
+        let key = derive_key(passphrase, &salt)?;
+        *self.pending.write() = match config.pending_key.as_deref().and_then(from_hex) {
+            Some(sealed) => Some(unseal_key(&key, &sealed)?),
+            None => None,
+        };
+        *self.key.write() = Some(key);
+        Ok(created)
+    }
+
+    /// Drop the key (the app locked); encrypted files stay unreadable until
+    /// the next `unlock`.
+    pub fn forget(&self) {
+        *self.key.write() = None;
+        *self.pending.write() = None;
+    }
+
+    /// Whether `relative` lies in an encrypted directory.
+    pub fn covers(&self, relative: &str) -> bool {
+        let relative = relative.replace('\\', "/");
+        self.config.read().directories.iter().any(|dir| {
+            let dir = dir.trim_end_matches('/');
+            relative == dir || relative.starts_with(&format!("{}/", dir))
+        })
+    }
+
+    /// Directories are checked by `safe_path::relative` and kept with `/`
+    /// separators.
+    pub fn set_directories(&self, directories: Vec<String>) -> Result<(), String> {
+        let directories = directories
+            .iter()
+            .map(|dir| {
+                let relative = safe_path::relative(dir)?;
+                let parts: Vec<_> = relative.iter().map(|p| p.to_string_lossy()).collect();
+                if parts.is_empty() {
+                    return Err(format!("Invalid directory: {}", dir));
+                }
+                Ok(parts.join("/"))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        if !directories.is_empty() && self.key.read().is_none() {
+            return Err("Set a lock passphrase before enabling encryption".to_string());
+        }
+        self.config.write().directories = directories;
+        Ok(())
+    }
+
+    /// Rename the encrypted directories after they moved on disk; the
+    /// files stay encrypted, so no key is needed.
+    pub fn rename_directories(&self, rename: impl Fn(&str) -> String) {
+        let mut config = self.config.write();
+        config.directories = config.directories.iter().map(|d| rename(d)).collect();
+    }
+
+    /// Read a soul file, decrypting it if it carries the encryption header.
+    pub fn read(&self, path: &Path) -> Result<String, String> {
+        self.decode(fs::read(path).map_err(|e| e.to_string())?)
+    }
+
+    /// File bytes as text, decrypting them if they carry the vault header.
+    pub fn decode(&self, data: Vec<u8>) -> Result<String, String> {
+        String::from_utf8(self.decode_bytes(data)?).map_err(|e| e.to_string())
+    }
+
+    /// `decode` for binary files (media).
+    pub fn decode_bytes(&self, data: Vec<u8>) -> Result<Vec<u8>, String> {
+        if !is_encrypted(&data) {
+            return Ok(data);
+        }
+        let key = (*self.key.read())
+            .ok_or_else(|| "File is encrypted — unlock the app first".to_string())?;
+        // Mid passphrase change a file may already be under the new key
+        decrypt(&key, &data).or_else(|e| match *self.pending.read() {
+            Some(pending) => decrypt(&pending, &data),
+            None => Err(e),
+        })
+    }
+
+    /// Bytes to write for `relative`: ciphertext inside encrypted directories.
+    pub fn seal(&self, relative: &str, content: &str) -> Result<Vec<u8>, String> {
+        if !self.covers(relative) {
+            return Ok(content.as_bytes().to_vec());
+        }
+        let key = (*self.key.read())
+            .ok_or_else(|| "Encryption key unavailable — unlock the app first".to_string())?;
+        encrypt(&key, content.as_bytes())
+    }
+
+    /// Bring the files on disk in line with the config: encrypt plaintext
+    /// inside encrypted directories, decrypt files that are no longer covered.
+    pub fn migrate(
+        &self,
+        soul_path: &Path,
+        token: &CancelToken,
+    ) -> Result<MigrationReport, String> {
+        let key = (*self.key.read())
+            .ok_or_else(|| "Encryption key unavailable — unlock the app first".to_string())?;
+        let mut report = MigrationReport::default();
+        let files = files_under(soul_path);
+        let total = files.len() as u64;
+        for (done, path) in files.into_iter().enumerate() {
+            token.check()?;
+            token.set_progress(done as u64, total);
+            let Ok(relative) = path.strip_prefix(soul_path) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            let Ok(data) = fs::read(&path) else {
+                continue;
+            };
+            let (covered, encrypted) = (self.covers(&relative), is_encrypted(&data));
+            let result = match (covered, encrypted) {
+                (true, false) => encrypt(&key, &data).map(|d| (d, &mut report.encrypted)),
+                (false, true) => decrypt(&key, &data).map(|d| (d, &mut report.decrypted)),
+                _ => continue,
+            };
+            match result.and_then(|(d, list)| {
+                write_atomic(&path, &d)?;
+                list.push(relative.clone());
+                Ok(())
+            }) {
+                Ok(()) => {}
+                Err(e) => report.failed.push(format!("{}: {}", relative, e)),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Start a passphrase change: derive the new key and stage it next to
+    /// the current one. The config must be saved before `rekey_files`, so
+    /// a crash never leaves files under a key whose salt was not stored.
+    pub fn stage_rekey(&self, soul_path: &Path, passphrase: &str) -> Result<(), String> {
+        let mut salt = vec![0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        let new = derive_key(passphrase, &salt)?;
+        let mut config = self.config.write();
+        match *self.key.read() {
+            Some(old) => config.pending_key = Some(to_hex(&encrypt(&old, &new)?)),
+            None if encrypted_files(soul_path).next().is_some() => {
+                return Err("Unlock the app before changing the passphrase".to_string());
+            }
+            // Nothing is encrypted yet, so nothing needs the old key
+            None => config.pending_key = None,
+        }
+        config.pending_salt = Some(to_hex(&salt));
+        *self.pending.write() = Some(new);
+        Ok(())
+    }
+
+    /// Re-encrypt every file still under the current key with the staged
+    /// one. Files already moved are skipped, so this can run again.
+    pub fn rekey_files(&self, soul_path: &Path) -> Result<usize, String> {
+        let (old, new) = self.keys()?;
+        let mut rewritten = 0;
+        for (path, data) in encrypted_files(soul_path) {
+            let Some(old) = old else {
+                return Err(format!("{}: encrypted, but no key is set", path.display()));
+            };
+            match decrypt(&old, &data) {
+                Ok(plain) => write_atomic(&path, &encrypt(&new, &plain)?)?,
+                Err(_) if decrypt(&new, &data).is_ok() => continue,
+                Err(e) => return Err(format!("{}: {}", path.display(), e)),
+            }
+            rewritten += 1;
+        }
+        Ok(rewritten)
+    }
+
+    /// The config once the staged change is complete: the new salt, with
+    /// the old one dropped.
+    pub fn committed_config(&self) -> EncryptionConfig {
+        let mut config = self.config.read().clone();
+        config.salt = config.pending_salt.take().or(config.salt);
+        config.pending_key = None;
+        config
+    }
+
+    /// Make the staged key current, after `committed_config` was saved.
+    pub fn commit_rekey(&self) {
+        let committed = self.committed_config();
+        *self.config.write() = committed;
+        if let Some(new) = self.pending.write().take() {
+            *self.key.write() = Some(new);
+        }
+    }
+
+    /// Undo a staged change: files already under the new key go back to
+    /// the current one and the staged key is dropped. Save the config after.
+    pub fn rollback_rekey(&self, soul_path: &Path) -> Result<usize, String> {
+        let mut restored = 0;
+        if self.config.read().pending_salt.is_some() {
+            let (old, new) = self.keys()?;
+            if let Some(old) = old {
+                for (path, data) in encrypted_files(soul_path) {
+                    if let Ok(plain) = decrypt(&new, &data) {
+                        write_atomic(&path, &encrypt(&old, &plain)?)?;
+                        restored += 1;
+                    }
+                }
+            }
+        }
+        let mut config = self.config.write();
+        config.pending_salt = None;
+        config.pending_key = None;
+        *self.pending.write() = None;
+        Ok(restored)
+    }
+
+    /// Current and staged key of a passphrase change.
+    fn keys(&self) -> Result<(Option<[u8; 32]>, [u8; 32]), String> {
+        let new = (*self.pending.read())
+            .ok_or_else(|| "Encryption key unavailable — unlock the app first".to_string())?;
+        Ok((*self.key.read(), new))
+    }
+
+    pub fn status(&self) -> EncryptionStatus {
+        let directories = self.config.read().directories.clone();
+        let warnings = directories
+            .iter()
+            .map(|dir| {
+                format!(
+                    "The engine reads {} directly and only sees ciphertext: memory search, \
+                     indexing, state diffs and write policies won't work for these files",
+                    dir
+                )
+            })
+            .collect();
+        EncryptionStatus {
+            directories,
+            unlocked: self.key.read().is_some(),
+            warnings,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A soul with plaintext in `private/` and `public/`, removed when
+    /// dropped
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("soulos-vault-{}-{}", std::process::id(), name));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(dir.join("private/deep")).unwrap();
+            fs::create_dir_all(dir.join("public")).unwrap();
+            fs::write(dir.join("private/a.md"), "a").unwrap();
+            fs::write(dir.join("private/deep/b.md"), "b").unwrap();
+            fs::write(dir.join("public/c.md"), "c").unwrap();
+            Self(dir)
+        }
+
+        fn raw(&self, name: &str) -> Vec<u8> {
+            fs::read(self.0.join(name)).unwrap()
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// A vault unlocked with `passphrase`, encrypting `private/`
+    fn unlocked(config: EncryptionConfig, passphrase: &str) -> Vault {
+        let vault = Vault::new(config);
+        vault.unlock(passphrase).unwrap();
+        vault.set_directories(vec!["private".to_string()]).unwrap();
+        vault
+    }
+
+    /// `migrate` `soul` into `private/` under `vault`'s key
+    fn encrypted(soul: &Scratch, vault: &Vault) {
+        let report = vault.migrate(&soul.0, &CancelToken::default()).unwrap();
+        assert_eq!(report.encrypted.len(), 2, "{:?}", report);
+    }
+
+    fn read(vault: &Vault, soul: &Scratch, name: &str) -> Result<String, String> {
+        vault.read(&soul.0.join(name))
+    }
+
+    #[test]
+    fn seals_only_encrypted_directories_and_reads_them_back() {
+        let vault = unlocked(EncryptionConfig::default(), "secret");
+        let sealed = vault.seal("private/x.md", "hidden").unwrap();
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed.windows(6).any(|w| w == b"hidden"));
+        assert_eq!(vault.decode(sealed).unwrap(), "hidden");
+        assert_eq!(vault.seal("public/x.md", "open").unwrap(), b"open");
+        assert_eq!(
+            vault.seal("private\\x.md", "x").map(|d| is_encrypted(&d)),
+            Ok(true)
+        );
+        // Plaintext passes through
+        assert_eq!(vault.decode(b"plain".to_vec()).unwrap(), "plain");
+    }
+
+    #[test]
+    fn refuses_wrong_keys_and_damaged_files() {
+        let vault = unlocked(EncryptionConfig::default(), "secret");
+        let sealed = vault.seal("private/x.md", "hidden").unwrap();
+
+        let other = Vault::new(vault.config());
+        other.unlock("wrong").unwrap();
+        assert!(other
+            .decode(sealed.clone())
+            .unwrap_err()
+            .contains("wrong key"));
+
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(vault.decode(tampered).is_err());
+        assert!(vault
+            .decode(sealed[..MAGIC.len() + 4].to_vec())
+            .unwrap_err()
+            .contains("truncated"));
+
+        vault.forget();
+        assert!(vault.decode(sealed).unwrap_err().contains("unlock"));
+        assert!(vault
+            .seal("private/x.md", "x")
+            .unwrap_err()
+            .contains("unlock"));
+    }
+
+    #[test]
+    fn checks_directory_names() {
+        let vault = unlocked(EncryptionConfig::default(), "secret");
+        for dir in ["", ".", "../outside", "/abs", "C:\\x", "a/../../b"] {
+            assert!(
+                vault.set_directories(vec![dir.to_string()]).is_err(),
+                "{}",
+                dir
+            );
+        }
+        vault
+            .set_directories(vec!["notes..old".to_string(), ".\\a\\b\\".to_string()])
+            .unwrap();
+        assert_eq!(vault.config().directories, ["notes..old", "a/b"]);
+        assert!(Vault::default()
+            .set_directories(vec!["a".to_string()])
+            .is_err());
+    }
+
+    #[test]
+    fn migrate_encrypts_and_decrypts_to_match_the_config() {
+        let soul = Scratch::new("migrate");
+        let vault = unlocked(EncryptionConfig::default(), "secret");
+        encrypted(&soul, &vault);
+        assert!(is_encrypted(&soul.raw("private/deep/b.md")));
+        assert_eq!(soul.raw("public/c.md"), b"c");
+        assert_eq!(read(&vault, &soul, "private/a.md").unwrap(), "a");
+
+        vault.set_directories(Vec::new()).unwrap();
+        let report = vault.migrate(&soul.0, &CancelToken::default()).unwrap();
+        assert_eq!(report.decrypted.len(), 2);
+        assert_eq!(soul.raw("private/a.md"), b"a");
+    }
+
+    #[test]
+    fn reads_every_file_in_the_middle_of_a_rekey() {
+        let soul = Scratch::new("mid-rekey");
+        let vault = unlocked(EncryptionConfig::default(), "old");
+        encrypted(&soul, &vault);
+        vault.stage_rekey(&soul.0, "new").unwrap();
+        // Interrupted after one file
+        let path = soul.0.join("private/a.md");
+        let new = vault.pending.read().unwrap();
+        write_atomic(&path, &encrypt(&new, b"a").unwrap()).unwrap();
+
+        assert_eq!(read(&vault, &soul, "private/a.md").unwrap(), "a");
+        assert_eq!(read(&vault, &soul, "private/deep/b.md").unwrap(), "b");
+        // After a restart too: the staged key comes back from the config
+        let restarted = Vault::new(vault.config());
+        restarted.unlock("old").unwrap();
+        assert_eq!(read(&restarted, &soul, "private/a.md").unwrap(), "a");
+        assert_eq!(read(&restarted, &soul, "private/deep/b.md").unwrap(), "b");
+        // Rekeying again skips the file already moved
+        assert_eq!(restarted.rekey_files(&soul.0).unwrap(), 1);
+    }
+
+    #[test]
+    fn commits_a_rekey() {
+        let soul = Scratch::new("commit");
+        let vault = unlocked(EncryptionConfig::default(), "old");
+        encrypted(&soul, &vault);
+        vault.stage_rekey(&soul.0, "new").unwrap();
+        assert_eq!(vault.rekey_files(&soul.0).unwrap(), 2);
+        let committed = vault.committed_config();
+        assert_eq!(committed.salt, vault.config().pending_salt);
+        assert!(committed.pending_salt.is_none() && committed.pending_key.is_none());
+        vault.commit_rekey();
+        assert_eq!(read(&vault, &soul, "private/a.md").unwrap(), "a");
+
+        let restarted = Vault::new(committed.clone());
+        restarted.unlock("new").unwrap();
+        assert_eq!(read(&restarted, &soul, "private/deep/b.md").unwrap(), "b");
+        let old = Vault::new(committed);
+        old.unlock("old").unwrap();
+        assert!(read(&old, &soul, "private/a.md").is_err());
+    }
+
+    #[test]
+    fn rolls_back_an_interrupted_rekey() {
+        let soul = Scratch::new("rollback");
+        let vault = unlocked(EncryptionConfig::default(), "old");
+        encrypted(&soul, &vault);
+        let before = vault.config();
+        vault.stage_rekey(&soul.0, "new").unwrap();
+        assert_eq!(vault.rekey_files(&soul.0).unwrap(), 2);
+
+        // Unlocked with the old passphrase after a crash
+        let restarted = Vault::new(vault.config());
+        restarted.unlock("old").unwrap();
+        assert_eq!(restarted.rollback_rekey(&soul.0).unwrap(), 2);
+        let config = restarted.config();
+        assert_eq!(config.salt, before.salt);
+        assert!(config.pending_salt.is_none() && config.pending_key.is_none());
+
+        // Only the old key is needed again
+        let old = Vault::new(config);
+        old.unlock("old").unwrap();
+        assert!(old.pending.read().is_none());
+        assert_eq!(read(&old, &soul, "private/a.md").unwrap(), "a");
+        assert_eq!(read(&old, &soul, "private/deep/b.md").unwrap(), "b");
+    }
+}
@@ -0,0 +1,159 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::paths::{self, Location};
+
+/// Entries kept in `Vitals::recent`, enough for one EKG sweep
+const RECENT: usize = 24;
+/// Day logs read; the previous day bridges the interval across midnight
+const DAYS: usize = 2;
+
+/// One `## HH:MM — <pulse type>` section of a heartbeat day log.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct HeartbeatEntry {
+    /// Local time, YYYY-MM-DDTHH:MM:SS
+    pub timestamp: String,
+    pub kind: String,
+    /// HEARTBEAT_OK, UPDATED, WRITTEN, … (German codes as written)
+    pub result: Option<String>,
+    pub detail: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct Vitals {
+    /// Timestamp of the latest beat
+    pub last_beat: String,
+    #[ts(type = "number | null")]
+    pub since_last_secs: Option<u64>,
+    /// Beats logged today
+    pub cycles: usize,
+    /// Seconds between the two latest beats: the engine's loop latency
+    #[ts(type = "number | null")]
+    pub interval_secs: Option<u64>,
+    /// Seconds between consecutive recent beats, oldest first
+    #[ts(type = "number[]")]
+    pub intervals: Vec<u64>,
+    /// Beats today that logged an error
+    pub errors: usize,
+    /// Latest entries, oldest first
+    pub recent: Vec<HeartbeatEntry>,
+}
+
+/// Day logs are heartbeat/YYYY-MM-DD.md; digests and other notes aren't.
+fn log_date(file_name: &str) -> Option<NaiveDate> {
+    let stem = file_name.strip_suffix(".md")?;
+    NaiveDate::parse_from_str(stem, "%Y-%m-%d").ok()
+}
+
+/// Whether a path relative to the soul is a heartbeat day log.
+pub fn is_log(relative: &str) -> bool {
+    paths::classify(relative) == Some(Location::Heartbeat)
+        && relative.rsplit('/').next().and_then(log_date).is_some()
+}
+
+fn field<'a>(line: &'a str, names: &[&str]) -> Option<&'a str> {
+    let line = line.trim().trim_start_matches(['-', '*']).trim_start();
+    names.iter().find_map(|name| {
+        let rest = line.get(..name.len())?;
+        if !rest.eq_ignore_ascii_case(name) {
+            return None;
+        }
+        line[name.len()..].strip_prefix(':').map(str::trim)
+    })
+}
+
+/// The entries of one day log, in file order.
+fn parse(date: NaiveDate, content: &str) -> Vec<(NaiveDateTime, HeartbeatEntry)> {
+    let mut entries: Vec<(NaiveDateTime, HeartbeatEntry)> = Vec::new();
+    for line in content.lines() {
+        if let Some(header) = line.strip_prefix("## ") {
+            let (time, kind) = header
+                .split_once(" — ")
+                .or_else(|| header.split_once(" - "))
+                .unwrap_or((header, ""));
+            let time = time.trim();
+            let Some(time) = NaiveTime::parse_from_str(time, "%H:%M:%S")
+                .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M"))
+                .ok()
+            else {
+                continue;
+            };
+            let at = date.and_time(time);
+            let kind = kind.trim().to_string();
+            let lower = kind.to_lowercase();
+            let error = (lower.contains("error") || lower.contains("fehler")).then(|| kind.clone());
+            entries.push((
+                at,
+                HeartbeatEntry {
+                    timestamp: at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                    kind,
+                    result: None,
+                    detail: None,
+                    error,
+                },
+            ));
+            continue;
+        }
+        let Some((_, entry)) = entries.last_mut() else {
+            continue;
+        };
+        if let Some(result) = field(line, &["Result", "Ergebnis"]) {
+            entry.result = Some(result.to_string());
+        } else if let Some(detail) = field(line, &["Detail"]) {
+            entry.detail = Some(detail.to_string());
+        } else if let Some(error) = field(line, &["Error", "Fehler"]) {
+            entry.error = Some(error.to_string());
+        }
+    }
+    entries
+}
+
+/// Vital signs from the latest heartbeat day logs. None if the soul has no
+/// heartbeat yet.
+pub fn read(soul_path: &Path) -> Option<Vitals> {
+    let dir = paths::resolve(soul_path, Location::Heartbeat);
+    let mut days: Vec<(NaiveDate, PathBuf)> = fs::read_dir(&dir)
+        .ok()?
+        .flatten()
+        .filter_map(|e| Some((log_date(&e.file_name().to_string_lossy())?, e.path())))
+        .collect();
+    days.sort_by_key(|(date, _)| *date);
+    let days = &days[days.len().saturating_sub(DAYS)..];
+
+    let mut entries = Vec::new();
+    for (date, path) in days {
+        entries.extend(parse(*date, &fs::read_to_string(path).unwrap_or_default()));
+    }
+    // Hand-written logs aren't always in order
+    entries.sort_by_key(|(at, _)| *at);
+    let (last, _) = entries.last()?;
+    let last = *last;
+
+    let today = Local::now().date_naive();
+    let todays = entries.iter().filter(|(at, _)| at.date() == today);
+    let recent = &entries[entries.len().saturating_sub(RECENT)..];
+    let intervals: Vec<u64> = recent
+        .windows(2)
+        .map(|w| (w[1].0 - w[0].0).num_seconds().max(0) as u64)
+        .collect();
+
+    Some(Vitals {
+        last_beat: last.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        since_last_secs: (Local::now().naive_local() - last)
+            .num_seconds()
+            .try_into()
+            .ok(),
+        cycles: todays.clone().count(),
+        interval_secs: intervals.last().copied(),
+        errors: todays.filter(|(_, e)| e.error.is_some()).count(),
+        intervals,
+        recent: recent.iter().map(|(_, e)| e.clone()).collect(),
+    })
+}
@@ -1,22 +1,316 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::fs;
-use std::path::Path;
-use std::sync::{Arc, Mutex};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use notify::event::{ModifyKind, RemoveKind};
+use notify::{
+    Config, Event, EventHandler, EventKind, PollWatcher, RecommendedWatcher, RecursiveMode,
+    Watcher,
+};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Emitter, Manager};
+use ts_rs::TS;
 
+use crate::ambient;
+use crate::background;
+use crate::config::AppConfig;
+use crate::digest;
+use crate::env_watch;
+use crate::metrics::Metrics;
+use crate::paths::{self, Location};
+use crate::pins;
+use crate::bench::{PipelineBench, BENCH_PREFIX};
+use crate::policy::{PolicyEngine, POLICY_FILE};
+use crate::recent;
+use crate::review::ReviewQueue;
+use crate::vault::Vault;
+use crate::vitals;
+use crate::status::StatusCache;
+use crate::tags;
 use crate::types::{SoulActivity, SoulMood, SoulPulse};
 
-// Decay timing (matches soul-monitor)
+// Default decay timing (matches soul-monitor)
 const BRIGHT_MS: u64 = 6000;
 const AFTERGLOW_MS: u64 = 15000;
-const TOTAL_DECAY_MS: u64 = BRIGHT_MS + AFTERGLOW_MS;
 const WORKING_TIMEOUT_MS: u64 = 20000;
 
+// Intensity weighting: events per node are counted within this window
+const INTENSITY_WINDOW_MS: u64 = 2000;
+/// A change of this many bytes (or more) lights a node at full intensity
+const FULL_INTENSITY_BYTES: f64 = 16384.0;
+/// This many events within the window also count as full intensity
+const FULL_INTENSITY_EVENTS: f64 = 6.0;
+/// Floor so even a timestamp touch glows faintly
+const MIN_INTENSITY: f64 = 0.2;
+
+/// A directory watched in addition to soul_path (e.g. media/ on an external drive).
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct WatchRoot {
+    /// Label used as the file prefix in `soul:activity` events
+    pub name: String,
+    pub path: PathBuf,
+    /// Path patterns → node IDs. Patterns ending in "/" match a directory
+    /// anywhere in the relative path, others match the file name suffix.
+    #[serde(default)]
+    pub nodes: Vec<NodeMapping>,
+    /// Node for files no mapping matches (None = ignore them)
+    #[serde(default)]
+    pub default_node: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct NodeMapping {
+    pub pattern: String,
+    pub node: String,
+}
+
+impl WatchRoot {
+    fn resolve_node(&self, relative_path: &str) -> Option<String> {
+        for mapping in &self.nodes {
+            let matched = if mapping.pattern.ends_with('/') {
+                relative_path.starts_with(&mapping.pattern)
+                    || relative_path.contains(&format!("/{}", mapping.pattern))
+            } else {
+                relative_path.ends_with(&mapping.pattern)
+            };
+            if matched {
+                return Some(mapping.node.clone());
+            }
+        }
+        self.default_node.clone()
+    }
+}
+
+/// A running watcher: native events (FSEvents/inotify) or polling for
+/// network volumes where native events don't arrive.
+/// Only held so the watcher stays alive.
+#[allow(dead_code)]
+pub enum WatcherBackend {
+    Native(RecommendedWatcher),
+    Poll(PollWatcher),
+}
+
+/// Which backend watches a root, as reported by `get_watcher_info()`.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct WatchedRootInfo {
+    pub name: String,
+    pub path: String,
+    pub backend: String, // "native" or "poll"
+    pub filesystem: Option<String>,
+    #[ts(type = "number | null")]
+    pub poll_interval_ms: Option<u64>,
+}
+
+/// Owns the live notify watchers. Extra roots can be swapped at runtime.
+pub struct WatcherHandles {
+    main: Mutex<(WatcherBackend, WatchedRootInfo)>,
+    extra: Mutex<Vec<(WatcherBackend, WatchedRootInfo)>>,
+}
+
+impl WatcherHandles {
+    pub fn replace_extra(&self, watchers: Vec<(WatcherBackend, WatchedRootInfo)>) {
+        *self.extra.lock() = watchers;
+    }
+
+    /// Swap the soul watcher; the old one stops when dropped.
+    pub fn replace_main(&self, watcher: (WatcherBackend, WatchedRootInfo)) {
+        *self.main.lock() = watcher;
+    }
+
+    pub fn info(&self) -> Vec<WatchedRootInfo> {
+        let mut roots = vec![self.main.lock().1.clone()];
+        roots.extend(self.extra.lock().iter().map(|(_, info)| info.clone()));
+        roots
+    }
+}
+
+/// Filesystem type if `path` lives on a network mount (NFS, SMB, ...).
+#[cfg(target_os = "linux")]
+fn network_filesystem(path: &Path) -> Option<String> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    let name = match stat.f_type as u32 {
+        0x6969 => "nfs",
+        0x517B => "smbfs",
+        0xFF53_4D42 => "cifs",
+        0xFE53_4D42 => "smb2",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+/// Filesystem type if `path` lives on a network mount (NFS, SMB, ...).
+#[cfg(target_os = "macos")]
+fn network_filesystem(path: &Path) -> Option<String> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    let name = unsafe { std::ffi::CStr::from_ptr(stat.f_fstypename.as_ptr()) }
+        .to_string_lossy()
+        .to_string();
+    matches!(name.as_str(), "nfs" | "smbfs" | "afpfs" | "webdav" | "cifs").then_some(name)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn network_filesystem(_path: &Path) -> Option<String> {
+    None
+}
+
+/// Watch `path` recursively, falling back to polling on network filesystems.
+fn spawn_watcher<F: EventHandler>(
+    name: &str,
+    path: &Path,
+    config: &WatcherConfig,
+    handler: F,
+) -> Result<(WatcherBackend, WatchedRootInfo), String> {
+    let filesystem = network_filesystem(path);
+    let mut info = WatchedRootInfo {
+        name: name.to_string(),
+        path: path.to_string_lossy().to_string(),
+        backend: "native".to_string(),
+        filesystem: filesystem.clone(),
+        poll_interval_ms: None,
+    };
+
+    if config.force_polling || filesystem.is_some() {
+        let interval = config.network_poll_interval_ms.max(250);
+        let mut watcher = PollWatcher::new(
+            handler,
+            Config::default().with_poll_interval(Duration::from_millis(interval)),
+        )
+        .map_err(|e| e.to_string())?;
+        watcher
+            .watch(path, RecursiveMode::Recursive)
+            .map_err(|e| e.to_string())?;
+        info.backend = "poll".to_string();
+        info.poll_interval_ms = Some(interval);
+        return Ok((WatcherBackend::Poll(watcher), info));
+    }
+
+    let mut watcher = RecommendedWatcher::new(
+        handler,
+        Config::default().with_poll_interval(Duration::from_millis(200)),
+    )
+    .map_err(|e| e.to_string())?;
+    watcher
+        .watch(path, RecursiveMode::Recursive)
+        .map_err(|e| e.to_string())?;
+    Ok((WatcherBackend::Native(watcher), info))
+}
+
+/// Easing applied to the afterglow phase
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "lowercase")]
+pub enum DecayCurve {
+    Linear,
+    Exponential,
+    Cosine,
+}
+
+/// Runtime-tunable watcher settings (persisted in AppConfig)
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(default)]
+pub struct WatcherConfig {
+    /// How often node levels are recomputed and `soul:nodes` may be emitted
+    pub snapshot_fps: u32,
+    /// Time a node stays fully lit after activity
+    #[ts(type = "number")]
+    pub bright_ms: u64,
+    /// Duration of the fade-out after the bright phase
+    #[ts(type = "number")]
+    pub afterglow_ms: u64,
+    pub afterglow_curve: DecayCurve,
+    /// The soul counts as "working" this long after the last activity
+    #[ts(type = "number")]
+    pub working_timeout_ms: u64,
+    /// Max emitted pulses per second for each activity type
+    pub pulse_max_per_sec: u32,
+    /// Identical pulses (same type and label) within this window are merged
+    #[ts(type = "number")]
+    pub pulse_merge_ms: u64,
+    /// Poll interval used when a root is on a network volume
+    #[ts(type = "number")]
+    pub network_poll_interval_ms: u64,
+    /// Always use the polling backend, even on local disks
+    pub force_polling: bool,
+}
+
+impl Default for WatcherConfig {
+    fn default() -> Self {
+        Self {
+            snapshot_fps: 10,
+            bright_ms: BRIGHT_MS,
+            afterglow_ms: AFTERGLOW_MS,
+            afterglow_curve: DecayCurve::Linear,
+            working_timeout_ms: WORKING_TIMEOUT_MS,
+            pulse_max_per_sec: 4,
+            pulse_merge_ms: 1000,
+            network_poll_interval_ms: 2000,
+            force_polling: false,
+        }
+    }
+}
+
+impl WatcherConfig {
+    /// Named timing presets. Non-timing settings are kept from `self`.
+    pub fn with_preset(&self, name: &str) -> Option<Self> {
+        let (bright_ms, afterglow_ms, afterglow_curve, working_timeout_ms) = match name {
+            "default" => (BRIGHT_MS, AFTERGLOW_MS, DecayCurve::Linear, WORKING_TIMEOUT_MS),
+            "calm" => (10000, 30000, DecayCurve::Cosine, 30000),
+            "lively" => (2500, 6000, DecayCurve::Exponential, 10000),
+            _ => return None,
+        };
+        Some(Self {
+            bright_ms,
+            afterglow_ms,
+            afterglow_curve,
+            working_timeout_ms,
+            ..self.clone()
+        })
+    }
+
+    /// Two-phase decay: full brightness, then an eased afterglow from 0.5 to 0
+    fn decay_level(&self, elapsed_ms: u64) -> f64 {
+        if elapsed_ms < self.bright_ms {
+            return 1.0;
+        }
+        let afterglow_elapsed = elapsed_ms - self.bright_ms;
+        if afterglow_elapsed >= self.afterglow_ms {
+            return 0.0;
+        }
+        let t = afterglow_elapsed as f64 / self.afterglow_ms as f64;
+        let eased = match self.afterglow_curve {
+            DecayCurve::Linear => 1.0 - t,
+            DecayCurve::Exponential => {
+                // Normalized so the curve still reaches exactly 0 at t = 1
+                let k = 4.0_f64;
+                ((-k * t).exp() - (-k).exp()) / (1.0 - (-k).exp())
+            }
+            DecayCurve::Cosine => 0.5 * (1.0 + (std::f64::consts::PI * t).cos()),
+        };
+        0.5 * eased
+    }
+}
+
 /// Maps file path patterns to brain node IDs
-fn resolve_node(relative_path: &str) -> Option<&'static str> {
+pub fn resolve_node(relative_path: &str) -> Option<&'static str> {
     let patterns: &[(&[&str], &str)] = &[
         (&["SEED.md", "SOUL.md"], "seed"),
         (&["KERN.md", "CORE.md"], "kern"),
@@ -32,17 +326,12 @@ fn resolve_node(relative_path: &str) -> Option<&'static str> {
     ];
 
     // Directory-based patterns
-    if relative_path.contains("beziehungen/") || relative_path.contains("relationships/") {
-        return Some("bonds");
-    }
-    if relative_path.contains("erinnerungen/") || relative_path.contains("memories/") {
-        return Some("mem");
-    }
-    if relative_path.contains("heartbeat/") {
-        return Some("heartbeat");
-    }
-    if relative_path.contains("zustandslog/") || relative_path.contains("statelog/") {
-        return Some("statelog");
+    match paths::classify(relative_path) {
+        Some(Location::Relationships) => return Some("bonds"),
+        Some(Location::Memories | Location::Memory(_)) => return Some("mem"),
+        Some(Location::Heartbeat) => return Some("heartbeat"),
+        Some(Location::StateLog) => return Some("statelog"),
+        Some(Location::Soul) | None => {}
     }
     if relative_path.contains("media/") {
         return Some("mem");
@@ -89,123 +378,627 @@ fn activity_nodes(activity: &str) -> Option<&'static [&'static str]> {
     }
 }
 
+/// How activity on a node nudges the estimated mood: (valence, energy) offsets.
+/// Shadow work pulls valence down, garden/dreams calm things, code and research energize.
+fn node_mood_bias(node: &str) -> (f64, f64) {
+    match node {
+        "schatten" => (-0.5, 0.0),
+        "bewusstsein" => (-0.1, -0.1),
+        "traeume" => (0.1, -0.3),
+        "garten" => (0.2, -0.3),
+        "bonds" => (0.3, 0.1),
+        "wachstum" => (0.2, 0.2),
+        "evolution" => (0.1, 0.3),
+        "manifest" => (0.0, 0.35),
+        "interessen" => (0.1, 0.3),
+        "graph" | "kern" => (0.0, 0.1),
+        _ => (0.0, 0.0),
+    }
+}
+
+// Baseline mood (matches soul-engine ImpulseState defaults)
+const BASELINE_VALENCE: f64 = 0.3;
+const BASELINE_ENERGY: f64 = 0.5;
+
+/// Estimate a mood from the weighted mix of currently active nodes.
+fn infer_mood(levels: &HashMap<String, f64>) -> Option<SoulMood> {
+    let total: f64 = levels.values().sum();
+    if total <= 0.0 {
+        return None;
+    }
+
+    let (mut dv, mut de) = (0.0, 0.0);
+    for (node, level) in levels {
+        let (v, e) = node_mood_bias(node);
+        dv += v * level;
+        de += e * level;
+    }
+    let valence = (BASELINE_VALENCE + dv / total).clamp(-1.0, 1.0);
+    let energy = (BASELINE_ENERGY + de / total).clamp(0.0, 1.0);
+
+    let label = mood_label(valence, energy);
+
+    Some(SoulMood {
+        valence: Some((valence * 100.0).round() / 100.0),
+        energy: Some((energy * 100.0).round() / 100.0),
+        label: Some(label.to_string()),
+        inferred: true,
+    })
+}
+
+/// Same quadrants as soul-engine's MOOD_LABELS
+pub fn mood_label(valence: f64, energy: f64) -> &'static str {
+    match (valence, energy > 0.5) {
+        (v, true) if v > 0.15 => "begeistert",
+        (v, false) if v > 0.15 => "zufrieden",
+        (v, true) if v < -0.15 => "unruhig",
+        (v, false) if v < -0.15 => "melancholisch",
+        (_, true) => "neugierig",
+        (_, false) => "nachdenklich",
+    }
+}
+
 #[derive(Clone)]
 pub struct WatcherState {
-    inner: Arc<Mutex<WatcherInner>>,
+    inner: Arc<RwLock<WatcherInner>>,
+    /// Latest node levels computed by the ticker — what the UI reads
+    snapshot: Arc<RwLock<HashMap<String, f64>>>,
+    config: Arc<RwLock<WatcherConfig>>,
+    /// Paths changed while background activity was suspended, with the
+    /// extra root they belong to (None = soul_path)
+    deferred: Arc<Mutex<BTreeMap<PathBuf, Option<WatchRoot>>>>,
+}
+
+struct NodeActivation {
+    last_active: Instant,
+    /// 0..1 weight applied to the decay curve
+    intensity: f64,
 }
 
 struct WatcherInner {
-    active_nodes: HashMap<String, Instant>,
+    active_nodes: HashMap<String, NodeActivation>,
+    /// Recent event timestamps per node (for burst weighting)
+    recent_events: HashMap<String, VecDeque<Instant>>,
+    /// Last seen size per relative file path (for bytes-changed weighting)
+    file_sizes: HashMap<String, u64>,
+    /// Emitted pulse times per activity type (rate limiting)
+    pulse_times: HashMap<String, VecDeque<Instant>>,
+    /// Last emitted label per activity type (merging)
+    last_pulse: HashMap<String, (String, Instant)>,
+    /// Pulses dropped since the last emitted one
+    suppressed_pulses: u32,
     last_any_pulse: Instant,
     current_mood: Option<SoulMood>,
     last_jsonl_size: u64,
 }
 
 impl WatcherState {
-    pub fn new() -> Self {
+    pub fn new(config: WatcherConfig) -> Self {
         Self {
-            inner: Arc::new(Mutex::new(WatcherInner {
+            inner: Arc::new(RwLock::new(WatcherInner {
                 active_nodes: HashMap::new(),
+                recent_events: HashMap::new(),
+                file_sizes: HashMap::new(),
+                pulse_times: HashMap::new(),
+                last_pulse: HashMap::new(),
+                suppressed_pulses: 0,
                 last_any_pulse: Instant::now() - Duration::from_secs(60),
                 current_mood: None,
                 last_jsonl_size: 0,
             })),
+            snapshot: Arc::new(RwLock::new(HashMap::new())),
+            config: Arc::new(RwLock::new(config)),
+            deferred: Arc::new(Mutex::new(BTreeMap::new())),
         }
     }
 
+    /// Remember the paths of a change event for `replay_deferred`.
+    fn defer(&self, event: &Event, root: Option<&WatchRoot>) {
+        if !matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+        ) {
+            return;
+        }
+        let mut deferred = self.deferred.lock();
+        for path in &event.paths {
+            deferred.insert(path.clone(), root.cloned());
+        }
+    }
+
+    pub fn deferred_count(&self) -> usize {
+        self.deferred.lock().len()
+    }
+
+    /// Applies immediately — the ticker and decay math read the config on every frame.
+    pub fn set_config(&self, config: WatcherConfig) {
+        *self.config.write() = config;
+    }
+
     /// Returns activity level 0..1 for a node with two-phase decay
     pub fn get_activity(&self, node_id: &str) -> f64 {
-        let inner = self.inner.lock().unwrap();
-        let last_active = match inner.active_nodes.get(node_id) {
-            Some(t) => t,
+        let inner = self.inner.read();
+        let activation = match inner.active_nodes.get(node_id) {
+            Some(a) => a,
             None => return 0.0,
         };
-        let elapsed = last_active.elapsed().as_millis() as u64;
-
-        if elapsed < BRIGHT_MS {
-            1.0
-        } else if elapsed < TOTAL_DECAY_MS {
-            let afterglow_elapsed = elapsed - BRIGHT_MS;
-            let t = afterglow_elapsed as f64 / AFTERGLOW_MS as f64;
-            0.5 * (1.0 - t)
-        } else {
-            0.0
-        }
+        self.config
+            .read()
+            .decay_level(activation.last_active.elapsed().as_millis() as u64)
+            * activation.intensity
     }
 
     pub fn is_working(&self) -> bool {
-        let inner = self.inner.lock().unwrap();
-        inner.last_any_pulse.elapsed().as_millis() < WORKING_TIMEOUT_MS as u128
+        let inner = self.inner.read();
+        let timeout = self.config.read().working_timeout_ms;
+        inner.last_any_pulse.elapsed().as_millis() < timeout as u128
     }
 
+    /// Latest snapshot from the node ticker (no decay math on the caller's thread)
     pub fn get_active_nodes_map(&self) -> HashMap<String, f64> {
-        let inner = self.inner.lock().unwrap();
-        let mut result = HashMap::new();
-        for (node, _) in &inner.active_nodes {
-            // Calculate activity without re-locking
-            let elapsed = inner
-                .active_nodes
-                .get(node)
-                .map(|t| t.elapsed().as_millis() as u64)
-                .unwrap_or(TOTAL_DECAY_MS + 1);
-
-            let level = if elapsed < BRIGHT_MS {
-                1.0
-            } else if elapsed < TOTAL_DECAY_MS {
-                let afterglow_elapsed = elapsed - BRIGHT_MS;
-                let t = afterglow_elapsed as f64 / AFTERGLOW_MS as f64;
-                0.5 * (1.0 - t)
-            } else {
-                0.0
-            };
+        self.snapshot.read().clone()
+    }
 
-            if level > 0.0 {
-                result.insert(node.clone(), level);
+    /// Recompute all node levels, dropping nodes that have fully decayed.
+    /// Levels are rounded to 0.01 so the snapshot only changes visibly.
+    fn compute_levels(&self) -> HashMap<String, f64> {
+        let config = self.config.read().clone();
+        let mut expired = Vec::new();
+        let mut levels = HashMap::new();
+        {
+            let inner = self.inner.read();
+            for (node, activation) in &inner.active_nodes {
+                let level = config
+                    .decay_level(activation.last_active.elapsed().as_millis() as u64)
+                    * activation.intensity;
+                if level > 0.0 {
+                    levels.insert(node.clone(), (level * 100.0).round() / 100.0);
+                } else {
+                    expired.push(node.clone());
+                }
             }
         }
-        result
+        if !expired.is_empty() {
+            let mut inner = self.inner.write();
+            for node in expired {
+                inner.active_nodes.remove(&node);
+            }
+        }
+        levels
     }
 
+    /// Mood written by the engine (.soul-mood) if there is one; otherwise an
+    /// estimate from recent activity, flagged `inferred`.
     pub fn get_mood(&self) -> Option<SoulMood> {
-        let inner = self.inner.lock().unwrap();
-        inner.current_mood.clone()
+        if let Some(mood) = self.inner.read().current_mood.clone() {
+            return Some(mood);
+        }
+        infer_mood(&self.snapshot.read())
     }
 
-    fn activate_node(&self, node: &str) {
-        let mut inner = self.inner.lock().unwrap();
-        inner.active_nodes.insert(node.to_string(), Instant::now());
-        inner.last_any_pulse = Instant::now();
+    /// Light a node. A weaker activation never dims a node that is still
+    /// in its bright phase from a stronger one.
+    fn activate_node(&self, node: &str, intensity: f64) {
+        let bright_ms = self.config.read().bright_ms;
+        let mut inner = self.inner.write();
+        let now = Instant::now();
+        let intensity = match inner.active_nodes.get(node) {
+            Some(prev) if (prev.last_active.elapsed().as_millis() as u64) < bright_ms => {
+                intensity.max(prev.intensity)
+            }
+            _ => intensity,
+        };
+        inner.active_nodes.insert(
+            node.to_string(),
+            NodeActivation {
+                last_active: now,
+                intensity,
+            },
+        );
+        inner.last_any_pulse = now;
+    }
+
+    /// Decide whether a pulse gets emitted. Returns the number of pulses
+    /// suppressed since the last emission (to report with this one), or
+    /// `None` if this pulse is merged into a recent identical one / rate limited.
+    fn admit_pulse(&self, activity: &str, label: &str) -> Option<u32> {
+        let config = self.config.read().clone();
+        let mut inner = self.inner.write();
+        let now = Instant::now();
+
+        let merged = inner.last_pulse.get(activity).is_some_and(|(last_label, at)| {
+            last_label == label
+                && (now.duration_since(*at).as_millis() as u64) < config.pulse_merge_ms
+        });
+
+        let times = inner.pulse_times.entry(activity.to_string()).or_default();
+        while times
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= Duration::from_secs(1))
+        {
+            times.pop_front();
+        }
+        let limited = times.len() >= config.pulse_max_per_sec.max(1) as usize;
+
+        if merged || limited {
+            inner.suppressed_pulses += 1;
+            return None;
+        }
+
+        times.push_back(now);
+        inner
+            .last_pulse
+            .insert(activity.to_string(), (label.to_string(), now));
+        Some(std::mem::take(&mut inner.suppressed_pulses))
+    }
+
+    /// Intensity for a file change: log-scaled bytes changed since the last
+    /// event on that file, or the node's event rate in the window — whichever is larger.
+    fn change_intensity(&self, node: &str, relative: &str, new_size: Option<u64>) -> f64 {
+        let mut inner = self.inner.write();
+        let now = Instant::now();
+        let window = Duration::from_millis(INTENSITY_WINDOW_MS);
+
+        let events = inner.recent_events.entry(node.to_string()).or_default();
+        events.push_back(now);
+        while events
+            .front()
+            .is_some_and(|t| now.duration_since(*t) > window)
+        {
+            events.pop_front();
+        }
+        let event_count = events.len() as f64;
+
+        let old_size = match new_size {
+            Some(size) => inner.file_sizes.insert(relative.to_string(), size),
+            None => inner.file_sizes.remove(relative),
+        };
+        let delta = match (old_size, new_size) {
+            (Some(old), Some(new)) => old.abs_diff(new),
+            (None, Some(new)) => new,
+            (Some(old), None) => old,
+            (None, None) => 0,
+        };
+
+        let size_weight = (1.0 + delta as f64).ln() / (1.0 + FULL_INTENSITY_BYTES).ln();
+        let burst_weight = (event_count - 1.0) / (FULL_INTENSITY_EVENTS - 1.0);
+        let weight = size_weight.max(burst_weight).clamp(0.0, 1.0);
+        MIN_INTENSITY + (1.0 - MIN_INTENSITY) * weight
     }
 
     fn set_mood(&self, mood: SoulMood) {
-        let mut inner = self.inner.lock().unwrap();
+        let mut inner = self.inner.write();
         inner.current_mood = Some(mood);
     }
+
+    /// Seed a node from a file changed `age` ago (before launch), so it
+    /// resumes its decay where it would have been. Doesn't touch `is_working`.
+    fn hydrate_node(&self, node: &str, age: Duration) {
+        let Some(last_active) = Instant::now().checked_sub(age) else {
+            return;
+        };
+        let mut inner = self.inner.write();
+        let newer = inner
+            .active_nodes
+            .get(node)
+            .is_none_or(|prev| prev.last_active < last_active);
+        if newer {
+            inner.active_nodes.insert(
+                node.to_string(),
+                NodeActivation {
+                    last_active,
+                    intensity: 1.0,
+                },
+            );
+        }
+    }
+
+    fn seed_file_size(&self, relative: &str, size: u64) {
+        self.inner
+            .write()
+            .file_sizes
+            .insert(relative.to_string(), size);
+    }
+}
+
+/// Soul-state files: everything that maps to a brain node (review mode tracks these).
+pub fn is_soul_state(relative: &str) -> bool {
+    !is_ignored(relative) && resolve_node(relative).is_some()
+}
+
+/// Directories that never map to brain nodes
+fn is_ignored(relative: &str) -> bool {
+    relative.contains("node_modules")
+        || relative.contains("soul-monitor")
+        || relative.contains("seelen-protokoll")
+        || relative.contains("target/")
+        || relative.contains(".git/")
+}
+
+/// Startup scan: light nodes whose files changed within the decay window
+/// before launch, record file sizes for change weighting, and emit one
+/// `soul:hydrated` snapshot so the brain isn't dark until the next change.
+fn hydrate(app: &AppHandle, state: &WatcherState, soul_path: &Path) {
+    let window = {
+        let config = state.config.read();
+        Duration::from_millis(config.bright_ms + config.afterglow_ms)
+    };
+    let now = SystemTime::now();
+
+    let mut dirs = vec![soul_path.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let relative = match path.strip_prefix(soul_path) {
+                Ok(r) => r.to_string_lossy().to_string(),
+                Err(_) => continue,
+            };
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                if !is_ignored(&format!("{}/", relative)) {
+                    dirs.push(path);
+                }
+                continue;
+            }
+            if is_ignored(&relative) {
+                continue;
+            }
+            let Some(node) = resolve_node(&relative) else {
+                continue;
+            };
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            state.seed_file_size(&relative, meta.len());
+
+            let age = meta
+                .modified()
+                .ok()
+                .and_then(|mtime| now.duration_since(mtime).ok());
+            if let Some(age) = age.filter(|a| *a < window) {
+                state.hydrate_node(node, age);
+            }
+        }
+    }
+
+    let levels = state.compute_levels();
+    *state.snapshot.write() = levels.clone();
+    let _ = app.emit("soul:hydrated", levels);
 }
 
-pub fn start_watcher(app: &AppHandle, soul_path: &Path) -> Result<RecommendedWatcher, String> {
-    let state = WatcherState::new();
+/// Background ticker: recomputes node levels at the configured frame rate and
+/// emits a single `soul:nodes` snapshot, only when something actually changed.
+pub fn start_node_ticker(app: AppHandle, state: WatcherState) {
+    std::thread::Builder::new()
+        .name("soul-node-ticker".to_string())
+        .spawn(move || loop {
+            let fps = state.config.read().snapshot_fps.clamp(1, 60);
+            std::thread::sleep(Duration::from_millis(1000 / fps as u64));
+            if background::suspended(&app) {
+                continue;
+            }
+
+            let levels = state.compute_levels();
+            let changed = *state.snapshot.read() != levels;
+            if changed {
+                *state.snapshot.write() = levels.clone();
+                // Ambient mode streams its own, slower feed
+                if !ambient::active(&app) {
+                    let _ = app.emit("soul:nodes", levels);
+                }
+            }
+        })
+        .expect("Failed to spawn node ticker");
+}
+
+pub fn start_watcher(
+    app: &AppHandle,
+    soul_path: &Path,
+    config: WatcherConfig,
+    roots: &[WatchRoot],
+) -> Result<WatcherHandles, String> {
+    let state = WatcherState::new(config);
+
+    // Pick up a mood file written before launch — it stays authoritative over inference
+    if let Some(mood) = read_mood_file(&soul_path.join(".soul-mood")) {
+        state.set_mood(mood);
+    }
+
     app.manage(state.clone());
 
+    // Hydrate before the ticker starts so its first frame already has the scan
+    hydrate(app, &state, soul_path);
+    start_node_ticker(app.clone(), state.clone());
+
+    Ok(WatcherHandles {
+        main: Mutex::new(watch_soul(app, &state, soul_path)?),
+        extra: Mutex::new(start_extra_watchers(app, &state, roots)),
+    })
+}
+
+/// Follow a relocated soul: swap the soul watcher to `soul_path`, or start
+/// watching if no soul existed at launch.
+pub fn repoint(app: &AppHandle, soul_path: &Path) -> Result<(), String> {
+    match (app.try_state::<WatcherHandles>(), app.try_state::<WatcherState>()) {
+        (Some(handles), Some(state)) => {
+            handles.replace_main(watch_soul(app, &state, soul_path)?);
+            Ok(())
+        }
+        _ => {
+            let (config, roots) = {
+                let cfg = app.state::<Arc<RwLock<AppConfig>>>();
+                let cfg = cfg.read();
+                (cfg.watcher.clone(), cfg.watch_roots.clone())
+            };
+            let handles = start_watcher(app, soul_path, config, &roots)?;
+            app.manage(handles);
+            Ok(())
+        }
+    }
+}
+
+fn watch_soul(
+    app: &AppHandle,
+    state: &WatcherState,
+    soul_path: &Path,
+) -> Result<(WatcherBackend, WatchedRootInfo), String> {
     let soul_path_owned = soul_path.to_path_buf();
     let app_handle = app.clone();
     let watcher_state = state.clone();
+    let config = state.config.read().clone();
 
-    let mut watcher = RecommendedWatcher::new(
+    spawn_watcher(
+        "soul",
+        soul_path,
+        &config,
         move |res: Result<Event, notify::Error>| {
-            if let Ok(event) = res {
-                handle_fs_event(&app_handle, &watcher_state, &soul_path_owned, event);
+            let Ok(event) = res else {
+                return;
+            };
+            if background::suspended(&app_handle) {
+                watcher_state.defer(&event, None);
+                return;
+            }
+            let metrics = app_handle.try_state::<Arc<Metrics>>();
+            if let Some(m) = &metrics {
+                m.watcher_event_received();
+            }
+            handle_fs_event(&app_handle, &watcher_state, &soul_path_owned, event);
+            if let Some(m) = &metrics {
+                m.watcher_event_handled();
             }
         },
-        Config::default().with_poll_interval(Duration::from_millis(200)),
     )
-    .map_err(|e| e.to_string())?;
+}
 
-    watcher
-        .watch(soul_path, RecursiveMode::Recursive)
-        .map_err(|e| e.to_string())?;
+/// One watcher per additional root, all feeding the same WatcherState.
+/// Roots that can't be watched (e.g. an unplugged drive) are skipped.
+pub fn start_extra_watchers(
+    app: &AppHandle,
+    state: &WatcherState,
+    roots: &[WatchRoot],
+) -> Vec<(WatcherBackend, WatchedRootInfo)> {
+    let mut watchers = Vec::new();
+    for root in roots {
+        match watch_extra_root(app, state, root) {
+            Ok(w) => watchers.push(w),
+            Err(e) => eprintln!("[watcher] skipping root {}: {}", root.path.display(), e),
+        }
+    }
+    watchers
+}
 
-    Ok(watcher)
+fn watch_extra_root(
+    app: &AppHandle,
+    state: &WatcherState,
+    root: &WatchRoot,
+) -> Result<(WatcherBackend, WatchedRootInfo), String> {
+    let root_owned = root.clone();
+    let app_handle = app.clone();
+    let watcher_state = state.clone();
+    let config = state.config.read().clone();
+
+    spawn_watcher(
+        &root.name,
+        &root.path,
+        &config,
+        move |res: Result<Event, notify::Error>| {
+            let Ok(event) = res else {
+                return;
+            };
+            if background::suspended(&app_handle) {
+                watcher_state.defer(&event, Some(&root_owned));
+                return;
+            }
+            let metrics = app_handle.try_state::<Arc<Metrics>>();
+            if let Some(m) = &metrics {
+                m.watcher_event_received();
+            }
+            handle_root_event(&app_handle, &watcher_state, &root_owned, event);
+            if let Some(m) = &metrics {
+                m.watcher_event_handled();
+            }
+        },
+    )
+}
+
+/// Process the changes collected while background activity was suspended,
+/// once per path with its current state.
+pub fn replay_deferred(app: &AppHandle) {
+    let Some(state) = app.try_state::<WatcherState>() else {
+        return;
+    };
+    let deferred = std::mem::take(&mut *state.deferred.lock());
+    let soul_path = app
+        .state::<Arc<RwLock<AppConfig>>>()
+        .read()
+        .soul_path
+        .clone();
+    for (path, root) in deferred {
+        let kind = if path.exists() {
+            EventKind::Modify(ModifyKind::Any)
+        } else {
+            EventKind::Remove(RemoveKind::Any)
+        };
+        let event = Event::new(kind).add_path(path);
+        match root {
+            Some(root) => handle_root_event(app, &state, &root, event),
+            None => handle_fs_event(app, &state, &soul_path, event),
+        }
+    }
+}
+
+fn handle_root_event(app: &AppHandle, state: &WatcherState, root: &WatchRoot, event: Event) {
+    if !matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    ) {
+        return;
+    }
+
+    for path in &event.paths {
+        let relative = match path.strip_prefix(&root.path) {
+            Ok(r) => r.to_string_lossy().to_string(),
+            Err(_) => continue,
+        };
+        if let Some(node) = root.resolve_node(&relative) {
+            let file = format!("{}/{}", root.name, relative);
+            activate_file(app, state, &node, &file, path);
+        }
+    }
+}
+
+/// Light the node for a changed file (weighted by change size) and emit `soul:activity`.
+fn activate_file(app: &AppHandle, state: &WatcherState, node: &str, file: &str, path: &Path) {
+    let size = fs::metadata(path).ok().map(|m| m.len());
+    let intensity = state.change_intensity(node, file, size);
+    emit_activity(app, state, node, file, "change", intensity);
+}
+
+/// Light a node and emit `soul:activity`.
+pub fn emit_activity(
+    app: &AppHandle,
+    state: &WatcherState,
+    node: &str,
+    file: &str,
+    event_type: &str,
+    intensity: f64,
+) {
+    state.activate_node(node, intensity);
+    let _ = app.emit(
+        "soul:activity",
+        SoulActivity {
+            node: node.to_string(),
+            file: file.to_string(),
+            event_type: event_type.to_string(),
+            intensity,
+        },
+    );
 }
 
 fn handle_fs_event(
@@ -229,12 +1022,7 @@ fn handle_fs_event(
         };
 
         // Skip directories we don't care about
-        if relative.contains("node_modules")
-            || relative.contains("soul-monitor")
-            || relative.contains("seelen-protokoll")
-            || relative.contains("target/")
-            || relative.contains(".git/")
-        {
+        if is_ignored(&relative) {
             continue;
         }
 
@@ -244,6 +1032,30 @@ fn handle_fs_event(
             continue;
         }
 
+        // Policy and review can't inspect ciphertext
+        let encrypted = app
+            .try_state::<Arc<Vault>>()
+            .is_some_and(|v| v.covers(&relative));
+
+        if let Some(policy) = app.try_state::<Arc<PolicyEngine>>() {
+            if relative == POLICY_FILE {
+                policy.load(soul_path);
+                continue;
+            }
+            if !encrypted {
+                policy.check(app, soul_path, &relative);
+            }
+        }
+
+        // Synthetic churn from benchmark_pipeline ends here
+        if relative.starts_with(BENCH_PREFIX) {
+            let _ = app.emit("bench:event", &relative);
+            if let Some(bench) = app.try_state::<Arc<PipelineBench>>() {
+                bench.observe(&relative);
+            }
+            continue;
+        }
+
         // Handle .soul-mood
         if relative == ".soul-mood" {
             handle_mood(app, state, path);
@@ -256,21 +1068,54 @@ fn handle_fs_event(
             continue;
         }
 
+        if relative == pins::PINS_FILE {
+            pins::changed(app, soul_path);
+            continue;
+        }
+
+        if relative == ".env" {
+            env_watch::changed(app, soul_path);
+            continue;
+        }
+
+        if relative == "SEED.md" {
+            handle_seed(app, soul_path);
+        }
+
+        tags::changed(app, soul_path, &relative);
+        recent::changed(app, soul_path, &relative);
+
+        if !encrypted && vitals::is_log(&relative) {
+            handle_heartbeat(app, soul_path);
+        }
+
         // Regular file → resolve to node
         if let Some(node) = resolve_node(&relative) {
-            state.activate_node(node);
-            let _ = app.emit(
-                "soul:activity",
-                SoulActivity {
-                    node: node.to_string(),
-                    file: relative.clone(),
-                    event_type: "change".to_string(),
-                },
-            );
+            if let Some(review) = app.try_state::<Arc<ReviewQueue>>().filter(|_| !encrypted) {
+                review.intercept(app, soul_path, &relative);
+            }
+            activate_file(app, state, node, &relative, path);
         }
     }
 }
 
+/// SEED.md changed: re-parse the cached status and tell the UI if it differs.
+fn handle_seed(app: &AppHandle, soul_path: &Path) {
+    let Some(cache) = app.try_state::<Arc<StatusCache>>() else {
+        return;
+    };
+    if let Some(status) = cache.refresh(soul_path) {
+        let _ = app.emit("soul:status-changed", status);
+    }
+}
+
+/// A heartbeat day log changed: re-read the vital signs for the EKG.
+fn handle_heartbeat(app: &AppHandle, soul_path: &Path) {
+    if let Some(vitals) = vitals::read(soul_path) {
+        let _ = app.emit("soul:vitals", vitals);
+    }
+}
+
 fn handle_pulse(app: &AppHandle, state: &WatcherState, path: &Path) {
     let content = match fs::read_to_string(path) {
         Ok(c) => c.trim().to_string(),
@@ -289,9 +1134,30 @@ fn handle_pulse(app: &AppHandle, state: &WatcherState, path: &Path) {
         (content.to_lowercase(), content.clone())
     };
 
-    let nodes = match activity_nodes(&activity) {
+    if let Some(pulse) = emit_pulse(app, state, &activity, &label) {
+        digest::record_pulse(&pulse);
+    }
+}
+
+/// Emit a pulse and light its nodes. Returns the pulse unless the activity is
+/// unknown or the pulse was merged / rate limited.
+pub fn emit_pulse(
+    app: &AppHandle,
+    state: &WatcherState,
+    activity: &str,
+    label: &str,
+) -> Option<SoulPulse> {
+    let nodes = activity_nodes(activity)?;
+
+    // Rate limit / merge — suppressed pulses still keep their nodes lit, silently
+    let suppressed = match state.admit_pulse(activity, label) {
         Some(n) => n,
-        None => return,
+        None => {
+            for node in nodes {
+                state.activate_node(node, 1.0);
+            }
+            return None;
+        }
     };
 
     // Emit pulse for whisper integration
@@ -300,43 +1166,44 @@ fn handle_pulse(app: &AppHandle, state: &WatcherState, path: &Path) {
         .unwrap_or_default()
         .as_millis() as u64;
 
-    let _ = app.emit(
-        "soul:pulse",
-        SoulPulse {
-            activity_type: activity.clone(),
-            label: label.clone(),
-            timestamp: ts,
-        },
-    );
+    let pulse = SoulPulse {
+        activity_type: activity.to_string(),
+        label: label.to_string(),
+        timestamp: ts,
+        suppressed,
+    };
+    let _ = app.emit("soul:pulse", &pulse);
 
+    // Pulses are explicit signals from the engine — always full intensity
+    let file = format!(".soul-pulse [{}]", label);
     for node in nodes {
-        state.activate_node(node);
-        let _ = app.emit(
-            "soul:activity",
-            SoulActivity {
-                node: node.to_string(),
-                file: format!(".soul-pulse [{}]", label),
-                event_type: "pulse".to_string(),
-            },
-        );
+        emit_activity(app, state, node, &file, "pulse", 1.0);
     }
+    Some(pulse)
 }
 
-fn handle_mood(app: &AppHandle, state: &WatcherState, path: &Path) {
-    let content = match fs::read_to_string(path) {
-        Ok(c) => c.trim().to_string(),
-        Err(_) => return,
-    };
+fn read_mood_file(path: &Path) -> Option<SoulMood> {
+    let content = fs::read_to_string(path).ok()?;
+    let content = content.trim();
     if content.is_empty() {
-        return;
+        return None;
     }
+    serde_json::from_str::<SoulMood>(content).ok()
+}
 
-    if let Ok(mood) = serde_json::from_str::<SoulMood>(&content) {
-        state.set_mood(mood.clone());
-        let _ = app.emit("soul:mood", mood);
+fn handle_mood(app: &AppHandle, state: &WatcherState, path: &Path) {
+    if let Some(mood) = read_mood_file(path) {
+        digest::record_mood(&mood);
+        apply_mood(app, state, mood);
     }
 }
 
+/// Make `mood` the current mood and emit `soul:mood`.
+pub fn apply_mood(app: &AppHandle, state: &WatcherState, mood: SoulMood) {
+    state.set_mood(mood.clone());
+    let _ = app.emit("soul:mood", mood);
+}
+
 fn handle_events(app: &AppHandle, state: &WatcherState, path: &Path) {
     let metadata = match fs::metadata(path) {
         Ok(m) => m,
@@ -345,7 +1212,7 @@ fn handle_events(app: &AppHandle, state: &WatcherState, path: &Path) {
     let size = metadata.len();
 
     {
-        let mut inner = state.inner.lock().unwrap();
+        let mut inner = state.inner.write();
         if size <= inner.last_jsonl_size {
             inner.last_jsonl_size = size;
             return;
@@ -25,8 +25,9 @@ import WorldWindowView from "./views/WorldWindowView";
 import BondsView from "./views/BondsView";
 import SetupWizard from "./views/SetupWizard";
 import FoundingChat from "./views/FoundingChat";
+import SoulMissingView from "./views/SoulMissingView";
 import { useActiveNodes, useCurrentPulse, useMood, useActivityFeed } from "./lib/store";
-import { commands } from "./lib/tauri";
+import { commands, events } from "./lib/tauri";
 import { openUrl, toggleBrowser, toggleBrowserMode, onBrowserOpenUrl } from "./lib/browser";
 import { useEngineSocket } from "./lib/useEngineSocket";
 
@@ -377,7 +378,7 @@ function BootSplash({ onDone }: { onDone: () => void }) {
 
 /* ── App Phase Type ────────────────────────────────────────── */
 
-type AppPhase = "loading" | "setup" | "founding" | "ready";
+type AppPhase = "loading" | "setup" | "founding" | "missing" | "ready";
 
 /* ── App ───────────────────────────────────────────────────── */
 
@@ -404,6 +405,14 @@ function App() {
       .catch(() => setAppPhase("setup")); // fallback to setup on error
   }, [booting]);
 
+  // The soul can disappear while running (unplugged drive, deleted folder)
+  useEffect(() => {
+    const unlisten = events.onSoulHealth((health) => {
+      if (!health.ok) setAppPhase("missing");
+    });
+    return () => { unlisten.then((fn) => fn()); };
+  }, []);
+
   // Show onboarding for new souls (sessions < 5, not dismissed)
   useEffect(() => {
     if (appPhase !== "ready") return;
@@ -420,6 +429,11 @@ function App() {
     localStorage.setItem("soul-onboarding-dismissed", "true");
   }, []);
 
+  // Remembered for "restore previous session" on the next start
+  useEffect(() => {
+    commands.setOpenViews(openPanel ? [openPanel] : []).catch(() => {});
+  }, [openPanel]);
+
   const togglePanel = useCallback((id: PanelId) => {
     setOpenPanel((prev) => (prev === id ? null : id));
   }, []);
@@ -508,6 +522,23 @@ function App() {
         </div>
       )}
 
+      {/* Soul missing: relink or restore */}
+      {!booting && appPhase === "missing" && (
+        <div className="h-full" style={{ backgroundColor: "var(--bg-base)" }}>
+          <div
+            className="h-8 flex-shrink-0"
+            onMouseDown={() => getCurrentWindow().startDragging()}
+          />
+          <div style={{ height: "calc(100% - 32px)" }}>
+            <SoulMissingView
+              onRecovered={() =>
+                commands.getAppState().then((state) => setAppPhase(state as AppPhase))
+              }
+            />
+          </div>
+        </div>
+      )}
+
       {/* Founding Interview */}
       {!booting && appPhase === "founding" && (
         <div className="h-full" style={{ backgroundColor: "var(--bg-base)" }}>
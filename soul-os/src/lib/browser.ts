@@ -1,15 +1,19 @@
 import { invoke } from "@tauri-apps/api/core";
+import { invokeElevated } from "./tauri";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 
 let lastUrl: string | null = null;
+let lastProfile: string | undefined;
 let fullMode = false;
 let browserOpen = false;
 
-export async function openUrl(url: string, full = false): Promise<void> {
+/** `profile` opens the page with that browser profile's cookies and storage. */
+export async function openUrl(url: string, full = false, profile?: string): Promise<void> {
   lastUrl = url;
+  lastProfile = profile;
   fullMode = full;
   browserOpen = true;
-  await invoke("open_browser", { url, fullMode: full });
+  await invokeElevated("open_browser", { url, fullMode: full, profile }, "Open the embedded browser");
 }
 
 export async function closeBrowser(): Promise<void> {
@@ -27,13 +31,17 @@ export async function toggleBrowser(): Promise<void> {
   if (browserOpen) {
     await closeBrowser();
   } else {
-    await openUrl(lastUrl || DEFAULT_URL, fullMode);
+    await openUrl(lastUrl || DEFAULT_URL, fullMode, lastProfile);
   }
 }
 
 export async function toggleBrowserMode(): Promise<void> {
   fullMode = !fullMode;
-  await invoke("open_browser", { url: lastUrl || DEFAULT_URL, fullMode });
+  await invokeElevated(
+    "open_browser",
+    { url: lastUrl || DEFAULT_URL, fullMode, profile: lastProfile },
+    "Open the embedded browser",
+  );
 }
 
 export function getLastUrl(): string | null {
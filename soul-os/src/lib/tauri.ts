@@ -1,117 +1,625 @@
-import { invoke } from "@tauri-apps/api/core";
+import { convertFileSrc, invoke } from "@tauri-apps/api/core";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
+import type { Commands } from "./bindings/commands";
+import type { Events } from "./bindings/events";
+import type { CrashConfig } from "./bindings/CrashConfig";
+import type { MetricsConfig } from "./bindings/MetricsConfig";
+import type { CompanionConfig } from "./bindings/CompanionConfig";
+import type { CompanionPairing } from "./bindings/CompanionPairing";
+import type { FoundingAnswers } from "./bindings/FoundingAnswers";
+import type { DownloadConfig } from "./bindings/DownloadConfig";
+import type { NetworkConfig } from "./bindings/NetworkConfig";
+import type { EnvPolicy } from "./bindings/EnvPolicy";
+import type { RetentionConfig } from "./bindings/RetentionConfig";
+import type { RollupConfig } from "./bindings/RollupConfig";
+import type { RenderOptions } from "./bindings/RenderOptions";
+import type { RelocationReport } from "./bindings/RelocationReport";
+import type { BatchOp } from "./bindings/BatchOp";
+import type { BatchReport } from "./bindings/BatchReport";
+import type { SoulHealth } from "./bindings/SoulHealth";
+import type { StreamInfo } from "./bindings/StreamInfo";
+
+// --- Elevation ---
+
+/**
+ * Invoke a sensitive command. If the backend refuses because no elevated
+ * session is unlocked, ask the user (native dialog) and retry once.
+ */
+export async function invokeElevated<T>(
+  cmd: string,
+  args: Record<string, unknown> = {},
+  reason = cmd,
+): Promise<T> {
+  try {
+    return await invoke<T>(cmd, args);
+  } catch (e) {
+    if (!String(e).includes("requires an elevated session")) throw e;
+    const granted = await invoke<boolean>("request_elevation", { reason });
+    if (!granted) throw e;
+    return invoke<T>(cmd, args);
+  }
+}
 
-// --- Types matching Rust structs ---
+// --- Types (generated from the Rust structs, see src-tauri/src/api.rs) ---
+
+export type { SoulStatus } from "./bindings/SoulStatus";
+export type { SoulPulse } from "./bindings/SoulPulse";
+export type { SoulActivity } from "./bindings/SoulActivity";
+export type { SoulMood } from "./bindings/SoulMood";
+export type { Vitals } from "./bindings/Vitals";
+export type { DailyHighlight } from "./bindings/DailyHighlight";
+export type { AmbientStatus } from "./bindings/AmbientStatus";
+export type { AmbientFrame } from "./bindings/AmbientFrame";
+export type { PtyInfo } from "./bindings/PtyInfo";
+export type { Capabilities } from "./bindings/Capabilities";
+export type { CommandRecord } from "./bindings/CommandRecord";
+export type { RestorePlan } from "./bindings/RestorePlan";
+export type { RestoreTerminal } from "./bindings/RestoreTerminal";
+export type { StateEntry } from "./bindings/StateEntry";
+export type { StatelogPage } from "./bindings/StatelogPage";
+export type { StateCount } from "./bindings/StateCount";
+export type { StateDistribution } from "./bindings/StateDistribution";
+export type { HeartbeatEntry } from "./bindings/HeartbeatEntry";
+export type { SidecarStatus } from "./bindings/SidecarStatus";
+export type { SidecarExit } from "./bindings/SidecarExit";
+export type { CrashReason } from "./bindings/CrashReason";
+export type { BootProgress } from "./bindings/BootProgress";
+export type { BootReport } from "./bindings/BootReport";
+export type { AutostartStatus } from "./bindings/AutostartStatus";
+export type { GitCommit } from "./bindings/GitCommit";
+export type { LockStatus } from "./bindings/LockStatus";
+export type { EncryptionStatus } from "./bindings/EncryptionStatus";
+export type { MigrationReport } from "./bindings/MigrationReport";
+export type { PiiFinding } from "./bindings/PiiFinding";
+export type { PiiKind } from "./bindings/PiiKind";
+export type { PiiReport } from "./bindings/PiiReport";
+export type { ExportInfo } from "./bindings/ExportInfo";
+export type { MemoryExport } from "./bindings/MemoryExport";
+export type { RenderOptions } from "./bindings/RenderOptions";
+export type { RenderedMarkdown } from "./bindings/RenderedMarkdown";
+export type { WikiLink } from "./bindings/WikiLink";
+export type { GrowthPoint } from "./bindings/GrowthPoint";
+export type { GrowthMetrics } from "./bindings/GrowthMetrics";
+export type { BenchmarkResult } from "./bindings/BenchmarkResult";
+export type { BrowserProfile } from "./bindings/BrowserProfile";
+export type { ClippedPage } from "./bindings/ClippedPage";
+export type { NetworkConfig } from "./bindings/NetworkConfig";
+export type { ProxyMode } from "./bindings/ProxyMode";
+export type { EnvPolicy } from "./bindings/EnvPolicy";
+export type { EnvChange } from "./bindings/EnvChange";
+export type { EnvKeyInfo } from "./bindings/EnvKeyInfo";
+export type { EffectiveEnvVar } from "./bindings/EffectiveEnvVar";
+export type { EnvValueType } from "./bindings/EnvValueType";
+export type { RelocationReport } from "./bindings/RelocationReport";
+export type { SoulHealth } from "./bindings/SoulHealth";
+export type { SoulIdentity } from "./bindings/SoulIdentity";
+export type { StreamChunk } from "./bindings/StreamChunk";
+export type { StreamError } from "./bindings/StreamError";
+export type { StreamInfo } from "./bindings/StreamInfo";
+export type { BlobHandle } from "./bindings/BlobHandle";
+export type { MemoryHit } from "./bindings/MemoryHit";
+export type { SearchHit } from "./bindings/SearchHit";
+export type { SearchResults } from "./bindings/SearchResults";
+export type { EmbeddingStatus } from "./bindings/EmbeddingStatus";
+export type { Timeline } from "./bindings/Timeline";
+export type { TimelineItem } from "./bindings/TimelineItem";
+export type { CategoryStats } from "./bindings/CategoryStats";
+export type { DocumentStats } from "./bindings/DocumentStats";
+export type { SoulTextStats } from "./bindings/SoulTextStats";
+export type { LanguageGuess } from "./bindings/LanguageGuess";
+export type { Misspelling } from "./bindings/Misspelling";
+export type { SpellcheckReport } from "./bindings/SpellcheckReport";
+export type { LintFinding } from "./bindings/LintFinding";
+export type { LintReport } from "./bindings/LintReport";
+export type { WriteConfirmation } from "./bindings/WriteConfirmation";
+export type { DownloadConfig } from "./bindings/DownloadConfig";
+export type { DownloadProgress } from "./bindings/DownloadProgress";
+export type { MediaAttachment } from "./bindings/MediaAttachment";
+export type { RetentionConfig } from "./bindings/RetentionConfig";
+export type { PurgeItem } from "./bindings/PurgeItem";
+export type { PurgeReport } from "./bindings/PurgeReport";
+export type { RollupConfig } from "./bindings/RollupConfig";
+export type { RollupProposal } from "./bindings/RollupProposal";
+export type { RollupReport } from "./bindings/RollupReport";
+export type { PreflightCheck } from "./bindings/PreflightCheck";
+export type { SetupPreflight } from "./bindings/SetupPreflight";
+export type { AreaUsage } from "./bindings/AreaUsage";
+export type { Remediation } from "./bindings/Remediation";
+export type { Recommendation } from "./bindings/Recommendation";
+export type { HousekeepingReport } from "./bindings/HousekeepingReport";
+export type { BatchOp } from "./bindings/BatchOp";
+export type { BatchReport } from "./bindings/BatchReport";
+export type { SoulLayout } from "./bindings/SoulLayout";
+export type { LayoutReport } from "./bindings/LayoutReport";
+export type { SimulationStatus } from "./bindings/SimulationStatus";
+export type { TagCount } from "./bindings/TagCount";
+export type { AssistantSummary } from "./bindings/AssistantSummary";
+export type { TagSuggestions } from "./bindings/TagSuggestions";
+export type { BundleSection } from "./bindings/BundleSection";
+export type { ContextBundle } from "./bindings/ContextBundle";
+export type { RecentFile } from "./bindings/RecentFile";
+export type { SuggestedFile } from "./bindings/SuggestedFile";
+export type { TaskInfo } from "./bindings/TaskInfo";
+export type { TaskState } from "./bindings/TaskState";
+export type { JournalOp } from "./bindings/JournalOp";
+export type { RecoveryAction } from "./bindings/RecoveryAction";
+export type { RecoveryReport } from "./bindings/RecoveryReport";
+export type { CrashConfig } from "./bindings/CrashConfig";
+export type { FoundingAnswers } from "./bindings/FoundingAnswers";
+export type { FoundingPreview } from "./bindings/FoundingPreview";
+export type { OverwriteConfirmation } from "./bindings/OverwriteConfirmation";
+export type { SoulFile } from "./bindings/SoulFile";
+export type { CrashReport } from "./bindings/CrashReport";
+export type { MetricsConfig } from "./bindings/MetricsConfig";
+export type { CompanionConfig } from "./bindings/CompanionConfig";
+export type { CompanionDevice } from "./bindings/CompanionDevice";
+export type { CompanionPairing } from "./bindings/CompanionPairing";
+export type { CompanionNote } from "./bindings/CompanionNote";
+export type { Peer } from "./bindings/Peer";
+export type { PairRequest } from "./bindings/PairRequest";
+export type { DownReason } from "./bindings/DownReason";
+export type { DowntimeIncident } from "./bindings/DowntimeIncident";
+export type { EngineAvailability } from "./bindings/EngineAvailability";
+export type { EngineUpdateReport } from "./bindings/EngineUpdateReport";
+export type { EngineVersionInfo } from "./bindings/EngineVersionInfo";
+export type { CompatLevel } from "./bindings/CompatLevel";
+export type { Compatibility } from "./bindings/Compatibility";
+export type { RuntimeProfile } from "./bindings/RuntimeProfile";
+export type { ProfileStatus } from "./bindings/ProfileStatus";
+export type { BackgroundStatus } from "./bindings/BackgroundStatus";
+export type { ApiManifest } from "./bindings/ApiManifest";
+export type { CommandSpec } from "./bindings/CommandSpec";
+export type { ParamSpec } from "./bindings/ParamSpec";
+export type { EventSpec } from "./bindings/EventSpec";
+export type { Commands } from "./bindings/commands";
+export type { Events } from "./bindings/events";
+
+/** Shape of the check_node JSON (untyped on the Rust side). */
+export interface NodeInfo {
+  found: boolean;
+  path: string;
+  version: string;
+}
 
-export interface SoulStatus {
-  name: string;
-  born: string;
-  sessions: number;
-  model: string;
-  state: string;
-  mood: string;
-  seed_size: number;
+/** Batch of sidecar output lines; `dropped` counts older lines lost to backpressure. */
+export interface SidecarOutput {
+  process: string;
+  lines: string[];
+  dropped: number;
 }
 
-export interface SoulPulse {
-  activity_type: string;
-  label: string;
-  timestamp: number;
+export type MemoryCategory = "core" | "episodic" | "semantic" | "emotional" | "archive";
+
+/**
+ * Typed invoke: argument and result types come from the generated Commands map,
+ * so a changed Rust signature fails the frontend build instead of at runtime.
+ */
+export function call<K extends keyof Commands>(
+  cmd: K,
+  ...args: {} extends Commands[K]["args"] ? [Commands[K]["args"]?] : [Commands[K]["args"]]
+): Promise<Commands[K]["returns"]> {
+  return invoke<Commands[K]["returns"]>(cmd, args[0]);
 }
 
-export interface SoulActivity {
-  node: string;
-  file: string;
-  event_type: string;
+/** `foundingCreate` refused to found over an existing soul without an overwrite token. */
+export function isAlreadyFounded(err: unknown): boolean {
+  return String(err).startsWith("AlreadyFounded");
 }
 
-export interface SoulMood {
-  valence: number | null;
-  energy: number | null;
-  label: string | null;
+/** `writeSoulFile` refused a protected path without a confirmation token. */
+export function isProtected(err: unknown): boolean {
+  return String(err).startsWith("Protected");
 }
 
-export interface SidecarStatus {
-  process: string;
-  status: string;
-  pid: number | null;
-  uptime_secs: number | null;
+/** A command refused to return a value over the IPC payload limit; use its `stream*` variant. */
+export function isTooLarge(err: unknown): boolean {
+  return String(err).startsWith("TooLarge");
 }
 
-export interface GitCommit {
-  hash: string;
-  date: string;
-  message: string;
-  files_changed: number;
+/**
+ * Collect a streamed value: listens for its chunks, acknowledges each one
+ * (the backend only sends a few ahead) and resolves with the joined text.
+ */
+export async function receiveStream(start: (streamId: string) => Promise<StreamInfo>): Promise<string> {
+  const streamId = crypto.randomUUID();
+  const parts: string[] = [];
+  const unlisten: UnlistenFn[] = [];
+  try {
+    return await new Promise<string>((resolve, reject) => {
+      Promise.all([
+        on("stream:chunk", (chunk) => {
+          if (chunk.stream_id !== streamId) return;
+          parts[chunk.seq] = chunk.data;
+          call("stream_ack", { streamId, seq: chunk.seq }).catch(() => {});
+          if (chunk.last) resolve(parts.join(""));
+        }),
+        on("stream:error", (e) => {
+          if (e.stream_id === streamId) reject(e.error);
+        }),
+      ])
+        .then((fns) => {
+          unlisten.push(...fns);
+          return start(streamId);
+        })
+        .catch(reject);
+    });
+  } finally {
+    unlisten.forEach((fn) => fn());
+  }
 }
 
-export interface NodeInfo {
-  found: boolean;
-  path: string;
-  version: string;
+/** URL the webview can fetch a shared blob from (once, before it expires). */
+export function blobUrl(token: string): string {
+  return convertFileSrc(token, "soul-blob");
+}
+
+/** Retry a one-shot read as a stream when it was over the payload limit. */
+function orStream(read: Promise<string>, start: (streamId: string) => Promise<StreamInfo>): Promise<string> {
+  return read.catch((err) => (isTooLarge(err) ? receiveStream(start) : Promise.reject(err)));
 }
 
 // --- Commands (Frontend → Rust) ---
 
 export const commands = {
   // App state & config
-  getAppState: () => invoke<string>("get_app_state"),
-  getSoulPath: () => invoke<string>("get_soul_path"),
-  setSoulPath: (path: string) => invoke<void>("set_soul_path", { path }),
+  getAppState: () => call("get_app_state"),
+  getSoulPath: () => call("get_soul_path"),
+  setSoulPath: (path: string) => call("set_soul_path", { path }),
+  /** Launch check of the soul path; null during first-run setup. */
+  getSoulHealth: () => call("get_soul_health"),
+  getSoulIdentity: () => call("get_soul_identity"),
+  /** Recover a missing soul by pointing the app at where it lives now. */
+  relinkSoul: (path: string) =>
+    invokeElevated<SoulHealth>("relink_soul", { path }, "Link SoulOS to this soul"),
+  /** Recover a missing soul from the newest backup. */
+  restoreLatestBackup: () =>
+    invokeElevated<SoulHealth>("restore_latest_backup", {}, "Restore the soul from a backup"),
+  /** Move (or copy) the soul to an empty directory and switch over to it. */
+  relocateSoul: (newPath: string, moveData: boolean, opId?: string) =>
+    invokeElevated<RelocationReport>(
+      "relocate_soul",
+      { newPath, moveData, opId },
+      "Move the soul to a new location",
+    ),
   checkNode: () => invoke<NodeInfo>("check_node"),
-  createSoulDirectories: () => invoke<void>("create_soul_directories"),
+  /** Free space, write permission, case sensitivity and path length of a candidate soul path; creates nothing. */
+  preflightSoulPath: (path?: string) => call("preflight_soul_path", { path }),
+  createSoulDirectories: () => call("create_soul_directories"),
+  // German (seele/, erinnerungen/) or English directory names; null if mixed and unmarked
+  getSoulLayout: () => call("get_soul_layout"),
+  // Renames directories, rewrites markdown links and commits; resumes after a crash
+  migrateSoulLayout: (targetLocale: "de" | "en") =>
+    call("migrate_soul_layout", { targetLocale }),
+
+  // Privacy lock (every other command fails with "Locked" while locked)
+  getLockStatus: () => call("get_lock_status"),
+  lockApp: () => call("lock_app"),
+  unlockApp: (passphrase: string) => call("unlock_app", { passphrase }),
+  setLockPassphrase: (current: string | null, passphrase: string | null) =>
+    call("set_lock_passphrase", { current, passphrase }),
+  setAutoLock: (minutes: number, onSleep: boolean) =>
+    call("set_auto_lock", { minutes, onSleep }),
+
+  // At-rest encryption (key derived from the lock passphrase)
+  getEncryptionStatus: () => call("get_encryption_status"),
+  setEncryptedDirectories: (directories: string[]) =>
+    call("set_encrypted_directories", { directories }),
+  migrateEncryption: () => call("migrate_encryption"),
+
+  // PII scanner (redact rewrites matches with placeholders)
+  scanForPii: (scope?: string, redact?: boolean) =>
+    call("scan_for_pii", { scope, redact }),
+
+  // Export (never includes .env or relationships)
+  exportSoulFiltered: (categories: MemoryCategory[], anonymize: boolean, destination?: string) =>
+    call("export_soul_filtered", { categories, anonymize, destination }),
+  // Markdown renderer shared by every view (sanitized, wiki links resolved)
+  renderMarkdown: (nameOrContent: string, options?: RenderOptions) =>
+    call("render_markdown", { nameOrContent, options }),
+  // One memory as a mood-styled share ("pdf" opens the print dialog)
+  exportMemory: (name: string, format: "html" | "html-print" | "svg", destination?: string) =>
+    call("export_memory", { name, format, destination }),
+  // Print a soul document (SEED.md, a memory) through the OS dialog
+  printDocument: (name: string) => call("print_document", { name }),
+
+  // Analytics ("soul growth" chart)
+  getGrowthMetrics: (range?: string, resolution?: "day" | "week" | "month") =>
+    call("get_growth_metrics", { range, resolution }),
+
+  // Developer: watcher pipeline latency
+  benchmarkPipeline: (nFiles: number) => call("benchmark_pipeline", { nFiles }),
+
+  // Simulation / demo mode (no disk writes, no Node)
+  startSimulation: (profile?: "calm" | "busy" | "dreaming") =>
+    call("start_simulation", { profile }),
+  stopSimulation: () => call("stop_simulation"),
+  getSimulationStatus: () => call("get_simulation_status"),
+
+  // API discovery
+  getApiManifest: () => call("get_api_manifest"),
+
+  // Background tasks (activity center); pass an opId to a long command to cancel it
+  listTasks: () => call("list_tasks"),
+  cancelTask: (id: string) => call("cancel_task", { id }),
+
+  // Crash recovery of operations interrupted by the last run
+  getRecoveryReport: () => call("get_recovery_report"),
+
+  // Crash reports (opt-in); pending ones are redacted exactly as they'd be sent
+  getCrashConfig: () => call("get_crash_config"),
+  setCrashConfig: (crashConfig: CrashConfig) => call("set_crash_config", { crashConfig }),
+  getPendingCrashReports: () => call("get_pending_crash_reports"),
+  sendCrashReports: (ids: string[]) => call("send_crash_reports", { ids }),
+  dismissCrashReports: (ids: string[]) => call("dismiss_crash_reports", { ids }),
+
+  // Prometheus endpoint on 127.0.0.1:<port>/metrics (opt-in)
+  getMetricsConfig: () => call("get_metrics_config"),
+  setMetricsConfig: (metricsConfig: MetricsConfig) => call("set_metrics_config", { metricsConfig }),
+
+  // Mobile companion: paired devices read status and push quick notes over
+  // <host>:<port> (GET /status, GET /notes?since=, POST /notes)
+  getCompanionConfig: () => call("get_companion_config"),
+  setCompanionConfig: (companionConfig: CompanionConfig) =>
+    call("set_companion_config", { companionConfig }),
+  /** The token is shown only once. */
+  pairCompanionDevice: (name: string) =>
+    invokeElevated<CompanionPairing>("pair_companion_device", { name }, "Pair a companion device"),
+  // Paired devices; with `remoteView` on they can open an encrypted, read-only
+  // live view (GET /stream) of nodes, mood and status
+  listPairedDevices: () => call("list_paired_devices"),
+  revokeDevice: (id: string) => call("revoke_device", { id }),
+  // Other SoulOS instances found via mDNS (companion `discovery`); pairing is
+  // confirmed with the code the other instance shows
+  listPeers: () => call("list_peers"),
+  requestPeerPairing: (peerId: string) => call("request_peer_pairing", { peerId }),
+  confirmPeerPairing: (peerId: string, code: string) =>
+    call("confirm_peer_pairing", { peerId, code }),
+  // A peer's request (`peers:pair-request`) has to be accepted here before
+  // its code works
+  answerPeerPairing: (requestId: string, accept: boolean) =>
+    call("answer_peer_pairing", { requestId, accept }),
+  forgetPeer: (peerId: string) => call("forget_peer", { peerId }),
+  getPeerStatus: (peerId: string) => call("get_peer_status", { peerId }),
+
+  // Quiet mode: pause tray animation, watcher, health checks and schedulers
+  suspendBackground: (suspended: boolean) => call("suspend_background", { suspended }),
+  getBackgroundStatus: () => call("get_background_status"),
+  // Screensaver mode: starts after the window has been unfocused for a while
+  getAmbientStatus: () => call("get_ambient_status"),
+
+  // Runtime profiles: "performance" | "balanced" | "battery_saver", or "auto" (follow power source)
+  getRuntimeProfile: () => call("get_runtime_profile"),
+  setRuntimeProfile: (name: string) => call("set_runtime_profile", { name }),
 
   // Soul data
-  getSoulStatus: () => invoke<SoulStatus>("get_soul_status"),
-  readSoulFile: (name: string) => invoke<string>("read_soul_file", { name }),
-  writeSoulFile: (name: string, content: string) =>
-    invoke<void>("write_soul_file", { name, content }),
+  getSoulStatus: () => call("get_soul_status"),
+  // Parsed heartbeat logs (null before the first heartbeat)
+  getVitals: () => call("get_vitals"),
+  // A passage from the garden/dreams, the same one all day (null before the soul wrote any)
+  getDailyHighlight: () => call("get_daily_highlight"),
+  // State snapshots, newest first; from/to are YYYY-MM-DD
+  listStatelog: (filter: { from?: string; to?: string; offset?: number; limit?: number } = {}) =>
+    call("list_statelog", filter),
+  getStateDistribution: (days?: number) => call("get_state_distribution", { days }),
+  /** A day in the life of the soul (YYYY-MM-DD, default today), oldest first. */
+  getTimeline: (day?: string, opId?: string) => call("get_timeline", { day, opId }),
+  /** Words, characters, headings and reading time of one file (editor status bar). */
+  getDocumentStats: (name: string) => call("get_document_stats", { name }),
+  /** Text statistics of the whole soul, total and per category (growth dashboard). */
+  getSoulTextStats: (opId?: string) => call("get_soul_text_stats", { opId }),
+  /** German or English, by stopwords. */
+  detectLanguage: (text: string) => call("detect_language", { text }),
+  /** Offline spellcheck; offsets and lengths are in UTF-16 units like the editor's. */
+  spellcheck: (text: string, lang?: "de" | "en") => call("spellcheck", { text, lang }),
+  readSoulFile: (name: string) =>
+    orStream(call("read_soul_file", { name }), (streamId) =>
+      call("stream_soul_file", { name, streamId }),
+    ),
+  streamSoulFile: (name: string, streamId: string) => call("stream_soul_file", { name, streamId }),
+  /** Large or binary soul file as a one-time URL (see `blobUrl`); nothing goes through JSON IPC. */
+  shareSoulFile: (name: string) => call("share_soul_file", { name }),
+  /** "What does the soul remember about X": memory passages by meaning, best first. */
+  semanticSearch: (query: string, k?: number) => call("semantic_search", { query, k }),
+  /** Search box session: send every keystroke, results come via `onSearchResults` (debounced). */
+  openSearchSession: () => call("open_search_session"),
+  updateSearchQuery: (session: string, query: string) =>
+    call("update_search_query", { session, query }),
+  closeSearchSession: (session: string) => call("close_search_session", { session }),
+  /** Memories most similar to the open file, one per file with its best-matching passage. */
+  getRelated: (name: string, k?: number) => call("get_related", { name, k }),
+  indexMemories: () => call("index_memories"),
+  /** Memory tags (frontmatter `tags:` and inline `#tag`), most used first. */
+  listTags: () => call("list_tags"),
+  /** Memory files with `tag`; `project` also matches `project/soul`. */
+  getFilesByTag: (tag: string) => call("get_files_by_tag", { tag }),
+  retagFile: (name: string, add: string[], remove: string[]) =>
+    call("retag_file", { name, add, remove }),
+  /** LLM summary of a soul file via the assistant (founding server, started on demand). */
+  assistantSummarize: (file: string) => call("assistant_summarize", { file }),
+  /** Suggested tags, the soul's existing ones first; apply with retagFile. */
+  assistantSuggestTags: (file: string) => call("assistant_suggest_tags", { file }),
+  /** Soul context to paste into another AI tool, within a token budget; copied to the clipboard by default. */
+  buildContextBundle: (budgetTokens?: number, focus?: string, target?: "clipboard" | "file" | "none") =>
+    call("build_context_bundle", { budgetTokens, focus, target }),
+  /** Soul files opened or edited most recently, newest first. */
+  getRecentFiles: (limit?: number) => call("get_recent_files", { limit }),
+  /** What to open next: frequent and recent files, boosted while their brain node is active. */
+  getSuggestedFiles: () => call("get_suggested_files"),
+  /** Quick-access files; pins are stored in the soul and travel with it. */
+  listPinned: () => call("list_pinned"),
+  pinFile: (name: string) => call("pin_file", { name }),
+  unpinFile: (name: string) => call("unpin_file", { name }),
+  streamAck: (streamId: string, seq: number) => call("stream_ack", { streamId, seq }),
+  cancelStream: (streamId: string) => call("cancel_stream", { streamId }),
+  /** Protected paths need `confirmationToken` from `requestWriteConfirmation` (see `isProtected`). */
+  writeSoulFile: (name: string, content: string, confirmationToken?: string) =>
+    call("write_soul_file", { name, content, confirmationToken }),
+  getProtectedPaths: () => call("get_protected_paths"),
+  setProtectedPaths: (paths: string[]) =>
+    invokeElevated<string[]>("set_protected_paths", { paths }, "Change the protected files"),
+  /** One-time permission to write a protected file; the reason is audited. */
+  requestWriteConfirmation: (name: string, reason: string) =>
+    call("request_write_confirmation", { name, reason }),
+  /** Structural findings for SEED/CORE/MANIFEST (and headings elsewhere); pass `content` for unsaved text. */
+  lintSoulDocument: (name: string, content?: string) =>
+    call("lint_soul_document", { name, content }),
 
   // Environment
   readEnv: () => invoke<Record<string, string>>("read_env"),
   writeEnv: (entries: Record<string, string>) =>
-    invoke<void>("write_env", { entries }),
+    invokeElevated<void>("write_env", { entries }, "Write API keys to .env"),
+  /** Descriptions, types and defaults of the known .env keys. */
+  getEnvSchema: () => call("get_env_schema"),
+  /** Merged env of "soul-engine", "soul-chain" or "founding", with each value's layer
+   *  (system < app < soul < session < launch); secrets masked. */
+  getEffectiveEnv: (process: string) => call("get_effective_env", { process }),
+  /** Until the app quits; without `process` for every sidecar, `null` removes the override. */
+  setSessionEnv: (name: string, value: string | null, process?: string) =>
+    invokeElevated<void>(
+      "set_session_env",
+      { name, value, process },
+      "Override an environment variable",
+    ),
+  clearSessionEnv: () => call("clear_session_env"),
+  /** App-level default in app data, used unless the soul's .env sets the variable. */
+  setEnvDefault: (name: string, value: string | null) =>
+    invokeElevated<void>(
+      "set_env_default",
+      { name, value },
+      "Change a default environment variable",
+    ),
 
   // Brain visualization
   getActiveNodes: () => invoke<Record<string, number>>("get_active_nodes"),
-  getIsWorking: () => invoke<boolean>("get_is_working"),
+  getIsWorking: () => call("get_is_working"),
 
   // Founding
-  startFounding: () => invoke<number>("start_founding"),
-  stopFounding: () => invoke<void>("stop_founding"),
+  startFounding: () => call("start_founding"),
+  stopFounding: () => call("stop_founding"),
   foundingChat: (message: string, history: Array<{ role: string; content: string }>) =>
     invoke<{ reply: string; round: number; done: boolean }>("founding_chat", { message, history }),
-  foundingCreate: (history: Array<{ role: string; content: string }>) =>
-    invoke<{ success: boolean; filesCreated: string[] }>("founding_create", { history }),
+  /** Without an LLM provider the files come from templates and `answers`. */
+  foundingPreview: (history: Array<{ role: string; content: string }>, answers?: FoundingAnswers) =>
+    call("founding_preview", { history, answers }),
+  /** Backs up the existing soul; the token lets `foundingCreate` found over it. */
+  confirmOverwrite: () => call("confirm_overwrite"),
+  /** Writes the files of a reviewed preview. Fails with an `AlreadyFounded`
+   * error over an existing soul unless `overwriteToken` is given. */
+  foundingCreate: (token: string, overwriteToken?: string) =>
+    invoke<{ success: boolean; filesCreated: string[]; templateFounded: boolean }>("founding_create", {
+      token,
+      overwriteToken,
+    }),
 
   // Engine control
-  startEngine: () => invoke<void>("start_engine"),
-  stopEngine: () => invoke<void>("stop_engine"),
-  getSidecarStatus: () => invoke<SidecarStatus>("get_sidecar_status"),
+  startEngine: () => call("start_engine"),
+  stopEngine: () => call("stop_engine"),
+  pauseEngine: () => call("pause_engine"),
+  resumeEngine: () => call("resume_engine"),
+  reloadEngine: () => call("reload_engine"),
+  getEngineVersion: () => call("get_engine_version"),
+  rollbackEngine: () => call("rollback_engine"),
+  getReloadOnConfigChange: () => call("get_reload_on_config_change"),
+  setReloadOnConfigChange: (enabled: boolean) => call("set_reload_on_config_change", { enabled }),
+  getEngineLogLevel: () => call("get_engine_log_level"),
+  /** "trace" … "fatal"; also filters forwarded stdout. Returns "runtime" or "next_start". */
+  setEngineLogLevel: (level: string) => call("set_engine_log_level", { level }),
+  /** Flags plus the report of the launch boot (also narrated live as boot:progress, phase "autostart") */
+  getAutostart: () => call("get_autostart"),
+  setAutostart: (engine: boolean, chain: boolean) => call("set_autostart", { engine, chain }),
+  getSidecarStatus: () => call("get_sidecar_status"),
+  /** range: "24h", "7d" (default), "30d", "90d" or "all" */
+  getEngineAvailability: (range?: string) => call("get_engine_availability", { range }),
 
   // Chain control
-  startChain: () => invoke<void>("start_chain"),
-  stopChain: () => invoke<void>("stop_chain"),
-  getChainStatus: () => invoke<SidecarStatus>("get_chain_status"),
+  startChain: () => call("start_chain"),
+  stopChain: () => call("stop_chain"),
+  getChainStatus: () => call("get_chain_status"),
+
+  // Boot orchestration: watcher → chain → engine when ready, founding server while founding
+  bootAll: () => call("boot_all"),
+  shutdownAll: () => call("shutdown_all"),
 
   // PTY
-  createPty: (cols: number, rows: number) => invoke<number>("create_pty", { cols, rows }),
-  writePty: (id: number, data: string) => invoke<void>("write_pty", { id, data }),
-  resizePty: (id: number, cols: number, rows: number) => invoke<void>("resize_pty", { id, cols, rows }),
-  closePty: (id: number) => invoke<void>("close_pty", { id }),
+  // restricted: sandboxed shell that may only write to the soul and temp dirs
+  // cwd/purpose: reopen a terminal from the restore plan
+  createPty: (cols: number, rows: number, restricted = false, cwd?: string, purpose?: string) =>
+    invokeElevated<number>("create_pty", { cols, rows, restricted, cwd, purpose }, "Open a terminal"),
+  listPtys: () => call("list_ptys"),
+  // Previous session: terminals and views open at the last quit
+  getRestorePlan: () => call("get_restore_plan"),
+  dismissRestorePlan: () => call("dismiss_restore_plan"),
+  setOpenViews: (views: string[]) => call("set_open_views", { views }),
+  // Commands typed into the terminals (password prompts excluded)
+  getPtyCommandHistory: (id: number) => call("get_pty_command_history", { id }),
+  searchCommandHistory: (query: string, limit?: number) => call("search_command_history", { query, limit }),
+  writePty: (id: number, data: string) => call("write_pty", { id, data }),
+  // Bracketed, chunked paste — reliable for multi-kilobyte text in REPLs
+  pastePty: (id: number, text: string) => call("paste_pty", { id, text }),
+  resizePty: (id: number, cols: number, rows: number) => call("resize_pty", { id, cols, rows }),
+  closePty: (id: number) => call("close_pty", { id }),
+  detachPtyWindow: (id: number) => call("detach_pty_window", { id }),
+  attachPtyWindow: (id: number) => call("attach_pty_window", { id }),
 
   // State Versioning (Git)
-  getStateHistory: (limit?: number) => invoke<GitCommit[]>("get_state_history", { limit }),
-  getStateDiff: (hash: string) => invoke<string>("get_state_diff", { hash }),
-  rollbackState: (hash: string) => invoke<string>("rollback_state", { hash }),
+  getStateHistory: (limit?: number) => call("get_state_history", { limit }),
+  getStateDiff: (hash: string) =>
+    orStream(call("get_state_diff", { hash }), (streamId) =>
+      call("stream_state_diff", { hash, streamId }),
+    ),
+  streamStateDiff: (hash: string, streamId: string) => call("stream_state_diff", { hash, streamId }),
+  rollbackState: (hash: string) =>
+    invokeElevated<string>("rollback_state", { hash }, "Roll back the soul state"),
 
   // Directory listing
-  listDirectory: (name: string) => invoke<string[]>("list_directory", { name }),
-
-  // Embedded Browser
-  openBrowser: (url: string, fullMode: boolean) =>
-    invoke<void>("open_browser", { url, fullMode }),
-  closeBrowser: () => invoke<void>("close_browser"),
+  listDirectory: (name: string) => call("list_directory", { name }),
+
+  // Embedded Browser (a profile gets its own cookies and storage)
+  openBrowser: (url: string, fullMode: boolean, profile?: string) =>
+    invokeElevated<void>("open_browser", { url, fullMode, profile }, "Open the embedded browser"),
+  closeBrowser: () => call("close_browser"),
+  listBrowserProfiles: () => call("list_browser_profiles"),
+  clearBrowserProfile: (name: string) => call("clear_browser_profile", { name }),
+
+  // Browser downloads (saved to <soul>/media/downloads)
+  getDownloadConfig: () => call("get_download_config"),
+  setDownloadConfig: (downloadConfig: DownloadConfig) =>
+    call("set_download_config", { downloadConfig }),
+  listDownloads: () => call("list_downloads"),
+  /** Save a page (URL, or a browser window label such as "soul-browser") to semantic memories. */
+  clipPage: (urlOrWindowId: string) => call("clip_page", { urlOrWindowId }),
+
+  // Retention (old event archives and terminal history; 0 days keeps forever)
+  getRetentionConfig: () => call("get_retention_config"),
+  setRetentionConfig: (retentionConfig: RetentionConfig) =>
+    call("set_retention_config", { retentionConfig }),
+  previewPurge: () => call("preview_purge"),
+  runPurge: () => call("run_purge"),
+  getRollupConfig: () => call("get_rollup_config"),
+  setRollupConfig: (rollupConfig: RollupConfig) => call("set_rollup_config", { rollupConfig }),
+  /** Condense old episodic memories now; each week lands in the review queue as one group. */
+  runRollup: () => call("run_rollup"),
+  /** Apply or drop a whole group of pending changes, e.g. a rollup's `group`. */
+  approveChangeGroup: (group: string) => call("approve_change_group", { group }),
+  rejectChangeGroup: (group: string) => call("reject_change_group", { group }),
+
+  // Housekeeping (disk usage and clean-up recommendations)
+  getHousekeepingReport: (opId?: string) => call("get_housekeeping_report", { opId }),
+  deleteSoulFiles: (names: string[]) =>
+    invokeElevated<number>("delete_soul_files", { names }, "Delete files from the soul"),
+  /** File operations as one unit: all or nothing, one git commit. */
+  applyBatch: (ops: BatchOp[], opId?: string) =>
+    invokeElevated<BatchReport>("apply_batch", { ops, opId }, "Change several soul files at once"),
+
+  // Network (proxy and user agent for backend HTTP, sidecars and the browser)
+  getNetworkConfig: () => call("get_network_config"),
+  setNetworkConfig: (networkConfig: NetworkConfig) =>
+    call("set_network_config", { networkConfig }),
+
+  // Environment passed to terminals, sidecars and helper commands
+  getEnvPolicy: () => call("get_env_policy"),
+  setEnvPolicy: (envPolicy: EnvPolicy) => call("set_env_policy", { envPolicy }),
 
   // Engine Monitor (server-side proxy to avoid webview fetch issues)
   fetchEngineSubsystems: () =>
@@ -120,33 +628,122 @@ export const commands = {
 
 // --- Events (Rust → Frontend) ---
 
-export const events = {
-  onPulse: (handler: (pulse: SoulPulse) => void): Promise<UnlistenFn> =>
-    listen<SoulPulse>("soul:pulse", (e) => handler(e.payload)),
-
-  onMood: (handler: (mood: SoulMood) => void): Promise<UnlistenFn> =>
-    listen<SoulMood>("soul:mood", (e) => handler(e.payload)),
+/** Typed listen for events whose payload is a generated type. */
+function on<K extends keyof Events>(
+  event: K,
+  handler: (payload: Events[K]) => void,
+): Promise<UnlistenFn> {
+  return listen<Events[K]>(event, (e) => handler(e.payload));
+}
 
-  onActivity: (handler: (activity: SoulActivity) => void): Promise<UnlistenFn> =>
-    listen<SoulActivity>("soul:activity", (e) => handler(e.payload)),
+export const events = {
+  onPulse: (handler: (pulse: Events["soul:pulse"]) => void): Promise<UnlistenFn> =>
+    on("soul:pulse", handler),
+
+  onMood: (handler: (mood: Events["soul:mood"]) => void): Promise<UnlistenFn> =>
+    on("soul:mood", handler),
+
+  onActivity: (handler: (activity: Events["soul:activity"]) => void): Promise<UnlistenFn> =>
+    on("soul:activity", handler),
+
+  onVitals: (handler: (vitals: Events["soul:vitals"]) => void): Promise<UnlistenFn> =>
+    on("soul:vitals", handler),
+  onSoulHealth: (handler: (p: Events["soul:health"]) => void): Promise<UnlistenFn> =>
+    on("soul:health", handler),
+  onSoulRelocated: (handler: (p: Events["soul:relocated"]) => void): Promise<UnlistenFn> =>
+    on("soul:relocated", handler),
+  onTagsChanged: (handler: (p: Events["tags:changed"]) => void): Promise<UnlistenFn> =>
+    on("tags:changed", handler),
+  onPinsChanged: (handler: (pinned: Events["pins:changed"]) => void): Promise<UnlistenFn> =>
+    on("pins:changed", handler),
 
   onBusEvent: (handler: (event: unknown) => void): Promise<UnlistenFn> =>
     listen("soul:bus-event", (e) => handler(e.payload)),
 
+  // Terminal output is emitted to the window hosting the PTY, so listen on
+  // this window only (a detached terminal lives in its own window).
   onPtyData: (handler: (data: { id: number; data: string }) => void): Promise<UnlistenFn> =>
-    listen("pty:data", (e) => handler(e.payload as { id: number; data: string })),
+    getCurrentWebviewWindow().listen("pty:data", (e) => handler(e.payload as { id: number; data: string })),
 
   onPtyExit: (handler: (data: { id: number }) => void): Promise<UnlistenFn> =>
-    listen("pty:exit", (e) => handler(e.payload as { id: number })),
-
-  onSidecarStdout: (handler: (data: { process: string; line: string }) => void): Promise<UnlistenFn> =>
-    listen("sidecar:stdout", (e) => handler(e.payload as { process: string; line: string })),
-
-  onSidecarStderr: (handler: (data: { process: string; line: string }) => void): Promise<UnlistenFn> =>
-    listen("sidecar:stderr", (e) => handler(e.payload as { process: string; line: string })),
-
-  onSidecarStatus: (handler: (status: SidecarStatus) => void): Promise<UnlistenFn> =>
-    listen<SidecarStatus>("sidecar:status", (e) => handler(e.payload)),
+    getCurrentWebviewWindow().listen("pty:exit", (e) => handler(e.payload as { id: number })),
+
+  onPtyDetached: (handler: (p: Events["pty:detached"]) => void): Promise<UnlistenFn> =>
+    on("pty:detached", handler),
+
+  onPtyAttached: (handler: (p: Events["pty:attached"]) => void): Promise<UnlistenFn> =>
+    on("pty:attached", handler),
+
+  onSidecarStdout: (handler: (data: SidecarOutput) => void): Promise<UnlistenFn> =>
+    listen("sidecar:stdout", (e) => handler(e.payload as SidecarOutput)),
+
+  onSidecarStderr: (handler: (data: SidecarOutput) => void): Promise<UnlistenFn> =>
+    listen("sidecar:stderr", (e) => handler(e.payload as SidecarOutput)),
+
+  onSidecarStatus: (handler: (status: Events["sidecar:status"]) => void): Promise<UnlistenFn> =>
+    on("sidecar:status", handler),
+
+  /** A sidecar died on its own; `message` says what to do, `reason` is the classified cause. */
+  onSidecarCrashed: (handler: (p: Events["sidecar:crashed"]) => void): Promise<UnlistenFn> =>
+    on("sidecar:crashed", handler),
+
+  onBootProgress: (handler: (p: Events["boot:progress"]) => void): Promise<UnlistenFn> =>
+    on("boot:progress", handler),
+
+  onEngineReloaded: (handler: (p: Events["sidecar:reloaded"]) => void): Promise<UnlistenFn> =>
+    on("sidecar:reloaded", handler),
+  /** Keys of the soul's .env changed; offer a reload if restart_keys is set and restarted is false. */
+  onEnvChanged: (handler: (p: Events["env:changed"]) => void): Promise<UnlistenFn> =>
+    on("env:changed", handler),
+
+  onEngineUpdated: (handler: (report: Events["engine:updated"]) => void): Promise<UnlistenFn> =>
+    on("engine:updated", handler),
+
+  onBrowserDownload: (handler: (download: Events["browser:download"]) => void): Promise<UnlistenFn> =>
+    on("browser:download", handler),
+
+  onCompanionNote: (handler: (note: Events["companion:note"]) => void): Promise<UnlistenFn> =>
+    on("companion:note", handler),
+
+  onPeersChanged: (handler: (peers: Events["peers:changed"]) => void): Promise<UnlistenFn> =>
+    on("peers:changed", handler),
+
+  onPeerPairRequest: (handler: (request: Events["peers:pair-request"]) => void): Promise<UnlistenFn> =>
+    on("peers:pair-request", handler),
+
+  onSearchResults: (handler: (results: Events["search:results"]) => void): Promise<UnlistenFn> =>
+    on("search:results", handler),
+
+  onSimulationStarted: (handler: (status: Events["simulation:started"]) => void): Promise<UnlistenFn> =>
+    on("simulation:started", handler),
+  onSimulationStopped: (handler: (status: Events["simulation:stopped"]) => void): Promise<UnlistenFn> =>
+    on("simulation:stopped", handler),
+
+  onLocked: (handler: (status: Events["app:locked"]) => void): Promise<UnlistenFn> =>
+    on("app:locked", handler),
+  onUnlocked: (handler: (status: Events["app:unlocked"]) => void): Promise<UnlistenFn> =>
+    on("app:unlocked", handler),
+
+  onTaskProgress: (handler: (task: Events["task:progress"]) => void): Promise<UnlistenFn> =>
+    on("task:progress", handler),
+  onTaskFinished: (handler: (task: Events["task:finished"]) => void): Promise<UnlistenFn> =>
+    on("task:finished", handler),
+  onStreamChunk: (handler: (chunk: Events["stream:chunk"]) => void): Promise<UnlistenFn> =>
+    on("stream:chunk", handler),
+  onStreamError: (handler: (error: Events["stream:error"]) => void): Promise<UnlistenFn> =>
+    on("stream:error", handler),
+  onRecoveryReport: (handler: (report: Events["recovery:report"]) => void): Promise<UnlistenFn> =>
+    on("recovery:report", handler),
+  onProfileChanged: (handler: (status: Events["profile:changed"]) => void): Promise<UnlistenFn> =>
+    on("profile:changed", handler),
+  onBackgroundChanged: (handler: (status: Events["background:changed"]) => void): Promise<UnlistenFn> =>
+    on("background:changed", handler),
+  onAmbientChanged: (handler: (status: Events["ambient:changed"]) => void): Promise<UnlistenFn> =>
+    on("ambient:changed", handler),
+  onAmbientFrame: (handler: (frame: Events["ambient:frame"]) => void): Promise<UnlistenFn> =>
+    on("ambient:frame", handler),
+  onCrashPending: (handler: (reports: Events["crash:pending"]) => void): Promise<UnlistenFn> =>
+    on("crash:pending", handler),
 
   // Soul engine feature events
   onMemoryIndexed: (handler: (data: unknown) => void): Promise<UnlistenFn> =>
@@ -103,12 +103,25 @@ export default function FoundingChat({ onComplete }: Props) {
           // Give the user a moment to read the final message
           await new Promise((r) => setTimeout(r, 2000));
 
-          // Create all soul files from the conversation
+          // Render the soul files from the conversation, then write them
           const fullHistory = [
             ...newMessages,
             { role: "ai", content: resp.reply },
           ];
-          await commands.foundingCreate(fullHistory);
+          const preview = await commands.foundingPreview(fullHistory);
+          const overwrite =
+            (preview.overwrites.length === 0 && !preview.already_founded) ||
+            window.confirm(
+              (language === "de"
+                ? "Hier lebt bereits eine Seele. Sie wird vorher gesichert, diese Dateien werden ueberschrieben:\n"
+                : "A soul already lives here. It is backed up first; these files will be overwritten:\n") +
+                preview.overwrites.join("\n"),
+            );
+          if (!overwrite) throw new Error("Founding cancelled: existing soul files kept");
+          const overwriteToken = preview.already_founded
+            ? (await commands.confirmOverwrite()).overwrite_token
+            : undefined;
+          await commands.foundingCreate(preview.token, overwriteToken);
 
           setPhase("done");
           await commands.stopFounding().catch(() => {});
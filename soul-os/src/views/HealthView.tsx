@@ -1,5 +1,5 @@
 import { useEffect, useState, useCallback } from "react";
-import { commands } from "../lib/tauri";
+import { commands, events, type Vitals } from "../lib/tauri";
 import { computeMaturity } from "./health-compute";
 
 /* ── Types ────────────────────────────────────────────────── */
@@ -62,6 +62,7 @@ export default function HealthView() {
   const [health, setHealth] = useState<HealthData | null>(null);
   const [maturity, setMaturity] = useState<MaturityData | null>(null);
   const [loading, setLoading] = useState(true);
+  const [vitals, setVitals] = useState<Vitals | null>(null);
 
   const refresh = useCallback(async () => {
     setLoading(true);
@@ -86,6 +87,12 @@ export default function HealthView() {
 
   useEffect(() => { refresh(); }, [refresh]);
 
+  useEffect(() => {
+    commands.getVitals().then(setVitals).catch(() => {});
+    const unlisten = events.onVitals(setVitals);
+    return () => { unlisten.then((fn) => fn()); };
+  }, []);
+
   if (loading) {
     return (
       <div className="h-full flex items-center justify-center" style={{ backgroundColor: "var(--bg-base)" }}>
@@ -150,6 +157,9 @@ export default function HealthView() {
           })}
         </div>
 
+        {/* Heartbeat vitals */}
+        {vitals && <VitalsEkg vitals={vitals} />}
+
         {/* Maturity Section */}
         {maturity && (
           <div>
@@ -199,6 +209,48 @@ export default function HealthView() {
   );
 }
 
+/* ── Heartbeat EKG ────────────────────────────────────────── */
+
+function formatInterval(secs: number | null): string {
+  if (secs === null) return "–";
+  if (secs < 3600) return `${Math.round(secs / 60)}m`;
+  return `${(secs / 3600).toFixed(1)}h`;
+}
+
+/** One spike per recent beat, spaced by the interval before it. */
+function VitalsEkg({ vitals }: { vitals: Vitals }) {
+  const width = 600;
+  const height = 60;
+  const mid = height / 2;
+  const total = vitals.intervals.reduce((a, b) => a + b, 0) || 1;
+  const points: string[] = [`0,${mid}`];
+  let x = 12;
+  vitals.recent.forEach((entry, i) => {
+    if (i > 0) x += (vitals.intervals[i - 1] / total) * (width - 24);
+    const amp = entry.error ? 0.35 : 0.8;
+    points.push(`${x - 4},${mid}`, `${x - 2},${mid + 6}`, `${x},${mid - mid * amp}`, `${x + 2},${mid + mid * 0.4}`, `${x + 4},${mid}`);
+  });
+  points.push(`${width},${mid}`);
+  const color = vitals.errors > 0 ? "#ffc800" : "var(--heartbeat)";
+
+  return (
+    <div className="mb-8">
+      <div className="flex items-center gap-3 mb-3">
+        <span className="text-xs font-semibold uppercase tracking-wider" style={{ color: "var(--heartbeat)" }}>
+          Vitals
+        </span>
+        <span className="text-[10px] font-mono" style={{ color: "var(--text-muted)" }}>
+          {vitals.cycles} beats today · loop {formatInterval(vitals.interval_secs)} · last {formatInterval(vitals.since_last_secs)} ago
+          {vitals.errors > 0 && ` · ${vitals.errors} errors`}
+        </span>
+      </div>
+      <svg viewBox={`0 0 ${width} ${height}`} className="w-full h-16" preserveAspectRatio="none">
+        <polyline points={points.join(" ")} fill="none" stroke={color} strokeWidth="1.5" style={{ filter: `drop-shadow(0 0 4px ${color})` }} />
+      </svg>
+    </div>
+  );
+}
+
 /* ── Basic health computation (client-side fallback) ──────── */
 
 async function computeBasicHealth(): Promise<HealthData> {
@@ -99,7 +99,12 @@ export default function SettingsView() {
     setUpdateStatus("checking");
     setUpdateError(null);
     try {
-      const update = await check();
+      // The updater runs in the webview plugin, so pass the network settings along
+      const network = await commands.getNetworkConfig();
+      const update = await check({
+        proxy: network.proxy_mode === "manual" ? network.proxy_url ?? undefined : undefined,
+        headers: network.user_agent ? { "User-Agent": network.user_agent } : undefined,
+      });
       if (update?.available) {
         setUpdateInfo(update);
         setUpdateStatus("available");
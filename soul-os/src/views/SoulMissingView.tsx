@@ -0,0 +1,85 @@
+import { useState, useEffect } from "react";
+import { open } from "@tauri-apps/plugin-dialog";
+import { commands, type SoulHealth } from "../lib/tauri";
+
+interface Props {
+  onRecovered: () => void;
+}
+
+const PROBLEMS: Record<string, string> = {
+  unreachable: "The soul directory can't be found.",
+  volume_missing: "The drive holding the soul is not connected.",
+  not_writable: "SoulOS can't write to the soul directory.",
+  fingerprint_missing: "The directory no longer contains this soul.",
+  fingerprint_mismatch: "A different soul lives at this path.",
+};
+
+export default function SoulMissingView({ onRecovered }: Props) {
+  const [health, setHealth] = useState<SoulHealth | null>(null);
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    commands.getSoulHealth().then(setHealth).catch(() => {});
+  }, []);
+
+  const recover = async (action: () => Promise<SoulHealth>) => {
+    setBusy(true);
+    setError(null);
+    try {
+      await action();
+      onRecovered();
+    } catch (e) {
+      setError(String(e));
+      setBusy(false);
+    }
+  };
+
+  const relink = async () => {
+    const selected = await open({ directory: true, title: "Where is your soul now?" });
+    if (selected) recover(() => commands.relinkSoul(selected as string));
+  };
+
+  return (
+    <div className="h-full flex items-center justify-center" style={{ backgroundColor: "var(--bg-base)" }}>
+      <div className="w-full max-w-md mx-auto px-8 py-10 text-center">
+        <h1 className="text-xl font-light tracking-wider mb-3" style={{ color: "var(--text-bright)" }}>
+          Soul missing
+        </h1>
+        <p className="text-sm leading-relaxed mb-2" style={{ color: "var(--text-dim)" }}>
+          {(health?.problem && PROBLEMS[health.problem]) ?? "The soul directory is not usable."}
+        </p>
+        {health && (
+          <p className="text-xs font-mono mb-8 break-all" style={{ color: "var(--text-dim)" }}>
+            {health.detail ?? health.path}
+          </p>
+        )}
+
+        {error && (
+          <div className="mb-4 px-4 py-2 rounded-lg text-xs" style={{ backgroundColor: "rgba(255,60,60,0.1)", color: "var(--heartbeat)" }}>
+            {error}
+          </div>
+        )}
+
+        <div className="flex flex-col gap-3">
+          <button
+            onClick={relink}
+            disabled={busy}
+            className="px-6 py-2.5 rounded-lg text-sm font-medium cursor-default"
+            style={{ backgroundColor: "var(--accent)", color: "#fff", opacity: busy ? 0.5 : 1 }}
+          >
+            Locate soul…
+          </button>
+          <button
+            onClick={() => recover(commands.restoreLatestBackup)}
+            disabled={busy}
+            className="px-6 py-2.5 rounded-lg text-sm cursor-default"
+            style={{ backgroundColor: "var(--bg-surface)", color: "var(--text-dim)", opacity: busy ? 0.5 : 1 }}
+          >
+            Restore latest backup
+          </button>
+        </div>
+      </div>
+    </div>
+  );
+}
@@ -229,6 +229,15 @@ function TerminalPane({ paneId, isActive }: { paneId: number; isActive: boolean
           commands.writePty(id, data).catch(console.error);
         });
 
+        // Paste: the backend brackets and chunks it (xterm.js would send it in one write)
+        containerRef.current?.addEventListener("paste", (e) => {
+          const text = e.clipboardData?.getData("text/plain");
+          if (!text) return;
+          e.preventDefault();
+          e.stopPropagation();
+          commands.pastePty(id, text).catch(console.error);
+        }, true);
+
         // Resize: notify PTY of terminal dimension changes
         term.onResize(({ cols, rows }) => {
           commands.resizePty(id, cols, rows).catch(console.error);